@@ -13,7 +13,7 @@ const SECRET: &str = "mySecret";
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Prepare keys
     println!(r#"Private key secret="{}""#, SECRET);
-    let private_key = PrivateKey::from_secret(SECRET.as_bytes());
+    let private_key = PrivateKey::from_secret_hkdf(SECRET.as_bytes(), b"", b"").unwrap();
     let public_key = private_key.to_public_key("cosmospub")?;
     let address = public_key.to_address();
     // Print some diagnostics
@@ -45,6 +45,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         chain_id: "mychainid".to_string(),
         fee,
         timeout_height: 100,
+        ..Default::default()
     };
 
     let tx = private_key.sign_std_msg(&[msg], args, "")?;
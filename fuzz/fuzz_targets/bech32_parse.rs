@@ -0,0 +1,13 @@
+//! Parses arbitrary strings as addresses, any address that parses must round trip
+#![no_main]
+
+use deep_space::Address;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    if let Ok(address) = data.parse::<Address>() {
+        let encoded = address.to_bech32(address.get_prefix()).unwrap();
+        assert_eq!(encoded.parse::<Address>().unwrap(), address);
+    }
+    let _ = Address::validate(data, Some("cosmos"));
+});
@@ -0,0 +1,11 @@
+//! Parses arbitrary JSON as a Msg, any Msg that parses must serialize again
+#![no_main]
+
+use deep_space::Msg;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(msg) = serde_json::from_slice::<Msg>(data) {
+        serde_json::to_value(&msg).unwrap();
+    }
+});
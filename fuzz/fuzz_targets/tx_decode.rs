@@ -0,0 +1,13 @@
+//! Decodes arbitrary bytes as TxRaw, any tx that decodes must survive a JSON round trip
+#![no_main]
+
+use deep_space::DecodedTx;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(decoded) = DecodedTx::decode(data) {
+        let json = serde_json::to_value(&decoded).unwrap();
+        let parsed: DecodedTx = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, decoded);
+    }
+});
@@ -1,16 +1,52 @@
 use crate::error::AddressError;
+use crate::utils::bytes_to_hex_str;
 use crate::utils::contains_non_hex_chars;
 use crate::utils::hex_str_to_bytes;
 use crate::utils::ArrayString;
 use bech32::{self, FromBase32};
 use bech32::{ToBase32, Variant};
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha3::{Digest, Keccak256};
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::str::FromStr;
 
+/// The checksum variant of a bech32 string, Cosmos chains use Bech32 but some newer
+/// chains and test networks emit Bech32m (BIP-350). Decoding accepts either
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bech32Variant {
+    #[default]
+    Bech32,
+    Bech32m,
+}
+
+impl From<Variant> for Bech32Variant {
+    fn from(value: Variant) -> Self {
+        match value {
+            Variant::Bech32 => Bech32Variant::Bech32,
+            Variant::Bech32m => Bech32Variant::Bech32m,
+        }
+    }
+}
+
+impl From<Bech32Variant> for Variant {
+    fn from(value: Bech32Variant) -> Self {
+        match value {
+            Bech32Variant::Bech32 => Variant::Bech32,
+            Bech32Variant::Bech32m => Variant::Bech32m,
+        }
+    }
+}
+
 /// An address that's derived from a given PublicKey
-#[derive(PartialEq, Eq, Copy, Clone, Hash, Deserialize, Serialize)]
+#[derive(PartialEq, Eq, Copy, Clone, Hash)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow),
+    diesel(sql_type = diesel::sql_types::Text)
+)]
 pub struct Address {
     bytes: [u8; 20],
     prefix: ArrayString,
@@ -61,18 +97,34 @@ impl Address {
     /// Obtain a bech32 encoded address with a given prefix.
     ///
     /// * `hrp` - A prefix for bech32 encoding. The convention for addresses
-    /// in Cosmos is `cosmos`.
+    ///   in Cosmos is `cosmos`.
+    ///
     /// note this does not update the prefix stored in the address
     pub fn to_bech32<T: Into<String>>(&self, hrp: T) -> Result<String, AddressError> {
-        let bech32 = bech32::encode(&hrp.into(), self.bytes.to_base32(), Variant::Bech32)?;
+        self.to_bech32_variant(hrp, Bech32Variant::Bech32)
+    }
+
+    /// Obtain a bech32 or bech32m encoded address with a given prefix
+    pub fn to_bech32_variant<T: Into<String>>(
+        &self,
+        hrp: T,
+        variant: Bech32Variant,
+    ) -> Result<String, AddressError> {
+        let bech32 = bech32::encode(&hrp.into(), self.bytes.to_base32(), variant.into())?;
         Ok(bech32)
     }
 
-    /// Parse a bech32 encoded address
+    /// Parse a bech32 encoded address, either checksum variant is accepted
     ///
     /// * `s` - A bech32 encoded address
     pub fn from_bech32(s: String) -> Result<Address, AddressError> {
-        let (hrp, data, _) = match bech32::decode(&s) {
+        Ok(Address::from_bech32_with_variant(&s)?.0)
+    }
+
+    /// Parse a bech32 encoded address along with the checksum variant it used, so that
+    /// it can be encoded back the same way
+    pub fn from_bech32_with_variant(s: &str) -> Result<(Address, Bech32Variant), AddressError> {
+        let (hrp, data, variant) = match bech32::decode(s) {
             Ok(val) => val,
             Err(e) => {
                 println!("{:?}", e);
@@ -88,7 +140,77 @@ impl Address {
             return Err(AddressError::Bech32WrongLength);
         }
         addr.copy_from_slice(&vec);
-        Address::from_bytes(addr, &hrp)
+        Ok((Address::from_bytes(addr, &hrp)?, variant.into()))
+    }
+
+    /// Parses and validates a user provided bech32 address, unlike `from_bech32` the
+    /// error distinguishes a bad checksum, a prefix other than `expected_prefix` and
+    /// a decoded length other than 20 bytes so that users can be told what is wrong.
+    /// Like `from_bech32` either checksum variant is accepted
+    pub fn validate(s: &str, expected_prefix: Option<&str>) -> Result<Address, AddressError> {
+        let (hrp, data, _) = match bech32::decode(s) {
+            Ok(val) => val,
+            Err(bech32::Error::InvalidChecksum) => return Err(AddressError::Bech32InvalidChecksum),
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(expected) = expected_prefix {
+            if hrp != expected {
+                return Err(AddressError::WrongPrefix {
+                    expected: expected.to_string(),
+                    found: hrp,
+                });
+            }
+        }
+        let bytes: Vec<u8> = match FromBase32::from_base32(&data) {
+            Ok(val) => val,
+            Err(_e) => return Err(AddressError::Bech32InvalidBase32),
+        };
+        if bytes.len() != 20 {
+            return Err(AddressError::Bech32WrongLength);
+        }
+        Address::from_slice(&bytes, hrp)
+    }
+
+    /// Encodes the address bytes as an EIP-55 checksummed Ethereum address, on Ethermint
+    /// chains and in Gravity bridge flows the same 20 bytes are used in both encodings
+    pub fn to_eth_hex(&self) -> String {
+        let lower = bytes_to_hex_str(&self.bytes);
+        let hash = Keccak256::digest(lower.as_bytes());
+        let mut out = String::from("0x");
+        for (i, c) in lower.chars().enumerate() {
+            // each hex character of the address is uppercased if the matching
+            // nibble of the hash of the lowercase address is 8 or greater
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if nibble >= 8 {
+                out.push(c.to_ascii_uppercase());
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    /// Parses a 0x prefixed Ethereum address into an Address with the given bech32
+    /// prefix. All lowercase or all uppercase addresses carry no checksum, mixed case
+    /// addresses must have a valid EIP-55 checksum
+    pub fn from_eth_hex<T: Into<String>>(s: &str, prefix: T) -> Result<Address, AddressError> {
+        let hex = s.strip_prefix("0x").unwrap_or(s);
+        if hex.len() != 40 {
+            return Err(AddressError::HexDecodeErrorWrongLength);
+        }
+        let bytes = hex_str_to_bytes(hex).map_err(AddressError::HexDecodeError)?;
+        let address = Address::from_slice(&bytes, prefix)?;
+        let mixed_case = hex.chars().any(|c| c.is_ascii_lowercase())
+            && hex.chars().any(|c| c.is_ascii_uppercase());
+        if mixed_case && address.to_eth_hex()[2..] != *hex {
+            return Err(AddressError::InvalidEip55Checksum);
+        }
+        Ok(address)
+    }
+
+    /// A quick check that `s` is a valid bech32 address of any prefix
+    pub fn is_valid_bech32(s: &str) -> bool {
+        Address::validate(s, None).is_ok()
     }
 }
 
@@ -129,6 +251,92 @@ impl fmt::Debug for Address {
     }
 }
 
+/// Serializes as the bech32 string in human readable formats such as JSON and as the
+/// raw bytes followed by the prefix in binary formats
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            (self.bytes, self.prefix).serialize(serializer)
+        }
+    }
+}
+
+/// Earlier versions serialized the address as a struct of its bytes and prefix, both
+/// that and a string are accepted in human readable formats
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AddressRepr {
+    Str(String),
+    Legacy {
+        bytes: [u8; 20],
+        prefix: ArrayString,
+    },
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            match AddressRepr::deserialize(deserializer)? {
+                AddressRepr::Str(s) => s.parse().map_err(D::Error::custom),
+                AddressRepr::Legacy { bytes, prefix } => Ok(Address { bytes, prefix }),
+            }
+        } else {
+            let (bytes, prefix) = <([u8; 20], ArrayString)>::deserialize(deserializer)?;
+            Ok(Address { bytes, prefix })
+        }
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl borsh::BorshSerialize for Address {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        borsh::BorshSerialize::serialize(&self.bytes, writer)?;
+        borsh::BorshSerialize::serialize(self.prefix.as_str(), writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl borsh::BorshDeserialize for Address {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let bytes = <[u8; 20] as borsh::BorshDeserialize>::deserialize_reader(reader)?;
+        let prefix = <String as borsh::BorshDeserialize>::deserialize_reader(reader)?;
+        Address::from_bytes(bytes, prefix)
+            .map_err(|e| borsh::io::Error::new(borsh::io::ErrorKind::InvalidData, e.to_string()))
+    }
+}
+
+#[test]
+fn test_address_serde() {
+    let address = Address::from_bytes([7; 20], "osmo").unwrap();
+    let json = serde_json::to_string(&address).unwrap();
+    assert_eq!(json, format!("\"{}\"", address));
+    assert_eq!(serde_json::from_str::<Address>(&json).unwrap(), address);
+    // the struct form written by earlier versions
+    let legacy = serde_json::json!({ "bytes": vec![7; 20], "prefix": "osmo" });
+    assert_eq!(serde_json::from_value::<Address>(legacy).unwrap(), address);
+
+    // binary formats keep the layout of the earlier derived struct, whose prefix was
+    // 32 optional chars and the number used
+    #[derive(Serialize)]
+    struct LegacyAddress {
+        bytes: [u8; 20],
+        prefix: ([Option<char>; 32], usize),
+    }
+    let mut chars = [None; 32];
+    for (slot, c) in chars.iter_mut().zip("osmo".chars()) {
+        *slot = Some(c);
+    }
+    let legacy = bincode::serialize(&LegacyAddress {
+        bytes: [7; 20],
+        prefix: (chars, 4),
+    })
+    .unwrap();
+    assert_eq!(bincode::deserialize::<Address>(&legacy).unwrap(), address);
+    assert_eq!(bincode::serialize(&address).unwrap(), legacy);
+}
+
 #[test]
 fn test_bech32() {
     let address = Address::from_bytes([0; 20], "cosmos").unwrap();
@@ -145,6 +353,22 @@ fn test_bech32() {
         .expect("Failed to decode");
 }
 
+#[test]
+fn test_bech32m() {
+    let address: Address = "cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrp"
+        .parse()
+        .unwrap();
+    let encoded = address
+        .to_bech32_variant("cosmos", Bech32Variant::Bech32m)
+        .unwrap();
+    assert_ne!(encoded, address.to_string());
+    assert_eq!(encoded.parse::<Address>().unwrap(), address);
+    assert!(Address::validate(&encoded, Some("cosmos")).is_ok());
+    let (decoded, variant) = Address::from_bech32_with_variant(&encoded).unwrap();
+    assert_eq!(decoded, address);
+    assert_eq!(variant, Bech32Variant::Bech32m);
+}
+
 #[test]
 fn test_default_prefix() {
     Address::from_bytes([0; 20], Address::DEFAULT_PREFIX).unwrap();
@@ -163,3 +387,62 @@ fn test_parse() {
         .parse()
         .unwrap();
 }
+
+#[test]
+fn test_validate() {
+    let valid = "cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrp";
+    assert!(Address::is_valid_bech32(valid));
+    assert!(Address::validate(valid, Some("cosmos")).is_ok());
+    assert!(matches!(
+        Address::validate(valid, Some("osmo")),
+        Err(AddressError::WrongPrefix { .. })
+    ));
+    assert!(matches!(
+        Address::validate("cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrq", None),
+        Err(AddressError::Bech32InvalidChecksum)
+    ));
+    // a valid 32 byte module account style address
+    let long = bech32::encode("cosmos", [1u8; 32].to_base32(), Variant::Bech32).unwrap();
+    assert!(matches!(
+        Address::validate(&long, None),
+        Err(AddressError::Bech32WrongLength)
+    ));
+    assert!(!Address::is_valid_bech32("not an address"));
+}
+
+#[test]
+fn test_eth_hex() {
+    // from the EIP-55 test vectors
+    let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    let address = Address::from_eth_hex(checksummed, "evmos").unwrap();
+    assert_eq!(address.to_eth_hex(), checksummed);
+    assert_eq!(address.get_prefix(), "evmos");
+    assert_eq!(
+        Address::from_eth_hex(&checksummed.to_lowercase(), "evmos").unwrap(),
+        address
+    );
+    let bad = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD";
+    assert!(matches!(
+        Address::from_eth_hex(bad, "evmos"),
+        Err(AddressError::InvalidEip55Checksum)
+    ));
+    assert!(Address::from_eth_hex("0x1234", "evmos").is_err());
+}
+
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    // this tests that any address survives a round trip through bech32
+    fn proptest_bech32_round_trip(bytes in proptest::array::uniform20(0u8..), prefix in "[a-z]{1,10}") {
+        let address = Address::from_bytes(bytes, prefix.clone()).unwrap();
+        let encoded = address.to_bech32(prefix).unwrap();
+        proptest::prop_assert_eq!(encoded.parse::<Address>().unwrap(), address);
+    }
+
+    #[test]
+    // this tests that parsing arbitrary input returns rather than panicking
+    fn proptest_parse_arbitrary(s in "\\PC*") {
+        let _ = s.parse::<Address>();
+        let _ = Address::validate(&s, Some("cosmos"));
+    }
+}
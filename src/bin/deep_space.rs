@@ -0,0 +1,181 @@
+//! A command line companion to the library for generating keys, deriving addresses,
+//! signing transactions offline and broadcasting them. Built with the `cli` feature
+//!
+//! Keys are never passed as arguments, where they would end up in shell history, they
+//! are read from the DEEP_SPACE_MNEMONIC (with an optional DEEP_SPACE_PASSPHRASE),
+//! DEEP_SPACE_MNEMONIC_FILE or DEEP_SPACE_PRIVATE_KEY environment variables
+
+use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
+use deep_space::error::PhraseInputError;
+use deep_space::mnemonic::{self, Mnemonic};
+use deep_space::{Contact, Fee, MessageArgs, Msg, PrivateKey, TxBuilder};
+use serde_derive::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::process::exit;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: deep-space <command> [options]
+
+Commands:
+  keygen [--prefix P] [--words N]     generate a mnemonic and print its address
+  address [--prefix P]                print the address of the key in the environment
+  sign <tx.json>                      sign a JSON tx, printing base64 TxRaw bytes
+  broadcast <grpc url> <base64 tx> [--prefix P] [--wait SECONDS]
+                                      broadcast signed TxRaw bytes
+
+The key is read from DEEP_SPACE_MNEMONIC (and DEEP_SPACE_PASSPHRASE), a file
+named by DEEP_SPACE_MNEMONIC_FILE, or DEEP_SPACE_PRIVATE_KEY as hex. A tx file looks like
+  {\"chain_id\": \"cosmoshub-4\", \"account_number\": 1, \"sequence\": 0,
+   \"fee\": {\"amount\": [{\"denom\": \"uatom\", \"amount\": \"5000\"}], \"gas_limit\": 200000},
+   \"memo\": \"\", \"messages\": [{\"@type\": \"/cosmos.bank.v1beta1.MsgSend\", ...}]}";
+
+/// The contents of a tx file for the sign command, messages use the proto3 JSON form
+#[derive(Deserialize)]
+struct TxFile {
+    chain_id: String,
+    account_number: u64,
+    sequence: u64,
+    fee: Fee,
+    #[serde(default)]
+    memo: String,
+    #[serde(default)]
+    timeout_height: u64,
+    messages: Vec<Msg>,
+}
+
+/// Positional arguments and `--name value` options
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Args, String> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => match args.next() {
+                    Some(value) => {
+                        options.insert(name.to_string(), value);
+                    }
+                    None => return Err(format!("--{} needs a value", name)),
+                },
+                None => positional.push(arg),
+            }
+        }
+        Ok(Args {
+            positional,
+            options,
+        })
+    }
+
+    fn option(&self, name: &str, default: &str) -> String {
+        self.options
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| default.to_string())
+    }
+
+    fn positional(&self, index: usize, name: &str) -> Result<&str, String> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("missing {}", name))
+    }
+}
+
+fn key_from_env() -> Result<PrivateKey, Box<dyn Error>> {
+    match mnemonic::from_env_or_file("DEEP_SPACE_MNEMONIC") {
+        Ok(phrase) => {
+            let passphrase = env::var("DEEP_SPACE_PASSPHRASE").unwrap_or_default();
+            return Ok(phrase.to_private_key(&passphrase)?);
+        }
+        Err(PhraseInputError::NotSet(_)) => {}
+        Err(e) => return Err(e.into()),
+    }
+    if let Ok(hex) = env::var("DEEP_SPACE_PRIVATE_KEY") {
+        return Ok(hex.trim().parse()?);
+    }
+    Err("set DEEP_SPACE_MNEMONIC, DEEP_SPACE_MNEMONIC_FILE or DEEP_SPACE_PRIVATE_KEY".into())
+}
+
+async fn run(command: &str, args: Args) -> Result<(), Box<dyn Error>> {
+    let prefix = args.option("prefix", "cosmos");
+    match command {
+        "keygen" => {
+            let words: usize = args.option("words", "24").parse()?;
+            let mnemonic = Mnemonic::generate(words).map_err(|e| e.to_string())?;
+            let key = PrivateKey::from_phrase(mnemonic.as_str(), "")?;
+            println!("mnemonic: {}", mnemonic.as_str());
+            println!("address: {}", key.to_address(&prefix)?);
+        }
+        "address" => {
+            let key = key_from_env()?;
+            println!("{}", key.to_address(&prefix)?);
+        }
+        "sign" => {
+            let file = std::fs::read_to_string(args.positional(0, "tx file")?)?;
+            let tx: TxFile = serde_json::from_str(&file)?;
+            let args = MessageArgs {
+                sequence: tx.sequence,
+                fee: tx.fee,
+                timeout_height: tx.timeout_height,
+                chain_id: tx.chain_id,
+                account_number: tx.account_number,
+                ..Default::default()
+            };
+            let bytes = TxBuilder::from_args(args)
+                .msgs(tx.messages)
+                .memo(tx.memo)
+                .sign_to_bytes(&key_from_env()?)?;
+            println!("{}", base64::encode(bytes));
+        }
+        "broadcast" => {
+            let url = args.positional(0, "grpc url")?;
+            let bytes = base64::decode(args.positional(1, "base64 tx")?.trim())?;
+            let contact = Contact::new(url, Duration::from_secs(30), &prefix)?;
+            let res = contact.send_transaction(bytes, BroadcastMode::Sync).await?;
+            let res = match args.options.get("wait") {
+                Some(wait) => {
+                    let wait = Duration::from_secs(wait.parse()?);
+                    contact.wait_for_tx(&res.txhash, wait).await?
+                }
+                None => res,
+            };
+            println!("txhash: {}", res.txhash);
+            println!("code: {}", res.code);
+            if res.height != 0 {
+                println!("height: {}", res.height);
+            }
+            if !res.raw_log.is_empty() {
+                println!("log: {}", res.raw_log);
+            }
+        }
+        other => return Err(format!("unknown command {}\n\n{}", other, USAGE).into()),
+    }
+    Ok(())
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let mut args = env::args().skip(1);
+    let command = match args.next() {
+        Some(c) if c != "--help" && c != "-h" => c,
+        _ => {
+            println!("{}", USAGE);
+            return;
+        }
+    };
+    let res = match Args::parse(args) {
+        Ok(args) => run(&command, args).await,
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = res {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+}
@@ -0,0 +1,145 @@
+//! Contains message builders and queries for the authz module, which lets a granter
+//! authorize a grantee to send messages on its behalf. Bots commonly keep funds in a
+//! cold granter account and sign with a hot grantee key
+
+use crate::client::types::LatestBlock;
+use crate::error::CosmosGrpcError;
+use crate::{Address, Coin, Contact, Msg, PrivateKey};
+use cosmos_sdk_proto::cosmos::authz::v1beta1::query_client::QueryClient as AuthzQueryClient;
+use cosmos_sdk_proto::cosmos::authz::v1beta1::{Grant, MsgExec, QueryGrantsRequest};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use std::collections::BTreeSet;
+use std::time::Duration;
+use tonic::Code as GrpcCode;
+
+pub const MSG_EXEC_TYPE_URL: &str = "/cosmos.authz.v1beta1.MsgExec";
+
+impl Msg {
+    /// Creates an authz MsgExec, `grantee` executes `msgs` with the authority of the
+    /// granter that signs them
+    pub fn authz_exec(grantee: Address, msgs: Vec<Msg>) -> Self {
+        let exec = MsgExec {
+            grantee: grantee.to_string(),
+            msgs: msgs.into_iter().map(|msg| msg.0).collect(),
+        };
+        Msg::new(MSG_EXEC_TYPE_URL, exec)
+    }
+}
+
+impl Contact {
+    /// Gets the grants from `granter` to `grantee`, only those allowing `msg_type_url`
+    /// if it is provided. Expired grants the chain has not pruned yet are included
+    pub async fn get_authz_grants(
+        &self,
+        granter: Address,
+        grantee: Address,
+        msg_type_url: Option<&str>,
+    ) -> Result<Vec<Grant>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = AuthzQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .grants(QueryGrantsRequest {
+                    // chain prefix is validated as part of this client, so this can't
+                    // panic
+                    granter: granter.to_bech32(&self.config.prefix).unwrap(),
+                    grantee: grantee.to_bech32(&self.config.prefix).unwrap(),
+                    msg_type_url: msg_type_url.unwrap_or_default().to_string(),
+                    pagination: None,
+                })
+                .await;
+            match res {
+                Ok(res) => Ok(res.into_inner().grants),
+                // returned when filtering by a type url nothing is granted for
+                Err(e) if e.code() == GrpcCode::NotFound => Ok(Vec::new()),
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
+    }
+
+    /// Checks that `grantee` holds an unexpired grant from `granter` for the type of
+    /// every message in `msgs`, returning BadInput naming the first message type that
+    /// is not covered. Expiration is compared against the time of the latest block
+    pub async fn check_authz_grants(
+        &self,
+        granter: Address,
+        grantee: Address,
+        msgs: &[Msg],
+    ) -> Result<(), CosmosGrpcError> {
+        let now = match self.get_latest_block().await? {
+            LatestBlock::Latest { block } => block
+                .header
+                .and_then(|h| h.time)
+                .map(|t| t.seconds)
+                .unwrap_or_default(),
+            LatestBlock::Syncing { .. } => return Err(CosmosGrpcError::NodeNotSynced),
+            LatestBlock::WaitingToStart => return Err(CosmosGrpcError::ChainNotRunning),
+        };
+        let type_urls: BTreeSet<&str> = msgs.iter().map(|msg| msg.type_url()).collect();
+        for type_url in type_urls {
+            let grants = self
+                .get_authz_grants(granter, grantee, Some(type_url))
+                .await?;
+            let valid = grants.iter().any(|grant| match &grant.expiration {
+                Some(expiration) => expiration.seconds > now,
+                None => true,
+            });
+            if !valid {
+                return Err(CosmosGrpcError::BadInput(format!(
+                    "{} has no unexpired authz grant from {} for {}",
+                    grantee, granter, type_url
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends `messages` on behalf of `granter`, wrapped in a MsgExec signed by the
+    /// grantee. The grants are checked first so a missing or expired grant fails before
+    /// any fee is spent. The messages must name the granter as their signer, the fee is
+    /// paid by the grantee and the grantee's sequence is used
+    pub async fn send_msgs_as(
+        &self,
+        grantee_key: PrivateKey,
+        granter: Address,
+        messages: &[Msg],
+        memo: Option<String>,
+        fee_coin: &[Coin],
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        if messages.is_empty() {
+            return Err(CosmosGrpcError::BadInput("No messages to send".to_string()));
+        }
+        let grantee = grantee_key.to_address(&self.config.prefix)?;
+        self.check_authz_grants(granter, grantee, messages).await?;
+        let exec = Msg::authz_exec(grantee, messages.to_vec());
+        self.send_message(&[exec], memo, fee_coin, wait_timeout, grantee_key)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+    use prost::Message;
+
+    #[test]
+    fn test_authz_exec() {
+        let granter = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let grantee = Address::from_bytes([2; 20], "cosmos").unwrap();
+        let send = Msg::new(
+            "/cosmos.bank.v1beta1.MsgSend",
+            MsgSend {
+                from_address: granter.to_string(),
+                to_address: grantee.to_string(),
+                amount: vec![],
+            },
+        );
+        let exec = Msg::authz_exec(grantee, vec![send.clone()]);
+        assert_eq!(exec.type_url(), MSG_EXEC_TYPE_URL);
+        let decoded = MsgExec::decode(exec.0.value.as_slice()).unwrap();
+        assert_eq!(decoded.grantee, grantee.to_string());
+        assert_eq!(decoded.msgs, vec![send.0]);
+    }
+}
@@ -1,57 +1,145 @@
 //! Contains utilities and query endpoints for use with the Cosmos bank module
 //!
+pub mod sweep;
+
+use super::paginator::Paginator;
 use super::PAGE;
 use crate::error::CosmosGrpcError;
-use crate::{Address, Coin, Contact};
+use crate::{Address, Coin, Contact, Uint256};
 use cosmos_sdk_proto::cosmos::bank::v1beta1::query_client::QueryClient as BankQueryClient;
 use cosmos_sdk_proto::cosmos::bank::v1beta1::{
-    Metadata, QueryDenomMetadataRequest, QueryDenomsMetadataRequest, QuerySupplyOfRequest,
-    QueryTotalSupplyRequest,
+    Metadata, Params, QueryDenomMetadataRequest, QueryDenomsMetadataRequest, QueryParamsRequest,
+    QuerySupplyOfRequest, QueryTotalSupplyRequest,
 };
 use cosmos_sdk_proto::cosmos::bank::v1beta1::{QueryAllBalancesRequest, QueryBalanceRequest};
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
 
 impl Contact {
     /// gets the total supply of all coins on chain
+    #[deprecated(
+        since = "3.0.0",
+        note = "use get_total_supply or paginate_total_supply"
+    )]
     pub async fn query_total_supply(&self) -> Result<Vec<Coin>, CosmosGrpcError> {
-        let mut grpc = BankQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = grpc
-            .total_supply(QueryTotalSupplyRequest { pagination: PAGE })
-            .await?
-            .into_inner();
-        let mut out = Vec::new();
-        for val in res.supply {
-            out.push(val.into())
-        }
-        Ok(out)
+        Ok(self.get_total_supply(PAGE).await?.0)
+    }
+
+    /// Gets one page of the total supply of all coins on chain, along with the key of the
+    /// next page if there is one. Pass `PAGE` to fetch everything in one request
+    pub async fn get_total_supply(
+        &self,
+        pagination: Option<PageRequest>,
+    ) -> Result<(Vec<Coin>, Option<PageResponse>), CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = BankQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .total_supply(QueryTotalSupplyRequest {
+                    pagination: pagination.clone(),
+                })
+                .await?
+                .into_inner();
+            Ok((
+                res.supply.into_iter().map(Coin::from).collect(),
+                res.pagination,
+            ))
+        })
+        .await
+    }
+
+    /// Pages through the total supply of all coins on chain
+    pub fn paginate_total_supply(&self) -> Paginator<Coin> {
+        let contact = self.clone();
+        Paginator::new(move |pagination| {
+            let contact = contact.clone();
+            async move { contact.get_total_supply(Some(pagination)).await }
+        })
+    }
+
+    /// Pages through the denom metadata of every token type on the chain
+    pub fn paginate_denoms_metadata(&self) -> Paginator<Metadata> {
+        let contact = self.clone();
+        Paginator::new(move |pagination| {
+            let contact = contact.clone();
+            async move {
+                contact
+                    .retry(|| async {
+                        let mut grpc =
+                            BankQueryClient::new(contact.get_channel().await?).accept_gzip();
+                        let res = grpc
+                            .denoms_metadata(QueryDenomsMetadataRequest {
+                                pagination: Some(pagination.clone()),
+                            })
+                            .await?
+                            .into_inner();
+                        Ok((res.metadatas, res.pagination))
+                    })
+                    .await
+            }
+        })
     }
 
-    /// gets the supply of an individual token
+    /// Gets the supply of an individual token, zero if the chain has no such token
+    pub async fn get_supply_of(&self, denom: &str) -> Result<Coin, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = BankQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .supply_of(QuerySupplyOfRequest {
+                    denom: denom.to_string(),
+                })
+                .await?
+                .into_inner();
+            Ok(match res.amount {
+                Some(v) => v.into(),
+                None => Coin {
+                    amount: Uint256::zero(),
+                    denom: denom.to_string(),
+                },
+            })
+        })
+        .await
+    }
+
+    /// gets the supply of an individual token, the bank module reports a zero amount
+    /// rather than nothing for unknown tokens so this is always Some
+    #[deprecated(since = "3.0.0", note = "use get_supply_of")]
     pub async fn query_supply_of(&self, denom: String) -> Result<Option<Coin>, CosmosGrpcError> {
-        let mut grpc = BankQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = grpc
-            .supply_of(QuerySupplyOfRequest { denom })
-            .await?
-            .into_inner();
-        match res.amount {
-            Some(v) => Ok(Some(v.into())),
-            None => Ok(None),
-        }
+        Ok(Some(self.get_supply_of(&denom).await?))
+    }
+
+    /// Gets the parameters of the bank module, including which denoms may be sent
+    pub async fn get_bank_params(&self) -> Result<Params, CosmosGrpcError> {
+        self.cached("bank_params", || {
+            self.retry(|| async {
+                let mut grpc = BankQueryClient::new(self.get_channel().await?).accept_gzip();
+                let res = grpc
+                    .params(QueryParamsRequest {})
+                    .await?
+                    .into_inner()
+                    .params;
+                match res {
+                    Some(params) => Ok(params),
+                    None => Err(CosmosGrpcError::BadResponse(
+                        "No bank params returned".to_string(),
+                    )),
+                }
+            })
+        })
+        .await
     }
 
     /// Gets the denom metadata for every token type on the chain
     pub async fn get_all_denoms_metadata(&self) -> Result<Vec<Metadata>, CosmosGrpcError> {
-        let mut grpc = BankQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = grpc
-            .denoms_metadata(QueryDenomsMetadataRequest { pagination: PAGE })
-            .await?
-            .into_inner();
-        Ok(res.metadatas)
+        self.cached("denoms_metadata", || {
+            self.retry(|| async {
+                let mut grpc = BankQueryClient::new(self.get_channel().await?).accept_gzip();
+                let res = grpc
+                    .denoms_metadata(QueryDenomsMetadataRequest { pagination: PAGE })
+                    .await?
+                    .into_inner();
+                Ok(res.metadatas)
+            })
+        })
+        .await
     }
 
     /// Gets the denom metadata for a specific token
@@ -59,36 +147,77 @@ impl Contact {
         &self,
         denom: String,
     ) -> Result<Option<Metadata>, CosmosGrpcError> {
-        let mut grpc = BankQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = grpc
-            .denom_metadata(QueryDenomMetadataRequest { denom })
-            .await?
-            .into_inner();
-        Ok(res.metadata)
+        self.cached(format!("denom_metadata/{denom}"), || {
+            self.retry(|| async {
+                let mut grpc = BankQueryClient::new(self.get_channel().await?).accept_gzip();
+                let res = grpc
+                    .denom_metadata(QueryDenomMetadataRequest {
+                        denom: denom.clone(),
+                    })
+                    .await?
+                    .into_inner();
+                Ok(res.metadata)
+            })
+        })
+        .await
+    }
+
+    /// Parses a user provided amount such as `1.5atom` into base units using the denom
+    /// metadata of this chain, see `Coin::parse_with_metadata`
+    pub async fn parse_coin(&self, value: &str) -> Result<Coin, CosmosGrpcError> {
+        let metadata = self.get_all_denoms_metadata().await?;
+        Coin::parse_with_metadata(value, &metadata).map_err(CosmosGrpcError::BadInput)
     }
 
     /// Gets the coin balances for an individual account
     pub async fn get_balances(&self, address: Address) -> Result<Vec<Coin>, CosmosGrpcError> {
-        let mut bankrpc = BankQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = bankrpc
-            .all_balances(QueryAllBalancesRequest {
-                // chain prefix is validated as part of this client, so this can't
-                // panic
-                address: address.to_bech32(&self.chain_prefix).unwrap(),
-                pagination: PAGE,
-            })
-            .await?
-            .into_inner();
-        let balances = res.balances;
-        let mut ret = Vec::new();
-        for value in balances {
-            ret.push(value.into());
-        }
-        Ok(ret)
+        self.retry(|| async {
+            let mut bankrpc = BankQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = bankrpc
+                .all_balances(QueryAllBalancesRequest {
+                    // chain prefix is validated as part of this client, so this can't
+                    // panic
+                    address: address.to_bech32(&self.config.prefix).unwrap(),
+                    pagination: PAGE,
+                })
+                .await?
+                .into_inner();
+            let balances = res.balances;
+            let mut ret = Vec::new();
+            for value in balances {
+                ret.push(value.into());
+            }
+            Ok(ret)
+        })
+        .await
+    }
+
+    /// Pages through the coin balances of an account, for accounts holding too many
+    /// denoms to fetch at once
+    pub fn paginate_balances(&self, address: Address) -> Paginator<Coin> {
+        let contact = self.clone();
+        Paginator::new(move |pagination| {
+            let contact = contact.clone();
+            async move {
+                contact
+                    .retry(|| async {
+                        let mut bankrpc =
+                            BankQueryClient::new(contact.get_channel().await?).accept_gzip();
+                        let res = bankrpc
+                            .all_balances(QueryAllBalancesRequest {
+                                address: address.to_bech32(&contact.config.prefix).unwrap(),
+                                pagination: Some(pagination.clone()),
+                            })
+                            .await?
+                            .into_inner();
+                        Ok((
+                            res.balances.into_iter().map(Coin::from).collect(),
+                            res.pagination,
+                        ))
+                    })
+                    .await
+            }
+        })
     }
 
     /// Gets the balance of a single for an individual account
@@ -97,21 +226,127 @@ impl Contact {
         address: Address,
         denom: String,
     ) -> Result<Option<Coin>, CosmosGrpcError> {
-        let mut bankrpc = BankQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = bankrpc
-            .balance(QueryBalanceRequest {
-                // chain prefix is validated as part of this client, so this can't
-                // panic
-                address: address.to_bech32(&self.chain_prefix).unwrap(),
-                denom,
-            })
-            .await?
-            .into_inner();
-        match res.balance {
-            Some(v) => Ok(Some(v.into())),
-            None => Ok(None),
-        }
+        self.retry(|| async {
+            let mut bankrpc = BankQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = bankrpc
+                .balance(QueryBalanceRequest {
+                    // chain prefix is validated as part of this client, so this can't
+                    // panic
+                    address: address.to_bech32(&self.config.prefix).unwrap(),
+                    denom: denom.clone(),
+                })
+                .await?
+                .into_inner();
+            match res.balance {
+                Some(v) => Ok(Some(v.into())),
+                None => Ok(None),
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock_grpc::MockGrpc;
+    use crate::client::RetryPolicy;
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::{
+        QueryBalanceResponse, QuerySupplyOfResponse, QueryTotalSupplyResponse,
+    };
+    use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tonic::Status;
+
+    #[actix_rt::test]
+    #[allow(deprecated)]
+    async fn test_supply_queries() {
+        let coin = |denom: &str, amount: &str| ProtoCoin {
+            denom: denom.to_string(),
+            amount: amount.to_string(),
+        };
+        let url = MockGrpc::new()
+            .with(
+                "/cosmos.bank.v1beta1.Query/TotalSupply",
+                move |_: QueryTotalSupplyRequest| {
+                    Ok(QueryTotalSupplyResponse {
+                        supply: vec![coin("uatom", "100"), coin("uosmo", "7")],
+                        pagination: None,
+                    })
+                },
+            )
+            .with(
+                "/cosmos.bank.v1beta1.Query/SupplyOf",
+                move |req: QuerySupplyOfRequest| {
+                    let amount = if req.denom == "uatom" { "100" } else { "0" };
+                    Ok(QuerySupplyOfResponse {
+                        amount: Some(coin(&req.denom, amount)),
+                    })
+                },
+            )
+            .serve()
+            .await;
+        let contact = Contact::new(&url, Duration::from_secs(5), "cosmos").unwrap();
+
+        let (supply, _) = contact.get_total_supply(PAGE).await.unwrap();
+        assert_eq!(supply.len(), 2);
+        assert_eq!(contact.query_total_supply().await.unwrap(), supply);
+
+        let atom = contact.get_supply_of("uatom").await.unwrap();
+        assert_eq!(atom, "100uatom".parse().unwrap());
+        assert_eq!(
+            contact.query_supply_of("uatom".to_string()).await.unwrap(),
+            Some(atom)
+        );
+        assert_eq!(
+            contact.get_supply_of("unknown").await.unwrap().amount,
+            Uint256::zero()
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_query_retries_unavailable() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let grpc = MockGrpc::new().with(
+            "/cosmos.bank.v1beta1.Query/Balance",
+            move |req: QueryBalanceRequest| {
+                // the node drops the first request, as it would while restarting
+                if counter.fetch_add(1, Ordering::Relaxed) == 0 {
+                    return Err(Status::unavailable("restarting"));
+                }
+                Ok(QueryBalanceResponse {
+                    balance: Some(ProtoCoin {
+                        denom: req.denom,
+                        amount: "42".to_string(),
+                    }),
+                })
+            },
+        );
+        let url = grpc.serve().await;
+        let contact = Contact::new(&url, Duration::from_secs(5), "cosmos")
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                base_delay: Duration::from_millis(1),
+                ..Default::default()
+            });
+        let address: Address = "cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a"
+            .parse()
+            .unwrap();
+        let balance = contact
+            .get_balance(address, "uatom".to_string())
+            .await
+            .unwrap();
+        assert_eq!(balance, Some("42uatom".parse().unwrap()));
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+
+        // without retries the same failure reaches the caller
+        attempts.store(0, Ordering::Relaxed);
+        let contact = contact.with_retry_policy(RetryPolicy::none());
+        let res = contact.get_balance(address, "uatom".to_string()).await;
+        assert!(res.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
     }
 }
@@ -0,0 +1,255 @@
+//! Sweeping deposit addresses, the funds held by the child addresses of a wallet are
+//! consolidated into a single destination. Each transaction sweeps several addresses
+//! at once, every swept address signs it and the best funded one pays the fee
+
+use crate::error::{CosmosGrpcError, PrivateKeyError};
+use crate::mnemonic::SecretMnemonic;
+use crate::tx::TxBuilder;
+use crate::{Address, Coin, Contact, Fee, Msg, PrivateKey, PublicKey, Uint256, Xpub};
+use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::time::Duration;
+
+/// The cost of sweeping, fees grow with the number of addresses in a transaction since
+/// every address adds a message and a signature
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepFeePolicy {
+    /// The fee paid for each address swept, a tx sweeping n addresses pays n times this
+    pub fee_per_address: Coin,
+    /// The gas limit for each address swept
+    pub gas_per_address: u64,
+    /// The most addresses swept by a single transaction
+    pub max_addresses_per_tx: usize,
+}
+
+/// One planned sweep transaction, the first address pays the fee
+#[derive(Debug, Clone, PartialEq)]
+struct SweepBatch {
+    /// The child index of each address and the coins it sends
+    sends: Vec<(u32, Vec<Coin>)>,
+    fee: Coin,
+}
+
+fn fee_balance(balances: &[Coin], denom: &str) -> Uint256 {
+    balances
+        .iter()
+        .find(|c| c.denom == denom)
+        .map(|c| c.amount)
+        .unwrap_or_else(Uint256::zero)
+}
+
+/// Groups funded addresses into transactions. Addresses holding only the fee denom are
+/// skipped unless they hold more than the fee they add, and each transaction is paid
+/// by its best funded address, so addresses are left behind once no remaining address
+/// can pay for a transaction that includes them
+fn plan_sweep(funded: Vec<(u32, Vec<Coin>)>, policy: &SweepFeePolicy) -> Vec<SweepBatch> {
+    let denom = &policy.fee_per_address.denom;
+    let per_address = policy.fee_per_address.amount;
+    let mut candidates: Vec<(u32, Vec<Coin>)> = funded
+        .into_iter()
+        .map(|(i, coins)| {
+            let coins: Vec<Coin> = coins
+                .into_iter()
+                .filter(|c| c.amount != Uint256::zero())
+                .collect();
+            (i, coins)
+        })
+        .filter(|(_, coins)| {
+            coins.iter().any(|c| &c.denom != denom) || fee_balance(coins, denom) > per_address
+        })
+        .collect();
+    candidates.sort_by_key(|(_, coins)| std::cmp::Reverse(fee_balance(coins, denom)));
+
+    let mut batches = Vec::new();
+    let mut remaining: VecDeque<(u32, Vec<Coin>)> = candidates.into();
+    while let Some((payer_index, payer_coins)) = remaining.pop_front() {
+        let payer_balance = fee_balance(&payer_coins, denom);
+        // the payer must keep something to send after the fee, a signer without a
+        // message of its own is rejected by the chain
+        let payable = if per_address == Uint256::zero() {
+            usize::MAX
+        } else {
+            let (count, rem) = payer_balance.divide(per_address).unwrap();
+            let count = if rem == Uint256::zero() {
+                count.checked_sub(Uint256::from_u64(1)).unwrap_or(count)
+            } else {
+                count
+            };
+            count
+                .try_resize_to_u64()
+                .map(|c| c as usize)
+                .unwrap_or(usize::MAX)
+        };
+        let size = payable
+            .min(policy.max_addresses_per_tx)
+            .min(remaining.len() + 1);
+        if size == 0 {
+            break;
+        }
+        let fee = Coin {
+            amount: per_address
+                .checked_mul(Uint256::from_u64(size as u64))
+                .unwrap(),
+            denom: denom.clone(),
+        };
+        let payer_sends: Vec<Coin> = payer_coins
+            .into_iter()
+            .filter_map(|mut c| {
+                if &c.denom == denom {
+                    c.amount = c.amount.checked_sub(fee.amount)?;
+                }
+                Some(c).filter(|c| c.amount != Uint256::zero())
+            })
+            .collect();
+        let mut sends = vec![(payer_index, payer_sends)];
+        sends.extend(remaining.drain(..size - 1));
+        batches.push(SweepBatch { sends, fee });
+    }
+    batches
+}
+
+impl Contact {
+    /// Sweeps every funded address among the `indexes` children of the wallet, the
+    /// addresses m/44'/coin_type'/0'/0/i, into `destination`. Balances are found from
+    /// the public keys alone and private keys are only derived for funded addresses.
+    /// Returns the response of every transaction sent, which may be fewer than planned
+    /// if a broadcast fails
+    pub async fn sweep(
+        &self,
+        mnemonic: &SecretMnemonic,
+        passphrase: &str,
+        indexes: Range<u32>,
+        destination: Address,
+        fee_policy: SweepFeePolicy,
+        wait_timeout: Option<Duration>,
+    ) -> Result<Vec<TxResponse>, CosmosGrpcError> {
+        let seed = mnemonic.to_seed(passphrase);
+        let base_path = format!("{}/0", self.config.account_path());
+        let xpub = Xpub::from_seed_and_path(&base_path, &seed)?;
+
+        let mut funded = Vec::new();
+        for i in indexes {
+            let address = xpub
+                .derive_child(i)
+                .map_err(PrivateKeyError::from)?
+                .to_address(&self.config.prefix)
+                .map_err(PrivateKeyError::from)?;
+            let balances = self.get_balances(address).await?;
+            if !balances.is_empty() {
+                funded.push((i, balances));
+            }
+        }
+
+        let mut responses = Vec::new();
+        for batch in plan_sweep(funded, &fee_policy) {
+            let mut keys = Vec::new();
+            let mut msgs = Vec::new();
+            for (i, coins) in batch.sends.iter() {
+                let key = PrivateKey::from_seed_and_path(&format!("{}/{}", base_path, i), &seed)?;
+                let send = MsgSend {
+                    from_address: key.to_address(&self.config.prefix)?.to_string(),
+                    to_address: destination.to_bech32(&self.config.prefix).unwrap(),
+                    amount: coins.iter().cloned().map(Into::into).collect(),
+                };
+                msgs.push(Msg::new("/cosmos.bank.v1beta1.MsgSend", send));
+                keys.push(key);
+            }
+            let fee = Fee {
+                amount: vec![batch.fee],
+                gas_limit: fee_policy.gas_per_address * batch.sends.len() as u64,
+                payer: None,
+                granter: None,
+            };
+
+            let payer = keys[0].to_address(&self.config.prefix)?;
+            let args = self.get_message_args(payer, fee).await?;
+            let mut builder = TxBuilder::from_args(args.clone()).msgs(msgs).signer(
+                keys[0].to_public_key(PublicKey::DEFAULT_PREFIX)?,
+                args.account_number,
+                args.sequence,
+            );
+            for key in keys.iter().skip(1) {
+                let account = self
+                    .get_account_info(key.to_address(&self.config.prefix)?)
+                    .await?;
+                builder = builder.co_signer(
+                    key.to_public_key(PublicKey::DEFAULT_PREFIX)?,
+                    account.account_number,
+                    account.sequence,
+                );
+            }
+            let mut tx = builder.build_multi_signer()?;
+            for key in keys.iter() {
+                tx.sign(key)?;
+            }
+
+            let response = self
+                .send_transaction(tx.into_tx_raw_bytes()?, BroadcastMode::Sync)
+                .await?;
+            let response = match wait_timeout {
+                Some(timeout) => self.wait_for_tx(&response.txhash, timeout).await?,
+                None => response,
+            };
+            responses.push(response);
+        }
+        Ok(responses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coins(s: &str) -> Vec<Coin> {
+        s.split(',').map(|c| c.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_plan_sweep() {
+        let policy = SweepFeePolicy {
+            fee_per_address: "10uatom".parse().unwrap(),
+            gas_per_address: 100_000,
+            max_addresses_per_tx: 3,
+        };
+        let funded = vec![
+            (0, coins("5uatom")),
+            (1, coins("100uatom")),
+            (2, coins("1ufoo")),
+            (3, coins("50uatom,7ufoo")),
+            (4, coins("25uatom")),
+            (5, coins("0uatom")),
+        ];
+        let batches = plan_sweep(funded, &policy);
+        // 0 and 5 are dust, 1 pays for itself, 3 and 4, leaving 2 which holds no
+        // uatom to pay for its own sweep
+        assert_eq!(
+            batches,
+            vec![SweepBatch {
+                sends: vec![
+                    (1, coins("70uatom")),
+                    (3, coins("50uatom,7ufoo")),
+                    (4, coins("25uatom")),
+                ],
+                fee: "30uatom".parse().unwrap(),
+            },]
+        );
+
+        // a payer with just enough for itself and one more
+        let funded = vec![(0, coins("21uatom")), (1, coins("1ufoo"))];
+        let batches = plan_sweep(funded, &policy);
+        assert_eq!(
+            batches[0].sends,
+            vec![(0, coins("1uatom")), (1, coins("1ufoo"))]
+        );
+        assert_eq!(batches[0].fee, "20uatom".parse().unwrap());
+
+        // the payer could pay for more addresses than are left
+        let funded = vec![(0, coins("100uatom"))];
+        let batches = plan_sweep(funded, &policy);
+        assert_eq!(batches[0].sends, vec![(0, coins("90uatom"))]);
+        assert_eq!(batches[0].fee, "10uatom".parse().unwrap());
+    }
+}
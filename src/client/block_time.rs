@@ -0,0 +1,163 @@
+//! Converts between block heights and wall clock times using the average block time of
+//! recent blocks, for timeout heights, unbonding completion and upgrade heights. The
+//! block time of a chain drifts, so estimates far from the sampled blocks are less
+//! accurate and should be refreshed as the target approaches
+
+use crate::client::types::LatestBlock;
+use crate::error::CosmosGrpcError;
+use crate::Contact;
+use cosmos_sdk_proto::tendermint::types::Block;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The number of blocks the average block time is measured over by default
+pub const DEFAULT_BLOCK_TIME_SAMPLE: u64 = 100;
+
+/// The average block time measured between two blocks, anchored at the later block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockTimeSample {
+    pub height: u64,
+    pub time: SystemTime,
+    pub average_block_time: Duration,
+}
+
+impl BlockTimeSample {
+    /// Measures the average block time between two blocks, None if the second block
+    /// is not after the first
+    pub fn from_blocks(
+        start_height: u64,
+        start_time: SystemTime,
+        end_height: u64,
+        end_time: SystemTime,
+    ) -> Option<BlockTimeSample> {
+        let blocks = end_height.checked_sub(start_height).filter(|b| *b > 0)?;
+        let elapsed = end_time.duration_since(start_time).ok()?;
+        Some(BlockTimeSample {
+            height: end_height,
+            time: end_time,
+            average_block_time: elapsed / blocks.min(u32::MAX as u64) as u32,
+        })
+    }
+
+    /// The estimated height of the block produced at `time`, times before the sampled
+    /// block are estimated backwards and never go below height 1
+    pub fn estimate_height_at(&self, time: SystemTime) -> u64 {
+        let block_nanos = self.average_block_time.as_nanos().max(1);
+        match time.duration_since(self.time) {
+            Ok(ahead) => {
+                let blocks = ahead.as_nanos() / block_nanos;
+                self.height
+                    .saturating_add(blocks.min(u64::MAX as u128) as u64)
+            }
+            Err(e) => {
+                let blocks = e.duration().as_nanos() / block_nanos;
+                self.height
+                    .saturating_sub(blocks.min(u64::MAX as u128) as u64)
+                    .max(1)
+            }
+        }
+    }
+
+    /// The estimated time block `height` is produced at
+    pub fn estimate_time_of_height(&self, height: u64) -> SystemTime {
+        let offset = |blocks: u64| {
+            let nanos = self.average_block_time.as_nanos() * blocks as u128;
+            Duration::from_nanos(nanos.min(u64::MAX as u128) as u64)
+        };
+        if height >= self.height {
+            self.time + offset(height - self.height)
+        } else {
+            self.time
+                .checked_sub(offset(self.height - height))
+                .unwrap_or(UNIX_EPOCH)
+        }
+    }
+}
+
+/// The height and time of a block
+fn block_height_and_time(block: &Block) -> Result<(u64, SystemTime), CosmosGrpcError> {
+    let header = block
+        .header
+        .as_ref()
+        .ok_or_else(|| CosmosGrpcError::BadResponse("Null block header?".to_string()))?;
+    let time = header
+        .time
+        .clone()
+        .and_then(|t| SystemTime::try_from(t).ok())
+        .ok_or_else(|| CosmosGrpcError::BadResponse("Invalid block time".to_string()))?;
+    Ok((header.height as u64, time))
+}
+
+impl Contact {
+    /// Measures the average block time over the last `blocks` blocks. The node must
+    /// still have the block `blocks` below the latest, pruned nodes keep a limited
+    /// history
+    pub async fn get_block_time_sample(
+        &self,
+        blocks: u64,
+    ) -> Result<BlockTimeSample, CosmosGrpcError> {
+        self.cached(format!("block_time_sample/{}", blocks), || async {
+            let latest = match self.get_latest_block().await? {
+                LatestBlock::Latest { block } | LatestBlock::Syncing { block } => block,
+                LatestBlock::WaitingToStart => return Err(CosmosGrpcError::ChainNotRunning),
+            };
+            let (end_height, end_time) = block_height_and_time(&latest)?;
+            let start_height = end_height.saturating_sub(blocks).max(1);
+            let start = self.get_block(start_height).await?.ok_or_else(|| {
+                CosmosGrpcError::BadResponse(format!("Block {} is not available", start_height))
+            })?;
+            let (start_height, start_time) = block_height_and_time(&start)?;
+            BlockTimeSample::from_blocks(start_height, start_time, end_height, end_time).ok_or_else(
+                || CosmosGrpcError::BadResponse("Not enough blocks to sample".to_string()),
+            )
+        })
+        .await
+    }
+
+    /// Estimates the height of the block produced at `time`, for example to pick a
+    /// timeout height for a transaction, see [BlockTimeSample::estimate_height_at]
+    pub async fn estimate_block_height_at(&self, time: SystemTime) -> Result<u64, CosmosGrpcError> {
+        let sample = self
+            .get_block_time_sample(DEFAULT_BLOCK_TIME_SAMPLE)
+            .await?;
+        Ok(sample.estimate_height_at(time))
+    }
+
+    /// The time block `height` was or will be produced at. Blocks that exist return
+    /// their header time, future blocks are estimated from the recent block time
+    pub async fn estimate_time_of_height(
+        &self,
+        height: u64,
+    ) -> Result<SystemTime, CosmosGrpcError> {
+        let sample = self
+            .get_block_time_sample(DEFAULT_BLOCK_TIME_SAMPLE)
+            .await?;
+        if height <= sample.height {
+            if let Some(block) = self.get_block(height).await? {
+                return Ok(block_height_and_time(&block)?.1);
+            }
+        }
+        Ok(sample.estimate_time_of_height(height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_time_sample() {
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(secs);
+        let sample = BlockTimeSample::from_blocks(900, at(1_000), 1_000, at(1_600)).unwrap();
+        assert_eq!(sample.average_block_time, Duration::from_secs(6));
+        assert_eq!(sample.estimate_height_at(at(1_600)), 1_000);
+        assert_eq!(sample.estimate_height_at(at(1_660)), 1_010);
+        assert_eq!(sample.estimate_height_at(at(1_665)), 1_010);
+        assert_eq!(sample.estimate_height_at(at(1_000)), 900);
+        assert_eq!(sample.estimate_height_at(UNIX_EPOCH), 734);
+        assert_eq!(sample.estimate_time_of_height(1_100), at(2_200));
+        assert_eq!(sample.estimate_time_of_height(950), at(1_300));
+
+        assert!(BlockTimeSample::from_blocks(1_000, at(1_000), 1_000, at(1_600)).is_none());
+        assert!(BlockTimeSample::from_blocks(900, at(1_600), 1_000, at(1_000)).is_none());
+    }
+}
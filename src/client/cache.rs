@@ -0,0 +1,168 @@
+//! An opt in TTL cache for query results that rarely change, such as module params,
+//! denom metadata and the active validator set. Hot loops that build many transactions
+//! otherwise repeat these round trips for every transaction
+
+use crate::client::Contact;
+use crate::error::CosmosGrpcError;
+use crate::Address;
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Cache keys of per account entries start with this prefix
+const ACCOUNT_PREFIX: &str = "account/";
+
+type Entry = (Instant, Arc<dyn Any + Send + Sync>);
+
+/// Query results keyed by query, shared by all clones of a Contact
+#[derive(Debug)]
+pub(crate) struct QueryCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl QueryCache {
+    pub fn new(ttl: Duration) -> Self {
+        QueryCache {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get<T: Clone + 'static>(&self, key: &str) -> Option<T> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((inserted, value)) if inserted.elapsed() < self.ttl => {
+                value.downcast_ref::<T>().cloned()
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert<T: Send + Sync + 'static>(&self, key: String, value: T) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key, (Instant::now(), Arc::new(value)));
+    }
+
+    fn invalidate_prefix(&self, prefix: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|key, _| !key.starts_with(prefix));
+    }
+}
+
+impl Contact {
+    /// Enables caching of slow changing query results for `ttl`. The cache is shared
+    /// by clones of the returned Contact and covers module params, denom metadata,
+    /// the active validator set and account numbers
+    pub fn with_cache(mut self, ttl: Duration) -> Self {
+        self.cache = Some(Arc::new(QueryCache::new(ttl)));
+        self
+    }
+
+    /// Drops every cached query result
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate_prefix("");
+        }
+    }
+
+    /// Drops the cached results for one account, this happens automatically when a
+    /// transaction is rejected for an account sequence mismatch
+    pub fn invalidate_account_cache(&self, address: Address) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate_prefix(&account_key(address));
+        }
+    }
+
+    /// Drops the cached results for all accounts
+    pub(crate) fn invalidate_accounts_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate_prefix(ACCOUNT_PREFIX);
+        }
+    }
+
+    /// Returns the cached result for `key` if there is a fresh one, otherwise runs
+    /// `query` and caches a successful result
+    pub(crate) async fn cached<T, F, Fut>(
+        &self,
+        key: impl Into<String>,
+        query: F,
+    ) -> Result<T, CosmosGrpcError>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, CosmosGrpcError>>,
+    {
+        // historical queries are not cached, they are rarely repeated
+        let cache = match &self.cache {
+            Some(cache) if self.height.is_none() => cache,
+            _ => return query().await,
+        };
+        let key = key.into();
+        if let Some(value) = cache.get::<T>(&key) {
+            return Ok(value);
+        }
+        let value = query().await?;
+        cache.insert(key, value.clone());
+        Ok(value)
+    }
+
+    /// Gets the account number of an address, account numbers never change so this is
+    /// cached when caching is enabled
+    pub async fn get_account_number(&self, address: Address) -> Result<u64, CosmosGrpcError> {
+        let key = format!("{}/number", account_key(address));
+        self.cached(key, || async {
+            Ok(self.get_account_info(address).await?.account_number)
+        })
+        .await
+    }
+}
+
+fn account_key(address: Address) -> String {
+    format!("{}{}", ACCOUNT_PREFIX, address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[actix_rt::test]
+    async fn test_query_cache() {
+        let contact = Contact::new("http://127.0.0.1:1", Duration::from_secs(1), "cosmos")
+            .unwrap()
+            .with_cache(Duration::from_millis(50));
+        let calls = AtomicU32::new(0);
+        let query = || async { Ok(calls.fetch_add(1, Ordering::Relaxed)) };
+        assert_eq!(contact.cached("key", query).await.unwrap(), 0);
+        assert_eq!(contact.cached("key", query).await.unwrap(), 0);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(contact.cached("key", query).await.unwrap(), 1);
+
+        contact
+            .cache
+            .as_ref()
+            .unwrap()
+            .insert(format!("{}x", ACCOUNT_PREFIX), 5u32);
+        contact.invalidate_accounts_cache();
+        assert_eq!(
+            contact
+                .cached(format!("{}x", ACCOUNT_PREFIX), query)
+                .await
+                .unwrap(),
+            2
+        );
+        contact.clear_cache();
+        assert_eq!(contact.cached("key", query).await.unwrap(), 3);
+    }
+}
@@ -0,0 +1,251 @@
+//! Support for the [cosmos/chain-registry](https://github.com/cosmos/chain-registry) metadata
+//! format, allows a Contact to be constructed knowing only the name of a chain
+
+use crate::client::{ChainConfig, ChainStatus, Contact};
+use crate::error::CosmosGrpcError;
+use std::time::Duration;
+
+/// The default location of the chain registry, chain metadata is found at `{url}/{chain_name}/chain.json`
+pub const CHAIN_REGISTRY_URL: &str =
+    "https://raw.githubusercontent.com/cosmos/chain-registry/master";
+
+/// The subset of a chain registry `chain.json` file relevant to sending transactions,
+/// unknown fields are ignored
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChainInfo {
+    pub chain_name: String,
+    pub chain_id: String,
+    pub bech32_prefix: String,
+    /// The BIP44 coin type of the chain
+    #[serde(default)]
+    pub slip44: Option<u32>,
+    #[serde(default)]
+    pub fees: Option<ChainFees>,
+    #[serde(default)]
+    pub staking: Option<ChainStaking>,
+    #[serde(default)]
+    pub apis: ChainApis,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChainFees {
+    pub fee_tokens: Vec<FeeToken>,
+}
+
+/// A token accepted for fees, gas prices are in units of this denom per gas
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FeeToken {
+    pub denom: String,
+    pub fixed_min_gas_price: Option<f64>,
+    pub low_gas_price: Option<f64>,
+    pub average_gas_price: Option<f64>,
+    pub high_gas_price: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChainStaking {
+    pub staking_tokens: Vec<StakingToken>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StakingToken {
+    pub denom: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ChainApis {
+    #[serde(default)]
+    pub rpc: Vec<ApiEndpoint>,
+    #[serde(default)]
+    pub rest: Vec<ApiEndpoint>,
+    #[serde(default)]
+    pub grpc: Vec<ApiEndpoint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ApiEndpoint {
+    pub address: String,
+    pub provider: Option<String>,
+}
+
+impl ChainInfo {
+    /// Downloads the metadata for the given chain name from the public chain registry
+    pub async fn fetch(chain_name: &str) -> Result<ChainInfo, CosmosGrpcError> {
+        ChainInfo::fetch_from(CHAIN_REGISTRY_URL, chain_name).await
+    }
+
+    /// Downloads the metadata for the given chain name from a chain registry mirror
+    pub async fn fetch_from(
+        registry_url: &str,
+        chain_name: &str,
+    ) -> Result<ChainInfo, CosmosGrpcError> {
+        let url = format!(
+            "{}/{}/chain.json",
+            registry_url.trim_end_matches('/'),
+            chain_name
+        );
+        let res = match reqwest::get(&url).await {
+            Ok(res) => res,
+            Err(e) => {
+                return Err(CosmosGrpcError::BadResponse(format!(
+                    "Failed to fetch {} {}",
+                    url, e
+                )))
+            }
+        };
+        if !res.status().is_success() {
+            return Err(CosmosGrpcError::BadInput(format!(
+                "Chain registry returned {} for {}",
+                res.status(),
+                chain_name
+            )));
+        }
+        match res.json().await {
+            Ok(info) => Ok(info),
+            Err(e) => Err(CosmosGrpcError::BadStruct(format!(
+                "Failed to parse {} {}",
+                url, e
+            ))),
+        }
+    }
+
+    /// The gRPC endpoints of this chain as urls, registry entries are usually
+    /// bare host:port pairs so a scheme is added where one is missing
+    pub fn grpc_urls(&self) -> Vec<String> {
+        self.apis
+            .grpc
+            .iter()
+            .map(|api| {
+                if api.address.contains("://") {
+                    api.address.clone()
+                } else {
+                    format!("http://{}", api.address)
+                }
+            })
+            .collect()
+    }
+
+    /// The denoms accepted for fees on this chain, in the registry's order of preference
+    pub fn fee_denoms(&self) -> Vec<String> {
+        match &self.fees {
+            Some(fees) => fees.fee_tokens.iter().map(|t| t.denom.clone()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// A ChainConfig for this chain using the first listed fee token
+    pub fn to_chain_config(&self) -> ChainConfig {
+        let mut config = ChainConfig::new(self.bech32_prefix.clone());
+        if let Some(denom) = self.fee_denoms().first() {
+            config.gas_price = self.gas_price(denom).unwrap_or_default();
+            config.denom = denom.clone();
+        }
+        if let Some(coin_type) = self.slip44 {
+            config.coin_type = coin_type;
+        }
+        config.chain_id = Some(self.chain_id.clone());
+        config
+    }
+
+    /// The average gas price for the given fee denom, falling back to the minimum gas price
+    pub fn gas_price(&self, denom: &str) -> Option<f64> {
+        let fees = self.fees.as_ref()?;
+        let token = fees.fee_tokens.iter().find(|t| t.denom == denom)?;
+        token
+            .average_gas_price
+            .or(token.low_gas_price)
+            .or(token.fixed_min_gas_price)
+    }
+}
+
+impl Contact {
+    /// Creates a Contact for the named chain (as it appears in the chain registry, for
+    /// example "osmosis"), the registry's gRPC endpoints are tried in order and the first
+    /// one with a running chain is used
+    pub async fn from_chain_name(
+        chain_name: &str,
+        timeout: Duration,
+    ) -> Result<Contact, CosmosGrpcError> {
+        let info = ChainInfo::fetch(chain_name).await?;
+        Contact::from_chain_info(&info, timeout).await
+    }
+
+    /// Creates a Contact from already downloaded chain registry metadata, see `from_chain_name`
+    pub async fn from_chain_info(
+        info: &ChainInfo,
+        timeout: Duration,
+    ) -> Result<Contact, CosmosGrpcError> {
+        for url in info.grpc_urls() {
+            let contact = Contact::new_with_config(&url, timeout, info.to_chain_config())?;
+            match tokio::time::timeout(timeout, contact.get_chain_status()).await {
+                Ok(Ok(ChainStatus::Moving { .. })) => return Ok(contact),
+                Ok(Ok(status)) => warn!("Skipping {} with status {:?}", url, status),
+                Ok(Err(e)) => warn!("Skipping {} {}", url, e),
+                Err(_) => warn!("Skipping {} timed out", url),
+            }
+        }
+        Err(CosmosGrpcError::BadInput(format!(
+            "No working gRPC endpoint for {} in the chain registry",
+            info.chain_name
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAIN_JSON: &str = r#"{
+        "$schema": "../chain.schema.json",
+        "chain_name": "osmosis",
+        "status": "live",
+        "network_type": "mainnet",
+        "chain_id": "osmosis-1",
+        "bech32_prefix": "osmo",
+        "slip44": 118,
+        "fees": {
+            "fee_tokens": [
+                {
+                    "denom": "uosmo",
+                    "fixed_min_gas_price": 0.0025,
+                    "low_gas_price": 0.0025,
+                    "average_gas_price": 0.025,
+                    "high_gas_price": 0.04
+                },
+                {
+                    "denom": "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+                }
+            ]
+        },
+        "staking": {"staking_tokens": [{"denom": "uosmo"}]},
+        "apis": {
+            "rpc": [{"address": "https://rpc.osmosis.zone/", "provider": "Osmosis Foundation"}],
+            "grpc": [
+                {"address": "grpc.osmosis.zone:9090", "provider": "Osmosis Foundation"},
+                {"address": "https://osmosis-grpc.example.com:443"}
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_chain_info() {
+        let info: ChainInfo = serde_json::from_str(CHAIN_JSON).unwrap();
+        assert_eq!(info.chain_id, "osmosis-1");
+        assert_eq!(info.bech32_prefix, "osmo");
+        assert_eq!(
+            info.grpc_urls(),
+            vec![
+                "http://grpc.osmosis.zone:9090".to_string(),
+                "https://osmosis-grpc.example.com:443".to_string()
+            ]
+        );
+        assert_eq!(info.fee_denoms().len(), 2);
+        assert_eq!(info.gas_price("uosmo"), Some(0.025));
+        assert_eq!(info.gas_price(&info.fee_denoms()[1]), None);
+        let config = info.to_chain_config();
+        assert_eq!(config.denom, "uosmo");
+        assert_eq!(config.coin_type, 118);
+        assert_eq!(config.chain_id, Some("osmosis-1".to_string()));
+        assert_eq!(info.staking.unwrap().staking_tokens[0].denom, "uosmo");
+    }
+}
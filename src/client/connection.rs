@@ -0,0 +1,228 @@
+//! Management of the gRPC channel shared by all clones of a Contact. The channel is
+//! configured with HTTP/2 keepalive so that connections silently dropped by NATs or
+//! load balancers are detected, and is re-established with exponential backoff
+
+use crate::client::headers::HeaderChannel;
+use crate::client::height::HeightChannel;
+use crate::client::limits::{GrpcCompression, LimitedChannel};
+use crate::client::proxy::{Proxy, ProxyConnector};
+use crate::client::rate_limit::{RateLimit, RateLimitedChannel};
+use crate::client::Contact;
+use crate::error::CosmosGrpcError;
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::service_client::ServiceClient as TendermintServiceClient;
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetSyncingRequest;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::time::sleep;
+use tonic::transport::{Channel, Endpoint};
+
+/// The channel type handed to gRPC clients, instrumented when the metrics feature is enabled
+#[cfg(feature = "metrics")]
+type BaseChannel = crate::client::metrics::MeteredChannel;
+#[cfg(not(feature = "metrics"))]
+type BaseChannel = Channel;
+/// The channel a Contact makes requests through, see `Contact::grpc_channel`
+pub type GrpcChannel =
+    HeaderChannel<HeightChannel<RateLimitedChannel<LimitedChannel<BaseChannel>>>>;
+
+#[cfg(feature = "metrics")]
+fn base_channel(channel: Channel) -> BaseChannel {
+    crate::client::metrics::MeteredChannel::new(channel)
+}
+#[cfg(not(feature = "metrics"))]
+fn base_channel(channel: Channel) -> BaseChannel {
+    channel
+}
+
+/// Settings for the underlying gRPC connection of a Contact
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionConfig {
+    /// How often to send HTTP/2 keepalive pings, None disables keepalive
+    pub keepalive_interval: Option<Duration>,
+    /// How long to wait for a keepalive ping to be acknowledged before the connection is considered dead
+    pub keepalive_timeout: Duration,
+    /// How many times to try connecting before giving up
+    pub max_connect_attempts: u32,
+    /// The delay after the first failed connection attempt, doubled after every failure
+    pub initial_backoff: Duration,
+    /// The maximum delay between connection attempts
+    pub max_backoff: Duration,
+    /// The maximum number of requests in flight at once, further requests are queued
+    pub max_in_flight: Option<usize>,
+    /// Spaces requests out to stay under an endpoint's rate limit, requests over the
+    /// limit are queued until the bucket refills
+    pub rate_limit: Option<RateLimit>,
+    /// The largest gRPC response accepted in bytes, larger responses fail with a
+    /// `ResourceExhausted` status. None accepts responses of any size, tonic 0.7 does
+    /// not apply the 4MB default of newer versions, so large validator sets and wasm
+    /// state queries succeed without raising this
+    pub max_decoding_message_size: Option<usize>,
+    /// Whether the node is asked to compress responses
+    pub compression: GrpcCompression,
+    /// Connect through a SOCKS5 or HTTP proxy, TCP keepalive is not set on proxied
+    /// connections but HTTP/2 keepalive still applies
+    pub proxy: Option<Proxy>,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        ConnectionConfig {
+            keepalive_interval: Some(Duration::from_secs(30)),
+            keepalive_timeout: Duration::from_secs(10),
+            max_connect_attempts: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(8),
+            max_in_flight: None,
+            rate_limit: None,
+            max_decoding_message_size: None,
+            compression: GrpcCompression::Gzip,
+            proxy: None,
+        }
+    }
+}
+
+impl Contact {
+    /// Returns the shared gRPC channel, connecting if there is no channel yet
+    pub(crate) async fn get_channel(&self) -> Result<GrpcChannel, CosmosGrpcError> {
+        if let Some(channel) = self.channel.lock().unwrap().clone() {
+            return Ok(self.wrap_channel(channel));
+        }
+        let channel = self.connect_with_backoff().await?;
+        *self.channel.lock().unwrap() = Some(channel.clone());
+        Ok(self.wrap_channel(channel))
+    }
+
+    /// The gRPC channel of this Contact, for use with tonic clients generated for
+    /// modules this crate does not cover, for example
+    /// `QueryClient::new(contact.grpc_channel().await?)`. Requests share the connection
+    /// and are made at this Contact's height and under its rate limit, wrap calls in
+    /// `Contact::retry` to retry them like the built in queries
+    pub async fn grpc_channel(&self) -> Result<GrpcChannel, CosmosGrpcError> {
+        self.get_channel().await
+    }
+
+    fn wrap_channel(&self, channel: Channel) -> GrpcChannel {
+        // custom headers are applied first so a height set with at_height wins
+        HeaderChannel::new(
+            HeightChannel::new(
+                RateLimitedChannel::new(
+                    LimitedChannel::new(
+                        base_channel(channel),
+                        self.connection.max_decoding_message_size,
+                        self.connection.compression,
+                    ),
+                    self.rate_limiter.clone(),
+                ),
+                self.height,
+            ),
+            self.headers.clone(),
+        )
+    }
+
+    /// Drops the shared channel, the next request will establish a new connection
+    pub fn reset_connection(&self) {
+        *self.channel.lock().unwrap() = None;
+    }
+
+    /// The number of times this Contact (or any of it's clones) has had to re-establish
+    /// it's connection after the initial connection
+    pub fn get_reconnect_count(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    /// Checks that the node responds over the current connection, if it does not the
+    /// connection is re-established and checked again
+    pub async fn check_connection(&self) -> Result<(), CosmosGrpcError> {
+        if self.ping().await.is_ok() {
+            return Ok(());
+        }
+        warn!("Connection to {} is unhealthy, reconnecting", self.url);
+        self.reset_connection();
+        self.ping().await
+    }
+
+    async fn ping(&self) -> Result<(), CosmosGrpcError> {
+        let mut grpc = TendermintServiceClient::new(self.get_channel().await?);
+        grpc.get_syncing(GetSyncingRequest {}).await?;
+        Ok(())
+    }
+
+    fn endpoint(&self) -> Result<Endpoint, CosmosGrpcError> {
+        let mut endpoint = Endpoint::new(self.url.clone())?.connect_timeout(self.timeout);
+        if let Some(limit) = self.connection.max_in_flight {
+            endpoint = endpoint.concurrency_limit(limit.max(1));
+        }
+        if let Some(interval) = self.connection.keepalive_interval {
+            endpoint = endpoint
+                .http2_keep_alive_interval(interval)
+                .keep_alive_timeout(self.connection.keepalive_timeout)
+                .keep_alive_while_idle(true)
+                .tcp_keepalive(Some(interval));
+        }
+        Ok(endpoint)
+    }
+
+    async fn connect_with_backoff(&self) -> Result<Channel, CosmosGrpcError> {
+        let endpoint = self.endpoint()?;
+        let mut backoff = self.connection.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            let res = match &self.connection.proxy {
+                Some(proxy) => {
+                    endpoint
+                        .connect_with_connector(ProxyConnector::new(proxy.clone()))
+                        .await
+                }
+                None => endpoint.connect().await,
+            };
+            match res {
+                Ok(channel) => {
+                    if self.connected.swap(true, Ordering::Relaxed) {
+                        self.reconnects.fetch_add(1, Ordering::Relaxed);
+                        #[cfg(feature = "metrics")]
+                        crate::client::metrics::record_reconnect();
+                    }
+                    return Ok(channel);
+                }
+                Err(e) => {
+                    if attempt >= self.connection.max_connect_attempts {
+                        return Err(e.into());
+                    }
+                    warn!(
+                        "Failed to connect to {} attempt {} retrying in {}ms: {}",
+                        self.url,
+                        attempt,
+                        backoff.as_millis(),
+                        e
+                    );
+                    sleep(backoff).await;
+                    backoff = std::cmp::min(backoff * 2, self.connection.max_backoff);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[actix_rt::test]
+    async fn test_connect_backoff_gives_up() {
+        let contact = Contact::new("http://127.0.0.1:1", Duration::from_secs(1), "cosmos")
+            .unwrap()
+            .with_connection_config(ConnectionConfig {
+                max_connect_attempts: 3,
+                initial_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(15),
+                ..Default::default()
+            });
+        let start = Instant::now();
+        assert!(contact.check_connection().await.is_err());
+        // the check connects twice, each time backing off 10ms then 15ms
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(contact.get_reconnect_count(), 0);
+    }
+}
@@ -0,0 +1,139 @@
+//! A set of Contacts for several chains, keyed by chain id, with fan out helpers for
+//! tooling that needs the same information from every chain at once such as portfolio
+//! trackers and bridge relayers
+
+use crate::client::{ChainStatus, Contact};
+use crate::error::CosmosGrpcError;
+use crate::{Address, Coin};
+use futures_util::future::join_all;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// The default number of requests a ContactSet will have in flight at once
+pub const DEFAULT_MAX_CONCURRENT: usize = 16;
+
+/// Contacts for several chains keyed by chain id. Fan out operations run concurrently
+/// on the caller's runtime and share a single budget of in flight requests across all
+/// chains so that querying many chains does not open an unbounded number of requests
+#[derive(Clone)]
+pub struct ContactSet {
+    contacts: HashMap<String, Contact>,
+    budget: Arc<Semaphore>,
+}
+
+impl Default for ContactSet {
+    fn default() -> Self {
+        ContactSet::new(DEFAULT_MAX_CONCURRENT)
+    }
+}
+
+impl ContactSet {
+    /// Creates an empty set allowing at most `max_concurrent` fan out requests at once
+    pub fn new(max_concurrent: usize) -> Self {
+        ContactSet {
+            contacts: HashMap::new(),
+            budget: Arc::new(Semaphore::new(max_concurrent.max(1))),
+        }
+    }
+
+    /// Adds a Contact for the given chain id, returning the Contact it replaced if any
+    pub fn insert(&mut self, chain_id: impl Into<String>, contact: Contact) -> Option<Contact> {
+        self.contacts.insert(chain_id.into(), contact)
+    }
+
+    /// Adds a Contact using the chain id from its ChainConfig, or if that is not set
+    /// the chain id reported by the node
+    pub async fn add(&mut self, contact: Contact) -> Result<String, CosmosGrpcError> {
+        let chain_id = match &contact.get_config().chain_id {
+            Some(chain_id) => chain_id.clone(),
+            None => contact.get_chain_id().await?,
+        };
+        self.contacts.insert(chain_id.clone(), contact);
+        Ok(chain_id)
+    }
+
+    pub fn remove(&mut self, chain_id: &str) -> Option<Contact> {
+        self.contacts.remove(chain_id)
+    }
+
+    pub fn get(&self, chain_id: &str) -> Option<&Contact> {
+        self.contacts.get(chain_id)
+    }
+
+    pub fn chain_ids(&self) -> Vec<String> {
+        self.contacts.keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.contacts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contacts.is_empty()
+    }
+
+    /// Runs `f` against every Contact concurrently, within the request budget of this
+    /// set, and returns each chain's result keyed by chain id. One chain failing does
+    /// not affect the results of the others
+    pub async fn for_each<F, Fut, T>(&self, f: F) -> HashMap<String, Result<T, CosmosGrpcError>>
+    where
+        F: Fn(Contact) -> Fut,
+        Fut: Future<Output = Result<T, CosmosGrpcError>>,
+    {
+        let futures = self.contacts.iter().map(|(chain_id, contact)| {
+            let fut = f(contact.clone());
+            let budget = self.budget.clone();
+            async move {
+                // the semaphore is never closed so acquire can not fail
+                let _permit = budget.acquire().await.unwrap();
+                (chain_id.clone(), fut.await)
+            }
+        });
+        join_all(futures).await.into_iter().collect()
+    }
+
+    /// The balances of `address` on every chain, the address is encoded with each
+    /// chain's prefix. Note that chains using a different coin type, such as Ethermint
+    /// chains, derive different addresses from the same key
+    pub async fn get_balances(
+        &self,
+        address: Address,
+    ) -> HashMap<String, Result<Vec<Coin>, CosmosGrpcError>> {
+        self.for_each(|contact| async move { contact.get_balances(address).await })
+            .await
+    }
+
+    /// The status of every chain
+    pub async fn get_chain_statuses(
+        &self,
+    ) -> HashMap<String, Result<ChainStatus, CosmosGrpcError>> {
+        self.for_each(|contact| async move { contact.get_chain_status().await })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[actix_rt::test]
+    async fn test_contact_set() {
+        let mut set = ContactSet::new(2);
+        for (chain_id, prefix) in [("cosmoshub-4", "cosmos"), ("osmosis-1", "osmo")] {
+            let contact =
+                Contact::new("http://localhost:1", Duration::from_secs(1), prefix).unwrap();
+            assert!(set.insert(chain_id, contact).is_none());
+        }
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.get("osmosis-1").unwrap().get_prefix(), "osmo");
+
+        let prefixes = set
+            .for_each(|contact| async move { Ok(contact.get_prefix()) })
+            .await;
+        assert_eq!(prefixes["cosmoshub-4"].as_ref().unwrap(), "cosmos");
+        assert_eq!(prefixes["osmosis-1"].as_ref().unwrap(), "osmo");
+    }
+}
@@ -0,0 +1,251 @@
+//! The `CosmosClient` trait covers the core query and broadcast methods of Contact so
+//! that applications can be written generically over a real Contact, a MockContact in
+//! tests, or their own wrappers
+
+use super::types::{BaseAccount, ChainStatus, LatestBlock, TimeoutPolicy};
+use crate::error::CosmosGrpcError;
+use crate::{Address, Coin, Contact, Fee, MessageArgs, Msg, PrivateKey};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{BroadcastMode, SimulateResponse};
+use std::future::Future;
+use std::time::Duration;
+
+/// The core query and broadcast operations of a Cosmos client, see the methods of the
+/// same name on Contact for details. The async methods return `Send` futures so
+/// generic code can be spawned onto a multi threaded runtime, which also means the
+/// trait can not be used as `dyn CosmosClient`, take a generic `C: CosmosClient` instead
+pub trait CosmosClient {
+    /// The bech32 prefix of addresses on this chain
+    fn get_prefix(&self) -> String;
+
+    fn get_chain_status(&self)
+        -> impl Future<Output = Result<ChainStatus, CosmosGrpcError>> + Send;
+
+    fn get_latest_block(&self)
+        -> impl Future<Output = Result<LatestBlock, CosmosGrpcError>> + Send;
+
+    fn get_chain_id(&self) -> impl Future<Output = Result<String, CosmosGrpcError>> + Send;
+
+    fn get_account_info(
+        &self,
+        address: Address,
+    ) -> impl Future<Output = Result<BaseAccount, CosmosGrpcError>> + Send;
+
+    fn get_balances(
+        &self,
+        address: Address,
+    ) -> impl Future<Output = Result<Vec<Coin>, CosmosGrpcError>> + Send;
+
+    fn get_balance(
+        &self,
+        address: Address,
+        denom: String,
+    ) -> impl Future<Output = Result<Option<Coin>, CosmosGrpcError>> + Send;
+
+    fn get_message_args(
+        &self,
+        our_address: Address,
+        fee: Fee,
+    ) -> impl Future<Output = Result<MessageArgs, CosmosGrpcError>> + Send;
+
+    fn simulate_tx(
+        &self,
+        messages: &[Msg],
+        private_key: &PrivateKey,
+    ) -> impl Future<Output = Result<SimulateResponse, CosmosGrpcError>> + Send;
+
+    fn get_fee_info(
+        &self,
+        messages: &[Msg],
+        fee_token: &[Coin],
+        private_key: &PrivateKey,
+    ) -> impl Future<Output = Result<Fee, CosmosGrpcError>> + Send;
+
+    fn send_transaction(
+        &self,
+        msg: Vec<u8>,
+        mode: BroadcastMode,
+    ) -> impl Future<Output = Result<TxResponse, CosmosGrpcError>> + Send;
+
+    fn send_message(
+        &self,
+        messages: &[Msg],
+        memo: Option<String>,
+        fee_coin: &[Coin],
+        wait_timeout: Option<Duration>,
+        private_key: PrivateKey,
+    ) -> impl Future<Output = Result<TxResponse, CosmosGrpcError>> + Send;
+
+    fn send_coins(
+        &self,
+        coin: Coin,
+        fee_coin: Option<Coin>,
+        destination: Address,
+        wait_timeout: Option<Duration>,
+        private_key: PrivateKey,
+    ) -> impl Future<Output = Result<TxResponse, CosmosGrpcError>> + Send;
+
+    fn wait_for_tx(
+        &self,
+        txhash: &str,
+        timeout: TimeoutPolicy,
+    ) -> impl Future<Output = Result<TxResponse, CosmosGrpcError>> + Send;
+}
+
+/// Implements CosmosClient by forwarding to the inherent methods of the same name
+macro_rules! impl_cosmos_client {
+    ($client:ty) => {
+        impl CosmosClient for $client {
+            fn get_prefix(&self) -> String {
+                <$client>::get_prefix(self)
+            }
+
+            fn get_chain_status(
+                &self,
+            ) -> impl Future<Output = Result<ChainStatus, CosmosGrpcError>> + Send {
+                <$client>::get_chain_status(self)
+            }
+
+            fn get_latest_block(
+                &self,
+            ) -> impl Future<Output = Result<LatestBlock, CosmosGrpcError>> + Send {
+                <$client>::get_latest_block(self)
+            }
+
+            fn get_chain_id(&self) -> impl Future<Output = Result<String, CosmosGrpcError>> + Send {
+                <$client>::get_chain_id(self)
+            }
+
+            fn get_account_info(
+                &self,
+                address: Address,
+            ) -> impl Future<Output = Result<BaseAccount, CosmosGrpcError>> + Send {
+                <$client>::get_account_info(self, address)
+            }
+
+            fn get_balances(
+                &self,
+                address: Address,
+            ) -> impl Future<Output = Result<Vec<Coin>, CosmosGrpcError>> + Send {
+                <$client>::get_balances(self, address)
+            }
+
+            fn get_balance(
+                &self,
+                address: Address,
+                denom: String,
+            ) -> impl Future<Output = Result<Option<Coin>, CosmosGrpcError>> + Send {
+                <$client>::get_balance(self, address, denom)
+            }
+
+            fn get_message_args(
+                &self,
+                our_address: Address,
+                fee: Fee,
+            ) -> impl Future<Output = Result<MessageArgs, CosmosGrpcError>> + Send {
+                <$client>::get_message_args(self, our_address, fee)
+            }
+
+            fn simulate_tx(
+                &self,
+                messages: &[Msg],
+                private_key: &PrivateKey,
+            ) -> impl Future<Output = Result<SimulateResponse, CosmosGrpcError>> + Send {
+                <$client>::simulate_tx(self, messages, private_key)
+            }
+
+            fn get_fee_info(
+                &self,
+                messages: &[Msg],
+                fee_token: &[Coin],
+                private_key: &PrivateKey,
+            ) -> impl Future<Output = Result<Fee, CosmosGrpcError>> + Send {
+                <$client>::get_fee_info(self, messages, fee_token, private_key)
+            }
+
+            fn send_transaction(
+                &self,
+                msg: Vec<u8>,
+                mode: BroadcastMode,
+            ) -> impl Future<Output = Result<TxResponse, CosmosGrpcError>> + Send {
+                <$client>::send_transaction(self, msg, mode)
+            }
+
+            fn send_message(
+                &self,
+                messages: &[Msg],
+                memo: Option<String>,
+                fee_coin: &[Coin],
+                wait_timeout: Option<Duration>,
+                private_key: PrivateKey,
+            ) -> impl Future<Output = Result<TxResponse, CosmosGrpcError>> + Send {
+                <$client>::send_message(self, messages, memo, fee_coin, wait_timeout, private_key)
+            }
+
+            fn send_coins(
+                &self,
+                coin: Coin,
+                fee_coin: Option<Coin>,
+                destination: Address,
+                wait_timeout: Option<Duration>,
+                private_key: PrivateKey,
+            ) -> impl Future<Output = Result<TxResponse, CosmosGrpcError>> + Send {
+                <$client>::send_coins(self, coin, fee_coin, destination, wait_timeout, private_key)
+            }
+
+            fn wait_for_tx(
+                &self,
+                txhash: &str,
+                timeout: TimeoutPolicy,
+            ) -> impl Future<Output = Result<TxResponse, CosmosGrpcError>> + Send {
+                <$client>::wait_for_tx(self, txhash, timeout)
+            }
+        }
+    };
+}
+
+impl_cosmos_client!(Contact);
+#[cfg(feature = "testing")]
+impl_cosmos_client!(super::MockContact);
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::client::MockContact;
+
+    /// Generic application code, as a downstream crate would write it
+    async fn total_of<C: CosmosClient>(
+        client: &C,
+        address: Address,
+        denom: &str,
+    ) -> Result<Coin, CosmosGrpcError> {
+        let balance = client.get_balance(address, denom.to_string()).await?;
+        Ok(balance.unwrap_or(Coin {
+            amount: crate::Uint256::zero(),
+            denom: denom.to_string(),
+        }))
+    }
+
+    fn assert_send<T: Send>(_: T) {}
+
+    #[actix_rt::test]
+    async fn test_generic_client() {
+        let mock = MockContact::new("cosmos");
+        let address = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"")
+            .unwrap()
+            .to_address("cosmos")
+            .unwrap();
+        mock.set_balances(address, vec!["5uatom".parse().unwrap()]);
+        assert_eq!(
+            total_of(&mock, address, "uatom").await.unwrap(),
+            "5uatom".parse().unwrap()
+        );
+        assert_eq!(
+            total_of(&mock, address, "ufoo").await.unwrap(),
+            "0ufoo".parse().unwrap()
+        );
+
+        let contact = Contact::new("http://127.0.0.1:1", Duration::from_secs(1), "cosmos").unwrap();
+        assert_send(total_of(&contact, address, "uatom"));
+    }
+}
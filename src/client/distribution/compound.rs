@@ -0,0 +1,168 @@
+//! Auto compounding, claiming staking rewards and delegating them back in one transaction
+
+use crate::error::CosmosGrpcError;
+use crate::Uint256;
+use crate::{Coin, Contact, DecCoin, Msg, PrivateKey};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::MsgWithdrawDelegatorReward;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::MsgDelegate;
+use std::cmp::min;
+use std::time::Duration;
+
+/// The fee to pay for a compounding transaction and the balance to leave spendable
+/// afterwards, so that the account can still pay for its next transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompoundFeePolicy {
+    pub fee: Coin,
+    /// The balance of the bond denom that must remain spendable once the fee is paid and
+    /// the rewards are delegated, less of the rewards are restaked to keep it
+    pub reserve: Uint256,
+}
+
+/// Decides how much of each validator's reward to delegate back to it. Rewards are
+/// restaked in full unless the spendable balance after the fee would drop below the
+/// reserve, in which case the shortfall is kept back from the last validators
+fn plan_compound(
+    rewards: Vec<(String, Uint256)>,
+    balance: Uint256,
+    fee: Uint256,
+    reserve: Uint256,
+) -> Vec<(String, Uint256)> {
+    let total = rewards
+        .iter()
+        .fold(Uint256::zero(), |acc, (_, v)| acc.checked_add(*v).unwrap());
+    // once the rewards are withdrawn the account holds balance + total, the fee and
+    // the reserve come out of that before anything is delegated
+    let available = balance
+        .checked_add(total)
+        .and_then(|v| v.checked_sub(fee))
+        .and_then(|v| v.checked_sub(reserve))
+        .unwrap_or_else(Uint256::zero);
+    let mut remaining = min(total, available);
+    let mut plan = Vec::new();
+    for (validator, reward) in rewards {
+        let amount = min(reward, remaining);
+        if amount == Uint256::zero() {
+            continue;
+        }
+        remaining = remaining.checked_sub(amount).unwrap();
+        plan.push((validator, amount));
+    }
+    plan
+}
+
+impl Contact {
+    /// Withdraws the staking rewards of every delegation whose validator passes
+    /// `validator_filter` and delegates them back to the same validator in a single
+    /// transaction. Only rewards in the bond denom are restaked, and only as much as
+    /// keeps `fee_policy.reserve` spendable after the fee. Returns None without sending
+    /// anything when there is nothing to restake
+    pub async fn compound_rewards(
+        &self,
+        private_key: PrivateKey,
+        validator_filter: impl Fn(&str) -> bool,
+        fee_policy: CompoundFeePolicy,
+        wait_timeout: Option<Duration>,
+    ) -> Result<Option<TxResponse>, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
+        let (params, rewards) = tokio::try_join!(
+            self.get_staking_params(),
+            self.get_delegation_summary(our_address)
+        )?;
+        let bond_denom = params.bond_denom;
+        let balance = self
+            .get_balance(our_address, bond_denom.clone())
+            .await?
+            .map(|c| c.amount)
+            .unwrap_or_else(Uint256::zero);
+
+        let mut withdrawals = Vec::new();
+        let mut restakeable = Vec::new();
+        for reward in rewards.rewards {
+            if !validator_filter(&reward.validator_address) {
+                continue;
+            }
+            withdrawals.push(reward.validator_address.clone());
+            for coin in reward.reward {
+                if coin.denom != bond_denom {
+                    continue;
+                }
+                let coin = DecCoin::try_from(coin)
+                    .map_err(|e| CosmosGrpcError::BadResponse(e.to_string()))?
+                    .truncate();
+                restakeable.push((reward.validator_address.clone(), coin.amount));
+            }
+        }
+        let fee = if fee_policy.fee.denom == bond_denom {
+            fee_policy.fee.amount
+        } else {
+            Uint256::zero()
+        };
+        let plan = plan_compound(restakeable, balance, fee, fee_policy.reserve);
+        if plan.is_empty() {
+            return Ok(None);
+        }
+
+        let mut msgs = Vec::new();
+        for validator_address in withdrawals {
+            let msg = MsgWithdrawDelegatorReward {
+                delegator_address: our_address.to_string(),
+                validator_address,
+            };
+            msgs.push(Msg::new(
+                "/cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward",
+                msg,
+            ));
+        }
+        for (validator_address, amount) in plan {
+            let msg = MsgDelegate {
+                delegator_address: our_address.to_string(),
+                validator_address,
+                amount: Some(
+                    Coin {
+                        amount,
+                        denom: bond_denom.clone(),
+                    }
+                    .into(),
+                ),
+            };
+            msgs.push(Msg::new("/cosmos.staking.v1beta1.MsgDelegate", msg));
+        }
+
+        let res = self
+            .send_message(&msgs, None, &[fee_policy.fee], wait_timeout, private_key)
+            .await?;
+        Ok(Some(res))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amounts(plan: Vec<(String, Uint256)>) -> Vec<(String, u128)> {
+        plan.into_iter()
+            .map(|(v, a)| (v, a.try_resize_to_u128().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_compound() {
+        let u = Uint256::from_u128;
+        let rewards = vec![("val1".to_string(), u(100)), ("val2".to_string(), u(50))];
+
+        // plenty of balance, everything is restaked
+        let plan = plan_compound(rewards.clone(), u(1000), u(10), u(100));
+        assert_eq!(
+            amounts(plan),
+            vec![("val1".to_string(), 100), ("val2".to_string(), 50)]
+        );
+
+        // 20 + 150 - 10 - 100 leaves 60 to restake
+        let plan = plan_compound(rewards.clone(), u(20), u(10), u(100));
+        assert_eq!(amounts(plan), vec![("val1".to_string(), 60)]);
+
+        // the rewards don't even cover the fee and reserve
+        assert!(plan_compound(rewards, u(0), u(100), u(100)).is_empty());
+    }
+}
@@ -2,14 +2,14 @@
 //! including the community pool
 
 use super::{ChainStatus, PAGE};
-use crate::error::CosmosGrpcError;
+use crate::error::{CoinError, CosmosGrpcError};
 use crate::Uint256;
-use crate::{Address, Coin, Contact, Msg, PrivateKey};
+use crate::{Address, Coin, Contact, DecCoin, Msg, PrivateKey};
 use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
-use cosmos_sdk_proto::cosmos::base::v1beta1::DecCoin;
+use cosmos_sdk_proto::cosmos::base::v1beta1::DecCoin as ProtoDecCoin;
 use cosmos_sdk_proto::cosmos::distribution::v1beta1::query_client::QueryClient as DistQueryClient;
 use cosmos_sdk_proto::cosmos::distribution::v1beta1::{
-    MsgFundCommunityPool, QueryValidatorSlashesRequest,
+    DelegationDelegatorReward, MsgFundCommunityPool, QueryValidatorSlashesRequest,
 };
 use cosmos_sdk_proto::cosmos::distribution::v1beta1::{
     MsgWithdrawDelegatorReward, ValidatorSlashEvent,
@@ -17,6 +17,7 @@ use cosmos_sdk_proto::cosmos::distribution::v1beta1::{
 use cosmos_sdk_proto::cosmos::distribution::v1beta1::{
     MsgWithdrawValidatorCommission, QueryDelegationRewardsRequest,
 };
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::{Params, QueryParamsRequest};
 use cosmos_sdk_proto::cosmos::distribution::v1beta1::{
     QueryCommunityPoolRequest, QueryDelegationTotalRewardsRequest,
 };
@@ -25,31 +26,95 @@ use cosmos_sdk_proto::cosmos::distribution::v1beta1::{
 };
 use std::time::Duration;
 
+pub mod compound;
+
 // required because dec coins are multiplied by 1*10^18
 const ONE_ETH: Uint256 = Uint256::from_u128(10u128.pow(18));
 
+/// The most reward withdrawals `withdraw_all_rewards` puts in a single transaction, keeps
+/// the gas of each transaction well under the block gas limit of most chains
+pub const WITHDRAW_REWARDS_BATCH_SIZE: usize = 20;
+
+/// The validators of the delegations whose reward in the threshold denom is at least
+/// the threshold amount, rewards are truncated to whole coins before comparing
+fn validators_above_threshold(
+    rewards: Vec<DelegationDelegatorReward>,
+    threshold: &Coin,
+) -> Result<Vec<String>, CoinError> {
+    let mut validators = Vec::new();
+    for delegation in rewards {
+        for reward in delegation.reward {
+            let reward = DecCoin::try_from(reward)?.truncate();
+            if reward.denom == threshold.denom && reward.amount >= threshold.amount {
+                validators.push(delegation.validator_address);
+                break;
+            }
+        }
+    }
+    Ok(validators)
+}
+
 impl Contact {
+    /// Gets the coins in the community pool at full precision
+    pub async fn get_community_pool(&self) -> Result<Vec<DecCoin>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = DistQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc.community_pool(QueryCommunityPoolRequest {}).await?;
+            res.into_inner()
+                .pool
+                .into_iter()
+                .map(|v| {
+                    v.try_into()
+                        .map_err(|e: CoinError| CosmosGrpcError::BadResponse(e.to_string()))
+                })
+                .collect()
+        })
+        .await
+    }
+
     /// Gets a list of coins in the community pool, note returned values from this endpoint
     /// are in DecCoins for precision, for the sake of ease of use this endpoint converts them
     /// into their normal form, for easy comparison against any other coin or amount.
     pub async fn query_community_pool(&self) -> Result<Vec<Coin>, CosmosGrpcError> {
-        let mut grpc = DistQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = grpc.community_pool(QueryCommunityPoolRequest {}).await?;
-        let val = res.into_inner().pool;
-        let mut res = Vec::new();
-        for v in val {
-            let parse_result = Uint256::from_dec_or_hex_str_restricted(&v.amount);
-            match parse_result {
-                Ok(parse_result) => res.push(Coin {
-                    denom: v.denom,
-                    amount: parse_result.divide(ONE_ETH).unwrap().0,
-                }),
-                Err(e) => return Err(CosmosGrpcError::ParseError { error: e }),
+        self.retry(|| async {
+            let mut grpc = DistQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc.community_pool(QueryCommunityPoolRequest {}).await?;
+            let val = res.into_inner().pool;
+            let mut res = Vec::new();
+            for v in val {
+                let parse_result = Uint256::from_dec_or_hex_str_restricted(&v.amount);
+                match parse_result {
+                    Ok(parse_result) => res.push(Coin {
+                        denom: v.denom,
+                        amount: parse_result.divide(ONE_ETH).unwrap().0,
+                    }),
+                    Err(e) => return Err(CosmosGrpcError::ParseError { error: e }),
+                }
             }
-        }
-        Ok(res)
+            Ok(res)
+        })
+        .await
+    }
+
+    /// Gets the parameters of the distribution module, including the community tax
+    pub async fn get_distribution_params(&self) -> Result<Params, CosmosGrpcError> {
+        self.cached("distribution_params", || {
+            self.retry(|| async {
+                let mut grpc = DistQueryClient::new(self.get_channel().await?).accept_gzip();
+                let res = grpc
+                    .params(QueryParamsRequest {})
+                    .await?
+                    .into_inner()
+                    .params;
+                match res {
+                    Some(params) => Ok(params),
+                    None => Err(CosmosGrpcError::BadResponse(
+                        "No distribution params returned".to_string(),
+                    )),
+                }
+            })
+        })
+        .await
     }
 
     /// Gets the slashing events of a validator starting from Genesis to the current block height
@@ -57,25 +122,26 @@ impl Contact {
         &self,
         validator_address: impl ToString,
     ) -> Result<Vec<ValidatorSlashEvent>, CosmosGrpcError> {
-        let mut grpc = DistQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let current_block = self.get_chain_status().await?;
-        let current_block = match current_block {
-            ChainStatus::Moving { block_height } => block_height,
-            _ => return Err(CosmosGrpcError::ChainNotRunning),
-        };
+        self.retry(|| async {
+            let mut grpc = DistQueryClient::new(self.get_channel().await?).accept_gzip();
+            let current_block = self.get_chain_status().await?;
+            let current_block = match current_block {
+                ChainStatus::Moving { block_height } => block_height,
+                _ => return Err(CosmosGrpcError::ChainNotRunning),
+            };
 
-        let res = grpc
-            .validator_slashes(QueryValidatorSlashesRequest {
-                validator_address: validator_address.to_string(),
-                starting_height: 0,
-                ending_height: current_block,
-                pagination: PAGE,
-            })
-            .await?
-            .into_inner();
-        Ok(res.slashes)
+            let res = grpc
+                .validator_slashes(QueryValidatorSlashesRequest {
+                    validator_address: validator_address.to_string(),
+                    starting_height: 0,
+                    ending_height: current_block,
+                    pagination: PAGE,
+                })
+                .await?
+                .into_inner();
+            Ok(res.slashes)
+        })
+        .await
     }
 
     /// Withdraws rewards for the specified delegator to the specified validator
@@ -86,7 +152,7 @@ impl Contact {
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
         let msg = MsgWithdrawDelegatorReward {
             delegator_address: our_address.to_string(),
             validator_address: validator_address.to_string(),
@@ -105,16 +171,17 @@ impl Contact {
         &self,
         delegator_address: Address,
     ) -> Result<Vec<String>, CosmosGrpcError> {
-        let mut grpc = DistQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = grpc
-            .delegator_validators(QueryDelegatorValidatorsRequest {
-                delegator_address: delegator_address.to_string(),
-            })
-            .await?
-            .into_inner();
-        Ok(res.validators)
+        self.retry(|| async {
+            let mut grpc = DistQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .delegator_validators(QueryDelegatorValidatorsRequest {
+                    delegator_address: delegator_address.to_string(),
+                })
+                .await?
+                .into_inner();
+            Ok(res.validators)
+        })
+        .await
     }
 
     /// gets the rewards for a specific delegation between a single delegator and validator
@@ -122,19 +189,20 @@ impl Contact {
         &self,
         delegator_address: Address,
         validator_address: Address,
-    ) -> Result<Vec<DecCoin>, CosmosGrpcError> {
-        let mut grpc = DistQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = grpc
-            .delegation_rewards(QueryDelegationRewardsRequest {
-                delegator_address: delegator_address.to_string(),
-                validator_address: validator_address.to_string(),
-            })
-            .await?
-            .into_inner()
-            .rewards;
-        Ok(res)
+    ) -> Result<Vec<ProtoDecCoin>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = DistQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .delegation_rewards(QueryDelegationRewardsRequest {
+                    delegator_address: delegator_address.to_string(),
+                    validator_address: validator_address.to_string(),
+                })
+                .await?
+                .into_inner()
+                .rewards;
+            Ok(res)
+        })
+        .await
     }
 
     /// gets the rewards for a specific delegation between a single delegator and validator
@@ -142,16 +210,17 @@ impl Contact {
         &self,
         delegator_address: Address,
     ) -> Result<QueryDelegationTotalRewardsResponse, CosmosGrpcError> {
-        let mut grpc = DistQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = grpc
-            .delegation_total_rewards(QueryDelegationTotalRewardsRequest {
-                delegator_address: delegator_address.to_string(),
-            })
-            .await?
-            .into_inner();
-        Ok(res)
+        self.retry(|| async {
+            let mut grpc = DistQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .delegation_total_rewards(QueryDelegationTotalRewardsRequest {
+                    delegator_address: delegator_address.to_string(),
+                })
+                .await?
+                .into_inner();
+            Ok(res)
+        })
+        .await
     }
 
     /// Withdraws all rewards for the specified delegator across all validators they have
@@ -162,7 +231,7 @@ impl Contact {
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
 
         let delegated = self.query_delegator_validators(our_address).await?;
 
@@ -184,6 +253,54 @@ impl Contact {
             .await
     }
 
+    /// Withdraws the rewards of every delegation with at least `min_reward_threshold` in
+    /// rewards, skipping dust that is not worth the fee. The withdrawals are split into
+    /// transactions of at most `WITHDRAW_REWARDS_BATCH_SIZE` messages which are broadcast
+    /// one after another, each waiting up to `wait_timeout` to be included so the next
+    /// one is signed with the correct sequence. Returns the response of every transaction,
+    /// an empty list if no delegation met the threshold
+    pub async fn withdraw_all_rewards(
+        &self,
+        private_key: PrivateKey,
+        min_reward_threshold: Coin,
+        fee: Coin,
+        wait_timeout: Duration,
+    ) -> Result<Vec<TxResponse>, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
+        let rewards = self.query_all_delegation_rewards(our_address).await?;
+        let validators = validators_above_threshold(rewards.rewards, &min_reward_threshold)
+            .map_err(|e| CosmosGrpcError::BadResponse(e.to_string()))?;
+
+        let msgs: Vec<Msg> = validators
+            .into_iter()
+            .map(|validator_address| {
+                let msg = MsgWithdrawDelegatorReward {
+                    delegator_address: our_address.to_string(),
+                    validator_address,
+                };
+                Msg::new(
+                    "/cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward",
+                    msg,
+                )
+            })
+            .collect();
+
+        let mut responses = Vec::new();
+        for batch in msgs.chunks(WITHDRAW_REWARDS_BATCH_SIZE) {
+            let res = self
+                .send_message(
+                    batch,
+                    None,
+                    std::slice::from_ref(&fee),
+                    Some(wait_timeout),
+                    private_key.clone(),
+                )
+                .await?;
+            responses.push(res);
+        }
+        Ok(responses)
+    }
+
     /// Withdraws commission from the provided validator
     pub async fn withdraw_validator_commission(
         &self,
@@ -212,7 +329,7 @@ impl Contact {
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
         let msg = MsgFundCommunityPool {
             amount: amount.into_iter().map(|a| a.into()).collect(),
             depositor: our_address.to_string(),
@@ -223,3 +340,48 @@ impl Contact {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reward(validator: &str, amounts: &[(&str, &str)]) -> DelegationDelegatorReward {
+        DelegationDelegatorReward {
+            validator_address: validator.to_string(),
+            reward: amounts
+                .iter()
+                .map(|(amount, denom)| ProtoDecCoin {
+                    amount: amount.to_string(),
+                    denom: denom.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_validators_above_threshold() {
+        // dec coin amounts are scaled by 10^18, these are 1.5, 0.9 and 2 uatom
+        let rewards = vec![
+            reward("valoper1", &[("1500000000000000000", "uatom")]),
+            reward(
+                "valoper2",
+                &[
+                    ("900000000000000000", "uatom"),
+                    ("5000000000000000000", "ufoo"),
+                ],
+            ),
+            reward("valoper3", &[("2000000000000000000", "uatom")]),
+            reward("valoper4", &[]),
+        ];
+        let threshold: Coin = "1uatom".parse().unwrap();
+        assert_eq!(
+            validators_above_threshold(rewards.clone(), &threshold).unwrap(),
+            vec!["valoper1", "valoper3"]
+        );
+        let threshold: Coin = "2uatom".parse().unwrap();
+        assert_eq!(
+            validators_above_threshold(rewards, &threshold).unwrap(),
+            vec!["valoper3"]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Queries for the epochs module, which runs hooks such as Osmosis rewards distribution
+//! or Stride staking on a fixed wall clock schedule rather than at block heights. The
+//! module is part of the Cosmos SDK since 0.53 and was previously maintained by
+//! Osmosis, both serve the same messages under different packages
+
+use crate::error::CosmosGrpcError;
+use crate::Contact;
+use prost_types::{Duration as ProtoDuration, Timestamp};
+use std::time::{Duration, SystemTime};
+use tonic::Code as GrpcCode;
+
+/// The EpochInfos query of the Cosmos SDK epochs module
+const COSMOS_EPOCH_INFOS_PATH: &str = "/cosmos.epochs.v1beta1.Query/EpochInfos";
+/// The EpochInfos query of the Osmosis epochs module, used by chains that adopted it
+/// before it moved into the Cosmos SDK
+const OSMOSIS_EPOCH_INFOS_PATH: &str = "/osmosis.epochs.v1beta1.Query/EpochInfos";
+
+/// The schedule and progress of one epoch, such as `day` or `week`
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EpochInfo {
+    #[prost(string, tag = "1")]
+    pub identifier: String,
+    /// The time the first epoch starts
+    #[prost(message, optional, tag = "2")]
+    pub start_time: Option<Timestamp>,
+    #[prost(message, optional, tag = "3")]
+    pub duration: Option<ProtoDuration>,
+    #[prost(int64, tag = "4")]
+    pub current_epoch: i64,
+    #[prost(message, optional, tag = "5")]
+    pub current_epoch_start_time: Option<Timestamp>,
+    /// False until the first epoch has started
+    #[prost(bool, tag = "6")]
+    pub epoch_counting_started: bool,
+    #[prost(int64, tag = "8")]
+    pub current_epoch_start_height: i64,
+}
+
+impl EpochInfo {
+    pub fn epoch_duration(&self) -> Option<Duration> {
+        self.duration
+            .clone()
+            .and_then(|d| Duration::try_from(d).ok())
+    }
+
+    /// The earliest time the next epoch can start. The epoch ends in the first block
+    /// at or after this time, so it may end up to a block time later
+    pub fn next_epoch_start(&self) -> Option<SystemTime> {
+        let start = if self.epoch_counting_started {
+            self.current_epoch_start_time.clone()?
+        } else {
+            return SystemTime::try_from(self.start_time.clone()?).ok();
+        };
+        Some(SystemTime::try_from(start).ok()? + self.epoch_duration()?)
+    }
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryEpochsInfoRequest {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryEpochsInfoResponse {
+    #[prost(message, repeated, tag = "1")]
+    epochs: Vec<EpochInfo>,
+}
+
+impl Contact {
+    /// Gets every epoch of the chain's epochs module, trying the Cosmos SDK module
+    /// first and the Osmosis module second. Fails with an Unimplemented request error
+    /// on chains without either
+    pub async fn get_epoch_infos(&self) -> Result<Vec<EpochInfo>, CosmosGrpcError> {
+        let res: Result<QueryEpochsInfoResponse, CosmosGrpcError> = self
+            .query_raw(COSMOS_EPOCH_INFOS_PATH, QueryEpochsInfoRequest {})
+            .await;
+        let res = match res {
+            Err(CosmosGrpcError::RequestError { error })
+                if error.code() == GrpcCode::Unimplemented =>
+            {
+                self.query_raw(OSMOSIS_EPOCH_INFOS_PATH, QueryEpochsInfoRequest {})
+                    .await?
+            }
+            res => res?,
+        };
+        Ok(res.epochs)
+    }
+
+    /// Gets one epoch by identifier, None if the chain has no such epoch
+    pub async fn get_epoch_info(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<EpochInfo>, CosmosGrpcError> {
+        Ok(self
+            .get_epoch_infos()
+            .await?
+            .into_iter()
+            .find(|e| e.identifier == identifier))
+    }
+
+    /// Estimates the height of the first block of the next epoch, None if the chain
+    /// has no such epoch
+    pub async fn estimate_next_epoch_height(
+        &self,
+        identifier: &str,
+    ) -> Result<Option<u64>, CosmosGrpcError> {
+        let start = match self.get_epoch_info(identifier).await? {
+            Some(epoch) => epoch.next_epoch_start().ok_or_else(|| {
+                CosmosGrpcError::BadResponse(format!("Epoch {} has no schedule", identifier))
+            })?,
+            None => return Ok(None),
+        };
+        Ok(Some(self.estimate_block_height_at(start).await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_next_epoch_start() {
+        let mut epoch = EpochInfo {
+            identifier: "day".to_string(),
+            start_time: Some(Timestamp {
+                seconds: 1_000,
+                nanos: 0,
+            }),
+            duration: Some(ProtoDuration {
+                seconds: 86_400,
+                nanos: 0,
+            }),
+            current_epoch: 0,
+            current_epoch_start_time: None,
+            epoch_counting_started: false,
+            current_epoch_start_height: 0,
+        };
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(secs);
+        assert_eq!(epoch.next_epoch_start(), Some(at(1_000)));
+
+        epoch.epoch_counting_started = true;
+        epoch.current_epoch = 3;
+        epoch.current_epoch_start_time = Some(Timestamp {
+            seconds: 200_000,
+            nanos: 0,
+        });
+        assert_eq!(epoch.next_epoch_start(), Some(at(286_400)));
+        epoch.duration = None;
+        assert_eq!(epoch.next_epoch_start(), None);
+    }
+}
@@ -0,0 +1,220 @@
+//! Paying fees in tokens other than the chain's native denom, through the Osmosis
+//! x/txfees module or the FeeAbs x/feeabs module. Enabled with the `fee-abstraction`
+//! feature
+//!
+//! Both modules accept a fee in an alternative denom if it is worth at least the native
+//! fee at the module's price, so the fee is converted at that price and rounded up
+
+use crate::coin::DEC_COIN_PRECISION;
+use crate::error::CosmosGrpcError;
+use crate::{Address, Coin, Contact, Uint256};
+
+pub(crate) mod proto;
+
+use proto::*;
+
+/// Status of a FeeAbs host chain config whose price is being kept up to date
+const FEEABS_STATUS_UPDATED: i32 = 0;
+
+/// The fee abstraction modules that are supported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeAbstractionModule {
+    /// Osmosis x/txfees, fee tokens are priced from their Osmosis pool
+    OsmosisTxFees,
+    /// FeeAbs x/feeabs, IBC tokens are priced from an Osmosis TWAP
+    FeeAbs,
+}
+
+/// An accepted fee denom and its price in the native denom
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeTokenPrice {
+    pub denom: String,
+    /// The amount of the native denom one unit of `denom` is worth, scaled by 10^18
+    pub price: Uint256,
+}
+
+impl FeeTokenPrice {
+    /// Converts a fee in the native denom into this denom, rounding up. None if the
+    /// price is zero
+    pub fn convert(&self, native_fee: &Uint256) -> Option<Coin> {
+        let scale = Uint256::from_u128(10u128.pow(DEC_COIN_PRECISION));
+        let scaled = native_fee.checked_mul(scale)?;
+        let (amount, remainder) = scaled.divide(self.price)?;
+        let amount = if remainder == Uint256::zero() {
+            amount
+        } else {
+            amount.checked_add(Uint256::from_u64(1))?
+        };
+        Some(Coin {
+            amount,
+            denom: self.denom.clone(),
+        })
+    }
+}
+
+/// Parses an sdk.Dec as returned over gRPC, an integer scaled by 10^18, also accepting
+/// the decimal point form used in JSON
+fn parse_dec(value: &str) -> Result<Uint256, CosmosGrpcError> {
+    let bad = || CosmosGrpcError::BadResponse(format!("Invalid price {}", value));
+    let digits = match value.split_once('.') {
+        Some((whole, fraction)) if fraction.len() <= DEC_COIN_PRECISION as usize => format!(
+            "{}{:0<width$}",
+            whole,
+            fraction,
+            width = DEC_COIN_PRECISION as usize
+        ),
+        Some(_) => return Err(bad()),
+        None => value.to_string(),
+    };
+    Uint256::from_dec_or_hex_str_restricted(&digits).map_err(|_| bad())
+}
+
+impl Contact {
+    /// Gets the alternative fee denoms the chain accepts along with their prices,
+    /// FeeAbs tokens whose price is outdated or frozen are left out
+    pub async fn get_fee_token_prices(
+        &self,
+        module: FeeAbstractionModule,
+    ) -> Result<Vec<FeeTokenPrice>, CosmosGrpcError> {
+        let mut prices = Vec::new();
+        match module {
+            FeeAbstractionModule::OsmosisTxFees => {
+                let res: QueryFeeTokensResponse = self
+                    .query_raw(
+                        "/osmosis.txfees.v1beta1.Query/FeeTokens",
+                        QueryFeeTokensRequest {},
+                    )
+                    .await?;
+                for token in res.fee_tokens {
+                    let price: QueryDenomSpotPriceResponse = self
+                        .query_raw(
+                            "/osmosis.txfees.v1beta1.Query/DenomSpotPrice",
+                            QueryDenomSpotPriceRequest {
+                                denom: token.denom.clone(),
+                            },
+                        )
+                        .await?;
+                    prices.push(FeeTokenPrice {
+                        denom: token.denom,
+                        price: parse_dec(&price.spot_price)?,
+                    });
+                }
+            }
+            FeeAbstractionModule::FeeAbs => {
+                let res: QueryAllHostChainConfigResponse = self
+                    .query_raw(
+                        "/feeabstraction.feeabs.v1beta1.Query/AllHostChainConfig",
+                        QueryAllHostChainConfigRequest {},
+                    )
+                    .await?;
+                for config in res.all_host_chain_config {
+                    if config.status != FEEABS_STATUS_UPDATED {
+                        continue;
+                    }
+                    let twap: QueryOsmosisArithmeticTwapResponse = self
+                        .query_raw(
+                            "/feeabstraction.feeabs.v1beta1.Query/OsmosisArithmeticTwap",
+                            QueryOsmosisArithmeticTwapRequest {
+                                ibc_denom: config.ibc_denom.clone(),
+                            },
+                        )
+                        .await?;
+                    prices.push(FeeTokenPrice {
+                        denom: config.ibc_denom,
+                        price: parse_dec(&twap.arithmetic_twap)?,
+                    });
+                }
+            }
+        }
+        Ok(prices)
+    }
+
+    /// Picks a fee `address` can pay. The native fee is used if the balance covers it,
+    /// otherwise the first accepted denom whose converted fee the balance covers. Falls
+    /// back to the native fee when no denom is affordable or the chain does not run
+    /// `module`, leaving the chain to report the shortfall
+    pub async fn get_payable_fee(
+        &self,
+        module: FeeAbstractionModule,
+        native_fee: Coin,
+        address: Address,
+    ) -> Result<Coin, CosmosGrpcError> {
+        let balances = self.get_balances(address).await?;
+        let balance_of = |denom: &str| {
+            balances
+                .iter()
+                .find(|c| c.denom == denom)
+                .map(|c| c.amount)
+                .unwrap_or_else(Uint256::zero)
+        };
+        if balance_of(&native_fee.denom) >= native_fee.amount {
+            return Ok(native_fee);
+        }
+        let prices = match self.get_fee_token_prices(module).await {
+            Ok(prices) => prices,
+            Err(e) => {
+                warn!("Could not get fee token prices, using the native fee {}", e);
+                return Ok(native_fee);
+            }
+        };
+        Ok(pick_fee(&prices, native_fee, balance_of))
+    }
+}
+
+/// The first converted fee the balance covers, or the native fee
+fn pick_fee(
+    prices: &[FeeTokenPrice],
+    native_fee: Coin,
+    balance_of: impl Fn(&str) -> Uint256,
+) -> Coin {
+    prices
+        .iter()
+        .filter_map(|price| price.convert(&native_fee.amount))
+        .find(|fee| balance_of(&fee.denom) >= fee.amount)
+        .unwrap_or(native_fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_conversion() {
+        assert_eq!(
+            parse_dec("2.5").unwrap(),
+            parse_dec("2500000000000000000").unwrap()
+        );
+        assert!(parse_dec("abc").is_err());
+
+        // one ibc/ATOM is worth 2.5 uosmo, 1001 uosmo needs 400.4 rounded up
+        let atom = FeeTokenPrice {
+            denom: "ibc/ATOM".to_string(),
+            price: parse_dec("2.5").unwrap(),
+        };
+        let native = Coin {
+            amount: Uint256::from_u64(1001),
+            denom: "uosmo".to_string(),
+        };
+        let fee = atom.convert(&native.amount).unwrap();
+        assert_eq!(fee.amount, Uint256::from_u64(401));
+
+        let usdc = FeeTokenPrice {
+            denom: "ibc/USDC".to_string(),
+            price: parse_dec("0.5").unwrap(),
+        };
+        let prices = vec![atom, usdc];
+        let balances = |denom: &str| match denom {
+            "ibc/USDC" => Uint256::from_u64(5000),
+            _ => Uint256::zero(),
+        };
+        let fee = pick_fee(&prices, native.clone(), balances);
+        assert_eq!(fee.denom, "ibc/USDC");
+        assert_eq!(fee.amount, Uint256::from_u64(2002));
+
+        // nothing affordable, the native fee is kept
+        assert_eq!(
+            pick_fee(&prices, native.clone(), |_| Uint256::zero()),
+            native
+        );
+    }
+}
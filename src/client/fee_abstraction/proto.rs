@@ -0,0 +1,70 @@
+//! Hand written prost types for the Osmosis x/txfees and the FeeAbs x/feeabs queries,
+//! these mirror the query.proto of each module. Dec values are strings holding the
+//! integer scaled by 10^18
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryFeeTokensRequest {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct FeeToken {
+    #[prost(string, tag = "1")]
+    pub denom: String,
+    #[prost(uint64, tag = "2")]
+    pub pool_id: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryFeeTokensResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub fee_tokens: Vec<FeeToken>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryDenomSpotPriceRequest {
+    #[prost(string, tag = "1")]
+    pub denom: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryDenomSpotPriceResponse {
+    #[prost(uint64, tag = "1")]
+    pub pool_id: u64,
+    /// The amount of the base denom one unit of the fee token is worth
+    #[prost(string, tag = "2")]
+    pub spot_price: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryAllHostChainConfigRequest {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct HostChainFeeAbsConfig {
+    #[prost(string, tag = "1")]
+    pub ibc_denom: String,
+    #[prost(string, tag = "2")]
+    pub osmosis_pool_token_denom_in: String,
+    #[prost(uint64, tag = "3")]
+    pub pool_id: u64,
+    /// 0 updated, 1 outdated, 2 frozen
+    #[prost(int32, tag = "4")]
+    pub status: i32,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryAllHostChainConfigResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub all_host_chain_config: Vec<HostChainFeeAbsConfig>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryOsmosisArithmeticTwapRequest {
+    #[prost(string, tag = "1")]
+    pub ibc_denom: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryOsmosisArithmeticTwapResponse {
+    /// The amount of the native denom one unit of the IBC denom is worth
+    #[prost(string, tag = "1")]
+    pub arithmetic_twap: String,
+}
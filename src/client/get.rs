@@ -3,112 +3,182 @@ use crate::client::types::BlockParams;
 use crate::client::types::CosmosAccount;
 use crate::client::types::*;
 use crate::coin::Fee;
+use crate::error::PrivateKeyError;
+use crate::mnemonic::SecretMnemonic;
+use crate::tx::TxBuilder;
+use crate::xpub::Xpub;
 use crate::{address::Address, private_key::MessageArgs};
 use crate::{client::Contact, error::CosmosGrpcError};
-use bytes::BytesMut;
 use cosmos_sdk_proto::cosmos::auth::v1beta1::{
-    query_client::QueryClient as AuthQueryClient, BaseAccount as ProtoBaseAccount,
-    QueryAccountRequest,
+    query_client::QueryClient as AuthQueryClient, Params as AuthParams, QueryAccountRequest,
+    QueryParamsRequest as QueryAuthParamsRequest,
 };
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::PageRequest;
 use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::service_client::ServiceClient as TendermintServiceClient;
 use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetBlockByHeightRequest;
 use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetLatestBlockRequest;
 use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetSyncingRequest;
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetValidatorSetByHeightRequest;
 use cosmos_sdk_proto::cosmos::params::v1beta1::query_client::QueryClient as ParamsQueryClient;
 use cosmos_sdk_proto::cosmos::params::v1beta1::QueryParamsRequest;
 use cosmos_sdk_proto::cosmos::params::v1beta1::QueryParamsResponse;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::service_client::ServiceClient as TxServiceClient;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::GetTxRequest;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::GetTxResponse;
-use cosmos_sdk_proto::cosmos::vesting::v1beta1::ContinuousVestingAccount;
-use cosmos_sdk_proto::cosmos::vesting::v1beta1::DelayedVestingAccount;
-use cosmos_sdk_proto::cosmos::vesting::v1beta1::PeriodicVestingAccount;
 use cosmos_sdk_proto::tendermint::types::Block;
-use prost::Message;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use std::time::Instant;
 use tokio::time::sleep;
 use tonic::Code as GrpcCode;
 
+/// How often to check if a new block has been produced
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Tendermint returns at most 100 validators per request
+const VALIDATOR_SET_PAGE_SIZE: u64 = 100;
+
 impl Contact {
     /// Gets the current chain status, returns an enum taking into account the various possible states
     /// of the chain and the requesting full node. In the common case this provides the block number
     pub async fn get_chain_status(&self) -> Result<ChainStatus, CosmosGrpcError> {
-        let mut grpc = TendermintServiceClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let syncing = grpc.get_syncing(GetSyncingRequest {}).await?.into_inner();
-
-        if syncing.syncing {
-            Ok(ChainStatus::Syncing)
-        } else {
-            let block = grpc.get_latest_block(GetLatestBlockRequest {}).await;
-            match block {
-                Ok(block) => match block.into_inner().block {
-                    Some(block) => match block.last_commit {
-                        // for some reason the block height can be negative, we cast it to a u64 for the sake
-                        // of logical bounds checking
-                        Some(commit) => Ok(ChainStatus::Moving {
-                            block_height: commit.height as u64,
-                        }),
-                        None => Err(CosmosGrpcError::BadResponse(
-                            "No commit in block?".to_string(),
-                        )),
+        self.retry(|| async {
+            let mut grpc = TendermintServiceClient::new(self.get_channel().await?).accept_gzip();
+            let syncing = grpc.get_syncing(GetSyncingRequest {}).await?.into_inner();
+
+            if syncing.syncing {
+                Ok(ChainStatus::Syncing)
+            } else {
+                let block = grpc.get_latest_block(GetLatestBlockRequest {}).await;
+                match block {
+                    Ok(block) => match block.into_inner().block {
+                        Some(block) => match block.last_commit {
+                            // for some reason the block height can be negative, we cast it to a u64 for the sake
+                            // of logical bounds checking
+                            Some(commit) => Ok(ChainStatus::Moving {
+                                block_height: commit.height as u64,
+                            }),
+                            None => Err(CosmosGrpcError::BadResponse(
+                                "No commit in block?".to_string(),
+                            )),
+                        },
+                        None => Ok(ChainStatus::WaitingToStart),
                     },
-                    None => Ok(ChainStatus::WaitingToStart),
-                },
-                // if get syncing succeeded and this fails, it means there's 'no block' and
-                // we're waiting to start
-                Err(e) => {
-                    if e.message().contains("nil Block") {
-                        Ok(ChainStatus::WaitingToStart)
-                    } else {
-                        Err(e.into())
+                    // if get syncing succeeded and this fails, it means there's 'no block' and
+                    // we're waiting to start
+                    Err(e) => {
+                        if e.message().contains("nil Block") {
+                            Ok(ChainStatus::WaitingToStart)
+                        } else {
+                            Err(e.into())
+                        }
                     }
                 }
             }
-        }
+        })
+        .await
     }
 
     /// Gets the latest block from the node, taking into account the possibility that the chain is halted
     /// and also the possibility that the node is syncing
     pub async fn get_latest_block(&self) -> Result<LatestBlock, CosmosGrpcError> {
-        let mut grpc = TendermintServiceClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let syncing = grpc
-            .get_syncing(GetSyncingRequest {})
-            .await?
-            .into_inner()
-            .syncing;
-
-        let block = grpc.get_latest_block(GetLatestBlockRequest {}).await?;
-        let block = block.into_inner().block;
-        match block {
-            Some(block) => {
-                if syncing {
-                    Ok(LatestBlock::Syncing { block })
-                } else {
-                    Ok(LatestBlock::Latest { block })
+        self.retry(|| async {
+            let mut grpc = TendermintServiceClient::new(self.get_channel().await?).accept_gzip();
+            let syncing = grpc
+                .get_syncing(GetSyncingRequest {})
+                .await?
+                .into_inner()
+                .syncing;
+
+            let block = grpc.get_latest_block(GetLatestBlockRequest {}).await?;
+            let block = block.into_inner().block;
+            match block {
+                Some(block) => {
+                    if syncing {
+                        Ok(LatestBlock::Syncing { block })
+                    } else {
+                        Ok(LatestBlock::Latest { block })
+                    }
                 }
+                None => Ok(LatestBlock::WaitingToStart),
             }
-            None => Ok(LatestBlock::WaitingToStart),
+        })
+        .await
+    }
+
+    /// Gets the chain id reported in the latest block header of the node
+    pub async fn get_chain_id(&self) -> Result<String, CosmosGrpcError> {
+        match self.get_latest_block().await? {
+            LatestBlock::Latest { block } | LatestBlock::Syncing { block } => match block.header {
+                Some(header) => Ok(header.chain_id),
+                None => Err(CosmosGrpcError::BadResponse(
+                    "Null block header?".to_string(),
+                )),
+            },
+            LatestBlock::WaitingToStart => Err(CosmosGrpcError::ChainNotRunning),
         }
     }
 
     /// Gets the specified block from the node, returns none if no block is available
     pub async fn get_block(&self, block: u64) -> Result<Option<Block>, CosmosGrpcError> {
-        let mut grpc = TendermintServiceClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
+        self.retry(|| async {
+            let mut grpc = TendermintServiceClient::new(self.get_channel().await?).accept_gzip();
 
-        let block = grpc
-            .get_block_by_height(GetBlockByHeightRequest {
-                height: block as i64,
-            })
-            .await?
-            .into_inner();
-        Ok(block.block)
+            let block = grpc
+                .get_block_by_height(GetBlockByHeightRequest {
+                    height: block as i64,
+                })
+                .await?
+                .into_inner();
+            Ok(block.block)
+        })
+        .await
+    }
+
+    /// Gets the Tendermint validator set of the block at `height`, every page of the set
+    /// is fetched so sets larger than the node's page size are complete. Nodes do not
+    /// reliably report the total, so paging stops at a short page with no next key
+    pub async fn get_validator_set(&self, height: u64) -> Result<ValidatorSet, CosmosGrpcError> {
+        let mut validators = Vec::new();
+        let mut next_key = Vec::new();
+        loop {
+            let pagination = PageRequest {
+                // the key and offset are exclusive, the sdk pages this query by offset
+                offset: if next_key.is_empty() {
+                    validators.len() as u64
+                } else {
+                    0
+                },
+                key: next_key,
+                limit: VALIDATOR_SET_PAGE_SIZE,
+                count_total: true,
+                reverse: false,
+            };
+            let res = self
+                .retry(|| async {
+                    let mut grpc =
+                        TendermintServiceClient::new(self.get_channel().await?).accept_gzip();
+                    Ok(grpc
+                        .get_validator_set_by_height(GetValidatorSetByHeightRequest {
+                            height: height as i64,
+                            pagination: Some(pagination.clone()),
+                        })
+                        .await?
+                        .into_inner())
+                })
+                .await?;
+            let (total, key) = res
+                .pagination
+                .map(|p| (p.total, p.next_key))
+                .unwrap_or_default();
+            let received = res.validators.len() as u64;
+            validators.extend(res.validators);
+            let complete = total > 0 && validators.len() as u64 >= total;
+            if received == 0 || complete || (key.is_empty() && received < VALIDATOR_SET_PAGE_SIZE) {
+                break;
+            }
+            next_key = key;
+        }
+        Ok(ValidatorSet { height, validators })
     }
 
     /// Gets the specified block range from the node, returning None if no block is available
@@ -120,16 +190,21 @@ impl Contact {
         start: u64,
         end: u64,
     ) -> Result<Vec<Option<Block>>, CosmosGrpcError> {
-        let mut grpc = TendermintServiceClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
+        let grpc = TendermintServiceClient::new(self.get_channel().await?).accept_gzip();
 
         let mut result = Vec::new();
         for i in start..end {
-            let block = grpc
-                .get_block_by_height(GetBlockByHeightRequest { height: i as i64 })
-                .await?
-                .into_inner();
+            let block = self
+                .retry(|| {
+                    let mut grpc = grpc.clone();
+                    async move {
+                        Ok(grpc
+                            .get_block_by_height(GetBlockByHeightRequest { height: i as i64 })
+                            .await?
+                            .into_inner())
+                    }
+                })
+                .await?;
             result.push(block.block);
         }
 
@@ -141,22 +216,25 @@ impl Contact {
     /// This is extra useful because cosmos-sdk behaves very strangely when
     /// a transaction above the max allowed gas is submitted.
     pub async fn get_block_params(&self) -> Result<BlockParams, CosmosGrpcError> {
-        let res = self.get_param("baseapp", "BlockParams").await?;
-        if let Some(v) = res.param {
-            match serde_json::from_str(&v.value) {
-                Ok(v) => {
-                    let v: BlockParamsJson = v;
-                    Ok(v.into())
+        self.cached("block_params", || async {
+            let res = self.get_param("baseapp", "BlockParams").await?;
+            if let Some(v) = res.param {
+                match serde_json::from_str(&v.value) {
+                    Ok(v) => {
+                        let v: BlockParamsJson = v;
+                        Ok(v.into())
+                    }
+                    Err(e) => Err(CosmosGrpcError::BadResponse(e.to_string())),
                 }
-                Err(e) => Err(CosmosGrpcError::BadResponse(e.to_string())),
+            } else {
+                // if we hit this error the value has been moved and we're probably
+                // woefully out of date.
+                Err(CosmosGrpcError::BadResponse(
+                    "No BlockParams? Deep Space probably needs to be upgraded".to_string(),
+                ))
             }
-        } else {
-            // if we hit this error the value has been moved and we're probably
-            // woefully out of date.
-            Err(CosmosGrpcError::BadResponse(
-                "No BlockParams? Deep Space probably needs to be upgraded".to_string(),
-            ))
-        }
+        })
+        .await
     }
 
     /// Queries a registered parameter given it's subspace and key, this should work
@@ -166,91 +244,216 @@ impl Contact {
         subspace: impl ToString,
         key: impl ToString,
     ) -> Result<QueryParamsResponse, CosmosGrpcError> {
-        let mut grpc = ParamsQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        Ok(grpc
-            .params(QueryParamsRequest {
-                subspace: subspace.to_string(),
-                key: key.to_string(),
-            })
-            .await?
-            .into_inner())
+        self.retry(|| async {
+            let mut grpc = ParamsQueryClient::new(self.get_channel().await?).accept_gzip();
+            Ok(grpc
+                .params(QueryParamsRequest {
+                    subspace: subspace.to_string(),
+                    key: key.to_string(),
+                })
+                .await?
+                .into_inner())
+        })
+        .await
     }
 
     /// Gets account info for the provided Cosmos account using the accounts endpoint
     /// accounts do not have any info if they have no tokens or are otherwise never seen
     /// before in this case we return the special error NoToken
     pub async fn get_account_info(&self, address: Address) -> Result<BaseAccount, CosmosGrpcError> {
-        let mut agrpc = AuthQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = agrpc
-            // todo detect chain prefix here
-            .account(QueryAccountRequest {
-                address: address.to_bech32(&self.chain_prefix).unwrap(),
-            })
-            .await;
-        match res {
-            Ok(account) => {
-                // null pointer if this fails to unwrap
-                let value = account.into_inner().account.unwrap();
-                let mut buf = BytesMut::with_capacity(value.value.len());
-                buf.extend_from_slice(&value.value);
-                match (
-                    ProtoBaseAccount::decode(buf.clone()),
-                    PeriodicVestingAccount::decode(buf.clone()),
-                    ContinuousVestingAccount::decode(buf.clone()),
-                    DelayedVestingAccount::decode(buf.clone()),
-                ) {
-                    (Ok(d), _, _, _) => Ok(d.get_base_account()),
-                    (_, Ok(d), _, _) => Ok(d.get_base_account()),
-                    (_, _, Ok(d), _) => Ok(d.get_base_account()),
-                    (_, _, _, Ok(d)) => Ok(d.get_base_account()),
-                    (Err(e), _, _, _) => Err(CosmosGrpcError::DecodeError { error: e }),
-                }
+        Ok(self.get_account(address).await?.get_base_account())
+    }
+
+    /// Gets the full account for the provided address, including vesting schedules or
+    /// module account info. Returns the special error NoToken if the account has never
+    /// been seen by the chain
+    pub async fn get_account(&self, address: Address) -> Result<AccountType, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut agrpc = AuthQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = agrpc
+                // todo detect chain prefix here
+                .account(QueryAccountRequest {
+                    address: address.to_bech32(&self.config.prefix).unwrap(),
+                })
+                .await;
+            match res {
+                Ok(account) => match account.into_inner().account {
+                    Some(value) => AccountType::decode_any_with(value, &self.registry),
+                    None => Err(CosmosGrpcError::BadResponse(
+                        "No account returned".to_string(),
+                    )),
+                },
+                Err(e) => match e.code() {
+                    GrpcCode::NotFound => Err(CosmosGrpcError::NoToken),
+                    _ => Err(CosmosGrpcError::RequestError { error: e }),
+                },
             }
-            Err(e) => match e.code() {
-                GrpcCode::NotFound => Err(CosmosGrpcError::NoToken),
-                _ => Err(CosmosGrpcError::RequestError { error: e }),
+        })
+        .await
+    }
+
+    /// Restores the accounts of a wallet the way wallet software does on import. Addresses
+    /// on m/44'/coin_type'/0'/0/i are checked in order until `gap_limit` unused addresses
+    /// in a row have been seen, an address is used if the chain has an account for it or
+    /// it holds a balance. `xpub_or_phrase` is either an xpub exported at the account level
+    /// m/44'/coin_type'/0' or a mnemonic phrase without a passphrase, a phrase never
+    /// leaves this function, only the derived xpub is used for the scan
+    pub async fn discover_accounts(
+        &self,
+        xpub_or_phrase: &str,
+        gap_limit: u32,
+    ) -> Result<Vec<DiscoveredAccount>, CosmosGrpcError> {
+        let xpub = match xpub_or_phrase.parse::<Xpub>() {
+            Ok(xpub) => xpub,
+            Err(_) => match SecretMnemonic::parse(xpub_or_phrase) {
+                Ok(phrase) => phrase.to_xpub(&self.config.account_path(), "")?,
+                Err(e) => {
+                    return Err(PrivateKeyError::from(e).into());
+                }
             },
+        };
+
+        let mut found = Vec::new();
+        let mut unused = 0;
+        let mut index = 0;
+        while unused < gap_limit {
+            let address = xpub.address_at(&self.config.prefix, index)?;
+            let account = match self.get_account(address).await {
+                Ok(account) => Some(account),
+                Err(CosmosGrpcError::NoToken) => None,
+                Err(e) => return Err(e),
+            };
+            let balances = self.get_balances(address).await?;
+            if account.is_some() || !balances.is_empty() {
+                found.push(DiscoveredAccount {
+                    index,
+                    address,
+                    account,
+                    balances,
+                });
+                unused = 0;
+            } else {
+                unused += 1;
+            }
+            index += 1;
         }
+        Ok(found)
     }
 
     // Gets a transaction using it's hash value, TODO should fail if the transaction isn't found
     pub async fn get_tx_by_hash(&self, txhash: String) -> Result<GetTxResponse, CosmosGrpcError> {
-        let mut txrpc = TxServiceClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = txrpc
-            .get_tx(GetTxRequest { hash: txhash })
-            .await?
-            .into_inner();
-        Ok(res)
+        self.retry(|| async {
+            let mut txrpc = TxServiceClient::new(self.get_channel().await?).accept_gzip();
+            let res = txrpc
+                .get_tx(GetTxRequest {
+                    hash: txhash.clone(),
+                })
+                .await?
+                .into_inner();
+            Ok(res)
+        })
+        .await
+    }
+
+    /// Gets the params of the auth, bank, staking, distribution, gov and slashing modules
+    /// in one call, each set is also available from its own module query
+    pub async fn get_chain_params(&self) -> Result<ChainParams, CosmosGrpcError> {
+        let (auth, bank, staking, distribution, gov, slashing) = tokio::try_join!(
+            self.get_auth_params(),
+            self.get_bank_params(),
+            self.get_staking_params(),
+            self.get_distribution_params(),
+            self.get_gov_params(),
+            self.get_slashing_params(),
+        )?;
+        Ok(ChainParams {
+            auth,
+            bank,
+            staking,
+            distribution,
+            gov,
+            slashing,
+        })
+    }
+
+    /// Gets the auth module params, these include the memo length limit and the
+    /// gas charged per tx byte and signature
+    pub async fn get_auth_params(&self) -> Result<AuthParams, CosmosGrpcError> {
+        self.cached("auth_params", || {
+            self.retry(|| async {
+                let mut grpc = AuthQueryClient::new(self.get_channel().await?).accept_gzip();
+                let res = grpc
+                    .params(QueryAuthParamsRequest {})
+                    .await?
+                    .into_inner()
+                    .params;
+                match res {
+                    Some(params) => Ok(params),
+                    None => Err(CosmosGrpcError::BadResponse("No auth params".to_string())),
+                }
+            })
+        })
+        .await
+    }
+
+    /// The chain's max_memo_characters auth param, queried once and then cached for the
+    /// life of this Contact. None if the params could not be queried, in which case
+    /// memos are left for the chain to check
+    pub async fn get_max_memo_characters(&self) -> Option<u64> {
+        let cached = self.max_memo_characters.load(Ordering::Relaxed);
+        if cached != 0 {
+            return Some(cached);
+        }
+        match self.get_auth_params().await {
+            Ok(params) => {
+                self.max_memo_characters
+                    .store(params.max_memo_characters, Ordering::Relaxed);
+                Some(params.max_memo_characters)
+            }
+            Err(e) => {
+                warn!("Could not get auth params to check memo length {:?}", e);
+                None
+            }
+        }
     }
 
     /// Grabs an up to date MessageArgs structure for an address,
     /// provided a fee value to insert into the structure. The goal of
     /// this function is to be very minimal and make a lot of choices for
-    /// the user. Like how to handle changes in chain-id or timeout heights
+    /// the user. Like how to handle changes in chain-id or timeout heights.
+    /// The sequence is always that of the latest block, even on a Contact from `at_height`
     pub async fn get_message_args(
         &self,
         our_address: Address,
         fee: Fee,
     ) -> Result<MessageArgs, CosmosGrpcError> {
-        let account_info = self.get_account_info(our_address).await?;
+        let account_info = self.at_latest().get_account_info(our_address).await?;
 
         let latest_block = self.get_latest_block().await?;
 
         match latest_block {
             LatestBlock::Latest { block } => {
                 if let Some(header) = block.header {
+                    if let Some(chain_id) = &self.config.chain_id {
+                        if *chain_id != header.chain_id {
+                            return Err(CosmosGrpcError::BadInput(format!(
+                                "Expected chain id {} but the node is on {}",
+                                chain_id, header.chain_id
+                            )));
+                        }
+                    }
                     Ok(MessageArgs {
                         sequence: account_info.sequence,
                         account_number: account_info.account_number,
                         chain_id: header.chain_id,
                         fee,
                         timeout_height: header.height as u64 + 100,
+                        relative_timeout: None,
+                        extension_options: Vec::new(),
+                        non_critical_extension_options: Vec::new(),
+                        unordered: false,
+                        timeout_timestamp: None,
+                        max_memo_characters: self.get_max_memo_characters().await,
                     })
                 } else {
                     Err(CosmosGrpcError::BadResponse(
@@ -259,7 +462,40 @@ impl Contact {
                 }
             }
             LatestBlock::Syncing { .. } => Err(CosmosGrpcError::NodeNotSynced),
-            LatestBlock::WaitingToStart { .. } => Err(CosmosGrpcError::ChainNotRunning),
+            LatestBlock::WaitingToStart => Err(CosmosGrpcError::ChainNotRunning),
+        }
+    }
+
+    /// Resolves the relative timeout of `args` against the latest block, args without
+    /// one are returned unchanged without querying the node
+    pub async fn resolve_message_args(
+        &self,
+        mut args: MessageArgs,
+    ) -> Result<MessageArgs, CosmosGrpcError> {
+        if args.relative_timeout.is_some() {
+            args.resolve_relative_timeout(self.get_moving_height().await?);
+        }
+        Ok(args)
+    }
+
+    /// Resolves the relative timeout set on a [TxBuilder] against the latest block
+    pub async fn resolve_tx_builder(
+        &self,
+        builder: TxBuilder,
+    ) -> Result<TxBuilder, CosmosGrpcError> {
+        if builder.get_relative_timeout().is_none() {
+            return Ok(builder);
+        }
+        let height = self.get_moving_height().await?;
+        Ok(builder.resolve_relative_timeout(height))
+    }
+
+    /// The latest height, an error if the node is syncing or the chain has not started
+    async fn get_moving_height(&self) -> Result<u64, CosmosGrpcError> {
+        match self.get_chain_status().await? {
+            ChainStatus::Moving { block_height } => Ok(block_height),
+            ChainStatus::Syncing => Err(CosmosGrpcError::NodeNotSynced),
+            ChainStatus::WaitingToStart => Err(CosmosGrpcError::ChainNotRunning),
         }
     }
 
@@ -282,10 +518,11 @@ impl Contact {
                 (Ok(ChainStatus::WaitingToStart), _) => {
                     return Err(CosmosGrpcError::ChainNotRunning)
                 }
-                // we don't want a single error to exit this loop early
-                (Err(_), _) => {}
+                // we don't want a single transient error to exit this loop early
+                (Err(e), _) if (self.retry.retryable)(&e) => {}
+                (Err(e), _) => return Err(e),
             }
-            sleep(Duration::from_secs(1)).await;
+            sleep(BLOCK_POLL_INTERVAL).await;
         }
         Err(CosmosGrpcError::NoBlockProduced { time: timeout })
     }
@@ -299,11 +536,152 @@ struct BlockParamsJson {
 }
 impl From<BlockParamsJson> for BlockParams {
     fn from(input: BlockParamsJson) -> Self {
-        let max_gas = match input.max_gas.parse() {
-            Ok(v) => Some(v),
-            Err(_) => None,
-        };
+        let max_gas = input.max_gas.parse().ok();
         let max_bytes = input.max_bytes.parse().unwrap_or(0u64);
         BlockParams { max_bytes, max_gas }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock_grpc::MockGrpc;
+    use crate::client::RetryPolicy;
+    use cosmos_sdk_proto::cosmos::auth::v1beta1::BaseAccount as ProtoBaseAccount;
+    use cosmos_sdk_proto::cosmos::auth::v1beta1::{QueryAccountRequest, QueryAccountResponse};
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::{
+        QueryAllBalancesRequest, QueryAllBalancesResponse,
+    };
+    use cosmos_sdk_proto::cosmos::base::query::v1beta1::PageResponse;
+    use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::{
+        GetValidatorSetByHeightResponse, Validator,
+    };
+    use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+    use prost::Message;
+    use prost_types::Any;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    const PHRASE: &str = "purse sure leg gap above pull rescue glass circle attract erupt can sail gasp shy clarify inflict anger sketch hobby scare mad reject where";
+
+    #[actix_rt::test]
+    async fn test_discover_accounts() {
+        let xpub = SecretMnemonic::parse(PHRASE)
+            .unwrap()
+            .to_xpub("m/44'/118'/0'", "")
+            .unwrap();
+        let address = |index| xpub.address_at("cosmos", index).unwrap().to_string();
+        // index 0 has an account, index 2 only a balance, everything else is unused
+        let (with_account, with_balance) = (address(0), address(2));
+        let grpc = MockGrpc::new()
+            .with(
+                "/cosmos.auth.v1beta1.Query/Account",
+                move |req: QueryAccountRequest| {
+                    if req.address != with_account {
+                        return Err(tonic::Status::not_found("account not found"));
+                    }
+                    let account = ProtoBaseAccount {
+                        address: req.address,
+                        account_number: 5,
+                        ..Default::default()
+                    };
+                    Ok(QueryAccountResponse {
+                        account: Some(Any {
+                            type_url: "/cosmos.auth.v1beta1.BaseAccount".to_string(),
+                            value: account.encode_to_vec(),
+                        }),
+                    })
+                },
+            )
+            .with(
+                "/cosmos.bank.v1beta1.Query/AllBalances",
+                move |req: QueryAllBalancesRequest| {
+                    let mut balances = Vec::new();
+                    if req.address == with_balance {
+                        balances.push(ProtoCoin {
+                            denom: "uatom".to_string(),
+                            amount: "10".to_string(),
+                        });
+                    }
+                    Ok(QueryAllBalancesResponse {
+                        balances,
+                        pagination: None,
+                    })
+                },
+            );
+        let url = grpc.serve().await;
+        let contact = Contact::new(&url, Duration::from_secs(5), "cosmos").unwrap();
+
+        // the scan continues past the unused index 1 and stops after 2 unused in a row
+        let found = contact.discover_accounts(PHRASE, 2).await.unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].index, found[1].index), (0, 2));
+        assert_eq!(found[0].address.to_string(), address(0));
+        assert!(matches!(
+            found[0].account,
+            Some(AccountType::Base(ProtoBaseAccount {
+                account_number: 5,
+                ..
+            }))
+        ));
+        assert!(found[1].account.is_none());
+        assert_eq!(found[1].balances, vec!["10uatom".parse().unwrap()]);
+        let queried = grpc
+            .calls()
+            .iter()
+            .filter(|path| path.ends_with("/Account"))
+            .count();
+        assert_eq!(queried, 5);
+
+        // an exported xpub finds the same accounts, a gap limit of 1 stops at index 1
+        let found = contact
+            .discover_accounts(&xpub.to_string(), 1)
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].address.to_string(), address(0));
+
+        assert!(contact.discover_accounts("not a phrase", 2).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_get_validator_set_without_total() {
+        let failed = Arc::new(AtomicBool::new(false));
+        let fail_once = failed.clone();
+        let grpc = MockGrpc::new().with(
+            "/cosmos.base.tendermint.v1beta1.Service/GetValidatorSetByHeight",
+            move |req: GetValidatorSetByHeightRequest| {
+                let page = req.pagination.unwrap();
+                // the second page is dropped once and has to be retried
+                if page.offset == 100 && !fail_once.swap(true, Ordering::Relaxed) {
+                    return Err(tonic::Status::unavailable("restarting"));
+                }
+                let validators = (page.offset..250.min(page.offset + page.limit))
+                    .map(|i| Validator {
+                        voting_power: i as i64,
+                        ..Default::default()
+                    })
+                    .collect();
+                // no total and no next key, as returned by nodes that skip count_total
+                Ok(GetValidatorSetByHeightResponse {
+                    block_height: req.height,
+                    validators,
+                    pagination: Some(PageResponse::default()),
+                })
+            },
+        );
+        let url = grpc.serve().await;
+        let contact = Contact::new(&url, Duration::from_secs(5), "cosmos")
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                base_delay: Duration::from_millis(1),
+                ..Default::default()
+            });
+        let set = contact.get_validator_set(10).await.unwrap();
+        assert_eq!(set.height, 10);
+        assert_eq!(set.validators.len(), 250);
+        assert_eq!(set.validators[249].voting_power, 249);
+        assert!(failed.load(Ordering::Relaxed));
+        assert_eq!(grpc.calls().len(), 4);
+    }
+}
@@ -0,0 +1,157 @@
+//! Decoding of governance proposal content. A v1beta1 proposal carries a single
+//! content Any, under gov v1 legacy content is wrapped in a MsgExecLegacyContent
+
+use crate::error::CosmosGrpcError;
+use crate::TypeUrl;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::CommunityPoolSpendProposal;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::{Proposal, TextProposal};
+use cosmos_sdk_proto::cosmos::params::v1beta1::ParameterChangeProposal;
+use cosmos_sdk_proto::cosmos::upgrade::v1beta1::{
+    CancelSoftwareUpgradeProposal, SoftwareUpgradeProposal,
+};
+use prost::Message;
+use prost_types::Any;
+
+/// MsgExecLegacyContent from gov v1 (Cosmos SDK 0.46), executes legacy content with the
+/// gov module as the signer
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgExecLegacyContent {
+    #[prost(message, optional, tag = "1")]
+    pub content: Option<Any>,
+    #[prost(string, tag = "2")]
+    pub authority: String,
+}
+
+impl TypeUrl for MsgExecLegacyContent {
+    const TYPE_URL: &'static str = "/cosmos.gov.v1.MsgExecLegacyContent";
+}
+
+/// The content of a governance proposal, decoded by type url
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProposalContent {
+    Text(TextProposal),
+    ParameterChange(ParameterChangeProposal),
+    SoftwareUpgrade(SoftwareUpgradeProposal),
+    CancelSoftwareUpgrade(CancelSoftwareUpgradeProposal),
+    CommunityPoolSpend(CommunityPoolSpendProposal),
+    /// Legacy content submitted through gov v1
+    ExecLegacyContent {
+        content: Box<ProposalContent>,
+        authority: String,
+    },
+    /// Any content type not listed above, left encoded
+    Other(Any),
+}
+
+impl ProposalContent {
+    /// Decodes a content Any, content of an unknown type becomes `Other` rather than an
+    /// error so that new proposal types do not break callers
+    pub fn decode_any(value: Any) -> Result<ProposalContent, CosmosGrpcError> {
+        let buf = value.value.as_slice();
+        let res = match value.type_url.as_str() {
+            TextProposal::TYPE_URL => ProposalContent::Text(Message::decode(buf)?),
+            ParameterChangeProposal::TYPE_URL => {
+                ProposalContent::ParameterChange(Message::decode(buf)?)
+            }
+            SoftwareUpgradeProposal::TYPE_URL => {
+                ProposalContent::SoftwareUpgrade(Message::decode(buf)?)
+            }
+            CancelSoftwareUpgradeProposal::TYPE_URL => {
+                ProposalContent::CancelSoftwareUpgrade(Message::decode(buf)?)
+            }
+            CommunityPoolSpendProposal::TYPE_URL => {
+                ProposalContent::CommunityPoolSpend(Message::decode(buf)?)
+            }
+            MsgExecLegacyContent::TYPE_URL => {
+                let msg = MsgExecLegacyContent::decode(buf)?;
+                ProposalContent::ExecLegacyContent {
+                    content: Box::new(ProposalContent::decode_any(
+                        msg.content.unwrap_or_default(),
+                    )?),
+                    authority: msg.authority,
+                }
+            }
+            _ => ProposalContent::Other(value),
+        };
+        Ok(res)
+    }
+
+    /// Decodes the content of a v1beta1 proposal, None if it has no content
+    pub fn from_proposal(proposal: &Proposal) -> Result<Option<ProposalContent>, CosmosGrpcError> {
+        proposal
+            .content
+            .clone()
+            .map(ProposalContent::decode_any)
+            .transpose()
+    }
+
+    /// The title of the proposal, None for content of an unknown type
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            ProposalContent::Text(p) => Some(&p.title),
+            ProposalContent::ParameterChange(p) => Some(&p.title),
+            ProposalContent::SoftwareUpgrade(p) => Some(&p.title),
+            ProposalContent::CancelSoftwareUpgrade(p) => Some(&p.title),
+            ProposalContent::CommunityPoolSpend(p) => Some(&p.title),
+            ProposalContent::ExecLegacyContent { content, .. } => content.title(),
+            ProposalContent::Other(_) => None,
+        }
+    }
+
+    /// The description of the proposal, None for content of an unknown type
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            ProposalContent::Text(p) => Some(&p.description),
+            ProposalContent::ParameterChange(p) => Some(&p.description),
+            ProposalContent::SoftwareUpgrade(p) => Some(&p.description),
+            ProposalContent::CancelSoftwareUpgrade(p) => Some(&p.description),
+            ProposalContent::CommunityPoolSpend(p) => Some(&p.description),
+            ProposalContent::ExecLegacyContent { content, .. } => content.description(),
+            ProposalContent::Other(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::encode_any;
+
+    #[test]
+    fn test_decode_proposal_content() {
+        let text = TextProposal {
+            title: "Title".to_string(),
+            description: "Description".to_string(),
+        };
+        let any = encode_any(text.clone(), TextProposal::TYPE_URL);
+        assert_eq!(
+            ProposalContent::decode_any(any.clone()).unwrap(),
+            ProposalContent::Text(text.clone())
+        );
+
+        let wrapped = encode_any(
+            MsgExecLegacyContent {
+                content: Some(any),
+                authority: "cosmos10d07y265gmmuvt4z0w9aw880jnsr700j6zn9kn".to_string(),
+            },
+            MsgExecLegacyContent::TYPE_URL,
+        );
+        let content = ProposalContent::decode_any(wrapped).unwrap();
+        assert_eq!(content.title(), Some("Title"));
+        match content {
+            ProposalContent::ExecLegacyContent { content, .. } => {
+                assert_eq!(*content, ProposalContent::Text(text))
+            }
+            other => panic!("Unexpected content {:?}", other),
+        }
+
+        let unknown = Any {
+            type_url: "/chain.custom.v1.Proposal".to_string(),
+            value: vec![1, 2, 3],
+        };
+        assert_eq!(
+            ProposalContent::decode_any(unknown.clone()).unwrap(),
+            ProposalContent::Other(unknown)
+        );
+    }
+}
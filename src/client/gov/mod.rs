@@ -1,33 +1,89 @@
 //! Contains utility functions for interacting with and modifying Cosmos validator staking status
 
+use super::types::GovParams;
 use super::PAGE;
 use crate::error::CosmosGrpcError;
 use crate::Coin;
 use crate::Contact;
 use crate::Msg;
 use crate::PrivateKey;
+use content::ProposalContent;
 use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::query_client::QueryClient as GovQueryClient;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::MsgSubmitProposal;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::MsgVote;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::Proposal;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::ProposalStatus;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::QueryParamsRequest;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::QueryProposalsRequest;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::QueryProposalsResponse;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::VoteOption;
 use prost_types::Any;
 use std::time::Duration;
 
+pub mod content;
+
 impl Contact {
+    /// Gets the voting, deposit and tally parameters of the governance module
+    pub async fn get_gov_params(&self) -> Result<GovParams, CosmosGrpcError> {
+        self.cached("gov_params", || {
+            self.retry(|| async {
+                let grpc = GovQueryClient::new(self.get_channel().await?).accept_gzip();
+                // the gov module returns one of its three param sets per query
+                let query = |params_type: &str| {
+                    let req = QueryParamsRequest {
+                        params_type: params_type.to_string(),
+                    };
+                    let mut grpc = grpc.clone();
+                    async move { grpc.params(req).await }
+                };
+                let (voting, deposit, tally) =
+                    tokio::try_join!(query("voting"), query("deposit"), query("tallying"))?;
+                match (
+                    voting.into_inner().voting_params,
+                    deposit.into_inner().deposit_params,
+                    tally.into_inner().tally_params,
+                ) {
+                    (Some(voting), Some(deposit), Some(tally)) => Ok(GovParams {
+                        voting,
+                        deposit,
+                        tally,
+                    }),
+                    _ => Err(CosmosGrpcError::BadResponse(
+                        "No gov params returned".to_string(),
+                    )),
+                }
+            })
+        })
+        .await
+    }
+
     /// Gets a list of governance proposals, user provides filter items
     pub async fn get_governance_proposals(
         &self,
         filters: QueryProposalsRequest,
     ) -> Result<QueryProposalsResponse, CosmosGrpcError> {
-        let mut grpc = GovQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-        let res = grpc.proposals(filters).await?.into_inner();
-        Ok(res)
+        self.retry(|| async {
+            let mut grpc = GovQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc.proposals(filters.clone()).await?.into_inner();
+            Ok(res)
+        })
+        .await
+    }
+
+    /// Gets a list of governance proposals along with their decoded content, see
+    /// `ProposalContent::decode_any` for how unknown content types are handled
+    pub async fn get_decoded_governance_proposals(
+        &self,
+        filters: QueryProposalsRequest,
+    ) -> Result<Vec<(Proposal, Option<ProposalContent>)>, CosmosGrpcError> {
+        let res = self.get_governance_proposals(filters).await?;
+        let mut out = Vec::new();
+        for proposal in res.proposals {
+            let content = ProposalContent::from_proposal(&proposal)?;
+            out.push((proposal, content));
+        }
+        Ok(out)
     }
 
     /// Gets a list of all active governance proposals currently in the voting period
@@ -98,7 +154,7 @@ impl Contact {
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
         let vote = MsgVote {
             proposal_id,
             voter: our_address.to_string(),
@@ -119,7 +175,7 @@ impl Contact {
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
         let proposal = MsgSubmitProposal {
             proposer: our_address.to_string(),
             content: Some(content),
@@ -131,3 +187,64 @@ impl Contact {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock_grpc::MockGrpc;
+    use cosmos_sdk_proto::cosmos::gov::v1beta1::{
+        DepositParams, QueryParamsResponse, TallyParams, VotingParams,
+    };
+
+    /// A gov module answering each params_type with only that part, like the sdk does
+    fn gov_params(tally: bool) -> MockGrpc {
+        MockGrpc::new().with(
+            "/cosmos.gov.v1beta1.Query/Params",
+            move |req: QueryParamsRequest| {
+                let mut res = QueryParamsResponse::default();
+                match req.params_type.as_str() {
+                    "voting" => {
+                        res.voting_params = Some(VotingParams {
+                            voting_period: Some(prost_types::Duration {
+                                seconds: 172800,
+                                nanos: 0,
+                            }),
+                        })
+                    }
+                    "deposit" => {
+                        res.deposit_params = Some(DepositParams {
+                            min_deposit: vec!["10uatom".parse::<Coin>().unwrap().into()],
+                            max_deposit_period: None,
+                        })
+                    }
+                    "tallying" if tally => {
+                        res.tally_params = Some(TallyParams {
+                            quorum: b"0.334000000000000000".to_vec(),
+                            ..Default::default()
+                        })
+                    }
+                    _ => {}
+                }
+                Ok(res)
+            },
+        )
+    }
+
+    #[actix_rt::test]
+    async fn test_get_gov_params() {
+        let url = gov_params(true).serve().await;
+        let contact = Contact::new(&url, Duration::from_secs(5), "cosmos").unwrap();
+        let params = contact.get_gov_params().await.unwrap();
+        assert_eq!(params.voting.voting_period.unwrap().seconds, 172800);
+        assert_eq!(params.deposit.min_deposit[0].amount, "10");
+        assert_eq!(params.tally.quorum, b"0.334000000000000000");
+
+        // a missing part fails the whole query
+        let url = gov_params(false).serve().await;
+        let contact = Contact::new(&url, Duration::from_secs(5), "cosmos").unwrap();
+        assert!(matches!(
+            contact.get_gov_params().await,
+            Err(CosmosGrpcError::BadResponse(_))
+        ));
+    }
+}
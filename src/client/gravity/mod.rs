@@ -0,0 +1,181 @@
+//! Contains message builders and queries for the Gravity Bridge module, used by bridge
+//! users to send tokens to Ethereum and by orchestrators to request and sign batches
+//! and to follow validator set updates. Enabled with the `gravity` feature
+
+use crate::error::CosmosGrpcError;
+use crate::utils::bytes_to_hex_str;
+use crate::{Address, Coin, Contact, Msg};
+
+pub mod proto;
+
+use proto::*;
+pub use proto::{BridgeValidator, Erc20Token, OutgoingTransferTx, OutgoingTxBatch, Valset};
+
+pub const MSG_SEND_TO_ETH_TYPE_URL: &str = "/gravity.v1.MsgSendToEth";
+pub const MSG_REQUEST_BATCH_TYPE_URL: &str = "/gravity.v1.MsgRequestBatch";
+pub const MSG_CONFIRM_BATCH_TYPE_URL: &str = "/gravity.v1.MsgConfirmBatch";
+
+impl Msg {
+    /// Creates a MsgSendToEth, moving `amount` into the batch pool for transfer to
+    /// `eth_dest`. `bridge_fee` is paid to the relayer of the batch and `chain_fee`
+    /// to the stakers of the Cosmos chain, chains without a chain fee accept None
+    pub fn send_to_eth(
+        sender: Address,
+        eth_dest: &str,
+        amount: Coin,
+        bridge_fee: Coin,
+        chain_fee: Option<Coin>,
+    ) -> Self {
+        let msg = MsgSendToEth {
+            sender: sender.to_string(),
+            eth_dest: eth_dest.to_string(),
+            amount: Some(amount.into()),
+            bridge_fee: Some(bridge_fee.into()),
+            chain_fee: chain_fee.map(|fee| fee.into()),
+        };
+        Msg::new(MSG_SEND_TO_ETH_TYPE_URL, msg)
+    }
+
+    /// Creates a MsgRequestBatch, asking the chain to build a batch out of the pending
+    /// transfers of `denom`
+    pub fn request_batch(sender: Address, denom: String) -> Self {
+        let msg = MsgRequestBatch {
+            sender: sender.to_string(),
+            denom,
+        };
+        Msg::new(MSG_REQUEST_BATCH_TYPE_URL, msg)
+    }
+
+    /// Creates a MsgConfirmBatch submitting an orchestrator's Ethereum signature over
+    /// a batch, `eth_signer` is the Ethereum address the orchestrator signed with
+    pub fn confirm_batch(
+        orchestrator: Address,
+        batch: &OutgoingTxBatch,
+        eth_signer: &str,
+        signature: &[u8],
+    ) -> Self {
+        let msg = MsgConfirmBatch {
+            nonce: batch.batch_nonce,
+            token_contract: batch.token_contract.clone(),
+            eth_signer: eth_signer.to_string(),
+            orchestrator: orchestrator.to_string(),
+            signature: bytes_to_hex_str(signature),
+        };
+        Msg::new(MSG_CONFIRM_BATCH_TYPE_URL, msg)
+    }
+}
+
+impl Contact {
+    /// Gets the current validator set, this may not have been requested or relayed
+    /// to Ethereum yet
+    pub async fn get_gravity_current_valset(&self) -> Result<Valset, CosmosGrpcError> {
+        let res: QueryCurrentValsetResponse = self
+            .query_raw(
+                "/gravity.v1.Query/CurrentValset",
+                QueryCurrentValsetRequest {},
+            )
+            .await?;
+        match res.valset {
+            Some(valset) => Ok(valset),
+            None => Err(CosmosGrpcError::BadResponse("No valset".to_string())),
+        }
+    }
+
+    /// Gets a previously requested validator set by nonce, None if there is no
+    /// validator set with this nonce or it has been pruned
+    pub async fn get_gravity_valset(&self, nonce: u64) -> Result<Option<Valset>, CosmosGrpcError> {
+        let res: QueryValsetRequestResponse = self
+            .query_raw(
+                "/gravity.v1.Query/ValsetRequest",
+                QueryValsetRequestRequest { nonce },
+            )
+            .await?;
+        Ok(res.valset)
+    }
+
+    /// Gets the most recently requested validator sets
+    pub async fn get_gravity_latest_valsets(&self) -> Result<Vec<Valset>, CosmosGrpcError> {
+        let res: QueryValsetsResponse = self
+            .query_raw(
+                "/gravity.v1.Query/LastValsetRequests",
+                QueryLastValsetRequestsRequest {},
+            )
+            .await?;
+        Ok(res.valsets)
+    }
+
+    /// Gets the validator sets the given orchestrator has not yet signed
+    pub async fn get_gravity_pending_valsets(
+        &self,
+        orchestrator: Address,
+    ) -> Result<Vec<Valset>, CosmosGrpcError> {
+        let res: QueryValsetsResponse = self
+            .query_raw(
+                "/gravity.v1.Query/LastPendingValsetRequestByAddr",
+                QueryByAddrRequest {
+                    address: orchestrator.to_bech32(&self.config.prefix).unwrap(),
+                },
+            )
+            .await?;
+        Ok(res.valsets)
+    }
+
+    /// Gets all batches waiting to be relayed to Ethereum
+    pub async fn get_gravity_batches(&self) -> Result<Vec<OutgoingTxBatch>, CosmosGrpcError> {
+        let res: QueryOutgoingTxBatchesResponse = self
+            .query_raw(
+                "/gravity.v1.Query/OutgoingTxBatches",
+                QueryOutgoingTxBatchesRequest {},
+            )
+            .await?;
+        Ok(res.batches)
+    }
+
+    /// Gets the batches the given orchestrator has not yet signed
+    pub async fn get_gravity_pending_batches(
+        &self,
+        orchestrator: Address,
+    ) -> Result<Vec<OutgoingTxBatch>, CosmosGrpcError> {
+        let res: QueryLastPendingBatchRequestByAddrResponse = self
+            .query_raw(
+                "/gravity.v1.Query/LastPendingBatchRequestByAddr",
+                QueryByAddrRequest {
+                    address: orchestrator.to_bech32(&self.config.prefix).unwrap(),
+                },
+            )
+            .await?;
+        Ok(res.batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrivateKey;
+    use prost::Message;
+
+    #[test]
+    fn test_gravity_messages() {
+        let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let address = key.to_address("gravity").unwrap();
+        let amount: Coin = "100ugraviton".parse().unwrap();
+        let fee: Coin = "1ugraviton".parse().unwrap();
+        let eth_dest = "0xD041c41EA1bf0F006ADBb6d2c9ef9D425dE5eaD7";
+        let msg = Msg::send_to_eth(address, eth_dest, amount.clone(), fee, None);
+        assert_eq!(msg.0.type_url, MSG_SEND_TO_ETH_TYPE_URL);
+        let decoded = MsgSendToEth::decode(msg.0.value.as_slice()).unwrap();
+        assert_eq!(decoded.eth_dest, eth_dest);
+        assert_eq!(Coin::from(decoded.amount.unwrap()), amount);
+        assert!(decoded.chain_fee.is_none());
+
+        let batch = OutgoingTxBatch {
+            batch_nonce: 3,
+            token_contract: eth_dest.to_string(),
+            ..Default::default()
+        };
+        let msg = Msg::confirm_batch(address, &batch, eth_dest, &[0xab, 0xcd]);
+        let decoded = MsgConfirmBatch::decode(msg.0.value.as_slice()).unwrap();
+        assert_eq!(decoded.nonce, 3);
+        assert_eq!(decoded.signature, "abcd");
+    }
+}
@@ -0,0 +1,155 @@
+//! The Gravity Bridge module is not included in the proto crate, these mirror the
+//! messages of gravity/v1 msgs.proto, types.proto, batch.proto and query.proto that
+//! orchestrators and bridge users need
+
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+
+/// A member of a validator set as seen by the Ethereum contract
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct BridgeValidator {
+    #[prost(uint64, tag = "1")]
+    pub power: u64,
+    #[prost(string, tag = "2")]
+    pub ethereum_address: String,
+}
+
+/// A snapshot of the validator set that orchestrators sign and relay to Ethereum
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Valset {
+    #[prost(uint64, tag = "1")]
+    pub nonce: u64,
+    #[prost(message, repeated, tag = "2")]
+    pub members: Vec<BridgeValidator>,
+    #[prost(uint64, tag = "3")]
+    pub height: u64,
+    #[prost(string, tag = "4")]
+    pub reward_amount: String,
+    #[prost(string, tag = "5")]
+    pub reward_token: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Erc20Token {
+    #[prost(string, tag = "1")]
+    pub contract: String,
+    #[prost(string, tag = "2")]
+    pub amount: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct OutgoingTransferTx {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(string, tag = "2")]
+    pub sender: String,
+    #[prost(string, tag = "3")]
+    pub dest_address: String,
+    #[prost(message, optional, tag = "4")]
+    pub erc20_token: Option<Erc20Token>,
+    #[prost(message, optional, tag = "5")]
+    pub erc20_fee: Option<Erc20Token>,
+}
+
+/// A batch of transfers of a single ERC20 token out to Ethereum
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct OutgoingTxBatch {
+    #[prost(uint64, tag = "1")]
+    pub batch_nonce: u64,
+    #[prost(uint64, tag = "2")]
+    pub batch_timeout: u64,
+    #[prost(message, repeated, tag = "3")]
+    pub transactions: Vec<OutgoingTransferTx>,
+    #[prost(string, tag = "4")]
+    pub token_contract: String,
+    #[prost(uint64, tag = "5")]
+    pub cosmos_block_created: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgSendToEth {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(string, tag = "2")]
+    pub eth_dest: String,
+    #[prost(message, optional, tag = "3")]
+    pub amount: Option<ProtoCoin>,
+    #[prost(message, optional, tag = "4")]
+    pub bridge_fee: Option<ProtoCoin>,
+    #[prost(message, optional, tag = "5")]
+    pub chain_fee: Option<ProtoCoin>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgRequestBatch {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(string, tag = "2")]
+    pub denom: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgConfirmBatch {
+    #[prost(uint64, tag = "1")]
+    pub nonce: u64,
+    #[prost(string, tag = "2")]
+    pub token_contract: String,
+    #[prost(string, tag = "3")]
+    pub eth_signer: String,
+    #[prost(string, tag = "4")]
+    pub orchestrator: String,
+    #[prost(string, tag = "5")]
+    pub signature: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryCurrentValsetRequest {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryCurrentValsetResponse {
+    #[prost(message, optional, tag = "1")]
+    pub valset: Option<Valset>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryValsetRequestRequest {
+    #[prost(uint64, tag = "1")]
+    pub nonce: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryValsetRequestResponse {
+    #[prost(message, optional, tag = "1")]
+    pub valset: Option<Valset>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryLastValsetRequestsRequest {}
+
+/// The response of both LastValsetRequests and LastPendingValsetRequestByAddr
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryValsetsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub valsets: Vec<Valset>,
+}
+
+/// The request of both LastPendingValsetRequestByAddr and LastPendingBatchRequestByAddr
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryByAddrRequest {
+    #[prost(string, tag = "1")]
+    pub address: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryOutgoingTxBatchesRequest {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryOutgoingTxBatchesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub batches: Vec<OutgoingTxBatch>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryLastPendingBatchRequestByAddrResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub batch: Vec<OutgoingTxBatch>,
+}
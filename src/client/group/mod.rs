@@ -0,0 +1,269 @@
+//! Contains message builders and queries for the Cosmos SDK group module (x/group), available
+//! on SDK 0.46+ chains. Groups are sets of weighted members that control group policy
+//! accounts, proposals of arbitrary messages are executed by a policy once they pass.
+
+use super::PAGE;
+use crate::error::CosmosGrpcError;
+use crate::{Address, Contact, Msg};
+use prost_types::Any;
+
+pub mod proto;
+
+use proto::*;
+pub use proto::{
+    Exec, GroupInfo, GroupMember, GroupPolicyInfo, MemberRequest, Proposal, ProposalStatus,
+    TallyResult, ThresholdDecisionPolicy, VoteOption,
+};
+
+pub const MSG_CREATE_GROUP_TYPE_URL: &str = "/cosmos.group.v1.MsgCreateGroup";
+pub const MSG_CREATE_GROUP_POLICY_TYPE_URL: &str = "/cosmos.group.v1.MsgCreateGroupPolicy";
+pub const MSG_GROUP_SUBMIT_PROPOSAL_TYPE_URL: &str = "/cosmos.group.v1.MsgSubmitProposal";
+pub const MSG_GROUP_VOTE_TYPE_URL: &str = "/cosmos.group.v1.MsgVote";
+pub const MSG_GROUP_EXEC_TYPE_URL: &str = "/cosmos.group.v1.MsgExec";
+pub const THRESHOLD_DECISION_POLICY_TYPE_URL: &str = "/cosmos.group.v1.ThresholdDecisionPolicy";
+pub const PERCENTAGE_DECISION_POLICY_TYPE_URL: &str = "/cosmos.group.v1.PercentageDecisionPolicy";
+
+impl MemberRequest {
+    /// A member with the given weight, weights are decimals for example "1" or "0.5"
+    pub fn new(address: Address, weight: impl ToString) -> Self {
+        MemberRequest {
+            address: address.to_string(),
+            weight: weight.to_string(),
+            metadata: String::new(),
+        }
+    }
+}
+
+fn exec(try_exec: bool) -> i32 {
+    if try_exec {
+        Exec::Try as i32
+    } else {
+        Exec::Unspecified as i32
+    }
+}
+
+impl Msg {
+    /// Creates a MsgCreateGroup, the group id is found in the events of the resulting tx
+    pub fn create_group(admin: Address, members: Vec<MemberRequest>, metadata: String) -> Self {
+        let msg = MsgCreateGroup {
+            admin: admin.to_string(),
+            members,
+            metadata,
+        };
+        Msg::new(MSG_CREATE_GROUP_TYPE_URL, msg)
+    }
+
+    /// Creates a MsgCreateGroupPolicy, `decision_policy` is usually an encoded
+    /// ThresholdDecisionPolicy or PercentageDecisionPolicy
+    pub fn create_group_policy(
+        admin: Address,
+        group_id: u64,
+        metadata: String,
+        decision_policy: Any,
+    ) -> Self {
+        let msg = MsgCreateGroupPolicy {
+            admin: admin.to_string(),
+            group_id,
+            metadata,
+            decision_policy: Some(decision_policy),
+        };
+        Msg::new(MSG_CREATE_GROUP_POLICY_TYPE_URL, msg)
+    }
+
+    /// Creates a group MsgSubmitProposal executing `messages` from the group policy account
+    /// if it passes. If `try_exec` is set the proposal is executed immediately should the
+    /// proposers' votes alone be enough to pass it
+    pub fn group_submit_proposal(
+        group_policy_address: Address,
+        proposers: &[Address],
+        messages: &[Msg],
+        title: String,
+        summary: String,
+        try_exec: bool,
+    ) -> Self {
+        let msg = MsgSubmitProposal {
+            group_policy_address: group_policy_address.to_string(),
+            proposers: proposers.iter().map(|p| p.to_string()).collect(),
+            metadata: String::new(),
+            messages: messages.iter().map(|m| m.0.clone()).collect(),
+            exec: exec(try_exec),
+            title,
+            summary,
+        };
+        Msg::new(MSG_GROUP_SUBMIT_PROPOSAL_TYPE_URL, msg)
+    }
+
+    /// Creates a group MsgVote, if `try_exec` is set the proposal is executed as soon as
+    /// this vote causes it to pass
+    pub fn group_vote(
+        proposal_id: u64,
+        voter: Address,
+        option: VoteOption,
+        try_exec: bool,
+    ) -> Self {
+        let msg = MsgVote {
+            proposal_id,
+            voter: voter.to_string(),
+            option: option as i32,
+            metadata: String::new(),
+            exec: exec(try_exec),
+        };
+        Msg::new(MSG_GROUP_VOTE_TYPE_URL, msg)
+    }
+
+    /// Creates a group MsgExec, executing a proposal that has passed
+    pub fn group_exec(proposal_id: u64, executor: Address) -> Self {
+        let msg = MsgExec {
+            proposal_id,
+            executor: executor.to_string(),
+        };
+        Msg::new(MSG_GROUP_EXEC_TYPE_URL, msg)
+    }
+}
+
+impl Contact {
+    /// Gets the info of a group by id
+    pub async fn get_group_info(&self, group_id: u64) -> Result<GroupInfo, CosmosGrpcError> {
+        let res: QueryGroupInfoResponse = self
+            .query_raw(
+                "/cosmos.group.v1.Query/GroupInfo",
+                QueryGroupInfoRequest { group_id },
+            )
+            .await?;
+        match res.info {
+            Some(info) => Ok(info),
+            None => Err(CosmosGrpcError::BadResponse("No group info".to_string())),
+        }
+    }
+
+    /// Gets the members of a group
+    pub async fn get_group_members(
+        &self,
+        group_id: u64,
+    ) -> Result<Vec<GroupMember>, CosmosGrpcError> {
+        let res: QueryGroupMembersResponse = self
+            .query_raw(
+                "/cosmos.group.v1.Query/GroupMembers",
+                QueryGroupMembersRequest {
+                    group_id,
+                    pagination: PAGE,
+                },
+            )
+            .await?;
+        Ok(res.members)
+    }
+
+    /// Gets the policies (accounts) of a group
+    pub async fn get_group_policies(
+        &self,
+        group_id: u64,
+    ) -> Result<Vec<GroupPolicyInfo>, CosmosGrpcError> {
+        let res: QueryGroupPoliciesResponse = self
+            .query_raw(
+                "/cosmos.group.v1.Query/GroupPoliciesByGroup",
+                QueryGroupPoliciesByGroupRequest {
+                    group_id,
+                    pagination: PAGE,
+                },
+            )
+            .await?;
+        Ok(res.group_policies)
+    }
+
+    /// Gets the group policies administered by the given address
+    pub async fn get_group_policies_by_admin(
+        &self,
+        admin: Address,
+    ) -> Result<Vec<GroupPolicyInfo>, CosmosGrpcError> {
+        let res: QueryGroupPoliciesResponse = self
+            .query_raw(
+                "/cosmos.group.v1.Query/GroupPoliciesByAdmin",
+                QueryGroupPoliciesByAdminRequest {
+                    admin: admin.to_bech32(&self.config.prefix).unwrap(),
+                    pagination: PAGE,
+                },
+            )
+            .await?;
+        Ok(res.group_policies)
+    }
+
+    /// Gets a group proposal by id, proposals are pruned once they are executed or
+    /// the voting period ends so this may return an error for old proposals
+    pub async fn get_group_proposal(&self, proposal_id: u64) -> Result<Proposal, CosmosGrpcError> {
+        let res: QueryProposalResponse = self
+            .query_raw(
+                "/cosmos.group.v1.Query/Proposal",
+                QueryProposalRequest { proposal_id },
+            )
+            .await?;
+        match res.proposal {
+            Some(proposal) => Ok(proposal),
+            None => Err(CosmosGrpcError::BadResponse("No proposal".to_string())),
+        }
+    }
+
+    /// Gets the proposals of a group policy account
+    pub async fn get_group_proposals(
+        &self,
+        group_policy_address: Address,
+    ) -> Result<Vec<Proposal>, CosmosGrpcError> {
+        let res: QueryProposalsByGroupPolicyResponse = self
+            .query_raw(
+                "/cosmos.group.v1.Query/ProposalsByGroupPolicy",
+                QueryProposalsByGroupPolicyRequest {
+                    address: group_policy_address.to_bech32(&self.config.prefix).unwrap(),
+                    pagination: PAGE,
+                },
+            )
+            .await?;
+        Ok(res.proposals)
+    }
+
+    /// Gets the current tally of a group proposal that is still being voted on
+    pub async fn get_group_proposal_tally(
+        &self,
+        proposal_id: u64,
+    ) -> Result<TallyResult, CosmosGrpcError> {
+        let res: QueryTallyResultResponse = self
+            .query_raw(
+                "/cosmos.group.v1.Query/TallyResult",
+                QueryTallyResultRequest { proposal_id },
+            )
+            .await?;
+        match res.tally {
+            Some(tally) => Ok(tally),
+            None => Err(CosmosGrpcError::BadResponse("No tally".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrivateKey;
+    use prost::Message;
+
+    #[test]
+    fn test_group_messages() {
+        let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let address = key.to_address("cosmos").unwrap();
+        let vote = Msg::group_vote(7, address, VoteOption::Yes, true);
+        assert_eq!(vote.0.type_url, MSG_GROUP_VOTE_TYPE_URL);
+        let decoded = MsgVote::decode(vote.0.value.as_slice()).unwrap();
+        assert_eq!(decoded.proposal_id, 7);
+        assert_eq!(decoded.option, VoteOption::Yes as i32);
+        assert_eq!(decoded.exec, Exec::Try as i32);
+
+        let proposal = Msg::group_submit_proposal(
+            address,
+            &[address],
+            std::slice::from_ref(&vote),
+            "title".to_string(),
+            "summary".to_string(),
+            false,
+        );
+        let decoded = MsgSubmitProposal::decode(proposal.0.value.as_slice()).unwrap();
+        assert_eq!(decoded.messages, vec![vote.0]);
+        assert_eq!(decoded.proposers, vec![address.to_string()]);
+    }
+}
@@ -0,0 +1,334 @@
+//! The x/group module (Cosmos SDK 0.46+) is not included in the proto crate, these
+//! mirror the messages of cosmos/group/v1 types.proto, tx.proto and query.proto
+//! that deep_space uses
+
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
+use prost_types::{Any, Duration, Timestamp};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum VoteOption {
+    Unspecified = 0,
+    Yes = 1,
+    Abstain = 2,
+    No = 3,
+    NoWithVeto = 4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ProposalStatus {
+    Unspecified = 0,
+    Submitted = 1,
+    Accepted = 2,
+    Rejected = 3,
+    Aborted = 4,
+    Withdrawn = 5,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ProposalExecutorResult {
+    Unspecified = 0,
+    NotRun = 1,
+    Success = 2,
+    Failure = 3,
+}
+
+/// Whether a proposal or vote should attempt to execute immediately
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Exec {
+    Unspecified = 0,
+    Try = 1,
+}
+
+/// A member to add to a group, weight is a decimal string
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemberRequest {
+    #[prost(string, tag = "1")]
+    pub address: String,
+    #[prost(string, tag = "2")]
+    pub weight: String,
+    #[prost(string, tag = "3")]
+    pub metadata: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Member {
+    #[prost(string, tag = "1")]
+    pub address: String,
+    #[prost(string, tag = "2")]
+    pub weight: String,
+    #[prost(string, tag = "3")]
+    pub metadata: String,
+    #[prost(message, optional, tag = "4")]
+    pub added_at: Option<Timestamp>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupMember {
+    #[prost(uint64, tag = "1")]
+    pub group_id: u64,
+    #[prost(message, optional, tag = "2")]
+    pub member: Option<Member>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupInfo {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(string, tag = "2")]
+    pub admin: String,
+    #[prost(string, tag = "3")]
+    pub metadata: String,
+    #[prost(uint64, tag = "4")]
+    pub version: u64,
+    #[prost(string, tag = "5")]
+    pub total_weight: String,
+    #[prost(message, optional, tag = "6")]
+    pub created_at: Option<Timestamp>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupPolicyInfo {
+    #[prost(string, tag = "1")]
+    pub address: String,
+    #[prost(uint64, tag = "2")]
+    pub group_id: u64,
+    #[prost(string, tag = "3")]
+    pub admin: String,
+    #[prost(string, tag = "4")]
+    pub metadata: String,
+    #[prost(uint64, tag = "5")]
+    pub version: u64,
+    #[prost(message, optional, tag = "6")]
+    pub decision_policy: Option<Any>,
+    #[prost(message, optional, tag = "7")]
+    pub created_at: Option<Timestamp>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DecisionPolicyWindows {
+    #[prost(message, optional, tag = "1")]
+    pub voting_period: Option<Duration>,
+    #[prost(message, optional, tag = "2")]
+    pub min_execution_period: Option<Duration>,
+}
+
+/// A proposal passes once the sum of yes votes reaches threshold, a decimal string
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ThresholdDecisionPolicy {
+    #[prost(string, tag = "1")]
+    pub threshold: String,
+    #[prost(message, optional, tag = "2")]
+    pub windows: Option<DecisionPolicyWindows>,
+}
+
+/// A proposal passes once the fraction of yes votes reaches percentage, a decimal string
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PercentageDecisionPolicy {
+    #[prost(string, tag = "1")]
+    pub percentage: String,
+    #[prost(message, optional, tag = "2")]
+    pub windows: Option<DecisionPolicyWindows>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TallyResult {
+    #[prost(string, tag = "1")]
+    pub yes_count: String,
+    #[prost(string, tag = "2")]
+    pub abstain_count: String,
+    #[prost(string, tag = "3")]
+    pub no_count: String,
+    #[prost(string, tag = "4")]
+    pub no_with_veto_count: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Proposal {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(string, tag = "2")]
+    pub group_policy_address: String,
+    #[prost(string, tag = "3")]
+    pub metadata: String,
+    #[prost(string, repeated, tag = "4")]
+    pub proposers: Vec<String>,
+    #[prost(message, optional, tag = "5")]
+    pub submit_time: Option<Timestamp>,
+    #[prost(uint64, tag = "6")]
+    pub group_version: u64,
+    #[prost(uint64, tag = "7")]
+    pub group_policy_version: u64,
+    #[prost(enumeration = "ProposalStatus", tag = "8")]
+    pub status: i32,
+    #[prost(message, optional, tag = "9")]
+    pub final_tally_result: Option<TallyResult>,
+    #[prost(message, optional, tag = "10")]
+    pub voting_period_end: Option<Timestamp>,
+    #[prost(enumeration = "ProposalExecutorResult", tag = "11")]
+    pub executor_result: i32,
+    #[prost(message, repeated, tag = "12")]
+    pub messages: Vec<Any>,
+    #[prost(string, tag = "13")]
+    pub title: String,
+    #[prost(string, tag = "14")]
+    pub summary: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgCreateGroup {
+    #[prost(string, tag = "1")]
+    pub admin: String,
+    #[prost(message, repeated, tag = "2")]
+    pub members: Vec<MemberRequest>,
+    #[prost(string, tag = "3")]
+    pub metadata: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgCreateGroupPolicy {
+    #[prost(string, tag = "1")]
+    pub admin: String,
+    #[prost(uint64, tag = "2")]
+    pub group_id: u64,
+    #[prost(string, tag = "3")]
+    pub metadata: String,
+    #[prost(message, optional, tag = "4")]
+    pub decision_policy: Option<Any>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgSubmitProposal {
+    #[prost(string, tag = "1")]
+    pub group_policy_address: String,
+    #[prost(string, repeated, tag = "2")]
+    pub proposers: Vec<String>,
+    #[prost(string, tag = "3")]
+    pub metadata: String,
+    #[prost(message, repeated, tag = "4")]
+    pub messages: Vec<Any>,
+    #[prost(enumeration = "Exec", tag = "5")]
+    pub exec: i32,
+    #[prost(string, tag = "6")]
+    pub title: String,
+    #[prost(string, tag = "7")]
+    pub summary: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgVote {
+    #[prost(uint64, tag = "1")]
+    pub proposal_id: u64,
+    #[prost(string, tag = "2")]
+    pub voter: String,
+    #[prost(enumeration = "VoteOption", tag = "3")]
+    pub option: i32,
+    #[prost(string, tag = "4")]
+    pub metadata: String,
+    #[prost(enumeration = "Exec", tag = "5")]
+    pub exec: i32,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgExec {
+    #[prost(uint64, tag = "1")]
+    pub proposal_id: u64,
+    #[prost(string, tag = "2")]
+    pub executor: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryGroupInfoRequest {
+    #[prost(uint64, tag = "1")]
+    pub group_id: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryGroupInfoResponse {
+    #[prost(message, optional, tag = "1")]
+    pub info: Option<GroupInfo>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryGroupMembersRequest {
+    #[prost(uint64, tag = "1")]
+    pub group_id: u64,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageRequest>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryGroupMembersResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub members: Vec<GroupMember>,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageResponse>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryGroupPoliciesByGroupRequest {
+    #[prost(uint64, tag = "1")]
+    pub group_id: u64,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageRequest>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryGroupPoliciesByAdminRequest {
+    #[prost(string, tag = "1")]
+    pub admin: String,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageRequest>,
+}
+
+/// The response of both GroupPoliciesByGroup and GroupPoliciesByAdmin
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryGroupPoliciesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub group_policies: Vec<GroupPolicyInfo>,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageResponse>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryProposalRequest {
+    #[prost(uint64, tag = "1")]
+    pub proposal_id: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryProposalResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proposal: Option<Proposal>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryProposalsByGroupPolicyRequest {
+    #[prost(string, tag = "1")]
+    pub address: String,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageRequest>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryProposalsByGroupPolicyResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub proposals: Vec<Proposal>,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageResponse>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryTallyResultRequest {
+    #[prost(uint64, tag = "1")]
+    pub proposal_id: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryTallyResultResponse {
+    #[prost(message, optional, tag = "1")]
+    pub tally: Option<TallyResult>,
+}
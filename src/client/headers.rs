@@ -0,0 +1,125 @@
+//! Custom gRPC metadata sent with every request, such as the API keys required by
+//! hosted node providers or tracing headers
+
+use crate::client::Contact;
+use crate::error::CosmosGrpcError;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tonic::codegen::http::header::{HeaderMap, HeaderName, HeaderValue};
+use tonic::codegen::http::Request;
+use tonic::codegen::Service;
+
+/// Wraps a gRPC channel, adding the configured headers to every request and replacing
+/// any value already set for the same name
+#[derive(Debug, Clone)]
+pub struct HeaderChannel<S> {
+    inner: S,
+    headers: Arc<HeaderMap>,
+}
+
+impl<S> HeaderChannel<S> {
+    pub(crate) fn new(inner: S, headers: Arc<HeaderMap>) -> Self {
+        HeaderChannel { inner, headers }
+    }
+}
+
+impl<S, B> Service<Request<B>> for HeaderChannel<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        for (name, value) in self.headers.iter() {
+            request.headers_mut().insert(name, value.clone());
+        }
+        self.inner.call(request)
+    }
+}
+
+impl Contact {
+    /// Returns a Contact that sends `name: value` with every request, replacing any
+    /// earlier value for `name`. The returned Contact shares this one's connection and
+    /// this one is unaffected, so the same call both configures a Contact for every
+    /// request and overrides a header for a single call, for example
+    /// `contact.with_header("x-api-key", key)?.get_balances(address)`. Values are
+    /// marked sensitive so they are not printed by debug output
+    pub fn with_header(&self, name: &str, value: &str) -> Result<Contact, CosmosGrpcError> {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| CosmosGrpcError::BadInput(format!("Invalid header name {}", e)))?;
+        let mut value = HeaderValue::from_str(value)
+            .map_err(|e| CosmosGrpcError::BadInput(format!("Invalid header value {}", e)))?;
+        value.set_sensitive(true);
+        let mut contact = self.clone();
+        Arc::make_mut(&mut contact.headers).insert(name, value);
+        Ok(contact)
+    }
+
+    /// Returns a Contact that no longer sends the header `name`
+    pub fn without_header(&self, name: &str) -> Contact {
+        let mut contact = self.clone();
+        Arc::make_mut(&mut contact.headers).remove(name);
+        contact
+    }
+
+    /// The custom headers sent with every request
+    pub fn get_headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+    use std::time::Duration;
+
+    /// Returns the headers of each request
+    struct Echo;
+
+    impl Service<Request<()>> for Echo {
+        type Response = HeaderMap;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<()>) -> Self::Future {
+            ready(Ok(request.headers().clone()))
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_headers() {
+        let contact = Contact::new("http://localhost:9090", Duration::from_secs(1), "cosmos")
+            .unwrap()
+            .with_header("x-api-key", "secret")
+            .unwrap()
+            .with_header("x-trace", "a")
+            .unwrap();
+        let per_call = contact.with_header("x-trace", "b").unwrap();
+        assert_eq!(contact.get_headers()["x-trace"], "a");
+        assert!(contact.with_header("bad header", "a").is_err());
+
+        let mut channel = HeaderChannel::new(Echo, Arc::new(per_call.get_headers().clone()));
+        let mut request = Request::new(());
+        request
+            .headers_mut()
+            .insert("x-trace", HeaderValue::from_static("c"));
+        let headers = channel.call(request).await.unwrap();
+        assert_eq!(headers["x-api-key"], "secret");
+        assert_eq!(headers["x-trace"], "b");
+
+        let removed = per_call.without_header("x-api-key");
+        assert!(removed.get_headers().get("x-api-key").is_none());
+    }
+}
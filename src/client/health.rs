@@ -0,0 +1,129 @@
+//! Detects nodes that have fallen behind the chain. A lagging node checks transactions
+//! against stale account state, so a sequence that is correct for the chain is rejected
+//! as a mismatch, or accepted and then never included. The lag is estimated from how
+//! long ago the node's latest block was produced, which also grows while the chain
+//! itself is halted
+
+use crate::client::block_time::DEFAULT_BLOCK_TIME_SAMPLE;
+use crate::client::types::LatestBlock;
+use crate::error::CosmosGrpcError;
+use crate::Contact;
+use std::time::{Duration, SystemTime};
+
+/// The state of the node a Contact is connected to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeHealth {
+    pub latest_height: u64,
+    pub latest_block_time: SystemTime,
+    /// True if the node reports that it is still syncing
+    pub catching_up: bool,
+    /// How long before the check the latest block was produced, zero if the local
+    /// clock is behind the block time
+    pub block_age: Duration,
+    /// The average block time of the node's recent blocks
+    pub average_block_time: Duration,
+}
+
+impl NodeHealth {
+    /// The estimated number of blocks the chain has produced that the node has not
+    /// seen yet, zero while the latest block is younger than one block time
+    pub fn blocks_behind(&self) -> u64 {
+        let blocks = self.block_age.as_nanos() / self.average_block_time.as_nanos().max(1);
+        blocks.min(u64::MAX as u128) as u64
+    }
+
+    /// True if the node is syncing or more than `max_lag` blocks behind
+    pub fn is_lagging(&self, max_lag: u64) -> bool {
+        self.catching_up || self.blocks_behind() > max_lag
+    }
+}
+
+impl Contact {
+    /// Makes broadcasts through this Contact fail with `NodeLagging` if the node is
+    /// more than `max_lag` blocks behind, and with `NodeNotSynced` if it is syncing.
+    /// None disables the check, which is the default since it costs a query per send
+    pub fn with_max_node_lag(mut self, max_lag: Option<u64>) -> Self {
+        self.max_node_lag = max_lag;
+        self
+    }
+
+    pub fn get_max_node_lag(&self) -> Option<u64> {
+        self.max_node_lag
+    }
+
+    /// Checks the latest height, sync status and block staleness of the node
+    pub async fn check_node_health(&self) -> Result<NodeHealth, CosmosGrpcError> {
+        let (block, catching_up) = match self.get_latest_block().await? {
+            LatestBlock::Latest { block } => (block, false),
+            LatestBlock::Syncing { block } => (block, true),
+            LatestBlock::WaitingToStart => return Err(CosmosGrpcError::ChainNotRunning),
+        };
+        let header = block
+            .header
+            .ok_or_else(|| CosmosGrpcError::BadResponse("Null block header?".to_string()))?;
+        let latest_block_time = header
+            .time
+            .and_then(|t| SystemTime::try_from(t).ok())
+            .ok_or_else(|| CosmosGrpcError::BadResponse("Invalid block time".to_string()))?;
+        let block_age = SystemTime::now()
+            .duration_since(latest_block_time)
+            .unwrap_or_default();
+        let sample = self
+            .get_block_time_sample(DEFAULT_BLOCK_TIME_SAMPLE)
+            .await?;
+        Ok(NodeHealth {
+            latest_height: header.height as u64,
+            latest_block_time,
+            catching_up,
+            block_age,
+            average_block_time: sample.average_block_time,
+        })
+    }
+
+    /// Fails if a maximum node lag is set and the node exceeds it
+    pub(crate) async fn check_node_lag(&self) -> Result<(), CosmosGrpcError> {
+        let max_lag = match self.max_node_lag {
+            Some(max_lag) => max_lag,
+            None => return Ok(()),
+        };
+        let health = self.check_node_health().await?;
+        if health.catching_up {
+            Err(CosmosGrpcError::NodeNotSynced)
+        } else if health.is_lagging(max_lag) {
+            Err(CosmosGrpcError::NodeLagging {
+                blocks_behind: health.blocks_behind(),
+                max_lag,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_node_lag() {
+        let mut health = NodeHealth {
+            latest_height: 1_000,
+            latest_block_time: UNIX_EPOCH,
+            catching_up: false,
+            block_age: Duration::from_secs(5),
+            average_block_time: Duration::from_secs(6),
+        };
+        assert_eq!(health.blocks_behind(), 0);
+        assert!(!health.is_lagging(0));
+
+        health.block_age = Duration::from_secs(61);
+        assert_eq!(health.blocks_behind(), 10);
+        assert!(health.is_lagging(9));
+        assert!(!health.is_lagging(10));
+
+        health.block_age = Duration::ZERO;
+        health.catching_up = true;
+        assert!(health.is_lagging(u64::MAX));
+    }
+}
@@ -0,0 +1,132 @@
+//! Queries against historical state. The Cosmos SDK answers a gRPC query at the height
+//! given in the `x-cosmos-block-height` header, as long as the node has not pruned
+//! the state of that height
+
+use crate::client::Contact;
+use std::task::{Context, Poll};
+use tonic::codegen::http::header::HeaderValue;
+use tonic::codegen::http::Request;
+use tonic::codegen::Service;
+
+/// The gRPC header the Cosmos SDK reads the query height from
+pub const BLOCK_HEIGHT_HEADER: &str = "x-cosmos-block-height";
+
+/// The tx service broadcasts and simulates against the current state, a height header
+/// would make the node simulate against historical state
+const TX_SERVICE: &str = "/cosmos.tx.v1beta1.Service/";
+
+/// Wraps a gRPC channel, setting the block height header on every request except
+/// those to the tx service when a height is set
+#[derive(Debug, Clone)]
+pub struct HeightChannel<S> {
+    inner: S,
+    height: Option<u64>,
+}
+
+impl<S> HeightChannel<S> {
+    pub(crate) fn new(inner: S, height: Option<u64>) -> Self {
+        HeightChannel { inner, height }
+    }
+}
+
+impl<S, B> Service<Request<B>> for HeightChannel<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        let tx_service = request.uri().path().starts_with(TX_SERVICE);
+        if let (Some(height), false) = (self.height, tx_service) {
+            request
+                .headers_mut()
+                .insert(BLOCK_HEIGHT_HEADER, HeaderValue::from(height));
+        }
+        self.inner.call(request)
+    }
+}
+
+impl Contact {
+    /// Returns a Contact that queries the state of the chain as of block `height`, for
+    /// example to look up balances or delegations for a report. The Contact shares its
+    /// connection with this one, but does not use the query cache
+    ///
+    /// Transactions can still be sent through the returned Contact, broadcasts and
+    /// simulations skip the height header and the sequence is looked up at the latest
+    /// block. Every other query made while sending, such as the block params used to
+    /// check the gas limit, is answered at `height`, so prefer sending through a Contact
+    /// that is not pinned to a height
+    pub fn at_height(&self, height: u64) -> Contact {
+        let mut contact = self.clone();
+        contact.height = Some(height);
+        contact
+    }
+
+    /// This Contact with the query height cleared, for the lookups that have to reflect
+    /// the current state even when the caller queries historical state
+    pub(crate) fn at_latest(&self) -> Contact {
+        let mut contact = self.clone();
+        contact.height = None;
+        contact
+    }
+
+    /// The height queries are made at, None for the latest block
+    pub fn get_query_height(&self) -> Option<u64> {
+        self.height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+
+    /// Returns the height header of each request
+    struct Echo;
+
+    impl Service<Request<()>> for Echo {
+        type Response = Option<HeaderValue>;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<()>) -> Self::Future {
+            ready(Ok(request.headers().get(BLOCK_HEIGHT_HEADER).cloned()))
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_height_header() {
+        let mut channel = HeightChannel::new(Echo, Some(1234));
+        let header = channel.call(Request::new(())).await.unwrap();
+        assert_eq!(header.unwrap(), "1234");
+
+        let mut channel = HeightChannel::new(Echo, None);
+        assert!(channel.call(Request::new(())).await.unwrap().is_none());
+
+        // broadcasts and simulations always act on the current state
+        let mut channel = HeightChannel::new(Echo, Some(1234));
+        for path in [
+            "/cosmos.tx.v1beta1.Service/BroadcastTx",
+            "/cosmos.tx.v1beta1.Service/Simulate",
+        ] {
+            let request = Request::builder().uri(path).body(()).unwrap();
+            assert!(channel.call(request).await.unwrap().is_none());
+        }
+        let request = Request::builder()
+            .uri("/cosmos.auth.v1beta1.Query/Account")
+            .body(())
+            .unwrap();
+        assert_eq!(channel.call(request).await.unwrap().unwrap(), "1234");
+    }
+}
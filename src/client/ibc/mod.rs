@@ -0,0 +1,472 @@
+//! Contains queries for IBC client, connection, channel and packet state as well as the
+//! packet relaying messages. Together with the proofs returned alongside each value this
+//! is enough to relay packets over a single channel
+
+use super::PAGE;
+use crate::error::CosmosGrpcError;
+use crate::utils::encode_any;
+use crate::{Address, Contact, Msg};
+use cosmos_sdk_proto::ibc::core::channel::v1::query_client::QueryClient as ChannelQueryClient;
+use cosmos_sdk_proto::ibc::core::channel::v1::{
+    Channel, MsgAcknowledgement, MsgRecvPacket, MsgTimeout, Packet, PacketState,
+    QueryChannelRequest, QueryNextSequenceReceiveRequest, QueryPacketAcknowledgementRequest,
+    QueryPacketCommitmentRequest, QueryPacketCommitmentsRequest, QueryPacketReceiptRequest,
+    QueryUnreceivedAcksRequest, QueryUnreceivedPacketsRequest,
+};
+use cosmos_sdk_proto::ibc::core::client::v1::query_client::QueryClient as ClientQueryClient;
+use cosmos_sdk_proto::ibc::core::client::v1::{Height, MsgUpdateClient, QueryClientStateRequest};
+use cosmos_sdk_proto::ibc::core::connection::v1::query_client::QueryClient as ConnectionQueryClient;
+use cosmos_sdk_proto::ibc::core::connection::v1::{ConnectionEnd, QueryConnectionRequest};
+use cosmos_sdk_proto::ibc::lightclients::tendermint::v1::ClientState as TendermintClientState;
+use cosmos_sdk_proto::ibc::lightclients::tendermint::v1::Header as TendermintHeader;
+use prost::{DecodeError, Message};
+use prost_types::Any;
+
+pub const TENDERMINT_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.tendermint.v1.ClientState";
+pub const TENDERMINT_HEADER_TYPE_URL: &str = "/ibc.lightclients.tendermint.v1.Header";
+pub const MSG_RECV_PACKET_TYPE_URL: &str = "/ibc.core.channel.v1.MsgRecvPacket";
+pub const MSG_ACKNOWLEDGEMENT_TYPE_URL: &str = "/ibc.core.channel.v1.MsgAcknowledgement";
+pub const MSG_TIMEOUT_TYPE_URL: &str = "/ibc.core.channel.v1.MsgTimeout";
+pub const MSG_UPDATE_CLIENT_TYPE_URL: &str = "/ibc.core.client.v1.MsgUpdateClient";
+
+/// A value read from the IBC store along with the merkle proof of that value at
+/// `proof_height`, the proof is what the counterparty chain verifies when relaying
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proven<T> {
+    pub value: T,
+    pub proof: Vec<u8>,
+    pub proof_height: Height,
+}
+
+impl<T> Proven<T> {
+    fn new(
+        value: Option<T>,
+        proof: Vec<u8>,
+        proof_height: Option<Height>,
+        name: &str,
+    ) -> Result<Self, CosmosGrpcError> {
+        match (value, proof_height) {
+            (Some(value), Some(proof_height)) => Ok(Proven {
+                value,
+                proof,
+                proof_height,
+            }),
+            (None, _) => Err(CosmosGrpcError::BadResponse(format!(
+                "No {} returned",
+                name
+            ))),
+            (_, None) => Err(CosmosGrpcError::BadResponse(format!(
+                "No proof height returned for {}",
+                name
+            ))),
+        }
+    }
+}
+
+/// The state of an IBC light client, only Tendermint clients are decoded, other
+/// client types such as solo machines are returned as is
+#[derive(Debug, Clone, PartialEq)]
+pub enum IbcClientState {
+    Tendermint(TendermintClientState),
+    Other(Any),
+}
+
+impl IbcClientState {
+    pub fn decode_any(value: Any) -> Result<Self, DecodeError> {
+        match value.type_url.as_str() {
+            TENDERMINT_CLIENT_STATE_TYPE_URL => Ok(IbcClientState::Tendermint(
+                TendermintClientState::decode(value.value.as_slice())?,
+            )),
+            _ => Ok(IbcClientState::Other(value)),
+        }
+    }
+
+    /// The chain id this client tracks, None for non Tendermint clients
+    pub fn chain_id(&self) -> Option<&str> {
+        match self {
+            IbcClientState::Tendermint(state) => Some(&state.chain_id),
+            IbcClientState::Other(_) => None,
+        }
+    }
+
+    /// The latest height of the counterparty chain known to this client
+    pub fn latest_height(&self) -> Option<Height> {
+        match self {
+            IbcClientState::Tendermint(state) => state.latest_height.clone(),
+            IbcClientState::Other(_) => None,
+        }
+    }
+
+    /// True if this client has been frozen for misbehaviour, a frozen client can no
+    /// longer be updated and packets over it can not be relayed
+    pub fn is_frozen(&self) -> bool {
+        match self {
+            IbcClientState::Tendermint(state) => match &state.frozen_height {
+                Some(height) => height.revision_number != 0 || height.revision_height != 0,
+                None => false,
+            },
+            IbcClientState::Other(_) => false,
+        }
+    }
+}
+
+impl Msg {
+    /// Creates a MsgRecvPacket delivering `packet` to the destination chain, `commitment`
+    /// is the packet commitment queried from the source chain with `get_packet_commitment`
+    /// at a height the destination chain's client has already been updated to
+    pub fn recv_packet(packet: Packet, commitment: &Proven<Vec<u8>>, signer: Address) -> Self {
+        let msg = MsgRecvPacket {
+            packet: Some(packet),
+            proof_commitment: commitment.proof.clone(),
+            proof_height: Some(commitment.proof_height.clone()),
+            signer: signer.to_string(),
+        };
+        Msg::new(MSG_RECV_PACKET_TYPE_URL, msg)
+    }
+
+    /// Creates a MsgAcknowledgement relaying the acknowledgement of `packet` back to the
+    /// source chain, `acknowledgement` is queried from the destination chain with
+    /// `get_packet_acknowledgement`
+    pub fn acknowledgement(
+        packet: Packet,
+        acknowledgement: &Proven<Vec<u8>>,
+        signer: Address,
+    ) -> Self {
+        let msg = MsgAcknowledgement {
+            packet: Some(packet),
+            acknowledgement: acknowledgement.value.clone(),
+            proof_acked: acknowledgement.proof.clone(),
+            proof_height: Some(acknowledgement.proof_height.clone()),
+            signer: signer.to_string(),
+        };
+        Msg::new(MSG_ACKNOWLEDGEMENT_TYPE_URL, msg)
+    }
+
+    /// Creates a MsgTimeout for a `packet` that was never received on the destination
+    /// chain. `unreceived` proves this on the destination chain, for an unordered channel
+    /// it is the packet receipt from `get_packet_receipt` and for an ordered channel the
+    /// next receive sequence from `get_next_sequence_recv`
+    pub fn timeout<T>(
+        packet: Packet,
+        unreceived: &Proven<T>,
+        next_sequence_recv: u64,
+        signer: Address,
+    ) -> Self {
+        let msg = MsgTimeout {
+            packet: Some(packet),
+            proof_unreceived: unreceived.proof.clone(),
+            proof_height: Some(unreceived.proof_height.clone()),
+            next_sequence_recv,
+            signer: signer.to_string(),
+        };
+        Msg::new(MSG_TIMEOUT_TYPE_URL, msg)
+    }
+
+    /// Creates a MsgUpdateClient advancing a Tendermint light client to the height of
+    /// `header`, this must be included before any message carrying proofs at that height
+    pub fn update_client(client_id: &str, header: TendermintHeader, signer: Address) -> Self {
+        let msg = MsgUpdateClient {
+            client_id: client_id.to_string(),
+            header: Some(encode_any(header, TENDERMINT_HEADER_TYPE_URL)),
+            signer: signer.to_string(),
+        };
+        Msg::new(MSG_UPDATE_CLIENT_TYPE_URL, msg)
+    }
+}
+
+impl Contact {
+    /// Gets the state of an IBC light client on this chain by client id, for example
+    /// 07-tendermint-0
+    pub async fn get_ibc_client_state(
+        &self,
+        client_id: &str,
+    ) -> Result<Proven<IbcClientState>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = ClientQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .client_state(QueryClientStateRequest {
+                    client_id: client_id.to_string(),
+                })
+                .await?
+                .into_inner();
+            let state = match res.client_state {
+                Some(state) => Some(IbcClientState::decode_any(state)?),
+                None => None,
+            };
+            Proven::new(state, res.proof, res.proof_height, "client state")
+        })
+        .await
+    }
+
+    /// Gets an IBC connection end on this chain by connection id, for example connection-0
+    pub async fn get_ibc_connection(
+        &self,
+        connection_id: &str,
+    ) -> Result<Proven<ConnectionEnd>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = ConnectionQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .connection(QueryConnectionRequest {
+                    connection_id: connection_id.to_string(),
+                })
+                .await?
+                .into_inner();
+            Proven::new(res.connection, res.proof, res.proof_height, "connection")
+        })
+        .await
+    }
+
+    /// Gets an IBC channel end on this chain, for example port transfer and channel-0
+    pub async fn get_ibc_channel(
+        &self,
+        port_id: &str,
+        channel_id: &str,
+    ) -> Result<Proven<Channel>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = ChannelQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .channel(QueryChannelRequest {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                })
+                .await?
+                .into_inner();
+            Proven::new(res.channel, res.proof, res.proof_height, "channel")
+        })
+        .await
+    }
+
+    /// Gets the sequence of the next packet this chain expects to receive on an
+    /// ordered channel
+    pub async fn get_next_sequence_recv(
+        &self,
+        port_id: &str,
+        channel_id: &str,
+    ) -> Result<Proven<u64>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = ChannelQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .next_sequence_receive(QueryNextSequenceReceiveRequest {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                })
+                .await?
+                .into_inner();
+            Proven::new(
+                Some(res.next_sequence_receive),
+                res.proof,
+                res.proof_height,
+                "next sequence receive",
+            )
+        })
+        .await
+    }
+
+    /// Gets the commitments of packets sent from this chain over the given channel that
+    /// have not yet been acknowledged or timed out
+    pub async fn get_packet_commitments(
+        &self,
+        port_id: &str,
+        channel_id: &str,
+    ) -> Result<Vec<PacketState>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = ChannelQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .packet_commitments(QueryPacketCommitmentsRequest {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    pagination: PAGE,
+                })
+                .await?
+                .into_inner();
+            Ok(res.commitments)
+        })
+        .await
+    }
+
+    /// Gets the commitment of a single packet sent from this chain, used to prove the
+    /// packet to the destination chain in a MsgRecvPacket
+    pub async fn get_packet_commitment(
+        &self,
+        port_id: &str,
+        channel_id: &str,
+        sequence: u64,
+    ) -> Result<Proven<Vec<u8>>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = ChannelQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .packet_commitment(QueryPacketCommitmentRequest {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    sequence,
+                })
+                .await?
+                .into_inner();
+            Proven::new(
+                Some(res.commitment),
+                res.proof,
+                res.proof_height,
+                "packet commitment",
+            )
+        })
+        .await
+    }
+
+    /// Gets the acknowledgement this chain wrote for a received packet, used to relay
+    /// the acknowledgement back to the source chain in a MsgAcknowledgement
+    pub async fn get_packet_acknowledgement(
+        &self,
+        port_id: &str,
+        channel_id: &str,
+        sequence: u64,
+    ) -> Result<Proven<Vec<u8>>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = ChannelQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .packet_acknowledgement(QueryPacketAcknowledgementRequest {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    sequence,
+                })
+                .await?
+                .into_inner();
+            Proven::new(
+                Some(res.acknowledgement),
+                res.proof,
+                res.proof_height,
+                "packet acknowledgement",
+            )
+        })
+        .await
+    }
+
+    /// Gets whether this chain has received a packet on an unordered channel, if not
+    /// the proof of absence is used to time the packet out on the source chain
+    pub async fn get_packet_receipt(
+        &self,
+        port_id: &str,
+        channel_id: &str,
+        sequence: u64,
+    ) -> Result<Proven<bool>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = ChannelQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .packet_receipt(QueryPacketReceiptRequest {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    sequence,
+                })
+                .await?
+                .into_inner();
+            Proven::new(
+                Some(res.received),
+                res.proof,
+                res.proof_height,
+                "packet receipt",
+            )
+        })
+        .await
+    }
+
+    /// Filters `sequences`, the sequences of packets committed on the counterparty chain,
+    /// down to those that this chain has not yet received. Note that `port_id` and
+    /// `channel_id` are the identifiers of the channel end on this chain
+    pub async fn get_unreceived_packets(
+        &self,
+        port_id: &str,
+        channel_id: &str,
+        sequences: &[u64],
+    ) -> Result<Vec<u64>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = ChannelQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .unreceived_packets(QueryUnreceivedPacketsRequest {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    packet_commitment_sequences: sequences.to_vec(),
+                })
+                .await?
+                .into_inner();
+            Ok(res.sequences)
+        })
+        .await
+    }
+
+    /// Filters `sequences`, the sequences of packets sent from this chain that the
+    /// counterparty has acknowledged, down to those whose acknowledgement has not yet
+    /// been relayed back to this chain
+    pub async fn get_unreceived_acks(
+        &self,
+        port_id: &str,
+        channel_id: &str,
+        sequences: &[u64],
+    ) -> Result<Vec<u64>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = ChannelQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .unreceived_acks(QueryUnreceivedAcksRequest {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    packet_ack_sequences: sequences.to_vec(),
+                })
+                .await?
+                .into_inner();
+            Ok(res.sequences)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_client_state() {
+        let state = TendermintClientState {
+            chain_id: "osmosis-1".to_string(),
+            latest_height: Some(Height {
+                revision_number: 1,
+                revision_height: 100,
+            }),
+            frozen_height: Some(Height::default()),
+            ..Default::default()
+        };
+        let any = Any {
+            type_url: TENDERMINT_CLIENT_STATE_TYPE_URL.to_string(),
+            value: state.encode_to_vec(),
+        };
+        let decoded = IbcClientState::decode_any(any).unwrap();
+        assert_eq!(decoded.chain_id(), Some("osmosis-1"));
+        assert_eq!(decoded.latest_height().unwrap().revision_height, 100);
+        assert!(!decoded.is_frozen());
+    }
+
+    #[test]
+    fn test_relay_messages() {
+        let key = crate::PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let signer = key.to_address("cosmos").unwrap();
+        let packet = Packet {
+            sequence: 5,
+            source_port: "transfer".to_string(),
+            source_channel: "channel-0".to_string(),
+            ..Default::default()
+        };
+        let ack = Proven {
+            value: vec![1, 2, 3],
+            proof: vec![4, 5, 6],
+            proof_height: Height {
+                revision_number: 4,
+                revision_height: 1000,
+            },
+        };
+        let msg = Msg::acknowledgement(packet.clone(), &ack, signer);
+        assert_eq!(msg.0.type_url, MSG_ACKNOWLEDGEMENT_TYPE_URL);
+        let decoded = MsgAcknowledgement::decode(msg.0.value.as_slice()).unwrap();
+        assert_eq!(decoded.packet, Some(packet));
+        assert_eq!(decoded.acknowledgement, ack.value);
+        assert_eq!(decoded.proof_acked, ack.proof);
+        assert_eq!(decoded.proof_height, Some(ack.proof_height));
+        assert_eq!(decoded.signer, signer.to_string());
+    }
+}
@@ -0,0 +1,281 @@
+//! Contains helpers for interchain queries, reading the state of another chain over IBC.
+//! Enabled with the `icq` feature
+//!
+//! Two styles are supported. Neutron style kv queries are registered with a message,
+//! relayers then keep the values of the registered keys up to date and the result is
+//! queried from the local chain. ICS-31 async-icq, as used by Quicksilver and Osmosis,
+//! sends the queries in an IBC packet to the `icqhost` port and the responses come back
+//! in the acknowledgement. Both read raw store keys, built with the `KvKey` constructors
+
+use crate::error::CosmosGrpcError;
+use crate::{Address, Coin, Contact, Msg, Uint256};
+use cosmos_sdk_proto::cosmos::staking::v1beta1::Delegation;
+use cosmos_sdk_proto::tendermint::abci::{RequestQuery, ResponseQuery};
+use prost::Message;
+use serde_json::{json, Value};
+
+pub mod proto;
+
+use proto::*;
+pub use proto::{KvKey, QueryResult, StorageValue};
+
+/// The port of the ICS-31 host module
+pub const ICQ_HOST_PORT: &str = "icqhost";
+/// The channel version of ICS-31
+pub const ICQ_VERSION: &str = "icq-1";
+
+pub const MSG_REGISTER_INTERCHAIN_QUERY_TYPE_URL: &str =
+    "/neutron.interchainqueries.MsgRegisterInterchainQuery";
+pub const MSG_REMOVE_INTERCHAIN_QUERY_TYPE_URL: &str =
+    "/neutron.interchainqueries.MsgRemoveInterchainQueryRequest";
+
+const BALANCES_PREFIX: u8 = 0x02;
+const VALIDATORS_PREFIX: u8 = 0x21;
+const DELEGATION_PREFIX: u8 = 0x31;
+
+/// Appends `bytes` preceded by its length, the way the SDK stores addresses in keys
+fn push_length_prefixed(key: &mut Vec<u8>, bytes: &[u8]) {
+    key.push(bytes.len() as u8);
+    key.extend_from_slice(bytes);
+}
+
+impl KvKey {
+    /// The balance of `denom` held by `address`, decode the value with
+    /// `parse_balance_value`. Uses the length prefixed key layout of SDK 0.46 and later
+    pub fn bank_balance(address: Address, denom: &str) -> KvKey {
+        let mut key = vec![BALANCES_PREFIX];
+        push_length_prefixed(&mut key, address.as_bytes());
+        key.extend_from_slice(denom.as_bytes());
+        KvKey {
+            path: "bank".to_string(),
+            key,
+        }
+    }
+
+    /// The delegation of `delegator` to `validator`, decode the value with
+    /// `parse_delegation_value`
+    pub fn staking_delegation(delegator: Address, validator: Address) -> KvKey {
+        let mut key = vec![DELEGATION_PREFIX];
+        push_length_prefixed(&mut key, delegator.as_bytes());
+        push_length_prefixed(&mut key, validator.as_bytes());
+        KvKey {
+            path: "staking".to_string(),
+            key,
+        }
+    }
+
+    /// A validator, the value is a proto encoded Validator. Needed to convert delegation
+    /// shares into tokens
+    pub fn staking_validator(validator: Address) -> KvKey {
+        let mut key = vec![VALIDATORS_PREFIX];
+        push_length_prefixed(&mut key, validator.as_bytes());
+        KvKey {
+            path: "staking".to_string(),
+            key,
+        }
+    }
+
+    /// The ICS-31 query reading this key, `height` zero reads the latest state
+    pub fn to_request_query(&self, height: i64, prove: bool) -> RequestQuery {
+        RequestQuery {
+            data: self.key.clone(),
+            path: format!("/store/{}/key", self.path),
+            height,
+            prove,
+        }
+    }
+}
+
+/// Decodes a balance read with `KvKey::bank_balance`. SDK 0.47 and later store the
+/// amount as a decimal string, earlier versions a proto encoded Coin. A missing key is
+/// a zero balance
+pub fn parse_balance_value(denom: &str, value: &[u8]) -> Result<Coin, CosmosGrpcError> {
+    if value.is_empty() {
+        return Ok(Coin {
+            amount: Uint256::zero(),
+            denom: denom.to_string(),
+        });
+    }
+    if value.iter().all(u8::is_ascii_digit) {
+        // only ascii digits, so this is valid utf8
+        let amount = std::str::from_utf8(value).unwrap();
+        return Ok(Coin {
+            amount: parse_amount(amount)?,
+            denom: denom.to_string(),
+        });
+    }
+    let coin = cosmos_sdk_proto::cosmos::base::v1beta1::Coin::decode(value)?;
+    Ok(Coin {
+        amount: parse_amount(&coin.amount)?,
+        denom: coin.denom,
+    })
+}
+
+fn parse_amount(amount: &str) -> Result<Uint256, CosmosGrpcError> {
+    Uint256::from_dec_or_hex_str_restricted(amount)
+        .map_err(|error| CosmosGrpcError::ParseError { error })
+}
+
+/// Decodes a delegation read with `KvKey::staking_delegation`, None if there is none
+pub fn parse_delegation_value(value: &[u8]) -> Result<Option<Delegation>, CosmosGrpcError> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Delegation::decode(value)?))
+}
+
+/// Encodes ICS-31 packet data carrying `requests`, ready to be sent to the `icqhost`
+/// port. Like other IBC application packets it is JSON
+pub fn encode_icq_packet(requests: Vec<RequestQuery>, memo: &str) -> Vec<u8> {
+    let query = CosmosQuery { requests };
+    let packet = json!({
+        "data": base64::encode(query.encode_to_vec()),
+        "memo": memo,
+    });
+    serde_json::to_vec(&packet).unwrap()
+}
+
+/// Decodes ICS-31 packet data into its requests and memo
+pub fn decode_icq_packet(packet: &[u8]) -> Result<(Vec<RequestQuery>, String), CosmosGrpcError> {
+    let packet: Value = serde_json::from_slice(packet)
+        .map_err(|e| CosmosGrpcError::BadInput(format!("Invalid ICQ packet {}", e)))?;
+    let data = json_bytes(&packet, "data").map_err(CosmosGrpcError::BadInput)?;
+    let memo = packet["memo"].as_str().unwrap_or_default().to_string();
+    Ok((CosmosQuery::decode(data.as_slice())?.requests, memo))
+}
+
+/// Encodes the acknowledgement a host sends for a successful ICS-31 packet
+pub fn encode_icq_ack(responses: Vec<ResponseQuery>) -> Vec<u8> {
+    let response = CosmosResponse { responses };
+    let ack = json!({ "data": base64::encode(response.encode_to_vec()) });
+    let result = json!({ "result": base64::encode(serde_json::to_vec(&ack).unwrap()) });
+    serde_json::to_vec(&result).unwrap()
+}
+
+/// Decodes the acknowledgement of an ICS-31 packet into the responses, in the order of
+/// the requests. An error acknowledgement is returned as BadResponse
+pub fn decode_icq_ack(ack: &[u8]) -> Result<Vec<ResponseQuery>, CosmosGrpcError> {
+    let bad = |e: String| CosmosGrpcError::BadResponse(format!("Invalid ICQ ack {}", e));
+    let ack: Value = serde_json::from_slice(ack).map_err(|e| bad(e.to_string()))?;
+    if let Some(error) = ack["error"].as_str() {
+        return Err(CosmosGrpcError::BadResponse(format!(
+            "ICQ packet failed {}",
+            error
+        )));
+    }
+    let result = json_bytes(&ack, "result").map_err(bad)?;
+    let result: Value = serde_json::from_slice(&result).map_err(|e| bad(e.to_string()))?;
+    let data = json_bytes(&result, "data").map_err(bad)?;
+    Ok(CosmosResponse::decode(data.as_slice())?.responses)
+}
+
+/// Reads a base64 encoded bytes field of a JSON object
+fn json_bytes(value: &Value, name: &str) -> Result<Vec<u8>, String> {
+    match value[name].as_str() {
+        Some(encoded) => base64::decode(encoded).map_err(|e| format!("{} {}", name, e)),
+        None => Err(format!("missing {}", name)),
+    }
+}
+
+impl Msg {
+    /// Creates a Neutron MsgRegisterInterchainQuery for the values of `keys` on the
+    /// chain at the other end of `connection_id`, updated every `update_period` blocks.
+    /// Registration requires a deposit set by the interchainqueries module params
+    pub fn register_kv_interchain_query(
+        connection_id: String,
+        keys: Vec<KvKey>,
+        update_period: u64,
+        sender: Address,
+    ) -> Self {
+        let msg = MsgRegisterInterchainQuery {
+            query_type: "kv".to_string(),
+            keys,
+            transactions_filter: String::new(),
+            connection_id,
+            update_period,
+            sender: sender.to_string(),
+        };
+        Msg::new(MSG_REGISTER_INTERCHAIN_QUERY_TYPE_URL, msg)
+    }
+
+    /// Creates a Neutron MsgRemoveInterchainQueryRequest, returning the deposit
+    pub fn remove_interchain_query(query_id: u64, sender: Address) -> Self {
+        let msg = MsgRemoveInterchainQueryRequest {
+            query_id,
+            sender: sender.to_string(),
+        };
+        Msg::new(MSG_REMOVE_INTERCHAIN_QUERY_TYPE_URL, msg)
+    }
+}
+
+impl Contact {
+    /// Gets the latest result of a Neutron kv interchain query
+    pub async fn get_interchain_query_result(
+        &self,
+        query_id: u64,
+    ) -> Result<QueryResult, CosmosGrpcError> {
+        let res: QueryRegisteredQueryResultResponse = self
+            .query_raw(
+                "/neutron.interchainqueries.Query/QueryResult",
+                QueryRegisteredQueryResultRequest { query_id },
+            )
+            .await?;
+        match res.result {
+            Some(result) => Ok(result),
+            None => Err(CosmosGrpcError::BadResponse(format!(
+                "No result for interchain query {}",
+                query_id
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kv_keys() {
+        let address = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let validator = Address::from_bytes([2; 20], "cosmosvaloper").unwrap();
+        let key = KvKey::bank_balance(address, "uatom");
+        assert_eq!(key.path, "bank");
+        assert_eq!(key.key[..2], [0x02, 20]);
+        assert_eq!(&key.key[22..], b"uatom");
+        let key = KvKey::staking_delegation(address, validator);
+        assert_eq!(key.key.len(), 1 + 21 + 21);
+        assert_eq!(key.key[22], 20);
+        assert_eq!(key.key[23], 2);
+        let query = key.to_request_query(0, true);
+        assert_eq!(query.path, "/store/staking/key");
+
+        let coin = parse_balance_value("uatom", b"12500").unwrap();
+        assert_eq!(coin.to_string(), "12500uatom");
+        let legacy = cosmos_sdk_proto::cosmos::base::v1beta1::Coin {
+            denom: "uatom".to_string(),
+            amount: "7".to_string(),
+        };
+        let coin = parse_balance_value("uatom", &legacy.encode_to_vec()).unwrap();
+        assert_eq!(coin.to_string(), "7uatom");
+        assert!(parse_balance_value("uatom", b"").unwrap().amount == Uint256::zero());
+    }
+
+    #[test]
+    fn test_icq_packet() {
+        let address = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let request = KvKey::bank_balance(address, "uatom").to_request_query(0, false);
+        let packet = encode_icq_packet(vec![request.clone()], "memo");
+        let (requests, memo) = decode_icq_packet(&packet).unwrap();
+        assert_eq!(requests, vec![request]);
+        assert_eq!(memo, "memo");
+
+        let response = ResponseQuery {
+            value: b"100".to_vec(),
+            height: 12,
+            ..Default::default()
+        };
+        let ack = encode_icq_ack(vec![response.clone()]);
+        assert_eq!(decode_icq_ack(&ack).unwrap(), vec![response]);
+        assert!(decode_icq_ack(br#"{"error":"ABCI code: 4"}"#).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! Hand written prost types for interchain queries, the Neutron interchainqueries module
+//! tx.proto and query.proto and the ICS-31 async-icq packet types of ibc-apps. Fields
+//! deep_space does not use, such as the proofs and blocks of query results, are
+//! skipped when decoding
+
+use cosmos_sdk_proto::tendermint::abci::{RequestQuery, ResponseQuery};
+
+/// A key in a store of the remote chain, `path` is the store name such as `bank`
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct KvKey {
+    #[prost(string, tag = "1")]
+    pub path: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub key: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgRegisterInterchainQuery {
+    /// `kv` or `tx`
+    #[prost(string, tag = "1")]
+    pub query_type: String,
+    #[prost(message, repeated, tag = "2")]
+    pub keys: Vec<KvKey>,
+    /// A JSON filter for `tx` queries, empty for `kv` queries
+    #[prost(string, tag = "3")]
+    pub transactions_filter: String,
+    #[prost(string, tag = "4")]
+    pub connection_id: String,
+    /// The number of blocks between updates of the result
+    #[prost(uint64, tag = "5")]
+    pub update_period: u64,
+    #[prost(string, tag = "6")]
+    pub sender: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgRemoveInterchainQueryRequest {
+    #[prost(uint64, tag = "1")]
+    pub query_id: u64,
+    #[prost(string, tag = "2")]
+    pub sender: String,
+}
+
+/// The value of one registered key, as submitted by a relayer
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StorageValue {
+    #[prost(string, tag = "1")]
+    pub storage_prefix: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub value: Vec<u8>,
+}
+
+/// The latest result of a registered kv query
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryResult {
+    #[prost(message, repeated, tag = "1")]
+    pub kv_results: Vec<StorageValue>,
+    /// The remote chain height the values were read at
+    #[prost(uint64, tag = "3")]
+    pub height: u64,
+    #[prost(uint64, tag = "4")]
+    pub revision: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryRegisteredQueryResultRequest {
+    #[prost(uint64, tag = "1")]
+    pub query_id: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryRegisteredQueryResultResponse {
+    #[prost(message, optional, tag = "1")]
+    pub result: Option<QueryResult>,
+}
+
+/// The queries carried by an ICS-31 packet
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CosmosQuery {
+    #[prost(message, repeated, tag = "1")]
+    pub requests: Vec<RequestQuery>,
+}
+
+/// The responses carried by an ICS-31 acknowledgement, in request order
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CosmosResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub responses: Vec<ResponseQuery>,
+}
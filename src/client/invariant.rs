@@ -21,7 +21,7 @@ impl Contact {
     /// use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
     /// use deep_space::{Coin, client::Contact, Fee, MessageArgs, Msg, PrivateKey};
     /// use std::time::Duration;
-    /// let private_key = PrivateKey::from_secret("mySecret".as_bytes());
+    /// let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
     /// let contact = Contact::new("https:://your-grpc-server", Duration::from_secs(5), "prefix").unwrap();
     /// // future must be awaited in tokio runtime
     /// contact.invariant_check("gravity", "module-balance", private_key);
@@ -33,7 +33,7 @@ impl Contact {
         private_key: PrivateKey,
     ) -> Result<SimulateResponse, CosmosGrpcError> {
         trace!("Creating simulated invariant transaction");
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
 
         let verify = MsgVerifyInvariant {
             sender: our_address.to_string(),
@@ -42,7 +42,7 @@ impl Contact {
         };
         let msg = Msg::new(MSG_VERIFY_INVARIANT_TYPE, verify);
         trace!("Submitting simulation");
-        self.simulate_tx(&[msg], private_key).await
+        self.simulate_tx(&[msg], &private_key).await
     }
 
     /// A utility function which executes the specified invariant and returns the TxResponse if one is given
@@ -60,7 +60,7 @@ impl Contact {
     /// use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
     /// use deep_space::{Coin, client::Contact, Fee, MessageArgs, Msg, PrivateKey, u256};
     /// use std::time::Duration;
-    /// let private_key = PrivateKey::from_secret("mySecret".as_bytes());
+    /// let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
     /// let public_key = private_key.to_public_key("cosmospub").unwrap();
     /// let address = public_key.to_address();
     /// let coin = Coin {
@@ -80,7 +80,7 @@ impl Contact {
         private_key: PrivateKey,
     ) -> Result<TxResponse, CosmosGrpcError> {
         trace!("Creating chain-halting invariant transaction");
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
 
         let verify = MsgVerifyInvariant {
             sender: our_address.to_string(),
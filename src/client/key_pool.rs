@@ -0,0 +1,270 @@
+//! A pool of hot keys for sending transactions concurrently. Transactions from one
+//! account must be included in sequence order, so a single key can only have one
+//! transaction in flight at a time without risking sequence mismatches. The pool hands
+//! each sender its own key and returns it once that key's transaction is included
+
+use crate::error::CosmosGrpcError;
+use crate::mnemonic::SecretMnemonic;
+use crate::{Address, Coin, Contact, Msg, PrivateKey, Uint256};
+use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+struct KeyPoolInner {
+    keys: Vec<(PrivateKey, Address)>,
+    available: Mutex<VecDeque<usize>>,
+    permits: Arc<Semaphore>,
+}
+
+/// A set of keys handed out round robin, a key is held by one sender at a time. Cloning
+/// the pool shares the same keys
+#[derive(Clone)]
+pub struct KeyPool {
+    inner: Arc<KeyPoolInner>,
+}
+
+/// A key checked out of a KeyPool, it goes to the back of the queue when dropped
+pub struct PooledKey {
+    inner: Arc<KeyPoolInner>,
+    index: usize,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl PooledKey {
+    pub fn private_key(&self) -> &PrivateKey {
+        &self.inner.keys[self.index].0
+    }
+
+    pub fn address(&self) -> Address {
+        self.inner.keys[self.index].1
+    }
+
+    /// The position of this key in the pool
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl Drop for PooledKey {
+    fn drop(&mut self) {
+        // the permit is released after this runs, so a waiter always finds a key
+        self.inner.available.lock().unwrap().push_back(self.index);
+    }
+}
+
+impl KeyPool {
+    /// Creates a pool of `keys`, with addresses using `prefix`
+    pub fn new(keys: Vec<PrivateKey>, prefix: &str) -> Result<KeyPool, CosmosGrpcError> {
+        if keys.is_empty() {
+            return Err(CosmosGrpcError::BadInput("KeyPool needs a key".to_string()));
+        }
+        let mut pool = Vec::new();
+        for key in keys {
+            let address = key.to_address(prefix)?;
+            pool.push((key, address));
+        }
+        let count = pool.len();
+        Ok(KeyPool {
+            inner: Arc::new(KeyPoolInner {
+                keys: pool,
+                available: Mutex::new((0..count).collect()),
+                permits: Arc::new(Semaphore::new(count)),
+            }),
+        })
+    }
+
+    /// Derives the keys at `account_path`/0/i for every i in `indexes`, the same
+    /// addresses as [Contact::sweep] uses. Pass the account path of the chain config,
+    /// such as m/44'/118'/0'
+    pub fn from_mnemonic(
+        mnemonic: &SecretMnemonic,
+        passphrase: &str,
+        account_path: &str,
+        indexes: Range<u32>,
+        prefix: &str,
+    ) -> Result<KeyPool, CosmosGrpcError> {
+        let seed = mnemonic.to_seed(passphrase);
+        let mut keys = Vec::new();
+        for i in indexes {
+            keys.push(PrivateKey::from_seed_and_path(
+                &format!("{}/0/{}", account_path, i),
+                &seed,
+            )?);
+        }
+        KeyPool::new(keys, prefix)
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.keys.is_empty()
+    }
+
+    /// The addresses of every key in the pool, in pool order
+    pub fn addresses(&self) -> Vec<Address> {
+        self.inner
+            .keys
+            .iter()
+            .map(|(_, address)| *address)
+            .collect()
+    }
+
+    /// Waits for a free key, the one that has been idle the longest
+    pub async fn acquire(&self) -> PooledKey {
+        // the semaphore is never closed, so this can't panic
+        let permit = self.inner.permits.clone().acquire_owned().await.unwrap();
+        self.checkout(permit)
+    }
+
+    /// Takes a free key without waiting, None if every key is in use
+    pub fn try_acquire(&self) -> Option<PooledKey> {
+        let permit = self.inner.permits.clone().try_acquire_owned().ok()?;
+        Some(self.checkout(permit))
+    }
+
+    fn checkout(&self, permit: OwnedSemaphorePermit) -> PooledKey {
+        // each permit matches a queued index, so the queue can't be empty here
+        let index = self.inner.available.lock().unwrap().pop_front().unwrap();
+        PooledKey {
+            inner: self.inner.clone(),
+            index,
+            _permit: permit,
+        }
+    }
+}
+
+/// Plans the top ups bringing every balance below `min_balance` back up to
+/// `target_balance`, in the order of `balances`
+fn plan_top_ups(
+    balances: &[(Address, Uint256)],
+    min_balance: Uint256,
+    target_balance: Uint256,
+) -> Vec<(Address, Uint256)> {
+    balances
+        .iter()
+        .filter(|(_, balance)| *balance < min_balance)
+        .filter_map(|(address, balance)| {
+            let top_up = target_balance.checked_sub(*balance)?;
+            if top_up == Uint256::zero() {
+                None
+            } else {
+                Some((*address, top_up))
+            }
+        })
+        .collect()
+}
+
+impl Contact {
+    /// Sends the messages built by `build` from a key of `pool`, `build` is passed the
+    /// address of the key so that it can name it as the signer. The key is held until
+    /// the transaction is included or `wait_timeout` passes, so every key has at most one
+    /// transaction in flight and concurrent callers never race on a sequence
+    pub async fn send_message_from_pool<F>(
+        &self,
+        pool: &KeyPool,
+        build: F,
+        memo: Option<String>,
+        fee_coin: &[Coin],
+        wait_timeout: Duration,
+    ) -> Result<TxResponse, CosmosGrpcError>
+    where
+        F: FnOnce(Address) -> Vec<Msg>,
+    {
+        let key = pool.acquire().await;
+        let messages = build(key.address());
+        self.send_message(
+            &messages,
+            memo,
+            fee_coin,
+            Some(wait_timeout),
+            key.private_key().clone(),
+        )
+        .await
+    }
+
+    /// Tops up every key of `pool` holding less than `min_balance` of its denom to
+    /// `target_balance`, sending from `funder` in a single transaction. Returns None if
+    /// no key needed funds
+    pub async fn rebalance_key_pool(
+        &self,
+        pool: &KeyPool,
+        funder: PrivateKey,
+        min_balance: Coin,
+        target_balance: Uint256,
+        fee_coin: &[Coin],
+        wait_timeout: Option<Duration>,
+    ) -> Result<Option<TxResponse>, CosmosGrpcError> {
+        let funder_address = funder.to_address(&self.config.prefix)?;
+        let mut balances = Vec::new();
+        for address in pool.addresses() {
+            let balance = self
+                .get_balance(address, min_balance.denom.clone())
+                .await?
+                .map(|coin| coin.amount)
+                .unwrap_or_default();
+            balances.push((address, balance));
+        }
+
+        let top_ups = plan_top_ups(&balances, min_balance.amount, target_balance);
+        if top_ups.is_empty() {
+            return Ok(None);
+        }
+        let msgs: Vec<Msg> = top_ups
+            .into_iter()
+            .map(|(address, amount)| {
+                let send = MsgSend {
+                    from_address: funder_address.to_string(),
+                    to_address: address.to_string(),
+                    amount: vec![Coin {
+                        amount,
+                        denom: min_balance.denom.clone(),
+                    }
+                    .into()],
+                };
+                Msg::new("/cosmos.bank.v1beta1.MsgSend", send)
+            })
+            .collect();
+        let res = self
+            .send_message(&msgs, None, fee_coin, wait_timeout, funder)
+            .await?;
+        Ok(Some(res))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_key_pool_round_robin() {
+        let keys = (0..3u8)
+            .map(|i| PrivateKey::from_secret_hkdf(&[i], b"", b"").unwrap())
+            .collect();
+        let pool = KeyPool::new(keys, "cosmos").unwrap();
+        let first = pool.acquire().await;
+        let second = pool.acquire().await;
+        let third = pool.acquire().await;
+        assert_eq!((first.index(), second.index(), third.index()), (0, 1, 2));
+        assert!(pool.try_acquire().is_none());
+        drop(second);
+        drop(first);
+        assert_eq!(pool.acquire().await.index(), 1);
+        assert_eq!(pool.acquire().await.index(), 0);
+        assert!(KeyPool::new(Vec::new(), "cosmos").is_err());
+
+        let a = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let b = Address::from_bytes([2; 20], "cosmos").unwrap();
+        let top_ups = plan_top_ups(
+            &[(a, Uint256::from_u64(5)), (b, Uint256::from_u64(50))],
+            Uint256::from_u64(10),
+            Uint256::from_u64(100),
+        );
+        assert_eq!(top_ups, vec![(a, Uint256::from_u64(95))]);
+    }
+}
@@ -0,0 +1,191 @@
+//! Response size limits and compression for the gRPC channel. Large queries such as
+//! validator sets or wasm contract state can be tens of megabytes, the limit turns an
+//! oversized response into a clear error instead of unbounded memory use
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tonic::codegen::http::{HeaderMap, Request, Response};
+use tonic::codegen::{Body, Bytes, Future, Service, StdError};
+use tonic::Status;
+
+/// The gRPC header listing the encodings a client accepts responses in
+const ACCEPT_ENCODING_HEADER: &str = "grpc-accept-encoding";
+
+/// The compression of gRPC responses. tonic 0.7 only supports gzip, zstd requires a
+/// newer tonic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GrpcCompression {
+    /// Responses are sent uncompressed, which trades bandwidth for node CPU
+    None,
+    /// Responses are gzip compressed when the node supports it
+    #[default]
+    Gzip,
+}
+
+/// Wraps a gRPC channel, limiting the size of response bodies and removing the accepted
+/// encodings from requests when compression is disabled
+#[derive(Debug, Clone)]
+pub struct LimitedChannel<S> {
+    inner: S,
+    max_decoding_message_size: Option<usize>,
+    compression: GrpcCompression,
+}
+
+impl<S> LimitedChannel<S> {
+    pub(crate) fn new(
+        inner: S,
+        max_decoding_message_size: Option<usize>,
+        compression: GrpcCompression,
+    ) -> Self {
+        LimitedChannel {
+            inner,
+            max_decoding_message_size,
+            compression,
+        }
+    }
+}
+
+impl<S, R, B> Service<Request<R>> for LimitedChannel<S>
+where
+    S: Service<Request<R>, Response = Response<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<LimitedBody<B>>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<R>) -> Self::Future {
+        if self.compression == GrpcCompression::None {
+            request.headers_mut().remove(ACCEPT_ENCODING_HEADER);
+        }
+        let limit = self.max_decoding_message_size;
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let response = future.await?;
+            Ok(response.map(|body| LimitedBody {
+                inner: body,
+                limit,
+                received: 0,
+            }))
+        })
+    }
+}
+
+/// A response body that fails with `ResourceExhausted` once more than the limit has
+/// been received
+#[derive(Debug)]
+pub struct LimitedBody<B> {
+    inner: B,
+    limit: Option<usize>,
+    received: usize,
+}
+
+// some generated clients require a default body
+impl<B: Default> Default for LimitedBody<B> {
+    fn default() -> Self {
+        LimitedBody {
+            inner: B::default(),
+            limit: None,
+            received: 0,
+        }
+    }
+}
+
+impl<B> Body for LimitedBody<B>
+where
+    B: Body<Data = Bytes> + Unpin,
+    B::Error: Into<StdError>,
+{
+    type Data = Bytes;
+    type Error = StdError;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let data = match Pin::new(&mut self.inner).poll_data(cx) {
+            Poll::Ready(Some(Ok(data))) => data,
+            Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e.into()))),
+            Poll::Ready(None) => return Poll::Ready(None),
+            Poll::Pending => return Poll::Pending,
+        };
+        self.received = self.received.saturating_add(data.len());
+        match self.limit {
+            Some(limit) if self.received > limit => {
+                Poll::Ready(Some(Err(Box::new(Status::resource_exhausted(format!(
+                    "gRPC response is larger than the max decoding message size of {} bytes, \
+                     see ConnectionConfig::max_decoding_message_size",
+                    limit
+                ))))))
+            }
+            _ => Poll::Ready(Some(Ok(data))),
+        }
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner)
+            .poll_trailers(cx)
+            .map_err(Into::into)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::poll_fn;
+
+    /// A body that yields a single chunk
+    struct Chunk(Option<Bytes>);
+
+    impl Body for Chunk {
+        type Data = Bytes;
+        type Error = Status;
+
+        fn poll_data(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Option<Result<Bytes, Status>>> {
+            Poll::Ready(self.0.take().map(Ok))
+        }
+
+        fn poll_trailers(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<Option<HeaderMap>, Status>> {
+            Poll::Ready(Ok(None))
+        }
+    }
+
+    async fn read(limit: Option<usize>, len: usize) -> Result<usize, StdError> {
+        let mut body = LimitedBody {
+            inner: Chunk(Some(Bytes::from(vec![0u8; len]))),
+            limit,
+            received: 0,
+        };
+        let mut total = 0;
+        while let Some(data) = poll_fn(|cx| Pin::new(&mut body).poll_data(cx)).await {
+            total += data?.len();
+        }
+        Ok(total)
+    }
+
+    #[actix_rt::test]
+    async fn test_limited_body() {
+        assert_eq!(read(None, 5000).await.unwrap(), 5000);
+        assert_eq!(read(Some(5000), 5000).await.unwrap(), 5000);
+        let err = read(Some(4999), 5000).await.unwrap_err();
+        let status = err.downcast::<Status>().unwrap();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    }
+}
@@ -0,0 +1,102 @@
+//! Optional instrumentation of Contact using the [metrics](https://docs.rs/metrics) facade.
+//! Nothing is recorded until the application installs a recorder, for example
+//! `metrics-exporter-prometheus`, so the choice of backend is left to the caller.
+
+use crate::error::{BroadcastError, CosmosGrpcError, SdkErrorCode};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tonic::body::BoxBody;
+use tonic::codegen::http::{Request, Response};
+use tonic::codegen::{Future, Service};
+use tonic::transport::{Body, Channel, Error as TransportError};
+
+/// Histogram of gRPC request latency in seconds, labeled by `method` and `status`
+pub const QUERY_LATENCY_SECONDS: &str = "deep_space_grpc_request_duration_seconds";
+/// Counter of gRPC requests, labeled by `method` and `status`
+pub const QUERY_TOTAL: &str = "deep_space_grpc_requests_total";
+/// Counter of transactions broadcast, labeled by `result`
+pub const BROADCAST_TOTAL: &str = "deep_space_broadcast_total";
+/// Counter of transactions rejected for an account sequence mismatch
+pub const SEQUENCE_MISMATCH_TOTAL: &str = "deep_space_sequence_mismatch_total";
+/// Counter of gRPC connections re-established after the initial connection
+pub const RECONNECT_TOTAL: &str = "deep_space_reconnect_total";
+
+/// A gRPC channel that records the latency and status of every request made through it
+#[derive(Debug, Clone)]
+pub struct MeteredChannel {
+    inner: Channel,
+}
+
+impl MeteredChannel {
+    pub fn new(inner: Channel) -> Self {
+        MeteredChannel { inner }
+    }
+}
+
+impl Service<Request<BoxBody>> for MeteredChannel {
+    type Response = Response<Body>;
+    type Error = TransportError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Service::poll_ready(&mut self.inner, cx)
+    }
+
+    fn call(&mut self, request: Request<BoxBody>) -> Self::Future {
+        let method = request.uri().path().to_string();
+        let future = Service::call(&mut self.inner, request);
+        Box::pin(async move {
+            let start = Instant::now();
+            let res = future.await;
+            let status = match &res {
+                // errors are usually sent as a trailers only response, putting the
+                // status in the headers, otherwise the status arrives with the body
+                Ok(response) => match response.headers().get("grpc-status") {
+                    Some(code) => code.to_str().unwrap_or("unknown").to_string(),
+                    None => "0".to_string(),
+                },
+                Err(_) => "transport_error".to_string(),
+            };
+            record_query(method, status, start.elapsed());
+            res
+        })
+    }
+}
+
+fn record_query(method: String, status: String, elapsed: Duration) {
+    ::metrics::histogram!(QUERY_LATENCY_SECONDS, "method" => method.clone(), "status" => status.clone())
+        .record(elapsed.as_secs_f64());
+    ::metrics::counter!(QUERY_TOTAL, "method" => method, "status" => status).increment(1);
+}
+
+/// Records the outcome of a transaction broadcast
+pub(crate) fn record_broadcast(res: &Result<TxResponse, CosmosGrpcError>) {
+    let result = match res {
+        Ok(_) => "success",
+        Err(CosmosGrpcError::TransactionFailed {
+            reason: BroadcastError::NotIncluded,
+            ..
+        }) => "failure",
+        Err(CosmosGrpcError::TransactionFailed { reason, .. }) => {
+            if matches!(reason, BroadcastError::SequenceMismatch { .. })
+                || matches!(
+                    reason.sdk_error(),
+                    Some(SdkErrorCode::ErrWrongSequence | SdkErrorCode::ErrInvalidSequence)
+                )
+            {
+                ::metrics::counter!(SEQUENCE_MISMATCH_TOTAL).increment(1);
+            }
+            "rejected"
+        }
+        Err(CosmosGrpcError::InsufficientFees { .. }) => "rejected",
+        Err(_) => "failure",
+    };
+    ::metrics::counter!(BROADCAST_TOTAL, "result" => result).increment(1);
+}
+
+/// Records that a connection was re-established
+pub(crate) fn record_reconnect() {
+    ::metrics::counter!(RECONNECT_TOTAL).increment(1);
+}
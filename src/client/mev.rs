@@ -0,0 +1,225 @@
+//! Submission of transaction bundles to MEV auctions, either to a Skip style sidecar
+//! over JSON-RPC or on chain through a block-sdk MsgAuctionBid. Enabled with the `mev`
+//! feature
+//!
+//! A bundle is a list of signed TxRaw bytes that must be included in order at the top
+//! of a block, the searcher pays for that position with a bid
+
+use crate::coin::Fee;
+use crate::error::CosmosGrpcError;
+use crate::{Address, Coin, Contact, Msg, PrivateKey, PublicKey};
+use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+pub const MSG_AUCTION_BID_TYPE_URL: &str = "/sdk.auction.v1.MsgAuctionBid";
+
+/// MsgAuctionBid from the block-sdk x/auction module, bids for the top of the next
+/// block with the bundled transactions
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgAuctionBid {
+    #[prost(string, tag = "1")]
+    pub bidder: String,
+    #[prost(message, optional, tag = "2")]
+    pub bid: Option<ProtoCoin>,
+    /// Signed TxRaw bytes, included in this order after the bid
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    pub transactions: Vec<Vec<u8>>,
+}
+
+impl Msg {
+    /// Creates a MsgAuctionBid bidding `bid` for the top of block position with the
+    /// provided signed transactions
+    pub fn auction_bid(bidder: Address, bid: Coin, transactions: Vec<Vec<u8>>) -> Self {
+        let msg = MsgAuctionBid {
+            bidder: bidder.to_string(),
+            bid: Some(bid.into()),
+            transactions,
+        };
+        Msg::new(MSG_AUCTION_BID_TYPE_URL, msg)
+    }
+}
+
+/// Signed transactions to submit as a bundle to a sidecar auction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bundle {
+    pub transactions: Vec<Vec<u8>>,
+    /// The height the bundle is bidding for, usually the current height plus one
+    pub desired_height: u64,
+}
+
+/// A bundle along with the searcher's proof that they submitted it, the signature is
+/// over the sha256 of the concatenated transactions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedBundle {
+    pub bundle: Bundle,
+    pub public_key: PublicKey,
+    pub signature: [u8; 64],
+}
+
+impl Bundle {
+    /// The digest the searcher signs
+    pub fn digest(&self) -> [u8; 32] {
+        Sha256::digest(self.transactions.concat()).into()
+    }
+
+    pub fn sign(self, private_key: &PrivateKey) -> Result<SignedBundle, CosmosGrpcError> {
+        let public_key = private_key.to_public_key(PublicKey::DEFAULT_PREFIX)?;
+        let signature = private_key.sign_compact(self.digest())?;
+        Ok(SignedBundle {
+            bundle: self,
+            public_key,
+            signature,
+        })
+    }
+}
+
+impl SignedBundle {
+    /// The params of a `broadcast_bundle_sync` call, transactions, height, public key
+    /// and signature with the binary values base64 encoded
+    pub fn to_params(&self) -> Value {
+        let transactions: Vec<String> = self
+            .bundle
+            .transactions
+            .iter()
+            .map(base64::encode)
+            .collect();
+        json!([
+            transactions,
+            self.bundle.desired_height.to_string(),
+            base64::encode(self.public_key.to_vec()),
+            base64::encode(self.signature),
+        ])
+    }
+}
+
+/// A JSON-RPC client for a Skip style bundle sidecar, or any relay accepting the same
+/// `broadcast_bundle_sync` call
+#[derive(Debug, Clone)]
+pub struct MevRelay {
+    url: String,
+    method: String,
+    client: reqwest::Client,
+}
+
+impl MevRelay {
+    pub fn new(url: &str, timeout: Duration) -> Result<Self, CosmosGrpcError> {
+        let client = match reqwest::Client::builder().timeout(timeout).build() {
+            Ok(c) => c,
+            Err(e) => return Err(CosmosGrpcError::BadInput(format!("{}", e))),
+        };
+        Ok(MevRelay {
+            url: url.trim_end_matches('/').to_string(),
+            method: "broadcast_bundle_sync".to_string(),
+            client,
+        })
+    }
+
+    /// Uses a different JSON-RPC method name, for relays that accept the same params
+    pub fn with_method(mut self, method: impl Into<String>) -> Self {
+        self.method = method.into();
+        self
+    }
+
+    /// Submits a signed bundle, returning the result field of the relay's response
+    pub async fn submit_bundle(&self, bundle: &SignedBundle) -> Result<Value, CosmosGrpcError> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": self.method,
+            "params": bundle.to_params(),
+        });
+        let res = match self.client.post(&self.url).json(&body).send().await {
+            Ok(res) => res,
+            Err(e) => {
+                return Err(CosmosGrpcError::BadResponse(format!(
+                    "Failed to submit bundle to {} {}",
+                    self.url, e
+                )))
+            }
+        };
+        let mut res: Value = match res.json().await {
+            Ok(res) => res,
+            Err(e) => {
+                return Err(CosmosGrpcError::BadStruct(format!(
+                    "Failed to parse bundle response {}",
+                    e
+                )))
+            }
+        };
+        if let Some(error) = res.get("error").filter(|e| !e.is_null()) {
+            return Err(CosmosGrpcError::BadResponse(format!(
+                "Bundle rejected {}",
+                error
+            )));
+        }
+        Ok(res["result"].take())
+    }
+}
+
+impl Contact {
+    /// Signs a transaction paying `bid` to the auction's `auction_house` address, which
+    /// sidecar auctions expect as the last transaction of a bundle. `sequence_offset` is
+    /// the number of transactions from the same account earlier in the bundle
+    pub async fn build_bid_payment(
+        &self,
+        auction_house: Address,
+        bid: Coin,
+        fee: Fee,
+        sequence_offset: u64,
+        private_key: &PrivateKey,
+    ) -> Result<Vec<u8>, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.config.prefix)?;
+        let mut args = self.get_message_args(our_address, fee).await?;
+        args.sequence += sequence_offset;
+        let send = MsgSend {
+            from_address: our_address.to_string(),
+            to_address: auction_house.to_string(),
+            amount: vec![bid.into()],
+        };
+        let msg = Msg::new("/cosmos.bank.v1beta1.MsgSend", send);
+        Ok(private_key.sign_std_msg(&[msg], args, "")?)
+    }
+
+    /// Bids for the top of the next block on a chain running the block-sdk auction,
+    /// `transactions` are signed TxRaw bytes included in order after the bid
+    pub async fn send_auction_bid(
+        &self,
+        bid: Coin,
+        transactions: Vec<Vec<u8>>,
+        fee: Coin,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.config.prefix)?;
+        let msg = Msg::auction_bid(our_address, bid, transactions);
+        self.send_message(&[msg], None, &[fee], wait_timeout, private_key)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_bundle_params() {
+        let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let bundle = Bundle {
+            transactions: vec![vec![1, 2], vec![3]],
+            desired_height: 100,
+        };
+        assert_eq!(bundle.digest(), <[u8; 32]>::from(Sha256::digest([1, 2, 3])));
+        let signed = bundle.clone().sign(&private_key).unwrap();
+        let params = signed.to_params();
+        assert_eq!(params[0], json!(["AQI=", "Aw=="]));
+        assert_eq!(params[1], "100");
+        assert_eq!(
+            params[3],
+            base64::encode(private_key.sign_compact(bundle.digest()).unwrap())
+        );
+    }
+}
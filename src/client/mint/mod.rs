@@ -0,0 +1,57 @@
+//! Contains utility functions for querying the Cosmos sdk mint module
+
+use crate::error::CosmosGrpcError;
+use crate::utils::parse_sdk_dec;
+use crate::Contact;
+use cosmos_sdk_proto::cosmos::mint::v1beta1::query_client::QueryClient as MintQueryClient;
+use cosmos_sdk_proto::cosmos::mint::v1beta1::Params;
+use cosmos_sdk_proto::cosmos::mint::v1beta1::QueryAnnualProvisionsRequest;
+use cosmos_sdk_proto::cosmos::mint::v1beta1::QueryInflationRequest;
+use cosmos_sdk_proto::cosmos::mint::v1beta1::QueryParamsRequest;
+
+impl Contact {
+    /// Gets the parameters of the mint module
+    pub async fn get_mint_params(&self) -> Result<Params, CosmosGrpcError> {
+        self.cached("mint_params", || {
+            self.retry(|| async {
+                let mut grpc = MintQueryClient::new(self.get_channel().await?).accept_gzip();
+                let res = grpc.params(QueryParamsRequest {}).await?.into_inner();
+                match res.params {
+                    Some(params) => Ok(params),
+                    None => Err(CosmosGrpcError::BadResponse(
+                        "No mint params returned".to_string(),
+                    )),
+                }
+            })
+        })
+        .await
+    }
+
+    /// Gets the current annual inflation rate, 0.07 representing 7% inflation
+    pub async fn get_inflation(&self) -> Result<f64, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = MintQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .inflation(QueryInflationRequest {})
+                .await?
+                .into_inner()
+                .inflation;
+            parse_sdk_dec(&String::from_utf8_lossy(&res))
+        })
+        .await
+    }
+
+    /// Gets the number of tokens of the mint denom expected to be minted over the next year
+    pub async fn get_annual_provisions(&self) -> Result<f64, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = MintQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .annual_provisions(QueryAnnualProvisionsRequest {})
+                .await?
+                .into_inner()
+                .annual_provisions;
+            parse_sdk_dec(&String::from_utf8_lossy(&res))
+        })
+        .await
+    }
+}
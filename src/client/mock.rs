@@ -0,0 +1,490 @@
+//! An in memory stand in for Contact, for unit testing code built on deep_space without
+//! a running chain. The mock has the same query and broadcast methods as Contact, its
+//! responses are set up by the test and every broadcast tx is captured for inspection
+
+use super::retry::RetryPolicy;
+use super::send::broadcast_with_retry;
+use super::types::{BaseAccount, ChainStatus, LatestBlock, TimeoutPolicy};
+use super::MEMO;
+use crate::error::CosmosGrpcError;
+use crate::tx::DecodedTx;
+use crate::utils::bytes_to_hex_str;
+use crate::{Address, Coin, Fee, MessageArgs, Msg, PrivateKey};
+use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{GasInfo, TxResponse};
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{BroadcastMode, SimulateResponse};
+use cosmos_sdk_proto::tendermint::types::{Block, Header};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The gas a simulated tx uses unless set with `MockContact::set_gas_used`
+pub const DEFAULT_MOCK_GAS: u64 = 100_000;
+
+#[derive(Debug)]
+struct MockState {
+    chain_id: String,
+    block_height: u64,
+    gas_used: u64,
+    accounts: HashMap<Address, BaseAccount>,
+    balances: HashMap<Address, Vec<Coin>>,
+    /// Errors returned by the next calls, in order
+    errors: VecDeque<CosmosGrpcError>,
+    /// Responses for the next broadcasts, in order, a successful response is
+    /// generated once this is empty
+    responses: VecDeque<TxResponse>,
+    broadcasts: Vec<(Vec<u8>, TxResponse)>,
+}
+
+/// An in memory Contact with programmable responses, clones share their state so a
+/// clone can be handed to the code under test and the original kept for assertions
+#[derive(Debug, Clone)]
+pub struct MockContact {
+    prefix: String,
+    retry: RetryPolicy,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockContact {
+    pub fn new(prefix: &str) -> Self {
+        MockContact {
+            prefix: prefix.to_string(),
+            retry: RetryPolicy::none(),
+            state: Arc::new(Mutex::new(MockState {
+                chain_id: "mock-chain".to_string(),
+                block_height: 1,
+                gas_used: DEFAULT_MOCK_GAS,
+                accounts: HashMap::new(),
+                balances: HashMap::new(),
+                errors: VecDeque::new(),
+                responses: VecDeque::new(),
+                broadcasts: Vec::new(),
+            })),
+        }
+    }
+
+    /// Sets the retry policy broadcasts are made under, by default nothing is retried
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn set_chain_id(&self, chain_id: &str) {
+        self.state.lock().unwrap().chain_id = chain_id.to_string();
+    }
+
+    pub fn set_block_height(&self, height: u64) {
+        self.state.lock().unwrap().block_height = height;
+    }
+
+    /// The gas simulated transactions report as used
+    pub fn set_gas_used(&self, gas_used: u64) {
+        self.state.lock().unwrap().gas_used = gas_used;
+    }
+
+    pub fn set_account(&self, account: BaseAccount) {
+        self.state
+            .lock()
+            .unwrap()
+            .accounts
+            .insert(account.address, account);
+    }
+
+    pub fn set_balances(&self, address: Address, balances: Vec<Coin>) {
+        self.state
+            .lock()
+            .unwrap()
+            .balances
+            .insert(address, balances);
+    }
+
+    /// Makes the next call to any method fail with `error`, errors queue up so several
+    /// consecutive failures can be set up
+    pub fn push_error(&self, error: CosmosGrpcError) {
+        self.state.lock().unwrap().errors.push_back(error);
+    }
+
+    /// Sets the response of the next broadcast, for example a tx that fails in the
+    /// ante handler with a non zero code
+    pub fn push_broadcast_response(&self, response: TxResponse) {
+        self.state.lock().unwrap().responses.push_back(response);
+    }
+
+    /// Every tx broadcast through this mock, or any of its clones, in order
+    pub fn broadcast_txs(&self) -> Vec<DecodedTx> {
+        self.state
+            .lock()
+            .unwrap()
+            .broadcasts
+            .iter()
+            .map(|(bytes, _)| DecodedTx::decode(bytes).unwrap())
+            .collect()
+    }
+
+    /// The raw bytes of every tx broadcast through this mock, in order
+    pub fn broadcast_bytes(&self) -> Vec<Vec<u8>> {
+        let state = self.state.lock().unwrap();
+        state.broadcasts.iter().map(|(b, _)| b.clone()).collect()
+    }
+
+    fn check_error(&self) -> Result<(), CosmosGrpcError> {
+        match self.state.lock().unwrap().errors.pop_front() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    pub fn get_prefix(&self) -> String {
+        self.prefix.clone()
+    }
+
+    pub async fn get_chain_status(&self) -> Result<ChainStatus, CosmosGrpcError> {
+        self.check_error()?;
+        Ok(ChainStatus::Moving {
+            block_height: self.state.lock().unwrap().block_height,
+        })
+    }
+
+    pub async fn get_latest_block(&self) -> Result<LatestBlock, CosmosGrpcError> {
+        self.check_error()?;
+        let state = self.state.lock().unwrap();
+        Ok(LatestBlock::Latest {
+            block: Block {
+                header: Some(Header {
+                    chain_id: state.chain_id.clone(),
+                    height: state.block_height as i64,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        })
+    }
+
+    pub async fn get_chain_id(&self) -> Result<String, CosmosGrpcError> {
+        self.check_error()?;
+        Ok(self.state.lock().unwrap().chain_id.clone())
+    }
+
+    /// Returns `CosmosGrpcError::NoToken` for accounts that were never set, as a chain
+    /// does for addresses that never received tokens
+    pub async fn get_account_info(&self, address: Address) -> Result<BaseAccount, CosmosGrpcError> {
+        self.check_error()?;
+        match self.state.lock().unwrap().accounts.get(&address) {
+            Some(account) => Ok(account.clone()),
+            None => Err(CosmosGrpcError::NoToken),
+        }
+    }
+
+    pub async fn get_balances(&self, address: Address) -> Result<Vec<Coin>, CosmosGrpcError> {
+        self.check_error()?;
+        let state = self.state.lock().unwrap();
+        Ok(state.balances.get(&address).cloned().unwrap_or_default())
+    }
+
+    pub async fn get_balance(
+        &self,
+        address: Address,
+        denom: String,
+    ) -> Result<Option<Coin>, CosmosGrpcError> {
+        let balances = self.get_balances(address).await?;
+        Ok(balances.into_iter().find(|c| c.denom == denom))
+    }
+
+    pub async fn get_message_args(
+        &self,
+        our_address: Address,
+        fee: Fee,
+    ) -> Result<MessageArgs, CosmosGrpcError> {
+        let account = self.get_account_info(our_address).await?;
+        let state = self.state.lock().unwrap();
+        Ok(MessageArgs {
+            sequence: account.sequence,
+            account_number: account.account_number,
+            chain_id: state.chain_id.clone(),
+            fee,
+            timeout_height: state.block_height + 100,
+            relative_timeout: None,
+            extension_options: Vec::new(),
+            non_critical_extension_options: Vec::new(),
+            unordered: false,
+            timeout_timestamp: None,
+            max_memo_characters: None,
+        })
+    }
+
+    /// Signs the messages to check that they are valid and reports the configured gas
+    pub async fn simulate_tx(
+        &self,
+        messages: &[Msg],
+        private_key: &PrivateKey,
+    ) -> Result<SimulateResponse, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.prefix)?;
+        let fee = Fee {
+            gas_limit: u64::MAX >> 1,
+            ..Default::default()
+        };
+        let args = self.get_message_args(our_address, fee).await?;
+        private_key.sign_std_msg(messages, args, MEMO)?;
+        Ok(SimulateResponse {
+            gas_info: Some(GasInfo {
+                gas_wanted: 0,
+                gas_used: self.state.lock().unwrap().gas_used,
+            }),
+            result: None,
+        })
+    }
+
+    pub async fn get_fee_info(
+        &self,
+        messages: &[Msg],
+        fee_token: &[Coin],
+        private_key: &PrivateKey,
+    ) -> Result<Fee, CosmosGrpcError> {
+        let gas_info = self.simulate_tx(messages, private_key).await?.gas_info;
+        Ok(Fee {
+            amount: fee_token.to_vec(),
+            gas_limit: gas_info.unwrap_or_default().gas_used * 2,
+            granter: None,
+            payer: None,
+        })
+    }
+
+    /// Captures the tx and returns the next response set with `push_broadcast_response`,
+    /// or a successful response at the current height. Responses are checked and retried
+    /// the same way as by `Contact::send_transaction`, so a response with an sdk error
+    /// code is returned as `TransactionFailed`
+    pub async fn send_transaction(
+        &self,
+        msg: Vec<u8>,
+        _mode: BroadcastMode,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let decoded = DecodedTx::decode(&msg)?;
+        let gas_wanted = decoded.fee.map(|f| f.gas_limit as i64).unwrap_or_default();
+        broadcast_with_retry(&self.retry, "mock", &msg, || {
+            let response = self.next_broadcast(&msg, gas_wanted);
+            async move { response }
+        })
+        .await
+    }
+
+    fn next_broadcast(&self, msg: &[u8], gas_wanted: i64) -> Result<TxResponse, CosmosGrpcError> {
+        self.check_error()?;
+        let mut state = self.state.lock().unwrap();
+        let response = match state.responses.pop_front() {
+            Some(response) => response,
+            None => TxResponse {
+                height: state.block_height as i64,
+                txhash: bytes_to_hex_str(&Sha256::digest(msg)).to_uppercase(),
+                gas_wanted,
+                gas_used: state.gas_used as i64,
+                ..Default::default()
+            },
+        };
+        state.broadcasts.push((msg.to_vec(), response.clone()));
+        Ok(response)
+    }
+
+    /// Signs and broadcasts the messages, the sequence of the signing account is
+    /// incremented when the broadcast succeeds
+    pub async fn send_message(
+        &self,
+        messages: &[Msg],
+        memo: Option<String>,
+        fee_coin: &[Coin],
+        wait_timeout: Option<Duration>,
+        private_key: PrivateKey,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.prefix)?;
+        let memo = memo.unwrap_or_else(|| MEMO.to_string());
+        let fee = self.get_fee_info(messages, fee_coin, &private_key).await?;
+        let args = self.get_message_args(our_address, fee).await?;
+        let msg_bytes = private_key.sign_std_msg(messages, args, memo)?;
+        let response = self
+            .send_transaction(msg_bytes, BroadcastMode::Sync)
+            .await?;
+        if response.code == 0 {
+            if let Some(account) = self.state.lock().unwrap().accounts.get_mut(&our_address) {
+                account.sequence += 1;
+            }
+        }
+        match wait_timeout {
+            Some(timeout) => self.wait_for_tx(&response.txhash, timeout).await,
+            None => Ok(response),
+        }
+    }
+
+    pub async fn send_coins(
+        &self,
+        coin: Coin,
+        fee_coin: Option<Coin>,
+        destination: Address,
+        wait_timeout: Option<Duration>,
+        private_key: PrivateKey,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.prefix)?;
+        let send = MsgSend {
+            amount: vec![coin.into()],
+            from_address: our_address.to_string(),
+            to_address: destination.to_string(),
+        };
+        let msg = Msg::new("/cosmos.bank.v1beta1.MsgSend", send);
+        self.send_message(
+            &[msg],
+            None,
+            &[fee_coin.unwrap_or_default()],
+            wait_timeout,
+            private_key,
+        )
+        .await
+    }
+
+    /// Returns the response of a captured tx, `CosmosGrpcError::TimedOut` if no tx with
+    /// this hash was broadcast
+    pub async fn wait_for_tx(
+        &self,
+        txhash: &str,
+        _timeout: impl Into<TimeoutPolicy>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        self.check_error()?;
+        let state = self.state.lock().unwrap();
+        match state
+            .broadcasts
+            .iter()
+            .find(|(_, res)| res.txhash.eq_ignore_ascii_case(txhash))
+        {
+            Some((_, res)) => Ok(res.clone()),
+            None => Err(CosmosGrpcError::TimedOut {
+                txhash: txhash.to_string(),
+                last_seen_height: state.block_height,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::BroadcastError;
+
+    #[actix_rt::test]
+    async fn test_mock_contact() {
+        let contact = MockContact::new("cosmos");
+        let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let address = key.to_address("cosmos").unwrap();
+        let coin: Coin = "100uatom".parse().unwrap();
+
+        assert!(matches!(
+            contact.get_account_info(address).await,
+            Err(CosmosGrpcError::NoToken)
+        ));
+        contact.set_account(BaseAccount {
+            address,
+            pubkey: None,
+            account_number: 3,
+            sequence: 7,
+        });
+        contact.set_balances(address, vec![coin.clone()]);
+        assert_eq!(
+            contact
+                .get_balance(address, "uatom".to_string())
+                .await
+                .unwrap(),
+            Some(coin.clone())
+        );
+
+        let res = contact
+            .send_coins(
+                coin.clone(),
+                None,
+                address,
+                Some(Duration::from_secs(1)),
+                key.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.code, 0);
+        assert_eq!(res.gas_wanted, DEFAULT_MOCK_GAS as i64 * 2);
+        let txs = contact.broadcast_txs();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].hash, res.txhash);
+        assert_eq!(txs[0].sequences, vec![7]);
+        assert_eq!(contact.get_account_info(address).await.unwrap().sequence, 8);
+
+        contact.push_error(CosmosGrpcError::ChainNotRunning);
+        assert!(contact
+            .send_coins(coin, None, address, None, key)
+            .await
+            .is_err());
+        assert_eq!(contact.broadcast_txs().len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_broadcast_retry_in_mempool_cache() {
+        let contact = MockContact::new("cosmos").with_retry_policy(RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..Default::default()
+        });
+        let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let address = key.to_address("cosmos").unwrap();
+        let send = MsgSend {
+            amount: vec![],
+            from_address: address.to_string(),
+            to_address: address.to_string(),
+        };
+        let mut args = MessageArgs {
+            chain_id: "mock-chain".to_string(),
+            ..Default::default()
+        };
+        args.fee.gas_limit = 100_000;
+        let tx = key
+            .sign_std_msg(&[Msg::new("/cosmos.bank.v1beta1.MsgSend", send)], args, "")
+            .unwrap();
+        let txhash = bytes_to_hex_str(&Sha256::digest(&tx)).to_uppercase();
+        let in_cache = TxResponse {
+            code: 19,
+            codespace: "sdk".to_string(),
+            raw_log: "tx already exists in cache".to_string(),
+            ..Default::default()
+        };
+
+        // the first attempt times out after reaching the mempool, the retry is a duplicate
+        contact.push_error(CosmosGrpcError::RequestError {
+            error: tonic::Status::deadline_exceeded("timeout"),
+        });
+        contact.push_broadcast_response(in_cache.clone());
+        let res = contact
+            .send_transaction(tx.clone(), BroadcastMode::Sync)
+            .await
+            .unwrap();
+        assert_eq!(res.code, 0);
+        assert_eq!(res.txhash, txhash);
+
+        // without an earlier attempt the tx was sent by someone else, which is an error
+        contact.push_broadcast_response(in_cache);
+        assert!(matches!(
+            contact
+                .send_transaction(tx.clone(), BroadcastMode::Sync)
+                .await,
+            Err(CosmosGrpcError::TransactionFailed { .. })
+        ));
+
+        // a module error is a failure too, not only errors in the sdk codespace
+        contact.push_broadcast_response(TxResponse {
+            code: 5,
+            codespace: "wasm".to_string(),
+            raw_log: "execute wasm contract failed".to_string(),
+            ..Default::default()
+        });
+        match contact.send_transaction(tx, BroadcastMode::Sync).await {
+            Err(CosmosGrpcError::TransactionFailed { reason, .. }) => assert_eq!(
+                reason,
+                BroadcastError::Other {
+                    codespace: "wasm".to_string(),
+                    code: 5
+                }
+            ),
+            other => panic!("Expected a failed tx, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! A local gRPC server answering with canned responses, so that `Contact` methods can
+//! be tested against their real client code without a node. Only built for tests
+
+use bytes::{Buf, BufMut};
+use futures_util::future::BoxFuture;
+use prost::Message;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::net::TcpListener;
+use tonic::body::BoxBody;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::http::{Request, Response};
+use tonic::codegen::Service;
+use tonic::server::{Grpc, UnaryService};
+use tonic::transport::{Body, NamedService, Server};
+use tonic::Status;
+use tower_layer::Layer;
+
+type Handler = Arc<dyn Fn(&[u8]) -> Result<Vec<u8>, Status> + Send + Sync>;
+
+/// Canned responses by gRPC method path, every call is recorded
+#[derive(Clone, Default)]
+pub struct MockGrpc {
+    handlers: HashMap<String, Handler>,
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockGrpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers calls to `path`, for example "/cosmos.staking.v1beta1.Query/Params",
+    /// with `handler` applied to the decoded request
+    pub fn with<Req, Res, F>(mut self, path: &str, handler: F) -> Self
+    where
+        Req: Message + Default,
+        Res: Message,
+        F: Fn(Req) -> Result<Res, Status> + Send + Sync + 'static,
+    {
+        let handler = move |bytes: &[u8]| {
+            let req = Req::decode(bytes).map_err(|e| Status::invalid_argument(e.to_string()))?;
+            handler(req).map(|res| res.encode_to_vec())
+        };
+        self.handlers.insert(path.to_string(), Arc::new(handler));
+        self
+    }
+
+    /// The paths of every call made so far, in order
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Starts serving on a free local port and returns its url
+    pub async fn serve(&self) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = futures_util::stream::unfold(listener, |listener| async move {
+            let conn = listener.accept().await.map(|(stream, _)| stream);
+            Some((conn, listener))
+        });
+        // every request is answered by the layer, the router is never reached
+        let server = Server::builder()
+            .layer(self.clone())
+            .add_optional_service(None::<MockGrpc>)
+            .serve_with_incoming(incoming);
+        tokio::spawn(server);
+        url
+    }
+}
+
+impl NamedService for MockGrpc {
+    const NAME: &'static str = "mock";
+}
+
+impl<S> Layer<S> for MockGrpc {
+    type Service = MockGrpc;
+
+    fn layer(&self, _inner: S) -> MockGrpc {
+        self.clone()
+    }
+}
+
+impl Service<Request<Body>> for MockGrpc {
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response<BoxBody>, Infallible>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let path = req.uri().path().to_string();
+        self.calls.lock().unwrap().push(path.clone());
+        let handler = self.handlers.get(&path).cloned();
+        Box::pin(async move {
+            let mut grpc = Grpc::new(RawCodec);
+            Ok(grpc.unary(Unary(handler, path), req).await)
+        })
+    }
+}
+
+struct Unary(Option<Handler>, String);
+
+impl UnaryService<Vec<u8>> for Unary {
+    type Response = Vec<u8>;
+    type Future = futures_util::future::Ready<Result<tonic::Response<Vec<u8>>, Status>>;
+
+    fn call(&mut self, request: tonic::Request<Vec<u8>>) -> Self::Future {
+        let res = match &self.0 {
+            Some(handler) => handler(request.get_ref()).map(tonic::Response::new),
+            None => Err(Status::unimplemented(format!("no mock for {}", self.1))),
+        };
+        futures_util::future::ready(res)
+    }
+}
+
+/// Passes messages through as bytes, the handlers do the prost encoding
+struct RawCodec;
+
+impl Codec for RawCodec {
+    type Encode = Vec<u8>;
+    type Decode = Vec<u8>;
+    type Encoder = RawCodec;
+    type Decoder = RawCodec;
+
+    fn encoder(&mut self) -> RawCodec {
+        RawCodec
+    }
+
+    fn decoder(&mut self) -> RawCodec {
+        RawCodec
+    }
+}
+
+impl Encoder for RawCodec {
+    type Item = Vec<u8>;
+    type Error = Status;
+
+    fn encode(&mut self, item: Vec<u8>, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        dst.put_slice(&item);
+        Ok(())
+    }
+}
+
+impl Decoder for RawCodec {
+    type Item = Vec<u8>;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Vec<u8>>, Status> {
+        let len = src.remaining();
+        Ok(Some(src.copy_to_bytes(len).to_vec()))
+    }
+}
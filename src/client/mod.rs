@@ -1,18 +1,113 @@
 use std::time::Duration;
 
+pub mod authz;
 pub mod bank;
+pub mod block_time;
+pub mod cache;
+#[cfg(feature = "chain-registry")]
+pub mod chain_registry;
+pub mod connection;
+pub mod contact_set;
+pub mod cosmos_client;
 pub mod distribution;
+pub mod epochs;
+#[cfg(feature = "fee-abstraction")]
+pub mod fee_abstraction;
 pub mod get;
 pub mod gov;
+#[cfg(feature = "gravity")]
+pub mod gravity;
+pub mod group;
+pub mod headers;
+pub mod health;
+pub mod height;
+pub mod ibc;
+#[cfg(feature = "icq")]
+pub mod icq;
 pub mod invariant;
+pub mod key_pool;
+pub mod limits;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "mev")]
+pub mod mev;
+pub mod mint;
+#[cfg(feature = "testing")]
+pub mod mock;
+#[cfg(test)]
+pub(crate) mod mock_grpc;
+#[cfg(feature = "names")]
+pub mod names;
+#[cfg(feature = "oracle")]
+pub mod oracle;
+pub mod paginator;
+pub mod proxy;
+pub mod quorum;
+pub mod rate_limit;
+pub mod retry;
+#[cfg(feature = "tendermint-rpc")]
+pub mod rpc;
+pub mod scheduler;
 pub mod send;
+pub mod slashing;
+pub mod snapshot;
 pub mod staking;
 pub mod types;
+pub mod upgrade;
+#[cfg(feature = "cosmwasm")]
+pub mod wasm;
+#[cfg(feature = "tendermint-rpc")]
+pub mod watcher;
 
+pub use bank::sweep::SweepFeePolicy;
+pub use block_time::BlockTimeSample;
+pub use connection::ConnectionConfig;
+pub use connection::GrpcChannel;
+pub use contact_set::ContactSet;
+pub use cosmos_client::CosmosClient;
 use cosmos_sdk_proto::cosmos::base::query::v1beta1::PageRequest;
+pub use distribution::compound::CompoundFeePolicy;
+pub use gov::content::ProposalContent;
+pub use health::NodeHealth;
+pub use key_pool::KeyPool;
+pub use limits::GrpcCompression;
+#[cfg(feature = "testing")]
+pub use mock::MockContact;
+pub use paginator::Paginator;
+pub use proxy::Proxy;
+pub use rate_limit::RateLimit;
+pub use retry::RetryPolicy;
+#[cfg(feature = "tendermint-rpc")]
+pub use rpc::TendermintRpc;
+pub use scheduler::{Schedule, Scheduler};
+pub use snapshot::AccountSnapshot;
+pub use types::diff_validator_sets;
+pub use types::ChainConfig;
+pub use types::ChainParams;
 pub use types::ChainStatus;
+pub use types::DryRun;
+pub use types::GasHeuristic;
+pub use types::GovParams;
+pub use types::TimeoutPolicy;
+pub use types::ValidatorSet;
+pub use types::ValidatorSetDiff;
+#[cfg(feature = "tendermint-rpc")]
+pub use watcher::BalanceWatcher;
 
+use crate::error::PrivateKeyError;
+use crate::tx::estimate_tx_size;
 use crate::{error::CosmosGrpcError, utils::ArrayString};
+use crate::{Address, AnyRegistry, Coin, Fee, Msg, PrivateKey, TypeUrl, Uint256};
+use cache::QueryCache;
+use rate_limit::TokenBucket;
+use std::sync::atomic::{AtomicBool, AtomicU64};
+use std::sync::{Arc, Mutex};
+use tonic::client::Grpc;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::codegen::http::HeaderMap;
+use tonic::transport::Channel;
+use types::CosmosAccount;
 
 pub const MEMO: &str = "Sent with Deep Space";
 
@@ -40,26 +135,161 @@ pub struct Contact {
     /// The maximum amount of wall time any action taken
     /// will wait for.
     timeout: Duration,
-    /// The prefix being used by this node / chain for Addresses
-    chain_prefix: String,
+    /// The prefix, fee denom, and other chain specific settings
+    config: ChainConfig,
+    /// Keepalive and reconnection settings
+    connection: ConnectionConfig,
+    /// How requests that fail for transient reasons are retried
+    retry: RetryPolicy,
+    /// Shared by all clones of this Contact, None if requests are not rate limited
+    rate_limiter: Option<Arc<TokenBucket>>,
+    /// Shared by all clones of this Contact, None if query results are not cached
+    cache: Option<Arc<QueryCache>>,
+    /// The block height queries are made at, None for the latest block
+    height: Option<u64>,
+    /// Custom headers sent with every request
+    headers: Arc<HeaderMap>,
+    /// The gRPC channel shared by all clones of this Contact, None until
+    /// the first request or after the connection is reset
+    channel: Arc<Mutex<Option<Channel>>>,
+    /// Set once the first connection has been made
+    connected: Arc<AtomicBool>,
+    /// The number of times the connection has been re-established
+    reconnects: Arc<AtomicU64>,
+    /// The chain's max_memo_characters auth param, zero until first queried
+    max_memo_characters: Arc<AtomicU64>,
+    /// Decoders for chain specific types, used when parsing query responses
+    registry: Arc<AnyRegistry>,
+    /// Broadcasts fail if the node is more blocks behind than this, None to not check
+    max_node_lag: Option<u64>,
+    /// Further endpoints of the same chain that quorum queries are also made against
+    quorum_endpoints: Arc<Vec<Contact>>,
+    /// The Tendermint RPC endpoint, used for the few queries gRPC does not support
+    #[cfg(feature = "tendermint-rpc")]
+    rpc: Option<TendermintRpc>,
 }
 
 impl Contact {
     pub fn new(url: &str, timeout: Duration, chain_prefix: &str) -> Result<Self, CosmosGrpcError> {
+        Contact::new_with_config(url, timeout, ChainConfig::new(chain_prefix))
+    }
+
+    /// Creates a Contact with a full chain configuration, allowing default fees and
+    /// key derivation to be handled by the Contact
+    pub fn new_with_config(
+        url: &str,
+        timeout: Duration,
+        config: ChainConfig,
+    ) -> Result<Self, CosmosGrpcError> {
         let mut url = url;
         if !url.ends_with('/') {
             url = url.trim_end_matches('/');
         }
-        ArrayString::new(chain_prefix)?;
+        ArrayString::new(&config.prefix)?;
         Ok(Self {
             url: url.to_string(),
             timeout,
-            chain_prefix: chain_prefix.to_string(),
+            config,
+            connection: ConnectionConfig::default(),
+            retry: RetryPolicy::default(),
+            rate_limiter: None,
+            cache: None,
+            height: None,
+            headers: Arc::new(HeaderMap::new()),
+            channel: Arc::new(Mutex::new(None)),
+            connected: Arc::new(AtomicBool::new(false)),
+            reconnects: Arc::new(AtomicU64::new(0)),
+            max_memo_characters: Arc::new(AtomicU64::new(0)),
+            registry: Arc::new(AnyRegistry::new()),
+            max_node_lag: None,
+            quorum_endpoints: Arc::new(Vec::new()),
+            #[cfg(feature = "tendermint-rpc")]
+            rpc: None,
         })
     }
 
+    /// Replaces the keepalive, reconnection and rate limit settings of this Contact
+    pub fn with_connection_config(mut self, connection: ConnectionConfig) -> Self {
+        self.rate_limiter = connection
+            .rate_limit
+            .map(|limit| Arc::new(TokenBucket::new(limit)));
+        self.connection = connection;
+        self.channel = Arc::new(Mutex::new(None));
+        self
+    }
+
+    /// Sets the decoders used for chain specific types, such as the account types
+    /// returned by `get_account`
+    pub fn with_registry(mut self, registry: AnyRegistry) -> Self {
+        self.registry = Arc::new(registry);
+        self
+    }
+
+    /// Registers an account type with the registry of this Contact, so that
+    /// `get_account` and the transaction functions that look up sequences support
+    /// accounts of that type, for example the vesting accounts of a specific chain
+    pub fn with_account_type<A>(mut self) -> Self
+    where
+        A: prost::Message + Default + TypeUrl + CosmosAccount + PartialEq + Send + Sync + 'static,
+    {
+        let mut registry = (*self.registry).clone();
+        registry.register_account::<A>();
+        self.registry = Arc::new(registry);
+        self
+    }
+
+    pub fn get_registry(&self) -> &AnyRegistry {
+        &self.registry
+    }
+
     pub fn get_prefix(&self) -> String {
-        self.chain_prefix.clone()
+        self.config.prefix.clone()
+    }
+
+    pub fn get_config(&self) -> &ChainConfig {
+        &self.config
+    }
+
+    /// The address of the provided key on this chain
+    pub fn get_address(&self, private_key: &PrivateKey) -> Result<Address, PrivateKeyError> {
+        private_key.to_address(&self.config.prefix)
+    }
+
+    /// Derives the first account key for this chain's coin type from a mnemonic
+    pub fn key_from_phrase(
+        &self,
+        phrase: &str,
+        passphrase: &str,
+    ) -> Result<PrivateKey, PrivateKeyError> {
+        PrivateKey::from_hd_wallet_path(&self.config.hd_path(), phrase, passphrase)
+    }
+
+    /// A fee for the given gas limit priced at the configured gas price, the fee is
+    /// empty if no fee denom is configured
+    pub fn default_fee(&self, gas_limit: u64) -> Fee {
+        let mut amount = Vec::new();
+        if !self.config.denom.is_empty() {
+            let price = (self.config.gas_price * gas_limit as f64).ceil();
+            amount.push(Coin {
+                amount: Uint256::from_u128(price as u128),
+                denom: self.config.denom.clone(),
+            });
+        }
+        Fee {
+            amount,
+            gas_limit,
+            payer: None,
+            granter: None,
+        }
+    }
+
+    /// Estimates the gas needed by a transaction without a simulate round trip using the
+    /// configured `GasHeuristic`, prefer `simulate_tx` when connected to a node
+    pub fn estimate_gas_offline(&self, messages: &[Msg], memo: &str, num_signers: usize) -> u64 {
+        let size = estimate_tx_size(messages, memo, num_signers);
+        self.config
+            .gas_heuristic
+            .estimate_gas(size, messages.len(), num_signers)
     }
 
     pub fn get_url(&self) -> String {
@@ -69,6 +299,32 @@ impl Contact {
     pub fn get_timeout(&self) -> Duration {
         self.timeout
     }
+
+    /// Performs a unary gRPC query against an endpoint that the proto crate does not
+    /// provide a generated client for, such as the modules of a specific chain. `path`
+    /// is the full method path for example `/cosmos.upgrade.v1beta1.Query/CurrentPlan`.
+    /// The query shares this Contact's connection, height, rate limit and retry policy
+    pub async fn query_raw<Q, R>(&self, path: &str, request: Q) -> Result<R, CosmosGrpcError>
+    where
+        Q: prost::Message + Clone + 'static,
+        R: prost::Message + Default + 'static,
+    {
+        let path = PathAndQuery::try_from(path)
+            .map_err(|e| CosmosGrpcError::BadInput(format!("Invalid method path {}", e)))?;
+        self.retry(|| async {
+            let mut grpc = Grpc::new(self.get_channel().await?).accept_gzip();
+            grpc.ready().await?;
+            let res = grpc
+                .unary(
+                    tonic::Request::new(request.clone()),
+                    path.clone(),
+                    ProstCodec::default(),
+                )
+                .await?;
+            Ok(res.into_inner())
+        })
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -79,6 +335,130 @@ mod tests {
 
     const TIMEOUT: Duration = Duration::from_secs(60);
 
+    #[test]
+    fn test_chain_config() {
+        let mut config = ChainConfig::new("evmos");
+        config.denom = "aevmos".to_string();
+        config.gas_price = 0.5;
+        config.coin_type = 60;
+        let contact = Contact::new_with_config("http://localhost:9090", TIMEOUT, config).unwrap();
+        let fee = contact.default_fee(3);
+        assert_eq!(fee.gas_limit, 3);
+        assert_eq!(fee.amount[0].amount, crate::u256!(2));
+        assert_eq!(fee.amount[0].denom, "aevmos");
+
+        let phrase = "boost casual myth skin olympic sure apology creek theme conduct view panda board pride miss turkey lonely strategy panel mad blast panda work shuffle";
+        let key = contact.key_from_phrase(phrase, "").unwrap();
+        assert_eq!(
+            key,
+            PrivateKey::from_hd_wallet_path("m/44'/60'/0'/0/0", phrase, "").unwrap()
+        );
+        assert_eq!(
+            contact.get_address(&key).unwrap().get_prefix(),
+            "evmos".to_string()
+        );
+        assert_eq!(contact.get_config().account_path(), "m/44'/60'/0'");
+        let xpub =
+            crate::Xpub::from_phrase(&contact.get_config().account_path(), phrase, "").unwrap();
+        assert_eq!(
+            xpub.address_at("evmos", 0).unwrap(),
+            contact.get_address(&key).unwrap()
+        );
+
+        let contact = Contact::new("http://localhost:9090", TIMEOUT, "cosmos").unwrap();
+        assert!(contact.default_fee(100).amount.is_empty());
+    }
+
+    /// A chain specific account type, unknown to `AccountType::decode_any`
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    struct LockupAccount {
+        #[prost(message, optional, tag = "1")]
+        base_account: Option<cosmos_sdk_proto::cosmos::auth::v1beta1::BaseAccount>,
+        #[prost(int64, tag = "2")]
+        unlock_time: i64,
+    }
+
+    impl TypeUrl for LockupAccount {
+        const TYPE_URL: &'static str = "/lockup.v1.LockupAccount";
+    }
+
+    impl CosmosAccount for LockupAccount {
+        fn get_base_account(&self) -> types::BaseAccount {
+            self.base_account.clone().unwrap_or_default().into()
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_with_account_type() {
+        use crate::client::mock_grpc::MockGrpc;
+        use cosmos_sdk_proto::cosmos::auth::v1beta1::{QueryAccountRequest, QueryAccountResponse};
+        use prost::Message;
+
+        let address = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"")
+            .unwrap()
+            .to_address("cosmos")
+            .unwrap();
+        let account = LockupAccount {
+            base_account: Some(cosmos_sdk_proto::cosmos::auth::v1beta1::BaseAccount {
+                address: address.to_string(),
+                pub_key: None,
+                account_number: 12,
+                sequence: 4,
+            }),
+            unlock_time: 1_700_000_000,
+        };
+        let value = account.encode_to_vec();
+        let url = MockGrpc::new()
+            .with(
+                "/cosmos.auth.v1beta1.Query/Account",
+                move |req: QueryAccountRequest| {
+                    // a malformed account under the same type url for any other address
+                    let value = match req.address == address.to_string() {
+                        true => value.clone(),
+                        false => vec![0xff; 3],
+                    };
+                    Ok(QueryAccountResponse {
+                        account: Some(prost_types::Any {
+                            type_url: LockupAccount::TYPE_URL.to_string(),
+                            value,
+                        }),
+                    })
+                },
+            )
+            .serve()
+            .await;
+        let contact = Contact::new(&url, TIMEOUT, "cosmos").unwrap();
+        let registered = contact.clone().with_account_type::<LockupAccount>();
+
+        // registering the type does not change the registry of the original Contact
+        assert!(matches!(
+            contact.get_account(address).await,
+            Err(CosmosGrpcError::BadResponse(_))
+        ));
+        match registered.get_account(address).await.unwrap() {
+            types::AccountType::Custom(custom) => {
+                assert_eq!(custom.value.downcast_ref::<LockupAccount>(), Some(&account));
+                assert_eq!(custom.get_base_account().account_number, 12);
+            }
+            other => panic!("Expected a custom account, got {:?}", other),
+        }
+        assert_eq!(
+            registered.get_account_info(address).await.unwrap().sequence,
+            4
+        );
+
+        let other = Address::from_bytes([2; 20], "cosmos").unwrap();
+        assert!(registered.get_account(other).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_query_raw_bad_path() {
+        let contact = Contact::new("http://127.0.0.1:1", TIMEOUT, "cosmos").unwrap();
+        let res: Result<PageRequest, _> =
+            contact.query_raw("bad path", PageRequest::default()).await;
+        assert!(matches!(res, Err(CosmosGrpcError::BadInput(_))));
+    }
+
     /// If you run the start-chains.sh script in the Gravity repo it will pass
     /// port 9090 on localhost and allow you to debug things quickly
     /// then be used to run this test and debug things quickly. You will need
@@ -0,0 +1,236 @@
+//! Resolves human readable names to addresses, for CLIs and bots that take a recipient
+//! from a person. Names are looked up in a local address book first and then in the
+//! configured on chain name services, ICNS and Stargaze Names. Enabled with the `names`
+//! feature
+//!
+//! Both name services map a name to the account key of its owner, so a resolved
+//! address is re-encoded with the prefix of the chain it is used on. That is only the
+//! same account on chains sharing the owner's coin type
+
+use crate::error::CosmosGrpcError;
+use crate::{Address, Contact};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use tonic::Code as GrpcCode;
+
+/// A local mapping of names to addresses, loaded from a TOML file of `name = "address"`
+/// entries such as
+///
+/// ```toml
+/// treasury = "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AddressBook {
+    entries: BTreeMap<String, Address>,
+}
+
+impl AddressBook {
+    pub fn new() -> Self {
+        AddressBook::default()
+    }
+
+    /// Parses an address book from TOML, every entry must be a valid bech32 address
+    pub fn from_toml_str(s: &str) -> Result<AddressBook, CosmosGrpcError> {
+        let raw: BTreeMap<String, String> = toml::from_str(s)
+            .map_err(|e| CosmosGrpcError::BadInput(format!("Invalid address book {}", e)))?;
+        let mut book = AddressBook::new();
+        for (name, address) in raw {
+            let parsed = Address::from_bech32(address.clone()).map_err(|e| {
+                CosmosGrpcError::BadInput(format!(
+                    "Invalid address {} for {}: {}",
+                    address, name, e
+                ))
+            })?;
+            book.insert(name, parsed);
+        }
+        Ok(book)
+    }
+
+    /// Reads and parses an address book file
+    pub fn load(path: impl AsRef<Path>) -> Result<AddressBook, CosmosGrpcError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            CosmosGrpcError::BadInput(format!(
+                "Failed to read address book {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        AddressBook::from_toml_str(&contents)
+    }
+
+    /// Adds an entry, returning the address it replaced if any
+    pub fn insert(&mut self, name: impl Into<String>, address: Address) -> Option<Address> {
+        self.entries.insert(name.into(), address)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Address> {
+        self.entries.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// An on chain name service, identified by the contract that resolves names. The
+/// contracts live on Osmosis and Stargaze, so the Contact resolving a name must be
+/// connected to that chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameService {
+    /// The ICNS resolver contract. Names are written `alice.cosmos`, the suffix is the
+    /// bech32 prefix to resolve for and defaults to the prefix of the Contact
+    Icns { resolver: Address },
+    /// The Stargaze Names contract. Names are written `alice` or `alice.stars`
+    StargazeNames { contract: Address },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum IcnsQueryMsg {
+    Address { name: String, bech32_prefix: String },
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+struct IcnsAddressResponse {
+    address: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum StargazeNamesQueryMsg {
+    AssociatedAddress { name: String },
+}
+
+/// Resolves recipients, see [Contact::resolve_recipient]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NameResolver {
+    pub address_book: AddressBook,
+    /// Queried in order, the first service to resolve a name wins
+    pub services: Vec<NameService>,
+}
+
+impl NameResolver {
+    pub fn new(address_book: AddressBook, services: Vec<NameService>) -> Self {
+        NameResolver {
+            address_book,
+            services,
+        }
+    }
+}
+
+/// Splits `alice.cosmos` into its name and suffix
+fn split_name(name: &str) -> (&str, Option<&str>) {
+    match name.rsplit_once('.') {
+        Some((name, suffix)) => (name, Some(suffix)),
+        None => (name, None),
+    }
+}
+
+impl Contact {
+    /// Resolves `recipient`, which may be a bech32 address, an entry of the resolver's
+    /// address book or a name registered with one of its name services. Addresses are
+    /// returned as they are, names resolved by a name service are encoded with the
+    /// prefix of this Contact
+    pub async fn resolve_recipient(
+        &self,
+        resolver: &NameResolver,
+        recipient: &str,
+    ) -> Result<Address, CosmosGrpcError> {
+        let recipient = recipient.trim();
+        if let Ok(address) = Address::from_str(recipient) {
+            return Ok(address);
+        }
+        if let Some(address) = resolver.address_book.get(recipient) {
+            return Ok(address);
+        }
+        for service in resolver.services.iter() {
+            if let Some(mut address) = self.resolve_name(service, recipient).await? {
+                // chain prefix is validated as part of this client, so this can't
+                // panic
+                address.change_prefix(&self.config.prefix).unwrap();
+                return Ok(address);
+            }
+        }
+        Err(CosmosGrpcError::BadInput(format!(
+            "{} is not an address or a known name",
+            recipient
+        )))
+    }
+
+    /// Looks up `name` with a single name service, None if it is not registered
+    pub async fn resolve_name(
+        &self,
+        service: &NameService,
+        name: &str,
+    ) -> Result<Option<Address>, CosmosGrpcError> {
+        let res: Result<String, CosmosGrpcError> = match service {
+            NameService::Icns { resolver } => {
+                let (name, suffix) = split_name(name);
+                let query = IcnsQueryMsg::Address {
+                    name: name.to_string(),
+                    bech32_prefix: suffix.unwrap_or(&self.config.prefix).to_string(),
+                };
+                self.query_contract_smart::<IcnsAddressResponse>(*resolver, &query)
+                    .await
+                    .map(|res| res.address)
+            }
+            NameService::StargazeNames { contract } => {
+                let name = name.strip_suffix(".stars").unwrap_or(name);
+                let query = StargazeNamesQueryMsg::AssociatedAddress {
+                    name: name.to_string(),
+                };
+                self.query_contract_smart(*contract, &query).await
+            }
+        };
+        match res {
+            Ok(address) if address.is_empty() => Ok(None),
+            Ok(address) => match Address::from_bech32(address) {
+                Ok(address) => Ok(Some(address)),
+                Err(e) => Err(CosmosGrpcError::BadResponse(e.to_string())),
+            },
+            // both contracts fail the query for names that are not registered
+            Err(CosmosGrpcError::RequestError { error })
+                if matches!(
+                    error.code(),
+                    GrpcCode::Unknown | GrpcCode::InvalidArgument | GrpcCode::NotFound
+                ) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_book() {
+        let address = Address::from_bytes([3; 20], "cosmos").unwrap();
+        let toml = format!("treasury = \"{}\"\n", address);
+        let book = AddressBook::from_toml_str(&toml).unwrap();
+        assert_eq!(book.get("treasury"), Some(address));
+        assert_eq!(book.get("alice"), None);
+        assert!(AddressBook::from_toml_str("treasury = \"not an address\"").is_err());
+
+        assert_eq!(split_name("alice.osmo"), ("alice", Some("osmo")));
+        assert_eq!(split_name("alice"), ("alice", None));
+        let query = serde_json::to_string(&IcnsQueryMsg::Address {
+            name: "alice".to_string(),
+            bech32_prefix: "osmo".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            query,
+            r#"{"address":{"name":"alice","bech32_prefix":"osmo"}}"#
+        );
+    }
+}
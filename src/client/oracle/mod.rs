@@ -0,0 +1,202 @@
+//! Contains message builders and queries for Terra style x/oracle modules, used by price
+//! feeders to submit exchange rates with the prevote and vote commit reveal scheme.
+//! Enabled with the `oracle` feature
+//!
+//! A feeder submits a prevote with the hash of its rates in one vote period, then
+//! reveals the rates and salt with a vote in the next period
+
+use crate::client::ChainStatus;
+use crate::error::CosmosGrpcError;
+use crate::utils::bytes_to_hex_str;
+use crate::{Address, Contact, Msg};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+pub mod proto;
+
+pub use proto::OracleParams;
+use proto::*;
+
+/// The chains whose oracle module is supported, they share message layouts but each
+/// uses its own proto package
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OracleModule {
+    Terra,
+    Kujira,
+    Umee,
+}
+
+impl OracleModule {
+    pub fn prevote_type_url(&self) -> &'static str {
+        match self {
+            OracleModule::Terra => "/terra.oracle.v1beta1.MsgAggregateExchangeRatePrevote",
+            OracleModule::Kujira => "/kujira.oracle.MsgAggregateExchangeRatePrevote",
+            OracleModule::Umee => "/umee.oracle.v1.MsgAggregateExchangeRatePrevote",
+        }
+    }
+
+    pub fn vote_type_url(&self) -> &'static str {
+        match self {
+            OracleModule::Terra => "/terra.oracle.v1beta1.MsgAggregateExchangeRateVote",
+            OracleModule::Kujira => "/kujira.oracle.MsgAggregateExchangeRateVote",
+            OracleModule::Umee => "/umee.oracle.v1.MsgAggregateExchangeRateVote",
+        }
+    }
+
+    fn params_path(&self) -> &'static str {
+        match self {
+            OracleModule::Terra => "/terra.oracle.v1beta1.Query/Params",
+            OracleModule::Kujira => "/kujira.oracle.Query/Params",
+            OracleModule::Umee => "/umee.oracle.v1.Query/Params",
+        }
+    }
+}
+
+/// Generates a random salt for a prevote, four hex characters as the oracle modules
+/// only accept salts of one to four characters
+pub fn generate_oracle_salt() -> String {
+    let salt: [u8; 2] = rand::thread_rng().gen();
+    bytes_to_hex_str(&salt)
+}
+
+/// Computes the prevote hash, the first 20 bytes of the sha256 of
+/// `{salt}:{exchange_rates}:{validator}` hex encoded. `exchange_rates` must be exactly
+/// the string that will later be revealed in the vote, its format is chain specific
+pub fn aggregate_vote_hash(salt: &str, exchange_rates: &str, validator: Address) -> String {
+    let preimage = format!("{}:{}:{}", salt, exchange_rates, validator);
+    bytes_to_hex_str(&Sha256::digest(preimage.as_bytes())[..20])
+}
+
+/// The position of a block height within the oracle vote periods
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VotePeriod {
+    /// The index of the period, height / vote_period
+    pub period: u64,
+    /// The first block of the period
+    pub start_height: u64,
+    /// The last block of the period, a prevote made in this period can be revealed
+    /// from the next block on
+    pub end_height: u64,
+    /// Blocks left in the period after the given height
+    pub blocks_remaining: u64,
+}
+
+impl VotePeriod {
+    /// The vote period containing `height`, a `vote_period` of zero is treated as one
+    pub fn at_height(height: u64, vote_period: u64) -> Self {
+        let vote_period = vote_period.max(1);
+        let period = height / vote_period;
+        let start_height = period * vote_period;
+        let end_height = start_height + vote_period - 1;
+        VotePeriod {
+            period,
+            start_height,
+            end_height,
+            blocks_remaining: end_height - height,
+        }
+    }
+}
+
+impl Msg {
+    /// Creates a MsgAggregateExchangeRatePrevote, `hash` is computed with
+    /// `aggregate_vote_hash`. `feeder` is the account the validator delegated feeding to,
+    /// or the validator's own account
+    pub fn oracle_prevote(
+        module: OracleModule,
+        hash: String,
+        feeder: Address,
+        validator: Address,
+    ) -> Self {
+        let msg = MsgAggregateExchangeRatePrevote {
+            hash,
+            feeder: feeder.to_string(),
+            validator: validator.to_string(),
+        };
+        Msg::new(module.prevote_type_url(), msg)
+    }
+
+    /// Creates a MsgAggregateExchangeRateVote revealing the rates and salt of the
+    /// prevote made in the previous vote period
+    pub fn oracle_vote(
+        module: OracleModule,
+        salt: String,
+        exchange_rates: String,
+        feeder: Address,
+        validator: Address,
+    ) -> Self {
+        let msg = MsgAggregateExchangeRateVote {
+            salt,
+            exchange_rates,
+            feeder: feeder.to_string(),
+            validator: validator.to_string(),
+        };
+        Msg::new(module.vote_type_url(), msg)
+    }
+}
+
+impl Contact {
+    /// Gets the params of the oracle module that are common to all supported chains
+    pub async fn get_oracle_params(
+        &self,
+        module: OracleModule,
+    ) -> Result<OracleParams, CosmosGrpcError> {
+        let res: QueryParamsResponse = self
+            .query_raw(module.params_path(), QueryParamsRequest {})
+            .await?;
+        match res.params {
+            Some(params) => Ok(params),
+            None => Err(CosmosGrpcError::BadResponse("No oracle params".to_string())),
+        }
+    }
+
+    /// Gets the vote period the chain is currently in, feeders use this to time their
+    /// prevote and vote
+    pub async fn get_oracle_vote_period(
+        &self,
+        module: OracleModule,
+    ) -> Result<VotePeriod, CosmosGrpcError> {
+        let (params, status) =
+            tokio::try_join!(self.get_oracle_params(module), self.get_chain_status())?;
+        match status {
+            ChainStatus::Moving { block_height } => {
+                Ok(VotePeriod::at_height(block_height, params.vote_period))
+            }
+            ChainStatus::Syncing => Err(CosmosGrpcError::NodeNotSynced),
+            ChainStatus::WaitingToStart => Err(CosmosGrpcError::ChainNotRunning),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+
+    #[test]
+    fn test_aggregate_vote_hash() {
+        let validator: Address = "cosmosvaloper1sjllsnramtg3ewxqwwrwjxfgc4n4ef9u2lcnj0"
+            .parse()
+            .unwrap();
+        let hash = aggregate_vote_hash("1234", "1.500000000000000000uusd", validator);
+        assert_eq!(hash, "d2b4901d597cb60f1ad0ff7ffc10a829ad235e3b");
+        assert_eq!(generate_oracle_salt().len(), 4);
+
+        let msg = Msg::oracle_prevote(OracleModule::Kujira, hash.clone(), validator, validator);
+        assert_eq!(
+            msg.0.type_url,
+            "/kujira.oracle.MsgAggregateExchangeRatePrevote"
+        );
+        let decoded = MsgAggregateExchangeRatePrevote::decode(msg.0.value.as_slice()).unwrap();
+        assert_eq!(decoded.hash, hash);
+    }
+
+    #[test]
+    fn test_vote_period() {
+        let period = VotePeriod::at_height(25, 10);
+        assert_eq!(period.period, 2);
+        assert_eq!(period.start_height, 20);
+        assert_eq!(period.end_height, 29);
+        assert_eq!(period.blocks_remaining, 4);
+        assert_eq!(VotePeriod::at_height(29, 10).blocks_remaining, 0);
+    }
+}
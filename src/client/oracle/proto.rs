@@ -0,0 +1,47 @@
+//! Hand written prost types for the Terra style x/oracle module used by Kujira, Umee and
+//! other chains, these mirror the oracle tx.proto and query.proto of those chains. The
+//! message layouts are shared, only the proto package differs between chains
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgAggregateExchangeRatePrevote {
+    /// Hex encoded truncated sha256 of `{salt}:{exchange_rates}:{validator}`
+    #[prost(string, tag = "1")]
+    pub hash: String,
+    #[prost(string, tag = "2")]
+    pub feeder: String,
+    #[prost(string, tag = "3")]
+    pub validator: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgAggregateExchangeRateVote {
+    #[prost(string, tag = "1")]
+    pub salt: String,
+    #[prost(string, tag = "2")]
+    pub exchange_rates: String,
+    #[prost(string, tag = "3")]
+    pub feeder: String,
+    #[prost(string, tag = "4")]
+    pub validator: String,
+}
+
+/// The params shared by the oracle modules of every supported chain, the remaining
+/// fields differ between chains and are skipped when decoding
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OracleParams {
+    /// The length of a vote period in blocks
+    #[prost(uint64, tag = "1")]
+    pub vote_period: u64,
+    /// The fraction of voting power needed for a rate to be accepted, an sdk.Dec string
+    #[prost(string, tag = "2")]
+    pub vote_threshold: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryParamsRequest {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct QueryParamsResponse {
+    #[prost(message, optional, tag = "1")]
+    pub params: Option<OracleParams>,
+}
@@ -0,0 +1,151 @@
+//! A cursor over paginated list queries. The Cosmos SDK pages results with an opaque
+//! `next_key` returned with each page, a Paginator keeps track of it so callers can
+//! fetch page by page, collect everything, or consume the results as a Stream
+
+use crate::error::CosmosGrpcError;
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
+use futures_util::stream::{self, Stream};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+
+/// The page size used unless `with_page_size` is called, the SDK default
+pub const DEFAULT_PAGE_SIZE: u64 = 100;
+
+type PageFuture<T> =
+    Pin<Box<dyn Future<Output = Result<(Vec<T>, Option<PageResponse>), CosmosGrpcError>> + Send>>;
+
+/// Pages through the results of a list query, returned by the `paginate_` methods of
+/// Contact. Nothing is requested until the first page is asked for
+pub struct Paginator<T> {
+    fetch: Box<dyn FnMut(PageRequest) -> PageFuture<T> + Send>,
+    next_key: Vec<u8>,
+    page_size: u64,
+    done: bool,
+}
+
+impl<T: Send + 'static> Paginator<T> {
+    /// Creates a paginator from a query taking the page request, `fetch` must return
+    /// the page response of the query so the next page can be found
+    pub fn new<F, Fut>(mut fetch: F) -> Self
+    where
+        F: FnMut(PageRequest) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(Vec<T>, Option<PageResponse>), CosmosGrpcError>>
+            + Send
+            + 'static,
+    {
+        Paginator {
+            fetch: Box::new(move |request| Box::pin(fetch(request))),
+            next_key: Vec::new(),
+            page_size: DEFAULT_PAGE_SIZE,
+            done: false,
+        }
+    }
+
+    /// The number of results requested per page, nodes may return fewer
+    pub fn with_page_size(mut self, page_size: u64) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// True once the last page has been returned
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Fetches the next page, None once every page has been returned. A failed request
+    /// can be retried by calling this again
+    pub async fn next_page(&mut self) -> Result<Option<Vec<T>>, CosmosGrpcError> {
+        if self.done {
+            return Ok(None);
+        }
+        let request = PageRequest {
+            key: self.next_key.clone(),
+            offset: 0,
+            limit: self.page_size,
+            count_total: false,
+            reverse: false,
+        };
+        let (items, response) = (self.fetch)(request).await?;
+        self.next_key = response.map(|r| r.next_key).unwrap_or_default();
+        self.done = self.next_key.is_empty();
+        Ok(Some(items))
+    }
+
+    /// Fetches every remaining page
+    pub async fn all(mut self) -> Result<Vec<T>, CosmosGrpcError> {
+        let mut out = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            out.extend(page);
+        }
+        Ok(out)
+    }
+
+    /// The remaining results one at a time, pages are fetched as the stream is
+    /// consumed. The stream ends after the first error
+    pub fn into_stream(self) -> impl Stream<Item = Result<T, CosmosGrpcError>> + Send {
+        stream::unfold(
+            (Some(self), VecDeque::new()),
+            |(mut paginator, mut buffer)| async move {
+                loop {
+                    if let Some(item) = buffer.pop_front() {
+                        return Some((Ok(item), (paginator, buffer)));
+                    }
+                    match paginator.as_mut()?.next_page().await {
+                        Ok(Some(page)) => buffer.extend(page),
+                        Ok(None) => return None,
+                        Err(e) => return Some((Err(e), (None, buffer))),
+                    }
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    /// Pages through 0..total the way the SDK does, with the next key being the big
+    /// endian encoding of the next item
+    fn numbers(total: u64) -> Paginator<u64> {
+        Paginator::new(move |request: PageRequest| async move {
+            let start = if request.key.is_empty() {
+                0
+            } else {
+                u64::from_be_bytes(request.key.try_into().unwrap())
+            };
+            let end = (start + request.limit).min(total);
+            let next_key = if end < total {
+                end.to_be_bytes().to_vec()
+            } else {
+                Vec::new()
+            };
+            Ok((
+                (start..end).collect(),
+                Some(PageResponse { next_key, total: 0 }),
+            ))
+        })
+    }
+
+    #[actix_rt::test]
+    async fn test_paginator() {
+        let mut pages = numbers(5).with_page_size(2);
+        assert_eq!(pages.next_page().await.unwrap(), Some(vec![0, 1]));
+        assert_eq!(pages.next_page().await.unwrap(), Some(vec![2, 3]));
+        assert_eq!(pages.next_page().await.unwrap(), Some(vec![4]));
+        assert!(pages.is_done());
+        assert_eq!(pages.next_page().await.unwrap(), None);
+
+        assert_eq!(numbers(250).all().await.unwrap().len(), 250);
+
+        let streamed: Vec<u64> = numbers(7)
+            .with_page_size(3)
+            .into_stream()
+            .map(|r| r.unwrap())
+            .collect()
+            .await;
+        assert_eq!(streamed, (0..7).collect::<Vec<_>>());
+    }
+}
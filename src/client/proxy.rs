@@ -0,0 +1,322 @@
+//! Connecting to the gRPC endpoint through a SOCKS5 or HTTP CONNECT proxy. With Tor's
+//! SOCKS port (usually 127.0.0.1:9050) chain traffic is routed through Tor, and since
+//! hostnames are resolved by the proxy .onion endpoints can be used directly. Only the
+//! gRPC connection is proxied, the Tendermint RPC client connects directly
+
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tonic::codegen::http::Uri;
+use tonic::codegen::Service;
+
+/// Credentials for a proxy, the password is not printed by Debug
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProxyAuth {
+    pub username: String,
+    pub password: String,
+}
+
+impl std::fmt::Debug for ProxyAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyAuth")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// A proxy to connect through, `address` is the host:port of the proxy itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Proxy {
+    /// A SOCKS5 proxy such as Tor, the endpoint hostname is resolved by the proxy
+    Socks5 {
+        address: String,
+        auth: Option<ProxyAuth>,
+    },
+    /// An HTTP proxy supporting the CONNECT method, with optional basic auth
+    Http {
+        address: String,
+        auth: Option<ProxyAuth>,
+    },
+}
+
+impl Proxy {
+    /// The SOCKS port of a local Tor daemon
+    pub fn tor() -> Proxy {
+        Proxy::Socks5 {
+            address: "127.0.0.1:9050".to_string(),
+            auth: None,
+        }
+    }
+}
+
+/// Makes the TCP connections of a gRPC channel through a proxy
+#[derive(Debug, Clone)]
+pub(crate) struct ProxyConnector {
+    proxy: Proxy,
+}
+
+impl ProxyConnector {
+    pub fn new(proxy: Proxy) -> Self {
+        ProxyConnector { proxy }
+    }
+}
+
+impl Service<Uri> for ProxyConnector {
+    type Response = TcpStream;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<TcpStream, Error>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let proxy = self.proxy.clone();
+        Box::pin(async move {
+            let host = uri
+                .host()
+                .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "endpoint url has no host"))?
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_string();
+            let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+                Some("https") => 443,
+                _ => 80,
+            });
+            match proxy {
+                Proxy::Socks5 { address, auth } => {
+                    let mut stream = TcpStream::connect(address).await?;
+                    socks5_connect(&mut stream, &host, port, auth.as_ref()).await?;
+                    Ok(stream)
+                }
+                Proxy::Http { address, auth } => {
+                    let mut stream = TcpStream::connect(address).await?;
+                    http_connect(&mut stream, &host, port, auth.as_ref()).await?;
+                    Ok(stream)
+                }
+            }
+        })
+    }
+}
+
+fn proxy_error(message: String) -> Error {
+    Error::other(message)
+}
+
+/// Performs the RFC 1928 handshake asking the proxy to connect to host:port
+async fn socks5_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    auth: Option<&ProxyAuth>,
+) -> Result<(), Error> {
+    let method = if auth.is_some() { 0x02 } else { 0x00 };
+    stream.write_all(&[0x05, 0x01, method]).await?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != 0x05 || reply[1] != method {
+        return Err(proxy_error(
+            "SOCKS5 proxy does not accept our authentication method".to_string(),
+        ));
+    }
+    if let Some(auth) = auth {
+        // RFC 1929 username and password authentication
+        if auth.username.len() > 255 || auth.password.len() > 255 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "SOCKS5 credentials are limited to 255 bytes",
+            ));
+        }
+        let mut request = vec![0x01, auth.username.len() as u8];
+        request.extend_from_slice(auth.username.as_bytes());
+        request.push(auth.password.len() as u8);
+        request.extend_from_slice(auth.password.as_bytes());
+        stream.write_all(&request).await?;
+        stream.read_exact(&mut reply).await?;
+        if reply[1] != 0x00 {
+            return Err(proxy_error(
+                "SOCKS5 proxy rejected the credentials".to_string(),
+            ));
+        }
+    }
+
+    let mut request = vec![0x05, 0x01, 0x00];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(0x01);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(0x04);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            if host.len() > 255 {
+                return Err(Error::new(ErrorKind::InvalidInput, "hostname too long"));
+            }
+            request.push(0x03);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).await?;
+    if header[1] != 0x00 {
+        return Err(proxy_error(format!(
+            "SOCKS5 proxy failed to connect to {}:{} with code {}",
+            host, port, header[1]
+        )));
+    }
+    // the address the proxy bound, which we have no use for
+    let address_len = match header[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => stream.read_u8().await? as usize,
+        other => {
+            return Err(proxy_error(format!(
+                "SOCKS5 proxy replied with unknown address type {}",
+                other
+            )))
+        }
+    };
+    let mut bound = vec![0u8; address_len + 2];
+    stream.read_exact(&mut bound).await?;
+    Ok(())
+}
+
+/// Asks an HTTP proxy to open a tunnel to host:port
+async fn http_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    auth: Option<&ProxyAuth>,
+) -> Result<(), Error> {
+    let target = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(_)) => format!("[{}]:{}", host, port),
+        _ => format!("{}:{}", host, port),
+    };
+    let mut request = format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n");
+    if let Some(auth) = auth {
+        let credentials = base64::encode(format!("{}:{}", auth.username, auth.password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", credentials));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // read the response a byte at a time so nothing past the headers is consumed
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() > 8192 {
+            return Err(proxy_error("HTTP proxy response too long".to_string()));
+        }
+        response.push(stream.read_u8().await?);
+    }
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some("200") => Ok(()),
+        _ => Err(proxy_error(format!(
+            "HTTP proxy refused to connect to {}: {}",
+            target, status_line
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Connects through the proxy and checks that the tunnel carries data
+    async fn connect_through(proxy: Proxy, uri: &str) -> Result<Vec<u8>, Error> {
+        let mut stream = ProxyConnector::new(proxy)
+            .call(uri.parse().unwrap())
+            .await?;
+        stream.write_all(b"ping").await?;
+        let mut reply = vec![0u8; 4];
+        stream.read_exact(&mut reply).await?;
+        Ok(reply)
+    }
+
+    #[actix_rt::test]
+    async fn test_socks5_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut greeting = [0u8; 3];
+            socket.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(greeting, [5, 1, 2]);
+            socket.write_all(&[5, 2]).await.unwrap();
+            let mut login = [0u8; 9];
+            socket.read_exact(&mut login).await.unwrap();
+            assert_eq!(&login, b"\x01\x03bob\x03pwd");
+            socket.write_all(&[1, 0]).await.unwrap();
+            let mut request = vec![0u8; 5 + 17 + 2];
+            socket.read_exact(&mut request).await.unwrap();
+            assert_eq!(&request[..5], &[5, 1, 0, 3, 17]);
+            assert_eq!(&request[5..22], b"examplenode.onion");
+            assert_eq!(&request[22..], &9090u16.to_be_bytes());
+            socket
+                .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 80])
+                .await
+                .unwrap();
+            let mut ping = [0u8; 4];
+            socket.read_exact(&mut ping).await.unwrap();
+            socket.write_all(b"pong").await.unwrap();
+        });
+        let proxy = Proxy::Socks5 {
+            address,
+            auth: Some(ProxyAuth {
+                username: "bob".to_string(),
+                password: "pwd".to_string(),
+            }),
+        };
+        let reply = connect_through(proxy, "http://examplenode.onion:9090")
+            .await
+            .unwrap();
+        assert_eq!(reply, b"pong");
+    }
+
+    #[actix_rt::test]
+    async fn test_http_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            for status in ["200 Connection established", "403 Forbidden"] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                while !request.ends_with(b"\r\n\r\n") {
+                    request.push(socket.read_u8().await.unwrap());
+                }
+                let request = String::from_utf8(request).unwrap();
+                assert!(request.starts_with("CONNECT 10.0.0.1:9090 HTTP/1.1\r\n"));
+                let response = format!("HTTP/1.1 {}\r\n\r\n", status);
+                socket.write_all(response.as_bytes()).await.unwrap();
+                let mut ping = [0u8; 4];
+                if socket.read_exact(&mut ping).await.is_ok() {
+                    socket.write_all(b"pong").await.unwrap();
+                }
+            }
+        });
+        let proxy = Proxy::Http {
+            address,
+            auth: None,
+        };
+        let reply = connect_through(proxy.clone(), "http://10.0.0.1:9090")
+            .await
+            .unwrap();
+        assert_eq!(reply, b"pong");
+        assert!(connect_through(proxy, "http://10.0.0.1:9090")
+            .await
+            .is_err());
+    }
+}
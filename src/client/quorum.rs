@@ -0,0 +1,133 @@
+//! Reads that are checked against several independent endpoints of the same chain, so
+//! that a single malicious or corrupted provider can not report a false balance or
+//! account state before a large send. Nodes only agree on queries of the latest block
+//! if they are at the same height, pin the height with `Contact::at_height` for
+//! queries of state that changes every block
+
+use crate::client::Contact;
+use crate::error::CosmosGrpcError;
+use futures_util::future::join_all;
+use std::future::Future;
+use std::sync::Arc;
+
+impl Contact {
+    /// Adds endpoints that quorum queries are made against in addition to this
+    /// Contact's own. The endpoints share this Contact's chain config, connection
+    /// settings, retry policy, headers and registry but not its connection or cache
+    pub fn with_quorum_endpoints(mut self, urls: &[&str]) -> Result<Self, CosmosGrpcError> {
+        let mut endpoints = (*self.quorum_endpoints).clone();
+        for url in urls {
+            let mut contact = Contact::new_with_config(url, self.timeout, self.config.clone())?
+                .with_connection_config(self.connection.clone())
+                .with_retry_policy(self.retry);
+            contact.headers = self.headers.clone();
+            contact.registry = self.registry.clone();
+            endpoints.push(contact);
+        }
+        self.quorum_endpoints = Arc::new(endpoints);
+        Ok(self)
+    }
+
+    /// The urls of the endpoints added with `with_quorum_endpoints`
+    pub fn get_quorum_endpoints(&self) -> Vec<String> {
+        self.quorum_endpoints.iter().map(|c| c.get_url()).collect()
+    }
+
+    /// Runs `query` against this Contact and every quorum endpoint at once and returns
+    /// the result at least `required` of them agree on. Endpoints that fail count as
+    /// disagreeing. The query cache is bypassed so every endpoint is actually asked
+    ///
+    /// Fails with `NoQuorum` if no result reaches `required` or if two different
+    /// results both do, which is possible when `required` is half the endpoints or less
+    pub async fn quorum_query<F, Fut, T>(
+        &self,
+        query: F,
+        required: usize,
+    ) -> Result<T, CosmosGrpcError>
+    where
+        F: Fn(Contact) -> Fut,
+        Fut: Future<Output = Result<T, CosmosGrpcError>>,
+        T: PartialEq,
+    {
+        let endpoints = self.quorum_endpoints.len() + 1;
+        if required == 0 || required > endpoints {
+            return Err(CosmosGrpcError::BadInput(format!(
+                "A quorum of {} is not possible with {} endpoints",
+                required, endpoints
+            )));
+        }
+        let contacts = std::iter::once(self).chain(self.quorum_endpoints.iter());
+        let results = join_all(contacts.map(|contact| {
+            let mut contact = contact.clone();
+            contact.cache = None;
+            contact.height = self.height;
+            query(contact)
+        }))
+        .await;
+
+        // groups identical results, counting how many endpoints returned each
+        let mut groups: Vec<(T, usize)> = Vec::new();
+        for result in results {
+            match result {
+                Ok(value) => match groups.iter_mut().find(|(v, _)| *v == value) {
+                    Some((_, count)) => *count += 1,
+                    None => groups.push((value, 1)),
+                },
+                Err(e) => warn!("Quorum endpoint failed {:?}", e),
+            }
+        }
+        let agreeing = groups.iter().map(|(_, count)| *count).max().unwrap_or(0);
+        let mut reached = groups.into_iter().filter(|(_, count)| *count >= required);
+        match (reached.next(), reached.next()) {
+            (Some((value, _)), None) => Ok(value),
+            _ => Err(CosmosGrpcError::NoQuorum {
+                required,
+                agreeing,
+                endpoints,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[actix_rt::test]
+    async fn test_quorum_query() {
+        let contact = Contact::new("http://localhost:1", Duration::from_secs(1), "cosmos")
+            .unwrap()
+            .with_quorum_endpoints(&["http://localhost:2", "http://localhost:3"])
+            .unwrap();
+        assert_eq!(
+            contact.get_quorum_endpoints(),
+            vec!["http://localhost:2", "http://localhost:3"]
+        );
+
+        // the third endpoint reports a different value and the second fails
+        let query = |contact: Contact| async move {
+            match contact.get_url().as_str() {
+                "http://localhost:2" => Err(CosmosGrpcError::NodeNotSynced),
+                "http://localhost:3" => Ok(2),
+                _ => Ok(1),
+            }
+        };
+        assert_eq!(contact.quorum_query(query, 1).await.ok(), None);
+        assert!(matches!(
+            contact.quorum_query(query, 2).await,
+            Err(CosmosGrpcError::NoQuorum {
+                required: 2,
+                agreeing: 1,
+                endpoints: 3
+            })
+        ));
+        assert!(matches!(
+            contact.quorum_query(query, 4).await,
+            Err(CosmosGrpcError::BadInput(_))
+        ));
+
+        let query = |contact: Contact| async move { Ok(contact.get_url() != "http://localhost:3") };
+        assert!(contact.quorum_query(query, 2).await.unwrap());
+    }
+}
@@ -0,0 +1,152 @@
+//! Client side rate limiting for Contact. Public endpoints commonly answer bursts of
+//! requests with 429 or `ResourceExhausted`, a token bucket spaces requests out so that
+//! they are queued here rather than rejected by the node
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::time::{sleep, Sleep};
+use tonic::codegen::Service;
+
+/// Allows on average `requests` requests every `per`, with up to `burst` requests
+/// sent back to back after a quiet period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub per: Duration,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// A limit of `requests` per second with no bursting beyond that
+    pub fn per_second(requests: u32) -> Self {
+        RateLimit {
+            requests,
+            per: Duration::from_secs(1),
+            burst: requests,
+        }
+    }
+}
+
+/// A token bucket shared by all clones of a Contact
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    /// Tokens added per second
+    rate: f64,
+    burst: f64,
+    /// The tokens available as of the instant
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    pub fn new(limit: RateLimit) -> Self {
+        let burst = limit.burst.max(1) as f64;
+        TokenBucket {
+            rate: limit.requests.max(1) as f64 / limit.per.as_secs_f64().max(f64::EPSILON),
+            burst,
+            state: Mutex::new((burst, Instant::now())),
+        }
+    }
+
+    /// Takes a token if one is available, otherwise returns how long until one will be
+    pub fn try_take(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.1).as_secs_f64();
+        let tokens = (state.0 + elapsed * self.rate).min(self.burst);
+        *state = (tokens, now);
+        if tokens >= 1.0 {
+            state.0 -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - tokens) / self.rate))
+        }
+    }
+}
+
+/// Wraps a gRPC channel, waiting for a token from the bucket before each request
+pub struct RateLimitedChannel<S> {
+    inner: S,
+    bucket: Option<Arc<TokenBucket>>,
+    wait: Option<Pin<Box<Sleep>>>,
+    has_token: bool,
+}
+
+impl<S> RateLimitedChannel<S> {
+    pub(crate) fn new(inner: S, bucket: Option<Arc<TokenBucket>>) -> Self {
+        RateLimitedChannel {
+            inner,
+            bucket,
+            wait: None,
+            has_token: false,
+        }
+    }
+}
+
+impl<S: Clone> Clone for RateLimitedChannel<S> {
+    fn clone(&self) -> Self {
+        RateLimitedChannel::new(self.inner.clone(), self.bucket.clone())
+    }
+}
+
+impl<S: std::fmt::Debug> std::fmt::Debug for RateLimitedChannel<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimitedChannel")
+            .field("inner", &self.inner)
+            .field("bucket", &self.bucket)
+            .finish()
+    }
+}
+
+impl<S, R> Service<R> for RateLimitedChannel<S>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if let Some(bucket) = &self.bucket {
+            while !self.has_token {
+                if let Some(wait) = &mut self.wait {
+                    ready!(wait.as_mut().poll(cx));
+                    self.wait = None;
+                }
+                match bucket.try_take() {
+                    Ok(()) => self.has_token = true,
+                    Err(delay) => self.wait = Some(Box::pin(sleep(delay))),
+                }
+            }
+        }
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        self.has_token = false;
+        self.inner.call(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let bucket = TokenBucket::new(RateLimit {
+            requests: 10,
+            per: Duration::from_secs(1),
+            burst: 3,
+        });
+        for _ in 0..3 {
+            assert!(bucket.try_take().is_ok());
+        }
+        let wait = bucket.try_take().unwrap_err();
+        assert!(wait <= Duration::from_millis(100));
+        std::thread::sleep(wait);
+        assert!(bucket.try_take().is_ok());
+    }
+}
@@ -0,0 +1,193 @@
+//! Retrying of requests that fail for transient reasons, such as an overloaded or
+//! restarting node. The policy is set per Contact and applied to its queries and
+//! broadcasts, `Contact::retry` applies it to any other operation
+
+use crate::client::Contact;
+use crate::error::{BroadcastError, CosmosGrpcError};
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::sleep;
+use tonic::Code;
+
+/// How many times and how quickly to retry a failed request
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// The total number of attempts, 1 disables retries
+    pub max_attempts: u32,
+    /// The delay before the first retry, doubled for every retry after that
+    pub base_delay: Duration,
+    /// The maximum delay between attempts
+    pub max_delay: Duration,
+    /// The fraction of each delay that is randomized, between 0 and 1. Jitter keeps
+    /// many clients that failed at the same time from retrying in lockstep
+    pub jitter: f64,
+    /// Decides if an error is worth retrying, see `is_transient`
+    pub retryable: fn(&CosmosGrpcError) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(4),
+            jitter: 0.5,
+            retryable: is_transient,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that makes every request exactly once
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// The delay before retry number `retry`, starting at 1
+    pub fn delay(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return delay;
+        }
+        let factor = 1.0 - jitter * rand::thread_rng().gen::<f64>();
+        delay.mul_f64(factor)
+    }
+}
+
+/// The default retry classifier, true for errors that say nothing about the request
+/// itself: connection failures, a node that is unavailable, overloaded or timed
+/// out, and a full mempool
+pub fn is_transient(error: &CosmosGrpcError) -> bool {
+    match error {
+        CosmosGrpcError::ConnectionError { .. } => true,
+        CosmosGrpcError::RequestError { error } => matches!(
+            error.code(),
+            Code::Unavailable | Code::DeadlineExceeded | Code::ResourceExhausted | Code::Aborted
+        ),
+        CosmosGrpcError::TransactionFailed {
+            reason: BroadcastError::MempoolFull,
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+impl Contact {
+    /// Replaces the retry policy of this Contact
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn get_retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// Runs `op` until it succeeds, fails with an error the retry policy does not
+    /// consider retryable, or runs out of attempts
+    pub async fn retry<T, F, Fut>(&self, mut op: F) -> Result<T, CosmosGrpcError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, CosmosGrpcError>>,
+    {
+        retry_with(&self.retry, &self.url, |_| op()).await
+    }
+}
+
+/// Runs `op` under `policy`, passing it the attempt number starting at 1 so that an
+/// operation can tell a first attempt from a retry
+pub(crate) async fn retry_with<T, F, Fut>(
+    policy: &RetryPolicy,
+    url: &str,
+    mut op: F,
+) -> Result<T, CosmosGrpcError>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<T, CosmosGrpcError>>,
+{
+    let mut attempt = 1;
+    loop {
+        match op(attempt).await {
+            Err(e) if attempt < policy.max_attempts && (policy.retryable)(&e) => {
+                let delay = policy.delay(attempt);
+                warn!(
+                    "Request to {} failed on attempt {} retrying in {}ms: {}",
+                    url,
+                    attempt,
+                    delay.as_millis(),
+                    e
+                );
+                sleep(delay).await;
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tonic::Status;
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy {
+            jitter: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(policy.delay(1), Duration::from_millis(250));
+        assert_eq!(policy.delay(3), Duration::from_secs(1));
+        assert_eq!(policy.delay(40), Duration::from_secs(4));
+
+        let jittered = RetryPolicy::default();
+        for _ in 0..100 {
+            let delay = jittered.delay(2);
+            assert!(delay <= Duration::from_millis(500));
+            assert!(delay >= Duration::from_millis(250));
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_retry_transient_errors() {
+        let contact = Contact::new("http://127.0.0.1:1", Duration::from_secs(1), "cosmos")
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                base_delay: Duration::from_millis(1),
+                ..Default::default()
+            });
+        let calls = AtomicU32::new(0);
+        let res: Result<(), _> = contact
+            .retry(|| async {
+                calls.fetch_add(1, Ordering::Relaxed);
+                Err(CosmosGrpcError::RequestError {
+                    error: Status::unavailable("down"),
+                })
+            })
+            .await;
+        assert!(res.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+
+        calls.store(0, Ordering::Relaxed);
+        let res: Result<(), _> = contact
+            .retry(|| async {
+                calls.fetch_add(1, Ordering::Relaxed);
+                Err(CosmosGrpcError::RequestError {
+                    error: Status::invalid_argument("bad"),
+                })
+            })
+            .await;
+        assert!(res.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,358 @@
+//! A minimal client for the Tendermint JSON-RPC endpoint (usually port 26657), this
+//! is a sibling of the gRPC based Contact for the handful of operations that are not
+//! exposed over gRPC at all such as block results events and consensus health
+
+use crate::client::Contact;
+use crate::error::CosmosGrpcError;
+use crate::tx::DecodedTx;
+use crate::utils::hex_str_to_bytes;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+pub mod proof;
+pub mod types;
+
+pub use proof::VerifiedTx;
+pub use types::*;
+
+/// The order of results returned by tx_search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Order {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Order::Asc => "asc",
+            Order::Desc => "desc",
+        }
+    }
+}
+
+/// A JSON-RPC client for a Tendermint node
+#[derive(Debug, Clone)]
+pub struct TendermintRpc {
+    /// The RPC url, for example http://localhost:26657
+    url: String,
+    client: reqwest::Client,
+}
+
+impl TendermintRpc {
+    pub fn new(url: &str, timeout: Duration) -> Result<Self, CosmosGrpcError> {
+        let client = match reqwest::Client::builder().timeout(timeout).build() {
+            Ok(c) => c,
+            Err(e) => return Err(CosmosGrpcError::BadInput(format!("{}", e))),
+        };
+        Ok(TendermintRpc {
+            url: url.trim_end_matches('/').to_string(),
+            client,
+        })
+    }
+
+    pub fn get_url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Performs a single JSON-RPC call and parses the result field of the response
+    pub async fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, CosmosGrpcError> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let res = match self.client.post(&self.url).json(&body).send().await {
+            Ok(res) => res,
+            Err(e) => {
+                return Err(CosmosGrpcError::BadResponse(format!(
+                    "Failed to call {} on {} {}",
+                    method, self.url, e
+                )))
+            }
+        };
+        let res: RpcResponse<T> = match res.json().await {
+            Ok(res) => res,
+            Err(e) => {
+                return Err(CosmosGrpcError::BadStruct(format!(
+                    "Failed to parse {} response {}",
+                    method, e
+                )))
+            }
+        };
+        res.into_result()
+    }
+
+    /// Node info, the latest block, and whether the node is catching up
+    pub async fn status(&self) -> Result<RpcStatus, CosmosGrpcError> {
+        self.request("status", json!({})).await
+    }
+
+    /// The peers this node is connected to
+    pub async fn net_info(&self) -> Result<NetInfo, CosmosGrpcError> {
+        self.request("net_info", json!({})).await
+    }
+
+    /// A summary of the current consensus round, useful for checking how close a
+    /// stalled chain is to reaching 2/3 prevotes or precommits
+    pub async fn consensus_state(&self) -> Result<ConsensusState, CosmosGrpcError> {
+        self.request("consensus_state", json!({})).await
+    }
+
+    /// Searches indexed transactions using a Tendermint query, for example
+    /// `message.sender='cosmos1...'`, pages start at 1
+    pub async fn tx_search(
+        &self,
+        query: &str,
+        page: u32,
+        per_page: u8,
+        order: Order,
+    ) -> Result<TxSearchResult, CosmosGrpcError> {
+        self.request(
+            "tx_search",
+            json!({
+                "query": query,
+                "prove": false,
+                "page": page.to_string(),
+                "per_page": per_page.to_string(),
+                "order_by": order.as_str(),
+            }),
+        )
+        .await
+    }
+
+    /// A single transaction by hash, with its inclusion proof if `prove` is set
+    pub async fn tx(&self, hash: &[u8], prove: bool) -> Result<RpcTx, CosmosGrpcError> {
+        self.request(
+            "tx",
+            json!({ "hash": base64::encode(hash), "prove": prove }),
+        )
+        .await
+    }
+
+    /// The block at `height`, or the latest block if height is None
+    pub async fn block(&self, height: Option<u64>) -> Result<RpcBlockResponse, CosmosGrpcError> {
+        let params = match height {
+            Some(height) => json!({ "height": height.to_string() }),
+            None => json!({}),
+        };
+        self.request("block", params).await
+    }
+
+    /// Up to `limit` transactions waiting in the mempool, Tendermint caps limit at 100
+    pub async fn unconfirmed_txs(&self, limit: u32) -> Result<UnconfirmedTxs, CosmosGrpcError> {
+        self.request("unconfirmed_txs", json!({ "limit": limit.to_string() }))
+            .await
+    }
+
+    /// The number and total size of transactions in the mempool
+    pub async fn num_unconfirmed_txs(&self) -> Result<UnconfirmedTxs, CosmosGrpcError> {
+        self.request("num_unconfirmed_txs", json!({})).await
+    }
+
+    /// The results of executing the block at `height`, or the latest block if
+    /// height is None, including events emitted outside of transactions
+    pub async fn block_results(
+        &self,
+        height: Option<u64>,
+    ) -> Result<BlockResults, CosmosGrpcError> {
+        let params = match height {
+            Some(height) => json!({ "height": height.to_string() }),
+            None => json!({}),
+        };
+        self.request("block_results", params).await
+    }
+}
+
+impl Contact {
+    /// Sets the Tendermint RPC endpoint (usually port 26657) of this Contact, required
+    /// for queries such as `get_block_results` that are not available over gRPC
+    pub fn with_rpc_url(mut self, url: &str) -> Result<Self, CosmosGrpcError> {
+        self.rpc = Some(TendermintRpc::new(url, self.timeout)?);
+        Ok(self)
+    }
+
+    /// The Tendermint RPC client of this Contact, an error if no RPC url was set
+    pub fn get_rpc(&self) -> Result<&TendermintRpc, CosmosGrpcError> {
+        match &self.rpc {
+            Some(rpc) => Ok(rpc),
+            None => Err(CosmosGrpcError::BadInput(
+                "This Contact has no Tendermint RPC url, see with_rpc_url()".to_string(),
+            )),
+        }
+    }
+
+    /// Gets the events emitted while executing the block at `height`, including the
+    /// begin and end block events that never appear in any tx log such as staking
+    /// reward distribution and slashing. Requires an RPC url, see `with_rpc_url`
+    pub async fn get_block_results(&self, height: u64) -> Result<BlockEvents, CosmosGrpcError> {
+        let results = self.get_rpc()?.block_results(Some(height)).await?;
+        Ok(BlockEvents::from(results))
+    }
+
+    /// Gets a transaction by hash along with a Merkle proof of its inclusion, the proof is
+    /// verified against the data hash of the header of the block the tx is in. The
+    /// header itself comes from the same node, so a light client must still check the
+    /// returned data hash against a header it trusts. Requires an RPC url
+    pub async fn get_tx_with_proof(&self, txhash: &str) -> Result<VerifiedTx, CosmosGrpcError> {
+        let rpc = self.get_rpc()?;
+        let hash = match hex_str_to_bytes(txhash) {
+            Ok(hash) => hash,
+            Err(e) => return Err(CosmosGrpcError::BadInput(format!("Bad tx hash {:?}", e))),
+        };
+        let tx = rpc.tx(&hash, true).await?;
+        let proof = match &tx.proof {
+            Some(proof) => proof,
+            None => {
+                return Err(CosmosGrpcError::BadResponse(format!(
+                    "No proof returned for tx {}",
+                    txhash
+                )))
+            }
+        };
+        let header = rpc.block(Some(tx.height)).await?.block.header;
+        let data_hash = match hex_str_to_bytes(&header.data_hash) {
+            Ok(hash) => hash,
+            Err(e) => {
+                return Err(CosmosGrpcError::BadResponse(format!(
+                    "Bad data hash {:?}",
+                    e
+                )))
+            }
+        };
+        let tx_bytes = match base64::decode(&tx.tx) {
+            Ok(bytes) => bytes,
+            Err(e) => return Err(CosmosGrpcError::BadResponse(format!("Bad tx {}", e))),
+        };
+        proof::verify_tx_proof(txhash, &tx_bytes, proof, &data_hash)?;
+        Ok(VerifiedTx { tx, data_hash })
+    }
+
+    /// Gets up to `limit` pending transactions from the mempool of the RPC node, any
+    /// tx that can not be decoded as a Cosmos tx is skipped. Requires an RPC url
+    pub async fn get_unconfirmed_txs(&self, limit: u32) -> Result<Vec<DecodedTx>, CosmosGrpcError> {
+        let res = self.get_rpc()?.unconfirmed_txs(limit).await?;
+        let mut txs = Vec::new();
+        for tx in res.txs {
+            let bytes = match base64::decode(&tx) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    warn!("Skipping mempool tx with bad encoding {}", e);
+                    continue;
+                }
+            };
+            match DecodedTx::decode(&bytes) {
+                Ok(decoded) => txs.push(decoded),
+                Err(e) => warn!("Skipping undecodable mempool tx {}", e),
+            }
+        }
+        Ok(txs)
+    }
+
+    /// Gets the number of pending transactions and their total size in bytes from the
+    /// mempool of the RPC node. Requires an RPC url
+    pub async fn get_num_unconfirmed_txs(&self) -> Result<(u64, u64), CosmosGrpcError> {
+        let res = self.get_rpc()?.num_unconfirmed_txs().await?;
+        Ok((res.total, res.total_bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmos_sdk_proto::cosmos::tx::v1beta1::{AuthInfo, TxBody, TxRaw};
+    use prost::Message;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answers a single JSON-RPC call to `method` with `result`
+    async fn serve_result(method: &'static str, result: Value) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                request.push(socket.read_u8().await.unwrap());
+            }
+            let headers = String::from_utf8(request).unwrap().to_lowercase();
+            let length: usize = headers
+                .lines()
+                .find_map(|l| l.strip_prefix("content-length:"))
+                .unwrap()
+                .trim()
+                .parse()
+                .unwrap();
+            let mut body = vec![0u8; length];
+            socket.read_exact(&mut body).await.unwrap();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["method"], method);
+
+            let response = json!({"jsonrpc": "2.0", "id": 1, "result": result}).to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        url
+    }
+
+    #[actix_rt::test]
+    async fn test_get_unconfirmed_txs() {
+        let tx = TxRaw {
+            body_bytes: TxBody {
+                memo: "pending".to_string(),
+                timeout_height: 12,
+                ..Default::default()
+            }
+            .encode_to_vec(),
+            auth_info_bytes: AuthInfo::default().encode_to_vec(),
+            signatures: vec![vec![0; 64]],
+        }
+        .encode_to_vec();
+        // undecodable txs are skipped, whether the base64 or the proto is invalid
+        let url = serve_result(
+            "unconfirmed_txs",
+            json!({
+                "n_txs": "3",
+                "total": "3",
+                "total_bytes": "200",
+                "txs": [base64::encode(&tx), "not base64!", base64::encode([0xff, 0xff])],
+            }),
+        )
+        .await;
+        let contact = Contact::new("http://localhost:9090", Duration::from_secs(5), "cosmos")
+            .unwrap()
+            .with_rpc_url(&url)
+            .unwrap();
+        let txs = contact.get_unconfirmed_txs(10).await.unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0], DecodedTx::decode(&tx).unwrap());
+        assert_eq!(txs[0].memo, "pending");
+        assert_eq!(txs[0].timeout_height, 12);
+
+        let url = serve_result(
+            "num_unconfirmed_txs",
+            json!({"n_txs": "0", "total": "3", "total_bytes": "200", "txs": null}),
+        )
+        .await;
+        let contact = contact.with_rpc_url(&url).unwrap();
+        assert_eq!(contact.get_num_unconfirmed_txs().await.unwrap(), (3, 200));
+
+        // without an RPC url the mempool can not be read
+        let contact =
+            Contact::new("http://localhost:9090", Duration::from_secs(5), "cosmos").unwrap();
+        assert!(matches!(
+            contact.get_unconfirmed_txs(10).await,
+            Err(CosmosGrpcError::BadInput(_))
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+//! Verification of transaction inclusion proofs. Tendermint commits to the
+//! transactions of a block with an RFC 6962 style Merkle tree over the sha256 hash of
+//! each tx, the root of which is the `data_hash` of the block header
+
+use super::types::{RpcTx, TxProof};
+use crate::error::CosmosGrpcError;
+use crate::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use sha2::{Digest, Sha256};
+
+/// A transaction along with the header data hash its inclusion was verified against
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedTx {
+    pub tx: RpcTx,
+    /// The data hash of the header of the block containing the tx, a light client should
+    /// check this against a header it trusts
+    pub data_hash: Vec<u8>,
+}
+
+fn leaf_hash(leaf: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(leaf);
+    hasher.finalize().to_vec()
+}
+
+fn inner_hash(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().to_vec()
+}
+
+/// The largest power of two strictly less than `total`
+fn split_point(total: u64) -> u64 {
+    let point = 1 << (63 - total.leading_zeros());
+    if point == total {
+        point >> 1
+    } else {
+        point
+    }
+}
+
+/// Computes the root of the tree from a leaf hash and the hashes of its siblings from
+/// the bottom of the tree up, None if the number of aunts does not fit the tree
+fn root_from_aunts(index: u64, total: u64, leaf: Vec<u8>, aunts: &[Vec<u8>]) -> Option<Vec<u8>> {
+    if index >= total {
+        return None;
+    }
+    match (total, aunts.split_last()) {
+        (1, None) => Some(leaf),
+        (1, Some(_)) | (_, None) => None,
+        (_, Some((aunt, rest))) => {
+            let num_left = split_point(total);
+            if index < num_left {
+                let left = root_from_aunts(index, num_left, leaf, rest)?;
+                Some(inner_hash(&left, aunt))
+            } else {
+                let right = root_from_aunts(index - num_left, total - num_left, leaf, rest)?;
+                Some(inner_hash(aunt, &right))
+            }
+        }
+    }
+}
+
+fn decode_base64(value: &str, field: &str) -> Result<Vec<u8>, String> {
+    base64::decode(value).map_err(|e| format!("bad {} encoding {}", field, e))
+}
+
+/// Checks that `proof` proves the inclusion of `tx` in a block with the given header
+/// data hash, and that the tx hashes to `txhash`
+pub fn verify_tx_proof(
+    txhash: &str,
+    tx: &[u8],
+    proof: &TxProof,
+    data_hash: &[u8],
+) -> Result<(), CosmosGrpcError> {
+    let fail = |reason: String| CosmosGrpcError::InvalidProof {
+        txhash: txhash.to_string(),
+        reason,
+    };
+    let tx_hash = Sha256::digest(tx).to_vec();
+    match hex_str_to_bytes(txhash) {
+        Ok(expected) if expected == tx_hash => {}
+        _ => return Err(fail(format!("tx hashes to {}", bytes_to_hex_str(&tx_hash)))),
+    }
+    if decode_base64(&proof.data, "data").map_err(fail)? != tx {
+        return Err(fail("proof is for a different tx".to_string()));
+    }
+    let leaf = leaf_hash(&tx_hash);
+    if decode_base64(&proof.proof.leaf_hash, "leaf hash").map_err(fail)? != leaf {
+        return Err(fail("leaf hash does not match the tx".to_string()));
+    }
+    let aunts = proof
+        .proof
+        .aunts
+        .iter()
+        .map(|aunt| decode_base64(aunt, "aunt"))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(fail)?;
+    let root = root_from_aunts(proof.proof.index, proof.proof.total, leaf, &aunts)
+        .ok_or_else(|| fail("proof does not fit the tree".to_string()))?;
+    if root != data_hash {
+        return Err(fail(format!(
+            "proof root {} does not match data hash {}",
+            bytes_to_hex_str(&root),
+            bytes_to_hex_str(data_hash)
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::types::MerkleProof;
+    use super::*;
+
+    fn proof_for(tx: &[u8], index: u64, total: u64, aunts: &[Vec<u8>]) -> TxProof {
+        TxProof {
+            root_hash: String::new(),
+            data: base64::encode(tx),
+            proof: MerkleProof {
+                total,
+                index,
+                leaf_hash: base64::encode(leaf_hash(&Sha256::digest(tx))),
+                aunts: aunts.iter().map(base64::encode).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_verify_tx_proof() {
+        let txs: Vec<&[u8]> = vec![b"tx one", b"tx two", b"tx three"];
+        let hash = |tx: &[u8]| bytes_to_hex_str(&Sha256::digest(tx));
+        let leaves: Vec<Vec<u8>> = txs
+            .iter()
+            .map(|tx| leaf_hash(&Sha256::digest(tx)))
+            .collect();
+        // a tree of three txs splits into the first two and the third
+        let left = inner_hash(&leaves[0], &leaves[1]);
+        let root = inner_hash(&left, &leaves[2]);
+
+        let proof = proof_for(txs[0], 0, 3, &[leaves[1].clone(), leaves[2].clone()]);
+        verify_tx_proof(&hash(txs[0]), txs[0], &proof, &root).unwrap();
+        let proof = proof_for(txs[2], 2, 3, std::slice::from_ref(&left));
+        verify_tx_proof(&hash(txs[2]), txs[2], &proof, &root).unwrap();
+
+        // a single tx block's data hash is the leaf hash
+        let proof = proof_for(txs[1], 0, 1, &[]);
+        verify_tx_proof(&hash(txs[1]), txs[1], &proof, &leaves[1]).unwrap();
+
+        // the wrong position, a different data hash, and a proof for another tx all fail
+        let proof = proof_for(txs[2], 1, 3, std::slice::from_ref(&left));
+        assert!(verify_tx_proof(&hash(txs[2]), txs[2], &proof, &root).is_err());
+        let proof = proof_for(txs[2], 2, 3, &[left]);
+        assert!(verify_tx_proof(&hash(txs[2]), txs[2], &proof, &leaves[0]).is_err());
+        assert!(verify_tx_proof(&hash(txs[1]), txs[2], &proof, &root).is_err());
+    }
+}
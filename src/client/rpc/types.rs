@@ -0,0 +1,639 @@
+//! Response types of the Tendermint JSON-RPC endpoint. Tendermint encodes 64 bit
+//! integers as strings, these are parsed into numbers. Unknown fields are ignored.
+
+use crate::error::CosmosGrpcError;
+use crate::registry::{AnyRegistry, TypedValue};
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Accepts a number encoded either as a json string or a json number
+fn string_or_number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => s.parse().map_err(de::Error::custom),
+        Value::Number(n) => n.to_string().parse().map_err(de::Error::custom),
+        other => Err(de::Error::custom(format!(
+            "expected a number got {}",
+            other
+        ))),
+    }
+}
+
+/// The JSON-RPC response envelope
+#[derive(Deserialize, Debug)]
+pub(crate) struct RpcResponse<T> {
+    pub result: Option<T>,
+    pub error: Option<RpcErrorObject>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct RpcErrorObject {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<String>,
+}
+
+impl<T> RpcResponse<T> {
+    pub fn into_result(self) -> Result<T, CosmosGrpcError> {
+        match (self.result, self.error) {
+            (_, Some(e)) => Err(CosmosGrpcError::RpcError {
+                code: e.code,
+                message: e.message,
+                data: e.data,
+            }),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(CosmosGrpcError::BadResponse(
+                "RPC response has neither a result nor an error".to_string(),
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcStatus {
+    pub node_info: NodeInfo,
+    pub sync_info: SyncInfo,
+    pub validator_info: ValidatorInfo,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct NodeInfo {
+    pub id: String,
+    pub listen_addr: String,
+    /// The chain id
+    pub network: String,
+    pub version: String,
+    pub moniker: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SyncInfo {
+    pub latest_block_hash: String,
+    pub latest_app_hash: String,
+    #[serde(deserialize_with = "string_or_number")]
+    pub latest_block_height: u64,
+    pub latest_block_time: String,
+    /// The lowest block this node has, greater than 1 on pruned or state synced nodes
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub earliest_block_height: u64,
+    pub catching_up: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ValidatorInfo {
+    pub address: String,
+    #[serde(default)]
+    pub pub_key: Option<RpcPubKey>,
+    /// Zero if this node is not a validator
+    #[serde(deserialize_with = "string_or_number")]
+    pub voting_power: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcPubKey {
+    #[serde(rename = "type")]
+    pub key_type: String,
+    /// base64 encoded key bytes
+    pub value: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct NetInfo {
+    pub listening: bool,
+    #[serde(default)]
+    pub listeners: Vec<String>,
+    #[serde(deserialize_with = "string_or_number")]
+    pub n_peers: u64,
+    #[serde(default)]
+    pub peers: Vec<Peer>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Peer {
+    pub node_info: NodeInfo,
+    pub is_outbound: bool,
+    pub remote_ip: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ConsensusState {
+    pub round_state: RoundState,
+}
+
+/// The summary of the current round returned by consensus_state
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RoundState {
+    /// Formatted as height/round/step
+    #[serde(rename = "height/round/step")]
+    pub height_round_step: String,
+    pub start_time: String,
+    #[serde(default)]
+    pub proposal_block_hash: String,
+    #[serde(default)]
+    pub locked_block_hash: String,
+    #[serde(default)]
+    pub valid_block_hash: String,
+    #[serde(default)]
+    pub height_vote_set: Vec<HeightVoteSet>,
+    #[serde(default)]
+    pub proposer: Option<Proposer>,
+}
+
+impl RoundState {
+    fn part(&self, i: usize) -> Option<u64> {
+        self.height_round_step.split('/').nth(i)?.parse().ok()
+    }
+
+    pub fn height(&self) -> Option<u64> {
+        self.part(0)
+    }
+
+    pub fn round(&self) -> Option<u64> {
+        self.part(1)
+    }
+
+    pub fn step(&self) -> Option<u64> {
+        self.part(2)
+    }
+}
+
+/// The votes seen for a single round, the bit array strings end with the fraction of
+/// voting power that has voted for example `BA{100:xxx_} 60/100 = 0.60`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct HeightVoteSet {
+    #[serde(deserialize_with = "string_or_number")]
+    pub round: u64,
+    #[serde(default)]
+    pub prevotes: Vec<String>,
+    pub prevotes_bit_array: String,
+    #[serde(default)]
+    pub precommits: Vec<String>,
+    pub precommits_bit_array: String,
+}
+
+impl HeightVoteSet {
+    fn parse_fraction(bit_array: &str) -> Option<f64> {
+        bit_array.rsplit('=').next()?.trim().parse().ok()
+    }
+
+    /// The fraction of voting power that has prevoted in this round
+    pub fn prevote_fraction(&self) -> Option<f64> {
+        Self::parse_fraction(&self.prevotes_bit_array)
+    }
+
+    /// The fraction of voting power that has precommitted in this round
+    pub fn precommit_fraction(&self) -> Option<f64> {
+        Self::parse_fraction(&self.precommits_bit_array)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Proposer {
+    pub address: String,
+    #[serde(deserialize_with = "string_or_number")]
+    pub index: i64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TxSearchResult {
+    #[serde(default)]
+    pub txs: Vec<RpcTx>,
+    #[serde(deserialize_with = "string_or_number")]
+    pub total_count: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcTx {
+    /// Uppercase hex tx hash
+    pub hash: String,
+    #[serde(deserialize_with = "string_or_number")]
+    pub height: u64,
+    pub index: u32,
+    pub tx_result: ExecTxResult,
+    /// base64 encoded tx bytes
+    pub tx: String,
+    /// Only returned when a proof is requested
+    #[serde(default)]
+    pub proof: Option<TxProof>,
+}
+
+/// A Merkle proof that a tx is part of a block
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TxProof {
+    /// Uppercase hex root of the tx Merkle tree, the data hash of the block header
+    pub root_hash: String,
+    /// base64 encoded tx bytes
+    pub data: String,
+    pub proof: MerkleProof,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct MerkleProof {
+    /// The number of txs in the block
+    #[serde(deserialize_with = "string_or_number")]
+    pub total: u64,
+    /// The position of the tx in the block
+    #[serde(deserialize_with = "string_or_number")]
+    pub index: u64,
+    /// base64 encoded
+    pub leaf_hash: String,
+    /// base64 encoded hashes of the sibling subtrees, from the bottom of the tree up
+    #[serde(default, deserialize_with = "nullable_vec")]
+    pub aunts: Vec<String>,
+}
+
+/// The block returned by the block method, only the header is parsed
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcBlockResponse {
+    pub block: RpcBlock,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcBlock {
+    pub header: RpcHeader,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcHeader {
+    pub chain_id: String,
+    #[serde(deserialize_with = "string_or_number")]
+    pub height: u64,
+    pub time: String,
+    /// Uppercase hex root of the tx Merkle tree
+    pub data_hash: String,
+    pub app_hash: String,
+}
+
+/// The result of executing a single transaction
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ExecTxResult {
+    #[serde(default)]
+    pub code: u32,
+    #[serde(default)]
+    pub data: Option<String>,
+    #[serde(default)]
+    pub log: String,
+    #[serde(default)]
+    pub info: String,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub gas_wanted: i64,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub gas_used: i64,
+    #[serde(default, deserialize_with = "nullable_vec")]
+    pub events: Vec<AbciEvent>,
+    #[serde(default)]
+    pub codespace: String,
+}
+
+/// An event emitted during block execution. Tendermint 0.34 base64 encodes attribute
+/// keys and values while later versions use plain strings, no decoding is done here
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct AbciEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, deserialize_with = "nullable_vec")]
+    pub attributes: Vec<EventAttribute>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct EventAttribute {
+    pub key: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub index: bool,
+}
+
+/// The results of executing a block, fields Tendermint returns as null are empty
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct BlockResults {
+    #[serde(deserialize_with = "string_or_number")]
+    pub height: u64,
+    #[serde(default, deserialize_with = "nullable_vec")]
+    pub txs_results: Vec<ExecTxResult>,
+    #[serde(default, deserialize_with = "nullable_vec")]
+    pub begin_block_events: Vec<AbciEvent>,
+    #[serde(default, deserialize_with = "nullable_vec")]
+    pub end_block_events: Vec<AbciEvent>,
+    /// Replaces begin and end block events in CometBFT 0.38
+    #[serde(default, deserialize_with = "nullable_vec")]
+    pub finalize_block_events: Vec<AbciEvent>,
+    #[serde(default, deserialize_with = "nullable_vec")]
+    pub validator_updates: Vec<Value>,
+    #[serde(default)]
+    pub consensus_param_updates: Option<Value>,
+}
+
+/// The contents of the mempool as returned by unconfirmed_txs, txs is always
+/// empty for num_unconfirmed_txs
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct UnconfirmedTxs {
+    /// The number of txs returned
+    #[serde(deserialize_with = "string_or_number")]
+    pub n_txs: u64,
+    /// The number of txs in the mempool
+    #[serde(deserialize_with = "string_or_number")]
+    pub total: u64,
+    #[serde(deserialize_with = "string_or_number")]
+    pub total_bytes: u64,
+    /// base64 encoded tx bytes
+    #[serde(default, deserialize_with = "nullable_vec")]
+    pub txs: Vec<String>,
+}
+
+/// An attribute value that may be base64 encoded, Tendermint 0.34 encodes both the
+/// key and value. A key is only treated as encoded if it decodes to an identifier
+fn decode_attribute(attribute: &EventAttribute) -> (String, String) {
+    let value = attribute.value.clone().unwrap_or_default();
+    if let Ok(key) = base64::decode(&attribute.key) {
+        let is_identifier = !key.is_empty()
+            && key
+                .iter()
+                .all(|c| c.is_ascii_alphanumeric() || *c == b'_' || *c == b'.');
+        if is_identifier {
+            let value = match base64::decode(&value) {
+                Ok(v) => String::from_utf8_lossy(&v).to_string(),
+                Err(_) => value,
+            };
+            return (String::from_utf8_lossy(&key).to_string(), value);
+        }
+    }
+    (attribute.key.clone(), value)
+}
+
+/// An event with its attributes decoded to plain key value pairs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedEvent {
+    pub kind: String,
+    pub attributes: Vec<(String, String)>,
+}
+
+impl DecodedEvent {
+    /// The value of the first attribute with the given key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Decodes this event with the typed event decoders of `registry`, None if none is
+    /// registered for its type
+    pub fn decode_typed(
+        &self,
+        registry: &AnyRegistry,
+    ) -> Result<Option<TypedValue>, CosmosGrpcError> {
+        registry.decode_event(&self.kind, &self.attributes)
+    }
+
+    fn get_string(&self, key: &str) -> String {
+        self.get(key).unwrap_or_default().to_string()
+    }
+
+    fn parse_or_default<T: FromStr + Default>(&self, key: &str) -> T {
+        self.get(key)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl From<&AbciEvent> for DecodedEvent {
+    fn from(event: &AbciEvent) -> Self {
+        DecodedEvent {
+            kind: event.kind.clone(),
+            attributes: event.attributes.iter().map(decode_attribute).collect(),
+        }
+    }
+}
+
+/// The begin and end block events emitted by the standard Cosmos SDK modules, amounts
+/// are left as the coin strings emitted by the chain, for example `10.5uatom,3ufoo`
+/// for rewards which are DecCoins
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockEvent {
+    /// Rewards distributed to a validator and its delegators
+    Rewards { validator: String, amount: String },
+    /// The commission portion of rewards distributed to a validator
+    Commission { validator: String, amount: String },
+    /// The bonus paid to the block proposer
+    ProposerReward { validator: String, amount: String },
+    /// A validator was slashed, `address` is the consensus address
+    Slash {
+        address: String,
+        power: String,
+        reason: String,
+        jailed: Option<String>,
+    },
+    /// A validator missed a block, `address` is the consensus address
+    Liveness {
+        address: String,
+        missed_blocks: u64,
+        height: u64,
+    },
+    /// New tokens minted this block
+    Mint {
+        bonded_ratio: f64,
+        inflation: f64,
+        annual_provisions: f64,
+        amount: String,
+    },
+    CompleteUnbonding {
+        validator: String,
+        delegator: String,
+        amount: String,
+    },
+    CompleteRedelegation {
+        source_validator: String,
+        destination_validator: String,
+        delegator: String,
+        amount: String,
+    },
+    /// Any event not listed above
+    Other(DecodedEvent),
+}
+
+impl From<DecodedEvent> for BlockEvent {
+    fn from(event: DecodedEvent) -> Self {
+        match event.kind.as_str() {
+            "rewards" => BlockEvent::Rewards {
+                validator: event.get_string("validator"),
+                amount: event.get_string("amount"),
+            },
+            "commission" => BlockEvent::Commission {
+                validator: event.get_string("validator"),
+                amount: event.get_string("amount"),
+            },
+            "proposer_reward" => BlockEvent::ProposerReward {
+                validator: event.get_string("validator"),
+                amount: event.get_string("amount"),
+            },
+            "slash" => BlockEvent::Slash {
+                address: event.get_string("address"),
+                power: event.get_string("power"),
+                reason: event.get_string("reason"),
+                jailed: event.get("jailed").map(|v| v.to_string()),
+            },
+            "liveness" => BlockEvent::Liveness {
+                address: event.get_string("address"),
+                missed_blocks: event.parse_or_default("missed_blocks"),
+                height: event.parse_or_default("height"),
+            },
+            "mint" => BlockEvent::Mint {
+                bonded_ratio: event.parse_or_default("bonded_ratio"),
+                inflation: event.parse_or_default("inflation"),
+                annual_provisions: event.parse_or_default("annual_provisions"),
+                amount: event.get_string("amount"),
+            },
+            "complete_unbonding" => BlockEvent::CompleteUnbonding {
+                validator: event.get_string("validator"),
+                delegator: event.get_string("delegator"),
+                amount: event.get_string("amount"),
+            },
+            "complete_redelegation" => BlockEvent::CompleteRedelegation {
+                source_validator: event.get_string("source_validator"),
+                destination_validator: event.get_string("destination_validator"),
+                delegator: event.get_string("delegator"),
+                amount: event.get_string("amount"),
+            },
+            _ => BlockEvent::Other(event),
+        }
+    }
+}
+
+/// The typed events of a single block as returned by `Contact::get_block_results`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockEvents {
+    pub height: u64,
+    /// On CometBFT 0.38+ chains all block level events are reported here
+    pub begin_block: Vec<BlockEvent>,
+    pub end_block: Vec<BlockEvent>,
+    /// The events of each transaction in the block, in order
+    pub txs: Vec<Vec<DecodedEvent>>,
+}
+
+impl From<BlockResults> for BlockEvents {
+    fn from(results: BlockResults) -> Self {
+        let typed = |events: &[AbciEvent]| {
+            events
+                .iter()
+                .map(|e| BlockEvent::from(DecodedEvent::from(e)))
+                .collect::<Vec<_>>()
+        };
+        let mut begin_block = typed(&results.begin_block_events);
+        begin_block.extend(typed(&results.finalize_block_events));
+        BlockEvents {
+            height: results.height,
+            begin_block,
+            end_block: typed(&results.end_block_events),
+            txs: results
+                .txs_results
+                .iter()
+                .map(|tx| tx.events.iter().map(DecodedEvent::from).collect())
+                .collect(),
+        }
+    }
+}
+
+/// Tendermint returns null rather than an empty list in many places
+fn nullable_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let json = r#"{"jsonrpc":"2.0","id":1,"result":{
+            "node_info":{"protocol_version":{"p2p":"8","block":"11","app":"0"},"id":"ab12","listen_addr":"tcp://0.0.0.0:26656","network":"cosmoshub-4","version":"0.34.27","channels":"40","moniker":"node","other":{"tx_index":"on","rpc_address":"tcp://0.0.0.0:26657"}},
+            "sync_info":{"latest_block_hash":"AA","latest_app_hash":"BB","latest_block_height":"15000000","latest_block_time":"2023-04-01T00:00:00Z","earliest_block_hash":"CC","earliest_app_hash":"DD","earliest_block_height":"14000000","earliest_block_time":"2023-01-01T00:00:00Z","catching_up":false},
+            "validator_info":{"address":"EE","pub_key":{"type":"tendermint/PubKeyEd25519","value":"AAAA"},"voting_power":"0"}}}"#;
+        let res: RpcResponse<RpcStatus> = serde_json::from_str(json).unwrap();
+        let status = res.into_result().unwrap();
+        assert_eq!(status.node_info.network, "cosmoshub-4");
+        assert_eq!(status.sync_info.latest_block_height, 15_000_000);
+        assert_eq!(status.sync_info.earliest_block_height, 14_000_000);
+        assert!(!status.sync_info.catching_up);
+
+        let json = r#"{"n_txs":"0","total":"5","total_bytes":"1200","txs":null}"#;
+        let mempool: UnconfirmedTxs = serde_json::from_str(json).unwrap();
+        assert_eq!(mempool.total, 5);
+        assert!(mempool.txs.is_empty());
+
+        let json = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32603,"message":"Internal error","data":"height 10 is not available"}}"#;
+        let res: RpcResponse<RpcStatus> = serde_json::from_str(json).unwrap();
+        match res.into_result() {
+            Err(CosmosGrpcError::RpcError { code, .. }) => assert_eq!(code, -32603),
+            _ => panic!("Expected an rpc error"),
+        }
+    }
+
+    #[test]
+    fn test_decode_base64_events() {
+        // Tendermint 0.34 style, slash {address: cosmosvalcons1abc, power: 100, reason: double_sign}
+        let json = r#"{"height":"5","begin_block_events":[{"type":"slash","attributes":[
+            {"key":"YWRkcmVzcw==","value":"Y29zbW9zdmFsY29uczFhYmM=","index":true},
+            {"key":"cG93ZXI=","value":"MTAw","index":true},
+            {"key":"cmVhc29u","value":"ZG91YmxlX3NpZ24=","index":true}]}],
+            "end_block_events":[{"type":"complete_unbonding","attributes":[{"key":"amount","value":"5stake"},{"key":"validator","value":"val"},{"key":"delegator","value":"del"}]}]}"#;
+        let events = BlockEvents::from(serde_json::from_str::<BlockResults>(json).unwrap());
+        assert_eq!(
+            events.begin_block[0],
+            BlockEvent::Slash {
+                address: "cosmosvalcons1abc".to_string(),
+                power: "100".to_string(),
+                reason: "double_sign".to_string(),
+                jailed: None,
+            }
+        );
+        assert_eq!(
+            events.end_block[0],
+            BlockEvent::CompleteUnbonding {
+                validator: "val".to_string(),
+                delegator: "del".to_string(),
+                amount: "5stake".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_block_results_and_consensus() {
+        let json = r#"{"height":"12","txs_results":null,"begin_block_events":[{"type":"rewards","attributes":[{"key":"amount","value":"10stake","index":true}]}],"end_block_events":null,"validator_updates":null,"consensus_param_updates":null}"#;
+        let res: BlockResults = serde_json::from_str(json).unwrap();
+        assert_eq!(res.height, 12);
+        assert!(res.txs_results.is_empty());
+        assert_eq!(res.begin_block_events[0].kind, "rewards");
+        assert!(res.end_block_events.is_empty());
+
+        let events = BlockEvents::from(res);
+        assert_eq!(
+            events.begin_block[0],
+            BlockEvent::Rewards {
+                validator: String::new(),
+                amount: "10stake".to_string()
+            }
+        );
+
+        let json = r#"{"round_state":{"height/round/step":"100/2/6","start_time":"2023-04-01T00:00:00Z","proposal_block_hash":"","locked_block_hash":"","valid_block_hash":"","height_vote_set":[{"round":0,"prevotes":["nil-Vote"],"prevotes_bit_array":"BA{4:x___} 25/100 = 0.25","precommits":["nil-Vote"],"precommits_bit_array":"BA{4:____} 0/100 = 0.00"}],"proposer":{"address":"FF","index":3}}}"#;
+        let res: ConsensusState = serde_json::from_str(json).unwrap();
+        assert_eq!(res.round_state.height(), Some(100));
+        assert_eq!(res.round_state.round(), Some(2));
+        assert_eq!(
+            res.round_state.height_vote_set[0].prevote_fraction(),
+            Some(0.25)
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! A scheduler for recurring chain operations such as compounding rewards, oracle votes
+//! or batch requests. Jobs run on a block height or wall clock schedule, a job never
+//! runs twice at once, and a failing job is retried with backoff instead of on schedule
+
+use super::retry::RetryPolicy;
+use super::types::ChainStatus;
+use crate::error::CosmosGrpcError;
+use crate::Contact;
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+type JobFuture = Pin<Box<dyn Future<Output = Result<(), CosmosGrpcError>> + Send>>;
+
+/// When a job runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    /// Every time the block height passes a multiple of this many blocks, so jobs with
+    /// the same interval run at the same heights. Skipped while the chain is not moving
+    Blocks(u64),
+    /// Every this amount of wall time
+    Interval(Duration),
+}
+
+struct Job {
+    name: String,
+    schedule: Schedule,
+    run: Box<dyn FnMut(Contact) -> JobFuture + Send>,
+    running: bool,
+    last_height: Option<u64>,
+    last_run: Option<Instant>,
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl Job {
+    fn is_due(&self, height: Option<u64>, now: Instant) -> bool {
+        if self.running {
+            return false;
+        }
+        if let Some(retry_at) = self.retry_at {
+            return now >= retry_at;
+        }
+        match self.schedule {
+            Schedule::Blocks(blocks) => match (height, self.last_height) {
+                (None, _) => false,
+                (Some(_), None) => true,
+                (Some(height), Some(last)) => height / blocks.max(1) > last / blocks.max(1),
+            },
+            Schedule::Interval(interval) => match self.last_run {
+                None => true,
+                Some(last) => now >= last + interval,
+            },
+        }
+    }
+
+    fn finished(
+        &mut self,
+        res: Result<(), CosmosGrpcError>,
+        height: Option<u64>,
+        started: Instant,
+        backoff: &RetryPolicy,
+    ) {
+        self.running = false;
+        match res {
+            Ok(()) => {
+                self.failures = 0;
+                self.retry_at = None;
+                self.last_height = height.or(self.last_height);
+                self.last_run = Some(started);
+            }
+            Err(e) => {
+                self.failures = self.failures.saturating_add(1);
+                let delay = backoff.delay(self.failures);
+                warn!(
+                    "Scheduled job {} failed {} times, retrying in {}ms: {}",
+                    self.name,
+                    self.failures,
+                    delay.as_millis(),
+                    e
+                );
+                self.retry_at = Some(Instant::now() + delay);
+            }
+        }
+    }
+}
+
+/// Runs registered jobs against a Contact until the future returned by `run` is dropped
+pub struct Scheduler {
+    contact: Contact,
+    jobs: Vec<Job>,
+    poll_interval: Duration,
+    backoff: RetryPolicy,
+}
+
+impl Scheduler {
+    /// Creates a scheduler that checks for due jobs every second and backs failing jobs
+    /// off from 1 second up to 5 minutes
+    pub fn new(contact: Contact) -> Self {
+        Scheduler {
+            contact,
+            jobs: Vec::new(),
+            poll_interval: Duration::from_secs(1),
+            backoff: RetryPolicy {
+                max_attempts: u32::MAX,
+                base_delay: Duration::from_secs(1),
+                max_delay: Duration::from_secs(300),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// How often to check for due jobs, and for block schedules the block height
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// The delays between attempts of a failing job, only the delay fields of the
+    /// policy are used, a failing job is retried until it succeeds
+    pub fn with_backoff(mut self, backoff: RetryPolicy) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Registers a job, `job` is called with a clone of the Contact each time it runs
+    pub fn add_job<F, Fut>(
+        mut self,
+        name: impl Into<String>,
+        schedule: Schedule,
+        mut job: F,
+    ) -> Self
+    where
+        F: FnMut(Contact) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), CosmosGrpcError>> + Send + 'static,
+    {
+        self.jobs.push(Job {
+            name: name.into(),
+            schedule,
+            run: Box::new(move |contact| Box::pin(job(contact))),
+            running: false,
+            last_height: None,
+            last_run: None,
+            failures: 0,
+            retry_at: None,
+        });
+        self
+    }
+
+    /// Runs the jobs forever, drop the returned future to stop. Jobs run concurrently
+    /// with each other on the task polling this future
+    pub async fn run(mut self) {
+        let needs_height = self
+            .jobs
+            .iter()
+            .any(|job| matches!(job.schedule, Schedule::Blocks(_)));
+        let mut in_flight = FuturesUnordered::new();
+        let mut ticker = tokio::time::interval(self.poll_interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    let height = if needs_height {
+                        match self.contact.get_chain_status().await {
+                            Ok(ChainStatus::Moving { block_height }) => Some(block_height),
+                            _ => None,
+                        }
+                    } else {
+                        None
+                    };
+                    let now = Instant::now();
+                    for (index, job) in self.jobs.iter_mut().enumerate() {
+                        if job.is_due(height, now) {
+                            trace!("Running scheduled job {}", job.name);
+                            job.running = true;
+                            let fut = (job.run)(self.contact.clone());
+                            in_flight.push(async move { (index, height, now, fut.await) });
+                        }
+                    }
+                }
+                Some((index, height, started, res)) = in_flight.next(), if !in_flight.is_empty() => {
+                    self.jobs[index].finished(res, height, started, &self.backoff);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    fn job(schedule: Schedule) -> Job {
+        Job {
+            name: "test".to_string(),
+            schedule,
+            run: Box::new(|_| Box::pin(async { Ok(()) })),
+            running: false,
+            last_height: None,
+            last_run: None,
+            failures: 0,
+            retry_at: None,
+        }
+    }
+
+    #[test]
+    fn test_block_schedule() {
+        let now = Instant::now();
+        let backoff = RetryPolicy::default();
+        let mut job = job(Schedule::Blocks(10));
+        assert!(!job.is_due(None, now));
+        assert!(job.is_due(Some(15), now));
+        job.finished(Ok(()), Some(15), now, &backoff);
+        assert!(!job.is_due(Some(19), now));
+        assert!(job.is_due(Some(20), now));
+
+        job.running = true;
+        assert!(!job.is_due(Some(20), now));
+        job.finished(
+            Err(CosmosGrpcError::BadResponse("failed".to_string())),
+            Some(20),
+            now,
+            &backoff,
+        );
+        // a failed job waits out its backoff whatever the height
+        assert!(!job.is_due(Some(30), now));
+        assert!(job.is_due(Some(30), now + backoff.max_delay));
+    }
+
+    #[actix_rt::test]
+    async fn test_interval_jobs_do_not_overlap() {
+        let contact = Contact::new("http://127.0.0.1:1", Duration::from_secs(1), "cosmos").unwrap();
+        let runs = Arc::new(AtomicU32::new(0));
+        let active = Arc::new(AtomicU32::new(0));
+        let counter = (runs.clone(), active.clone());
+        let scheduler = Scheduler::new(contact)
+            .with_poll_interval(Duration::from_millis(5))
+            .add_job(
+                "slow",
+                Schedule::Interval(Duration::from_millis(1)),
+                move |_| {
+                    let (runs, active) = counter.clone();
+                    async move {
+                        assert_eq!(active.fetch_add(1, Ordering::SeqCst), 0);
+                        runs.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(30)).await;
+                        active.fetch_sub(1, Ordering::SeqCst);
+                        Ok(())
+                    }
+                },
+            );
+        let _ = tokio::time::timeout(Duration::from_millis(200), scheduler.run()).await;
+        let runs = runs.load(Ordering::SeqCst);
+        assert!((2..=7).contains(&runs), "{} runs", runs);
+    }
+}
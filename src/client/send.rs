@@ -1,45 +1,172 @@
 use crate::address::Address;
+use crate::client::retry::{retry_with, RetryPolicy};
 use crate::client::Contact;
 use crate::client::MEMO;
+use crate::client::{ChainStatus, DryRun, TimeoutPolicy};
 use crate::coin::Coin;
 use crate::coin::Fee;
-use crate::error::CosmosGrpcError;
+use crate::error::{BroadcastError, CosmosGrpcError, SdkErrorCode};
 use crate::msg::Msg;
 use crate::private_key::PrivateKey;
-use crate::utils::check_for_sdk_error;
+use crate::utils::{bytes_to_hex_str, check_for_sdk_error};
 use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastTxRequest;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::GetTxResponse;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::SimulateRequest;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::SimulateResponse;
 use cosmos_sdk_proto::cosmos::{
     base::abci::v1beta1::TxResponse, tx::v1beta1::service_client::ServiceClient as TxServiceClient,
 };
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::time::Duration;
 use std::time::Instant;
-use std::{clone::Clone, time::Duration};
 use tokio::time::sleep;
 use tonic::Code as TonicCode;
 
+/// How often to check if a transaction has been included in a block
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Broadcasts `tx_bytes` with `send` under the retry policy and checks the response for
+/// sdk errors. A broadcast that timed out may still have reached the mempool, so a
+/// retry rejected with ErrTxInMempoolCache is the earlier attempt succeeding and is
+/// returned as a success with the hash of `tx_bytes` rather than as a failed tx
+pub(crate) async fn broadcast_with_retry<F, Fut>(
+    policy: &RetryPolicy,
+    url: &str,
+    tx_bytes: &[u8],
+    mut send: F,
+) -> Result<TxResponse, CosmosGrpcError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<TxResponse, CosmosGrpcError>>,
+{
+    retry_with(policy, url, |attempt| {
+        let response = send();
+        async move {
+            let response = response.await?;
+            if attempt > 1
+                && response.codespace == "sdk"
+                && SdkErrorCode::from_code(response.code) == Some(SdkErrorCode::ErrTxInMempoolCache)
+            {
+                return Ok(TxResponse {
+                    txhash: bytes_to_hex_str(&Sha256::digest(tx_bytes)).to_uppercase(),
+                    code: 0,
+                    codespace: String::new(),
+                    raw_log: String::new(),
+                    ..response
+                });
+            }
+            // checks only for sdk errors, other types will not be handled
+            check_for_sdk_error(&response)?;
+            Ok(response)
+        }
+    })
+    .await
+}
+
+/// Polls `get_tx` until the tx is found or `policy` runs out. `get_status` is queried on
+/// every poll only for `TimeoutPolicy::Blocks`, a `TimeoutPolicy::Duration` queries it
+/// once on timeout to report the last seen height. A block policy also gives up if no
+/// block is produced within `stall_timeout`
+pub(crate) async fn poll_for_tx<FS, FutS, FT, FutT>(
+    policy: TimeoutPolicy,
+    stall_timeout: Duration,
+    retry: &RetryPolicy,
+    txhash: &str,
+    mut get_status: FS,
+    mut get_tx: FT,
+) -> Result<TxResponse, CosmosGrpcError>
+where
+    FS: FnMut() -> FutS,
+    FutS: Future<Output = Result<ChainStatus, CosmosGrpcError>>,
+    FT: FnMut() -> FutT,
+    FutT: Future<Output = Result<GetTxResponse, CosmosGrpcError>>,
+{
+    let start = Instant::now();
+    let mut start_height = None;
+    let mut last_seen_height = 0;
+    let mut last_progress = Instant::now();
+    loop {
+        if let TimeoutPolicy::Blocks(_) = policy {
+            if let Ok(ChainStatus::Moving { block_height }) = get_status().await {
+                if block_height > last_seen_height {
+                    last_seen_height = block_height;
+                    last_progress = Instant::now();
+                }
+                start_height.get_or_insert(block_height);
+            }
+        }
+
+        // TODO what actually determines when the tx is in the chain?
+        match get_tx().await {
+            Ok(status) => {
+                if let Some(res) = status.tx_response {
+                    return Ok(res);
+                }
+            }
+            Err(CosmosGrpcError::RequestError { error })
+                if matches!(
+                    error.code(),
+                    TonicCode::NotFound | TonicCode::Unknown | TonicCode::InvalidArgument
+                ) => {}
+            // transient errors are retried by polling again until the timeout
+            Err(e) if (retry.retryable)(&e) => {
+                trace!("Transient error waiting for tx {}: {}", txhash, e);
+            }
+            Err(e) => return Err(e),
+        }
+
+        let timed_out = match policy {
+            TimeoutPolicy::Duration(timeout) => {
+                let timed_out = start.elapsed() >= timeout;
+                if timed_out {
+                    if let Ok(ChainStatus::Moving { block_height }) = get_status().await {
+                        last_seen_height = block_height;
+                    }
+                }
+                timed_out
+            }
+            TimeoutPolicy::Blocks(blocks) => {
+                let passed = match start_height {
+                    Some(height) => last_seen_height >= height + blocks,
+                    None => false,
+                };
+                passed || last_progress.elapsed() >= stall_timeout
+            }
+        };
+        if timed_out {
+            return Err(CosmosGrpcError::TimedOut {
+                txhash: txhash.to_string(),
+                last_seen_height,
+            });
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
 impl Contact {
     /// Sends an already serialized and signed transaction, checking for various errors in the
     /// transaction response. This is the lowest level transaction sending function and you
     /// probably shouldn't use it unless you have specific needs. `send_message` is more
-    /// appropriate for general use.
+    /// appropriate for general use. Fails without broadcasting if the node is further
+    /// behind than allowed by `with_max_node_lag`
     ///
     /// # Arguments
     ///
     /// * `msg` - A proto encoded and already signed message in byte format
     /// * `mode` - The Broadcast mode to use, `BroadcastMode::Sync` waits for basic validation
-    ///            `BroadcastMode::Block` is supposed to wait for the tx to enter the chain
-    ///            but grpc timeouts mean this is unreliable. `BroadcastMode::Async` sends and
-    ///            returns without waiting for any validation
+    ///   `BroadcastMode::Block` is supposed to wait for the tx to enter the chain
+    ///   but grpc timeouts mean this is unreliable. `BroadcastMode::Async` sends and
+    ///   returns without waiting for any validation
     /// # Examples
     /// ```rust
     /// use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
     /// use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
     /// use deep_space::{Coin, client::Contact, Fee, MessageArgs, Msg, PrivateKey, u256};
     /// use std::time::Duration;
-    /// let private_key = PrivateKey::from_secret("mySecret".as_bytes());
+    /// let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
     /// let public_key = private_key.to_public_key("cosmospub").unwrap();
     /// let address = public_key.to_address();
     /// let coin = Coin {
@@ -64,33 +191,63 @@ impl Contact {
     ///     chain_id: "mychainid".to_string(),
     ///     fee,
     ///     timeout_height: 100,
+    ///     ..Default::default()
     /// };
     /// let tx = private_key.sign_std_msg(&[msg], args, "").unwrap();
     /// let contact = Contact::new("https:://your-grpc-server", Duration::from_secs(5), "prefix").unwrap();
     /// // future must be awaited in tokio runtime
     /// contact.send_transaction(tx, BroadcastMode::Sync);
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(url = %self.url, txhash))
+    )]
     pub async fn send_transaction(
         &self,
         // proto serialized message for us to turn into an 'any' object
         msg: Vec<u8>,
         mode: BroadcastMode,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let mut txrpc = TxServiceClient::connect(self.get_url())
-            .await?
-            .accept_gzip();
-        let response = txrpc
-            .broadcast_tx(BroadcastTxRequest {
-                tx_bytes: msg,
-                mode: mode.into(),
-            })
-            .await?
-            .into_inner()
-            .tx_response
-            .unwrap();
-        // checks only for sdk errors, other types will not be handled
-        check_for_sdk_error(&response)?;
-        Ok(response)
+        self.check_node_lag().await?;
+        let res = self.broadcast(msg, mode).await;
+        #[cfg(feature = "tracing")]
+        if let Ok(response) = &res {
+            tracing::Span::current().record("txhash", response.txhash.as_str());
+        }
+        #[cfg(feature = "metrics")]
+        crate::client::metrics::record_broadcast(&res);
+        res
+    }
+
+    async fn broadcast(
+        &self,
+        msg: Vec<u8>,
+        mode: BroadcastMode,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        broadcast_with_retry(&self.retry, &self.url, &msg, || async {
+            let mut txrpc = TxServiceClient::new(self.get_channel().await?).accept_gzip();
+            match txrpc
+                .broadcast_tx(BroadcastTxRequest {
+                    tx_bytes: msg.clone(),
+                    mode: mode.into(),
+                })
+                .await
+            {
+                Ok(response) => Ok(response.into_inner().tx_response.unwrap()),
+                Err(e) => Err(self.check_upgrade_halt(e.into()).await),
+            }
+        })
+        .await
+        .inspect_err(|e| {
+            // a sequence mismatch means our view of the signing account is stale
+            if let CosmosGrpcError::TransactionFailed {
+                reason: BroadcastError::SequenceMismatch { .. },
+                ..
+            } = e
+            {
+                self.invalidate_accounts_cache();
+            }
+        })
     }
 
     /// High level message sending function, you provide an arbitrary vector of messages to send
@@ -112,7 +269,7 @@ impl Contact {
     /// use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
     /// use deep_space::{Coin, client::Contact, Fee, MessageArgs, Msg, PrivateKey, u256};
     /// use std::time::Duration;
-    /// let private_key = PrivateKey::from_secret("mySecret".as_bytes());
+    /// let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
     /// let public_key = private_key.to_public_key("cosmospub").unwrap();
     /// let address = public_key.to_address();
     /// let coin = Coin {
@@ -129,6 +286,10 @@ impl Contact {
     /// // future must be awaited in tokio runtime
     /// contact.send_message(&vec![msg], None, &[coin], None, private_key);
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(chain_id, sequence, txhash))
+    )]
     pub async fn send_message(
         &self,
         messages: &[Msg],
@@ -137,12 +298,16 @@ impl Contact {
         wait_timeout: Option<Duration>,
         private_key: PrivateKey,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
         let memo = memo.unwrap_or_else(|| MEMO.to_string());
 
-        let fee = self.get_fee_info(messages, fee_coin, private_key).await?;
+        let fee = self.get_fee_info(messages, fee_coin, &private_key).await?;
 
         let args = self.get_message_args(our_address, fee).await?;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current()
+            .record("chain_id", args.chain_id.as_str())
+            .record("sequence", args.sequence);
         trace!("got optional tx info");
 
         let msg_bytes = private_key.sign_std_msg(messages, args, memo)?;
@@ -151,9 +316,26 @@ impl Contact {
             .send_transaction(msg_bytes, BroadcastMode::Sync)
             .await?;
 
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("txhash", response.txhash.as_str());
         trace!("broadcasted! with response {:?}", response);
         if let Some(time) = wait_timeout {
-            self.wait_for_tx(response, time).await
+            match self.wait_for_tx(&response.txhash, time).await {
+                Err(CosmosGrpcError::TimedOut { .. })
+                | Err(CosmosGrpcError::RequestError { .. }) => {
+                    Err(CosmosGrpcError::TransactionFailed {
+                        tx: response,
+                        time,
+                        reason: BroadcastError::NotIncluded,
+                    })
+                }
+                // a tx can pass the mempool checks and still fail when it is executed
+                Ok(tx) => match BroadcastError::from_tx_response(&tx) {
+                    Some(reason) => Err(CosmosGrpcError::TransactionFailed { tx, time, reason }),
+                    None => Ok(tx),
+                },
+                res => res,
+            }
         } else {
             Ok(response)
         }
@@ -165,7 +347,7 @@ impl Contact {
         &self,
         messages: &[Msg],
         fee_token: &[Coin],
-        private_key: PrivateKey,
+        private_key: &PrivateKey,
     ) -> Result<Fee, CosmosGrpcError> {
         let gas_info = self
             .simulate_tx(messages, private_key)
@@ -216,12 +398,9 @@ impl Contact {
     pub async fn simulate_tx(
         &self,
         messages: &[Msg],
-        private_key: PrivateKey,
+        private_key: &PrivateKey,
     ) -> Result<SimulateResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
-        let mut txrpc = TxServiceClient::connect(self.get_url())
-            .await?
-            .accept_gzip();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
 
         let fee_obj = Fee {
             amount: vec![],
@@ -235,13 +414,28 @@ impl Contact {
 
         let tx_bytes = private_key.sign_std_msg(messages, args, MEMO)?;
 
-        // used to avoid the deprication warning on SimulateRequest
-        #[allow(deprecated)]
-        let sim_request = SimulateRequest { tx_bytes, tx: None };
-
-        let response = txrpc.simulate(sim_request).await?.into_inner();
+        self.retry(|| async {
+            let mut txrpc = TxServiceClient::new(self.get_channel().await?).accept_gzip();
+            // used to avoid the deprication warning on SimulateRequest
+            #[allow(deprecated)]
+            let sim_request = SimulateRequest {
+                tx_bytes: tx_bytes.clone(),
+                tx: None,
+            };
+            Ok(txrpc.simulate(sim_request).await?.into_inner())
+        })
+        .await
+    }
 
-        Ok(response)
+    /// Simulates the provided messages without broadcasting them and decodes the result,
+    /// use this to inspect the events and message responses a transaction would produce,
+    /// such as those of a wasm execute, before committing to it
+    pub async fn dry_run(
+        &self,
+        messages: &[Msg],
+        private_key: &PrivateKey,
+    ) -> Result<DryRun, CosmosGrpcError> {
+        self.simulate_tx(messages, private_key).await?.try_into()
     }
 
     /// A utility function that creates a one to one simple Coin transfer
@@ -262,7 +456,7 @@ impl Contact {
     /// use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
     /// use deep_space::{Coin, client::Contact, Fee, MessageArgs, Msg, PrivateKey, u256};
     /// use std::time::Duration;
-    /// let private_key = PrivateKey::from_secret("mySecret".as_bytes());
+    /// let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
     /// let public_key = private_key.to_public_key("cosmospub").unwrap();
     /// let address = public_key.to_address();
     /// let coin = Coin {
@@ -282,12 +476,12 @@ impl Contact {
         private_key: PrivateKey,
     ) -> Result<TxResponse, CosmosGrpcError> {
         trace!("Creating transaction");
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
 
         let send = MsgSend {
             amount: vec![coin.into()],
-            from_address: our_address.to_bech32(&self.chain_prefix).unwrap(),
-            to_address: destination.to_bech32(&self.chain_prefix).unwrap(),
+            from_address: our_address.to_bech32(&self.config.prefix).unwrap(),
+            to_address: destination.to_bech32(&self.config.prefix).unwrap(),
         };
         let msg = Msg::new("/cosmos.bank.v1beta1.MsgSend", send);
         self.send_message(
@@ -301,41 +495,100 @@ impl Contact {
     }
 
     /// Utility function that waits for a tx to enter the chain by querying
-    /// it's txid, will not exit for timeout time unless the error is known
-    /// and unrecoverable
+    /// it's txid, `timeout` is either a Duration or `TimeoutPolicy::Blocks(n)` to
+    /// give up once n blocks have been produced without including the tx. When
+    /// waiting on blocks this also gives up if no block is produced within the
+    /// Contact's timeout. Returns `CosmosGrpcError::TimedOut` if the tx is not found
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(txhash = %txhash))
+    )]
     pub async fn wait_for_tx(
         &self,
-        response: TxResponse,
-        timeout: Duration,
+        txhash: &str,
+        timeout: impl Into<TimeoutPolicy>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let start = Instant::now();
-        while Instant::now() - start < timeout {
-            // TODO what actually determines when the tx is in the chain?
-            let status = self.get_tx_by_hash(response.txhash.clone()).await;
-            match status {
-                Ok(status) => {
-                    if let Some(res) = status.tx_response {
-                        return Ok(res);
-                    }
-                }
-                Err(CosmosGrpcError::RequestError { error }) => match error.code() {
-                    TonicCode::NotFound | TonicCode::Unknown | TonicCode::InvalidArgument => {}
-                    _ => {
-                        return Err(CosmosGrpcError::TransactionFailed {
-                            tx: response,
-                            time: Instant::now() - start,
-                            sdk_error: None,
-                        });
-                    }
-                },
-                Err(e) => return Err(e),
-            }
-            sleep(Duration::from_secs(1)).await;
+        let result = poll_for_tx(
+            timeout.into(),
+            self.timeout,
+            &self.retry,
+            txhash,
+            || self.get_chain_status(),
+            || self.get_tx_by_hash(txhash.to_string()),
+        )
+        .await;
+        match result {
+            Err(e @ CosmosGrpcError::TimedOut { .. }) => Err(self.check_upgrade_halt(e).await),
+            res => res,
         }
-        Err(CosmosGrpcError::TransactionFailed {
-            tx: response,
-            time: timeout,
-            sdk_error: None,
-        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[actix_rt::test]
+    async fn test_poll_for_tx_height_queries() {
+        let status_calls = Cell::new(0u64);
+        let tx_calls = Cell::new(0u64);
+        let get_status = || {
+            status_calls.set(status_calls.get() + 1);
+            async {
+                Ok(ChainStatus::Moving {
+                    block_height: 10 + status_calls.get(),
+                })
+            }
+        };
+        // the tx is found on the second poll
+        let get_tx = || {
+            tx_calls.set(tx_calls.get() + 1);
+            let found = tx_calls.get() >= 2;
+            async move {
+                Ok(GetTxResponse {
+                    tx_response: found.then(|| TxResponse {
+                        txhash: "AB".to_string(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            }
+        };
+        let retry = RetryPolicy::none();
+        let stall = Duration::from_secs(30);
+
+        // a duration policy polls the tx without asking for the height
+        let policy = TimeoutPolicy::Duration(Duration::from_secs(30));
+        let res = poll_for_tx(policy, stall, &retry, "AB", get_status, get_tx).await;
+        assert_eq!(res.unwrap().txhash, "AB");
+        assert_eq!((status_calls.get(), tx_calls.get()), (0, 2));
+
+        // and asks once when it times out, to report the last seen height
+        tx_calls.set(0);
+        let policy = TimeoutPolicy::Duration(Duration::ZERO);
+        let res = poll_for_tx(policy, stall, &retry, "AB", get_status, get_tx).await;
+        assert!(matches!(
+            res,
+            Err(CosmosGrpcError::TimedOut {
+                last_seen_height: 11,
+                ..
+            })
+        ));
+        assert_eq!((status_calls.get(), tx_calls.get()), (1, 1));
+
+        // a block policy needs the height on every poll
+        status_calls.set(0);
+        tx_calls.set(0);
+        let res = poll_for_tx(
+            TimeoutPolicy::Blocks(5),
+            stall,
+            &retry,
+            "AB",
+            get_status,
+            get_tx,
+        );
+        assert_eq!(res.await.unwrap().txhash, "AB");
+        assert_eq!((status_calls.get(), tx_calls.get()), (2, 2));
     }
 }
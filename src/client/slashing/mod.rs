@@ -0,0 +1,137 @@
+//! Contains utility functions for querying validator liveness through the Cosmos sdk slashing module
+
+use super::PAGE;
+use crate::error::CosmosGrpcError;
+use crate::Address;
+use crate::Contact;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::query_client::QueryClient as SlashingQueryClient;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::Params;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::QueryParamsRequest;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::QuerySigningInfoRequest;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::QuerySigningInfosRequest;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::ValidatorSigningInfo;
+
+impl Contact {
+    /// Gets the parameters of the slashing module, including the signed blocks window
+    pub async fn get_slashing_params(&self) -> Result<Params, CosmosGrpcError> {
+        self.cached("slashing_params", || {
+            self.retry(|| async {
+                let mut grpc = SlashingQueryClient::new(self.get_channel().await?).accept_gzip();
+                let res = grpc
+                    .params(QueryParamsRequest {})
+                    .await?
+                    .into_inner()
+                    .params;
+                match res {
+                    Some(params) => Ok(params),
+                    None => Err(CosmosGrpcError::BadResponse(
+                        "No slashing params returned".to_string(),
+                    )),
+                }
+            })
+        })
+        .await
+    }
+
+    /// Gets the signing info of every validator that has ever been in the active set
+    pub async fn get_signing_infos(&self) -> Result<Vec<ValidatorSigningInfo>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = SlashingQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .signing_infos(QuerySigningInfosRequest { pagination: PAGE })
+                .await?
+                .into_inner()
+                .info;
+            Ok(res)
+        })
+        .await
+    }
+
+    /// Gets the signing info of a single validator by it's consensus address (valcons)
+    /// returns None if this validator has never been in the active set
+    pub async fn get_signing_info(
+        &self,
+        validator_cons_address: Address,
+    ) -> Result<Option<ValidatorSigningInfo>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = SlashingQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .signing_info(QuerySigningInfoRequest {
+                    cons_address: validator_cons_address.to_string(),
+                })
+                .await?
+                .into_inner()
+                .val_signing_info;
+            Ok(res)
+        })
+        .await
+    }
+
+    /// Gets the number of blocks the given validator (by valcons address) has missed
+    /// within the current signed blocks window
+    pub async fn get_validator_missed_blocks(
+        &self,
+        validator_cons_address: Address,
+    ) -> Result<u64, CosmosGrpcError> {
+        match self.get_signing_info(validator_cons_address).await? {
+            Some(info) => Ok(info.missed_blocks_counter as u64),
+            None => Err(CosmosGrpcError::BadInput(format!(
+                "No signing info for {}",
+                validator_cons_address
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock_grpc::MockGrpc;
+    use crate::PrivateKey;
+    use cosmos_sdk_proto::cosmos::slashing::v1beta1::QueryParamsResponse;
+    use cosmos_sdk_proto::cosmos::slashing::v1beta1::QuerySigningInfoResponse;
+    use std::time::Duration;
+
+    #[actix_rt::test]
+    async fn test_validator_missed_blocks() {
+        let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let signing = key.to_address("cosmosvalcons").unwrap();
+        let never_signed = PrivateKey::from_secret_hkdf(b"other", b"", b"")
+            .unwrap()
+            .to_address("cosmosvalcons")
+            .unwrap();
+        let grpc = MockGrpc::new()
+            .with(
+                "/cosmos.slashing.v1beta1.Query/SigningInfo",
+                move |req: QuerySigningInfoRequest| {
+                    let val_signing_info =
+                        (req.cons_address == signing.to_string()).then(|| ValidatorSigningInfo {
+                            address: req.cons_address,
+                            missed_blocks_counter: 7,
+                            ..Default::default()
+                        });
+                    Ok(QuerySigningInfoResponse { val_signing_info })
+                },
+            )
+            .with(
+                "/cosmos.slashing.v1beta1.Query/Params",
+                |_: QueryParamsRequest| Ok(QueryParamsResponse { params: None }),
+            );
+        let url = grpc.serve().await;
+        let contact = Contact::new(&url, Duration::from_secs(5), "cosmos").unwrap();
+
+        assert_eq!(
+            contact.get_validator_missed_blocks(signing).await.unwrap(),
+            7
+        );
+        assert_eq!(contact.get_signing_info(never_signed).await.unwrap(), None);
+        assert!(matches!(
+            contact.get_validator_missed_blocks(never_signed).await,
+            Err(CosmosGrpcError::BadInput(_))
+        ));
+        assert!(matches!(
+            contact.get_slashing_params().await,
+            Err(CosmosGrpcError::BadResponse(_))
+        ));
+    }
+}
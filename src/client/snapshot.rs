@@ -0,0 +1,245 @@
+//! Serializable snapshots of the on chain state of a single account. A snapshot taken
+//! once from a live chain can be checked into the test suite of a bot and loaded to
+//! sign transactions or seed a mock without a chain running
+
+use super::types::{BaseAccount, LatestBlock};
+use super::PAGE;
+use crate::error::CosmosGrpcError;
+use crate::{Address, Coin, Contact, Fee, MessageArgs};
+use cosmos_sdk_proto::cosmos::authz::v1beta1::query_client::QueryClient as AuthzQueryClient;
+use cosmos_sdk_proto::cosmos::authz::v1beta1::{
+    GrantAuthorization, QueryGranteeGrantsRequest, QueryGranterGrantsRequest,
+};
+use cosmos_sdk_proto::cosmos::staking::v1beta1::query_client::QueryClient as StakingQueryClient;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::{
+    DelegationResponse, QueryDelegatorDelegationsRequest,
+};
+use tonic::Code as GrpcCode;
+
+/// The state of an account at a single block height
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AccountSnapshot {
+    pub chain_id: String,
+    pub height: u64,
+    pub address: Address,
+    pub account_number: u64,
+    pub sequence: u64,
+    pub balances: Vec<Coin>,
+    pub delegations: Vec<DelegationSnapshot>,
+    /// Authz grants given by or to this account
+    pub grants: Vec<GrantSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DelegationSnapshot {
+    pub validator: String,
+    /// The delegator's shares of the validator, an sdk.Dec string
+    pub shares: String,
+    pub balance: Coin,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GrantSnapshot {
+    pub granter: String,
+    pub grantee: String,
+    /// The type url of the authorization
+    pub authorization_type: String,
+    /// The base64 encoded authorization
+    pub authorization: String,
+    /// Unix seconds after which the grant expires, None if it never does
+    pub expiration: Option<i64>,
+}
+
+impl From<DelegationResponse> for DelegationSnapshot {
+    fn from(value: DelegationResponse) -> Self {
+        let delegation = value.delegation.unwrap_or_default();
+        DelegationSnapshot {
+            validator: delegation.validator_address,
+            shares: delegation.shares,
+            balance: value.balance.unwrap_or_default().into(),
+        }
+    }
+}
+
+impl From<GrantAuthorization> for GrantSnapshot {
+    fn from(value: GrantAuthorization) -> Self {
+        let authorization = value.authorization.unwrap_or_default();
+        GrantSnapshot {
+            granter: value.granter,
+            grantee: value.grantee,
+            authorization_type: authorization.type_url,
+            authorization: base64::encode(authorization.value),
+            expiration: value.expiration.map(|t| t.seconds),
+        }
+    }
+}
+
+impl AccountSnapshot {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, CosmosGrpcError> {
+        serde_json::from_str(json).map_err(|e| CosmosGrpcError::BadStruct(e.to_string()))
+    }
+
+    /// The account as `get_account_info` would have returned it, without the pubkey
+    pub fn base_account(&self) -> BaseAccount {
+        BaseAccount {
+            address: self.address,
+            pubkey: None,
+            account_number: self.account_number,
+            sequence: self.sequence,
+        }
+    }
+
+    /// Arguments for signing a transaction from this account offline, as
+    /// `Contact::get_message_args` would have returned them at the snapshot height
+    pub fn message_args(&self, fee: Fee) -> MessageArgs {
+        MessageArgs {
+            sequence: self.sequence,
+            account_number: self.account_number,
+            chain_id: self.chain_id.clone(),
+            fee,
+            timeout_height: self.height + 100,
+            relative_timeout: None,
+            extension_options: Vec::new(),
+            non_critical_extension_options: Vec::new(),
+            unordered: false,
+            timeout_timestamp: None,
+            max_memo_characters: None,
+        }
+    }
+}
+
+impl Contact {
+    /// Takes a snapshot of the balances, delegations, authz grants and sequence of an
+    /// account. Every query is made at the same height so the snapshot is consistent,
+    /// see `AccountSnapshot::from_json` to load it again
+    pub async fn export_account_state(
+        &self,
+        address: Address,
+    ) -> Result<AccountSnapshot, CosmosGrpcError> {
+        let header = match self.get_latest_block().await? {
+            LatestBlock::Latest { block } => block.header,
+            LatestBlock::Syncing { .. } => return Err(CosmosGrpcError::NodeNotSynced),
+            LatestBlock::WaitingToStart => return Err(CosmosGrpcError::ChainNotRunning),
+        };
+        let header = match header {
+            Some(header) => header,
+            None => {
+                return Err(CosmosGrpcError::BadResponse(
+                    "Null block header?".to_string(),
+                ))
+            }
+        };
+        let contact = self.at_height(header.height as u64);
+
+        let delegations = contact.retry(|| async {
+            let mut grpc = StakingQueryClient::new(contact.get_channel().await?).accept_gzip();
+            let res = grpc
+                .delegator_delegations(QueryDelegatorDelegationsRequest {
+                    delegator_addr: address.to_string(),
+                    pagination: PAGE,
+                })
+                .await?
+                .into_inner()
+                .delegation_responses;
+            Ok(res)
+        });
+        let (account, balances, delegations, grants) = tokio::try_join!(
+            contact.get_account_info(address),
+            contact.get_balances(address),
+            delegations,
+            contact.get_account_grants(address),
+        )?;
+
+        Ok(AccountSnapshot {
+            chain_id: header.chain_id,
+            height: header.height as u64,
+            address,
+            account_number: account.account_number,
+            sequence: account.sequence,
+            balances,
+            delegations: delegations.into_iter().map(Into::into).collect(),
+            grants,
+        })
+    }
+
+    /// Authz grants given by or to an address, empty on chains older than Cosmos SDK
+    /// 0.45.2 which can not list grants
+    async fn get_account_grants(
+        &self,
+        address: Address,
+    ) -> Result<Vec<GrantSnapshot>, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = AuthzQueryClient::new(self.get_channel().await?).accept_gzip();
+            let granted = grpc
+                .granter_grants(QueryGranterGrantsRequest {
+                    granter: address.to_string(),
+                    pagination: PAGE,
+                })
+                .await;
+            let received = grpc
+                .grantee_grants(QueryGranteeGrantsRequest {
+                    grantee: address.to_string(),
+                    pagination: PAGE,
+                })
+                .await;
+            let mut grants = Vec::new();
+            for res in [
+                granted.map(|r| r.into_inner().grants),
+                received.map(|r| r.into_inner().grants),
+            ] {
+                match res {
+                    Ok(res) => grants.extend(res.into_iter().map(GrantSnapshot::from)),
+                    Err(e) if e.code() == GrpcCode::Unimplemented => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            Ok(grants)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let address: Address = "cosmos1t0sgxmpxafdfjd3k6kgg50kdgn4muh5t0phml6"
+            .parse()
+            .unwrap();
+        let snapshot = AccountSnapshot {
+            chain_id: "testing".to_string(),
+            height: 1000,
+            address,
+            account_number: 7,
+            sequence: 42,
+            balances: vec!["100uatom".parse().unwrap()],
+            delegations: vec![DelegationSnapshot {
+                validator: "cosmosvaloper1xyz".to_string(),
+                shares: "50.000000000000000000".to_string(),
+                balance: "50uatom".parse().unwrap(),
+            }],
+            grants: vec![GrantSnapshot {
+                granter: address.to_string(),
+                grantee: "cosmos1abc".to_string(),
+                authorization_type: "/cosmos.authz.v1beta1.GenericAuthorization".to_string(),
+                authorization: String::new(),
+                expiration: Some(1_700_000_000),
+            }],
+        };
+        let loaded = AccountSnapshot::from_json(&snapshot.to_json()).unwrap();
+        assert_eq!(loaded, snapshot);
+
+        let args = loaded.message_args(Fee::default());
+        assert_eq!(args.sequence, 42);
+        assert_eq!(args.account_number, 7);
+        assert_eq!(args.chain_id, "testing");
+        assert_eq!(args.timeout_height, 1100);
+        assert_eq!(loaded.base_account().address, address);
+    }
+}
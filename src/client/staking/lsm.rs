@@ -0,0 +1,237 @@
+//! Liquid staking module (LSM) support, LSM allows delegations to be converted into
+//! transferable share tokens and back. These messages are part of the LSM fork of the
+//! staking module used by the Cosmos Hub and are not included in the proto crate, the
+//! types here mirror the LSM additions to cosmos/staking/v1beta1 tx.proto and query.proto
+
+use crate::error::CosmosGrpcError;
+use crate::Uint256;
+use crate::{Address, Coin, Contact, Msg};
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+
+pub const MSG_TOKENIZE_SHARES_TYPE_URL: &str = "/cosmos.staking.v1beta1.MsgTokenizeShares";
+pub const MSG_REDEEM_TOKENS_FOR_SHARES_TYPE_URL: &str =
+    "/cosmos.staking.v1beta1.MsgRedeemTokensForShares";
+pub const MSG_TRANSFER_TOKENIZE_SHARE_RECORD_TYPE_URL: &str =
+    "/cosmos.staking.v1beta1.MsgTransferTokenizeShareRecord";
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgTokenizeShares {
+    #[prost(string, tag = "1")]
+    pub delegator_address: String,
+    #[prost(string, tag = "2")]
+    pub validator_address: String,
+    #[prost(message, optional, tag = "3")]
+    pub amount: Option<ProtoCoin>,
+    #[prost(string, tag = "4")]
+    pub tokenized_share_owner: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgRedeemTokensForShares {
+    #[prost(string, tag = "1")]
+    pub delegator_address: String,
+    #[prost(message, optional, tag = "2")]
+    pub amount: Option<ProtoCoin>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgTransferTokenizeShareRecord {
+    #[prost(uint64, tag = "1")]
+    pub tokenize_share_record_id: u64,
+    #[prost(string, tag = "2")]
+    pub sender: String,
+    #[prost(string, tag = "3")]
+    pub new_owner: String,
+}
+
+/// Tracks the module account holding the delegation behind a set of share tokens, the
+/// owner of the record receives the rewards of that delegation
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TokenizeShareRecord {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(string, tag = "2")]
+    pub owner: String,
+    #[prost(string, tag = "3")]
+    pub module_account: String,
+    #[prost(string, tag = "4")]
+    pub validator: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryTokenizeShareRecordByIdRequest {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryTokenizeShareRecordByIdResponse {
+    #[prost(message, optional, tag = "1")]
+    record: Option<TokenizeShareRecord>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryTokenizeShareRecordsOwnedRequest {
+    #[prost(string, tag = "1")]
+    owner: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryTokenizeShareRecordsOwnedResponse {
+    #[prost(message, repeated, tag = "1")]
+    records: Vec<TokenizeShareRecord>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryTotalLiquidStakedRequest {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryTotalLiquidStakedResponse {
+    #[prost(string, tag = "1")]
+    tokens: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryTotalTokenizeSharedAssetsRequest {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryTotalTokenizeSharedAssetsResponse {
+    #[prost(message, optional, tag = "1")]
+    value: Option<ProtoCoin>,
+}
+
+impl Msg {
+    /// Creates a MsgTokenizeShares, converting `amount` of an existing delegation to
+    /// `validator` into share tokens. `owner` receives the tokenize share record and
+    /// with it the rewards of the tokenized delegation
+    pub fn tokenize_shares(
+        delegator: Address,
+        validator: Address,
+        amount: Coin,
+        owner: Address,
+    ) -> Self {
+        let msg = MsgTokenizeShares {
+            delegator_address: delegator.to_string(),
+            validator_address: validator.to_string(),
+            amount: Some(amount.into()),
+            tokenized_share_owner: owner.to_string(),
+        };
+        Msg::new(MSG_TOKENIZE_SHARES_TYPE_URL, msg)
+    }
+
+    /// Creates a MsgRedeemTokensForShares, converting share tokens (denom
+    /// `{valoper}/{record id}`) back into a delegation
+    pub fn redeem_tokens_for_shares(delegator: Address, amount: Coin) -> Self {
+        let msg = MsgRedeemTokensForShares {
+            delegator_address: delegator.to_string(),
+            amount: Some(amount.into()),
+        };
+        Msg::new(MSG_REDEEM_TOKENS_FOR_SHARES_TYPE_URL, msg)
+    }
+
+    /// Creates a MsgTransferTokenizeShareRecord, transferring the rewards of a
+    /// tokenized delegation to `new_owner`
+    pub fn transfer_tokenize_share_record(
+        record_id: u64,
+        sender: Address,
+        new_owner: Address,
+    ) -> Self {
+        let msg = MsgTransferTokenizeShareRecord {
+            tokenize_share_record_id: record_id,
+            sender: sender.to_string(),
+            new_owner: new_owner.to_string(),
+        };
+        Msg::new(MSG_TRANSFER_TOKENIZE_SHARE_RECORD_TYPE_URL, msg)
+    }
+}
+
+impl Contact {
+    /// Gets a tokenize share record by id
+    pub async fn get_tokenize_share_record(
+        &self,
+        id: u64,
+    ) -> Result<TokenizeShareRecord, CosmosGrpcError> {
+        let res: QueryTokenizeShareRecordByIdResponse = self
+            .query_raw(
+                "/cosmos.staking.v1beta1.Query/TokenizeShareRecordById",
+                QueryTokenizeShareRecordByIdRequest { id },
+            )
+            .await?;
+        match res.record {
+            Some(record) => Ok(record),
+            None => Err(CosmosGrpcError::BadResponse(
+                "No tokenize share record".to_string(),
+            )),
+        }
+    }
+
+    /// Gets the tokenize share records owned by the given address
+    pub async fn get_tokenize_share_records_owned(
+        &self,
+        owner: Address,
+    ) -> Result<Vec<TokenizeShareRecord>, CosmosGrpcError> {
+        let res: QueryTokenizeShareRecordsOwnedResponse = self
+            .query_raw(
+                "/cosmos.staking.v1beta1.Query/TokenizeShareRecordsOwned",
+                QueryTokenizeShareRecordsOwnedRequest {
+                    owner: owner.to_bech32(&self.config.prefix).unwrap(),
+                },
+            )
+            .await?;
+        Ok(res.records)
+    }
+
+    /// Gets the total amount of the staking token that is liquid staked, this is
+    /// limited by the chain's global liquid staking cap
+    pub async fn get_total_liquid_staked(&self) -> Result<Coin, CosmosGrpcError> {
+        let res: QueryTotalLiquidStakedResponse = self
+            .query_raw(
+                "/cosmos.staking.v1beta1.Query/TotalLiquidStaked",
+                QueryTotalLiquidStakedRequest {},
+            )
+            .await?;
+        let denom = self.get_staking_params().await?.bond_denom;
+        match Uint256::from_dec_or_hex_str_restricted(&res.tokens) {
+            Ok(amount) => Ok(Coin { amount, denom }),
+            Err(e) => Err(CosmosGrpcError::BadResponse(format!(
+                "Invalid liquid staked amount {} {:?}",
+                res.tokens, e
+            ))),
+        }
+    }
+
+    /// Gets the total value of all outstanding share tokens
+    pub async fn get_total_tokenize_shared_assets(&self) -> Result<Coin, CosmosGrpcError> {
+        let res: QueryTotalTokenizeSharedAssetsResponse = self
+            .query_raw(
+                "/cosmos.staking.v1beta1.Query/TotalTokenizeSharedAssets",
+                QueryTotalTokenizeSharedAssetsRequest {},
+            )
+            .await?;
+        match res.value {
+            Some(value) => Ok(value.into()),
+            None => Err(CosmosGrpcError::BadResponse(
+                "No tokenize shared assets".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrivateKey;
+    use prost::Message;
+
+    #[test]
+    fn test_tokenize_shares() {
+        let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let address = key.to_address("cosmos").unwrap();
+        let amount: Coin = "100uatom".parse().unwrap();
+        let msg = Msg::tokenize_shares(address, address, amount.clone(), address);
+        assert_eq!(msg.0.type_url, MSG_TOKENIZE_SHARES_TYPE_URL);
+        let decoded = MsgTokenizeShares::decode(msg.0.value.as_slice()).unwrap();
+        assert_eq!(decoded.tokenized_share_owner, address.to_string());
+        assert_eq!(Coin::from(decoded.amount.unwrap()), amount);
+    }
+}
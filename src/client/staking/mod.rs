@@ -1,45 +1,156 @@
 //! Contains utility functions for interacting with and submitting Cosmos governance proposals
 
+use super::paginator::Paginator;
 use super::PAGE;
+use crate::client::types::DelegationSummary;
 use crate::error::CosmosGrpcError;
+use crate::utils::parse_sdk_dec;
 use crate::Address;
 use crate::Coin;
 use crate::Contact;
 use crate::Msg;
 use crate::PrivateKey;
+use crate::Uint256;
 use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::query_client::QueryClient as DistQueryClient;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::QueryDelegationTotalRewardsRequest;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::query_client::QueryClient as StakingQueryClient;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::DelegationResponse;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::MsgBeginRedelegate;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::MsgDelegate;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::MsgUndelegate;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::Params;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::Pool;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryDelegationRequest;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryDelegatorDelegationsRequest;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryDelegatorUnbondingDelegationsRequest;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryParamsRequest;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryPoolRequest;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryRedelegationsRequest;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryValidatorDelegationsRequest;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryValidatorsRequest;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::Validator;
 use std::time::Duration;
 
+pub mod lsm;
+
+pub const MSG_CANCEL_UNBONDING_DELEGATION_TYPE_URL: &str =
+    "/cosmos.staking.v1beta1.MsgCancelUnbondingDelegation";
+
+/// MsgCancelUnbondingDelegation from Cosmos SDK 0.46, returns tokens that are still
+/// unbonding to the validator they were unbonding from. Not included in the proto crate
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgCancelUnbondingDelegation {
+    #[prost(string, tag = "1")]
+    pub delegator_address: String,
+    #[prost(string, tag = "2")]
+    pub validator_address: String,
+    /// The amount to cancel, at most the balance of the unbonding entry
+    #[prost(message, optional, tag = "3")]
+    pub amount: Option<ProtoCoin>,
+    /// The height at which the unbonding took place, identifies the unbonding entry
+    #[prost(int64, tag = "4")]
+    pub creation_height: i64,
+}
+
+/// Splits `amount` into `tranches` near equal coins that add up to it, any remainder is
+/// spread over the first tranches. Staking tools use this to unbond a delegation in parts,
+/// one undelegate per epoch, rather than all at once. Returns nothing for zero tranches
+pub fn split_unbond(amount: &Coin, tranches: u64) -> Vec<Coin> {
+    let (size, remainder) = match amount.amount.divide(Uint256::from_u64(tranches)) {
+        Some(v) => v,
+        None => return Vec::new(),
+    };
+    let remainder = remainder.resize_to_u64();
+    (0..tranches)
+        .map(|i| Coin {
+            // the remainder is less than the tranche count so this can't overflow
+            amount: if i < remainder {
+                size.checked_add(Uint256::from_u64(1)).unwrap()
+            } else {
+                size
+            },
+            denom: amount.denom.clone(),
+        })
+        .collect()
+}
+
+impl Msg {
+    /// Creates a MsgCancelUnbondingDelegation, `creation_height` is the height of the
+    /// unbonding entry as returned by `get_delegation_summary`
+    pub fn cancel_unbonding_delegation(
+        delegator: Address,
+        validator: Address,
+        amount: Coin,
+        creation_height: i64,
+    ) -> Self {
+        let msg = MsgCancelUnbondingDelegation {
+            delegator_address: delegator.to_string(),
+            validator_address: validator.to_string(),
+            amount: Some(amount.into()),
+            creation_height,
+        };
+        Msg::new(MSG_CANCEL_UNBONDING_DELEGATION_TYPE_URL, msg)
+    }
+}
+
 impl Contact {
     /// Gets a list of validators
     pub async fn get_validators_list(
         &self,
         filters: QueryValidatorsRequest,
     ) -> Result<Vec<Validator>, CosmosGrpcError> {
-        let mut grpc = StakingQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
+        self.retry(|| async {
+            let mut grpc = StakingQueryClient::new(self.get_channel().await?).accept_gzip();
 
-        let res = grpc.validators(filters).await?.into_inner().validators;
-        Ok(res)
+            let res = grpc
+                .validators(filters.clone())
+                .await?
+                .into_inner()
+                .validators;
+            Ok(res)
+        })
+        .await
+    }
+
+    /// Pages through the validators with the given bond status, for example
+    /// BOND_STATUS_BONDED, or every validator if the status is empty
+    pub fn paginate_validators(&self, status: impl Into<String>) -> Paginator<Validator> {
+        let contact = self.clone();
+        let status = status.into();
+        Paginator::new(move |pagination| {
+            let contact = contact.clone();
+            let status = status.clone();
+            async move {
+                contact
+                    .retry(|| async {
+                        let mut grpc =
+                            StakingQueryClient::new(contact.get_channel().await?).accept_gzip();
+                        let res = grpc
+                            .validators(QueryValidatorsRequest {
+                                status: status.clone(),
+                                pagination: Some(pagination.clone()),
+                            })
+                            .await?
+                            .into_inner();
+                        Ok((res.validators, res.pagination))
+                    })
+                    .await
+            }
+        })
     }
 
     /// Gets a list of bonded validators
     pub async fn get_active_validators(&self) -> Result<Vec<Validator>, CosmosGrpcError> {
-        let req = QueryValidatorsRequest {
-            pagination: PAGE,
-            status: "BOND_STATUS_BONDED".to_string(),
-        };
-        self.get_validators_list(req).await
+        self.cached("active_validators", || async {
+            let req = QueryValidatorsRequest {
+                pagination: PAGE,
+                status: "BOND_STATUS_BONDED".to_string(),
+            };
+            self.get_validators_list(req).await
+        })
+        .await
     }
 
     /// Gets a list of delegators who have delegated to this validator
@@ -47,19 +158,48 @@ impl Contact {
         &self,
         validator: Address,
     ) -> Result<Vec<DelegationResponse>, CosmosGrpcError> {
-        let mut grpc = StakingQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
+        self.retry(|| async {
+            let mut grpc = StakingQueryClient::new(self.get_channel().await?).accept_gzip();
 
-        let res = grpc
-            .validator_delegations(QueryValidatorDelegationsRequest {
-                validator_addr: validator.to_string(),
-                pagination: PAGE,
-            })
-            .await?
-            .into_inner()
-            .delegation_responses;
-        Ok(res)
+            let res = grpc
+                .validator_delegations(QueryValidatorDelegationsRequest {
+                    validator_addr: validator.to_string(),
+                    pagination: PAGE,
+                })
+                .await?
+                .into_inner()
+                .delegation_responses;
+            Ok(res)
+        })
+        .await
+    }
+
+    /// Pages through the delegations made to this validator, popular validators have
+    /// far more delegators than fit in one response
+    pub fn paginate_validator_delegations(
+        &self,
+        validator: Address,
+    ) -> Paginator<DelegationResponse> {
+        let contact = self.clone();
+        Paginator::new(move |pagination| {
+            let contact = contact.clone();
+            async move {
+                contact
+                    .retry(|| async {
+                        let mut grpc =
+                            StakingQueryClient::new(contact.get_channel().await?).accept_gzip();
+                        let res = grpc
+                            .validator_delegations(QueryValidatorDelegationsRequest {
+                                validator_addr: validator.to_string(),
+                                pagination: Some(pagination.clone()),
+                            })
+                            .await?
+                            .into_inner();
+                        Ok((res.delegation_responses, res.pagination))
+                    })
+                    .await
+            }
+        })
     }
 
     /// Gets a the delegation info for a given delegator and validator pair
@@ -68,20 +208,154 @@ impl Contact {
         validator: Address,
         delegator: Address,
     ) -> Result<Option<DelegationResponse>, CosmosGrpcError> {
-        let mut grpc = StakingQueryClient::connect(self.url.clone())
-            .await?
-            .accept_gzip();
-
-        let res = grpc
-            .delegation(QueryDelegationRequest {
-                delegator_addr: delegator.to_string(),
-                validator_addr: validator.to_string(),
+        self.retry(|| async {
+            let mut grpc = StakingQueryClient::new(self.get_channel().await?).accept_gzip();
+
+            let res = grpc
+                .delegation(QueryDelegationRequest {
+                    delegator_addr: delegator.to_string(),
+                    validator_addr: validator.to_string(),
+                })
+                .await?
+                .into_inner()
+                .delegation_response;
+
+            Ok(res)
+        })
+        .await
+    }
+
+    /// Gets all delegations, unbonding delegations, redelegations, and accumulated rewards
+    /// for a single delegator. The underlying queries are made concurrently.
+    pub async fn get_delegation_summary(
+        &self,
+        delegator: Address,
+    ) -> Result<DelegationSummary, CosmosGrpcError> {
+        let delegations = self.retry(|| async {
+            let mut grpc = StakingQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .delegator_delegations(QueryDelegatorDelegationsRequest {
+                    delegator_addr: delegator.to_string(),
+                    pagination: PAGE,
+                })
+                .await?
+                .into_inner()
+                .delegation_responses;
+            Ok(res)
+        });
+        let unbonding = self.retry(|| async {
+            let mut grpc = StakingQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .delegator_unbonding_delegations(QueryDelegatorUnbondingDelegationsRequest {
+                    delegator_addr: delegator.to_string(),
+                    pagination: PAGE,
+                })
+                .await?
+                .into_inner()
+                .unbonding_responses;
+            Ok(res)
+        });
+        let redelegations = self.retry(|| async {
+            let mut grpc = StakingQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .redelegations(QueryRedelegationsRequest {
+                    delegator_addr: delegator.to_string(),
+                    src_validator_addr: String::new(),
+                    dst_validator_addr: String::new(),
+                    pagination: PAGE,
+                })
+                .await?
+                .into_inner()
+                .redelegation_responses;
+            Ok(res)
+        });
+        let rewards = self.retry(|| async {
+            let mut grpc = DistQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .delegation_total_rewards(QueryDelegationTotalRewardsRequest {
+                    delegator_address: delegator.to_string(),
+                })
+                .await?
+                .into_inner();
+            Ok(res)
+        });
+
+        let (delegations, unbonding, redelegations, rewards) =
+            tokio::try_join!(delegations, unbonding, redelegations, rewards)?;
+
+        Ok(DelegationSummary {
+            delegations,
+            unbonding,
+            redelegations,
+            rewards: rewards.rewards,
+            total_rewards: rewards.total,
+        })
+    }
+
+    /// Gets the total amount of bonded and not bonded tokens in the staking module
+    pub async fn get_staking_pool(&self) -> Result<Pool, CosmosGrpcError> {
+        self.retry(|| async {
+            let mut grpc = StakingQueryClient::new(self.get_channel().await?).accept_gzip();
+
+            let res = grpc.pool(QueryPoolRequest {}).await?.into_inner().pool;
+            match res {
+                Some(pool) => Ok(pool),
+                None => Err(CosmosGrpcError::BadResponse(
+                    "No staking pool returned".to_string(),
+                )),
+            }
+        })
+        .await
+    }
+
+    /// Gets the parameters of the staking module, including the bond denom and unbonding time
+    pub async fn get_staking_params(&self) -> Result<Params, CosmosGrpcError> {
+        self.cached("staking_params", || {
+            self.retry(|| async {
+                let mut grpc = StakingQueryClient::new(self.get_channel().await?).accept_gzip();
+
+                let res = grpc
+                    .params(QueryParamsRequest {})
+                    .await?
+                    .into_inner()
+                    .params;
+                match res {
+                    Some(params) => Ok(params),
+                    None => Err(CosmosGrpcError::BadResponse(
+                        "No staking params returned".to_string(),
+                    )),
+                }
             })
-            .await?
-            .into_inner()
-            .delegation_response;
+        })
+        .await
+    }
+
+    /// Estimates the nominal annual return for staking, before validator commission, 0.12
+    /// representing 12%. This is the annual provisions of the mint module, minus the community
+    /// pool tax, divided among all bonded tokens. Fee revenue is not included and chains
+    /// that replace the stock mint module will produce meaningless results.
+    pub async fn estimate_staking_apr(&self) -> Result<f64, CosmosGrpcError> {
+        let pool = self.get_staking_pool().await?;
+        let annual_provisions = self.get_annual_provisions().await?;
+        let community_tax = self.get_distribution_params().await?.community_tax;
+        let community_tax = parse_sdk_dec(&community_tax)?;
 
-        Ok(res)
+        let bonded_tokens: f64 = match pool.bonded_tokens.parse() {
+            Ok(v) => v,
+            Err(_) => {
+                return Err(CosmosGrpcError::BadResponse(format!(
+                    "Failed to parse bonded tokens {}",
+                    pool.bonded_tokens
+                )))
+            }
+        };
+        if bonded_tokens == 0.0 {
+            return Err(CosmosGrpcError::BadResponse(
+                "No tokens are bonded".to_string(),
+            ));
+        }
+
+        Ok(annual_provisions * (1.0 - community_tax) / bonded_tokens)
     }
 
     /// Delegates tokens to a specified bonded validator
@@ -93,7 +367,7 @@ impl Contact {
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
         let vote = MsgDelegate {
             amount: Some(amount_to_delegate.into()),
             delegator_address: our_address.to_string(),
@@ -118,7 +392,7 @@ impl Contact {
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
         let redelegate = MsgBeginRedelegate {
             amount: Some(amount_to_redelegate.into()),
             delegator_address: our_address.to_string(),
@@ -142,7 +416,7 @@ impl Contact {
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
         let undelegate = MsgUndelegate {
             amount: Some(amount_to_undelegate.into()),
             delegator_address: our_address.to_string(),
@@ -153,4 +427,144 @@ impl Contact {
         self.send_message(&[msg], None, &[fee], wait_timeout, private_key)
             .await
     }
+
+    /// Cancels `amount` of an unbonding delegation, returning it to the validator it was
+    /// unbonding from. Requires a chain running Cosmos SDK 0.46 or later
+    pub async fn cancel_unbonding_delegation(
+        &self,
+        validator_address: Address,
+        amount: Coin,
+        creation_height: i64,
+        fee: Coin,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
+        let msg = Msg::cancel_unbonding_delegation(
+            our_address,
+            validator_address,
+            amount,
+            creation_height,
+        );
+        self.send_message(&[msg], None, &[fee], wait_timeout, private_key)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock_grpc::MockGrpc;
+    use cosmos_sdk_proto::cosmos::base::v1beta1::DecCoin;
+    use cosmos_sdk_proto::cosmos::distribution::v1beta1::{
+        DelegationDelegatorReward, QueryDelegationTotalRewardsResponse,
+    };
+    use cosmos_sdk_proto::cosmos::staking::v1beta1::{
+        Delegation, QueryDelegatorDelegationsResponse, QueryDelegatorUnbondingDelegationsResponse,
+        QueryRedelegationsResponse, UnbondingDelegation,
+    };
+    use prost::Message;
+
+    #[actix_rt::test]
+    async fn test_get_delegation_summary() {
+        let delegator = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"")
+            .unwrap()
+            .to_address("cosmos")
+            .unwrap();
+        let expected = delegator.to_string();
+        let grpc = MockGrpc::new()
+            .with(
+                "/cosmos.staking.v1beta1.Query/DelegatorDelegations",
+                move |req: QueryDelegatorDelegationsRequest| {
+                    assert_eq!(req.delegator_addr, expected);
+                    Ok(QueryDelegatorDelegationsResponse {
+                        delegation_responses: vec![DelegationResponse {
+                            delegation: Some(Delegation {
+                                delegator_address: req.delegator_addr,
+                                validator_address: "cosmosvaloper1a".to_string(),
+                                shares: "5000000000000000000".to_string(),
+                            }),
+                            balance: Some(ProtoCoin {
+                                denom: "uatom".to_string(),
+                                amount: "5".to_string(),
+                            }),
+                        }],
+                        pagination: None,
+                    })
+                },
+            )
+            .with(
+                "/cosmos.staking.v1beta1.Query/DelegatorUnbondingDelegations",
+                |req: QueryDelegatorUnbondingDelegationsRequest| {
+                    Ok(QueryDelegatorUnbondingDelegationsResponse {
+                        unbonding_responses: vec![UnbondingDelegation {
+                            delegator_address: req.delegator_addr,
+                            validator_address: "cosmosvaloper1b".to_string(),
+                            entries: vec![],
+                        }],
+                        pagination: None,
+                    })
+                },
+            )
+            .with(
+                "/cosmos.staking.v1beta1.Query/Redelegations",
+                |_: QueryRedelegationsRequest| Ok(QueryRedelegationsResponse::default()),
+            )
+            .with(
+                "/cosmos.distribution.v1beta1.Query/DelegationTotalRewards",
+                |_: QueryDelegationTotalRewardsRequest| {
+                    let reward = DecCoin {
+                        denom: "uatom".to_string(),
+                        amount: "1500000000000000000".to_string(),
+                    };
+                    Ok(QueryDelegationTotalRewardsResponse {
+                        rewards: vec![DelegationDelegatorReward {
+                            validator_address: "cosmosvaloper1a".to_string(),
+                            reward: vec![reward.clone()],
+                        }],
+                        total: vec![reward],
+                    })
+                },
+            );
+        let url = grpc.serve().await;
+        let contact = Contact::new(&url, Duration::from_secs(5), "cosmos").unwrap();
+
+        let summary = contact.get_delegation_summary(delegator).await.unwrap();
+        assert_eq!(summary.delegations.len(), 1);
+        assert_eq!(summary.delegations[0].balance.as_ref().unwrap().amount, "5");
+        assert_eq!(summary.unbonding[0].validator_address, "cosmosvaloper1b");
+        assert!(summary.redelegations.is_empty());
+        assert_eq!(summary.rewards[0].validator_address, "cosmosvaloper1a");
+        assert_eq!(summary.total_rewards[0].amount, "1500000000000000000");
+        assert_eq!(grpc.calls().len(), 4);
+
+        // any one of the queries failing fails the summary
+        let url = MockGrpc::new().serve().await;
+        let contact = Contact::new(&url, Duration::from_secs(5), "cosmos").unwrap();
+        assert!(contact.get_delegation_summary(delegator).await.is_err());
+    }
+
+    #[test]
+    fn test_split_unbond() {
+        let amount: Coin = "10uatom".parse().unwrap();
+        let tranches = split_unbond(&amount, 3);
+        let amounts: Vec<String> = tranches.iter().map(|c| c.to_string()).collect();
+        assert_eq!(amounts, vec!["4uatom", "3uatom", "3uatom"]);
+        assert_eq!(split_unbond(&amount, 1), vec![amount.clone()]);
+        assert!(split_unbond(&amount, 0).is_empty());
+    }
+
+    #[test]
+    fn test_cancel_unbonding_delegation() {
+        let address = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"")
+            .unwrap()
+            .to_address("cosmos")
+            .unwrap();
+        let amount: Coin = "100uatom".parse().unwrap();
+        let msg = Msg::cancel_unbonding_delegation(address, address, amount.clone(), 1234);
+        assert_eq!(msg.0.type_url, MSG_CANCEL_UNBONDING_DELEGATION_TYPE_URL);
+        let decoded = MsgCancelUnbondingDelegation::decode(msg.0.value.as_slice()).unwrap();
+        assert_eq!(decoded.creation_height, 1234);
+        assert_eq!(Coin::from(decoded.amount.unwrap()), amount);
+    }
 }
@@ -1,10 +1,130 @@
 use crate::address::Address;
+use crate::error::CosmosGrpcError;
+use crate::registry::{AnyRegistry, TypedValue};
+use crate::{Coin, Uint256};
 use cosmos_sdk_proto::cosmos::auth::v1beta1::BaseAccount as ProtoBaseAccount;
+use cosmos_sdk_proto::cosmos::auth::v1beta1::ModuleAccount;
+use cosmos_sdk_proto::cosmos::auth::v1beta1::Params as AuthParams;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::Params as BankParams;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{GasInfo, MsgData};
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::Validator as TendermintValidator;
+use cosmos_sdk_proto::cosmos::base::v1beta1::DecCoin;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::DelegationDelegatorReward;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::Params as DistributionParams;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::{DepositParams, TallyParams, VotingParams};
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::Params as SlashingParams;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::Params as StakingParams;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::{
+    DelegationResponse, RedelegationResponse, UnbondingDelegation,
+};
+use cosmos_sdk_proto::cosmos::tx::v1beta1::SimulateResponse;
 use cosmos_sdk_proto::cosmos::vesting::v1beta1::{
-    ContinuousVestingAccount, DelayedVestingAccount, PeriodicVestingAccount,
+    BaseVestingAccount, ContinuousVestingAccount, DelayedVestingAccount, PeriodicVestingAccount,
+    PermanentLockedAccount,
 };
+#[cfg(feature = "ethermint")]
+use cosmos_sdk_proto::ethermint::types::v1::EthAccount;
+use cosmos_sdk_proto::tendermint::abci::Event;
 use cosmos_sdk_proto::tendermint::types::Block;
+use prost::Message;
 use prost_types::Any;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Everything chain specific a Contact needs to know, stored inside the Contact so that
+/// helper functions do not need the prefix or fee denom passed on every call
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainConfig {
+    /// The bech32 prefix of account addresses
+    pub prefix: String,
+    /// The denom used for fees, empty if unknown
+    pub denom: String,
+    /// The price of one unit of gas in `denom`, used to compute default fees
+    pub gas_price: f64,
+    /// The BIP44 coin type used for key derivation, 118 for most Cosmos chains
+    pub coin_type: u32,
+    /// The expected chain id, if set transactions will not be built against a node on any other chain
+    pub chain_id: Option<String>,
+    /// Used to estimate gas without simulating, see `Contact::estimate_gas_offline`
+    pub gas_heuristic: GasHeuristic,
+}
+
+impl ChainConfig {
+    /// Creates a config with only a prefix set, the coin type defaults to 118
+    pub fn new(prefix: impl Into<String>) -> ChainConfig {
+        ChainConfig {
+            prefix: prefix.into(),
+            denom: String::new(),
+            gas_price: 0.0,
+            coin_type: 118,
+            chain_id: None,
+            gas_heuristic: GasHeuristic::default(),
+        }
+    }
+
+    /// The BIP44 path of the first account for this chain's coin type
+    pub fn hd_path(&self) -> String {
+        format!("{}/0/0", self.account_path())
+    }
+
+    /// The BIP44 account level path m/44'/coin_type'/0', this is where wallets
+    /// export extended public keys
+    pub fn account_path(&self) -> String {
+        format!("m/44'/{}'/0'", self.coin_type)
+    }
+}
+
+/// A rough model of the gas a transaction consumes, for use when a simulate round trip
+/// is not possible. The byte and signature costs mirror the auth module's
+/// `tx_size_cost_per_byte` and `sig_verify_cost_secp256k1` params, the remaining
+/// costs stand in for state access and vary by chain and message type, so the
+/// defaults are deliberately generous for common bank and staking messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasHeuristic {
+    /// Gas consumed by the ante handler and other fixed per tx work
+    pub base_gas: u64,
+    pub tx_size_cost_per_byte: u64,
+    pub sig_verify_cost: u64,
+    /// Gas consumed executing each message
+    pub gas_per_msg: u64,
+}
+
+impl Default for GasHeuristic {
+    fn default() -> Self {
+        GasHeuristic {
+            base_gas: 50_000,
+            tx_size_cost_per_byte: 10,
+            sig_verify_cost: 1_000,
+            gas_per_msg: 100_000,
+        }
+    }
+}
+
+impl GasHeuristic {
+    /// Estimates the gas used by a transaction of `tx_size` bytes containing
+    /// `num_msgs` messages signed by `num_signers` keys
+    pub fn estimate_gas(&self, tx_size: usize, num_msgs: usize, num_signers: usize) -> u64 {
+        self.base_gas
+            .saturating_add(self.tx_size_cost_per_byte.saturating_mul(tx_size as u64))
+            .saturating_add(self.sig_verify_cost.saturating_mul(num_signers as u64))
+            .saturating_add(self.gas_per_msg.saturating_mul(num_msgs as u64))
+    }
+}
+
+/// How long to wait for a transaction to be included in a block, see `Contact::wait_for_tx`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutPolicy {
+    /// Give up after this amount of wall time
+    Duration(Duration),
+    /// Give up once this many blocks have been produced without the tx
+    Blocks(u64),
+}
+
+impl From<Duration> for TimeoutPolicy {
+    fn from(value: Duration) -> Self {
+        TimeoutPolicy::Duration(value)
+    }
+}
 
 /// This struct represents the status of a Cosmos chain, instead of just getting the
 /// latest block height we mandate that chain status is used, this allows callers to
@@ -41,6 +161,33 @@ pub enum LatestBlock {
     WaitingToStart,
 }
 
+/// Everything a single delegator has staked, in the process of unstaking, or has
+/// earned from staking, as returned by `Contact::get_delegation_summary`
+#[derive(Debug, Clone)]
+pub struct DelegationSummary {
+    /// Active delegations, one per validator
+    pub delegations: Vec<DelegationResponse>,
+    /// Delegations in the process of unbonding, each may have several entries
+    pub unbonding: Vec<UnbondingDelegation>,
+    /// Redelegations that have not yet matured
+    pub redelegations: Vec<RedelegationResponse>,
+    /// Accumulated but unwithdrawn rewards, per validator
+    pub rewards: Vec<DelegationDelegatorReward>,
+    /// The sum of all accumulated rewards, DecCoins are multiplied by 1*10^18
+    pub total_rewards: Vec<DecCoin>,
+}
+
+/// An address found to be in use by `Contact::discover_accounts`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredAccount {
+    /// The last index of the path m/44'/coin_type'/0'/0/index
+    pub index: u32,
+    pub address: Address,
+    /// None if the chain has no account for this address but it holds a balance
+    pub account: Option<AccountType>,
+    pub balances: Vec<Coin>,
+}
+
 /// This is a parsed and validated version of the Cosmos base account proto
 /// struct
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -120,6 +267,370 @@ impl CosmosAccount for PeriodicVestingAccount {
     }
 }
 
+impl CosmosAccount for PermanentLockedAccount {
+    fn get_base_account(&self) -> BaseAccount {
+        self.base_vesting_account
+            .clone()
+            .unwrap()
+            .base_account
+            .unwrap()
+            .into()
+    }
+}
+
+impl CosmosAccount for ModuleAccount {
+    fn get_base_account(&self) -> BaseAccount {
+        self.base_account.clone().unwrap().into()
+    }
+}
+
+#[cfg(feature = "ethermint")]
+impl CosmosAccount for EthAccount {
+    fn get_base_account(&self) -> BaseAccount {
+        self.base_account.clone().unwrap().into()
+    }
+}
+
+/// Every account type the auth module may return, decoded according to the
+/// type url of the account Any
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountType {
+    Base(ProtoBaseAccount),
+    ContinuousVesting(ContinuousVestingAccount),
+    DelayedVesting(DelayedVestingAccount),
+    PeriodicVesting(PeriodicVestingAccount),
+    PermanentLocked(PermanentLockedAccount),
+    Module(ModuleAccount),
+    #[cfg(feature = "ethermint")]
+    Eth(EthAccount),
+    /// A chain specific account type registered with `AnyRegistry::register_account`
+    Custom(CustomAccount),
+}
+
+/// An account of a type registered with an AnyRegistry, `value` holds the decoded
+/// account
+#[derive(Debug, Clone)]
+pub struct CustomAccount {
+    pub value: TypedValue,
+    base_account: BaseAccount,
+}
+
+impl CustomAccount {
+    pub(crate) fn new(value: TypedValue, base_account: BaseAccount) -> Self {
+        CustomAccount {
+            value,
+            base_account,
+        }
+    }
+}
+
+impl PartialEq for CustomAccount {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl CosmosAccount for CustomAccount {
+    fn get_base_account(&self) -> BaseAccount {
+        self.base_account.clone()
+    }
+}
+
+impl AccountType {
+    /// Decodes an account Any as returned by the auth module
+    pub fn decode_any(value: Any) -> Result<AccountType, CosmosGrpcError> {
+        AccountType::decode_any_with(value, &AnyRegistry::new())
+    }
+
+    /// Decodes an account Any, account types registered with `registry` take
+    /// precedence over the built in types
+    pub fn decode_any_with(
+        value: Any,
+        registry: &AnyRegistry,
+    ) -> Result<AccountType, CosmosGrpcError> {
+        if let Some(account) = registry.decode_account(&value) {
+            return Ok(AccountType::Custom(account?));
+        }
+        let buf = value.value.as_slice();
+        let res = match value.type_url.as_str() {
+            "/cosmos.auth.v1beta1.BaseAccount" => AccountType::Base(Message::decode(buf)?),
+            "/cosmos.vesting.v1beta1.ContinuousVestingAccount" => {
+                AccountType::ContinuousVesting(Message::decode(buf)?)
+            }
+            "/cosmos.vesting.v1beta1.DelayedVestingAccount" => {
+                AccountType::DelayedVesting(Message::decode(buf)?)
+            }
+            "/cosmos.vesting.v1beta1.PeriodicVestingAccount" => {
+                AccountType::PeriodicVesting(Message::decode(buf)?)
+            }
+            "/cosmos.vesting.v1beta1.PermanentLockedAccount" => {
+                AccountType::PermanentLocked(Message::decode(buf)?)
+            }
+            "/cosmos.auth.v1beta1.ModuleAccount" => AccountType::Module(Message::decode(buf)?),
+            #[cfg(feature = "ethermint")]
+            "/ethermint.types.v1.EthAccount" => AccountType::Eth(Message::decode(buf)?),
+            other => {
+                return Err(CosmosGrpcError::BadResponse(format!(
+                    "Unknown account type {}",
+                    other
+                )))
+            }
+        };
+        Ok(res)
+    }
+
+    fn base_vesting_account(&self) -> Option<&BaseVestingAccount> {
+        match self {
+            AccountType::ContinuousVesting(a) => a.base_vesting_account.as_ref(),
+            AccountType::DelayedVesting(a) => a.base_vesting_account.as_ref(),
+            AccountType::PeriodicVesting(a) => a.base_vesting_account.as_ref(),
+            AccountType::PermanentLocked(a) => a.base_vesting_account.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// True if this account has (or had) a vesting schedule
+    pub fn is_vesting(&self) -> bool {
+        self.base_vesting_account().is_some()
+    }
+
+    /// The total amount of coins subject to vesting when the account was created
+    pub fn original_vesting(&self) -> Vec<Coin> {
+        match self.base_vesting_account() {
+            Some(v) => v
+                .original_vesting
+                .iter()
+                .cloned()
+                .map(|c| c.into())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Coins that were unlocked at the time they were delegated
+    pub fn delegated_free(&self) -> Vec<Coin> {
+        match self.base_vesting_account() {
+            Some(v) => v.delegated_free.iter().cloned().map(|c| c.into()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Coins that were still locked at the time they were delegated
+    pub fn delegated_vesting(&self) -> Vec<Coin> {
+        match self.base_vesting_account() {
+            Some(v) => v
+                .delegated_vesting
+                .iter()
+                .cloned()
+                .map(|c| c.into())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The unix time vesting begins, delayed and permanently locked accounts have no start time
+    pub fn vesting_start_time(&self) -> Option<i64> {
+        match self {
+            AccountType::ContinuousVesting(a) => Some(a.start_time),
+            AccountType::PeriodicVesting(a) => Some(a.start_time),
+            _ => None,
+        }
+    }
+
+    /// The unix time at which all coins are vested, permanently locked accounts never vest
+    pub fn vesting_end_time(&self) -> Option<i64> {
+        match self {
+            AccountType::PermanentLocked(_) => None,
+            _ => self.base_vesting_account().map(|v| v.end_time),
+        }
+    }
+
+    /// The coins which have vested as of the provided unix time
+    pub fn vested_coins(&self, unix_time: i64) -> Vec<Coin> {
+        let original = self.original_vesting();
+        match self {
+            AccountType::ContinuousVesting(a) => {
+                let end_time = self.vesting_end_time().unwrap_or_default();
+                if unix_time <= a.start_time {
+                    Vec::new()
+                } else if unix_time >= end_time {
+                    original
+                } else {
+                    let elapsed = Uint256::from_u64((unix_time - a.start_time) as u64);
+                    let duration = Uint256::from_u64((end_time - a.start_time) as u64);
+                    original
+                        .into_iter()
+                        .map(|c| Coin {
+                            amount: c
+                                .amount
+                                .checked_mul(elapsed)
+                                .and_then(|v| v.divide(duration))
+                                .map(|v| v.0)
+                                .unwrap_or_default(),
+                            denom: c.denom,
+                        })
+                        .collect()
+                }
+            }
+            AccountType::DelayedVesting(_) => match self.vesting_end_time() {
+                Some(end_time) if unix_time >= end_time => original,
+                _ => Vec::new(),
+            },
+            AccountType::PeriodicVesting(a) => {
+                let mut vested = Vec::new();
+                let mut period_start = a.start_time;
+                if unix_time <= a.start_time {
+                    return vested;
+                }
+                for period in a.vesting_periods.iter() {
+                    if unix_time - period_start < period.length {
+                        break;
+                    }
+                    vested.extend(period.amount.iter().cloned().map(Coin::from));
+                    period_start += period.length;
+                }
+                add_coins(&[], &vested)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// The coins which are still locked as of the provided unix time
+    pub fn vesting_coins(&self, unix_time: i64) -> Vec<Coin> {
+        sub_coins(&self.original_vesting(), &self.vested_coins(unix_time))
+    }
+}
+
+impl CosmosAccount for AccountType {
+    fn get_base_account(&self) -> BaseAccount {
+        match self {
+            AccountType::Base(a) => a.get_base_account(),
+            AccountType::ContinuousVesting(a) => a.get_base_account(),
+            AccountType::DelayedVesting(a) => a.get_base_account(),
+            AccountType::PeriodicVesting(a) => a.get_base_account(),
+            AccountType::PermanentLocked(a) => a.get_base_account(),
+            AccountType::Module(a) => a.get_base_account(),
+            #[cfg(feature = "ethermint")]
+            AccountType::Eth(a) => a.get_base_account(),
+            AccountType::Custom(a) => a.get_base_account(),
+        }
+    }
+}
+
+/// Sums two lists of coins, merging amounts of the same denom
+fn add_coins(a: &[Coin], b: &[Coin]) -> Vec<Coin> {
+    let mut out: Vec<Coin> = a.to_vec();
+    for coin in b {
+        match out.iter_mut().find(|c| c.denom == coin.denom) {
+            Some(c) => c.amount = c.amount.checked_add(coin.amount).unwrap(),
+            None => out.push(coin.clone()),
+        }
+    }
+    out
+}
+
+/// Subtracts b from a by denom, saturating at zero and dropping empty coins
+fn sub_coins(a: &[Coin], b: &[Coin]) -> Vec<Coin> {
+    let mut out = Vec::new();
+    for coin in a {
+        let amount = match b.iter().find(|c| c.denom == coin.denom) {
+            Some(c) => coin.amount.checked_sub(c.amount).unwrap_or_default(),
+            None => coin.amount,
+        };
+        if !amount.is_zero() {
+            out.push(Coin {
+                amount,
+                denom: coin.denom.clone(),
+            });
+        }
+    }
+    out
+}
+
+/// The governance module params, which the gov module returns in three parts
+#[derive(Debug, Clone, PartialEq)]
+pub struct GovParams {
+    pub voting: VotingParams,
+    pub deposit: DepositParams,
+    pub tally: TallyParams,
+}
+
+/// The params of the core modules, as returned by `Contact::get_chain_params`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainParams {
+    pub auth: AuthParams,
+    pub bank: BankParams,
+    pub staking: StakingParams,
+    pub distribution: DistributionParams,
+    pub gov: GovParams,
+    pub slashing: SlashingParams,
+}
+
+/// The Tendermint validator set of a block, as returned by `Contact::get_validator_set`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatorSet {
+    pub height: u64,
+    /// `address` is the bech32 consensus address of each validator
+    pub validators: Vec<TendermintValidator>,
+}
+
+/// The voting power of a validator that is in both sets changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PowerChange {
+    /// The consensus address of the validator
+    pub address: String,
+    pub old_power: i64,
+    pub new_power: i64,
+}
+
+/// The differences between two validator sets, see `diff_validator_sets`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ValidatorSetDiff {
+    /// Validators in the new set but not the old one
+    pub joined: Vec<TendermintValidator>,
+    /// Validators in the old set but not the new one
+    pub left: Vec<TendermintValidator>,
+    pub power_changes: Vec<PowerChange>,
+}
+
+impl ValidatorSetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.joined.is_empty() && self.left.is_empty() && self.power_changes.is_empty()
+    }
+}
+
+/// Compares two validator sets by consensus address, changes in proposer priority
+/// alone are ignored since it changes every block
+pub fn diff_validator_sets(
+    old: &[TendermintValidator],
+    new: &[TendermintValidator],
+) -> ValidatorSetDiff {
+    let old_by_address: HashMap<&str, &TendermintValidator> =
+        old.iter().map(|v| (v.address.as_str(), v)).collect();
+    let new_by_address: HashMap<&str, &TendermintValidator> =
+        new.iter().map(|v| (v.address.as_str(), v)).collect();
+    let mut diff = ValidatorSetDiff::default();
+    for validator in new {
+        match old_by_address.get(validator.address.as_str()) {
+            None => diff.joined.push(validator.clone()),
+            Some(previous) if previous.voting_power != validator.voting_power => {
+                diff.power_changes.push(PowerChange {
+                    address: validator.address.clone(),
+                    old_power: previous.voting_power,
+                    new_power: validator.voting_power,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    diff.left = old
+        .iter()
+        .filter(|v| !new_by_address.contains_key(v.address.as_str()))
+        .cloned()
+        .collect();
+    diff
+}
+
 /// A mirror of the BlockParams struct represents the maximum gas and bytes a block is allowed in the chain
 /// None represents unlimited
 #[derive(Debug, Clone)]
@@ -128,5 +639,245 @@ pub struct BlockParams {
     pub max_gas: Option<u64>,
 }
 
+/// TxMsgData as defined by Cosmos SDK 0.46, the generated TxMsgData predates the
+/// `msg_responses` field which replaced the per message `data`
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct TxMsgDataV046 {
+    #[prost(message, repeated, tag = "1")]
+    data: Vec<MsgData>,
+    #[prost(message, repeated, tag = "2")]
+    msg_responses: Vec<Any>,
+}
+
+/// The outcome of `Contact::dry_run`, a simulation of a transaction that is never
+/// broadcast. Gives the events a transaction would emit, for example by a wasm
+/// contract, along with the gas it would use
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRun {
+    pub gas_info: GasInfo,
+    pub events: Vec<Event>,
+    /// The response of each message in order, on chains before Cosmos SDK 0.46 these
+    /// are built from the message data, with the type url of the message itself
+    pub msg_responses: Vec<Any>,
+    /// The undecoded simulation response
+    pub response: SimulateResponse,
+}
+
+impl TryFrom<SimulateResponse> for DryRun {
+    type Error = CosmosGrpcError;
+
+    fn try_from(response: SimulateResponse) -> Result<Self, Self::Error> {
+        let gas_info = response.gas_info.clone().unwrap_or_default();
+        let (events, msg_responses) = match &response.result {
+            Some(result) => {
+                let data = TxMsgDataV046::decode(result.data.as_slice())?;
+                let msg_responses = if data.msg_responses.is_empty() {
+                    data.data
+                        .into_iter()
+                        .map(|d| Any {
+                            type_url: d.msg_type,
+                            value: d.data,
+                        })
+                        .collect()
+                } else {
+                    data.msg_responses
+                };
+                (result.events.clone(), msg_responses)
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+        Ok(DryRun {
+            gas_info,
+            events,
+            msg_responses,
+            response,
+        })
+    }
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::utils::encode_any;
+    use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+    use cosmos_sdk_proto::cosmos::vesting::v1beta1::Period;
+
+    fn base_vesting(amount: u64, end_time: i64) -> Option<BaseVestingAccount> {
+        Some(BaseVestingAccount {
+            base_account: Some(ProtoBaseAccount {
+                address: "cosmos1pr2n6tfymnn2tk6rkxlu9q5q2zq5ka3wtu7sdj".to_string(),
+                pub_key: None,
+                account_number: 5,
+                sequence: 7,
+            }),
+            original_vesting: vec![ProtoCoin {
+                denom: "stake".to_string(),
+                amount: amount.to_string(),
+            }],
+            delegated_free: Vec::new(),
+            delegated_vesting: Vec::new(),
+            end_time,
+        })
+    }
+
+    fn stake(amount: u64) -> Vec<Coin> {
+        vec![Coin {
+            amount: Uint256::from_u64(amount),
+            denom: "stake".to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_decode_vesting_accounts() {
+        let continuous = ContinuousVestingAccount {
+            base_vesting_account: base_vesting(1000, 200),
+            start_time: 100,
+        };
+        let any = encode_any(
+            continuous,
+            "/cosmos.vesting.v1beta1.ContinuousVestingAccount",
+        );
+        let account = AccountType::decode_any(any).unwrap();
+        assert!(account.is_vesting());
+        assert_eq!(account.get_base_account().sequence, 7);
+        assert_eq!(account.vesting_coins(50), stake(1000));
+        assert_eq!(account.vesting_coins(150), stake(500));
+        assert_eq!(account.vested_coins(175), stake(750));
+        assert_eq!(account.vesting_coins(200), Vec::new());
+
+        let periodic = AccountType::PeriodicVesting(PeriodicVestingAccount {
+            base_vesting_account: base_vesting(1000, 300),
+            start_time: 100,
+            vesting_periods: vec![
+                Period {
+                    length: 100,
+                    amount: vec![ProtoCoin {
+                        denom: "stake".to_string(),
+                        amount: "400".to_string(),
+                    }],
+                },
+                Period {
+                    length: 100,
+                    amount: vec![ProtoCoin {
+                        denom: "stake".to_string(),
+                        amount: "600".to_string(),
+                    }],
+                },
+            ],
+        });
+        assert_eq!(periodic.vesting_coins(199), stake(1000));
+        assert_eq!(periodic.vesting_coins(200), stake(600));
+        assert_eq!(periodic.vesting_coins(300), Vec::new());
+
+        let module = ModuleAccount {
+            base_account: base_vesting(0, 0).unwrap().base_account,
+            name: "distribution".to_string(),
+            permissions: Vec::new(),
+        };
+        let any = encode_any(module, "/cosmos.auth.v1beta1.ModuleAccount");
+        let account = AccountType::decode_any(any).unwrap();
+        assert!(!account.is_vesting());
+        assert_eq!(account.get_base_account().account_number, 5);
+
+        let any = Any {
+            type_url: "/unknown.Account".to_string(),
+            value: Vec::new(),
+        };
+        assert!(AccountType::decode_any(any).is_err());
+
+        // a chain specific vesting account, registered with a decoder function
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        struct StridePeriodicVestingAccount {
+            #[prost(message, optional, tag = "1")]
+            base_vesting_account: Option<BaseVestingAccount>,
+        }
+        let url = "/stride.vesting.StridePeriodicVestingAccount";
+        let any = encode_any(
+            StridePeriodicVestingAccount {
+                base_vesting_account: base_vesting(1000, 300),
+            },
+            url,
+        );
+        let mut registry = AnyRegistry::new();
+        registry.register_account_decoder(url, |buf| {
+            let account = StridePeriodicVestingAccount::decode(buf)?;
+            let base = account.base_vesting_account.clone().unwrap_or_default();
+            Ok((account, base.base_account.unwrap_or_default().into()))
+        });
+        assert!(registry.contains(url));
+        let account = AccountType::decode_any_with(any, &registry).unwrap();
+        assert!(matches!(account, AccountType::Custom(_)));
+        assert_eq!(account.get_base_account().sequence, 7);
+    }
+
+    #[test]
+    fn test_diff_validator_sets() {
+        let validator = |address: &str, voting_power: i64| TendermintValidator {
+            address: address.to_string(),
+            pub_key: None,
+            voting_power,
+            proposer_priority: 0,
+        };
+        let old = vec![validator("a", 10), validator("b", 20), validator("c", 30)];
+        let mut new = vec![validator("a", 10), validator("c", 35), validator("d", 5)];
+        let diff = diff_validator_sets(&old, &new);
+        assert_eq!(diff.joined, vec![validator("d", 5)]);
+        assert_eq!(diff.left, vec![validator("b", 20)]);
+        assert_eq!(
+            diff.power_changes,
+            vec![PowerChange {
+                address: "c".to_string(),
+                old_power: 30,
+                new_power: 35,
+            }]
+        );
+        new[0].proposer_priority = 100;
+        assert!(diff_validator_sets(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_dry_run_msg_responses() {
+        use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{Result as AbciResult, TxMsgData};
+        let legacy = TxMsgData {
+            data: vec![MsgData {
+                msg_type: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                data: vec![1, 2],
+            }],
+        };
+        let response = SimulateResponse {
+            gas_info: Some(GasInfo {
+                gas_wanted: 10,
+                gas_used: 5,
+            }),
+            result: Some(AbciResult {
+                data: legacy.encode_to_vec(),
+                log: String::new(),
+                events: vec![Event {
+                    r#type: "wasm".to_string(),
+                    attributes: Vec::new(),
+                }],
+            }),
+        };
+        let dry_run = DryRun::try_from(response.clone()).unwrap();
+        assert_eq!(dry_run.gas_info.gas_used, 5);
+        assert_eq!(dry_run.events[0].r#type, "wasm");
+        assert_eq!(
+            dry_run.msg_responses[0].type_url,
+            "/cosmos.bank.v1beta1.MsgSend"
+        );
+        assert_eq!(dry_run.msg_responses[0].value, vec![1, 2]);
+
+        let msg_response = encode_any(
+            MsgData::default(),
+            "/cosmwasm.wasm.v1.MsgExecuteContractResponse",
+        );
+        let current = TxMsgDataV046 {
+            data: Vec::new(),
+            msg_responses: vec![msg_response.clone()],
+        };
+        let mut response = response;
+        response.result.as_mut().unwrap().data = current.encode_to_vec();
+        let dry_run = DryRun::try_from(response).unwrap();
+        assert_eq!(dry_run.msg_responses, vec![msg_response]);
+    }
+}
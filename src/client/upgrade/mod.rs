@@ -0,0 +1,193 @@
+//! Contains utility functions for querying scheduled software upgrades through the Cosmos sdk upgrade module
+
+use super::ChainStatus;
+use crate::error::CosmosGrpcError;
+use crate::Contact;
+use cosmos_sdk_proto::cosmos::upgrade::v1beta1::Plan;
+
+// the upgrade query service is not included in the proto crate, these mirror
+// cosmos/upgrade/v1beta1/query.proto
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryCurrentPlanRequest {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryCurrentPlanResponse {
+    #[prost(message, optional, tag = "1")]
+    plan: Option<Plan>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryAppliedPlanRequest {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct QueryAppliedPlanResponse {
+    #[prost(int64, tag = "1")]
+    height: i64,
+}
+
+impl Contact {
+    /// Gets the currently scheduled software upgrade, if any
+    pub async fn get_pending_upgrade(&self) -> Result<Option<Plan>, CosmosGrpcError> {
+        let res: QueryCurrentPlanResponse = self
+            .query_raw(
+                "/cosmos.upgrade.v1beta1.Query/CurrentPlan",
+                QueryCurrentPlanRequest {},
+            )
+            .await?;
+        Ok(res.plan)
+    }
+
+    /// Gets the height at which the named upgrade was applied, None if it has not been applied
+    pub async fn get_applied_upgrade_height(
+        &self,
+        name: impl ToString,
+    ) -> Result<Option<i64>, CosmosGrpcError> {
+        let res: QueryAppliedPlanResponse = self
+            .query_raw(
+                "/cosmos.upgrade.v1beta1.Query/AppliedPlan",
+                QueryAppliedPlanRequest {
+                    name: name.to_string(),
+                },
+            )
+            .await?;
+        let res = res.height;
+        if res == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(res))
+        }
+    }
+
+    /// Returns the upgrade plan the chain is currently halted for, if any. A chain halts
+    /// for an upgrade once the block before the plan height is committed and will not
+    /// resume until the new binary is installed, any transactions sent in the meantime
+    /// will not enter the chain.
+    pub async fn get_upgrade_halt(&self) -> Result<Option<Plan>, CosmosGrpcError> {
+        let plan = match self.get_pending_upgrade().await? {
+            Some(plan) => plan,
+            None => return Ok(None),
+        };
+        match self.get_chain_status().await? {
+            ChainStatus::Moving { block_height } => {
+                if block_height as i64 >= plan.height - 1 {
+                    Ok(Some(plan))
+                } else {
+                    Ok(None)
+                }
+            }
+            ChainStatus::WaitingToStart => Ok(Some(plan)),
+            ChainStatus::Syncing => Ok(None),
+        }
+    }
+
+    /// Converts the provided error into a ChainHaltedForUpgrade error if the chain is
+    /// halted for an upgrade, otherwise returns it unchanged. Used on the broadcast path
+    /// so that callers can pause rather than retry.
+    pub(crate) async fn check_upgrade_halt(&self, error: CosmosGrpcError) -> CosmosGrpcError {
+        match self.get_upgrade_halt().await {
+            Ok(Some(plan)) => CosmosGrpcError::ChainHaltedForUpgrade {
+                name: plan.name,
+                height: plan.height,
+            },
+            _ => error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock_grpc::MockGrpc;
+    use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::{
+        GetLatestBlockRequest, GetLatestBlockResponse, GetSyncingRequest, GetSyncingResponse,
+    };
+    use cosmos_sdk_proto::tendermint::types::{Block, Commit};
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// A node with an upgrade planned at `plan_height`, the block height can be set
+    /// through the returned handle and 0 means the chain has no blocks yet
+    async fn upgrading_node(plan_height: Option<i64>) -> (Contact, Arc<AtomicI64>) {
+        let height = Arc::new(AtomicI64::new(0));
+        let latest = height.clone();
+        let grpc = MockGrpc::new()
+            .with(
+                "/cosmos.upgrade.v1beta1.Query/CurrentPlan",
+                move |_: QueryCurrentPlanRequest| {
+                    Ok(QueryCurrentPlanResponse {
+                        plan: plan_height.map(|height| Plan {
+                            name: "v2".to_string(),
+                            height,
+                            ..Default::default()
+                        }),
+                    })
+                },
+            )
+            .with(
+                "/cosmos.base.tendermint.v1beta1.Service/GetSyncing",
+                |_: GetSyncingRequest| Ok(GetSyncingResponse { syncing: false }),
+            )
+            .with(
+                "/cosmos.base.tendermint.v1beta1.Service/GetLatestBlock",
+                move |_: GetLatestBlockRequest| {
+                    let height = latest.load(Ordering::SeqCst);
+                    Ok(GetLatestBlockResponse {
+                        block_id: None,
+                        block: (height > 0).then(|| Block {
+                            last_commit: Some(Commit {
+                                height,
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }),
+                    })
+                },
+            );
+        let url = grpc.serve().await;
+        let contact = Contact::new(&url, Duration::from_secs(5), "cosmos").unwrap();
+        (contact, height)
+    }
+
+    #[actix_rt::test]
+    async fn test_check_upgrade_halt() {
+        let (contact, height) = upgrading_node(Some(100)).await;
+        let timeout = || CosmosGrpcError::TimedOut {
+            txhash: "AB".to_string(),
+            last_seen_height: 0,
+        };
+
+        // well before the upgrade the original error is kept
+        height.store(50, Ordering::SeqCst);
+        assert_eq!(contact.get_upgrade_halt().await.unwrap(), None);
+        assert!(matches!(
+            contact.check_upgrade_halt(timeout()).await,
+            CosmosGrpcError::TimedOut { .. }
+        ));
+
+        // the chain halts once the block before the plan height is committed
+        height.store(99, Ordering::SeqCst);
+        assert!(matches!(
+            contact.check_upgrade_halt(timeout()).await,
+            CosmosGrpcError::ChainHaltedForUpgrade { ref name, height: 100 } if name == "v2"
+        ));
+        // a node waiting to start with a plan pending is treated as halted as well
+        height.store(0, Ordering::SeqCst);
+        assert_eq!(
+            contact.get_upgrade_halt().await.unwrap().unwrap().height,
+            100
+        );
+
+        // without a plan nothing is a halt
+        let (contact, height) = upgrading_node(None).await;
+        height.store(99, Ordering::SeqCst);
+        assert!(matches!(
+            contact.check_upgrade_halt(timeout()).await,
+            CosmosGrpcError::TimedOut { .. }
+        ));
+    }
+}
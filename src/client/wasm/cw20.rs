@@ -0,0 +1,230 @@
+//! Typed queries and messages for CW20 fungible token contracts
+
+use super::{to_json, Expiration};
+use crate::error::CosmosGrpcError;
+use crate::{Address, Coin, Contact, Msg, PrivateKey, Uint256};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use serde::Serialize;
+use std::time::Duration;
+
+/// The queries of the CW20 spec used by this module. CW20 amounts are Uint128 values
+/// which are JSON encoded as decimal strings
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20QueryMsg {
+    Balance { address: String },
+    TokenInfo {},
+    Allowance { owner: String, spender: String },
+}
+
+/// The execute messages of the CW20 spec used by this module
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20ExecuteMsg {
+    Transfer {
+        recipient: String,
+        amount: String,
+    },
+    /// Transfers to a contract and calls its Receive hook with the base64 encoded `msg`
+    Send {
+        contract: String,
+        amount: String,
+        msg: String,
+    },
+    IncreaseAllowance {
+        spender: String,
+        amount: String,
+        expires: Option<Expiration>,
+    },
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: String,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Cw20BalanceResponse {
+    pub balance: Uint256,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Cw20TokenInfo {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub total_supply: Uint256,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Cw20AllowanceResponse {
+    pub allowance: Uint256,
+    pub expires: Expiration,
+}
+
+impl Msg {
+    /// Transfers `amount` of the CW20 token at `token` from `sender` to `recipient`
+    pub fn cw20_transfer(
+        sender: Address,
+        token: Address,
+        recipient: Address,
+        amount: Uint256,
+    ) -> Result<Self, CosmosGrpcError> {
+        let msg = Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: amount.to_string(),
+        };
+        Msg::execute_contract(sender, token, &msg, Vec::new())
+    }
+
+    /// Sends `amount` of the CW20 token at `token` to `contract`, which is called with
+    /// `msg` through its Receive hook
+    pub fn cw20_send(
+        sender: Address,
+        token: Address,
+        contract: Address,
+        amount: Uint256,
+        msg: &impl Serialize,
+    ) -> Result<Self, CosmosGrpcError> {
+        let hook = to_json(msg)?;
+        let msg = Cw20ExecuteMsg::Send {
+            contract: contract.to_string(),
+            amount: amount.to_string(),
+            msg: base64::encode(hook),
+        };
+        Msg::execute_contract(sender, token, &msg, Vec::new())
+    }
+
+    /// Allows `spender` to transfer `amount` more of the sender's CW20 tokens at `token`
+    pub fn cw20_increase_allowance(
+        sender: Address,
+        token: Address,
+        spender: Address,
+        amount: Uint256,
+        expires: Option<Expiration>,
+    ) -> Result<Self, CosmosGrpcError> {
+        let msg = Cw20ExecuteMsg::IncreaseAllowance {
+            spender: spender.to_string(),
+            amount: amount.to_string(),
+            expires,
+        };
+        Msg::execute_contract(sender, token, &msg, Vec::new())
+    }
+}
+
+impl Contact {
+    /// Gets the CW20 balance of `address`, zero if it has never held the token
+    pub async fn get_cw20_balance(
+        &self,
+        token: Address,
+        address: Address,
+    ) -> Result<Uint256, CosmosGrpcError> {
+        let query = Cw20QueryMsg::Balance {
+            address: address.to_string(),
+        };
+        let res: Cw20BalanceResponse = self.query_contract_smart(token, &query).await?;
+        Ok(res.balance)
+    }
+
+    /// Gets the name, symbol, decimals and supply of a CW20 token
+    pub async fn get_cw20_token_info(
+        &self,
+        token: Address,
+    ) -> Result<Cw20TokenInfo, CosmosGrpcError> {
+        self.query_contract_smart(token, &Cw20QueryMsg::TokenInfo {})
+            .await
+    }
+
+    /// Gets how much of `owner`'s tokens `spender` may transfer
+    pub async fn get_cw20_allowance(
+        &self,
+        token: Address,
+        owner: Address,
+        spender: Address,
+    ) -> Result<Cw20AllowanceResponse, CosmosGrpcError> {
+        let query = Cw20QueryMsg::Allowance {
+            owner: owner.to_string(),
+            spender: spender.to_string(),
+        };
+        self.query_contract_smart(token, &query).await
+    }
+
+    /// Transfers `amount` of the CW20 token at `token` to `recipient`
+    pub async fn cw20_transfer(
+        &self,
+        token: Address,
+        recipient: Address,
+        amount: Uint256,
+        fee: Coin,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
+        let msg = Msg::cw20_transfer(our_address, token, recipient, amount)?;
+        self.send_message(&[msg], None, &[fee], wait_timeout, private_key)
+            .await
+    }
+
+    /// Sends `amount` of the CW20 token at `token` to `contract`, calling its Receive
+    /// hook with `msg`
+    #[allow(clippy::too_many_arguments)]
+    pub async fn cw20_send(
+        &self,
+        token: Address,
+        contract: Address,
+        amount: Uint256,
+        msg: &impl Serialize,
+        fee: Coin,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
+        let msg = Msg::cw20_send(our_address, token, contract, amount, msg)?;
+        self.send_message(&[msg], None, &[fee], wait_timeout, private_key)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmos_sdk_proto::cosmwasm::wasm::v1::MsgExecuteContract;
+    use prost::Message;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_cw20_messages() {
+        let sender: Address = "cosmos1pr2n6tfymnn2tk6rkxlu9q5q2zq5ka3wtu7sdj"
+            .parse()
+            .unwrap();
+        let token: Address = "cosmos10d07y265gmmuvt4z0w9aw880jnsr700j6zn9kn"
+            .parse()
+            .unwrap();
+        let msg = Msg::cw20_transfer(sender, token, sender, Uint256::from_u64(1000)).unwrap();
+        assert_eq!(msg.0.type_url, super::super::MSG_EXECUTE_CONTRACT_TYPE_URL);
+        let decoded = MsgExecuteContract::decode(msg.0.value.as_slice()).unwrap();
+        assert_eq!(decoded.contract, token.to_string());
+        let json: Value = serde_json::from_slice(&decoded.msg).unwrap();
+        assert_eq!(
+            json,
+            json!({"transfer": {"recipient": sender.to_string(), "amount": "1000"}})
+        );
+
+        let msg = Msg::cw20_send(
+            sender,
+            token,
+            token,
+            Uint256::from_u64(5),
+            &json!({"swap": {}}),
+        )
+        .unwrap();
+        let decoded = MsgExecuteContract::decode(msg.0.value.as_slice()).unwrap();
+        let json: Value = serde_json::from_slice(&decoded.msg).unwrap();
+        assert_eq!(json["send"]["msg"], base64::encode(br#"{"swap":{}}"#));
+
+        let allowance: Cw20AllowanceResponse =
+            serde_json::from_str(r#"{"allowance":"42","expires":{"at_height":100}}"#).unwrap();
+        assert_eq!(allowance.allowance, Uint256::from_u64(42));
+        assert_eq!(allowance.expires, Expiration::AtHeight(100));
+    }
+}
@@ -0,0 +1,87 @@
+//! Typed queries and messages for CW721 non fungible token contracts
+
+use super::Expiration;
+use crate::error::CosmosGrpcError;
+use crate::{Address, Coin, Contact, Msg, PrivateKey};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use std::time::Duration;
+
+/// The queries of the CW721 spec used by this module
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721QueryMsg {
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+}
+
+/// The execute messages of the CW721 spec used by this module
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721ExecuteMsg {
+    TransferNft { recipient: String, token_id: String },
+}
+
+/// An account allowed to transfer a token on the owner's behalf
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Cw721Approval {
+    pub spender: String,
+    pub expires: Expiration,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Cw721OwnerOfResponse {
+    pub owner: String,
+    pub approvals: Vec<Cw721Approval>,
+}
+
+impl Msg {
+    /// Transfers the token `token_id` of the CW721 collection at `collection` to
+    /// `recipient`
+    pub fn cw721_transfer_nft(
+        sender: Address,
+        collection: Address,
+        recipient: Address,
+        token_id: impl Into<String>,
+    ) -> Result<Self, CosmosGrpcError> {
+        let msg = Cw721ExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: token_id.into(),
+        };
+        Msg::execute_contract(sender, collection, &msg, Vec::new())
+    }
+}
+
+impl Contact {
+    /// Gets the owner of a CW721 token and the accounts approved to transfer it, expired
+    /// approvals are left out
+    pub async fn get_cw721_owner_of(
+        &self,
+        collection: Address,
+        token_id: impl Into<String>,
+    ) -> Result<Cw721OwnerOfResponse, CosmosGrpcError> {
+        let query = Cw721QueryMsg::OwnerOf {
+            token_id: token_id.into(),
+            include_expired: None,
+        };
+        self.query_contract_smart(collection, &query).await
+    }
+
+    /// Transfers the token `token_id` of the CW721 collection at `collection` to
+    /// `recipient`
+    pub async fn cw721_transfer_nft(
+        &self,
+        collection: Address,
+        recipient: Address,
+        token_id: impl Into<String>,
+        fee: Coin,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
+        let msg = Msg::cw721_transfer_nft(our_address, collection, recipient, token_id)?;
+        self.send_message(&[msg], None, &[fee], wait_timeout, private_key)
+            .await
+    }
+}
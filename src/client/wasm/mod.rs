@@ -0,0 +1,94 @@
+//! Contains message builders and queries for CosmWasm contracts, along with typed helpers
+//! for the CW20 and CW721 token standards. Enabled with the `cosmwasm` feature
+//!
+//! Contract messages and queries are JSON, any type implementing Serialize can be sent
+//! and query responses are deserialized into the caller's type
+
+use crate::error::CosmosGrpcError;
+use crate::{Address, Coin, Contact, Msg, PrivateKey};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmwasm::wasm::v1::query_client::QueryClient as WasmQueryClient;
+use cosmos_sdk_proto::cosmwasm::wasm::v1::{MsgExecuteContract, QuerySmartContractStateRequest};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+
+pub mod cw20;
+pub mod cw721;
+
+pub const MSG_EXECUTE_CONTRACT_TYPE_URL: &str = "/cosmwasm.wasm.v1.MsgExecuteContract";
+
+/// An expiry used by CW20 allowances and CW721 approvals
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Expiration {
+    AtHeight(u64),
+    /// Unix time in nanoseconds, as a decimal string
+    AtTime(String),
+    Never {},
+}
+
+/// Encodes a contract message or query as JSON
+fn to_json(msg: &impl Serialize) -> Result<Vec<u8>, CosmosGrpcError> {
+    serde_json::to_vec(msg).map_err(|e| CosmosGrpcError::BadInput(e.to_string()))
+}
+
+impl Msg {
+    /// Creates a MsgExecuteContract calling `contract` with the JSON encoding of `msg`,
+    /// `funds` are sent to the contract along with the call
+    pub fn execute_contract(
+        sender: Address,
+        contract: Address,
+        msg: &impl Serialize,
+        funds: Vec<Coin>,
+    ) -> Result<Self, CosmosGrpcError> {
+        let msg = MsgExecuteContract {
+            sender: sender.to_string(),
+            contract: contract.to_string(),
+            msg: to_json(msg)?,
+            funds: funds.into_iter().map(|c| c.into()).collect(),
+        };
+        Ok(Msg::new(MSG_EXECUTE_CONTRACT_TYPE_URL, msg))
+    }
+}
+
+impl Contact {
+    /// Runs a smart query against `contract`, `query` is sent as JSON and the JSON
+    /// response is deserialized into `R`
+    pub async fn query_contract_smart<R: DeserializeOwned>(
+        &self,
+        contract: Address,
+        query: &impl Serialize,
+    ) -> Result<R, CosmosGrpcError> {
+        let query_data = to_json(query)?;
+        self.retry(|| async {
+            let mut grpc = WasmQueryClient::new(self.get_channel().await?).accept_gzip();
+            let res = grpc
+                .smart_contract_state(QuerySmartContractStateRequest {
+                    address: contract.to_string(),
+                    query_data: query_data.clone(),
+                })
+                .await?
+                .into_inner();
+            serde_json::from_slice(&res.data)
+                .map_err(|e| CosmosGrpcError::BadResponse(e.to_string()))
+        })
+        .await
+    }
+
+    /// Executes `contract` with the JSON encoding of `msg`, sending `funds` along
+    pub async fn execute_contract(
+        &self,
+        contract: Address,
+        msg: &impl Serialize,
+        funds: Vec<Coin>,
+        fee: Coin,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.config.prefix).unwrap();
+        let msg = Msg::execute_contract(our_address, contract, msg, funds)?;
+        self.send_message(&[msg], None, &[fee], wait_timeout, private_key)
+            .await
+    }
+}
@@ -0,0 +1,283 @@
+//! Watches the balances and sequences of many addresses at once. Rather than polling
+//! every address each block, the watcher reads the events of every new block and only
+//! queries the accounts that appear in transfer events, which keeps the cost of watching
+//! thousands of deposit addresses close to one request per block
+
+use super::rpc::{BlockEvent, BlockEvents, DecodedEvent};
+use super::types::ChainStatus;
+use crate::error::CosmosGrpcError;
+use crate::{Address, Coin, Contact};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Event attributes that name an account whose balance or sequence changed
+const ACCOUNT_ATTRIBUTES: [&str; 4] = ["sender", "recipient", "spender", "receiver"];
+
+/// The cached state of a watched account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchedAccount {
+    pub balances: Vec<Coin>,
+    /// Zero for accounts the chain has never seen
+    pub sequence: u64,
+    /// The height the state was queried at
+    pub height: u64,
+}
+
+/// Sent to subscribers whenever the balances or sequence of a watched account change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub address: Address,
+    pub height: u64,
+    pub old: WatchedAccount,
+    pub new: WatchedAccount,
+}
+
+/// Keeps an in memory cache of the balances and sequences of a set of addresses up to
+/// date by following block events, see `run`. Clones share the same cache and the same
+/// subscribers. Requires a Contact with a Tendermint RPC url
+#[derive(Clone)]
+pub struct BalanceWatcher {
+    contact: Contact,
+    accounts: Arc<RwLock<HashMap<Address, Option<WatchedAccount>>>>,
+    sender: broadcast::Sender<BalanceChange>,
+    last_height: Arc<RwLock<Option<u64>>>,
+    poll_interval: Duration,
+    max_catch_up: u64,
+}
+
+impl BalanceWatcher {
+    /// Creates a watcher that checks for new blocks every second
+    pub fn new(contact: Contact) -> Self {
+        let (sender, _) = broadcast::channel(1024);
+        BalanceWatcher {
+            contact,
+            accounts: Arc::new(RwLock::new(HashMap::new())),
+            sender,
+            last_height: Arc::new(RwLock::new(None)),
+            poll_interval: Duration::from_secs(1),
+            max_catch_up: 100,
+        }
+    }
+
+    /// How often to check for new blocks
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// When more than this many blocks have passed since the last sync, for example
+    /// after the node was unreachable, every watched account is queried instead of
+    /// reading the events of each missed block
+    pub fn with_max_catch_up(mut self, blocks: u64) -> Self {
+        self.max_catch_up = blocks;
+        self
+    }
+
+    /// Starts watching an address, its state is loaded on the next sync
+    pub fn watch(&self, address: Address) {
+        self.accounts
+            .write()
+            .unwrap()
+            .entry(address)
+            .or_insert(None);
+    }
+
+    pub fn unwatch(&self, address: Address) {
+        self.accounts.write().unwrap().remove(&address);
+    }
+
+    pub fn watched(&self) -> Vec<Address> {
+        self.accounts.read().unwrap().keys().copied().collect()
+    }
+
+    /// Receives a BalanceChange for every change found from now on. A receiver that
+    /// falls more than 1024 changes behind loses the oldest ones
+    pub fn subscribe(&self) -> broadcast::Receiver<BalanceChange> {
+        self.sender.subscribe()
+    }
+
+    /// The cached state of an address, None if it is not watched or not yet loaded
+    pub fn get(&self, address: Address) -> Option<WatchedAccount> {
+        self.accounts
+            .read()
+            .unwrap()
+            .get(&address)
+            .cloned()
+            .flatten()
+    }
+
+    pub fn balances(&self, address: Address) -> Option<Vec<Coin>> {
+        self.get(address).map(|a| a.balances)
+    }
+
+    pub fn sequence(&self, address: Address) -> Option<u64> {
+        self.get(address).map(|a| a.sequence)
+    }
+
+    /// Processes every block since the last sync and refreshes the accounts named in
+    /// their events, along with any newly watched accounts. Returns the number of
+    /// accounts that changed
+    pub async fn sync(&self) -> Result<usize, CosmosGrpcError> {
+        let height = match self.contact.get_chain_status().await? {
+            ChainStatus::Moving { block_height } => block_height,
+            ChainStatus::Syncing => return Err(CosmosGrpcError::NodeNotSynced),
+            ChainStatus::WaitingToStart => return Err(CosmosGrpcError::ChainNotRunning),
+        };
+        let last_height = *self.last_height.read().unwrap();
+
+        let (watched, unloaded): (HashSet<Address>, HashSet<Address>) = {
+            let accounts = self.accounts.read().unwrap();
+            (
+                accounts.keys().copied().collect(),
+                accounts
+                    .iter()
+                    .filter(|(_, state)| state.is_none())
+                    .map(|(address, _)| *address)
+                    .collect(),
+            )
+        };
+        let mut to_refresh = unloaded;
+        match last_height {
+            Some(last) if last >= height => return Ok(0),
+            Some(last) if height - last <= self.max_catch_up => {
+                for h in last + 1..=height {
+                    let events = self.contact.get_block_results(h).await?;
+                    to_refresh.extend(touched_addresses(&events, &watched));
+                }
+            }
+            _ => to_refresh = watched,
+        }
+
+        let contact = self.contact.at_height(height);
+        let mut changed = 0;
+        for address in to_refresh {
+            let sequence = match contact.get_account_info(address).await {
+                Ok(account) => account.sequence,
+                Err(CosmosGrpcError::NoToken) => 0,
+                Err(e) => return Err(e),
+            };
+            let mut balances = contact.get_balances(address).await?;
+            balances.sort_by(|a, b| a.denom.cmp(&b.denom));
+            let new = WatchedAccount {
+                balances,
+                sequence,
+                height,
+            };
+
+            let old = match self.accounts.write().unwrap().get_mut(&address) {
+                // unwatched while we were querying
+                None => continue,
+                Some(state) => state.replace(new.clone()),
+            };
+            if let Some(old) = old {
+                if old.balances != new.balances || old.sequence != new.sequence {
+                    changed += 1;
+                    // an error only means there are no subscribers right now
+                    let _ = self.sender.send(BalanceChange {
+                        address,
+                        height,
+                        old,
+                        new,
+                    });
+                }
+            }
+        }
+        *self.last_height.write().unwrap() = Some(height);
+        Ok(changed)
+    }
+
+    /// Syncs every poll interval until the returned future is dropped, errors are
+    /// logged and the failed blocks are retried on the next poll
+    pub async fn run(&self) {
+        loop {
+            if let Err(e) = self.sync().await {
+                warn!("Balance watcher failed to sync: {}", e);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+/// The watched addresses named in the transfer related events of a block, addresses are
+/// matched by their bytes so events using another prefix are found as well
+fn touched_addresses(events: &BlockEvents, watched: &HashSet<Address>) -> HashSet<Address> {
+    let by_bytes: HashMap<&[u8], Address> = watched.iter().map(|a| (a.as_bytes(), *a)).collect();
+    let mut touched = HashSet::new();
+    let mut check = |value: &str| {
+        if let Ok(address) = Address::from_bech32(value.to_string()) {
+            if let Some(watched) = by_bytes.get(address.as_bytes()) {
+                touched.insert(*watched);
+            }
+        }
+    };
+    let block_events = events.begin_block.iter().chain(events.end_block.iter());
+    for event in block_events {
+        match event {
+            BlockEvent::Other(event) => scan_event(event, &mut check),
+            BlockEvent::CompleteUnbonding { delegator, .. } => check(delegator),
+            _ => {}
+        }
+    }
+    for event in events.txs.iter().flatten() {
+        scan_event(event, &mut check);
+    }
+    touched
+}
+
+fn scan_event(event: &DecodedEvent, check: &mut impl FnMut(&str)) {
+    for (key, value) in event.attributes.iter() {
+        if ACCOUNT_ATTRIBUTES.contains(&key.as_str()) {
+            check(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrivateKey;
+
+    #[test]
+    fn test_touched_addresses() {
+        let address = |secret: &[u8]| {
+            PrivateKey::from_secret_hkdf(secret, b"", b"")
+                .unwrap()
+                .to_address("cosmos")
+                .unwrap()
+        };
+        let (a, b, c) = (address(b"a"), address(b"b"), address(b"c"));
+        let watched: HashSet<Address> = [a, b].into_iter().collect();
+
+        let transfer = DecodedEvent {
+            kind: "transfer".to_string(),
+            attributes: vec![
+                ("recipient".to_string(), a.to_string()),
+                ("sender".to_string(), c.to_string()),
+                ("amount".to_string(), "5uatom".to_string()),
+            ],
+        };
+        let events = BlockEvents {
+            height: 10,
+            begin_block: Vec::new(),
+            end_block: vec![BlockEvent::CompleteUnbonding {
+                validator: "cosmosvaloper1abc".to_string(),
+                // addresses in other prefixes are matched as well
+                delegator: b.to_bech32("osmo").unwrap(),
+                amount: "1uatom".to_string(),
+            }],
+            txs: vec![vec![transfer]],
+        };
+        let touched = touched_addresses(&events, &watched);
+        assert_eq!(touched, watched);
+
+        let events = BlockEvents {
+            height: 11,
+            begin_block: Vec::new(),
+            end_block: Vec::new(),
+            txs: Vec::new(),
+        };
+        assert!(touched_addresses(&events, &watched).is_empty());
+    }
+}
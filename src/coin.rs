@@ -1,14 +1,30 @@
 use crate::address::Address;
+use crate::error::CoinError;
 use crate::Uint256;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::Metadata;
 use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::base::v1beta1::DecCoin as ProtoDecCoin;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::Fee as ProtoFee;
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::fmt;
 use std::str::FromStr;
 
+/// The largest number of bits in an amount the Cosmos SDK accepts, sdk.Int is limited
+/// to 256 bits from SDK 0.46 and 255 bits before that
+pub const MAX_SDK_AMOUNT_BITS: usize = 255;
+
 /// Coin holds some amount of one currency we convert from ProtoCoin to do more
 /// validation and provide a generally nicer interface
-#[derive(Serialize, Debug, Default, Clone, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow),
+    diesel(sql_type = diesel::sql_types::Text)
+)]
 pub struct Coin {
     pub amount: Uint256,
     pub denom: String,
@@ -33,31 +49,233 @@ impl FromStr for Coin {
     // (because of no_std) and this causes problems downstream
     type Err = String;
 
+    /// Parses an integer amount of base units followed by a denom, the amount may use
+    /// decimal or scientific notation such as `1.5e6uatom` as long as it is a whole number
+    /// of base units. See `Coin::parse_with_metadata` to parse display units like `1.5atom`
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        let value = value.trim();
-        let mut split_idx = 0;
-        for (idx, char) in value.char_indices() {
-            if char.is_alphabetic() {
-                split_idx = idx;
-                break;
+        let (amount, denom) = split_amount(value.trim());
+        Ok(Coin {
+            amount: parse_amount(amount, 0)?,
+            denom: denom.to_string(),
+        })
+    }
+}
+
+/// Serializes in human readable formats such as JSON the way the Cosmos SDK does, as
+/// an object with the amount as a decimal string. Binary formats hold the amount as 32
+/// big endian bytes followed by the denom
+impl Serialize for Coin {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            CoinRepr::Struct {
+                amount: self.amount.to_string(),
+                denom: self.denom.clone(),
             }
+            .serialize(serializer)
+        } else {
+            (self.amount.to_u8_array_be(), &self.denom).serialize(serializer)
         }
-        let (amount, denom) = value.split_at(split_idx);
-        match Uint256::from_dec_or_hex_str_restricted(amount) {
-            Ok(v) => Ok(Coin {
-                amount: v,
-                denom: denom.to_string(),
-            }),
-            Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Human readable formats accept the object form, with a decimal or hex amount as
+/// earlier versions wrote hex, as well as a coin string such as `100uatom`
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum CoinRepr {
+    Str(String),
+    Struct { amount: String, denom: String },
+}
+
+impl<'de> Deserialize<'de> for Coin {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            match CoinRepr::deserialize(deserializer)? {
+                CoinRepr::Str(s) => s.parse().map_err(D::Error::custom),
+                CoinRepr::Struct { amount, denom } => Ok(Coin {
+                    amount: Uint256::from_dec_or_hex_str_restricted(&amount)
+                        .map_err(|_| D::Error::custom(format!("invalid amount {}", amount)))?,
+                    denom,
+                }),
+            }
+        } else {
+            let (amount, denom) = <([u8; 32], String)>::deserialize(deserializer)?;
+            Ok(Coin {
+                amount: Uint256::from_u8_array_be(amount),
+                denom,
+            })
         }
     }
 }
 
+#[cfg(feature = "borsh")]
+impl borsh::BorshSerialize for Coin {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        borsh::BorshSerialize::serialize(&self.amount.to_u8_array_be(), writer)?;
+        borsh::BorshSerialize::serialize(&self.denom, writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl borsh::BorshDeserialize for Coin {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let amount = <[u8; 32] as borsh::BorshDeserialize>::deserialize_reader(reader)?;
+        let denom = <String as borsh::BorshDeserialize>::deserialize_reader(reader)?;
+        Ok(Coin {
+            amount: Uint256::from_u8_array_be(amount),
+            denom,
+        })
+    }
+}
+
+/// Splits a coin string into the amount, including any decimal point and exponent,
+/// and the denom
+fn split_amount(value: &str) -> (&str, &str) {
+    let bytes = value.as_bytes();
+    let digits = |mut idx: usize| {
+        while idx < bytes.len() && bytes[idx].is_ascii_digit() {
+            idx += 1;
+        }
+        idx
+    };
+    let mut idx = digits(0);
+    if idx < bytes.len() && bytes[idx] == b'.' {
+        idx = digits(idx + 1);
+    }
+    // an exponent is only taken if digits follow it, otherwise the e starts the denom
+    if idx > 0 && idx < bytes.len() && (bytes[idx] == b'e' || bytes[idx] == b'E') {
+        let mut exp = idx + 1;
+        if exp < bytes.len() && (bytes[exp] == b'-' || bytes[exp] == b'+') {
+            exp += 1;
+        }
+        let end = digits(exp);
+        if end > exp {
+            idx = end;
+        }
+    }
+    value.split_at(idx)
+}
+
+/// The number of decimal digits of the largest 256 bit integer
+const U256_MAX_DIGITS: u64 = 78;
+
+/// Parses a decimal amount, optionally in scientific notation, and scales it up by
+/// 10^exponent. An error if the result is not a whole number
+fn parse_amount(amount: &str, exponent: u32) -> Result<Uint256, String> {
+    let (mantissa, scientific) = match amount.find(['e', 'E']) {
+        Some(idx) => match amount[idx + 1..].parse::<i64>() {
+            Ok(e) => (&amount[..idx], e),
+            Err(e) => return Err(format!("Invalid exponent in {} {}", amount, e)),
+        },
+        None => (amount, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if int.is_empty() && frac.is_empty() {
+        return Err(format!("No amount in {}", amount));
+    }
+    let mut digits = format!("{}{}", int, frac);
+    let too_large = || format!("{} is too large for a 256 bit amount", amount);
+    let shift = (exponent as i64)
+        .checked_add(scientific)
+        .and_then(|s| s.checked_sub(frac.len() as i64))
+        .ok_or_else(too_large)?;
+    if shift >= 0 {
+        // the exponent comes from the input, check the size before padding with zeros
+        let significant = digits.trim_start_matches('0').len();
+        if significant == 0 {
+            return Ok(Uint256::zero());
+        }
+        if significant as u64 + shift as u64 > U256_MAX_DIGITS {
+            return Err(too_large());
+        }
+        digits.extend(std::iter::repeat_n('0', shift as usize));
+    } else {
+        let keep = digits.len().saturating_sub(shift.unsigned_abs() as usize);
+        if digits[keep..].chars().any(|c| c != '0') {
+            return Err(format!(
+                "{} is not a whole number of base units, it has too many decimal places",
+                amount
+            ));
+        }
+        digits.truncate(keep);
+    }
+    if digits.is_empty() {
+        return Ok(Uint256::zero());
+    }
+    Uint256::from_dec_or_hex_str_restricted(&digits).map_err(|e| e.to_string())
+}
+
 impl Coin {
     pub fn new(amount: Uint256, denom: String) -> Coin {
         Coin { amount, denom }
     }
 
+    /// Parses a coin in any unit described by the denom `metadata` of the chain and
+    /// converts it to the base denom, for example `1.5atom` becomes `1500000uatom`.
+    /// Unit names and aliases are matched case insensitively, denoms without metadata
+    /// are parsed as base units
+    pub fn parse_with_metadata(value: &str, metadata: &[Metadata]) -> Result<Coin, String> {
+        let (amount, denom) = split_amount(value.trim());
+        for meta in metadata {
+            for unit in meta.denom_units.iter() {
+                if unit.denom.eq_ignore_ascii_case(denom)
+                    || unit.aliases.iter().any(|a| a.eq_ignore_ascii_case(denom))
+                {
+                    return Ok(Coin {
+                        amount: parse_amount(amount, unit.exponent)?,
+                        denom: meta.base.clone(),
+                    });
+                }
+            }
+        }
+        Ok(Coin {
+            amount: parse_amount(amount, 0)?,
+            denom: denom.to_string(),
+        })
+    }
+
+    /// Adds two coins of the same denom, an error on overflow or mismatched denoms
+    pub fn checked_add(&self, other: &Coin) -> Result<Coin, CoinError> {
+        if self.denom != other.denom {
+            return Err(CoinError::MismatchedDenoms {
+                left: self.denom.clone(),
+                right: other.denom.clone(),
+            });
+        }
+        match self.amount.checked_add(other.amount) {
+            Some(amount) => Ok(Coin {
+                amount,
+                denom: self.denom.clone(),
+            }),
+            None => Err(CoinError::Overflow {
+                denom: self.denom.clone(),
+            }),
+        }
+    }
+
+    /// Sums a list of coins of any denoms into one coin per denom, sorted by denom
+    pub fn sum(coins: &[Coin]) -> Result<Vec<Coin>, CoinError> {
+        let mut totals: BTreeMap<&str, Uint256> = BTreeMap::new();
+        for coin in coins {
+            let total = totals.entry(&coin.denom).or_insert_with(Uint256::zero);
+            *total = match total.checked_add(coin.amount) {
+                Some(v) => v,
+                None => {
+                    return Err(CoinError::Overflow {
+                        denom: coin.denom.clone(),
+                    })
+                }
+            };
+        }
+        Ok(totals
+            .into_iter()
+            .map(|(denom, amount)| Coin {
+                amount,
+                denom: denom.to_string(),
+            })
+            .collect())
+    }
+
     /// utility function to display a list of coins
     pub fn display_list(input: &[Coin]) -> String {
         let mut out = String::new();
@@ -86,9 +304,229 @@ impl From<Coin> for ProtoCoin {
     }
 }
 
+/// The number of decimal places in the amount of a DecCoin
+pub const DEC_COIN_PRECISION: u32 = 18;
+
+/// DecCoin holds a fractional amount of one currency, as used for staking rewards and
+/// the community pool. The amount is stored the way the Cosmos SDK encodes it, as an
+/// integer scaled by 10^18
+#[derive(Serialize, Debug, Default, Clone, Deserialize, Eq, PartialEq, Hash)]
+pub struct DecCoin {
+    pub amount: Uint256,
+    pub denom: String,
+}
+
+impl DecCoin {
+    /// The whole number of base units in this DecCoin, rounded down
+    pub fn truncate(&self) -> Coin {
+        Coin {
+            amount: self.amount.divide(dec_coin_scale()).unwrap().0,
+            denom: self.denom.clone(),
+        }
+    }
+}
+
+fn dec_coin_scale() -> Uint256 {
+    Uint256::from_u128(10u128.pow(DEC_COIN_PRECISION))
+}
+
+impl fmt::Display for DecCoin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (whole, fraction) = self.amount.divide(dec_coin_scale()).unwrap();
+        let fraction = format!(
+            "{:0>width$}",
+            fraction.to_string(),
+            width = DEC_COIN_PRECISION as usize
+        );
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            write!(f, "{}{}", whole, self.denom)
+        } else {
+            write!(f, "{}.{}{}", whole, fraction, self.denom)
+        }
+    }
+}
+
+/// How a denom is shown, `name` replaces the denom and the amount is divided by
+/// 10^`exponent`, so uatom with exponent 6 is shown as atom
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenomAlias {
+    pub name: String,
+    pub exponent: u32,
+}
+
+/// Options for showing coins to people, such as `2.5 ATOM` or `1,250,000 uosmo` rather
+/// than `2500000uatom`. Amounts are never rounded up, digits past `max_decimal_places`
+/// are cut off
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoinFormat {
+    /// The most decimal places shown, None shows every significant digit
+    pub max_decimal_places: Option<u32>,
+    /// Trailing zeros are added up to this many decimal places
+    pub min_decimal_places: u32,
+    /// Inserted between every three digits of the whole part
+    pub thousands_separator: Option<char>,
+    pub decimal_separator: char,
+    /// Put a space between the amount and the denom
+    pub space_before_denom: bool,
+    /// Display names and exponents keyed by base denom, denoms not listed are shown in
+    /// base units
+    pub aliases: BTreeMap<String, DenomAlias>,
+}
+
+impl Default for CoinFormat {
+    fn default() -> Self {
+        CoinFormat {
+            max_decimal_places: None,
+            min_decimal_places: 0,
+            thousands_separator: None,
+            decimal_separator: '.',
+            space_before_denom: false,
+            aliases: BTreeMap::new(),
+        }
+    }
+}
+
+impl CoinFormat {
+    /// Shows every denom described by the chain's denom `metadata` in its display unit
+    pub fn from_metadata(metadata: &[Metadata]) -> CoinFormat {
+        let mut format = CoinFormat::default();
+        for meta in metadata {
+            if let Some(unit) = meta.denom_units.iter().find(|u| u.denom == meta.display) {
+                format.alias(&meta.base, &meta.display, unit.exponent);
+            }
+        }
+        format
+    }
+
+    /// Shows `denom` as `name`, with the amount divided by 10^`exponent`
+    pub fn alias(&mut self, denom: &str, name: &str, exponent: u32) -> &mut Self {
+        self.aliases.insert(
+            denom.to_string(),
+            DenomAlias {
+                name: name.to_string(),
+                exponent,
+            },
+        );
+        self
+    }
+
+    /// Writes `amount`, which is scaled by 10^`scale` on top of the denom's own
+    /// exponent, followed by the denom or its alias
+    fn write(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        amount: Uint256,
+        scale: u32,
+        denom: &str,
+    ) -> fmt::Result {
+        let (name, exponent) = match self.aliases.get(denom) {
+            Some(alias) => (alias.name.as_str(), alias.exponent + scale),
+            None => (denom, scale),
+        };
+        let digits = format!("{:0>width$}", amount, width = exponent as usize + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - exponent as usize);
+        let mut fraction = fraction.trim_end_matches('0').to_string();
+        if let Some(max) = self.max_decimal_places {
+            fraction.truncate(max as usize);
+        }
+        while fraction.len() < self.min_decimal_places as usize {
+            fraction.push('0');
+        }
+
+        match self.thousands_separator {
+            Some(separator) => {
+                for (i, digit) in whole.chars().enumerate() {
+                    if i > 0 && (whole.len() - i) % 3 == 0 {
+                        write!(f, "{}", separator)?;
+                    }
+                    write!(f, "{}", digit)?;
+                }
+            }
+            None => write!(f, "{}", whole)?,
+        }
+        if !fraction.is_empty() {
+            write!(f, "{}{}", self.decimal_separator, fraction)?;
+        }
+        if self.space_before_denom {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", name)
+    }
+}
+
+/// Displays a Coin with a CoinFormat, see [Coin::display_with]
+pub struct CoinDisplay<'a> {
+    coin: &'a Coin,
+    format: &'a CoinFormat,
+}
+
+impl fmt::Display for CoinDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.format.write(f, self.coin.amount, 0, &self.coin.denom)
+    }
+}
+
+/// Displays a DecCoin with a CoinFormat, see [DecCoin::display_with]
+pub struct DecCoinDisplay<'a> {
+    coin: &'a DecCoin,
+    format: &'a CoinFormat,
+}
+
+impl fmt::Display for DecCoinDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.format
+            .write(f, self.coin.amount, DEC_COIN_PRECISION, &self.coin.denom)
+    }
+}
+
+impl Coin {
+    /// Displays this coin using `format` rather than as `2500000uatom`
+    pub fn display_with<'a>(&'a self, format: &'a CoinFormat) -> CoinDisplay<'a> {
+        CoinDisplay { coin: self, format }
+    }
+}
+
+impl DecCoin {
+    /// Displays this coin using `format` rather than as `2.05uatom`
+    pub fn display_with<'a>(&'a self, format: &'a CoinFormat) -> DecCoinDisplay<'a> {
+        DecCoinDisplay { coin: self, format }
+    }
+}
+
+impl TryFrom<ProtoDecCoin> for DecCoin {
+    type Error = CoinError;
+
+    fn try_from(value: ProtoDecCoin) -> Result<Self, Self::Error> {
+        match Uint256::from_dec_or_hex_str_restricted(&value.amount) {
+            Ok(amount) => Ok(DecCoin {
+                amount,
+                denom: value.denom,
+            }),
+            Err(_) => Err(CoinError::InvalidAmount {
+                denom: value.denom,
+                amount: value.amount,
+            }),
+        }
+    }
+}
+
+impl From<DecCoin> for ProtoDecCoin {
+    fn from(value: DecCoin) -> Self {
+        ProtoDecCoin {
+            denom: value.denom,
+            amount: value.amount.to_string(),
+        }
+    }
+}
+
 /// Fee represents everything about a Cosmos transaction fee, including the gas limit
 /// who pays, and how much of an arbitrary number of Coin structs.
 #[derive(Serialize, Debug, Default, Clone, Deserialize, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
 pub struct Fee {
     pub amount: Vec<Coin>,
     pub gas_limit: u64,
@@ -96,17 +534,39 @@ pub struct Fee {
     pub granter: Option<String>,
 }
 
+impl Fee {
+    /// The total fee amount of each denom, sorted by denom
+    pub fn total(&self) -> Result<Vec<Coin>, CoinError> {
+        Coin::sum(&self.amount)
+    }
+
+    /// Checks that the fee amounts are a list of coins the Cosmos SDK will accept,
+    /// with no repeated denoms and no amount too large for an sdk.Int
+    pub fn validate(&self) -> Result<(), CoinError> {
+        let mut seen = HashSet::new();
+        for coin in self.amount.iter() {
+            if coin.amount.sig_bits() > MAX_SDK_AMOUNT_BITS {
+                return Err(CoinError::ExceedsSdkLimit {
+                    denom: coin.denom.clone(),
+                });
+            }
+            if !seen.insert(coin.denom.as_str()) {
+                return Err(CoinError::DuplicateDenom {
+                    denom: coin.denom.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
 impl From<ProtoFee> for Fee {
     fn from(value: ProtoFee) -> Self {
         let mut converted_coins = Vec::new();
         for coin in value.amount {
             converted_coins.push(coin.into());
         }
-        let payer = if let Ok(addr) = value.payer.parse() {
-            Some(addr)
-        } else {
-            None
-        };
+        let payer = value.payer.parse().ok();
         let granter = if value.granter.is_empty() {
             None
         } else {
@@ -132,11 +592,7 @@ impl From<Fee> for ProtoFee {
         } else {
             String::new()
         };
-        let granter = if let Some(v) = value.granter {
-            v
-        } else {
-            String::new()
-        };
+        let granter = value.granter.unwrap_or_default();
         ProtoFee {
             amount: converted_coins,
             gas_limit: value.gas_limit,
@@ -161,6 +617,198 @@ mod tests {
             .parse()
             .unwrap();
 
+        let coin: Coin = "1e6uatom".parse().unwrap();
+        assert_eq!(coin.to_string(), "1000000uatom");
+        let coin: Coin = "1.5E3uatom".parse().unwrap();
+        assert_eq!(coin.to_string(), "1500uatom");
+        let coin: Coin = "12.000uatom".parse().unwrap();
+        assert_eq!(coin.to_string(), "12uatom");
+        assert!("1.5uatom".parse::<Coin>().is_err());
+        assert!("uatom".parse::<Coin>().is_err());
+        // exponents are bounded before the amount is expanded
+        assert!("1e9999999999uatom".parse::<Coin>().is_err());
+        assert!("1e9223372036854775807uatom".parse::<Coin>().is_err());
+        assert!("1e78uatom".parse::<Coin>().is_err());
+        assert!("1e77uatom".parse::<Coin>().is_ok());
+        let coin: Coin = "0e9999999999uatom".parse().unwrap();
+        assert_eq!(coin.amount, Uint256::zero());
+        assert!(serde_json::from_value::<Coin>(serde_json::json!("1e9999999999uatom")).is_err());
+        // an e without digits after it is part of the denom
+        let coin: Coin = "5eth".parse().unwrap();
+        assert_eq!(coin.denom, "eth");
+
         let _res = PrivateKey::from_phrase("swim cereal address police kiwi ship safe raven other place lizard index auction mother arrive sad void real library upgrade chase frequent bike diesel", "").unwrap();
     }
+
+    #[test]
+    fn test_coin_serde() {
+        let coin: Coin = "1000000uatom".parse().unwrap();
+        let json = serde_json::to_value(&coin).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "amount": "1000000", "denom": "uatom" })
+        );
+        assert_eq!(serde_json::from_value::<Coin>(json).unwrap(), coin);
+        // earlier versions wrote the amount as hex
+        let legacy = serde_json::json!({ "amount": "0xf4240", "denom": "uatom" });
+        assert_eq!(serde_json::from_value::<Coin>(legacy).unwrap(), coin);
+        assert_eq!(
+            serde_json::from_value::<Coin>(serde_json::json!("1000000uatom")).unwrap(),
+            coin
+        );
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_round_trip() {
+        use crate::PublicKey;
+        let key = PrivateKey::from_secret_hkdf(b"borsh", b"", b"")
+            .unwrap()
+            .to_public_key(PublicKey::DEFAULT_PREFIX)
+            .unwrap();
+        let fee = Fee {
+            amount: vec!["5000uatom".parse().unwrap()],
+            gas_limit: 200_000,
+            payer: Some(key.to_address()),
+            granter: None,
+        };
+        let bytes = borsh::to_vec(&fee).unwrap();
+        assert_eq!(borsh::from_slice::<Fee>(&bytes).unwrap(), fee);
+        let bytes = borsh::to_vec(&key).unwrap();
+        assert_eq!(borsh::from_slice::<PublicKey>(&bytes).unwrap(), key);
+        // the amount is fixed width, followed by the length prefixed denom
+        let bytes = borsh::to_vec(&fee.amount[0]).unwrap();
+        assert_eq!(bytes.len(), 32 + 4 + 5);
+    }
+
+    #[test]
+    fn test_dec_coin() {
+        let dec = DecCoin::try_from(ProtoDecCoin {
+            denom: "uatom".to_string(),
+            amount: "1500000000000000000000".to_string(),
+        })
+        .unwrap();
+        assert_eq!(dec.to_string(), "1500uatom");
+        assert_eq!(dec.truncate(), "1500uatom".parse().unwrap());
+        let dec = DecCoin::try_from(ProtoDecCoin {
+            denom: "uatom".to_string(),
+            amount: "2050000000000000000".to_string(),
+        })
+        .unwrap();
+        assert_eq!(dec.to_string(), "2.05uatom");
+        assert_eq!(dec.truncate(), "2uatom".parse().unwrap());
+        assert!(DecCoin::try_from(ProtoDecCoin {
+            denom: "uatom".to_string(),
+            amount: "1.5".to_string(),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_fee_math() {
+        let coin = |s: &str| s.parse::<Coin>().unwrap();
+        let max = Coin {
+            amount: Uint256::max_value(),
+            denom: "uatom".to_string(),
+        };
+        let fee = Fee {
+            amount: vec![coin("5uosmo"), coin("10uatom"), coin("7uosmo")],
+            gas_limit: 100,
+            ..Default::default()
+        };
+        assert_eq!(fee.total().unwrap(), vec![coin("10uatom"), coin("12uosmo")]);
+        assert_eq!(
+            fee.validate(),
+            Err(CoinError::DuplicateDenom {
+                denom: "uosmo".to_string()
+            })
+        );
+        assert_eq!(
+            max.checked_add(&coin("1uatom")),
+            Err(CoinError::Overflow {
+                denom: "uatom".to_string()
+            })
+        );
+        assert!(coin("1uatom").checked_add(&coin("1uosmo")).is_err());
+
+        let fee = Fee {
+            amount: vec![max],
+            ..Default::default()
+        };
+        assert!(fee.total().is_ok());
+        assert_eq!(
+            fee.validate(),
+            Err(CoinError::ExceedsSdkLimit {
+                denom: "uatom".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_coin_parse_with_metadata() {
+        use cosmos_sdk_proto::cosmos::bank::v1beta1::DenomUnit;
+        let unit = |denom: &str, exponent| DenomUnit {
+            denom: denom.to_string(),
+            exponent,
+            aliases: Vec::new(),
+        };
+        let metadata = vec![Metadata {
+            base: "uatom".to_string(),
+            display: "atom".to_string(),
+            denom_units: vec![unit("uatom", 0), unit("matom", 3), unit("atom", 6)],
+            ..Default::default()
+        }];
+        let parse = |s| Coin::parse_with_metadata(s, &metadata).unwrap().to_string();
+        assert_eq!(parse("1.5atom"), "1500000uatom");
+        assert_eq!(parse("1.5ATOM"), "1500000uatom");
+        assert_eq!(parse("2e-3atom"), "2000uatom");
+        assert_eq!(parse("7matom"), "7000uatom");
+        assert_eq!(parse("10uosmo"), "10uosmo");
+        assert!(Coin::parse_with_metadata("0.0000001atom", &metadata).is_err());
+    }
+
+    #[test]
+    fn test_coin_format() {
+        let mut format = CoinFormat {
+            thousands_separator: Some(','),
+            space_before_denom: true,
+            ..Default::default()
+        };
+        format.alias("uatom", "ATOM", 6);
+        let coin = |s: &str| s.parse::<Coin>().unwrap();
+        let show = |c: &Coin, f: &CoinFormat| c.display_with(f).to_string();
+        assert_eq!(show(&coin("2500000uatom"), &format), "2.5 ATOM");
+        assert_eq!(
+            show(&coin("1234567890123uatom"), &format),
+            "1,234,567.890123 ATOM"
+        );
+        assert_eq!(show(&coin("5uatom"), &format), "0.000005 ATOM");
+        assert_eq!(show(&coin("1250000uosmo"), &format), "1,250,000 uosmo");
+        format.max_decimal_places = Some(2);
+        format.min_decimal_places = 2;
+        format.decimal_separator = ',';
+        format.thousands_separator = Some('.');
+        assert_eq!(
+            show(&coin("1234567890123uatom"), &format),
+            "1.234.567,89 ATOM"
+        );
+        assert_eq!(show(&coin("3000000uatom"), &format), "3,00 ATOM");
+
+        let dec = DecCoin::try_from(ProtoDecCoin {
+            denom: "uatom".to_string(),
+            amount: "2050000000000000000".to_string(),
+        })
+        .unwrap();
+        let format = CoinFormat::from_metadata(&[Metadata {
+            base: "uatom".to_string(),
+            display: "atom".to_string(),
+            denom_units: vec![cosmos_sdk_proto::cosmos::bank::v1beta1::DenomUnit {
+                denom: "atom".to_string(),
+                exponent: 6,
+                aliases: Vec::new(),
+            }],
+            ..Default::default()
+        }]);
+        assert_eq!(dec.display_with(&format).to_string(), "0.00000205atom");
+    }
 }
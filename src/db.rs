@@ -0,0 +1,142 @@
+//! Database bindings for indexers that persist deep_space types. With the `sqlx` or
+//! `diesel` features Address, Coin and amounts can be bound to and read from TEXT
+//! columns directly. Addresses are stored as bech32 strings, coins as coin strings such
+//! as `100uatom` and amounts as decimal strings
+//!
+//! Uint256 is defined in u64_array_bigints so the traits can't be implemented for it
+//! here, `DbUint256` wraps it instead. The diesel bindings write through the raw bytes
+//! bind collector used by the Postgres and MySQL backends
+
+use crate::{Address, Coin, Uint256};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// A Uint256 stored as a decimal string, for amounts too large for a database integer
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow),
+    diesel(sql_type = diesel::sql_types::Text)
+)]
+pub struct DbUint256(pub Uint256);
+
+impl From<Uint256> for DbUint256 {
+    fn from(value: Uint256) -> Self {
+        DbUint256(value)
+    }
+}
+
+impl From<DbUint256> for Uint256 {
+    fn from(value: DbUint256) -> Self {
+        value.0
+    }
+}
+
+impl Display for DbUint256 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for DbUint256 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uint256::from_dec_or_hex_str_restricted(s)
+            .map(DbUint256)
+            .map_err(|_| format!("invalid amount {}", s))
+    }
+}
+
+/// Implements the sqlx and diesel TEXT bindings for a type through its Display and
+/// FromStr impls
+macro_rules! text_column {
+    ($($ty:ty),*) => {$(
+        #[cfg(feature = "sqlx")]
+        impl<DB: sqlx::Database> sqlx::Type<DB> for $ty
+        where
+            String: sqlx::Type<DB>,
+        {
+            fn type_info() -> DB::TypeInfo {
+                <String as sqlx::Type<DB>>::type_info()
+            }
+
+            fn compatible(ty: &DB::TypeInfo) -> bool {
+                <String as sqlx::Type<DB>>::compatible(ty)
+            }
+        }
+
+        #[cfg(feature = "sqlx")]
+        impl<'r, DB: sqlx::Database> sqlx::Decode<'r, DB> for $ty
+        where
+            String: sqlx::Decode<'r, DB>,
+        {
+            fn decode(
+                value: <DB as sqlx::Database>::ValueRef<'r>,
+            ) -> Result<Self, sqlx::error::BoxDynError> {
+                let value = <String as sqlx::Decode<'r, DB>>::decode(value)?;
+                Ok(value.parse::<$ty>()?)
+            }
+        }
+
+        #[cfg(feature = "sqlx")]
+        impl<'q, DB: sqlx::Database> sqlx::Encode<'q, DB> for $ty
+        where
+            String: sqlx::Encode<'q, DB>,
+        {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'q>,
+            ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+                <String as sqlx::Encode<'q, DB>>::encode(self.to_string(), buf)
+            }
+        }
+
+        #[cfg(feature = "diesel")]
+        impl<DB> diesel::deserialize::FromSql<diesel::sql_types::Text, DB> for $ty
+        where
+            DB: diesel::backend::Backend,
+            String: diesel::deserialize::FromSql<diesel::sql_types::Text, DB>,
+        {
+            fn from_sql(bytes: DB::RawValue<'_>) -> diesel::deserialize::Result<Self> {
+                let value = <String as diesel::deserialize::FromSql<
+                    diesel::sql_types::Text,
+                    DB,
+                >>::from_sql(bytes)?;
+                Ok(value.parse::<$ty>()?)
+            }
+        }
+
+        #[cfg(feature = "diesel")]
+        impl<DB> diesel::serialize::ToSql<diesel::sql_types::Text, DB> for $ty
+        where
+            for<'c> DB: diesel::backend::Backend<
+                BindCollector<'c> = diesel::query_builder::bind_collector::RawBytesBindCollector<DB>,
+            >,
+        {
+            fn to_sql<'b>(
+                &'b self,
+                out: &mut diesel::serialize::Output<'b, '_, DB>,
+            ) -> diesel::serialize::Result {
+                use std::io::Write;
+                out.write_all(self.to_string().as_bytes())?;
+                Ok(diesel::serialize::IsNull::No)
+            }
+        }
+    )*};
+}
+
+text_column!(Address, Coin, DbUint256);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_db_uint256_text() {
+        let amount: DbUint256 = "1000000000000000000000000000000".parse().unwrap();
+        assert_eq!(amount.to_string(), "1000000000000000000000000000000");
+        assert_eq!(Uint256::from(amount), amount.0);
+        assert!("12.5".parse::<DbUint256>().is_err());
+    }
+}
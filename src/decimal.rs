@@ -3,6 +3,7 @@
 //!
 //! [1]: https://pkg.go.dev/github.com/cosmos/cosmos-sdk/types#Dec
 
+use crate::Uint256;
 use rust_decimal::Error as DecimalLibraryError;
 use std::{
     convert::{TryFrom, TryInto},
@@ -14,6 +15,11 @@ use std::{
 pub enum DecimalError {
     ExcessivePrecision,
     InvalidPrecision,
+    /// The value does not fit in an `sdk.Dec`, or a product does not fit in a Uint256
+    Overflow,
+    DivideByZero,
+    /// A negative decimal was applied to an amount
+    Negative,
     DecimalError(DecimalLibraryError),
 }
 
@@ -26,6 +32,11 @@ impl fmt::Display for DecimalError {
             DecimalError::InvalidPrecision => {
                 write!(f, "Decimal is using an invalid precision must be 0 or 18")
             }
+            DecimalError::Overflow => write!(f, "Decimal overflow"),
+            DecimalError::DivideByZero => write!(f, "Decimal division by zero"),
+            DecimalError::Negative => {
+                write!(f, "Negative decimal can not be applied to an amount")
+            }
             DecimalError::DecimalError(v) => {
                 write!(f, "{:?}", v)
             }
@@ -74,6 +85,96 @@ impl Decimal {
         combined_decimal.set_scale(PRECISION)?;
         Ok(Decimal(combined_decimal))
     }
+
+    /// Encodes this decimal the way an `sdk.Dec` is encoded inside of a proto message,
+    /// as the underlying integer with 18 implied decimal places and no decimal point.
+    /// This is the form required for Dec fields in transaction messages.
+    pub fn to_proto_string(&self) -> String {
+        self.0.mantissa().to_string()
+    }
+
+    /// The decimal `numerator / denominator`, rounded down to 18 decimal places. For
+    /// example the share of a pool or a commission computed from two amounts
+    pub fn from_ratio(numerator: Uint256, denominator: Uint256) -> Result<Self, DecimalError> {
+        if denominator.is_zero() {
+            return Err(DecimalError::DivideByZero);
+        }
+        let scaled = numerator
+            .checked_mul(precision_scale())
+            .ok_or(DecimalError::Overflow)?
+            .divide(denominator)
+            // denominator is not zero
+            .unwrap()
+            .0;
+        // the mantissa of a rust_decimal is 96 bits
+        if scaled.sig_bits() > 96 {
+            return Err(DecimalError::Overflow);
+        }
+        let mantissa = scaled.resize_to_u128() as i128;
+        Ok(Decimal(rust_decimal::Decimal::try_from_i128_with_scale(
+            mantissa, PRECISION,
+        )?))
+    }
+
+    /// A decimal of `bps` basis points, 1 bps is 0.0001
+    pub fn from_bps(bps: u32) -> Self {
+        Decimal::from_mantissa(bps as i128 * 10i128.pow(PRECISION - 4))
+    }
+
+    /// A decimal of `percent` percent, 1% is 0.01
+    pub fn from_percent(percent: u32) -> Self {
+        Decimal::from_mantissa(percent as i128 * 10i128.pow(PRECISION - 2))
+    }
+
+    /// This decimal in basis points, rounded toward zero
+    pub fn to_bps(&self) -> i128 {
+        self.0.mantissa() / 10i128.pow(PRECISION - 4)
+    }
+
+    /// Multiplies `amount` by this decimal, rounding down. Use this for amounts paid
+    /// out, so rounding never pays more than is owed
+    pub fn mul_floor(&self, amount: Uint256) -> Result<Uint256, DecimalError> {
+        Ok(self.mul_amount(amount)?.0)
+    }
+
+    /// Multiplies `amount` by this decimal, rounding up. Use this for fees and minimum
+    /// amounts, so rounding never charges or requires less than the exact product
+    pub fn mul_ceil(&self, amount: Uint256) -> Result<Uint256, DecimalError> {
+        let (product, remainder) = self.mul_amount(amount)?;
+        if remainder.is_zero() {
+            Ok(product)
+        } else {
+            product
+                .checked_add(Uint256::one())
+                .ok_or(DecimalError::Overflow)
+        }
+    }
+
+    /// The product of `amount` and this decimal with the remainder of the division
+    fn mul_amount(&self, amount: Uint256) -> Result<(Uint256, Uint256), DecimalError> {
+        let mantissa = self.0.mantissa();
+        if mantissa < 0 {
+            return Err(DecimalError::Negative);
+        }
+        let scale =
+            Uint256::checked_exp10(self.0.scale() as usize).ok_or(DecimalError::Overflow)?;
+        let product = amount
+            .checked_mul(Uint256::from_u128(mantissa as u128))
+            .ok_or(DecimalError::Overflow)?;
+        // 10^scale is never zero
+        Ok(product.divide(scale).unwrap())
+    }
+
+    /// A decimal from a mantissa that is known to fit, with 18 decimal places
+    fn from_mantissa(mantissa: i128) -> Self {
+        Decimal(rust_decimal::Decimal::from_i128_with_scale(
+            mantissa, PRECISION,
+        ))
+    }
+}
+
+fn precision_scale() -> Uint256 {
+    Uint256::from_u128(10u128.pow(PRECISION))
 }
 
 impl Debug for Decimal {
@@ -129,10 +230,42 @@ impl_from_primitive_int_for_decimal!(u8, u16, u32, u64, usize);
 #[cfg(test)]
 mod tests {
     use super::Decimal;
+    use crate::Uint256;
 
     #[test]
     fn string_serialization_test() {
         let num = Decimal::from(-1i8);
         assert_eq!(num.to_string(), "-1.000000000000000000")
     }
+
+    #[test]
+    fn proto_serialization_test() {
+        let num: Decimal = "0.100000000000000000".parse().unwrap();
+        assert_eq!(num.to_proto_string(), "100000000000000000");
+        let num: Decimal = "0.050000000000000000".parse().unwrap();
+        assert_eq!(num.to_proto_string(), "50000000000000000");
+        assert_eq!(Decimal::from(0u8).to_proto_string(), "0");
+    }
+
+    #[test]
+    fn amount_math_test() {
+        let amount = |v: u64| Uint256::from_u64(v);
+        let ratio = Decimal::from_ratio(amount(1), amount(3)).unwrap();
+        assert_eq!(ratio.to_string(), "0.333333333333333333");
+        assert_eq!(ratio.mul_floor(amount(1000)).unwrap(), amount(333));
+        assert_eq!(ratio.mul_ceil(amount(1000)).unwrap(), amount(334));
+        assert!(Decimal::from_ratio(amount(1), amount(0)).is_err());
+        assert!(Decimal::from_ratio(Uint256::max_value(), amount(1)).is_err());
+
+        let fee = Decimal::from_bps(30);
+        assert_eq!(fee.to_string(), "0.003000000000000000");
+        assert_eq!(fee.to_bps(), 30);
+        assert_eq!(fee.mul_ceil(amount(1001)).unwrap(), amount(4));
+        assert_eq!(
+            Decimal::from_percent(5),
+            "0.050000000000000000".parse().unwrap()
+        );
+        assert_eq!(Decimal::from(2u8).mul_floor(amount(7)).unwrap(), amount(14));
+        assert!(Decimal::from(-1i8).mul_floor(amount(7)).is_err());
+    }
 }
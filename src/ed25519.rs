@@ -0,0 +1,166 @@
+//! SLIP-10 ed25519 keys derived from the same mnemonics as secp256k1 account keys, for
+//! validator consensus keys and chains with ed25519 accounts. Enabled with the
+//! `ed25519` feature
+//!
+//! SLIP-10 only defines hardened derivation for ed25519, every index of the path must
+//! be hardened, for example m/44'/118'/0'/0'/0'
+//! See: <https://github.com/satoshilabs/slips/blob/master/slip-0010.md>
+
+use crate::error::{AddressError, HdWalletError, PrivateKeyError};
+use crate::mnemonic::SecretMnemonic;
+use crate::private_key::parse_hd_path;
+use crate::utils::bytes_to_hex_str;
+use crate::xpub::HARDENED_OFFSET;
+use crate::Address;
+use ed25519_dalek::{Signer, SigningKey};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt;
+use zeroize::Zeroize;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// An ed25519 private key, the key material is zeroed on drop
+#[derive(Clone, PartialEq, Eq)]
+pub struct Ed25519PrivateKey([u8; 32]);
+
+impl Drop for Ed25519PrivateKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for Ed25519PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ed25519PrivateKey([REDACTED])")
+    }
+}
+
+/// Splits a SLIP-10 HMAC output into the key and the chain code
+fn split(hmac: HmacSha512) -> ([u8; 32], [u8; 32]) {
+    let hash = hmac.finalize().into_bytes();
+    let mut key = [0; 32];
+    let mut chain_code = [0; 32];
+    key.copy_from_slice(&hash[..32]);
+    chain_code.copy_from_slice(&hash[32..]);
+    (key, chain_code)
+}
+
+impl Ed25519PrivateKey {
+    /// Creates a key from its 32 byte seed, the form Tendermint and most wallets store
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Ed25519PrivateKey(bytes)
+    }
+
+    /// Derives the key at `path` from a BIP39 seed following SLIP-10
+    pub fn from_seed_and_path(path: &str, seed: &[u8]) -> Result<Self, PrivateKeyError> {
+        let path = parse_hd_path(path)?;
+        let mut hmac = HmacSha512::new_from_slice(b"ed25519 seed").unwrap();
+        hmac.update(seed);
+        let (mut key, mut chain_code) = split(hmac);
+        for (index, hardened) in path {
+            if !hardened {
+                key.zeroize();
+                return Err(HdWalletError::NonHardenedEd25519(index).into());
+            }
+            let mut hmac = HmacSha512::new_from_slice(&chain_code).unwrap();
+            hmac.update(&[0]);
+            hmac.update(&key);
+            hmac.update(&(index + HARDENED_OFFSET).to_be_bytes());
+            key.zeroize();
+            (key, chain_code) = split(hmac);
+        }
+        chain_code.zeroize();
+        Ok(Ed25519PrivateKey(key))
+    }
+
+    /// Derives the key at `path` from a phrase, see [Ed25519PrivateKey::from_seed_and_path]
+    pub fn from_hd_wallet_path(
+        path: &str,
+        phrase: &str,
+        passphrase: &str,
+    ) -> Result<Self, PrivateKeyError> {
+        let mnemonic = SecretMnemonic::parse(phrase)?;
+        Ed25519PrivateKey::from_seed_and_path(path, &mnemonic.to_seed(passphrase))
+    }
+
+    /// The 32 byte seed of this key, avoid copying it into storage that will not be
+    /// cleared
+    pub fn expose_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        SigningKey::from_bytes(&self.0).verifying_key().to_bytes()
+    }
+
+    pub fn sign(&self, msg: &[u8]) -> [u8; 64] {
+        SigningKey::from_bytes(&self.0).sign(msg).to_bytes()
+    }
+
+    /// The Tendermint address of this key, the first 20 bytes of the sha256 of the
+    /// public key. With a valcons prefix this is the consensus address of a validator
+    pub fn to_address(&self, prefix: &str) -> Result<Address, AddressError> {
+        let hash = Sha256::digest(self.public_key());
+        Address::from_slice(&hash[..20], prefix)
+    }
+
+    /// Encodes this key as a Tendermint priv_validator_key.json, for running a validator
+    /// with a consensus key derived from a mnemonic
+    pub fn to_priv_validator_key_json(&self) -> String {
+        let public_key = self.public_key();
+        let hash = Sha256::digest(public_key);
+        let mut private_key = self.0.to_vec();
+        private_key.extend_from_slice(&public_key);
+        let json = json!({
+            "address": bytes_to_hex_str(&hash[..20]).to_uppercase(),
+            "pub_key": {
+                "type": "tendermint/PubKeyEd25519",
+                "value": base64::encode(public_key),
+            },
+            "priv_key": {
+                "type": "tendermint/PrivKeyEd25519",
+                "value": base64::encode(&private_key),
+            },
+        });
+        private_key.zeroize();
+        serde_json::to_string_pretty(&json).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_str_to_bytes;
+
+    #[test]
+    fn test_slip10_vectors() {
+        let seed = hex_str_to_bytes("000102030405060708090a0b0c0d0e0f").unwrap();
+        let check = |path: &str, private: &str, public: &str| {
+            let key = Ed25519PrivateKey::from_seed_and_path(path, &seed).unwrap();
+            assert_eq!(bytes_to_hex_str(key.expose_bytes()), private);
+            assert_eq!(bytes_to_hex_str(&key.public_key()), public);
+        };
+        check(
+            "m",
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+            "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed",
+        );
+        check(
+            "m/0'/1'/2'/2'/1000000000'",
+            "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+            "3c24da049451555d51a7014a37337aa4e12d41e485abccfa46b47dfb2af54b7a",
+        );
+        assert!(Ed25519PrivateKey::from_seed_and_path("m/0'/1", &seed).is_err());
+
+        let key = Ed25519PrivateKey::from_seed_and_path("m/0'", &seed).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&key.to_priv_validator_key_json()).unwrap();
+        assert_eq!(json["pub_key"]["type"], "tendermint/PubKeyEd25519");
+        assert_eq!(
+            json["address"].as_str().unwrap().to_lowercase(),
+            bytes_to_hex_str(key.to_address("cosmosvalcons").unwrap().as_bytes())
+        );
+    }
+}
@@ -0,0 +1,649 @@
+//! EIP-712 typed data hashing and signing for Ethermint based chains (Evmos, Injective, Canto, etc)
+//! these chains accept Cosmos transactions signed by an Ethereum wallet over an EIP-712 typed data
+//! representation of the legacy amino JSON sign doc. The signature is carried in an
+//! `ExtensionOptionsWeb3Tx` extension option rather than the TxRaw signatures field.
+//!
+//! Since deep_space does not include an amino JSON codec the caller provides the amino JSON form of
+//! each message as an [AminoMsg], the EIP-712 types for the message are inferred from that JSON.
+//! This is only compiled if the ethermint feature is enabled.
+
+use crate::address::Address;
+use crate::error::Eip712Error;
+use crate::msg::Msg;
+use crate::private_key::{MessageArgs, PrivateKey};
+use crate::utils::encode_any;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    mode_info, AuthInfo, ModeInfo, SignerInfo, TxBody, TxRaw,
+};
+use cosmos_sdk_proto::ethermint::crypto::v1::ethsecp256k1::PubKey as ProtoEthSecp256k1Pubkey;
+use cosmos_sdk_proto::ethermint::types::v1::ExtensionOptionsWeb3Tx;
+use prost::Message;
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The type url of an Ethermint secp256k1 public key
+pub const ETH_SECP256K1_PUBKEY_TYPE_URL: &str = "/ethermint.crypto.v1.ethsecp256k1.PubKey";
+/// The type url of the extension option carrying the EIP-712 signature
+pub const EXTENSION_OPTIONS_WEB3_TX_TYPE_URL: &str = "/ethermint.types.v1.ExtensionOptionsWeb3Tx";
+/// SIGN_MODE_LEGACY_AMINO_JSON, EIP-712 signatures are verified against the amino sign doc
+pub const SIGN_MODE_LEGACY_AMINO_JSON: i32 = 127;
+
+/// A single named and typed member of an EIP-712 struct
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TypedField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+impl TypedField {
+    pub fn new(name: impl Into<String>, field_type: impl Into<String>) -> Self {
+        TypedField {
+            name: name.into(),
+            field_type: field_type.into(),
+        }
+    }
+}
+
+/// EIP-712 typed data, (de)serializes in the same JSON format used by `eth_signTypedData_v4`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TypedData {
+    pub types: BTreeMap<String, Vec<TypedField>>,
+    #[serde(rename = "primaryType")]
+    pub primary_type: String,
+    pub domain: Value,
+    pub message: Value,
+}
+
+impl TypedData {
+    /// The hash that is actually signed `keccak256(0x19 0x01 || domainSeparator || hashStruct(message))`
+    pub fn signing_hash(&self) -> Result<[u8; 32], Eip712Error> {
+        let domain_separator = self.hash_struct("EIP712Domain", &self.domain)?;
+        let message = self.hash_struct(&self.primary_type, &self.message)?;
+        let mut hasher = Keccak256::new();
+        hasher.update([0x19, 0x01]);
+        hasher.update(domain_separator);
+        hasher.update(message);
+        Ok(hasher.finalize().into())
+    }
+
+    /// `keccak256(typeHash || encodeData(value))` for the given struct type
+    pub fn hash_struct(&self, type_name: &str, value: &Value) -> Result<[u8; 32], Eip712Error> {
+        let encoded = self.encode_data(type_name, value)?;
+        Ok(keccak256(&encoded))
+    }
+
+    /// The type hash of the given struct type, `keccak256(encodeType(type))`
+    pub fn type_hash(&self, type_name: &str) -> Result<[u8; 32], Eip712Error> {
+        Ok(keccak256(self.encode_type(type_name)?.as_bytes()))
+    }
+
+    /// Encodes a struct type and all struct types it references, for example
+    /// `Mail(Person from,Person to,string contents)Person(string name,address wallet)`
+    pub fn encode_type(&self, type_name: &str) -> Result<String, Eip712Error> {
+        let mut deps = BTreeSet::new();
+        self.find_dependencies(type_name, &mut deps)?;
+        deps.remove(type_name);
+        let mut out = self.encode_single_type(type_name)?;
+        for dep in deps {
+            out += &self.encode_single_type(&dep)?;
+        }
+        Ok(out)
+    }
+
+    fn encode_single_type(&self, type_name: &str) -> Result<String, Eip712Error> {
+        let fields = self.fields(type_name)?;
+        let members: Vec<String> = fields
+            .iter()
+            .map(|f| format!("{} {}", f.field_type, f.name))
+            .collect();
+        Ok(format!("{}({})", type_name, members.join(",")))
+    }
+
+    fn find_dependencies(
+        &self,
+        type_name: &str,
+        found: &mut BTreeSet<String>,
+    ) -> Result<(), Eip712Error> {
+        let base = type_name.trim_end_matches("[]");
+        if found.contains(base) || !self.types.contains_key(base) {
+            return Ok(());
+        }
+        found.insert(base.to_string());
+        for field in self.fields(base)? {
+            self.find_dependencies(&field.field_type, found)?;
+        }
+        Ok(())
+    }
+
+    fn fields(&self, type_name: &str) -> Result<&Vec<TypedField>, Eip712Error> {
+        self.types
+            .get(type_name)
+            .ok_or_else(|| Eip712Error::UnknownType(type_name.to_string()))
+    }
+
+    fn encode_data(&self, type_name: &str, value: &Value) -> Result<Vec<u8>, Eip712Error> {
+        let mut out = self.type_hash(type_name)?.to_vec();
+        for field in self.fields(type_name)? {
+            let v = value.get(&field.name).unwrap_or(&Value::Null);
+            out.extend_from_slice(&self.encode_value(&field.field_type, v)?);
+        }
+        Ok(out)
+    }
+
+    fn encode_value(&self, field_type: &str, value: &Value) -> Result<[u8; 32], Eip712Error> {
+        if let Some(inner) = field_type.strip_suffix("[]") {
+            let items = match value {
+                Value::Array(items) => items.as_slice(),
+                Value::Null => &[],
+                _ => return Err(Eip712Error::BadValue(field_type.to_string())),
+            };
+            let mut concat = Vec::new();
+            for item in items {
+                concat.extend_from_slice(&self.encode_value(inner, item)?);
+            }
+            return Ok(keccak256(&concat));
+        }
+        if self.types.contains_key(field_type) {
+            return self.hash_struct(field_type, value);
+        }
+        match field_type {
+            "string" => match value {
+                Value::String(s) => Ok(keccak256(s.as_bytes())),
+                Value::Null => Ok(keccak256(&[])),
+                v => Ok(keccak256(v.to_string().as_bytes())),
+            },
+            "bytes" => Ok(keccak256(&parse_hex_value(value, field_type)?)),
+            "bool" => match value {
+                Value::Bool(b) => Ok(left_pad(&[*b as u8])),
+                _ => Err(Eip712Error::BadValue(field_type.to_string())),
+            },
+            "address" => {
+                let bytes = parse_hex_value(value, field_type)?;
+                if bytes.len() != 20 {
+                    return Err(Eip712Error::BadValue(field_type.to_string()));
+                }
+                Ok(left_pad(&bytes))
+            }
+            t if t.starts_with("uint") || t.starts_with("int") => {
+                encode_integer(value, t.starts_with("int"))
+                    .ok_or_else(|| Eip712Error::BadValue(field_type.to_string()))
+            }
+            t if t.starts_with("bytes") => {
+                let bytes = parse_hex_value(value, field_type)?;
+                if bytes.len() > 32 {
+                    return Err(Eip712Error::BadValue(field_type.to_string()));
+                }
+                let mut out = [0u8; 32];
+                out[..bytes.len()].copy_from_slice(&bytes);
+                Ok(out)
+            }
+            t => Err(Eip712Error::UnknownType(t.to_string())),
+        }
+    }
+}
+
+/// The amino JSON representation of a message, for example
+/// `{"type": "cosmos-sdk/MsgSend", "value": {"amount": [...], "from_address": ..., "to_address": ...}}`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AminoMsg {
+    #[serde(rename = "type")]
+    pub msg_type: String,
+    pub value: Value,
+}
+
+/// Builds the Ethermint EIP-712 domain for the given EVM chain id (e.g. 9001 for Evmos)
+pub fn ethermint_domain(eth_chain_id: u64) -> Value {
+    json!({
+        "name": "Cosmos Web3",
+        "version": "1.0.0",
+        "chainId": eth_chain_id,
+        "verifyingContract": "cosmos",
+        "salt": "0",
+    })
+}
+
+/// Wraps a legacy amino sign doc into the EIP-712 typed data structure Ethermint verifies
+/// All messages in the transaction must be of the same type, as the Ethermint ante handler
+/// only supports a single `MsgValue` schema per transaction.
+pub fn ethermint_typed_data(
+    amino_msgs: &[AminoMsg],
+    args: &MessageArgs,
+    memo: &str,
+    fee_payer: &str,
+    eth_chain_id: u64,
+) -> Result<TypedData, Eip712Error> {
+    let first = amino_msgs.first().ok_or(Eip712Error::NoMessages)?;
+    if amino_msgs.iter().any(|m| m.msg_type != first.msg_type) {
+        return Err(Eip712Error::MismatchedMsgTypes);
+    }
+
+    let mut types = BTreeMap::new();
+    types.insert(
+        "EIP712Domain".to_string(),
+        vec![
+            TypedField::new("name", "string"),
+            TypedField::new("version", "string"),
+            TypedField::new("chainId", "uint256"),
+            TypedField::new("verifyingContract", "string"),
+            TypedField::new("salt", "string"),
+        ],
+    );
+    types.insert(
+        "Tx".to_string(),
+        vec![
+            TypedField::new("account_number", "string"),
+            TypedField::new("chain_id", "string"),
+            TypedField::new("fee", "Fee"),
+            TypedField::new("memo", "string"),
+            TypedField::new("msgs", "Msg[]"),
+            TypedField::new("sequence", "string"),
+        ],
+    );
+    types.insert(
+        "Fee".to_string(),
+        vec![
+            TypedField::new("feePayer", "string"),
+            TypedField::new("amount", "Coin[]"),
+            TypedField::new("gas", "string"),
+        ],
+    );
+    types.insert(
+        "Coin".to_string(),
+        vec![
+            TypedField::new("denom", "string"),
+            TypedField::new("amount", "string"),
+        ],
+    );
+    types.insert(
+        "Msg".to_string(),
+        vec![
+            TypedField::new("type", "string"),
+            TypedField::new("value", "MsgValue"),
+        ],
+    );
+    infer_struct_types("MsgValue", &first.value, &mut types)?;
+
+    let amount: Vec<Value> = args
+        .fee
+        .amount
+        .iter()
+        .map(|c| json!({"denom": c.denom, "amount": c.amount.to_string()}))
+        .collect();
+    let message = json!({
+        "account_number": args.account_number.to_string(),
+        "chain_id": args.chain_id,
+        "fee": {
+            "amount": amount,
+            "feePayer": fee_payer,
+            "gas": args.fee.gas_limit.to_string(),
+        },
+        "memo": memo,
+        "msgs": amino_msgs,
+        "sequence": args.sequence.to_string(),
+    });
+
+    Ok(TypedData {
+        types,
+        primary_type: "Tx".to_string(),
+        domain: ethermint_domain(eth_chain_id),
+        message,
+    })
+}
+
+/// Derives the Ethermint address of a private key, unlike standard Cosmos addresses these are the
+/// last 20 bytes of the keccak256 hash of the uncompressed public key, the same bytes as the
+/// Ethereum address of the key
+pub fn ethermint_address(private_key: &PrivateKey, prefix: &str) -> Result<Address, Eip712Error> {
+    let uncompressed = private_key.to_uncompressed_public_key()?;
+    let hash = keccak256(&uncompressed[1..]);
+    Ok(Address::from_slice(&hash[12..], prefix)?)
+}
+
+/// Signs a transaction using EIP-712 typed data as Ethermint chains expect from Ethereum wallets
+/// returns the proto encoded TxRaw bytes ready to be broadcast.
+///
+/// # Arguments
+///
+/// * `private_key` - the eth-style secp256k1 key that signs and pays for the transaction
+/// * `messages` - the proto encoded messages to include in the transaction
+/// * `amino_msgs` - the amino JSON form of the same messages, in the same order
+/// * `args` - the sequence, account number, chain id and fee of the transaction
+/// * `memo` - the transaction memo
+/// * `eth_chain_id` - the EVM chain id used in the EIP-712 domain (e.g. 9001 for Evmos)
+/// * `prefix` - the bech32 prefix of the chain
+pub fn sign_ethermint_tx(
+    private_key: &PrivateKey,
+    messages: &[Msg],
+    amino_msgs: &[AminoMsg],
+    args: MessageArgs,
+    memo: impl Into<String>,
+    eth_chain_id: u64,
+    prefix: &str,
+) -> Result<Vec<u8>, Eip712Error> {
+    if messages.len() != amino_msgs.len() {
+        return Err(Eip712Error::MismatchedMsgTypes);
+    }
+    // the legacy amino sign doc has no place for these fields
+    if args.unordered || args.timeout_timestamp.is_some() {
+        return Err(Eip712Error::BadValue(
+            "unordered transactions are not supported by EIP-712 signing".to_string(),
+        ));
+    }
+    if args.relative_timeout.is_some() {
+        return Err(Eip712Error::BadValue(
+            "relative_timeout must be resolved before signing".to_string(),
+        ));
+    }
+    let memo = memo.into();
+    let fee_payer = ethermint_address(private_key, prefix)?.to_bech32(prefix)?;
+
+    let typed_data = ethermint_typed_data(amino_msgs, &args, &memo, &fee_payer, eth_chain_id)?;
+    let digest = typed_data.signing_hash()?;
+    let signature = private_key.sign_recoverable(digest)?.to_recoverable()?;
+
+    let web3_extension = ExtensionOptionsWeb3Tx {
+        typed_data_chain_id: eth_chain_id,
+        fee_payer,
+        fee_payer_sig: signature.to_vec(),
+    };
+
+    // the web3 extension must come first, Ethermint routes the tx to the EIP-712
+    // ante handler based on it
+    let mut extension_options = vec![encode_any(
+        web3_extension,
+        EXTENSION_OPTIONS_WEB3_TX_TYPE_URL,
+    )];
+    extension_options.extend(args.extension_options.iter().cloned());
+
+    let body = TxBody {
+        messages: messages.iter().map(|msg| msg.0.clone()).collect(),
+        memo,
+        timeout_height: args.timeout_height,
+        extension_options,
+        non_critical_extension_options: args.non_critical_extension_options.clone(),
+    };
+
+    let key = ProtoEthSecp256k1Pubkey {
+        key: private_key
+            .to_public_key(crate::PublicKey::DEFAULT_PREFIX)?
+            .to_vec(),
+    };
+    let signer_info = SignerInfo {
+        public_key: Some(encode_any(key, ETH_SECP256K1_PUBKEY_TYPE_URL)),
+        mode_info: Some(ModeInfo {
+            sum: Some(mode_info::Sum::Single(mode_info::Single {
+                mode: SIGN_MODE_LEGACY_AMINO_JSON,
+            })),
+        }),
+        sequence: args.sequence,
+    };
+    let auth_info = AuthInfo {
+        signer_infos: vec![signer_info],
+        fee: Some(args.fee.into()),
+    };
+
+    let tx_raw = TxRaw {
+        body_bytes: body.encode_to_vec(),
+        auth_info_bytes: auth_info.encode_to_vec(),
+        // the signature is carried in the web3 extension, Ethermint expects
+        // a single empty signature here
+        signatures: vec![Vec::new()],
+    };
+    Ok(tx_raw.encode_to_vec())
+}
+
+/// Walks a JSON object and registers EIP-712 struct types for it and all nested objects
+/// nested struct types are named after the field that contains them, `amount` becomes `TypeAmount`
+fn infer_struct_types(
+    type_name: &str,
+    value: &Value,
+    types: &mut BTreeMap<String, Vec<TypedField>>,
+) -> Result<(), Eip712Error> {
+    let object = match value {
+        Value::Object(o) => o,
+        _ => return Err(Eip712Error::BadValue(type_name.to_string())),
+    };
+    let mut fields = Vec::new();
+    for (name, v) in object.iter() {
+        if let Some(t) = infer_field_type(name, v, types)? {
+            fields.push(TypedField::new(name.clone(), t));
+        }
+    }
+    types.insert(type_name.to_string(), fields);
+    Ok(())
+}
+
+fn infer_field_type(
+    name: &str,
+    value: &Value,
+    types: &mut BTreeMap<String, Vec<TypedField>>,
+) -> Result<Option<String>, Eip712Error> {
+    Ok(match value {
+        // null values are omitted from both the types and the data
+        Value::Null => None,
+        Value::Bool(_) => Some("bool".to_string()),
+        Value::Number(n) => {
+            if n.is_u64() {
+                Some("uint64".to_string())
+            } else {
+                Some("int64".to_string())
+            }
+        }
+        Value::String(_) => Some("string".to_string()),
+        Value::Object(_) => {
+            let t = nested_type_name(name);
+            infer_struct_types(&t, value, types)?;
+            Some(t)
+        }
+        Value::Array(items) => match items.first() {
+            // empty arrays carry no type information, default to a string array
+            None => Some("string[]".to_string()),
+            Some(first) => infer_field_type(name, first, types)?.map(|t| format!("{}[]", t)),
+        },
+    })
+}
+
+fn nested_type_name(field: &str) -> String {
+    let mut out = String::from("Type");
+    for part in field.split('_') {
+        let mut chars = part.chars();
+        if let Some(c) = chars.next() {
+            out.extend(c.to_uppercase());
+            out.push_str(chars.as_str());
+        }
+    }
+    out
+}
+
+fn encode_integer(value: &Value, signed: bool) -> Option<[u8; 32]> {
+    let (negative, magnitude) = match value {
+        Value::Number(n) => {
+            if let Some(v) = n.as_u64() {
+                (false, v as u128)
+            } else {
+                let v = n.as_i64()?;
+                (v < 0, v.unsigned_abs() as u128)
+            }
+        }
+        Value::String(s) => {
+            let s = s.trim();
+            match s.strip_prefix('-') {
+                Some(rest) => (true, rest.parse().ok()?),
+                None => {
+                    let v = crate::Uint256::from_dec_or_hex_str(s).ok()?;
+                    return Some(v.to_u8_array_be());
+                }
+            }
+        }
+        _ => return None,
+    };
+    if negative && !signed {
+        return None;
+    }
+    let mut out = left_pad(&magnitude.to_be_bytes());
+    if negative {
+        // two's complement
+        for b in out.iter_mut() {
+            *b = !*b;
+        }
+        for b in out.iter_mut().rev() {
+            let (v, overflow) = b.overflowing_add(1);
+            *b = v;
+            if !overflow {
+                break;
+            }
+        }
+    }
+    Some(out)
+}
+
+fn parse_hex_value(value: &Value, field_type: &str) -> Result<Vec<u8>, Eip712Error> {
+    match value {
+        Value::String(s) => crate::utils::hex_str_to_bytes(s)
+            .map_err(|_| Eip712Error::BadValue(field_type.to_string())),
+        _ => Err(Eip712Error::BadValue(field_type.to_string())),
+    }
+}
+
+fn left_pad(bytes: &[u8]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out[32 - bytes.len()..].copy_from_slice(bytes);
+    out
+}
+
+fn keccak256(input: &[u8]) -> [u8; 32] {
+    Keccak256::digest(input).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coin::{Coin, Fee};
+    use crate::utils::bytes_to_hex_str;
+    use cosmos_sdk_proto::cosmos::tx::v1beta1::TxBody;
+
+    /// The example from the EIP-712 specification
+    fn mail_example() -> TypedData {
+        serde_json::from_value(json!({
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"},
+                    {"name": "verifyingContract", "type": "address"}
+                ],
+                "Person": [
+                    {"name": "name", "type": "string"},
+                    {"name": "wallet", "type": "address"}
+                ],
+                "Mail": [
+                    {"name": "from", "type": "Person"},
+                    {"name": "to", "type": "Person"},
+                    {"name": "contents", "type": "string"}
+                ]
+            },
+            "primaryType": "Mail",
+            "domain": {
+                "name": "Ether Mail",
+                "version": "1",
+                "chainId": 1,
+                "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+            },
+            "message": {
+                "from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+                "to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+                "contents": "Hello, Bob!"
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_eip712_spec_vector() {
+        let data = mail_example();
+        assert_eq!(
+            data.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            bytes_to_hex_str(&data.type_hash("Mail").unwrap()),
+            "a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2"
+        );
+        assert_eq!(
+            bytes_to_hex_str(&data.hash_struct("EIP712Domain", &data.domain).unwrap()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            bytes_to_hex_str(&data.signing_hash().unwrap()),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+    }
+
+    #[test]
+    fn test_ethermint_tx() {
+        let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let from = ethermint_address(&key, "evmos").unwrap();
+        let amino = AminoMsg {
+            msg_type: "cosmos-sdk/MsgSend".to_string(),
+            value: json!({
+                "amount": [{"amount": "1", "denom": "aevmos"}],
+                "from_address": from.to_string(),
+                "to_address": from.to_string(),
+            }),
+        };
+        let coin = Coin {
+            denom: "aevmos".to_string(),
+            amount: crate::u256!(1),
+        };
+        let send = cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend {
+            amount: vec![coin.clone().into()],
+            from_address: from.to_string(),
+            to_address: from.to_string(),
+        };
+        let args = MessageArgs {
+            sequence: 1,
+            account_number: 2,
+            chain_id: "evmos_9001-2".to_string(),
+            fee: Fee {
+                amount: vec![coin],
+                gas_limit: 200_000,
+                payer: None,
+                granter: None,
+            },
+            timeout_height: 0,
+            ..Default::default()
+        };
+        let typed = ethermint_typed_data(
+            std::slice::from_ref(&amino),
+            &args,
+            "",
+            &from.to_string(),
+            9001,
+        )
+        .unwrap();
+        assert_eq!(
+            typed.encode_type("MsgValue").unwrap(),
+            "MsgValue(TypeAmount[] amount,string from_address,string to_address)TypeAmount(string amount,string denom)"
+        );
+        let digest = typed.signing_hash().unwrap();
+
+        let msg = Msg::new("/cosmos.bank.v1beta1.MsgSend", send);
+        let raw = sign_ethermint_tx(&key, &[msg], &[amino], args, "", 9001, "evmos").unwrap();
+        let raw = TxRaw::decode(raw.as_slice()).unwrap();
+        let body = TxBody::decode(raw.body_bytes.as_slice()).unwrap();
+        let ext =
+            ExtensionOptionsWeb3Tx::decode(body.extension_options[0].value.as_slice()).unwrap();
+        assert_eq!(ext.typed_data_chain_id, 9001);
+        assert_eq!(ext.fee_payer, from.to_string());
+
+        // the signature in the extension must recover to the fee payer
+        let sig = crate::Signature::from_recoverable(&ext.fee_payer_sig).unwrap();
+        let recovered = sig.recover_pubkey(digest).unwrap();
+        assert_eq!(
+            recovered,
+            key.to_public_key(crate::PublicKey::DEFAULT_PREFIX).unwrap()
+        );
+    }
+}
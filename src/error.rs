@@ -45,7 +45,7 @@ pub enum CosmosGrpcError {
     TransactionFailed {
         tx: TxResponse,
         time: Duration,
-        sdk_error: Option<SdkErrorCode>,
+        reason: BroadcastError,
     },
     InsufficientFees {
         fee_info: FeeInfo,
@@ -57,6 +57,40 @@ pub enum CosmosGrpcError {
         max: u64,
         required: u64,
     },
+    /// The chain has reached the height of a scheduled software upgrade and
+    /// will not produce blocks until validators install the new binary
+    ChainHaltedForUpgrade {
+        name: String,
+        height: i64,
+    },
+    /// A transaction was not found on chain before the wait timed out
+    TimedOut {
+        txhash: String,
+        last_seen_height: u64,
+    },
+    /// An error object returned by the Tendermint JSON-RPC endpoint
+    RpcError {
+        code: i64,
+        message: String,
+        data: Option<String>,
+    },
+    /// A Merkle proof returned by the node does not prove what it claims to
+    InvalidProof {
+        txhash: String,
+        reason: String,
+    },
+    /// The node's latest block is further behind the chain than the Contact allows,
+    /// transactions sent through it would be checked against stale account state
+    NodeLagging {
+        blocks_behind: u64,
+        max_lag: u64,
+    },
+    /// Fewer than `required` endpoints agreed on the result of a quorum query
+    NoQuorum {
+        required: usize,
+        agreeing: usize,
+        endpoints: usize,
+    },
 }
 
 impl Display for CosmosGrpcError {
@@ -94,16 +128,12 @@ impl Display for CosmosGrpcError {
             CosmosGrpcError::InvalidPrefix => {
                 write!(f, "CosmosGrpc InvalidPrefix")
             }
-            CosmosGrpcError::TransactionFailed {
-                tx,
-                time,
-                sdk_error,
-            } => {
+            CosmosGrpcError::TransactionFailed { tx, time, reason } => {
                 write!(
                     f,
-                    "CosmosGrpc Transaction {:?} {:?} did not enter chain in {}ms",
+                    "CosmosGrpc Transaction {:?} {} did not enter chain in {}ms",
                     tx,
-                    sdk_error,
+                    reason,
                     time.as_millis()
                 )
             }
@@ -113,6 +143,50 @@ impl Display for CosmosGrpcError {
             CosmosGrpcError::ParseError { error } => {
                 write!(f, "Failed to Parse BigInt {:?}", error)
             }
+            CosmosGrpcError::ChainHaltedForUpgrade { name, height } => {
+                write!(
+                    f,
+                    "CosmosGrpc chain halted for upgrade {} at height {}",
+                    name, height
+                )
+            }
+            CosmosGrpcError::TimedOut {
+                txhash,
+                last_seen_height,
+            } => {
+                write!(
+                    f,
+                    "CosmosGrpc tx {} not found, last seen block {}",
+                    txhash, last_seen_height
+                )
+            }
+            CosmosGrpcError::RpcError {
+                code,
+                message,
+                data,
+            } => {
+                write!(f, "Tendermint RPC error {} {} {:?}", code, message, data)
+            }
+            CosmosGrpcError::InvalidProof { txhash, reason } => {
+                write!(f, "Invalid inclusion proof for tx {} {}", txhash, reason)
+            }
+            CosmosGrpcError::NodeLagging {
+                blocks_behind,
+                max_lag,
+            } => write!(
+                f,
+                "CosmosGrpc this node is an estimated {} blocks behind, more than the allowed {}",
+                blocks_behind, max_lag
+            ),
+            CosmosGrpcError::NoQuorum {
+                required,
+                agreeing,
+                endpoints,
+            } => write!(
+                f,
+                "CosmosGrpc only {} of {} endpoints agreed, {} required",
+                agreeing, endpoints, required
+            ),
             CosmosGrpcError::GasRequiredExceedsBlockMaximum { max, required } => {
                 write!(
                     f,
@@ -165,6 +239,15 @@ pub enum AddressError {
     HexDecodeErrorWrongLength,
     PrefixTooLong(ArrayStringError),
     BytesDecodeErrorWrongLength,
+    /// The address is well formed but its checksum does not match, usually a typo
+    Bech32InvalidChecksum,
+    /// The address is valid but for another chain
+    WrongPrefix {
+        expected: String,
+        found: String,
+    },
+    /// A mixed case Ethereum address whose case does not match its EIP-55 checksum
+    InvalidEip55Checksum,
 }
 
 impl fmt::Display for AddressError {
@@ -177,6 +260,13 @@ impl fmt::Display for AddressError {
             AddressError::HexDecodeErrorWrongLength => write!(f, "HexDecodeError Wrong Length"),
             AddressError::PrefixTooLong(val) => write!(f, "Prefix too long {}", val),
             AddressError::BytesDecodeErrorWrongLength => write!(f, "BytesDecodeError Wrong Length"),
+            AddressError::Bech32InvalidChecksum => write!(f, "Bech32InvalidChecksum"),
+            AddressError::WrongPrefix { expected, found } => write!(
+                f,
+                "Wrong address prefix, expected {} found {}",
+                expected, found
+            ),
+            AddressError::InvalidEip55Checksum => write!(f, "Invalid EIP-55 checksum"),
         }
     }
 }
@@ -281,6 +371,10 @@ pub enum PrivateKeyError {
     AddressError(AddressError),
     HdWalletError(HdWalletError),
     InvalidMnemonic { error: Bip39Error },
+    SignatureError(SignatureError),
+    InvalidMessageArgs(String),
+    CoinError(CoinError),
+    TxValidationError(TxValidationError),
 }
 
 impl fmt::Display for PrivateKeyError {
@@ -296,6 +390,10 @@ impl fmt::Display for PrivateKeyError {
             PrivateKeyError::InvalidMnemonic { error } => {
                 write!(f, "Failed to process mnemonic {:?}", error)
             }
+            PrivateKeyError::SignatureError(val) => write!(f, "{}", val),
+            PrivateKeyError::InvalidMessageArgs(val) => write!(f, "Invalid MessageArgs {}", val),
+            PrivateKeyError::CoinError(val) => write!(f, "Invalid fee {}", val),
+            PrivateKeyError::TxValidationError(val) => write!(f, "{}", val),
         }
     }
 }
@@ -338,16 +436,305 @@ impl From<ByteDecodeError> for PrivateKeyError {
     }
 }
 
+impl From<SignatureError> for PrivateKeyError {
+    fn from(error: SignatureError) -> Self {
+        PrivateKeyError::SignatureError(error)
+    }
+}
+
+impl From<CoinError> for PrivateKeyError {
+    fn from(error: CoinError) -> Self {
+        PrivateKeyError::CoinError(error)
+    }
+}
+
+impl From<TxValidationError> for PrivateKeyError {
+    fn from(error: TxValidationError) -> Self {
+        PrivateKeyError::TxValidationError(error)
+    }
+}
+
 impl From<Bip39Error> for PrivateKeyError {
     fn from(error: Bip39Error) -> Self {
         PrivateKeyError::InvalidMnemonic { error }
     }
 }
 
+#[derive(Debug)]
+pub enum SignatureError {
+    WrongLength(usize),
+    InvalidRecoveryId(u8),
+    MissingRecoveryId,
+    CurveError(CurveError),
+    PublicKeyError(PublicKeyError),
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FormatResult {
+        match self {
+            SignatureError::WrongLength(val) => {
+                write!(f, "SignatureError Wrong Length {} bytes", val)
+            }
+            SignatureError::InvalidRecoveryId(val) => {
+                write!(f, "SignatureError invalid recovery id {}", val)
+            }
+            SignatureError::MissingRecoveryId => {
+                write!(
+                    f,
+                    "SignatureError recovery id is required to recover a public key"
+                )
+            }
+            SignatureError::CurveError(val) => write!(f, "Secp256k1 Error {}", val),
+            SignatureError::PublicKeyError(val) => write!(f, "{}", val),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+impl From<CurveError> for SignatureError {
+    fn from(error: CurveError) -> Self {
+        SignatureError::CurveError(error)
+    }
+}
+
+impl From<PublicKeyError> for SignatureError {
+    fn from(error: PublicKeyError) -> Self {
+        SignatureError::PublicKeyError(error)
+    }
+}
+
+#[derive(Debug)]
+pub enum Eip712Error {
+    UnknownType(String),
+    BadValue(String),
+    NoMessages,
+    MismatchedMsgTypes,
+    PrivateKeyError(PrivateKeyError),
+}
+
+impl fmt::Display for Eip712Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FormatResult {
+        match self {
+            Eip712Error::UnknownType(val) => write!(f, "Eip712Error unknown type {}", val),
+            Eip712Error::BadValue(val) => {
+                write!(f, "Eip712Error value does not match type {}", val)
+            }
+            Eip712Error::NoMessages => write!(f, "Eip712Error transaction has no messages"),
+            Eip712Error::MismatchedMsgTypes => write!(
+                f,
+                "Eip712Error all messages in a transaction must be of the same type"
+            ),
+            Eip712Error::PrivateKeyError(val) => write!(f, "{}", val),
+        }
+    }
+}
+
+impl std::error::Error for Eip712Error {}
+
+impl From<PrivateKeyError> for Eip712Error {
+    fn from(error: PrivateKeyError) -> Self {
+        Eip712Error::PrivateKeyError(error)
+    }
+}
+
+impl From<AddressError> for Eip712Error {
+    fn from(error: AddressError) -> Self {
+        Eip712Error::PrivateKeyError(error.into())
+    }
+}
+
+impl From<SignatureError> for Eip712Error {
+    fn from(error: SignatureError) -> Self {
+        Eip712Error::PrivateKeyError(error.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoinError {
+    /// Adding amounts of this denom overflowed a Uint256
+    Overflow { denom: String },
+    /// The amount is larger than a Cosmos SDK Int can hold
+    ExceedsSdkLimit { denom: String },
+    /// Coins of different denoms can not be added
+    MismatchedDenoms { left: String, right: String },
+    /// The Cosmos SDK rejects coin lists with the same denom more than once
+    DuplicateDenom { denom: String },
+    /// The amount is not an unsigned integer
+    InvalidAmount { denom: String, amount: String },
+}
+
+impl fmt::Display for CoinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FormatResult {
+        match self {
+            CoinError::Overflow { denom } => write!(f, "CoinError {} amount overflowed", denom),
+            CoinError::ExceedsSdkLimit { denom } => write!(
+                f,
+                "CoinError {} amount is too large for the Cosmos SDK",
+                denom
+            ),
+            CoinError::MismatchedDenoms { left, right } => {
+                write!(f, "CoinError can not add {} to {}", right, left)
+            }
+            CoinError::DuplicateDenom { denom } => {
+                write!(f, "CoinError {} appears more than once", denom)
+            }
+            CoinError::InvalidAmount { denom, amount } => {
+                write!(f, "CoinError {} amount {} is not valid", denom, amount)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CoinError {}
+
+/// A transaction that the chain is certain to reject, caught before signing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxValidationError {
+    /// The Cosmos SDK rejects transactions without any messages
+    NoMessages,
+    /// The fee has a gas limit of zero
+    ZeroGas,
+    /// The memo is longer than the chain's max_memo_characters auth param,
+    /// both values are in bytes
+    MemoTooLong { length: usize, max: u64 },
+}
+
+impl fmt::Display for TxValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FormatResult {
+        match self {
+            TxValidationError::NoMessages => write!(f, "TxValidationError no messages"),
+            TxValidationError::ZeroGas => write!(f, "TxValidationError gas limit is zero"),
+            TxValidationError::MemoTooLong { length, max } => write!(
+                f,
+                "TxValidationError memo is {} bytes, the maximum is {}",
+                length, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TxValidationError {}
+
+/// A signed transaction from a third party that the chain would reject, or that could
+/// be altered without invalidating its signatures, see `tx::verify_tx_raw`. Signer
+/// indexes are in AuthInfo order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxVerificationError {
+    DecodeError(DecodeError),
+    /// The TxRaw does not re-encode to the same bytes, so relaying it as is would let
+    /// the same signed transaction be broadcast under several hashes
+    NonCanonicalEncoding,
+    SignatureCount {
+        signers: usize,
+        signatures: usize,
+    },
+    AccountNumberCount {
+        signers: usize,
+        account_numbers: usize,
+    },
+    UnsupportedPublicKey(usize),
+    UnsupportedSignMode(usize),
+    /// Two signer infos carry the same public key
+    DuplicateSigner {
+        first: usize,
+        second: usize,
+    },
+    InvalidSignatureLength {
+        index: usize,
+        length: usize,
+    },
+    /// A 65 byte signature with a recovery id, the chain only accepts compact signatures
+    RecoveryIdPresent(usize),
+    /// The s value is in the upper half of the curve order, the signature is valid but
+    /// malleable and the chain rejects it
+    HighS(usize),
+    InvalidSignature(usize),
+}
+
+impl fmt::Display for TxVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FormatResult {
+        match self {
+            TxVerificationError::DecodeError(val) => {
+                write!(f, "TxVerificationError could not decode {}", val)
+            }
+            TxVerificationError::NonCanonicalEncoding => {
+                write!(f, "TxVerificationError TxRaw is not canonically encoded")
+            }
+            TxVerificationError::SignatureCount {
+                signers,
+                signatures,
+            } => write!(
+                f,
+                "TxVerificationError {} signers but {} signatures",
+                signers, signatures
+            ),
+            TxVerificationError::AccountNumberCount {
+                signers,
+                account_numbers,
+            } => write!(
+                f,
+                "TxVerificationError {} signers but {} account numbers",
+                signers, account_numbers
+            ),
+            TxVerificationError::UnsupportedPublicKey(val) => write!(
+                f,
+                "TxVerificationError public key of signer {} is not secp256k1",
+                val
+            ),
+            TxVerificationError::UnsupportedSignMode(val) => write!(
+                f,
+                "TxVerificationError signer {} does not use SIGN_MODE_DIRECT",
+                val
+            ),
+            TxVerificationError::DuplicateSigner { first, second } => write!(
+                f,
+                "TxVerificationError signers {} and {} have the same public key",
+                first, second
+            ),
+            TxVerificationError::InvalidSignatureLength { index, length } => write!(
+                f,
+                "TxVerificationError signature of signer {} is {} bytes, expected 64",
+                index, length
+            ),
+            TxVerificationError::RecoveryIdPresent(val) => write!(
+                f,
+                "TxVerificationError signature of signer {} carries a recovery id",
+                val
+            ),
+            TxVerificationError::HighS(val) => write!(
+                f,
+                "TxVerificationError signature of signer {} is not low-s",
+                val
+            ),
+            TxVerificationError::InvalidSignature(val) => write!(
+                f,
+                "TxVerificationError signature of signer {} is invalid",
+                val
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TxVerificationError {}
+
+impl From<DecodeError> for TxVerificationError {
+    fn from(error: DecodeError) -> Self {
+        TxVerificationError::DecodeError(error)
+    }
+}
+
 #[derive(Debug)]
 pub enum HdWalletError {
     Bip39Error(Bip39Error),
     InvalidPathSpec(String),
+    /// Hardened children can not be derived from a public key
+    HardenedPublicDerivation(u32),
+    /// The derived child is not a valid key, per BIP32 the next index should be used
+    InvalidChildKey(u32),
+    InvalidXpub(String),
+    /// SLIP-10 ed25519 keys can only derive hardened children
+    NonHardenedEd25519(u32),
 }
 
 impl fmt::Display for HdWalletError {
@@ -355,12 +742,142 @@ impl fmt::Display for HdWalletError {
         match self {
             HdWalletError::Bip39Error(val) => write!(f, "{}", val),
             HdWalletError::InvalidPathSpec(val) => write!(f, "HDWalletError invalid path {}", val),
+            HdWalletError::HardenedPublicDerivation(val) => write!(
+                f,
+                "HDWalletError can not derive hardened child {} from a public key",
+                val
+            ),
+            HdWalletError::InvalidChildKey(val) => {
+                write!(f, "HDWalletError child {} is not a valid key", val)
+            }
+            HdWalletError::InvalidXpub(val) => write!(f, "HDWalletError invalid xpub {}", val),
+            HdWalletError::NonHardenedEd25519(val) => write!(
+                f,
+                "HDWalletError ed25519 keys can not derive non-hardened child {}",
+                val
+            ),
         }
     }
 }
 
 impl std::error::Error for HdWalletError {}
 
+/// Errors loading a seed phrase with `mnemonic::prompt_phrase` or
+/// `mnemonic::from_env_or_file`
+#[derive(Debug)]
+pub enum PhraseInputError {
+    /// Interactive entry needs stdin to be a terminal so the phrase is not echoed or logged
+    NotATerminal,
+    /// Turning off echo is only supported on unix
+    Unsupported,
+    /// Neither the variable nor the file variable is set
+    NotSet(String),
+    /// The file can be read by users other than its owner
+    InsecurePermissions {
+        path: String,
+        mode: u32,
+    },
+    Io(std::io::Error),
+    InvalidPhrase(Bip39Error),
+}
+
+impl fmt::Display for PhraseInputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FormatResult {
+        match self {
+            PhraseInputError::NotATerminal => write!(f, "Phrase entry requires a terminal"),
+            PhraseInputError::Unsupported => {
+                write!(f, "Phrase entry is not supported on this platform")
+            }
+            PhraseInputError::NotSet(var) => write!(f, "Neither {} nor {}_FILE is set", var, var),
+            PhraseInputError::InsecurePermissions { path, mode } => write!(
+                f,
+                "Phrase file {} has mode {:o}, it must not be accessible to group or others",
+                path, mode
+            ),
+            PhraseInputError::Io(e) => write!(f, "Failed to read phrase {}", e),
+            PhraseInputError::InvalidPhrase(e) => write!(f, "Invalid phrase {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PhraseInputError {}
+
+impl From<std::io::Error> for PhraseInputError {
+    fn from(error: std::io::Error) -> Self {
+        PhraseInputError::Io(error)
+    }
+}
+
+impl From<Bip39Error> for PhraseInputError {
+    fn from(error: Bip39Error) -> Self {
+        PhraseInputError::InvalidPhrase(error)
+    }
+}
+
+/// Errors reading a key in the ASCII armored format of the Cosmos SDK keyring
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArmorError {
+    /// The armor framing, base64 body or checksum is malformed
+    InvalidArmor(String),
+    UnsupportedKdf(String),
+    UnsupportedKeyType(String),
+    /// The passphrase is wrong or the ciphertext was modified
+    DecryptionFailed,
+    /// The decrypted bytes are not an amino encoded secp256k1 key
+    InvalidKey,
+}
+
+impl fmt::Display for ArmorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FormatResult {
+        match self {
+            ArmorError::InvalidArmor(val) => write!(f, "Invalid armor {}", val),
+            ArmorError::UnsupportedKdf(val) => write!(f, "Unsupported armor kdf {}", val),
+            ArmorError::UnsupportedKeyType(val) => write!(f, "Unsupported armor key type {}", val),
+            ArmorError::DecryptionFailed => {
+                write!(f, "Failed to decrypt armored key, check the passphrase")
+            }
+            ArmorError::InvalidKey => write!(f, "Armored key is not a secp256k1 key"),
+        }
+    }
+}
+
+impl std::error::Error for ArmorError {}
+
+/// Errors reading a browser wallet key store, see `wallet_json`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalletJsonError {
+    UnsupportedKdf(String),
+    UnsupportedCipher(String),
+    /// Key stores of hardware wallets hold no secret
+    UnsupportedType(String),
+    InvalidParams(String),
+    /// The password is wrong or the key store was modified
+    InvalidMac,
+    /// The decrypted secret is not a mnemonic or private key
+    InvalidSecret,
+}
+
+impl fmt::Display for WalletJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FormatResult {
+        match self {
+            WalletJsonError::UnsupportedKdf(val) => write!(f, "Unsupported key store kdf {}", val),
+            WalletJsonError::UnsupportedCipher(val) => {
+                write!(f, "Unsupported key store cipher {}", val)
+            }
+            WalletJsonError::UnsupportedType(val) => {
+                write!(f, "Key store of type {} holds no key", val)
+            }
+            WalletJsonError::InvalidParams(val) => write!(f, "Invalid key store {}", val),
+            WalletJsonError::InvalidMac => {
+                write!(f, "Key store mac mismatch, check the password")
+            }
+            WalletJsonError::InvalidSecret => write!(f, "Key store secret is not a valid key"),
+        }
+    }
+}
+
+impl std::error::Error for WalletJsonError {}
+
 /// A BIP39 error.
 #[derive(Clone, PartialEq, Eq)]
 pub enum Bip39Error {
@@ -374,6 +891,8 @@ pub enum Bip39Error {
     InvalidChecksum,
     /// The word list can be interpreted as multiple languages.
     AmbiguousWordList(Vec<Language>),
+    /// A BIP85 child index was not below 2^31, child mnemonics use hardened indexes
+    InvalidChildIndex(u32),
 }
 
 impl fmt::Display for Bip39Error {
@@ -396,6 +915,9 @@ impl fmt::Display for Bip39Error {
             Bip39Error::AmbiguousWordList(ref langs) => {
                 write!(f, "ambiguous word list: {:?}", langs)
             }
+            Bip39Error::InvalidChildIndex(i) => {
+                write!(f, "child index must be below 2^31: {}", i)
+            }
         }
     }
 }
@@ -564,3 +1086,186 @@ impl SdkErrorCode {
         }
     }
 }
+
+/// Why a broadcast transaction failed, classified from the code, codespace and raw log
+/// of the TxResponse so that callers can branch on the failure type
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BroadcastError {
+    OutOfGas {
+        gas_wanted: u64,
+        gas_used: u64,
+    },
+    /// The tx was signed with a stale or future sequence, `expected` is the account's
+    /// current sequence according to the node
+    SequenceMismatch {
+        expected: u64,
+        got: u64,
+    },
+    InsufficientFunds,
+    /// The chain does not recognize the type url of one of the messages
+    UnknownMsgType,
+    Unauthorized,
+    MempoolFull,
+    TxTooLarge,
+    /// The tx was accepted into the mempool but was not seen in a block in time
+    NotIncluded,
+    /// Any other error, `code` is only unique within `codespace`
+    Other {
+        codespace: String,
+        code: u32,
+    },
+}
+
+impl BroadcastError {
+    /// Classifies the error in a TxResponse, None if the tx succeeded
+    pub fn from_tx_response(input: &TxResponse) -> Option<BroadcastError> {
+        if input.code == 0 {
+            return None;
+        }
+        let other = || BroadcastError::Other {
+            codespace: input.codespace.clone(),
+            code: input.code,
+        };
+        if input.codespace != "sdk" {
+            return Some(other());
+        }
+        let log = input.raw_log.as_str();
+        let classified = match SdkErrorCode::from_code(input.code) {
+            Some(SdkErrorCode::ErrOutOfGas) => BroadcastError::OutOfGas {
+                gas_wanted: input.gas_wanted as u64,
+                gas_used: input.gas_used as u64,
+            },
+            Some(SdkErrorCode::ErrWrongSequence) | Some(SdkErrorCode::ErrInvalidSequence) => {
+                match parse_sequence_mismatch(log) {
+                    Some((expected, got)) => BroadcastError::SequenceMismatch { expected, got },
+                    None => other(),
+                }
+            }
+            Some(SdkErrorCode::ErrInsufficientFunds) => BroadcastError::InsufficientFunds,
+            Some(SdkErrorCode::ErrUnauthorized) => BroadcastError::Unauthorized,
+            Some(SdkErrorCode::ErrMempoolIsFull) => BroadcastError::MempoolFull,
+            Some(SdkErrorCode::ErrTxTooLarge) => BroadcastError::TxTooLarge,
+            // unregistered type urls fail either while decoding the tx or routing the msg
+            Some(SdkErrorCode::ErrUnknownRequest) | Some(SdkErrorCode::ErrTxDecode)
+                if log.contains("unable to resolve type URL")
+                    || log.contains("unrecognized")
+                    || log.contains("no message handler") =>
+            {
+                BroadcastError::UnknownMsgType
+            }
+            _ => other(),
+        };
+        Some(classified)
+    }
+
+    /// The sdk codespace error this was classified from, if any
+    pub fn sdk_error(&self) -> Option<SdkErrorCode> {
+        match self {
+            BroadcastError::OutOfGas { .. } => Some(SdkErrorCode::ErrOutOfGas),
+            BroadcastError::SequenceMismatch { .. } => Some(SdkErrorCode::ErrWrongSequence),
+            BroadcastError::InsufficientFunds => Some(SdkErrorCode::ErrInsufficientFunds),
+            BroadcastError::UnknownMsgType => Some(SdkErrorCode::ErrUnknownRequest),
+            BroadcastError::Unauthorized => Some(SdkErrorCode::ErrUnauthorized),
+            BroadcastError::MempoolFull => Some(SdkErrorCode::ErrMempoolIsFull),
+            BroadcastError::TxTooLarge => Some(SdkErrorCode::ErrTxTooLarge),
+            BroadcastError::NotIncluded => None,
+            BroadcastError::Other { codespace, code } => {
+                if codespace == "sdk" {
+                    SdkErrorCode::from_code(*code)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Parses the numbers out of "account sequence mismatch, expected 5, got 4: ..."
+fn parse_sequence_mismatch(log: &str) -> Option<(u64, u64)> {
+    let number_after = |marker: &str| -> Option<u64> {
+        let rest = &log[log.find(marker)? + marker.len()..];
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        rest[..end].parse().ok()
+    };
+    Some((number_after("expected ")?, number_after("got ")?))
+}
+
+impl fmt::Display for BroadcastError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FormatResult {
+        match self {
+            BroadcastError::OutOfGas {
+                gas_wanted,
+                gas_used,
+            } => write!(f, "out of gas, wanted {} used {}", gas_wanted, gas_used),
+            BroadcastError::SequenceMismatch { expected, got } => {
+                write!(f, "sequence mismatch, expected {} got {}", expected, got)
+            }
+            BroadcastError::InsufficientFunds => write!(f, "insufficient funds"),
+            BroadcastError::UnknownMsgType => write!(f, "unknown message type"),
+            BroadcastError::Unauthorized => write!(f, "unauthorized"),
+            BroadcastError::MempoolFull => write!(f, "mempool is full"),
+            BroadcastError::TxTooLarge => write!(f, "tx too large"),
+            BroadcastError::NotIncluded => write!(f, "not included in a block"),
+            BroadcastError::Other { codespace, code } => {
+                write!(f, "error code {} in codespace {}", code, codespace)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BroadcastError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_broadcast_error_classification() {
+        let response = |code: u32, codespace: &str, raw_log: &str| TxResponse {
+            code,
+            codespace: codespace.to_string(),
+            raw_log: raw_log.to_string(),
+            gas_wanted: 100,
+            gas_used: 150,
+            ..Default::default()
+        };
+        assert_eq!(BroadcastError::from_tx_response(&response(0, "", "")), None);
+        assert_eq!(
+            BroadcastError::from_tx_response(&response(
+                32,
+                "sdk",
+                "account sequence mismatch, expected 12, got 10: incorrect account sequence"
+            )),
+            Some(BroadcastError::SequenceMismatch {
+                expected: 12,
+                got: 10
+            })
+        );
+        assert_eq!(
+            BroadcastError::from_tx_response(&response(11, "sdk", "out of gas")),
+            Some(BroadcastError::OutOfGas {
+                gas_wanted: 100,
+                gas_used: 150
+            })
+        );
+        assert_eq!(
+            BroadcastError::from_tx_response(&response(
+                2,
+                "sdk",
+                "unable to resolve type URL /foo.MsgBar: tx parse error"
+            )),
+            Some(BroadcastError::UnknownMsgType)
+        );
+        assert_eq!(
+            BroadcastError::from_tx_response(&response(2, "sdk", "tx parse error")),
+            Some(BroadcastError::Other {
+                codespace: "sdk".to_string(),
+                code: 2
+            })
+        );
+        let module = BroadcastError::from_tx_response(&response(5, "bank", "")).unwrap();
+        assert_eq!(module.sdk_error(), None);
+    }
+}
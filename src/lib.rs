@@ -1,34 +1,77 @@
 #![warn(clippy::all)]
 #![allow(clippy::pedantic)]
+#![allow(clippy::result_large_err)]
 #![forbid(unsafe_code)]
 
+// with the tracing feature the same logging macros are provided by tracing,
+// which still emits log records when no tracing subscriber is installed
+#[cfg(not(feature = "tracing"))]
 #[macro_use]
 extern crate log;
+#[cfg(feature = "tracing")]
+#[macro_use]
+extern crate tracing;
 #[macro_use]
 extern crate serde_derive;
 
 pub mod address;
+#[cfg(feature = "armor")]
+pub mod armor;
 pub mod client;
 pub mod coin;
+#[cfg(any(feature = "sqlx", feature = "diesel"))]
+pub mod db;
 pub mod decimal;
+#[cfg(feature = "ed25519")]
+pub mod ed25519;
+#[cfg(feature = "ethermint")]
+pub mod eip712;
 pub mod error;
 pub mod mnemonic;
 pub mod msg;
 pub mod private_key;
+mod proto_json;
 pub mod public_key;
+pub mod registry;
 pub mod signature;
+pub mod summary;
+#[cfg(feature = "test_vectors")]
+pub mod test_vectors;
+pub mod tx;
+pub mod type_url;
 pub mod utils;
+#[cfg(feature = "wallet-json")]
+pub mod wallet_json;
+pub mod xpub;
 
 pub use address::Address;
+pub use address::Bech32Variant;
 pub use client::Contact;
 pub use coin::Coin;
+pub use coin::CoinFormat;
+pub use coin::DecCoin;
 pub use coin::Fee;
 pub use mnemonic::Mnemonic;
+pub use mnemonic::SecretMnemonic;
 pub use msg::Msg;
+pub use msg::ValidatorCommission;
 pub use private_key::MessageArgs;
 pub use private_key::PrivateKey;
+pub use proto_json::CanonicalJson;
 pub use public_key::PublicKey;
+pub use registry::AnyRegistry;
 pub use signature::Signature;
+pub use summary::TxFormatter;
+pub use tx::AsyncSigner;
+pub use tx::DecodedTx;
+pub use tx::MultiSignerTx;
+pub use tx::PartiallySignedTx;
+pub use tx::SignMode;
+pub use tx::TxBuilder;
+pub use tx::TxSigner;
+pub use tx::UnsignedTx;
+pub use type_url::TypeUrl;
+pub use xpub::Xpub;
 
 pub use u64_array_bigints::u256;
 pub use u64_array_bigints::U256 as Uint256;
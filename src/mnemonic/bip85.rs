@@ -0,0 +1,146 @@
+//! BIP85 deterministic entropy, child mnemonics derived from a root mnemonic. Each child
+//! is a fully independent phrase, so one backup of the root can restore the keys of
+//! many services while a leaked child reveals nothing about the root or its siblings
+//! See: <https://github.com/bitcoin/bips/blob/master/bip-0085.mediawiki>
+
+use super::{Language, Mnemonic};
+use crate::error::Bip39Error;
+use crate::private_key::HdNode;
+use crate::xpub::HARDENED_OFFSET;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+/// The purpose of every BIP85 path, m/83696968'
+const BIP85_PURPOSE: u32 = 83696968;
+/// The application number of BIP39 mnemonics
+const BIP39_APPLICATION: u32 = 39;
+
+/// The BIP85 code of a word list
+fn language_code(language: Language) -> u32 {
+    match language {
+        Language::English => 0,
+        Language::Japanese => 1,
+        Language::Korean => 2,
+        Language::Spanish => 3,
+        Language::SimplifiedChinese => 4,
+        Language::TraditionalChinese => 5,
+        Language::French => 6,
+        Language::Italian => 7,
+        Language::Czech => 8,
+    }
+}
+
+/// Derives the 64 bytes of entropy at the hardened `path` below `root`, the HMAC-SHA512
+/// of the derived private key
+fn derive_entropy(root: &HdNode, path: &[u32]) -> Zeroizing<Vec<u8>> {
+    let mut node = root.child(BIP85_PURPOSE, true);
+    for index in path {
+        node = node.child(*index, true);
+    }
+    let mut hmac = Hmac::<Sha512>::new_from_slice(b"bip-entropy-from-k").unwrap();
+    hmac.update(&node.secret_key);
+    Zeroizing::new(hmac.finalize().into_bytes().to_vec())
+}
+
+/// Derives the child mnemonic at m/83696968'/39'/language'/word_count'/index'
+fn derive_mnemonic(
+    root: &HdNode,
+    language: Language,
+    word_count: usize,
+    index: u32,
+) -> Result<Mnemonic, Bip39Error> {
+    if !matches!(word_count, 12 | 18 | 24) {
+        return Err(Bip39Error::BadWordCount(word_count));
+    }
+    if index >= HARDENED_OFFSET {
+        return Err(Bip39Error::InvalidChildIndex(index));
+    }
+    let path = [
+        BIP39_APPLICATION,
+        language_code(language),
+        word_count as u32,
+        index,
+    ];
+    let entropy = derive_entropy(root, &path);
+    Mnemonic::from_entropy_in(language, &entropy[..word_count / 3 * 4])
+}
+
+/// The BIP32 root of the seed of `mnemonic`
+fn root_node(mnemonic: &Mnemonic, passphrase: &str) -> HdNode {
+    let seed = Zeroizing::new(mnemonic.to_seed(passphrase));
+    // m is always a valid path
+    HdNode::from_seed_and_path("m", &seed).unwrap()
+}
+
+impl Mnemonic {
+    /// Derives the English child mnemonic of `word_count` words at `index` following
+    /// BIP85, so that any BIP85 wallet restores the same child from this phrase. Word
+    /// counts of 12, 18 and 24 are supported and `index` must be below 2^31
+    pub fn derive_child_mnemonic(
+        &self,
+        passphrase: &str,
+        word_count: usize,
+        index: u32,
+    ) -> Result<Mnemonic, Bip39Error> {
+        self.derive_child_mnemonic_in(Language::English, passphrase, word_count, index)
+    }
+
+    /// Derives a child mnemonic in `language`, see [Mnemonic::derive_child_mnemonic]
+    pub fn derive_child_mnemonic_in(
+        &self,
+        language: Language,
+        passphrase: &str,
+        word_count: usize,
+        index: u32,
+    ) -> Result<Mnemonic, Bip39Error> {
+        derive_mnemonic(&root_node(self, passphrase), language, word_count, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_str_to_bytes;
+
+    /// The master key of the BIP85 test vectors
+    fn test_root() -> HdNode {
+        let xprv = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+        let bytes = bs58::decode(xprv).with_check(None).into_vec().unwrap();
+        let mut chain_code = [0; 32];
+        let mut secret_key = [0; 32];
+        chain_code.copy_from_slice(&bytes[13..45]);
+        secret_key.copy_from_slice(&bytes[46..78]);
+        HdNode {
+            secret_key,
+            chain_code,
+            depth: 0,
+            parent_fingerprint: [0; 4],
+            child_number: 0,
+        }
+    }
+
+    #[test]
+    fn test_bip85_vectors() {
+        let root = test_root();
+        let entropy = derive_entropy(&root, &[0, 0]);
+        assert_eq!(
+            entropy[..],
+            hex_str_to_bytes("efecfbccffea313214232d29e71563d941229afb4338c21f9517c41aaa0d16f00b83d2a09ef747e7a64e8e2bd5a14869e693da66ce94ac2da570ab7ee48618f7").unwrap()[..]
+        );
+
+        let child = derive_mnemonic(&root, Language::English, 12, 0).unwrap();
+        assert_eq!(
+            child.as_str(),
+            "girl mad pet galaxy egg matter matrix prison refuse sense ordinary nose"
+        );
+        assert!(derive_mnemonic(&root, Language::English, 15, 0).is_err());
+        assert!(derive_mnemonic(&root, Language::English, 12, 1 << 31).is_err());
+
+        let root = Mnemonic::generate(24).unwrap();
+        let child = root.derive_child_mnemonic("", 24, 1).unwrap();
+        assert_eq!(child.word_count(), 24);
+        assert_eq!(child, root.derive_child_mnemonic("", 24, 1).unwrap());
+        assert_ne!(child, root.derive_child_mnemonic("", 24, 2).unwrap());
+    }
+}
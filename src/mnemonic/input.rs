@@ -0,0 +1,134 @@
+//! Standard ways for services and tools to load a seed phrase without it ending up in
+//! shell history, terminal scrollback or a world readable file
+
+use super::SecretMnemonic;
+use crate::error::PhraseInputError;
+use std::convert::TryFrom;
+use std::env;
+use std::io::{stdin, stdout, IsTerminal, Write};
+use zeroize::Zeroizing;
+
+/// Restores terminal echo when dropped, including when reading the phrase fails
+#[cfg(unix)]
+struct EchoGuard;
+
+#[cfg(unix)]
+impl EchoGuard {
+    fn new() -> Result<EchoGuard, PhraseInputError> {
+        stty("-echo")?;
+        Ok(EchoGuard)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EchoGuard {
+    fn drop(&mut self) {
+        let _ = stty("echo");
+    }
+}
+
+#[cfg(unix)]
+fn stty(setting: &str) -> Result<(), PhraseInputError> {
+    use std::process::{Command, Stdio};
+    let status = Command::new("stty")
+        .arg(setting)
+        .stdin(Stdio::inherit())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(PhraseInputError::Unsupported)
+    }
+}
+
+/// Prints `prompt` and reads a phrase from the terminal with echo turned off. Fails
+/// if stdin is not a terminal, so that a phrase is never read from a pipe by accident
+#[cfg(unix)]
+pub fn prompt_phrase(prompt: &str) -> Result<SecretMnemonic, PhraseInputError> {
+    if !stdin().is_terminal() {
+        return Err(PhraseInputError::NotATerminal);
+    }
+    let mut out = stdout();
+    write!(out, "{}", prompt)?;
+    out.flush()?;
+    let mut line = Zeroizing::new(String::new());
+    {
+        let _echo = EchoGuard::new()?;
+        stdin().read_line(&mut line)?;
+    }
+    writeln!(out)?;
+    Ok(SecretMnemonic::parse(line.trim())?)
+}
+
+/// Interactive entry is only supported on unix
+#[cfg(not(unix))]
+pub fn prompt_phrase(_prompt: &str) -> Result<SecretMnemonic, PhraseInputError> {
+    Err(PhraseInputError::Unsupported)
+}
+
+/// Loads a phrase from the environment variable `var`, or if that is not set from the
+/// file named by `{var}_FILE` as used for container secrets. On unix the file must not
+/// be accessible to group or others
+pub fn from_env_or_file(var: &str) -> Result<SecretMnemonic, PhraseInputError> {
+    if let Ok(phrase) = env::var(var) {
+        return Ok(SecretMnemonic::try_from(phrase)?);
+    }
+    let path = match env::var(format!("{}_FILE", var)) {
+        Ok(path) => path,
+        Err(_) => return Err(PhraseInputError::NotSet(var.to_string())),
+    };
+    check_permissions(&path)?;
+    let contents = Zeroizing::new(std::fs::read_to_string(&path)?);
+    Ok(SecretMnemonic::parse(contents.trim())?)
+}
+
+#[cfg(unix)]
+fn check_permissions(path: &str) -> Result<(), PhraseInputError> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(path)?.permissions().mode() & 0o777;
+    if mode & 0o077 != 0 {
+        return Err(PhraseInputError::InsecurePermissions {
+            path: path.to_string(),
+            mode,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &str) -> Result<(), PhraseInputError> {
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    const PHRASE: &str = "purse sure leg gap above pull rescue glass circle attract erupt can sail gasp shy clarify inflict anger sketch hobby scare mad reject where";
+
+    #[test]
+    fn test_from_env_or_file() {
+        let var = "DEEP_SPACE_TEST_INPUT_PHRASE";
+        assert!(matches!(
+            from_env_or_file(var),
+            Err(PhraseInputError::NotSet(_))
+        ));
+
+        let path = env::temp_dir().join(format!("deep_space_phrase_{}", std::process::id()));
+        std::fs::write(&path, format!("{}\n", PHRASE)).unwrap();
+        env::set_var(format!("{}_FILE", var), &path);
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(matches!(
+            from_env_or_file(var),
+            Err(PhraseInputError::InsecurePermissions { mode: 0o644, .. })
+        ));
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        let from_file = from_env_or_file(var).unwrap();
+        assert_eq!(from_file.expose_phrase(), PHRASE);
+        std::fs::remove_file(&path).unwrap();
+
+        env::set_var(var, PHRASE);
+        assert_eq!(from_env_or_file(var).unwrap().expose_phrase(), PHRASE);
+    }
+}
@@ -1,13 +1,23 @@
 use std::fmt;
 
+// the word lists stay consts so they remain usable in const contexts
+#[allow(clippy::large_const_arrays)]
 mod chinese_simplified;
+#[allow(clippy::large_const_arrays)]
 mod chinese_traditional;
+#[allow(clippy::large_const_arrays)]
 mod czech;
+#[allow(clippy::large_const_arrays)]
 mod english;
+#[allow(clippy::large_const_arrays)]
 mod french;
+#[allow(clippy::large_const_arrays)]
 mod italian;
+#[allow(clippy::large_const_arrays)]
 mod japanese;
+#[allow(clippy::large_const_arrays)]
 mod korean;
+#[allow(clippy::large_const_arrays)]
 mod spanish;
 
 /// Language to be used for the mnemonic phrase.
@@ -182,7 +192,7 @@ mod tests {
 
         for &(sum, lang) in &checksums {
             let mut hasher = Sha256::new();
-            for (_idx, word) in lang.word_list().iter().enumerate() {
+            for word in lang.word_list().iter() {
                 assert!(::unicode_normalization::is_nfkd(word));
                 hasher.update(format!("{}\n", word));
             }
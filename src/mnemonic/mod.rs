@@ -6,9 +6,14 @@
 //! If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
 //! This is only compiled if the bip39 feature is enabled.
 
+mod bip85;
+mod input;
 mod language;
+mod secret;
 
+pub use input::{from_env_or_file, prompt_phrase};
 pub use language::Language;
+pub use secret::SecretMnemonic;
 
 use crate::error::*;
 use fmt::Debug;
@@ -44,7 +49,7 @@ impl Mnemonic {
     /// Create a new [Mnemonic] in the specified language from the given entropy.
     /// Entropy must be a multiple of 32 bits (4 bytes) and 128-256 bits in length.
     pub fn from_entropy_in(language: Language, entropy: &[u8]) -> Result<Mnemonic, Bip39Error> {
-        if entropy.len() % 4 != 0 {
+        if !entropy.len().is_multiple_of(4) {
             return Err(Bip39Error::BadEntropyBitCount(entropy.len() * 8));
         }
 
@@ -53,7 +58,7 @@ impl Mnemonic {
         }
 
         let mut hasher = Sha256::new();
-        hasher.update(&entropy);
+        hasher.update(entropy);
         let check = hasher.finalize();
         let mut bits = vec![false; entropy.len() * 8 + entropy.len() / 4];
         for i in 0..entropy.len() {
@@ -88,7 +93,7 @@ impl Mnemonic {
     /// Generate a new Mnemonic in the given language.
     /// For the different supported word counts, see documentation on [Mnemonoc].
     pub fn generate_in(language: Language, word_count: usize) -> Result<Mnemonic, Bip39Error> {
-        if word_count < 6 || word_count % 6 != 0 || word_count > 24 {
+        if word_count < 6 || !word_count.is_multiple_of(6) || word_count > 24 {
             return Err(Bip39Error::BadWordCount(word_count));
         }
 
@@ -108,7 +113,7 @@ impl Mnemonic {
     /// Static method to validate a mnemonic in a given language.
     pub fn validate_in(language: Language, s: &str) -> Result<(), Bip39Error> {
         let words: Vec<&str> = s.split_whitespace().collect();
-        if words.len() < 6 || words.len() % 6 != 0 || words.len() > 24 {
+        if words.len() < 6 || !words.len().is_multiple_of(6) || words.len() > 24 {
             return Err(Bip39Error::BadWordCount(words.len()));
         }
 
@@ -0,0 +1,173 @@
+use super::Mnemonic;
+use crate::error::{Bip39Error, PrivateKeyError};
+use crate::private_key::PrivateKey;
+use crate::xpub::Xpub;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use zeroize::{Zeroize, Zeroizing};
+
+/// A [Mnemonic] that is treated as secret material. The phrase is zeroed when
+/// this value is dropped, seeds are handed out wrapped in [Zeroizing] and the
+/// Debug and Display implementations never print any words. Errors produced
+/// while parsing also have any offending word redacted. Intended for long running
+/// services that load a phrase once, for example from an environment variable,
+/// and hold it in memory.
+///
+/// ```rust
+/// use deep_space::SecretMnemonic;
+/// use std::convert::TryFrom;
+/// # std::env::set_var("MY_PHRASE", "purse sure leg gap above pull rescue glass circle attract erupt can sail gasp shy clarify inflict anger sketch hobby scare mad reject where");
+/// // the String returned by env::var is zeroed once it has been parsed
+/// let phrase = SecretMnemonic::try_from(std::env::var("MY_PHRASE").unwrap()).unwrap();
+/// let key = phrase.to_private_key("").unwrap();
+/// assert_eq!(format!("{:?}", phrase), "SecretMnemonic([REDACTED])");
+/// ```
+#[derive(Clone)]
+pub struct SecretMnemonic(Mnemonic);
+
+impl SecretMnemonic {
+    /// Parses a phrase without taking ownership of it, callers are responsible
+    /// for clearing their own copy of `phrase`
+    pub fn parse(phrase: &str) -> Result<SecretMnemonic, Bip39Error> {
+        match Mnemonic::parse(phrase) {
+            Ok(m) => Ok(SecretMnemonic(m)),
+            Err(Bip39Error::UnknownWord(mut word)) => {
+                word.zeroize();
+                Err(Bip39Error::UnknownWord("[REDACTED]".to_string()))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Provides access to the underlying phrase, avoid copying the result into
+    /// storage that will not be cleared
+    pub fn expose_phrase(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Get the number of words in the mnemonic.
+    pub fn word_count(&self) -> usize {
+        self.0.word_count()
+    }
+
+    /// Computes the BIP39 seed for this phrase, the seed is zeroed when the returned
+    /// value is dropped
+    pub fn to_seed(&self, passphrase: &str) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.0.to_seed(passphrase))
+    }
+
+    /// Derives the key at m/44'/118'/0'/0/0, see [PrivateKey::from_phrase]
+    pub fn to_private_key(&self, passphrase: &str) -> Result<PrivateKey, PrivateKeyError> {
+        self.to_private_key_path("m/44'/118'/0'/0/0", passphrase)
+    }
+
+    /// Derives the key at the given HD wallet path, see [PrivateKey::from_hd_wallet_path]
+    pub fn to_private_key_path(
+        &self,
+        path: &str,
+        passphrase: &str,
+    ) -> Result<PrivateKey, PrivateKeyError> {
+        PrivateKey::from_seed_and_path(path, &self.to_seed(passphrase))
+    }
+
+    /// Derives the extended public key at the given HD wallet path, see [Xpub]
+    pub fn to_xpub(&self, path: &str, passphrase: &str) -> Result<Xpub, PrivateKeyError> {
+        Xpub::from_seed_and_path(path, &self.to_seed(passphrase))
+    }
+
+    /// Derives the SLIP-10 ed25519 key at the given path, every index must be hardened
+    #[cfg(feature = "ed25519")]
+    pub fn to_ed25519_key(
+        &self,
+        path: &str,
+        passphrase: &str,
+    ) -> Result<crate::ed25519::Ed25519PrivateKey, PrivateKeyError> {
+        crate::ed25519::Ed25519PrivateKey::from_seed_and_path(path, &self.to_seed(passphrase))
+    }
+
+    /// Derives the English child mnemonic of `word_count` words at `index` following
+    /// BIP85, see [Mnemonic::derive_child_mnemonic]
+    pub fn derive_child_mnemonic(
+        &self,
+        passphrase: &str,
+        word_count: usize,
+        index: u32,
+    ) -> Result<SecretMnemonic, Bip39Error> {
+        self.0
+            .derive_child_mnemonic(passphrase, word_count, index)
+            .map(SecretMnemonic::from)
+    }
+}
+
+impl Drop for SecretMnemonic {
+    fn drop(&mut self) {
+        (self.0).0.zeroize();
+    }
+}
+
+impl From<Mnemonic> for SecretMnemonic {
+    fn from(mnemonic: Mnemonic) -> Self {
+        SecretMnemonic(mnemonic)
+    }
+}
+
+/// Takes ownership of the phrase and zeroes it once parsed, whether or not
+/// parsing succeeds
+impl TryFrom<String> for SecretMnemonic {
+    type Error = Bip39Error;
+
+    fn try_from(phrase: String) -> Result<Self, Self::Error> {
+        let phrase = Zeroizing::new(phrase);
+        SecretMnemonic::parse(&phrase)
+    }
+}
+
+impl FromStr for SecretMnemonic {
+    type Err = Bip39Error;
+
+    fn from_str(s: &str) -> Result<SecretMnemonic, Bip39Error> {
+        SecretMnemonic::parse(s)
+    }
+}
+
+impl fmt::Debug for SecretMnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretMnemonic([REDACTED])")
+    }
+}
+
+impl fmt::Display for SecretMnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHRASE: &str = "purse sure leg gap above pull rescue glass circle attract erupt can sail gasp shy clarify inflict anger sketch hobby scare mad reject where";
+
+    #[test]
+    fn test_secret_mnemonic() {
+        let secret: SecretMnemonic = PHRASE.parse().unwrap();
+        assert_eq!(format!("{:?}", secret), "SecretMnemonic([REDACTED])");
+        assert_eq!(secret.to_string(), "[REDACTED]");
+        assert_eq!(secret.word_count(), 24);
+        assert_eq!(
+            secret.to_private_key("").unwrap(),
+            PrivateKey::from_phrase(PHRASE, "").unwrap()
+        );
+        assert_eq!(
+            *secret.to_seed("test"),
+            Mnemonic::parse(PHRASE).unwrap().to_seed("test")
+        );
+
+        let bad = PHRASE.replace("purse", "pursx");
+        match SecretMnemonic::try_from(bad) {
+            Err(Bip39Error::UnknownWord(w)) => assert_eq!(w, "[REDACTED]"),
+            _ => panic!("Expected an unknown word error"),
+        }
+    }
+}
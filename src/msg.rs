@@ -2,18 +2,143 @@
 
 use prost_types::Any;
 
+use crate::decimal::Decimal;
+use crate::error::CosmosGrpcError;
+use crate::proto_json::ProtoJson;
+use crate::type_url::TypeUrl;
 use crate::utils::encode_any;
+use crate::{Address, Coin, Uint256};
+use cosmos_sdk_proto::cosmos::crypto::ed25519::PubKey as Ed25519PubKey;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::MsgUnjail;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::{
+    CommissionRates, Description, MsgCreateValidator, MsgEditValidator,
+};
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// The type url of an ed25519 public key, used for validator consensus keys
+pub const ED25519_PUBKEY_TYPE_URL: &str = "/cosmos.crypto.ed25519.PubKey";
 
 /// Transaction messages, encoded to allow arbitrary payloads
 #[derive(Debug, Clone, PartialEq)]
 pub struct Msg(pub(crate) Any);
 
+/// The commission settings of a new validator, the max rate and max change
+/// rate can never be modified after the validator is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidatorCommission {
+    pub rate: Decimal,
+    pub max_rate: Decimal,
+    pub max_change_rate: Decimal,
+}
+
+impl From<ValidatorCommission> for CommissionRates {
+    fn from(value: ValidatorCommission) -> Self {
+        CommissionRates {
+            rate: value.rate.to_proto_string(),
+            max_rate: value.max_rate.to_proto_string(),
+            max_change_rate: value.max_change_rate.to_proto_string(),
+        }
+    }
+}
+
 impl Msg {
     /// Create a new transaction message
     pub fn new<V: prost::Message>(type_url: impl Into<String>, value: V) -> Self {
         let any = encode_any(value, type_url);
         Msg(any)
     }
+
+    /// Create a new transaction message from any proto message with a known type url
+    pub fn from_proto<V: prost::Message + TypeUrl>(value: V) -> Self {
+        Msg::new(V::TYPE_URL, value)
+    }
+
+    /// The type url of this message
+    pub fn type_url(&self) -> &str {
+        &self.0.type_url
+    }
+
+    /// Decodes the inner proto message, an error if this message is of another type
+    pub fn decode_as<V: prost::Message + TypeUrl + Default>(&self) -> Result<V, CosmosGrpcError> {
+        if self.0.type_url != V::TYPE_URL {
+            return Err(CosmosGrpcError::BadInput(format!(
+                "Expected a {} message but found {}",
+                V::TYPE_URL,
+                self.0.type_url
+            )));
+        }
+        Ok(V::decode(self.0.value.as_slice())?)
+    }
+
+    /// Creates a MsgUnjail for the provided validator operator (valoper) address
+    pub fn unjail(validator_address: Address) -> Self {
+        let unjail = MsgUnjail {
+            validator_addr: validator_address.to_string(),
+        };
+        Msg::new("/cosmos.slashing.v1beta1.MsgUnjail", unjail)
+    }
+
+    /// Creates a MsgEditValidator, fields of the description set to "[do-not-modify]"
+    /// are left unchanged by the chain. Passing None for the commission rate or min self
+    /// delegation leaves them unchanged.
+    pub fn edit_validator(
+        validator_address: Address,
+        description: Description,
+        commission_rate: Option<Decimal>,
+        min_self_delegation: Option<Uint256>,
+    ) -> Self {
+        let edit = MsgEditValidator {
+            description: Some(description),
+            validator_address: validator_address.to_string(),
+            commission_rate: commission_rate
+                .map(|v| v.to_proto_string())
+                .unwrap_or_default(),
+            min_self_delegation: min_self_delegation
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        };
+        Msg::new("/cosmos.staking.v1beta1.MsgEditValidator", edit)
+    }
+
+    /// Creates a MsgCreateValidator
+    ///
+    /// # Arguments
+    ///
+    /// * `delegator_address` - the account creating the validator and making the initial self delegation
+    /// * `validator_address` - the valoper address of the same account
+    /// * `consensus_pubkey` - the raw ed25519 public key the validator signs blocks with
+    /// * `description` - the moniker and other public info of the validator
+    /// * `commission` - the commission rates of the validator
+    /// * `min_self_delegation` - the validator is jailed if the self delegation falls below this
+    /// * `value` - the initial self delegation
+    pub fn create_validator(
+        delegator_address: Address,
+        validator_address: Address,
+        consensus_pubkey: [u8; 32],
+        description: Description,
+        commission: ValidatorCommission,
+        min_self_delegation: Uint256,
+        value: Coin,
+    ) -> Self {
+        let pubkey = encode_any(
+            Ed25519PubKey {
+                key: consensus_pubkey.to_vec(),
+            },
+            ED25519_PUBKEY_TYPE_URL,
+        );
+        let create = MsgCreateValidator {
+            description: Some(description),
+            commission: Some(commission.into()),
+            min_self_delegation: min_self_delegation.to_string(),
+            delegator_address: delegator_address.to_string(),
+            validator_address: validator_address.to_string(),
+            pubkey: Some(pubkey),
+            value: Some(value.into()),
+        };
+        Msg::new("/cosmos.staking.v1beta1.MsgCreateValidator", create)
+    }
 }
 
 impl From<Any> for Msg {
@@ -27,3 +152,46 @@ impl From<Msg> for Any {
         msg.0
     }
 }
+
+/// Serializes to the proto3 JSON representation, an object with the type url in an
+/// `@type` field followed by the camelCase message fields. Messages deep_space does
+/// not know are represented by their base64 encoded bytes in a `value` field. Binary
+/// formats hold the type url followed by the proto encoded message
+impl Serialize for Msg {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            self.0.to_json().serialize(serializer)
+        } else {
+            (&self.0.type_url, &self.0.value).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Msg {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let value = Value::deserialize(deserializer)?;
+            Any::from_json(&value).map(Msg).map_err(D::Error::custom)
+        } else {
+            let (type_url, value) = <(String, Vec<u8>)>::deserialize(deserializer)?;
+            Ok(Msg(Any { type_url, value }))
+        }
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl borsh::BorshSerialize for Msg {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        borsh::BorshSerialize::serialize(&self.0.type_url, writer)?;
+        borsh::BorshSerialize::serialize(&self.0.value, writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl borsh::BorshDeserialize for Msg {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let type_url = <String as borsh::BorshDeserialize>::deserialize_reader(reader)?;
+        let value = <Vec<u8> as borsh::BorshDeserialize>::deserialize_reader(reader)?;
+        Ok(Msg(Any { type_url, value }))
+    }
+}
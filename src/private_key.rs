@@ -1,74 +1,166 @@
-use crate::mnemonic::Mnemonic;
+use crate::mnemonic::SecretMnemonic;
 use crate::msg::Msg;
 use crate::public_key::PublicKey;
+use crate::signature::Signature;
+use crate::tx::{AsyncSigner, TxParts, UnsignedTx};
 use crate::utils::bytes_to_hex_str;
-use crate::utils::encode_any;
 use crate::utils::hex_str_to_bytes;
+use crate::xpub::{fingerprint, HARDENED_OFFSET};
 use crate::{coin::Fee, Address};
 use crate::{error::*, utils::contains_non_hex_chars};
-use cosmos_sdk_proto::cosmos::crypto::secp256k1::PubKey as ProtoSecp256k1Pubkey;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::Tx;
-use cosmos_sdk_proto::cosmos::tx::v1beta1::{
-    mode_info, AuthInfo, ModeInfo, SignDoc, SignerInfo, TxBody, TxRaw,
-};
-use num::BigUint;
-use prost::Message;
+use prost_types::Any;
 use secp256k1::constants::CURVE_ORDER as CurveN;
 use secp256k1::scalar::Scalar;
 use secp256k1::Message as CurveMessage;
 use secp256k1::Secp256k1;
 use secp256k1::{PublicKey as PublicKeyEC, SecretKey};
+use serde::{Serialize, Serializer};
 use sha2::Sha512;
 use sha2::{Digest, Sha256};
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
+use std::time::SystemTime;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::{Zeroize, Zeroizing};
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct MessageArgs {
     pub sequence: u64,
     pub fee: Fee,
     pub timeout_height: u64,
+    /// A timeout this many blocks after the latest height, for callers that do not track
+    /// the head of the chain. `Contact::resolve_message_args` turns it into
+    /// `timeout_height`, a tx can not be signed while it is set
+    pub relative_timeout: Option<u64>,
     pub chain_id: String,
     pub account_number: u64,
+    /// Extension options attached to the TxBody, the chain will reject the tx
+    /// if it does not recognize any of these
+    pub extension_options: Vec<Any>,
+    /// Extension options attached to the TxBody that the chain may ignore
+    pub non_critical_extension_options: Vec<Any>,
+    /// Marks the tx as unordered (Cosmos SDK 0.53+), unordered transactions are
+    /// deduplicated by hash and `timeout_timestamp` rather than by account sequence
+    /// so many can be in flight at once. Requires `timeout_timestamp` to be set.
+    pub unordered: bool,
+    /// Time after which the chain will no longer include this tx (Cosmos SDK 0.53+)
+    pub timeout_timestamp: Option<SystemTime>,
+    /// The chain's max_memo_characters auth param, if set longer memos are rejected
+    /// before signing
+    pub max_memo_characters: Option<u64>,
 }
 
-struct TxParts {
-    body: TxBody,
-    body_buf: Vec<u8>,
-    auth_info: AuthInfo,
-    auth_buf: Vec<u8>,
-    signatures: Vec<Vec<u8>>,
+// prost_types::Any only derives PartialEq, but it is a type url and bytes so its
+// equality is total and every field of MessageArgs is Eq
+impl Eq for MessageArgs {}
+
+impl MessageArgs {
+    /// Sets `timeout_height` from `relative_timeout` given the latest height, does
+    /// nothing if no relative timeout is set
+    pub fn resolve_relative_timeout(&mut self, current_height: u64) {
+        if let Some(blocks) = self.relative_timeout.take() {
+            self.timeout_height = current_height.saturating_add(blocks);
+        }
+    }
 }
 
-/// This structure represents a private key of a Cosmos Network.
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
-pub struct PrivateKey([u8; 32]);
+/// This structure represents a private key of a Cosmos Network. The key bytes are
+/// zeroed when the key is dropped, note that moving a key may still leave copies
+/// behind on the stack, pass keys by reference where possible. Clone explicitly
+/// when multiple owned copies are actually required.
+#[derive(Clone)]
+pub struct PrivateKey(pub(crate) [u8; 32]);
 
-impl PrivateKey {
-    /// Create a private key using an arbitrary slice of bytes. This function is not resistant to side
-    /// channel attacks and may reveal your secret and private key. It is on the other hand more compact
-    /// than the bip32+bip39 logic.
-    pub fn from_secret(secret: &[u8]) -> PrivateKey {
-        let sec_hash = Sha256::digest(secret);
+/// Compares in constant time so that equality checks do not leak how many
+/// leading bytes of two keys match
+impl PartialEq for PrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
 
-        let mut i = BigUint::from_bytes_be(&sec_hash);
+impl Eq for PrivateKey {}
 
-        // Parameters of the curve as explained in https://en.bitcoin.it/wiki/Secp256k1
-        let mut n = BigUint::from_bytes_be(&CurveN);
-        n -= 1u64;
+impl Hash for PrivateKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
 
-        i %= n;
-        i += 1u64;
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
 
-        let mut result: [u8; 32] = Default::default();
-        let mut i_bytes = i.to_bytes_be();
-        // key has leading or trailing zero that's not displayed
-        // by default since this is a big int library missing a defined
-        // integer width.
-        while i_bytes.len() < 32 {
-            i_bytes.push(0);
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PrivateKey([REDACTED])")
+    }
+}
+
+/// Serializes as a redacted placeholder so that keys contained in logged or
+/// serialized structures are never written out
+impl Serialize for PrivateKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("[REDACTED]")
+    }
+}
+
+impl PrivateKey {
+    /// Create a private key using an arbitrary slice of bytes. The hash of the secret is
+    /// reduced onto the curve without secret dependent branches or allocations, but the
+    /// secret itself is only as strong as its entropy, use a mnemonic where possible.
+    /// This is more compact than the bip32+bip39 logic.
+    ///
+    /// The sha256-mod-n construction used here is specific to this library, it remains
+    /// available so that existing deployments keep deriving the same keys but new
+    /// deployments should use from_secret_hkdf()
+    #[deprecated(
+        since = "3.0.0",
+        note = "nonstandard construction, use from_secret_hkdf for new keys"
+    )]
+    pub fn from_secret(secret: &[u8]) -> PrivateKey {
+        let sec_hash: Zeroizing<[u8; 32]> = Zeroizing::new(Sha256::digest(secret).into());
+        PrivateKey(reduce_secret_hash(&sec_hash))
+    }
+
+    /// Create a private key from an arbitrary secret using HKDF-SHA256 (RFC 5869) as the
+    /// key derivation function. The 32 bytes of output keying material are used directly
+    /// as the key if they are a valid secp256k1 scalar, that is non zero and below the
+    /// curve order. Otherwise they are rejected and the expand step is repeated with a
+    /// single counter byte, from 1 to 255, appended to `info` until a valid key is found.
+    /// The chance of even one rejection is below 1 in 2^127, if every counter value is
+    /// rejected an error is returned rather than reusing a counter.
+    ///
+    /// `salt` may be empty, `info` should identify the application and purpose of the key
+    /// so that the same secret can safely be used to derive unrelated keys.
+    pub fn from_secret_hkdf(
+        secret: &[u8],
+        salt: &[u8],
+        info: &[u8],
+    ) -> Result<PrivateKey, PrivateKeyError> {
+        let prk = hkdf_sha256_extract(salt, secret);
+        let counters = std::iter::once(None).chain((1..=u8::MAX).map(Some));
+        for counter in counters {
+            let candidate = hkdf_sha256_expand(&prk, info, counter);
+            if SecretKey::from_slice(candidate.as_ref()).is_ok() {
+                return Ok(PrivateKey(*candidate));
+            }
         }
-        result.copy_from_slice(&i_bytes);
-        PrivateKey(result)
+        Err(PrivateKeyError::CurveError(
+            secp256k1::Error::InvalidSecretKey,
+        ))
+    }
+
+    /// The original BigUint implementation of from_secret, which is not constant time,
+    /// kept only to check that from_secret still derives the same keys
+    #[cfg(feature = "legacy-bigint-derivation")]
+    pub fn from_secret_legacy(secret: &[u8]) -> PrivateKey {
+        let sec_hash: [u8; 32] = Sha256::digest(secret).into();
+        PrivateKey(legacy::reduce_secret_hash(&sec_hash))
     }
 
     /// This function will take the key_import phrase provided by CosmosCLI
@@ -92,34 +184,56 @@ impl PrivateKey {
         phrase: &str,
         passphrase: &str,
     ) -> Result<PrivateKey, PrivateKeyError> {
-        if !path.starts_with('m') || path.contains('\\') {
-            return Err(HdWalletError::InvalidPathSpec(path.to_string()).into());
-        }
-        let mut iterator = path.split('/');
-        // discard the m
-        let _ = iterator.next();
-
-        let key_import = Mnemonic::from_str(phrase)?;
-        let seed_bytes = key_import.to_seed(passphrase);
-        let (master_secret_key, master_chain_code) = master_key_from_seed(&seed_bytes);
-        let mut secret_key = master_secret_key;
-        let mut chain_code = master_chain_code;
-
-        for mut val in iterator {
-            let mut hardened = false;
-            if val.contains('\'') {
-                hardened = true;
-                val = val.trim_matches('\'');
-            }
-            if let Ok(parsed_int) = val.parse() {
-                let (s, c) = get_child_key(secret_key, chain_code, parsed_int, hardened);
-                secret_key = s;
-                chain_code = c;
-            } else {
-                return Err(HdWalletError::InvalidPathSpec(path.to_string()).into());
+        let key_import = SecretMnemonic::parse(phrase)?;
+        PrivateKey::from_seed_and_path(path, &key_import.to_seed(passphrase))
+    }
+
+    /// Derives `count` sequential account keys m/44'/118'/0'/0/start..start+count
+    /// from a phrase. The expensive mnemonic to seed step and the hardened part of
+    /// the path are only computed once, which makes this much faster than calling
+    /// from_hd_wallet_path() for each index
+    pub fn derive_range(
+        phrase: &str,
+        passphrase: &str,
+        start: u32,
+        count: u32,
+    ) -> Result<Vec<PrivateKey>, PrivateKeyError> {
+        PrivateKey::derive_range_from_path("m/44'/118'/0'/0", phrase, passphrase, start, count)
+    }
+
+    /// Derives `count` sequential non-hardened children of `base_path` starting at
+    /// `start`, for example a base path of m/44'/60'/0'/0 for Ethermint chains
+    pub fn derive_range_from_path(
+        base_path: &str,
+        phrase: &str,
+        passphrase: &str,
+        start: u32,
+        count: u32,
+    ) -> Result<Vec<PrivateKey>, PrivateKeyError> {
+        match start.checked_add(count) {
+            Some(end) if end <= HARDENED_OFFSET => {}
+            _ => {
+                return Err(HdWalletError::InvalidPathSpec(format!(
+                    "{}/{} + {} exceeds the non-hardened range",
+                    base_path, start, count
+                ))
+                .into())
             }
         }
-        Ok(PrivateKey(secret_key))
+        let key_import = SecretMnemonic::parse(phrase)?;
+        let base = HdNode::from_seed_and_path(base_path, &key_import.to_seed(passphrase))?;
+        Ok((start..start + count)
+            .map(|i| PrivateKey(base.child(i, false).secret_key))
+            .collect())
+    }
+
+    /// Derives the key at `path` from an already computed BIP39 seed
+    pub(crate) fn from_seed_and_path(
+        path: &str,
+        seed: &[u8],
+    ) -> Result<PrivateKey, PrivateKeyError> {
+        let node = HdNode::from_seed_and_path(path, seed)?;
+        Ok(PrivateKey(node.secret_key))
     }
 
     /// Obtain a public key for a given private key
@@ -131,6 +245,15 @@ impl PrivateKey {
         Ok(PublicKey::from_bytes(compressed, prefix)?)
     }
 
+    /// Obtain the 65 byte uncompressed form of the public key for a given private key
+    /// this is the form used to derive Ethereum style addresses
+    pub fn to_uncompressed_public_key(&self) -> Result<[u8; 65], PrivateKeyError> {
+        let secp256k1 = Secp256k1::new();
+        let sk = SecretKey::from_slice(&self.0)?;
+        let pkey = PublicKeyEC::from_secret_key(&secp256k1, &sk);
+        Ok(pkey.serialize_uncompressed())
+    }
+
     /// Obtain an Address for a given private key, skipping the intermediate public key
     pub fn to_address(&self, prefix: &str) -> Result<Address, PrivateKeyError> {
         let pubkey = self.to_public_key("")?;
@@ -149,72 +272,30 @@ impl PrivateKey {
     ) -> Result<TxParts, PrivateKeyError> {
         // prefix does not matter in this case, you could use a blank string
         let our_pubkey = self.to_public_key(PublicKey::DEFAULT_PREFIX)?;
-        // Create TxBody
-        let body = TxBody {
-            messages: messages.iter().map(|msg| msg.0.clone()).collect(),
-            memo: memo.into(),
-            timeout_height: args.timeout_height,
-            extension_options: Default::default(),
-            non_critical_extension_options: Default::default(),
-        };
-
-        // A protobuf serialization of a TxBody
-        let mut body_buf = Vec::new();
-        body.encode(&mut body_buf).unwrap();
-
-        let key = ProtoSecp256k1Pubkey {
-            key: our_pubkey.to_vec(),
-        };
-
-        let pk_any = encode_any(key, "/cosmos.crypto.secp256k1.PubKey".to_string());
-
-        let single = mode_info::Single { mode: 1 };
-
-        let mode = Some(ModeInfo {
-            sum: Some(mode_info::Sum::Single(single)),
-        });
-
-        let signer_info = SignerInfo {
-            public_key: Some(pk_any),
-            mode_info: mode,
-            sequence: args.sequence,
-        };
-
-        let auth_info = AuthInfo {
-            signer_infos: vec![signer_info],
-            fee: Some(args.fee.into()),
-        };
-
-        // Protobuf serialization of `AuthInfo`
-        let mut auth_buf = Vec::new();
-        auth_info.encode(&mut auth_buf).unwrap();
-
-        let sign_doc = SignDoc {
-            body_bytes: body_buf.clone(),
-            auth_info_bytes: auth_buf.clone(),
-            chain_id: args.chain_id.to_string(),
-            account_number: args.account_number,
-        };
-
-        // Protobuf serialization of `SignDoc`
-        let mut signdoc_buf = Vec::new();
-        sign_doc.encode(&mut signdoc_buf).unwrap();
+        let unsigned = UnsignedTx::new(messages, args, memo, our_pubkey)?;
+        // Sign the signdoc
+        let compact = self.sign_compact(unsigned.digest())?;
+        Ok(unsigned.into_parts(compact))
+    }
 
+    /// Signs a sha256 digest, returning the compact 64 byte signature
+    pub(crate) fn sign_compact(&self, digest: [u8; 32]) -> Result<[u8; 64], PrivateKeyError> {
         let secp256k1 = Secp256k1::new();
         let sk = SecretKey::from_slice(&self.0)?;
-        let digest = Sha256::digest(&signdoc_buf);
         let msg = CurveMessage::from_slice(&digest)?;
-        // Sign the signdoc
         let signed = secp256k1.sign_ecdsa(&msg, &sk);
-        let compact = signed.serialize_compact().to_vec();
+        Ok(signed.serialize_compact())
+    }
 
-        Ok(TxParts {
-            body,
-            body_buf,
-            auth_info,
-            auth_buf,
-            signatures: vec![compact],
-        })
+    /// Signs a sha256 digest producing a low-s normalized signature with a recovery id.
+    /// Nonces are generated deterministically per RFC6979 so signing the same digest
+    /// twice produces the same signature.
+    pub fn sign_recoverable(&self, digest: [u8; 32]) -> Result<Signature, PrivateKeyError> {
+        let secp256k1 = Secp256k1::new();
+        let sk = SecretKey::from_slice(&self.0)?;
+        let msg = CurveMessage::from_slice(&digest)?;
+        let signed = secp256k1.sign_ecdsa_recoverable(&msg, &sk);
+        Ok(Signature::from_recoverable_ecdsa(signed))
     }
 
     /// Signs a transaction that contains at least one message using a single
@@ -226,15 +307,18 @@ impl PrivateKey {
         memo: impl Into<String>,
     ) -> Result<Tx, PrivateKeyError> {
         let parts = self.build_tx(messages, args, memo)?;
-        Ok(Tx {
-            body: Some(parts.body),
-            auth_info: Some(parts.auth_info),
-            signatures: parts.signatures,
-        })
+        Ok(parts.into_tx())
     }
 
     /// Signs a transaction that contains at least one message using a single
     /// private key.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(chain_id = %args.chain_id, sequence = args.sequence, txhash)
+        )
+    )]
     pub fn sign_std_msg(
         &self,
         messages: &[Msg],
@@ -243,30 +327,38 @@ impl PrivateKey {
     ) -> Result<Vec<u8>, PrivateKeyError> {
         let parts = self.build_tx(messages, args, memo)?;
 
-        let tx_raw = TxRaw {
-            body_bytes: parts.body_buf,
-            auth_info_bytes: parts.auth_buf,
-            signatures: parts.signatures,
-        };
-
-        let mut txraw_buf = Vec::new();
-        tx_raw.encode(&mut txraw_buf).unwrap();
+        let txraw_buf = parts.into_tx_raw_bytes();
         let digest = Sha256::digest(&txraw_buf);
-        trace!("TXID {}", bytes_to_hex_str(&digest));
+        let txhash = bytes_to_hex_str(&digest).to_uppercase();
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("txhash", txhash.as_str());
+        trace!("TXID {}", txhash);
 
         Ok(txraw_buf)
     }
 }
 
+impl AsyncSigner for PrivateKey {
+    fn public_key(&self) -> Result<PublicKey, PrivateKeyError> {
+        self.to_public_key(PublicKey::DEFAULT_PREFIX)
+    }
+
+    async fn sign_digest(&self, digest: [u8; 32]) -> Result<[u8; 64], PrivateKeyError> {
+        self.sign_compact(digest)
+    }
+}
+
 impl FromStr for PrivateKey {
     type Err = PrivateKeyError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match hex_str_to_bytes(s) {
             Ok(bytes) => {
+                let mut bytes = Zeroizing::new(bytes);
                 if bytes.len() == 32 {
-                    let mut inner = [0; 32];
-                    inner.copy_from_slice(&bytes[0..32]);
-                    Ok(PrivateKey(inner))
+                    let mut key = PrivateKey([0; 32]);
+                    key.0.copy_from_slice(&bytes[0..32]);
+                    bytes.zeroize();
+                    Ok(key)
                 } else {
                     Err(PrivateKeyError::HexDecodeErrorWrongLength)
                 }
@@ -282,6 +374,119 @@ impl FromStr for PrivateKey {
     }
 }
 
+/// Catches transactions the chain is certain to reject, the node would only report
+/// these after broadcast
+pub(crate) fn validate_tx(
+    messages: &[Msg],
+    args: &MessageArgs,
+    memo: &str,
+) -> Result<(), PrivateKeyError> {
+    if messages.is_empty() {
+        return Err(TxValidationError::NoMessages.into());
+    }
+    if args.fee.gas_limit == 0 {
+        return Err(TxValidationError::ZeroGas.into());
+    }
+    if let Some(max) = args.max_memo_characters {
+        // the sdk compares the byte length of the memo despite the param name
+        if memo.len() as u64 > max {
+            return Err(TxValidationError::MemoTooLong {
+                length: memo.len(),
+                max,
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// A private node in a BIP32 derivation tree along with the metadata needed to
+/// export it as an extended key. The key material is zeroed on drop.
+pub(crate) struct HdNode {
+    pub secret_key: [u8; 32],
+    pub chain_code: [u8; 32],
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: u32,
+}
+
+impl Drop for HdNode {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+        self.chain_code.zeroize();
+    }
+}
+
+impl HdNode {
+    /// Walks `path` (for example m/44'/118'/0') starting from the master key of `seed`
+    pub fn from_seed_and_path(path: &str, seed: &[u8]) -> Result<HdNode, PrivateKeyError> {
+        let path = parse_hd_path(path)?;
+        let (secret_key, chain_code) = master_key_from_seed(seed);
+        let mut node = HdNode {
+            secret_key,
+            chain_code,
+            depth: 0,
+            parent_fingerprint: [0; 4],
+            child_number: 0,
+        };
+        for (index, hardened) in path {
+            node = node.child(index, hardened);
+        }
+        Ok(node)
+    }
+
+    /// Derives the child at `index`, hardened or not
+    pub fn child(&self, index: u32, hardened: bool) -> HdNode {
+        let (secret_key, chain_code) =
+            get_child_key(self.secret_key, self.chain_code, index, hardened);
+        HdNode {
+            secret_key,
+            chain_code,
+            depth: self.depth + 1,
+            parent_fingerprint: fingerprint(&self.public_key()),
+            child_number: if hardened {
+                index + HARDENED_OFFSET
+            } else {
+                index
+            },
+        }
+    }
+
+    /// The compressed public key of this node
+    pub fn public_key(&self) -> [u8; 33] {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&self.secret_key).unwrap();
+        PublicKeyEC::from_secret_key(&secp, &secret_key).serialize()
+    }
+}
+
+/// Parses an HD wallet path such as m/44'/118'/0'/0/0 into its indexes and whether
+/// each one is hardened
+pub(crate) fn parse_hd_path(path: &str) -> Result<Vec<(u32, bool)>, HdWalletError> {
+    if !path.starts_with('m') || path.contains('\\') {
+        return Err(HdWalletError::InvalidPathSpec(path.to_string()));
+    }
+    let mut iterator = path.split('/');
+    // discard the m
+    let _ = iterator.next();
+
+    let mut res = Vec::new();
+    for mut val in iterator {
+        let mut hardened = false;
+        if val.contains('\'') {
+            hardened = true;
+            val = val.trim_matches('\'');
+        }
+        match val.parse::<u32>() {
+            Ok(parsed_int) if parsed_int < HARDENED_OFFSET && res.len() < u8::MAX as usize => {
+                res.push((parsed_int, hardened));
+            }
+            _ => return Err(HdWalletError::InvalidPathSpec(path.to_string())),
+        }
+    }
+    Ok(res)
+}
+
 /// This derives the master key from seed bytes, the actual usage is typically
 /// for Cosmos key_import support, where we import a seed phrase.
 fn master_key_from_seed(seed_bytes: &[u8]) -> ([u8; 32], [u8; 32]) {
@@ -316,7 +521,7 @@ fn get_child_key(
     use hmac::Mac;
     type HmacSha512 = Hmac<Sha512>;
 
-    let i = if hardened { 2u32.pow(31) + i } else { i };
+    let i = if hardened { HARDENED_OFFSET + i } else { i };
     let mut hasher = HmacSha512::new_from_slice(&c_parent).unwrap();
     if hardened {
         hasher.update(&[0u8]);
@@ -329,42 +534,166 @@ fn get_child_key(
     }
     hasher.update(&i.to_be_bytes());
 
-    let l_param = hasher.finalize().into_bytes();
-
-    // If you wanted to do this on your own (without add_tweak)
-    // it would go like this
-    //
-    // but this implementation is not constant time and performs
-    // allocations, opening us up to side channel attacks.
-    // that being said our Hmac and SHA libraries don't clearly
-    // indicate if they are constant time. So this ship may have
-    // already sailed
-    //
-    // let n = BigUint::from_bytes_be(&CurveN);
-    // let parse_i_l = BigUint::from_bytes_be(&l_param[0..32]);
-    // let parent_key = BigUint::from_bytes_be(&k_parent);
-    // let child_key = (parse_i_l.clone() + parent_key) % n.clone();
-    // if child_key == 0u32.into() {
-    //     panic!("child key is zeros!");
-    // } else if parse_i_l > n {
-    //     panic!("child key not in curve space!")
-    // }
-
-    let mut parse_i_l = SecretKey::from_slice(&l_param[0..32]).unwrap();
-    parse_i_l = parse_i_l
+    let mut l_param = hasher.finalize().into_bytes();
+
+    // the tweak is added with the constant time scalar arithmetic of libsecp256k1,
+    // the odds of I_L being out of range or the child key being zero are
+    // negligible (below 1 in 2^127) so we do not skip to the next index
+    let parse_i_l = SecretKey::from_slice(&l_param[0..32]).unwrap();
+    let child_key = parse_i_l
         .add_tweak(&Scalar::from_be_bytes(k_parent).unwrap())
         .unwrap();
-    let child_key = parse_i_l;
 
-    let mut child_key_res: [u8; 32] = [0; 32];
-    child_key_res
-        .copy_from_slice(&hex_str_to_bytes(&child_key.display_secret().to_string()).unwrap());
+    let child_key_res = child_key.secret_bytes();
     let mut chain_code_res: [u8; 32] = [0; 32];
     chain_code_res.copy_from_slice(&l_param[32..64]);
+    l_param.zeroize();
     (child_key_res, chain_code_res)
 }
 
+type HmacSha256 = hmac::Hmac<Sha256>;
+
+/// The HKDF extract step, an empty salt is equivalent to a salt of zeros
+fn hkdf_sha256_extract(salt: &[u8], ikm: &[u8]) -> Zeroizing<[u8; 32]> {
+    use hmac::Mac;
+    let mut hasher = HmacSha256::new_from_slice(salt).unwrap();
+    hasher.update(ikm);
+    Zeroizing::new(hasher.finalize().into_bytes().into())
+}
+
+/// The HKDF expand step producing a single block of output, `counter` is appended
+/// to `info` when rejection sampling
+fn hkdf_sha256_expand(prk: &[u8; 32], info: &[u8], counter: Option<u8>) -> Zeroizing<[u8; 32]> {
+    use hmac::Mac;
+    let mut hasher = HmacSha256::new_from_slice(prk).unwrap();
+    hasher.update(info);
+    if let Some(counter) = counter {
+        hasher.update(&[counter]);
+    }
+    hasher.update(&[1u8]);
+    Zeroizing::new(hasher.finalize().into_bytes().into())
+}
+
+/// Splits a big endian 256 bit integer into little endian u64 limbs
+fn to_limbs(bytes: &[u8; 32]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let start = 32 - (i + 1) * 8;
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&bytes[start..start + 8]);
+        *limb = u64::from_be_bytes(word);
+    }
+    limbs
+}
+
+/// Maps a secret hash onto [1, n - 1] as (hash mod (n - 1)) + 1 using fixed width
+/// arithmetic. Since 2^256 < 2 * (n - 1) a single conditional subtraction performs
+/// the reduction. Earlier releases wrote out keys with leading zero bytes shifted
+/// to the end of the array, that layout is reproduced here (also in constant time)
+/// so that existing secrets keep mapping to the same key.
+fn reduce_secret_hash(hash: &[u8; 32]) -> [u8; 32] {
+    let value = to_limbs(hash);
+    let mut n_minus_one = to_limbs(&CurveN);
+    // the low limb of n is non zero so this can not borrow
+    n_minus_one[0] -= 1;
+
+    let mut diff = [0u64; 4];
+    let mut borrow = 0u64;
+    for i in 0..4 {
+        let (d, b1) = value[i].overflowing_sub(n_minus_one[i]);
+        let (d, b2) = d.overflowing_sub(borrow);
+        diff[i] = d;
+        borrow = (b1 | b2) as u64;
+    }
+    // a final borrow means hash < n - 1 and no reduction is needed
+    let keep = Choice::from(borrow as u8);
+    let mut reduced = [0u64; 4];
+    for i in 0..4 {
+        reduced[i] = u64::conditional_select(&diff[i], &value[i], keep);
+    }
+
+    // reduced <= n - 2 so adding one can not overflow
+    let mut carry = 1u64;
+    for limb in reduced.iter_mut() {
+        let (sum, c) = limb.overflowing_add(carry);
+        *limb = sum;
+        carry = c as u64;
+    }
+
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    for (i, limb) in reduced.iter().enumerate() {
+        let start = 32 - (i + 1) * 8;
+        bytes[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+    }
+    reduced.zeroize();
+    diff.zeroize();
+
+    let mut leading_zeros = 0u8;
+    let mut still_zero = Choice::from(1);
+    for byte in bytes.iter() {
+        still_zero &= byte.ct_eq(&0);
+        leading_zeros += u8::conditional_select(&0, &1, still_zero);
+    }
+
+    let mut result = [0u8; 32];
+    for shift in 0..32u8 {
+        let selected = shift.ct_eq(&leading_zeros);
+        for (j, out) in result.iter_mut().enumerate() {
+            let src = j + shift as usize;
+            let byte = if src < 32 { bytes[src] } else { 0 };
+            out.conditional_assign(&byte, selected);
+        }
+    }
+    result
+}
+
+/// The original BigUint based derivation, this allocates and is not constant time
+#[cfg(feature = "legacy-bigint-derivation")]
+mod legacy {
+    use num::BigUint;
+    use secp256k1::constants::CURVE_ORDER as CurveN;
+
+    pub fn reduce_secret_hash(hash: &[u8; 32]) -> [u8; 32] {
+        let mut i = BigUint::from_bytes_be(hash);
+
+        // Parameters of the curve as explained in https://en.bitcoin.it/wiki/Secp256k1
+        let mut n = BigUint::from_bytes_be(&CurveN);
+        n -= 1u64;
+
+        i %= n;
+        i += 1u64;
+
+        let mut result: [u8; 32] = Default::default();
+        let mut i_bytes = i.to_bytes_be();
+        // key has leading or trailing zero that's not displayed
+        // by default since this is a big int library missing a defined
+        // integer width.
+        while i_bytes.len() < 32 {
+            i_bytes.push(0);
+        }
+        result.copy_from_slice(&i_bytes);
+        result
+    }
+
+    /// Adds I_L to the parent key mod n, as done by get_child_key
+    #[cfg(test)]
+    pub fn add_tweak(i_l: &[u8; 32], k_parent: &[u8; 32]) -> [u8; 32] {
+        let n = BigUint::from_bytes_be(&CurveN);
+        let parse_i_l = BigUint::from_bytes_be(i_l);
+        let parent_key = BigUint::from_bytes_be(k_parent);
+        let child_key = (parse_i_l + parent_key) % n;
+        let bytes = child_key.to_bytes_be();
+        let mut result = [0u8; 32];
+        result[32 - bytes.len()..].copy_from_slice(&bytes);
+        result
+    }
+}
+
+#[cfg(test)]
+use crate::mnemonic::Mnemonic;
+
 #[test]
+#[allow(deprecated)]
 fn test_secret() {
     let private_key = PrivateKey::from_secret(b"mySecret");
     assert_eq!(
@@ -545,6 +874,7 @@ fn test_vector_unhardened() {
 }
 
 #[test]
+#[allow(deprecated)]
 // this tests generating many thousands of private keys
 fn test_many_key_generation() {
     use rand::Rng;
@@ -556,9 +886,231 @@ fn test_many_key_generation() {
     }
 }
 
+#[test]
+// this tests generating many thousands of private keys with hkdf
+fn test_many_key_generation_hkdf() {
+    use rand::Rng;
+    for _ in 0..1000 {
+        let mut rng = rand::thread_rng();
+        let secret: [u8; 32] = rng.gen();
+        let cosmos_key = PrivateKey::from_secret_hkdf(&secret, b"", b"").unwrap();
+        let _cosmos_address = cosmos_key.to_public_key("cosmospub").unwrap().to_address();
+    }
+}
+
 #[test]
 // this tests that a bad phrase provides an error
 fn test_bad_phrase() {
     let cosmos_key = PrivateKey::from_phrase("bad phrase", "");
     assert!(cosmos_key.is_err())
 }
+
+#[test]
+// this tests that key material is never written out by Debug or Serialize
+fn test_redacted_output() {
+    let key: PrivateKey = "2c8f1d2c8e4e7fcf7b2f2bd4a2d6a4c1e5a0c9a51c4f6c2e6a7e0f3a1b2c3d4e"
+        .parse()
+        .unwrap();
+    let debug = format!("{:?}", key);
+    assert!(!debug.contains("2c8f"));
+    assert_eq!(serde_json::to_string(&key).unwrap(), "\"[REDACTED]\"");
+}
+
+#[test]
+// this tests that batch derivation matches deriving each path individually
+fn test_derive_range() {
+    let words = "purse sure leg gap above pull rescue glass circle attract erupt can sail gasp shy clarify inflict anger sketch hobby scare mad reject where";
+    let keys = PrivateKey::derive_range(words, "", 3, 4).unwrap();
+    assert_eq!(keys.len(), 4);
+    for (i, key) in (3..7).zip(keys.iter()) {
+        let expected =
+            PrivateKey::from_hd_wallet_path(&format!("m/44'/118'/0'/0/{}", i), words, "").unwrap();
+        assert_eq!(*key, expected);
+    }
+    assert!(PrivateKey::derive_range(words, "", u32::MAX, 2).is_err());
+    assert!(PrivateKey::derive_range(words, "", 0, 0)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+// this tests that the secret reduction agrees with the BigUint implementation, including
+// hashes above n - 1 and keys with leading zero bytes
+#[cfg(feature = "legacy-bigint-derivation")]
+#[allow(deprecated)]
+fn test_reduce_secret_hash_matches_legacy() {
+    use rand::Rng;
+    let mut rng = rand::thread_rng();
+    let mut edge_cases = vec![[0u8; 32], [0xff; 32], CurveN];
+    let mut n_minus_one = CurveN;
+    n_minus_one[31] -= 1;
+    edge_cases.push(n_minus_one);
+    for leading_zeros in 1..32 {
+        let mut hash: [u8; 32] = rng.gen();
+        hash[..leading_zeros].fill(0);
+        edge_cases.push(hash);
+    }
+    for _ in 0..1000 {
+        edge_cases.push(rng.gen());
+    }
+    for hash in edge_cases {
+        assert_eq!(reduce_secret_hash(&hash), legacy::reduce_secret_hash(&hash));
+    }
+    assert_eq!(
+        PrivateKey::from_secret(b"mySecret"),
+        PrivateKey::from_secret_legacy(b"mySecret")
+    );
+}
+
+#[test]
+// this tests that child key derivation agrees with the BigUint implementation
+#[cfg(feature = "legacy-bigint-derivation")]
+fn test_child_key_matches_legacy() {
+    use hmac::{Hmac, Mac};
+    use rand::Rng;
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let parent = PrivateKey::from_secret_hkdf(&rng.gen::<[u8; 32]>(), b"", b"")
+            .unwrap()
+            .0;
+        let chain_code: [u8; 32] = rng.gen();
+        let index: u32 = rng.gen_range(0..HARDENED_OFFSET);
+
+        let mut hasher = Hmac::<Sha512>::new_from_slice(&chain_code).unwrap();
+        hasher.update(&[0u8]);
+        hasher.update(&parent);
+        hasher.update(&(index + HARDENED_OFFSET).to_be_bytes());
+        let l_param = hasher.finalize().into_bytes();
+        let mut i_l = [0u8; 32];
+        i_l.copy_from_slice(&l_param[0..32]);
+
+        let (child, _) = get_child_key(parent, chain_code, index, true);
+        assert_eq!(child, legacy::add_tweak(&i_l, &parent));
+    }
+}
+
+#[test]
+// this tests the HKDF steps against RFC 5869 test case 1 and that the derived key
+// depends on the salt and info
+fn test_secret_hkdf() {
+    let ikm = [0x0bu8; 22];
+    let salt = hex_str_to_bytes("000102030405060708090a0b0c").unwrap();
+    let info = hex_str_to_bytes("f0f1f2f3f4f5f6f7f8f9").unwrap();
+    let prk = hkdf_sha256_extract(&salt, &ikm);
+    assert_eq!(
+        bytes_to_hex_str(prk.as_ref()),
+        "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"
+    );
+    let okm = hkdf_sha256_expand(&prk, &info, None);
+    assert_eq!(
+        bytes_to_hex_str(okm.as_ref()),
+        "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf"
+    );
+    assert_eq!(
+        PrivateKey::from_secret_hkdf(&ikm, &salt, &info).unwrap().0,
+        *okm
+    );
+
+    let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"deep_space").unwrap();
+    assert_ne!(
+        key,
+        PrivateKey::from_secret_hkdf(b"mySecret", b"salt", b"deep_space").unwrap()
+    );
+    assert_ne!(
+        key,
+        PrivateKey::from_secret_hkdf(b"mySecret", b"", b"other").unwrap()
+    );
+}
+
+#[test]
+// this tests that transactions the chain would reject are caught before signing
+fn test_tx_validation() {
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+    let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+    let address = key.to_address("cosmos").unwrap();
+    let msgs = vec![Msg::new(
+        "/cosmos.bank.v1beta1.MsgSend",
+        MsgSend {
+            from_address: address.to_string(),
+            to_address: address.to_string(),
+            amount: vec![],
+        },
+    )];
+    let mut args = MessageArgs {
+        chain_id: "mychainid".to_string(),
+        max_memo_characters: Some(4),
+        ..Default::default()
+    };
+    assert!(matches!(
+        key.sign_std_msg(&msgs, args.clone(), ""),
+        Err(PrivateKeyError::TxValidationError(
+            TxValidationError::ZeroGas
+        ))
+    ));
+    args.fee.gas_limit = 100_000;
+    assert!(matches!(
+        key.sign_std_msg(&[], args.clone(), ""),
+        Err(PrivateKeyError::TxValidationError(
+            TxValidationError::NoMessages
+        ))
+    ));
+    // multi byte characters count by their length in bytes
+    assert!(matches!(
+        key.sign_std_msg(&msgs, args.clone(), "éé!"),
+        Err(PrivateKeyError::TxValidationError(
+            TxValidationError::MemoTooLong { length: 5, max: 4 }
+        ))
+    ));
+    assert!(key.sign_std_msg(&msgs, args, "memo").is_ok());
+}
+
+#[test]
+fn test_relative_timeout() {
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+    let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+    let address = key.to_address("cosmos").unwrap();
+    let msgs = vec![Msg::new(
+        "/cosmos.bank.v1beta1.MsgSend",
+        MsgSend {
+            from_address: address.to_string(),
+            to_address: address.to_string(),
+            amount: vec![],
+        },
+    )];
+    let mut args = MessageArgs {
+        chain_id: "mychainid".to_string(),
+        relative_timeout: Some(50),
+        ..Default::default()
+    };
+    args.fee.gas_limit = 100_000;
+    assert!(matches!(
+        key.sign_std_msg(&msgs, args.clone(), ""),
+        Err(PrivateKeyError::InvalidMessageArgs(_))
+    ));
+    args.resolve_relative_timeout(1_000);
+    assert_eq!(args.timeout_height, 1_050);
+    assert_eq!(args.relative_timeout, None);
+    assert!(key.sign_std_msg(&msgs, args.clone(), "").is_ok());
+    // resolving again leaves an absolute timeout alone
+    args.resolve_relative_timeout(2_000);
+    assert_eq!(args.timeout_height, 1_050);
+}
+
+#[test]
+fn test_message_args_eq() {
+    fn assert_eq_impl<T: Eq>() {}
+    assert_eq_impl::<MessageArgs>();
+
+    let option = Any {
+        type_url: "/ethermint.types.v1.ExtensionOptionsWeb3Tx".to_string(),
+        value: vec![1, 2, 3],
+    };
+    let args = MessageArgs {
+        extension_options: vec![option.clone()],
+        ..Default::default()
+    };
+    assert_eq!(args, args.clone());
+    let mut other = args.clone();
+    other.extension_options[0].value = vec![1, 2];
+    assert_ne!(args, other);
+}
@@ -0,0 +1,601 @@
+//! Conversion of messages to and from the proto3 JSON representation, an object with an
+//! `@type` field holding the type url followed by the message fields in camelCase. 64 bit
+//! integers are strings and bytes are base64 as the proto3 JSON mapping requires, when
+//! parsing both camelCase and the original snake_case field names are accepted.
+//!
+//! Only commonly used messages are known, any other message is represented by its type
+//! url and base64 encoded proto bytes in a `value` field. [CanonicalJson] renders
+//! transactions and sign docs the way the Cosmos SDK prints them
+
+use crate::tx::{PartialSignature, PartiallySignedTx};
+use crate::type_url::TypeUrl;
+use cosmos_sdk_proto::cosmos::authz::v1beta1::MsgExec;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::crypto::secp256k1::PubKey as Secp256k1PubKey;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::{
+    MsgFundCommunityPool, MsgSetWithdrawAddress, MsgWithdrawDelegatorReward,
+    MsgWithdrawValidatorCommission,
+};
+use cosmos_sdk_proto::cosmos::gov::v1beta1::{MsgDeposit, MsgVote};
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::MsgUnjail;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::{MsgBeginRedelegate, MsgDelegate, MsgUndelegate};
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    mode_info, AuthInfo, Fee as ProtoFee, ModeInfo, SignDoc, SignerInfo, Tx, TxBody,
+};
+use cosmos_sdk_proto::ibc::applications::transfer::v1::MsgTransfer;
+use cosmos_sdk_proto::ibc::core::client::v1::Height;
+use prost::Message;
+use prost_types::Any;
+use serde_json::{Map, Value};
+
+/// A value with a proto3 JSON representation
+pub(crate) trait ProtoJson: Sized {
+    fn to_json(&self) -> Value;
+    fn from_json(value: &Value) -> Result<Self, String>;
+}
+
+impl ProtoJson for String {
+    fn to_json(&self) -> Value {
+        Value::String(self.clone())
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::String(s) => Ok(s.clone()),
+            Value::Null => Ok(String::new()),
+            other => Err(format!("Expected a string found {}", other)),
+        }
+    }
+}
+
+impl ProtoJson for u64 {
+    fn to_json(&self) -> Value {
+        Value::String(self.to_string())
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::String(s) => s
+                .parse()
+                .map_err(|e| format!("Invalid integer {} {}", s, e)),
+            Value::Number(n) => n.as_u64().ok_or(format!("Invalid integer {}", n)),
+            Value::Null => Ok(0),
+            other => Err(format!("Expected an integer found {}", other)),
+        }
+    }
+}
+
+impl ProtoJson for Vec<u8> {
+    fn to_json(&self) -> Value {
+        Value::String(base64::encode(self))
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::String(s) => base64::decode(s).map_err(|e| format!("Invalid base64 {}", e)),
+            Value::Null => Ok(Vec::new()),
+            other => Err(format!("Expected base64 bytes found {}", other)),
+        }
+    }
+}
+
+impl<T: ProtoJson> ProtoJson for Option<T> {
+    fn to_json(&self) -> Value {
+        match self {
+            Some(v) => v.to_json(),
+            None => Value::Null,
+        }
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Null => Ok(None),
+            v => Ok(Some(T::from_json(v)?)),
+        }
+    }
+}
+
+impl<T: ProtoJson> ProtoJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(|v| v.to_json()).collect())
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Array(values) => values.iter().map(T::from_json).collect(),
+            Value::Null => Ok(Vec::new()),
+            other => Err(format!("Expected an array found {}", other)),
+        }
+    }
+}
+
+/// Gets a field by its camelCase name or original snake_case name
+pub(crate) fn field<'a>(object: &'a Map<String, Value>, camel: &str, snake: &str) -> &'a Value {
+    object
+        .get(camel)
+        .or_else(|| object.get(snake))
+        .unwrap_or(&Value::Null)
+}
+
+pub(crate) fn as_object(value: &Value) -> Result<&Map<String, Value>, String> {
+    match value {
+        Value::Object(object) => Ok(object),
+        other => Err(format!("Expected an object found {}", other)),
+    }
+}
+
+/// Implements ProtoJson for a proto message from a list of its fields and their
+/// camelCase names, every field type must itself implement ProtoJson
+macro_rules! proto_json {
+    ($($ty:ty { $($field:ident: $camel:literal),* $(,)? })*) => {
+        $(
+            impl ProtoJson for $ty {
+                fn to_json(&self) -> Value {
+                    let mut object = Map::new();
+                    $(object.insert($camel.to_string(), self.$field.to_json());)*
+                    Value::Object(object)
+                }
+
+                fn from_json(value: &Value) -> Result<Self, String> {
+                    let _object = as_object(value)?;
+                    Ok(Self {
+                        $($field: ProtoJson::from_json(field(
+                            _object,
+                            $camel,
+                            stringify!($field),
+                        ))?,)*
+                    })
+                }
+            }
+        )*
+    };
+}
+
+proto_json! {
+    ProtoCoin { denom: "denom", amount: "amount" }
+    ProtoFee { amount: "amount", gas_limit: "gasLimit", payer: "payer", granter: "granter" }
+    Height { revision_number: "revisionNumber", revision_height: "revisionHeight" }
+    MsgSend { from_address: "fromAddress", to_address: "toAddress", amount: "amount" }
+    MsgDelegate {
+        delegator_address: "delegatorAddress",
+        validator_address: "validatorAddress",
+        amount: "amount",
+    }
+    MsgUndelegate {
+        delegator_address: "delegatorAddress",
+        validator_address: "validatorAddress",
+        amount: "amount",
+    }
+    MsgBeginRedelegate {
+        delegator_address: "delegatorAddress",
+        validator_src_address: "validatorSrcAddress",
+        validator_dst_address: "validatorDstAddress",
+        amount: "amount",
+    }
+    MsgWithdrawDelegatorReward {
+        delegator_address: "delegatorAddress",
+        validator_address: "validatorAddress",
+    }
+    MsgWithdrawValidatorCommission { validator_address: "validatorAddress" }
+    MsgSetWithdrawAddress {
+        delegator_address: "delegatorAddress",
+        withdraw_address: "withdrawAddress",
+    }
+    MsgFundCommunityPool { amount: "amount", depositor: "depositor" }
+    MsgDeposit { proposal_id: "proposalId", depositor: "depositor", amount: "amount" }
+    MsgUnjail { validator_addr: "validatorAddr" }
+    MsgExec { grantee: "grantee", msgs: "msgs" }
+    MsgTransfer {
+        source_port: "sourcePort",
+        source_channel: "sourceChannel",
+        token: "token",
+        sender: "sender",
+        receiver: "receiver",
+        timeout_height: "timeoutHeight",
+        timeout_timestamp: "timeoutTimestamp",
+    }
+    Secp256k1PubKey { key: "key" }
+    TxBody {
+        messages: "messages",
+        memo: "memo",
+        timeout_height: "timeoutHeight",
+        extension_options: "extensionOptions",
+        non_critical_extension_options: "nonCriticalExtensionOptions",
+    }
+    SignerInfo { public_key: "publicKey", mode_info: "modeInfo", sequence: "sequence" }
+    AuthInfo { signer_infos: "signerInfos", fee: "fee" }
+    Tx { body: "body", auth_info: "authInfo", signatures: "signatures" }
+    SignDoc {
+        body_bytes: "bodyBytes",
+        auth_info_bytes: "authInfoBytes",
+        chain_id: "chainId",
+        account_number: "accountNumber",
+    }
+    PartiallySignedTx {
+        body_bytes: "bodyBytes",
+        auth_info_bytes: "authInfoBytes",
+        chain_id: "chainId",
+        signers: "signers",
+    }
+    PartialSignature { account_number: "accountNumber", signature: "signature" }
+}
+
+const SIGN_MODES: [(i32, &str); 5] = [
+    (0, "SIGN_MODE_UNSPECIFIED"),
+    (1, "SIGN_MODE_DIRECT"),
+    (2, "SIGN_MODE_TEXTUAL"),
+    (127, "SIGN_MODE_LEGACY_AMINO_JSON"),
+    (191, "SIGN_MODE_EIP_191"),
+];
+
+// only single signer mode infos are supported, multisig mode infos are rendered
+// without their bit array and can not be parsed
+impl ProtoJson for ModeInfo {
+    fn to_json(&self) -> Value {
+        let mode_name = |mode: i32| match SIGN_MODES.iter().find(|(m, _)| *m == mode) {
+            Some((_, name)) => Value::String(name.to_string()),
+            None => Value::from(mode),
+        };
+        let mut object = Map::new();
+        match &self.sum {
+            Some(mode_info::Sum::Single(single)) => {
+                let mut inner = Map::new();
+                inner.insert("mode".to_string(), mode_name(single.mode));
+                object.insert("single".to_string(), Value::Object(inner));
+            }
+            Some(mode_info::Sum::Multi(multi)) => {
+                let mut inner = Map::new();
+                let infos = multi.mode_infos.iter().map(|m| m.to_json()).collect();
+                inner.insert("modeInfos".to_string(), Value::Array(infos));
+                object.insert("multi".to_string(), Value::Object(inner));
+            }
+            None => {}
+        }
+        Value::Object(object)
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let object = as_object(value)?;
+        let single = match object.get("single") {
+            Some(single) => as_object(single)?,
+            None if object.contains_key("multi") => {
+                return Err("Multisig mode info is not supported".to_string())
+            }
+            None => return Ok(ModeInfo { sum: None }),
+        };
+        let mode = match field(single, "mode", "mode") {
+            Value::String(name) => match SIGN_MODES.iter().find(|(_, n)| n == name) {
+                Some((mode, _)) => *mode,
+                None => return Err(format!("Unknown sign mode {}", name)),
+            },
+            Value::Number(n) => n.as_i64().unwrap_or_default() as i32,
+            _ => 0,
+        };
+        Ok(ModeInfo {
+            sum: Some(mode_info::Sum::Single(mode_info::Single { mode })),
+        })
+    }
+}
+
+const VOTE_OPTIONS: [&str; 5] = [
+    "VOTE_OPTION_UNSPECIFIED",
+    "VOTE_OPTION_YES",
+    "VOTE_OPTION_ABSTAIN",
+    "VOTE_OPTION_NO",
+    "VOTE_OPTION_NO_WITH_VETO",
+];
+
+// enums are represented by their name so MsgVote can not use the macro
+impl ProtoJson for MsgVote {
+    fn to_json(&self) -> Value {
+        let option = match VOTE_OPTIONS.get(self.option as usize) {
+            Some(name) => Value::String(name.to_string()),
+            None => Value::from(self.option),
+        };
+        let mut object = Map::new();
+        object.insert("proposalId".to_string(), self.proposal_id.to_json());
+        object.insert("voter".to_string(), self.voter.to_json());
+        object.insert("option".to_string(), option);
+        Value::Object(object)
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let object = as_object(value)?;
+        let option = match field(object, "option", "option") {
+            Value::String(name) => match VOTE_OPTIONS.iter().position(|o| o == name) {
+                Some(option) => option as i32,
+                None => return Err(format!("Unknown vote option {}", name)),
+            },
+            Value::Number(n) => n.as_i64().unwrap_or_default() as i32,
+            _ => 0,
+        };
+        Ok(MsgVote {
+            proposal_id: ProtoJson::from_json(field(object, "proposalId", "proposal_id"))?,
+            voter: ProtoJson::from_json(field(object, "voter", "voter"))?,
+            option,
+        })
+    }
+}
+
+/// Converts a known message to a JSON object with its `@type`, None for unknown types
+fn message_to_json<M: Message + TypeUrl + Default + ProtoJson>(any: &Any) -> Option<Value> {
+    if any.type_url != M::TYPE_URL {
+        return None;
+    }
+    let mut object = match M::decode(any.value.as_slice()).ok()?.to_json() {
+        Value::Object(object) => object,
+        _ => return None,
+    };
+    object.insert("@type".to_string(), Value::String(any.type_url.clone()));
+    Some(Value::Object(object))
+}
+
+fn message_from_json<M: Message + TypeUrl + ProtoJson>(
+    type_url: &str,
+    value: &Value,
+) -> Option<Result<Any, String>> {
+    if type_url != M::TYPE_URL {
+        return None;
+    }
+    Some(M::from_json(value).map(|msg| Any {
+        type_url: type_url.to_string(),
+        value: msg.encode_to_vec(),
+    }))
+}
+
+macro_rules! known_messages {
+    ($($ty:ty),* $(,)?) => {
+        fn known_to_json(any: &Any) -> Option<Value> {
+            None$(.or_else(|| message_to_json::<$ty>(any)))*
+        }
+
+        fn known_from_json(type_url: &str, value: &Value) -> Option<Result<Any, String>> {
+            None$(.or_else(|| message_from_json::<$ty>(type_url, value)))*
+        }
+    };
+}
+
+known_messages!(
+    MsgSend,
+    MsgDelegate,
+    MsgUndelegate,
+    MsgBeginRedelegate,
+    MsgWithdrawDelegatorReward,
+    MsgWithdrawValidatorCommission,
+    MsgSetWithdrawAddress,
+    MsgFundCommunityPool,
+    MsgVote,
+    MsgDeposit,
+    MsgUnjail,
+    MsgExec,
+    MsgTransfer,
+    Secp256k1PubKey,
+);
+
+impl ProtoJson for Any {
+    fn to_json(&self) -> Value {
+        match known_to_json(self) {
+            Some(value) => value,
+            None => {
+                let mut object = Map::new();
+                object.insert("@type".to_string(), Value::String(self.type_url.clone()));
+                object.insert("value".to_string(), self.value.to_json());
+                Value::Object(object)
+            }
+        }
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let object = as_object(value)?;
+        let type_url = match object.get("@type") {
+            Some(Value::String(type_url)) => type_url,
+            _ => return Err("Message has no @type".to_string()),
+        };
+        match known_from_json(type_url, value) {
+            Some(res) => res,
+            None => Ok(Any {
+                type_url: type_url.clone(),
+                value: ProtoJson::from_json(field(object, "value", "value"))?,
+            }),
+        }
+    }
+}
+
+/// Rendering in the Cosmos SDK's proto JSON form, as printed by the chain binaries for
+/// example by `gaiad tx sign --generate-only`. Field names are the original snake_case
+/// names, keys are sorted, defaults are included, 64 bit integers are strings, bytes
+/// are base64 and Any values are flattened next to their `@type`. Comparing this text
+/// for deep_space and the chain binary shows where a SignDoc differs
+pub trait CanonicalJson {
+    fn to_canonical_json(&self) -> String;
+}
+
+macro_rules! canonical_json {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl CanonicalJson for $ty {
+                fn to_canonical_json(&self) -> String {
+                    canonicalize(self.to_json()).to_string()
+                }
+            }
+        )*
+    };
+}
+
+canonical_json!(Tx, TxBody, AuthInfo, SignDoc, Any);
+
+impl CanonicalJson for crate::Msg {
+    fn to_canonical_json(&self) -> String {
+        self.0.to_canonical_json()
+    }
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Renames fields to snake_case and sorts object keys
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<(String, Value)> = object
+                .into_iter()
+                .map(|(k, v)| {
+                    let k = if k.starts_with('@') {
+                        k
+                    } else {
+                        to_snake_case(&k)
+                    };
+                    (k, canonicalize(v))
+                })
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().collect())
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(canonicalize).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_message_json() {
+        let send = MsgSend {
+            from_address: "cosmos1from".to_string(),
+            to_address: "cosmos1to".to_string(),
+            amount: vec![ProtoCoin {
+                denom: "uatom".to_string(),
+                amount: "100".to_string(),
+            }],
+        };
+        let any = Any {
+            type_url: MsgSend::TYPE_URL.to_string(),
+            value: send.encode_to_vec(),
+        };
+        let json = any.to_json();
+        assert_eq!(json["@type"], "/cosmos.bank.v1beta1.MsgSend");
+        assert_eq!(json["fromAddress"], "cosmos1from");
+        assert_eq!(json["amount"][0]["amount"], "100");
+        assert_eq!(Any::from_json(&json).unwrap(), any);
+
+        // snake case field names as output by the sdk are accepted
+        let snake = serde_json::json!({
+            "@type": "/cosmos.bank.v1beta1.MsgSend",
+            "from_address": "cosmos1from",
+            "to_address": "cosmos1to",
+            "amount": [{"denom": "uatom", "amount": "100"}],
+        });
+        assert_eq!(Any::from_json(&snake).unwrap(), any);
+
+        let vote = MsgVote {
+            proposal_id: 12,
+            voter: "cosmos1voter".to_string(),
+            option: 4,
+        };
+        let json = vote.to_json();
+        assert_eq!(json["proposalId"], "12");
+        assert_eq!(json["option"], "VOTE_OPTION_NO_WITH_VETO");
+        assert_eq!(MsgVote::from_json(&json).unwrap(), vote);
+    }
+
+    #[test]
+    fn test_unknown_message_json() {
+        let any = Any {
+            type_url: "/example.v1.MsgExample".to_string(),
+            value: vec![1, 2, 3],
+        };
+        let json = any.to_json();
+        assert_eq!(json["value"], "AQID");
+        assert_eq!(Any::from_json(&json).unwrap(), any);
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        let msg = crate::Msg::new(
+            MsgSend::TYPE_URL,
+            MsgSend {
+                from_address: "cosmos1from".to_string(),
+                to_address: "cosmos1to".to_string(),
+                amount: vec![ProtoCoin {
+                    denom: "uatom".to_string(),
+                    amount: "100".to_string(),
+                }],
+            },
+        );
+        assert_eq!(
+            msg.to_canonical_json(),
+            r#"{"@type":"/cosmos.bank.v1beta1.MsgSend","amount":[{"amount":"100","denom":"uatom"}],"from_address":"cosmos1from","to_address":"cosmos1to"}"#
+        );
+
+        let tx = Tx {
+            body: Some(TxBody {
+                messages: vec![msg.0.clone()],
+                memo: "hi".to_string(),
+                timeout_height: 0,
+                extension_options: Vec::new(),
+                non_critical_extension_options: Vec::new(),
+            }),
+            auth_info: Some(AuthInfo {
+                signer_infos: vec![SignerInfo {
+                    public_key: Some(Any {
+                        type_url: Secp256k1PubKey::TYPE_URL.to_string(),
+                        value: Secp256k1PubKey { key: vec![2; 33] }.encode_to_vec(),
+                    }),
+                    mode_info: Some(ModeInfo {
+                        sum: Some(mode_info::Sum::Single(mode_info::Single { mode: 1 })),
+                    }),
+                    sequence: 7,
+                }],
+                fee: Some(ProtoFee {
+                    amount: Vec::new(),
+                    gas_limit: 200_000,
+                    payer: String::new(),
+                    granter: String::new(),
+                }),
+            }),
+            signatures: vec![vec![1, 2, 3]],
+        };
+        let json: Value = serde_json::from_str(&tx.to_canonical_json()).unwrap();
+        assert_eq!(json["body"]["timeout_height"], "0");
+        assert_eq!(
+            json["body"]["non_critical_extension_options"],
+            Value::Array(Vec::new())
+        );
+        let signer = &json["auth_info"]["signer_infos"][0];
+        assert_eq!(
+            signer["public_key"]["@type"],
+            "/cosmos.crypto.secp256k1.PubKey"
+        );
+        assert_eq!(signer["public_key"]["key"], base64::encode([2; 33]));
+        assert_eq!(signer["mode_info"]["single"]["mode"], "SIGN_MODE_DIRECT");
+        assert_eq!(signer["sequence"], "7");
+        assert_eq!(json["auth_info"]["fee"]["gas_limit"], "200000");
+        assert_eq!(json["signatures"][0], "AQID");
+        let round_trip = Tx::from_json(&json).unwrap();
+        assert_eq!(round_trip, tx);
+
+        let sign_doc = SignDoc {
+            body_bytes: vec![1, 2, 3],
+            auth_info_bytes: vec![4, 5, 6],
+            chain_id: "cosmoshub-4".to_string(),
+            account_number: 42,
+        };
+        assert_eq!(
+            sign_doc.to_canonical_json(),
+            r#"{"account_number":"42","auth_info_bytes":"BAUG","body_bytes":"AQID","chain_id":"cosmoshub-4"}"#
+        );
+    }
+}
@@ -1,9 +1,11 @@
+use crate::address::{Address, Bech32Variant};
 use crate::error::*;
 use crate::utils::hex_str_to_bytes;
-use crate::{address::Address, utils::ArrayString};
-use bech32::Variant;
+use crate::utils::ArrayString;
 use bech32::{self, FromBase32, ToBase32};
 use ripemd::Ripemd160 as Ripemd;
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::Digest as Sha2Digest;
 use sha2::Sha256;
 use std::fmt::{self, Display, Formatter};
@@ -83,8 +85,8 @@ impl PublicKey {
     /// provided as a utility for one step creation and change of prefix if the conventions
     /// in `to_address()` are incorrect
     pub fn to_address_with_prefix(&self, prefix: &str) -> Result<Address, AddressError> {
-        let sha256 = Sha256::digest(&self.bytes);
-        let ripemd160 = Ripemd::digest(&sha256);
+        let sha256 = Sha256::digest(self.bytes);
+        let ripemd160 = Ripemd::digest(sha256);
         let mut bytes: [u8; 20] = Default::default();
         bytes.copy_from_slice(&ripemd160[..]);
         Address::from_bytes(bytes, prefix)
@@ -102,17 +104,26 @@ impl PublicKey {
     /// Create a bech32 encoded public key with an arbitrary prefix
     ///
     /// * `hrp` - A prefix for a bech32 encoding. By a convention
-    /// Cosmos Network uses `cosmospub` as a prefix for encoding public keys.
+    ///   Cosmos Network uses `cosmospub` as a prefix for encoding public keys.
     pub fn to_bech32<T: Into<String>>(&self, hrp: T) -> Result<String, PublicKeyError> {
+        self.to_bech32_variant(hrp, Bech32Variant::Bech32)
+    }
+
+    /// Create a bech32 or bech32m encoded public key with an arbitrary prefix
+    pub fn to_bech32_variant<T: Into<String>>(
+        &self,
+        hrp: T,
+        variant: Bech32Variant,
+    ) -> Result<String, PublicKeyError> {
         let bech32 = bech32::encode(
             &hrp.into(),
             self.to_amino_bytes().to_base32(),
-            Variant::Bech32,
+            variant.into(),
         )?;
         Ok(bech32)
     }
 
-    /// Parse a bech32 encoded public key
+    /// Parse a bech32 encoded public key, either checksum variant is accepted
     ///
     /// * `s` - A bech32 encoded public key
     pub fn from_bech32(s: String) -> Result<PublicKey, PublicKeyError> {
@@ -179,6 +190,52 @@ impl fmt::Debug for PublicKey {
     }
 }
 
+/// Serializes as the bech32 string in human readable formats such as JSON and as the
+/// raw bytes followed by the prefix in binary formats
+impl Serialize for PublicKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            // serde only implements arrays of up to 32 elements
+            (&self.bytes[..], self.prefix).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let value = String::deserialize(deserializer)?;
+            value.parse().map_err(D::Error::custom)
+        } else {
+            let (bytes, prefix) = <(Vec<u8>, ArrayString)>::deserialize(deserializer)?;
+            let bytes: [u8; 33] = bytes
+                .try_into()
+                .map_err(|_| D::Error::custom(PublicKeyError::BytesDecodeErrorWrongLength))?;
+            Ok(PublicKey { bytes, prefix })
+        }
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl borsh::BorshSerialize for PublicKey {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        borsh::BorshSerialize::serialize(&self.bytes, writer)?;
+        borsh::BorshSerialize::serialize(self.prefix.as_str(), writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl borsh::BorshDeserialize for PublicKey {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let bytes = <[u8; 33] as borsh::BorshDeserialize>::deserialize_reader(reader)?;
+        let prefix = <String as borsh::BorshDeserialize>::deserialize_reader(reader)?;
+        PublicKey::from_bytes(bytes, prefix)
+            .map_err(|e| borsh::io::Error::new(borsh::io::ErrorKind::InvalidData, e.to_string()))
+    }
+}
+
 #[test]
 fn check_bech32() {
     let raw_bytes = [
@@ -215,7 +272,13 @@ fn check_bech32() {
 
     let check: Result<PublicKey, PublicKeyError> =
         "cosmospub1addwnpepq2t9r2d2cnpzkfanqxdwum0hgcnxuxh8gmh8jae2de026xvwh5ruxuv5let".parse();
-    assert_eq!(check.unwrap(), public_key)
+    assert_eq!(check.unwrap(), public_key);
+
+    let bech32m = public_key
+        .to_bech32_variant("cosmospub", Bech32Variant::Bech32m)
+        .unwrap();
+    assert_ne!(bech32m, res);
+    assert_eq!(PublicKey::from_bech32(bech32m).unwrap(), public_key);
 }
 
 #[test]
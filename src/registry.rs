@@ -0,0 +1,395 @@
+//! A registry of decoders keyed by type url, so that the message, event and account
+//! types of a specific chain can be decoded by this crate without it knowing about
+//! them. Downstream crates register their types once and pass the registry to the tx
+//! decoder, event parser and `Contact::with_registry` for account queries
+//!
+//! ```
+//! use deep_space::registry::AnyRegistry;
+//! use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+//!
+//! let mut registry = AnyRegistry::new();
+//! registry.register::<MsgSend>();
+//! assert!(registry.contains("/cosmos.bank.v1beta1.MsgSend"));
+//! ```
+
+use crate::client::types::{BaseAccount, CosmosAccount, CustomAccount};
+use crate::error::CosmosGrpcError;
+use crate::TypeUrl;
+use prost::{DecodeError, Message};
+use prost_types::Any;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::any::Any as StdAny;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Implemented for every type a TypedValue can hold
+trait AnyValue: fmt::Debug + Send + Sync + 'static {
+    fn as_any(&self) -> &dyn StdAny;
+    fn eq_value(&self, other: &dyn AnyValue) -> bool;
+}
+
+impl<T: fmt::Debug + PartialEq + Send + Sync + 'static> AnyValue for T {
+    fn as_any(&self) -> &dyn StdAny {
+        self
+    }
+
+    fn eq_value(&self, other: &dyn AnyValue) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+}
+
+/// A value produced by a registered decoder, retrieve the concrete type with
+/// `downcast_ref`. Cloning is cheap, the value is shared
+#[derive(Clone)]
+pub struct TypedValue {
+    type_url: String,
+    value: Arc<dyn AnyValue>,
+}
+
+impl TypedValue {
+    pub fn new<T: fmt::Debug + PartialEq + Send + Sync + 'static>(
+        type_url: impl Into<String>,
+        value: T,
+    ) -> Self {
+        TypedValue {
+            type_url: type_url.into(),
+            value: Arc::new(value),
+        }
+    }
+
+    /// The type url, or for events the event type, this value was decoded from
+    pub fn type_url(&self) -> &str {
+        &self.type_url
+    }
+
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.value.as_any().downcast_ref()
+    }
+
+    pub fn is<T: 'static>(&self) -> bool {
+        self.value.as_any().is::<T>()
+    }
+}
+
+impl fmt::Debug for TypedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.value, f)
+    }
+}
+
+impl PartialEq for TypedValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_url == other.type_url && self.value.eq_value(other.value.as_ref())
+    }
+}
+
+/// An Any decoded by a registry, left encoded if its type is not registered
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedAny {
+    Typed(TypedValue),
+    Other(Any),
+}
+
+impl DecodedAny {
+    pub fn type_url(&self) -> &str {
+        match self {
+            DecodedAny::Typed(value) => value.type_url(),
+            DecodedAny::Other(any) => &any.type_url,
+        }
+    }
+
+    /// The decoded value if it is a `T`, None for other types and unregistered Anys
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        match self {
+            DecodedAny::Typed(value) => value.downcast_ref(),
+            DecodedAny::Other(_) => None,
+        }
+    }
+}
+
+type AnyDecoder = Arc<dyn Fn(&[u8]) -> Result<TypedValue, DecodeError> + Send + Sync>;
+type EventDecoder = Arc<dyn Fn(&Value) -> Result<TypedValue, serde_json::Error> + Send + Sync>;
+type AccountDecoder = Arc<dyn Fn(&[u8]) -> Result<CustomAccount, DecodeError> + Send + Sync>;
+
+/// Decoders for Anys and typed events, keyed by type url. Registering a type url again
+/// replaces its decoder. Clones share the decoder functions
+#[derive(Clone, Default)]
+pub struct AnyRegistry {
+    decoders: HashMap<String, AnyDecoder>,
+    events: HashMap<String, EventDecoder>,
+    accounts: HashMap<String, AccountDecoder>,
+}
+
+impl AnyRegistry {
+    pub fn new() -> Self {
+        AnyRegistry::default()
+    }
+
+    /// Registers a proto message under its type url
+    pub fn register<M>(&mut self) -> &mut Self
+    where
+        M: Message + Default + TypeUrl + PartialEq + Send + Sync + 'static,
+    {
+        self.register_as::<M>(M::TYPE_URL)
+    }
+
+    /// Registers a proto message under `type_url`, for types without a TypeUrl impl
+    pub fn register_as<M>(&mut self, type_url: &str) -> &mut Self
+    where
+        M: Message + Default + PartialEq + Send + Sync + 'static,
+    {
+        self.register_decoder(type_url, |buf| M::decode(buf))
+    }
+
+    /// Registers a decoding function for `type_url`, for values that need more than a
+    /// plain proto decode
+    pub fn register_decoder<T, F>(&mut self, type_url: &str, decoder: F) -> &mut Self
+    where
+        T: fmt::Debug + PartialEq + Send + Sync + 'static,
+        F: Fn(&[u8]) -> Result<T, DecodeError> + Send + Sync + 'static,
+    {
+        let url = type_url.to_string();
+        self.decoders.insert(
+            type_url.to_string(),
+            Arc::new(move |buf| Ok(TypedValue::new(url.clone(), decoder(buf)?))),
+        );
+        self
+    }
+
+    /// Registers an account type, so that `AccountType::decode_any_with` and
+    /// `Contact::get_account` return it as `AccountType::Custom`. The type is also
+    /// registered as a plain message
+    pub fn register_account<A>(&mut self) -> &mut Self
+    where
+        A: Message + Default + TypeUrl + CosmosAccount + PartialEq + Send + Sync + 'static,
+    {
+        self.register_account_decoder(A::TYPE_URL, |buf| {
+            let account = A::decode(buf)?;
+            let base_account = account.get_base_account();
+            Ok((account, base_account))
+        })
+    }
+
+    /// Registers a decoding function for an account type, for accounts that do not
+    /// implement CosmosAccount, such as the vesting accounts of a specific chain. The
+    /// function returns the account and its base account, whose account number and
+    /// sequence are used when signing transactions for it
+    pub fn register_account_decoder<T, F>(&mut self, type_url: &str, decoder: F) -> &mut Self
+    where
+        T: fmt::Debug + PartialEq + Send + Sync + 'static,
+        F: Fn(&[u8]) -> Result<(T, BaseAccount), DecodeError> + Send + Sync + 'static,
+    {
+        let decoder = Arc::new(decoder);
+        let url = type_url.to_string();
+        let account_decoder = decoder.clone();
+        self.accounts.insert(
+            type_url.to_string(),
+            Arc::new(move |buf| {
+                let (account, base_account) = account_decoder(buf)?;
+                Ok(CustomAccount::new(
+                    TypedValue::new(url.clone(), account),
+                    base_account,
+                ))
+            }),
+        );
+        self.register_decoder(type_url, move |buf| {
+            decoder(buf).map(|(account, _)| account)
+        })
+    }
+
+    /// Registers a typed event, as emitted by `EmitTypedEvent` in the Cosmos SDK. The
+    /// event type is the proto name of the event without a leading `/`, for example
+    /// `cosmos.authz.v1beta1.EventGrant`. Each attribute holds the JSON of one field,
+    /// `T` is deserialized from the object of all attributes
+    pub fn register_event<T>(&mut self, kind: &str) -> &mut Self
+    where
+        T: DeserializeOwned + fmt::Debug + PartialEq + Send + Sync + 'static,
+    {
+        let kind_owned = kind.to_string();
+        self.events.insert(
+            kind.to_string(),
+            Arc::new(move |value| Ok(TypedValue::new(kind_owned.clone(), T::deserialize(value)?))),
+        );
+        self
+    }
+
+    /// True if a message or account decoder is registered for `type_url`
+    pub fn contains(&self, type_url: &str) -> bool {
+        self.decoders.contains_key(type_url)
+    }
+
+    /// True if a typed event decoder is registered for `kind`
+    pub fn contains_event(&self, kind: &str) -> bool {
+        self.events.contains_key(kind)
+    }
+
+    /// Decodes `any` with its registered decoder, Anys of unregistered types are
+    /// returned as they are
+    pub fn decode(&self, any: Any) -> Result<DecodedAny, DecodeError> {
+        match self.decoders.get(&any.type_url) {
+            Some(decoder) => Ok(DecodedAny::Typed(decoder(&any.value)?)),
+            None => Ok(DecodedAny::Other(any)),
+        }
+    }
+
+    /// Decodes an account Any of a type registered with `register_account`, None for
+    /// other types
+    pub(crate) fn decode_account(&self, any: &Any) -> Option<Result<CustomAccount, DecodeError>> {
+        self.accounts
+            .get(&any.type_url)
+            .map(|decoder| decoder(&any.value))
+    }
+
+    /// Decodes a typed event from its type and plain key value attributes, None if no
+    /// decoder is registered for `kind`. Attributes that are not valid JSON are read as
+    /// strings, since chains also attach plain attributes such as `msg_index`
+    pub fn decode_event(
+        &self,
+        kind: &str,
+        attributes: &[(String, String)],
+    ) -> Result<Option<TypedValue>, CosmosGrpcError> {
+        let decoder = match self.events.get(kind) {
+            Some(decoder) => decoder,
+            None => return Ok(None),
+        };
+        let mut object = Map::new();
+        for (key, value) in attributes {
+            let value =
+                serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.clone()));
+            object.insert(key.clone(), value);
+        }
+        decoder(&Value::Object(object))
+            .map(Some)
+            .map_err(|e| CosmosGrpcError::BadResponse(format!("Invalid {} event {}", kind, e)))
+    }
+}
+
+impl fmt::Debug for AnyRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut types: Vec<&String> = self.decoders.keys().collect();
+        types.sort();
+        let mut events: Vec<&String> = self.events.keys().collect();
+        events.sort();
+        f.debug_struct("AnyRegistry")
+            .field("types", &types)
+            .field("events", &events)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::types::AccountType;
+    use crate::utils::encode_any;
+    use cosmos_sdk_proto::cosmos::auth::v1beta1::BaseAccount as ProtoBaseAccount;
+
+    /// A chain specific message unknown to the proto crate
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    struct MsgMintWidget {
+        #[prost(string, tag = "1")]
+        owner: String,
+        #[prost(uint64, tag = "2")]
+        count: u64,
+    }
+
+    impl TypeUrl for MsgMintWidget {
+        const TYPE_URL: &'static str = "/widgets.v1.MsgMintWidget";
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    struct WidgetAccount {
+        #[prost(message, optional, tag = "1")]
+        base_account: Option<ProtoBaseAccount>,
+        #[prost(uint64, tag = "2")]
+        widgets: u64,
+    }
+
+    impl TypeUrl for WidgetAccount {
+        const TYPE_URL: &'static str = "/widgets.v1.WidgetAccount";
+    }
+
+    impl CosmosAccount for WidgetAccount {
+        fn get_base_account(&self) -> BaseAccount {
+            self.base_account.clone().unwrap().into()
+        }
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct EventWidgetMinted {
+        owner: String,
+        count: String,
+    }
+
+    #[test]
+    fn test_any_registry() {
+        let mut registry = AnyRegistry::new();
+        registry
+            .register::<MsgMintWidget>()
+            .register_account::<WidgetAccount>()
+            .register_event::<EventWidgetMinted>("widgets.v1.EventWidgetMinted");
+
+        let msg = MsgMintWidget {
+            owner: "alice".to_string(),
+            count: 3,
+        };
+        let decoded = registry
+            .decode(encode_any(msg.clone(), MsgMintWidget::TYPE_URL))
+            .unwrap();
+        assert_eq!(decoded.type_url(), MsgMintWidget::TYPE_URL);
+        assert_eq!(decoded.downcast_ref::<MsgMintWidget>(), Some(&msg));
+        assert_eq!(decoded.downcast_ref::<WidgetAccount>(), None);
+        let unknown = encode_any(msg, "/widgets.v1.MsgBurnWidget");
+        assert_eq!(
+            registry.decode(unknown.clone()).unwrap(),
+            DecodedAny::Other(unknown)
+        );
+
+        let account = WidgetAccount {
+            base_account: Some(ProtoBaseAccount {
+                address: crate::Address::from_bytes([1; 20], "cosmos")
+                    .unwrap()
+                    .to_string(),
+                pub_key: None,
+                account_number: 7,
+                sequence: 2,
+            }),
+            widgets: 5,
+        };
+        let any = encode_any(account.clone(), WidgetAccount::TYPE_URL);
+        assert!(AccountType::decode_any(any.clone()).is_err());
+        match AccountType::decode_any_with(any, &registry).unwrap() {
+            AccountType::Custom(custom) => {
+                assert_eq!(custom.value.downcast_ref::<WidgetAccount>(), Some(&account));
+                assert_eq!(custom.get_base_account().account_number, 7);
+            }
+            other => panic!("Expected a custom account, got {:?}", other),
+        }
+
+        let attributes = vec![
+            ("owner".to_string(), "\"alice\"".to_string()),
+            ("count".to_string(), "\"3\"".to_string()),
+            ("msg_index".to_string(), "0".to_string()),
+        ];
+        let event = registry
+            .decode_event("widgets.v1.EventWidgetMinted", &attributes)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            event.downcast_ref::<EventWidgetMinted>(),
+            Some(&EventWidgetMinted {
+                owner: "alice".to_string(),
+                count: "3".to_string(),
+            })
+        );
+        assert!(registry
+            .decode_event("transfer", &attributes)
+            .unwrap()
+            .is_none());
+        assert!(registry
+            .decode_event("widgets.v1.EventWidgetMinted", &attributes[1..])
+            .is_err());
+    }
+}
@@ -1,9 +1,255 @@
+//! Secp256k1 signatures in the compact form used by Cosmos transactions
+
+use crate::error::SignatureError;
 use crate::public_key::PublicKey;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId, Signature as EcdsaSignature};
+use secp256k1::Message as CurveMessage;
+use secp256k1::{PublicKey as PublicKeyEC, Secp256k1};
+use std::fmt;
 
-/// Signed data that contains both the signature, and the public key
-/// used to sign it.
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+/// A secp256k1 ECDSA signature in the 64 byte compact (r || s) form used by Cosmos.
+/// The s value is always normalized to the lower half of the curve order, the Cosmos
+/// SDK and most EVM verifiers reject high-s signatures as malleable.
+/// The recovery id is optional, it is only known if the signature was produced locally
+/// or provided by the signer, it's required to recover a public key from the signature.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Signature {
-    pub signature: Vec<u8>,
-    pub pub_key: PublicKey,
+    bytes: [u8; 64],
+    recovery_id: Option<u8>,
+}
+
+impl Signature {
+    /// Parses a 64 byte compact signature, normalizing it to low-s form
+    pub fn from_compact(bytes: &[u8]) -> Result<Signature, SignatureError> {
+        if bytes.len() != 64 {
+            return Err(SignatureError::WrongLength(bytes.len()));
+        }
+        let sig = EcdsaSignature::from_compact(bytes)?;
+        Ok(Signature::from_ecdsa(sig, None))
+    }
+
+    /// Parses a DER encoded signature, normalizing it to low-s form
+    pub fn from_der(bytes: &[u8]) -> Result<Signature, SignatureError> {
+        let sig = EcdsaSignature::from_der(bytes)?;
+        Ok(Signature::from_ecdsa(sig, None))
+    }
+
+    /// Parses a 65 byte r || s || v signature as produced by Ethereum style signers,
+    /// v may be either the raw recovery id (0 or 1) or offset by 27
+    pub fn from_recoverable(bytes: &[u8]) -> Result<Signature, SignatureError> {
+        if bytes.len() != 65 {
+            return Err(SignatureError::WrongLength(bytes.len()));
+        }
+        let v = match bytes[64] {
+            v @ 0..=3 => v,
+            v @ 27..=30 => v - 27,
+            v => return Err(SignatureError::InvalidRecoveryId(v)),
+        };
+        let sig = EcdsaSignature::from_compact(&bytes[0..64])?;
+        Ok(Signature::from_ecdsa(sig, Some(v)))
+    }
+
+    /// Builds a Signature out of a secp256k1 library signature, normalizing s
+    /// and adjusting the recovery id to match if one is provided
+    pub(crate) fn from_ecdsa(sig: EcdsaSignature, recovery_id: Option<u8>) -> Signature {
+        let original = sig.serialize_compact();
+        let mut normalized = sig;
+        normalized.normalize_s();
+        let bytes = normalized.serialize_compact();
+        // negating s negates the y coordinate of R, so the parity
+        // bit of the recovery id flips along with it
+        let recovery_id = if bytes != original {
+            recovery_id.map(|v| v ^ 1)
+        } else {
+            recovery_id
+        };
+        Signature { bytes, recovery_id }
+    }
+
+    pub(crate) fn from_recoverable_ecdsa(sig: RecoverableSignature) -> Signature {
+        let (id, _) = sig.serialize_compact();
+        Signature::from_ecdsa(sig.to_standard(), Some(id.to_i32() as u8))
+    }
+
+    /// Attaches a recovery id to this signature
+    pub fn with_recovery_id(self, recovery_id: u8) -> Result<Signature, SignatureError> {
+        if recovery_id > 3 {
+            return Err(SignatureError::InvalidRecoveryId(recovery_id));
+        }
+        Ok(Signature {
+            bytes: self.bytes,
+            recovery_id: Some(recovery_id),
+        })
+    }
+
+    /// Returns the 64 byte compact form of this signature, this is the form
+    /// that goes into a Cosmos transaction
+    pub fn to_compact(&self) -> [u8; 64] {
+        self.bytes
+    }
+
+    /// Returns the compact form of this signature as a slice of bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the DER encoded form of this signature
+    pub fn to_der(&self) -> Vec<u8> {
+        // can't fail, we validated the signature on construction
+        let sig = EcdsaSignature::from_compact(&self.bytes).unwrap();
+        sig.serialize_der().to_vec()
+    }
+
+    /// The recovery id of this signature, if known
+    pub fn recovery_id(&self) -> Option<u8> {
+        self.recovery_id
+    }
+
+    /// Returns the 65 byte r || s || v form of this signature, v is offset by 27
+    /// as expected by Ethereum's ecrecover. Recovery ids 2 and 3, for the
+    /// vanishingly rare r values at or above the curve order, have no v that
+    /// Ethereum accepts and are rejected
+    pub fn to_recoverable(&self) -> Result<[u8; 65], SignatureError> {
+        let v = self.recovery_id.ok_or(SignatureError::MissingRecoveryId)?;
+        if v > 1 {
+            return Err(SignatureError::InvalidRecoveryId(v));
+        }
+        let mut out = [0u8; 65];
+        out[0..64].copy_from_slice(&self.bytes);
+        out[64] = v + 27;
+        Ok(out)
+    }
+
+    /// Recovers the public key that produced this signature over the provided sha256 digest
+    /// the recovery id must be known
+    pub fn recover_pubkey(&self, digest: [u8; 32]) -> Result<PublicKey, SignatureError> {
+        let v = self.recovery_id.ok_or(SignatureError::MissingRecoveryId)?;
+        let id = RecoveryId::from_i32(v as i32)?;
+        let sig = RecoverableSignature::from_compact(&self.bytes, id)?;
+        let msg = CurveMessage::from_slice(&digest)?;
+        let secp256k1 = Secp256k1::verification_only();
+        let key = secp256k1.recover_ecdsa(&msg, &sig)?;
+        Ok(PublicKey::from_bytes(
+            key.serialize(),
+            PublicKey::DEFAULT_PREFIX,
+        )?)
+    }
+
+    /// Checks that this signature was produced by the provided public key over the
+    /// provided sha256 digest
+    pub fn verify(&self, digest: [u8; 32], public_key: &PublicKey) -> bool {
+        let secp256k1 = Secp256k1::verification_only();
+        let (msg, key, sig) = match (
+            CurveMessage::from_slice(&digest),
+            PublicKeyEC::from_slice(public_key.as_bytes()),
+            EcdsaSignature::from_compact(&self.bytes),
+        ) {
+            (Ok(msg), Ok(key), Ok(sig)) => (msg, key, sig),
+            _ => return false,
+        };
+        secp256k1.verify_ecdsa(&msg, &sig, &key).is_ok()
+    }
+
+    /// Returns true if the provided compact signature is already in low-s form
+    pub fn is_low_s(bytes: &[u8]) -> Result<bool, SignatureError> {
+        let sig = Signature::from_compact(bytes)?;
+        Ok(sig.bytes[..] == bytes[..])
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Signature({}, recovery_id: {:?})",
+            crate::utils::bytes_to_hex_str(&self.bytes),
+            self.recovery_id
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::private_key::PrivateKey;
+    use crate::utils::{bytes_to_hex_str, hex_str_to_bytes};
+    use sha2::{Digest, Sha256};
+    use std::str::FromStr;
+
+    /// secp256k1 order n, used to produce the high-s twin of a signature
+    const CURVE_N: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+    pub(crate) fn flip_s(compact: [u8; 64]) -> [u8; 64] {
+        use num::BigUint;
+        let n = BigUint::from_bytes_be(&hex_str_to_bytes(CURVE_N).unwrap());
+        let s = BigUint::from_bytes_be(&compact[32..64]);
+        let high = (n - s).to_bytes_be();
+        let mut out = compact;
+        out[32..64].copy_from_slice(&[vec![0u8; 32 - high.len()], high].concat());
+        out
+    }
+
+    #[test]
+    fn test_rfc6979_vector() {
+        // private key 1 signing sha256("Satoshi Nakamoto"), a widely used deterministic
+        // nonce test vector for secp256k1
+        let key = PrivateKey::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        )
+        .unwrap();
+        let digest: [u8; 32] = Sha256::digest(b"Satoshi Nakamoto").into();
+        let sig = key.sign_recoverable(digest).unwrap();
+        assert_eq!(
+            bytes_to_hex_str(&sig.to_compact()),
+            "934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d82442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5"
+        );
+        // deterministic nonces mean signing twice is identical
+        assert_eq!(sig, key.sign_recoverable(digest).unwrap());
+    }
+
+    #[test]
+    fn test_low_s_normalization_and_recovery() {
+        let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let public_key = key.to_public_key(PublicKey::DEFAULT_PREFIX).unwrap();
+        let digest: [u8; 32] = Sha256::digest(b"deep space").into();
+        let sig = key.sign_recoverable(digest).unwrap();
+        assert!(Signature::is_low_s(sig.as_bytes()).unwrap());
+        assert!(sig.verify(digest, &public_key));
+        assert_eq!(sig.recover_pubkey(digest).unwrap(), public_key);
+
+        // a high-s signature with the matching recovery id normalizes to the same signature
+        let high = flip_s(sig.to_compact());
+        assert!(!Signature::is_low_s(&high).unwrap());
+        let mut high_recoverable = high.to_vec();
+        high_recoverable.push(sig.recovery_id().unwrap() ^ 1);
+        let normalized = Signature::from_recoverable(&high_recoverable).unwrap();
+        assert_eq!(normalized, sig);
+        assert_eq!(normalized.recover_pubkey(digest).unwrap(), public_key);
+    }
+
+    #[test]
+    fn test_der_round_trip() {
+        let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let digest: [u8; 32] = Sha256::digest(b"deep space").into();
+        let sig = key.sign_recoverable(digest).unwrap();
+        let der = sig.to_der();
+        let parsed = Signature::from_der(&der).unwrap();
+        assert_eq!(parsed.to_compact(), sig.to_compact());
+        assert_eq!(parsed.recovery_id(), None);
+        assert!(parsed.recover_pubkey(digest).is_err());
+
+        let eth = sig.to_recoverable().unwrap();
+        assert!(eth[64] == 27 || eth[64] == 28);
+        assert_eq!(Signature::from_recoverable(&eth).unwrap(), sig);
+
+        // v = 29 or 30 is rejected by Ethereum verifiers, so ids above 1 are never emitted
+        for id in [2, 3] {
+            let sig = sig.with_recovery_id(id).unwrap();
+            assert!(matches!(
+                sig.to_recoverable(),
+                Err(SignatureError::InvalidRecoveryId(v)) if v == id
+            ));
+        }
+        assert!(parsed.to_recoverable().is_err());
+    }
 }
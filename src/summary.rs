@@ -0,0 +1,277 @@
+//! Human readable one line summaries of decoded transactions, for CLIs and approval
+//! screens. Text taken from the transaction, such as the memo, is stripped of control
+//! and bidirectional override characters so it can not disguise the rest of the summary
+
+use crate::coin::Coin;
+use crate::tx::DecodedTx;
+use crate::Uint256;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::{Metadata, MsgSend};
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::MsgWithdrawDelegatorReward;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::MsgVote;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::{MsgBeginRedelegate, MsgDelegate, MsgUndelegate};
+use cosmos_sdk_proto::ibc::applications::transfer::v1::MsgTransfer;
+use prost::Message;
+use prost_types::Any;
+
+/// Controls how a summary describes messages and amounts. Every method has a default,
+/// override `format_msg` to describe additional message types and `format_coin` to
+/// change how amounts are shown
+pub trait TxFormatter {
+    /// Describes a message, None to fall back to the built in descriptions
+    fn format_msg(&self, _msg: &Any) -> Option<String> {
+        None
+    }
+
+    fn format_coin(&self, coin: &Coin) -> String {
+        coin.to_string()
+    }
+}
+
+/// Shows amounts in base units, for example `10000000uatom`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultFormatter;
+
+impl TxFormatter for DefaultFormatter {}
+
+/// Shows amounts in the display unit of the chain's denom metadata, for example
+/// `10 ATOM`, denoms without metadata are shown in base units
+#[derive(Debug, Clone, Default)]
+pub struct MetadataFormatter {
+    pub metadata: Vec<Metadata>,
+}
+
+impl TxFormatter for MetadataFormatter {
+    fn format_coin(&self, coin: &Coin) -> String {
+        let meta = match self.metadata.iter().find(|m| m.base == coin.denom) {
+            Some(meta) => meta,
+            None => return coin.to_string(),
+        };
+        let unit = match meta.denom_units.iter().find(|u| u.denom == meta.display) {
+            Some(unit) if unit.exponent <= 38 => unit,
+            _ => return coin.to_string(),
+        };
+        let scale = Uint256::from_u128(10u128.pow(unit.exponent));
+        let (whole, fraction) = coin.amount.divide(scale).unwrap();
+        let fraction = format!(
+            "{:0>width$}",
+            fraction.to_string(),
+            width = unit.exponent as usize
+        );
+        let fraction = fraction.trim_end_matches('0');
+        let symbol = if meta.symbol.is_empty() {
+            &meta.display
+        } else {
+            &meta.symbol
+        };
+        if fraction.is_empty() {
+            format!("{} {}", whole, symbol)
+        } else {
+            format!("{}.{} {}", whole, fraction, symbol)
+        }
+    }
+}
+
+/// Removes characters that could hide or reorder the surrounding text
+fn sanitize(text: &str) -> String {
+    text.chars()
+        .filter(|c| {
+            !c.is_control() && !matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+        })
+        .collect()
+}
+
+fn format_coins(formatter: &impl TxFormatter, coins: &[ProtoCoin]) -> String {
+    let formatted: Vec<String> = coins
+        .iter()
+        .map(
+            |coin| match Uint256::from_dec_or_hex_str_restricted(&coin.amount) {
+                Ok(amount) => formatter.format_coin(&Coin {
+                    amount,
+                    denom: sanitize(&coin.denom),
+                }),
+                Err(_) => format!("invalid amount of {}", sanitize(&coin.denom)),
+            },
+        )
+        .collect();
+    if formatted.is_empty() {
+        "nothing".to_string()
+    } else {
+        formatted.join(", ")
+    }
+}
+
+/// The built in descriptions of common messages, None for other types or messages
+/// that fail to decode
+fn describe(formatter: &impl TxFormatter, msg: &Any) -> Option<String> {
+    let buf = msg.value.as_slice();
+    let coin = |c: Option<ProtoCoin>| format_coins(formatter, &c.into_iter().collect::<Vec<_>>());
+    let res = match msg.type_url.as_str() {
+        "/cosmos.bank.v1beta1.MsgSend" => {
+            let m = MsgSend::decode(buf).ok()?;
+            format!(
+                "send {} from {} to {}",
+                format_coins(formatter, &m.amount),
+                m.from_address,
+                m.to_address
+            )
+        }
+        "/cosmos.staking.v1beta1.MsgDelegate" => {
+            let m = MsgDelegate::decode(buf).ok()?;
+            format!(
+                "delegate {} from {} to {}",
+                coin(m.amount),
+                m.delegator_address,
+                m.validator_address
+            )
+        }
+        "/cosmos.staking.v1beta1.MsgUndelegate" => {
+            let m = MsgUndelegate::decode(buf).ok()?;
+            format!(
+                "undelegate {} from {} for {}",
+                coin(m.amount),
+                m.validator_address,
+                m.delegator_address
+            )
+        }
+        "/cosmos.staking.v1beta1.MsgBeginRedelegate" => {
+            let m = MsgBeginRedelegate::decode(buf).ok()?;
+            format!(
+                "redelegate {} of {} from {} to {}",
+                coin(m.amount),
+                m.delegator_address,
+                m.validator_src_address,
+                m.validator_dst_address
+            )
+        }
+        "/cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward" => {
+            let m = MsgWithdrawDelegatorReward::decode(buf).ok()?;
+            format!(
+                "withdraw rewards of {} from {}",
+                m.delegator_address, m.validator_address
+            )
+        }
+        "/cosmos.gov.v1beta1.MsgVote" => {
+            let m = MsgVote::decode(buf).ok()?;
+            let option = match m.option {
+                1 => "yes",
+                2 => "abstain",
+                3 => "no",
+                4 => "no with veto",
+                _ => "unspecified",
+            };
+            format!(
+                "vote {} on proposal {} as {}",
+                option, m.proposal_id, m.voter
+            )
+        }
+        "/ibc.applications.transfer.v1.MsgTransfer" => {
+            let m = MsgTransfer::decode(buf).ok()?;
+            format!(
+                "ibc transfer {} from {} to {} over {}",
+                coin(m.token),
+                m.sender,
+                m.receiver,
+                m.source_channel
+            )
+        }
+        _ => return None,
+    };
+    Some(res)
+}
+
+impl DecodedTx {
+    /// A one line description of this tx using the default formatter, for example
+    /// `send 10uatom from cosmos1... to cosmos1...; memo: rent`
+    pub fn summarize(&self) -> String {
+        self.summarize_with(&DefaultFormatter)
+    }
+
+    /// A one line description of this tx, messages are described by `formatter` where
+    /// it can and otherwise by the built in descriptions or their type url
+    pub fn summarize_with(&self, formatter: &impl TxFormatter) -> String {
+        let mut parts: Vec<String> = self
+            .messages
+            .iter()
+            .map(|msg| {
+                let text = formatter
+                    .format_msg(msg)
+                    .or_else(|| describe(formatter, msg))
+                    .unwrap_or_else(|| format!("unknown message {}", msg.type_url));
+                sanitize(&text)
+            })
+            .collect();
+        if !self.memo.is_empty() {
+            parts.push(format!("memo: {}", sanitize(&self.memo)));
+        }
+        parts.join("; ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::encode_any;
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::DenomUnit;
+
+    fn decoded(messages: Vec<Any>, memo: &str) -> DecodedTx {
+        DecodedTx {
+            hash: String::new(),
+            messages,
+            memo: memo.to_string(),
+            timeout_height: 0,
+            fee: None,
+            sequences: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let send = encode_any(
+            MsgSend {
+                from_address: "cosmos1a".to_string(),
+                to_address: "cosmos1b".to_string(),
+                amount: vec![ProtoCoin {
+                    denom: "uatom".to_string(),
+                    amount: "10000000".to_string(),
+                }],
+            },
+            "/cosmos.bank.v1beta1.MsgSend",
+        );
+        let unknown = Any {
+            type_url: "/chain.custom.v1.MsgThing".to_string(),
+            value: Vec::new(),
+        };
+        let tx = decoded(vec![send, unknown], "rent\u{202E}evil\n");
+        assert_eq!(
+            tx.summarize(),
+            "send 10000000uatom from cosmos1a to cosmos1b; \
+             unknown message /chain.custom.v1.MsgThing; memo: rentevil"
+        );
+
+        let formatter = MetadataFormatter {
+            metadata: vec![Metadata {
+                description: String::new(),
+                denom_units: vec![
+                    DenomUnit {
+                        denom: "uatom".to_string(),
+                        exponent: 0,
+                        aliases: Vec::new(),
+                    },
+                    DenomUnit {
+                        denom: "atom".to_string(),
+                        exponent: 6,
+                        aliases: Vec::new(),
+                    },
+                ],
+                base: "uatom".to_string(),
+                display: "atom".to_string(),
+                name: String::new(),
+                symbol: "ATOM".to_string(),
+            }],
+        };
+        assert!(tx
+            .summarize_with(&formatter)
+            .starts_with("send 10 ATOM from cosmos1a to cosmos1b;"));
+    }
+}
@@ -0,0 +1,223 @@
+//! Canonical signing test vectors, enabled with the `test_vectors` feature. Each vector
+//! lists the inputs of a transaction along with the SignDoc, signature and TxRaw bytes
+//! this library produces for them. Downstream chains can build the same transactions with
+//! their own tooling (for example `gaiad tx ... --generate-only` followed by `gaiad tx sign`)
+//! and compare the results to check signature compatibility without running a node.
+
+use crate::error::PrivateKeyError;
+use crate::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use crate::{Address, Coin, Fee, MessageArgs, Msg, PrivateKey, PublicKey, UnsignedTx};
+
+/// A single signing test vector, all byte strings are lowercase hex
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignDocVector {
+    pub name: &'static str,
+    /// Inputs
+    pub private_key: &'static str,
+    pub chain_id: &'static str,
+    pub account_number: u64,
+    pub sequence: u64,
+    pub memo: &'static str,
+    pub timeout_height: u64,
+    /// The messages as a proto3 JSON array, in the format accepted by Msg's Deserialize impl
+    pub messages: &'static str,
+    /// The fee amount as a coin string, for example 5000uatom
+    pub fee: &'static str,
+    pub gas_limit: u64,
+    /// Outputs
+    pub address: &'static str,
+    pub sign_doc: &'static str,
+    pub signature: &'static str,
+    pub tx_raw: &'static str,
+}
+
+/// The outputs this library produces for a vector's inputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignDocOutput {
+    pub address: String,
+    pub sign_doc: String,
+    pub signature: String,
+    pub tx_raw: String,
+}
+
+impl SignDocVector {
+    pub fn private_key(&self) -> PrivateKey {
+        self.private_key.parse().unwrap()
+    }
+
+    pub fn messages(&self) -> Vec<Msg> {
+        serde_json::from_str(self.messages).unwrap()
+    }
+
+    pub fn message_args(&self) -> MessageArgs {
+        let amount: Coin = self.fee.parse().unwrap();
+        MessageArgs {
+            sequence: self.sequence,
+            fee: Fee {
+                amount: vec![amount],
+                gas_limit: self.gas_limit,
+                payer: None,
+                granter: None,
+            },
+            timeout_height: self.timeout_height,
+            chain_id: self.chain_id.to_string(),
+            account_number: self.account_number,
+            ..Default::default()
+        }
+    }
+
+    /// Builds and signs the transaction described by this vector's inputs
+    pub fn compute(&self) -> Result<SignDocOutput, PrivateKeyError> {
+        let key = self.private_key();
+        let public_key = key.to_public_key(PublicKey::DEFAULT_PREFIX)?;
+        let messages = self.messages();
+        let unsigned = UnsignedTx::new(&messages, self.message_args(), self.memo, public_key)?;
+        let signature = key.sign_recoverable(unsigned.digest())?.to_compact();
+        Ok(SignDocOutput {
+            address: key.to_address("cosmos")?.to_string(),
+            sign_doc: bytes_to_hex_str(unsigned.sign_doc_bytes()),
+            signature: bytes_to_hex_str(&signature),
+            tx_raw: bytes_to_hex_str(&unsigned.into_tx_raw_bytes(signature)),
+        })
+    }
+
+    /// Checks that this library still produces the expected outputs, returning the
+    /// name of the first output that differs
+    pub fn verify(&self) -> Result<(), String> {
+        let output = self
+            .compute()
+            .map_err(|e| format!("{}: {}", self.name, e))?;
+        let checks = [
+            ("address", self.address, output.address),
+            ("sign_doc", self.sign_doc, output.sign_doc),
+            ("signature", self.signature, output.signature),
+            ("tx_raw", self.tx_raw, output.tx_raw),
+        ];
+        for (field, expected, actual) in checks {
+            if expected != actual {
+                return Err(format!(
+                    "{}: {} expected {} got {}",
+                    self.name, field, expected, actual
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A bech32 address test vector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bech32Vector {
+    pub bech32: &'static str,
+    /// The address bytes as hex, None if the string must be rejected
+    pub bytes: Option<&'static str>,
+}
+
+impl Bech32Vector {
+    pub fn verify(&self) -> Result<(), String> {
+        let parsed = self.bech32.parse::<Address>();
+        match (self.bytes, parsed) {
+            (Some(bytes), Ok(address))
+                if address.as_bytes() == hex_str_to_bytes(bytes).unwrap() =>
+            {
+                Ok(())
+            }
+            (None, Err(_)) => Ok(()),
+            (expected, parsed) => Err(format!(
+                "{}: expected {:?} got {:?}",
+                self.bech32, expected, parsed
+            )),
+        }
+    }
+}
+
+pub const SIGN_DOC_VECTORS: &[SignDocVector] = &[
+    SignDocVector {
+        name: "bank send with memo",
+        private_key: "ed2a04978f54f5481dd23c9890642903d796029f8dfdc1f3b33e904016c8b844",
+        chain_id: "cosmoshub-4",
+        account_number: 12345,
+        sequence: 7,
+        memo: "deep_space",
+        timeout_height: 0,
+        messages: r#"[{"@type":"/cosmos.bank.v1beta1.MsgSend","fromAddress":"cosmos1csh2lt2vsx90ep3u482htzrgh50cs4h67wlk4v","toAddress":"cosmos1xem5ldz55xw8kt29wwz9867eldxt0qr5qsjcm2","amount":[{"denom":"uatom","amount":"1000000"}]}]"#,
+        fee: "5000uatom",
+        gas_limit: 200000,
+        address: "cosmos1csh2lt2vsx90ep3u482htzrgh50cs4h67wlk4v",
+        sign_doc: "0a9f010a90010a1c2f636f736d6f732e62616e6b2e763162657461312e4d736753656e6412700a2d636f736d6f7331637368326c743276737839306570337534383268747a7267683530637334683637776c6b3476122d636f736d6f733178656d356c647a35357877386b74323977777a39383637656c6478743071723571736a636d321a100a057561746f6d120731303030303030120a646565705f737061636512670a500a460a1f2f636f736d6f732e63727970746f2e736563703235366b312e5075624b657912230a210368bfb21033784d2ed2d8cfb81ef2aedbea3b89e8982c732359dbcf1c44c854f712040a020801180712130a0d0a057561746f6d12043530303010c09a0c1a0b636f736d6f736875622d3420b960",
+        signature: "512f25d6edfc3a5d0f327ad2d6e560bbfd89227ec19bf99ff7040ed00db95ec424a9e91034bf9f3754f6bf667b6e897f428da368dbe3051a3deee71c26bf4bd5",
+        tx_raw: "0a9f010a90010a1c2f636f736d6f732e62616e6b2e763162657461312e4d736753656e6412700a2d636f736d6f7331637368326c743276737839306570337534383268747a7267683530637334683637776c6b3476122d636f736d6f733178656d356c647a35357877386b74323977777a39383637656c6478743071723571736a636d321a100a057561746f6d120731303030303030120a646565705f737061636512670a500a460a1f2f636f736d6f732e63727970746f2e736563703235366b312e5075624b657912230a210368bfb21033784d2ed2d8cfb81ef2aedbea3b89e8982c732359dbcf1c44c854f712040a020801180712130a0d0a057561746f6d12043530303010c09a0c1a40512f25d6edfc3a5d0f327ad2d6e560bbfd89227ec19bf99ff7040ed00db95ec424a9e91034bf9f3754f6bf667b6e897f428da368dbe3051a3deee71c26bf4bd5",
+    },
+    SignDocVector {
+        name: "delegate with timeout height",
+        private_key: "a0f799bdbb3e790491f42d38a93b847786ac0cb5d062f9f36eb6c9b0f36c6a23",
+        chain_id: "testing",
+        account_number: 0,
+        sequence: 0,
+        memo: "",
+        timeout_height: 1000,
+        messages: r#"[{"@type":"/cosmos.staking.v1beta1.MsgDelegate","delegatorAddress":"cosmos1xem5ldz55xw8kt29wwz9867eldxt0qr5qsjcm2","validatorAddress":"cosmosvaloper1csh2lt2vsx90ep3u482htzrgh50cs4h6m6trel","amount":{"denom":"uatom","amount":"500"}}]"#,
+        fee: "2500uatom",
+        gas_limit: 150000,
+        address: "cosmos1xem5ldz55xw8kt29wwz9867eldxt0qr5qsjcm2",
+        sign_doc: "0aa0010a9a010a232f636f736d6f732e7374616b696e672e763162657461312e4d736744656c656761746512730a2d636f736d6f733178656d356c647a35357877386b74323977777a39383637656c6478743071723571736a636d321234636f736d6f7376616c6f70657231637368326c743276737839306570337534383268747a726768353063733468366d367472656c1a0c0a057561746f6d120335303018e80712650a4e0a460a1f2f636f736d6f732e63727970746f2e736563703235366b312e5075624b657912230a210284e4033365fdf86e9f6ac9dff7ce9085a7668152811e830f6fa1a0fe8c1cb53112040a02080112130a0d0a057561746f6d12043235303010f093091a0774657374696e67",
+        signature: "ff36e1ddd9b203d571309a5b550d1aaa6119ac6eb013c4fb1fdf36693328a17124fe519a5fd4f70d2e67a7e6c775479d72ae54d567a08029fbf997c508d00e68",
+        tx_raw: "0aa0010a9a010a232f636f736d6f732e7374616b696e672e763162657461312e4d736744656c656761746512730a2d636f736d6f733178656d356c647a35357877386b74323977777a39383637656c6478743071723571736a636d321234636f736d6f7376616c6f70657231637368326c743276737839306570337534383268747a726768353063733468366d367472656c1a0c0a057561746f6d120335303018e80712650a4e0a460a1f2f636f736d6f732e63727970746f2e736563703235366b312e5075624b657912230a210284e4033365fdf86e9f6ac9dff7ce9085a7668152811e830f6fa1a0fe8c1cb53112040a02080112130a0d0a057561746f6d12043235303010f093091a40ff36e1ddd9b203d571309a5b550d1aaa6119ac6eb013c4fb1fdf36693328a17124fe519a5fd4f70d2e67a7e6c775479d72ae54d567a08029fbf997c508d00e68",
+    },
+    SignDocVector {
+        name: "withdraw rewards and restake",
+        private_key: "ed2a04978f54f5481dd23c9890642903d796029f8dfdc1f3b33e904016c8b844",
+        chain_id: "cosmoshub-4",
+        account_number: 12345,
+        sequence: 8,
+        memo: "",
+        timeout_height: 0,
+        messages: r#"[{"@type":"/cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward","delegatorAddress":"cosmos1csh2lt2vsx90ep3u482htzrgh50cs4h67wlk4v","validatorAddress":"cosmosvaloper1csh2lt2vsx90ep3u482htzrgh50cs4h6m6trel"},{"@type":"/cosmos.staking.v1beta1.MsgDelegate","delegatorAddress":"cosmos1csh2lt2vsx90ep3u482htzrgh50cs4h67wlk4v","validatorAddress":"cosmosvaloper1csh2lt2vsx90ep3u482htzrgh50cs4h6m6trel","amount":{"denom":"uatom","amount":"250000"}}]"#,
+        fee: "7500uatom",
+        gas_limit: 300000,
+        address: "cosmos1csh2lt2vsx90ep3u482htzrgh50cs4h67wlk4v",
+        sign_doc: "0ac3020aa0010a372f636f736d6f732e646973747269627574696f6e2e763162657461312e4d7367576974686472617744656c656761746f7252657761726412650a2d636f736d6f7331637368326c743276737839306570337534383268747a7267683530637334683637776c6b34761234636f736d6f7376616c6f70657231637368326c743276737839306570337534383268747a726768353063733468366d367472656c0a9d010a232f636f736d6f732e7374616b696e672e763162657461312e4d736744656c656761746512760a2d636f736d6f7331637368326c743276737839306570337534383268747a7267683530637334683637776c6b34761234636f736d6f7376616c6f70657231637368326c743276737839306570337534383268747a726768353063733468366d367472656c1a0f0a057561746f6d120632353030303012670a500a460a1f2f636f736d6f732e63727970746f2e736563703235366b312e5075624b657912230a210368bfb21033784d2ed2d8cfb81ef2aedbea3b89e8982c732359dbcf1c44c854f712040a020801180812130a0d0a057561746f6d12043735303010e0a7121a0b636f736d6f736875622d3420b960",
+        signature: "b99574c7c4d01458d0a458d5e50325652a5711886395662e130581ac2394a7131aff38a4d0e078d6e0d5dc7b4698cd0ea62c491515e44b6a0d6601a9299b5c43",
+        tx_raw: "0ac3020aa0010a372f636f736d6f732e646973747269627574696f6e2e763162657461312e4d7367576974686472617744656c656761746f7252657761726412650a2d636f736d6f7331637368326c743276737839306570337534383268747a7267683530637334683637776c6b34761234636f736d6f7376616c6f70657231637368326c743276737839306570337534383268747a726768353063733468366d367472656c0a9d010a232f636f736d6f732e7374616b696e672e763162657461312e4d736744656c656761746512760a2d636f736d6f7331637368326c743276737839306570337534383268747a7267683530637334683637776c6b34761234636f736d6f7376616c6f70657231637368326c743276737839306570337534383268747a726768353063733468366d367472656c1a0f0a057561746f6d120632353030303012670a500a460a1f2f636f736d6f732e63727970746f2e736563703235366b312e5075624b657912230a210368bfb21033784d2ed2d8cfb81ef2aedbea3b89e8982c732359dbcf1c44c854f712040a020801180812130a0d0a057561746f6d12043735303010e0a7121a40b99574c7c4d01458d0a458d5e50325652a5711886395662e130581ac2394a7131aff38a4d0e078d6e0d5dc7b4698cd0ea62c491515e44b6a0d6601a9299b5c43",
+    },
+];
+
+pub const BECH32_VECTORS: &[Bech32Vector] = &[
+    Bech32Vector {
+        bech32: "cosmos1csh2lt2vsx90ep3u482htzrgh50cs4h67wlk4v",
+        bytes: Some("c42eafad4c818afc863ca9d5758868bd1f8856fa"),
+    },
+    Bech32Vector {
+        bech32: "cosmosvaloper1csh2lt2vsx90ep3u482htzrgh50cs4h6m6trel",
+        bytes: Some("c42eafad4c818afc863ca9d5758868bd1f8856fa"),
+    },
+    // bad checksum
+    Bech32Vector {
+        bech32: "cosmos1csh2lt2vsx90ep3u482htzrgh50cs4h67wlk4w",
+        bytes: None,
+    },
+    // mixed case
+    Bech32Vector {
+        bech32: "cosmos1csh2LT2VSX90EP3U482HTZRGH50CS4H67WLK4V",
+        bytes: None,
+    },
+    Bech32Vector {
+        bech32: "",
+        bytes: None,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors() {
+        for vector in SIGN_DOC_VECTORS {
+            vector.verify().unwrap();
+        }
+        for vector in BECH32_VECTORS {
+            vector.verify().unwrap();
+        }
+    }
+}
@@ -0,0 +1,1741 @@
+//! Unsigned transaction handling, allows the signature over a transaction to be produced
+//! outside of this crate. This is useful for hardware wallets, remote signers, and threshold
+//! (MPC) signing systems where the private key is never available in a single process.
+
+use crate::coin::Fee;
+use crate::error::{PrivateKeyError, TxVerificationError};
+use crate::msg::Msg;
+use crate::private_key::{validate_tx, MessageArgs, PrivateKey};
+use crate::proto_json::{as_object, field, ProtoJson};
+use crate::public_key::PublicKey;
+use crate::registry::{AnyRegistry, DecodedAny};
+use crate::signature::Signature;
+use crate::utils::{bytes_to_hex_str, encode_any};
+use crate::Address;
+use crate::Uint256;
+use cosmos_sdk_proto::cosmos::crypto::secp256k1::PubKey as ProtoSecp256k1Pubkey;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    mode_info, AuthInfo, Fee as ProtoFee, ModeInfo, SignDoc, SignerInfo, Tx, TxBody, TxRaw,
+};
+use prost::{DecodeError, Message};
+use prost_types::Any;
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::time::SystemTime;
+
+/// The type url of a secp256k1 public key as used in Cosmos SignerInfo
+pub const SECP256K1_PUBKEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+
+/// The sign mode a signer declares in its SignerInfo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignMode {
+    /// SIGN_MODE_DIRECT, the signature is over the proto encoded SignDoc
+    #[default]
+    Direct,
+    /// SIGN_MODE_LEGACY_AMINO_JSON, the signature is over the amino JSON sign doc which
+    /// this crate does not produce, the signer must build and sign it themselves
+    LegacyAminoJson,
+}
+
+impl SignMode {
+    pub fn as_i32(self) -> i32 {
+        match self {
+            SignMode::Direct => 1,
+            SignMode::LegacyAminoJson => 127,
+        }
+    }
+}
+
+/// A signer that produces a compact (64 byte r || s) secp256k1 signature over
+/// the sha256 digest of a SignDoc, possibly asynchronously. Implement this trait
+/// to plug an external signing system into transaction generation.
+pub trait AsyncSigner {
+    /// The compressed public key that will be placed in the SignerInfo of the transaction
+    /// this must match the key that produces the signature or the transaction will be rejected
+    fn public_key(&self) -> Result<PublicKey, PrivateKeyError>;
+
+    /// Signs the provided sha256 digest of a SignDoc, returning a compact signature
+    fn sign_digest(
+        &self,
+        digest: [u8; 32],
+    ) -> impl Future<Output = Result<[u8; 64], PrivateKeyError>> + Send;
+}
+
+/// TxBody as defined by Cosmos SDK 0.53, the generated TxBody predates the
+/// `unordered` and `timeout_timestamp` fields. When both are unset this encodes
+/// byte for byte identically to the older TxBody so it is used unconditionally.
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct TxBodyV053 {
+    #[prost(message, repeated, tag = "1")]
+    messages: Vec<prost_types::Any>,
+    #[prost(string, tag = "2")]
+    memo: String,
+    #[prost(uint64, tag = "3")]
+    timeout_height: u64,
+    #[prost(bool, tag = "4")]
+    unordered: bool,
+    #[prost(message, optional, tag = "5")]
+    timeout_timestamp: Option<prost_types::Timestamp>,
+    #[prost(message, repeated, tag = "1023")]
+    extension_options: Vec<prost_types::Any>,
+    #[prost(message, repeated, tag = "2047")]
+    non_critical_extension_options: Vec<prost_types::Any>,
+}
+
+/// Checks the unordered tx related fields of MessageArgs for consistency
+fn validate_timeouts(args: &MessageArgs) -> Result<(), PrivateKeyError> {
+    if args.relative_timeout.is_some() {
+        return Err(PrivateKeyError::InvalidMessageArgs(
+            "relative_timeout must be resolved before signing".to_string(),
+        ));
+    }
+    if let Some(timeout) = args.timeout_timestamp {
+        if timeout <= SystemTime::now() {
+            return Err(PrivateKeyError::InvalidMessageArgs(
+                "timeout_timestamp is in the past".to_string(),
+            ));
+        }
+    } else if args.unordered {
+        return Err(PrivateKeyError::InvalidMessageArgs(
+            "unordered transactions require a timeout_timestamp".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// One of the accounts signing a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxSigner {
+    pub public_key: PublicKey,
+    pub account_number: u64,
+    pub sequence: u64,
+}
+
+/// Encodes the TxBody and the AuthInfo, with one SignerInfo per signer in order
+fn encode_parts(
+    messages: &[Msg],
+    args: &MessageArgs,
+    memo: String,
+    signers: &[TxSigner],
+    sign_mode: SignMode,
+) -> Result<(TxBody, Vec<u8>, AuthInfo, Vec<u8>), PrivateKeyError> {
+    validate_timeouts(args)?;
+    args.fee.validate()?;
+
+    // Create TxBody
+    let body = TxBody {
+        messages: messages.iter().map(|msg| msg.0.clone()).collect(),
+        memo,
+        timeout_height: args.timeout_height,
+        extension_options: args.extension_options.clone(),
+        non_critical_extension_options: args.non_critical_extension_options.clone(),
+    };
+
+    // A protobuf serialization of a TxBody
+    let full_body = TxBodyV053 {
+        messages: body.messages.clone(),
+        memo: body.memo.clone(),
+        timeout_height: body.timeout_height,
+        unordered: args.unordered,
+        timeout_timestamp: args.timeout_timestamp.map(|t| t.into()),
+        extension_options: body.extension_options.clone(),
+        non_critical_extension_options: body.non_critical_extension_options.clone(),
+    };
+    let mut body_buf = Vec::new();
+    full_body.encode(&mut body_buf)?;
+    #[cfg(feature = "encoding-audit")]
+    audit_encoding::<TxBodyV053>(&body_buf, "TxBody");
+
+    let signer_infos = signers
+        .iter()
+        .map(|signer| {
+            let key = ProtoSecp256k1Pubkey {
+                key: signer.public_key.to_vec(),
+            };
+            let single = mode_info::Single {
+                mode: sign_mode.as_i32(),
+            };
+            SignerInfo {
+                public_key: Some(encode_any(key, SECP256K1_PUBKEY_TYPE_URL)),
+                mode_info: Some(ModeInfo {
+                    sum: Some(mode_info::Sum::Single(single)),
+                }),
+                sequence: signer.sequence,
+            }
+        })
+        .collect();
+
+    let auth_info = AuthInfo {
+        signer_infos,
+        fee: Some(args.fee.clone().into()),
+    };
+
+    // Protobuf serialization of `AuthInfo`
+    let mut auth_buf = Vec::new();
+    auth_info.encode(&mut auth_buf)?;
+    #[cfg(feature = "encoding-audit")]
+    audit_encoding::<AuthInfo>(&auth_buf, "AuthInfo");
+
+    Ok((body, body_buf, auth_info, auth_buf))
+}
+
+/// Decodes and re-encodes signed bytes, panicking if the result differs. The chain
+/// verifies signatures over the bytes it was sent, but some chains re-encode parts of
+/// the tx first, so any encoding that does not survive a round trip (fields out of
+/// order, defaults written out, unknown fields) fails verification there
+#[cfg(feature = "encoding-audit")]
+fn audit_encoding<M: Message + Default>(bytes: &[u8], name: &str) {
+    let decoded = match M::decode(bytes) {
+        Ok(decoded) => decoded,
+        Err(e) => panic!("Encoding audit: {} does not decode {}", name, e),
+    };
+    let reencoded = decoded.encode_to_vec();
+    if reencoded != bytes {
+        let offset = bytes
+            .iter()
+            .zip(reencoded.iter())
+            .position(|(a, b)| a != b)
+            .unwrap_or(bytes.len().min(reencoded.len()));
+        panic!(
+            "Encoding audit: {} is not deterministic, first difference at byte {}\n  encoded    {}\n  re-encoded {}",
+            name,
+            offset,
+            bytes_to_hex_str(bytes),
+            bytes_to_hex_str(&reencoded)
+        );
+    }
+}
+
+/// Internal struct containing the parts of a built transaction in a way
+/// that's easy to mix and match for various uses and output types.
+pub(crate) struct TxParts {
+    pub body: TxBody,
+    pub body_buf: Vec<u8>,
+    pub auth_info: AuthInfo,
+    pub auth_buf: Vec<u8>,
+    pub signatures: Vec<Vec<u8>>,
+}
+
+impl TxParts {
+    /// Converts these parts into the standard Tx type
+    pub fn into_tx(self) -> Tx {
+        Tx {
+            body: Some(self.body),
+            auth_info: Some(self.auth_info),
+            signatures: self.signatures,
+        }
+    }
+
+    /// Converts these parts into the proto encoded TxRaw bytes, ready for broadcast
+    pub fn into_tx_raw_bytes(self) -> Vec<u8> {
+        let tx_raw = TxRaw {
+            body_bytes: self.body_buf,
+            auth_info_bytes: self.auth_buf,
+            signatures: self.signatures,
+        };
+
+        let mut txraw_buf = Vec::new();
+        tx_raw.encode(&mut txraw_buf).unwrap();
+        txraw_buf
+    }
+}
+
+/// A fully assembled transaction which is awaiting a signature. The SignDoc
+/// bytes or their digest can be handed to any external signer, once a signature
+/// is available it can be attached to produce a Tx or broadcastable TxRaw bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnsignedTx {
+    body: TxBody,
+    body_buf: Vec<u8>,
+    auth_info: AuthInfo,
+    auth_buf: Vec<u8>,
+    sign_doc_buf: Vec<u8>,
+}
+
+impl UnsignedTx {
+    /// Assembles a transaction with a single signer identified by the provided public key
+    pub fn new(
+        messages: &[Msg],
+        args: MessageArgs,
+        memo: impl Into<String>,
+        public_key: PublicKey,
+    ) -> Result<UnsignedTx, PrivateKeyError> {
+        UnsignedTx::assemble(messages, args, memo, public_key, SignMode::Direct)
+    }
+
+    fn assemble(
+        messages: &[Msg],
+        args: MessageArgs,
+        memo: impl Into<String>,
+        public_key: PublicKey,
+        sign_mode: SignMode,
+    ) -> Result<UnsignedTx, PrivateKeyError> {
+        let memo = memo.into();
+        // external signers get the same checks as local keys before anything is signed
+        validate_tx(messages, &args, &memo)?;
+        let signer = TxSigner {
+            public_key,
+            account_number: args.account_number,
+            sequence: args.sequence,
+        };
+        let (body, body_buf, auth_info, auth_buf) =
+            encode_parts(messages, &args, memo, &[signer], sign_mode)?;
+
+        let sign_doc = SignDoc {
+            body_bytes: body_buf.clone(),
+            auth_info_bytes: auth_buf.clone(),
+            chain_id: args.chain_id,
+            account_number: args.account_number,
+        };
+
+        // Protobuf serialization of `SignDoc`
+        let mut sign_doc_buf = Vec::new();
+        sign_doc.encode(&mut sign_doc_buf)?;
+
+        Ok(UnsignedTx {
+            body,
+            body_buf,
+            auth_info,
+            auth_buf,
+            sign_doc_buf,
+        })
+    }
+
+    /// The proto encoded SignDoc, this is the payload that must be signed
+    pub fn sign_doc_bytes(&self) -> &[u8] {
+        &self.sign_doc_buf
+    }
+
+    /// The SignDoc of this transaction
+    pub fn sign_doc(&self) -> SignDoc {
+        SignDoc::decode(self.sign_doc_buf.as_slice()).unwrap()
+    }
+
+    /// The sha256 digest of the SignDoc, secp256k1 signers sign this value
+    pub fn digest(&self) -> [u8; 32] {
+        Sha256::digest(&self.sign_doc_buf).into()
+    }
+
+    /// Attaches an externally produced compact signature, producing the standard Tx type
+    /// note that the Tx type can not represent the `unordered` and `timeout_timestamp`
+    /// fields, use `into_tx_raw_bytes` for unordered transactions
+    pub fn into_signed_tx(self, signature: [u8; 64]) -> Tx {
+        self.into_parts(signature).into_tx()
+    }
+
+    /// Attaches an externally produced compact signature, producing proto encoded TxRaw
+    /// bytes ready to be broadcast
+    pub fn into_tx_raw_bytes(self, signature: [u8; 64]) -> Vec<u8> {
+        self.into_parts(signature).into_tx_raw_bytes()
+    }
+
+    /// Hands the digest of this transaction to the provided signer and waits for the signature
+    /// returns proto encoded TxRaw bytes ready to be broadcast
+    pub async fn sign_with<S: AsyncSigner>(self, signer: &S) -> Result<Vec<u8>, PrivateKeyError> {
+        let signature = signer.sign_digest(self.digest()).await?;
+        Ok(self.into_tx_raw_bytes(signature))
+    }
+
+    pub(crate) fn into_parts(self, signature: [u8; 64]) -> TxParts {
+        TxParts {
+            body: self.body,
+            body_buf: self.body_buf,
+            auth_info: self.auth_info,
+            auth_buf: self.auth_buf,
+            signatures: vec![signature.to_vec()],
+        }
+    }
+}
+
+/// Assembles a transaction step by step, as an alternative to passing messages,
+/// MessageArgs and a memo positionally to `PrivateKey::sign_std_msg` or `UnsignedTx::new`
+///
+/// ```
+/// use deep_space::{Fee, PrivateKey, TxBuilder};
+/// let key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+/// let fee = Fee {
+///     gas_limit: 100_000,
+///     ..Default::default()
+/// };
+/// let tx = TxBuilder::new("cosmoshub-4")
+///     .msg(deep_space::Msg::new("/cosmos.bank.v1beta1.MsgSend", ()))
+///     .memo("hello")
+///     .fee(fee)
+///     .timeout_height(1000)
+///     .sign_to_bytes(&key)
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TxBuilder {
+    messages: Vec<Msg>,
+    memo: String,
+    args: MessageArgs,
+    sign_mode: SignMode,
+    signer: Option<PublicKey>,
+    co_signers: Vec<TxSigner>,
+}
+
+impl TxBuilder {
+    pub fn new(chain_id: impl Into<String>) -> Self {
+        TxBuilder {
+            args: MessageArgs {
+                chain_id: chain_id.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Starts from MessageArgs, for example those returned by `Contact::get_message_args`
+    pub fn from_args(args: MessageArgs) -> Self {
+        TxBuilder {
+            args,
+            ..Default::default()
+        }
+    }
+
+    pub fn msg(mut self, msg: Msg) -> Self {
+        self.messages.push(msg);
+        self
+    }
+
+    pub fn msgs(mut self, msgs: impl IntoIterator<Item = Msg>) -> Self {
+        self.messages.extend(msgs);
+        self
+    }
+
+    pub fn memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = memo.into();
+        self
+    }
+
+    /// Replaces the fee, including any granter set by an earlier `fee_granter` call
+    pub fn fee(mut self, fee: Fee) -> Self {
+        self.args.fee = fee;
+        self
+    }
+
+    /// Has `granter` pay the fee through a feegrant allowance
+    pub fn fee_granter(mut self, granter: Address) -> Self {
+        self.args.fee.granter = Some(granter.to_string());
+        self
+    }
+
+    pub fn timeout_height(mut self, timeout_height: u64) -> Self {
+        self.args.timeout_height = timeout_height;
+        self
+    }
+
+    /// Times the tx out this many blocks after the latest height, see
+    /// `Contact::resolve_tx_builder`
+    pub fn relative_timeout(mut self, blocks: u64) -> Self {
+        self.args.relative_timeout = Some(blocks);
+        self
+    }
+
+    pub fn get_relative_timeout(&self) -> Option<u64> {
+        self.args.relative_timeout
+    }
+
+    /// Sets the timeout height from the relative timeout given the latest height
+    pub fn resolve_relative_timeout(mut self, current_height: u64) -> Self {
+        self.args.resolve_relative_timeout(current_height);
+        self
+    }
+
+    pub fn sign_mode(mut self, sign_mode: SignMode) -> Self {
+        self.sign_mode = sign_mode;
+        self
+    }
+
+    /// Sets the signing account, required unless the tx is signed with `sign` or
+    /// `sign_to_bytes` which default to the signing key and the account number and
+    /// sequence already set
+    pub fn signer(mut self, public_key: PublicKey, account_number: u64, sequence: u64) -> Self {
+        self.signer = Some(public_key);
+        self.args.account_number = account_number;
+        self.args.sequence = sequence;
+        self
+    }
+
+    /// Adds another signer after the one set with `signer`, signatures are expected in
+    /// the order signers were added. Transactions with several signers are assembled
+    /// with `build_multi_signer`
+    pub fn co_signer(mut self, public_key: PublicKey, account_number: u64, sequence: u64) -> Self {
+        self.co_signers.push(TxSigner {
+            public_key,
+            account_number,
+            sequence,
+        });
+        self
+    }
+
+    /// Assembles the transaction for an external signer
+    pub fn build_unsigned(self) -> Result<UnsignedTx, PrivateKeyError> {
+        if !self.co_signers.is_empty() {
+            return Err(PrivateKeyError::InvalidMessageArgs(
+                "transaction has several signers, use build_multi_signer".to_string(),
+            ));
+        }
+        let public_key = self
+            .signer
+            .ok_or_else(|| PrivateKeyError::InvalidMessageArgs("no signer set".to_string()))?;
+        UnsignedTx::assemble(
+            &self.messages,
+            self.args,
+            self.memo,
+            public_key,
+            self.sign_mode,
+        )
+    }
+
+    /// The SignDoc a SIGN_MODE_DIRECT signer signs
+    pub fn sign_doc(self) -> Result<SignDoc, PrivateKeyError> {
+        Ok(self.build_unsigned()?.sign_doc())
+    }
+
+    /// Signs with a local key, producing the standard Tx type
+    pub fn sign(self, private_key: &PrivateKey) -> Result<Tx, PrivateKeyError> {
+        Ok(self.sign_parts(private_key)?.into_tx())
+    }
+
+    /// Signs with a local key, producing proto encoded TxRaw bytes ready to be broadcast
+    pub fn sign_to_bytes(self, private_key: &PrivateKey) -> Result<Vec<u8>, PrivateKeyError> {
+        Ok(self.sign_parts(private_key)?.into_tx_raw_bytes())
+    }
+
+    fn sign_parts(mut self, private_key: &PrivateKey) -> Result<TxParts, PrivateKeyError> {
+        if self.sign_mode != SignMode::Direct {
+            return Err(PrivateKeyError::InvalidMessageArgs(
+                "only SIGN_MODE_DIRECT transactions can be signed locally".to_string(),
+            ));
+        }
+        let our_pubkey = private_key.to_public_key(PublicKey::DEFAULT_PREFIX)?;
+        match self.signer {
+            Some(signer) if signer.to_vec() != our_pubkey.to_vec() => {
+                return Err(PrivateKeyError::InvalidMessageArgs(
+                    "private key does not match the signer".to_string(),
+                ))
+            }
+            _ => self.signer = Some(our_pubkey),
+        }
+        let unsigned = self.build_unsigned()?;
+        let signature = private_key.sign_compact(unsigned.digest())?;
+        Ok(unsigned.into_parts(signature))
+    }
+
+    /// Assembles a transaction signed by the `signer` followed by every `co_signer`, each
+    /// party signs its own copy and the signatures are merged before broadcast
+    pub fn build_multi_signer(self) -> Result<MultiSignerTx, PrivateKeyError> {
+        validate_tx(&self.messages, &self.args, &self.memo)?;
+        let public_key = self
+            .signer
+            .ok_or_else(|| PrivateKeyError::InvalidMessageArgs("no signer set".to_string()))?;
+        let mut signers = vec![TxSigner {
+            public_key,
+            account_number: self.args.account_number,
+            sequence: self.args.sequence,
+        }];
+        signers.extend(self.co_signers);
+        let (body, body_buf, auth_info, auth_buf) = encode_parts(
+            &self.messages,
+            &self.args,
+            self.memo,
+            &signers,
+            self.sign_mode,
+        )?;
+        Ok(MultiSignerTx {
+            body,
+            body_buf,
+            auth_info,
+            auth_buf,
+            chain_id: self.args.chain_id,
+            sign_mode: self.sign_mode,
+            signatures: vec![None; signers.len()],
+            signers,
+        })
+    }
+}
+
+/// A transaction with several signers, such as an escrow release or a payment from
+/// shared accounts. Every signer signs a SignDoc over the same body and AuthInfo that
+/// differs only in its account number, the signatures must be in the order of the
+/// AuthInfo signer infos. Each party can sign its own clone, the copies are then
+/// combined with `merge`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiSignerTx {
+    body: TxBody,
+    body_buf: Vec<u8>,
+    auth_info: AuthInfo,
+    auth_buf: Vec<u8>,
+    chain_id: String,
+    sign_mode: SignMode,
+    signers: Vec<TxSigner>,
+    signatures: Vec<Option<Vec<u8>>>,
+}
+
+impl MultiSignerTx {
+    /// The signers in signature order
+    pub fn signers(&self) -> &[TxSigner] {
+        &self.signers
+    }
+
+    /// The position of `public_key` among the signers
+    pub fn signer_index(&self, public_key: &PublicKey) -> Option<usize> {
+        let key = public_key.to_vec();
+        self.signers
+            .iter()
+            .position(|s| s.public_key.to_vec() == key)
+    }
+
+    /// The SignDoc the signer at `index` signs
+    pub fn sign_doc(&self, index: usize) -> Result<SignDoc, PrivateKeyError> {
+        let signer = self.signers.get(index).ok_or_else(|| {
+            PrivateKeyError::InvalidMessageArgs(format!("no signer at index {}", index))
+        })?;
+        Ok(SignDoc {
+            body_bytes: self.body_buf.clone(),
+            auth_info_bytes: self.auth_buf.clone(),
+            chain_id: self.chain_id.clone(),
+            account_number: signer.account_number,
+        })
+    }
+
+    /// The sha256 digest of the SignDoc of the signer at `index`
+    pub fn digest(&self, index: usize) -> Result<[u8; 32], PrivateKeyError> {
+        let mut buf = Vec::new();
+        self.sign_doc(index)?.encode(&mut buf)?;
+        Ok(Sha256::digest(&buf).into())
+    }
+
+    /// Attaches an externally produced compact signature for the signer at `index`
+    pub fn add_signature(
+        &mut self,
+        index: usize,
+        signature: [u8; 64],
+    ) -> Result<(), PrivateKeyError> {
+        match self.signatures.get_mut(index) {
+            Some(slot) => {
+                *slot = Some(signature.to_vec());
+                Ok(())
+            }
+            None => Err(PrivateKeyError::InvalidMessageArgs(format!(
+                "no signer at index {}",
+                index
+            ))),
+        }
+    }
+
+    /// Signs with a local key, which must belong to one of the signers
+    pub fn sign(&mut self, private_key: &PrivateKey) -> Result<(), PrivateKeyError> {
+        if self.sign_mode != SignMode::Direct {
+            return Err(PrivateKeyError::InvalidMessageArgs(
+                "only SIGN_MODE_DIRECT transactions can be signed locally".to_string(),
+            ));
+        }
+        let our_pubkey = private_key.to_public_key(PublicKey::DEFAULT_PREFIX)?;
+        let index = self.signer_index(&our_pubkey).ok_or_else(|| {
+            PrivateKeyError::InvalidMessageArgs("private key does not match any signer".to_string())
+        })?;
+        let signature = private_key.sign_compact(self.digest(index)?)?;
+        self.add_signature(index, signature)
+    }
+
+    /// Copies the signatures of another party's copy of this transaction into this one,
+    /// both must have been built from the same messages, args and signers
+    pub fn merge(&mut self, other: &MultiSignerTx) -> Result<(), PrivateKeyError> {
+        if self.body_buf != other.body_buf
+            || self.auth_buf != other.auth_buf
+            || self.chain_id != other.chain_id
+            || self.signers != other.signers
+        {
+            return Err(PrivateKeyError::InvalidMessageArgs(
+                "can not merge signatures of different transactions".to_string(),
+            ));
+        }
+        for (ours, theirs) in self.signatures.iter_mut().zip(other.signatures.iter()) {
+            if ours.is_none() {
+                ours.clone_from(theirs);
+            }
+        }
+        Ok(())
+    }
+
+    /// True once every signer has signed
+    pub fn is_complete(&self) -> bool {
+        self.signatures.iter().all(Option::is_some)
+    }
+
+    /// Produces the standard Tx type, fails if a signature is missing
+    pub fn into_tx(self) -> Result<Tx, PrivateKeyError> {
+        Ok(self.into_parts()?.into_tx())
+    }
+
+    /// Produces proto encoded TxRaw bytes ready to be broadcast, fails if a signature is
+    /// missing
+    pub fn into_tx_raw_bytes(self) -> Result<Vec<u8>, PrivateKeyError> {
+        Ok(self.into_parts()?.into_tx_raw_bytes())
+    }
+
+    fn into_parts(self) -> Result<TxParts, PrivateKeyError> {
+        let signatures = self
+            .signatures
+            .into_iter()
+            .enumerate()
+            .map(|(index, signature)| {
+                signature.ok_or_else(|| {
+                    PrivateKeyError::InvalidMessageArgs(format!(
+                        "missing signature of signer {}",
+                        index
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(TxParts {
+            body: self.body,
+            body_buf: self.body_buf,
+            auth_info: self.auth_info,
+            auth_buf: self.auth_buf,
+            signatures,
+        })
+    }
+}
+
+impl From<MultiSignerTx> for PartiallySignedTx {
+    fn from(tx: MultiSignerTx) -> Self {
+        PartiallySignedTx {
+            body_bytes: tx.body_buf,
+            auth_info_bytes: tx.auth_buf,
+            chain_id: tx.chain_id,
+            signers: tx
+                .signers
+                .iter()
+                .zip(tx.signatures)
+                .map(|(signer, signature)| PartialSignature {
+                    account_number: signer.account_number,
+                    signature: signature.unwrap_or_default(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<PartiallySignedTx> for MultiSignerTx {
+    type Error = PrivateKeyError;
+
+    fn try_from(tx: PartiallySignedTx) -> Result<Self, Self::Error> {
+        let invalid = |e: DecodeError| PrivateKeyError::InvalidMessageArgs(e.to_string());
+        let body = TxBody::decode(tx.body_bytes.as_slice()).map_err(invalid)?;
+        let auth_info = AuthInfo::decode(tx.auth_info_bytes.as_slice()).map_err(invalid)?;
+        if auth_info.signer_infos.len() != tx.signers.len() {
+            return Err(PrivateKeyError::InvalidMessageArgs(format!(
+                "{} signer infos but {} signers",
+                auth_info.signer_infos.len(),
+                tx.signers.len()
+            )));
+        }
+        let mut sign_mode = None;
+        let mut signers = Vec::new();
+        for (info, signer) in auth_info.signer_infos.iter().zip(tx.signers.iter()) {
+            let mode = match info.mode_info.as_ref().and_then(|m| m.sum.as_ref()) {
+                Some(mode_info::Sum::Single(single)) if single.mode == 1 => SignMode::Direct,
+                Some(mode_info::Sum::Single(single)) if single.mode == 127 => {
+                    SignMode::LegacyAminoJson
+                }
+                _ => {
+                    return Err(PrivateKeyError::InvalidMessageArgs(
+                        "unsupported signer mode info".to_string(),
+                    ))
+                }
+            };
+            if *sign_mode.get_or_insert(mode) != mode {
+                return Err(PrivateKeyError::InvalidMessageArgs(
+                    "signers use different sign modes".to_string(),
+                ));
+            }
+            let key = match &info.public_key {
+                Some(any) if any.type_url == SECP256K1_PUBKEY_TYPE_URL => {
+                    ProtoSecp256k1Pubkey::decode(any.value.as_slice()).map_err(invalid)?
+                }
+                _ => {
+                    return Err(PrivateKeyError::InvalidMessageArgs(
+                        "signer public key is not secp256k1".to_string(),
+                    ))
+                }
+            };
+            signers.push(TxSigner {
+                public_key: PublicKey::from_slice(&key.key, PublicKey::DEFAULT_PREFIX)?,
+                account_number: signer.account_number,
+                sequence: info.sequence,
+            });
+        }
+        Ok(MultiSignerTx {
+            body,
+            body_buf: tx.body_bytes,
+            auth_info,
+            auth_buf: tx.auth_info_bytes,
+            chain_id: tx.chain_id,
+            sign_mode: sign_mode.unwrap_or_default(),
+            signers,
+            signatures: tx
+                .signers
+                .into_iter()
+                .map(|s| (!s.signature.is_empty()).then_some(s.signature))
+                .collect(),
+        })
+    }
+}
+
+/// A MultiSignerTx in a form that can be written to a file and passed between its
+/// signers, like a Bitcoin PSBT. The body and AuthInfo are kept as the exact bytes
+/// being signed so every copy signs the same transaction, the signer public keys and
+/// sequences are read from the AuthInfo. The binary form from `to_bytes` is the proto
+/// encoding of this message and Serialize produces its proto3 JSON, both are stable
+/// across versions
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PartiallySignedTx {
+    #[prost(bytes = "vec", tag = "1")]
+    pub body_bytes: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub auth_info_bytes: Vec<u8>,
+    #[prost(string, tag = "3")]
+    pub chain_id: String,
+    /// One entry per SignerInfo of the AuthInfo, in the same order
+    #[prost(message, repeated, tag = "4")]
+    pub signers: Vec<PartialSignature>,
+}
+
+/// The account number of one signer of a PartiallySignedTx and their signature
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PartialSignature {
+    #[prost(uint64, tag = "1")]
+    pub account_number: u64,
+    /// The compact signature, empty until this signer has signed
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature: Vec<u8>,
+}
+
+impl PartiallySignedTx {
+    /// The proto encoding of this transaction
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+
+    /// Parses the output of `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<PartiallySignedTx, DecodeError> {
+        PartiallySignedTx::decode(bytes)
+    }
+
+    /// The signers in signature order, with the public keys and sequences of the
+    /// AuthInfo
+    pub fn signers(&self) -> Result<Vec<TxSigner>, PrivateKeyError> {
+        Ok(MultiSignerTx::try_from(self.clone())?.signers)
+    }
+
+    /// Signs with a local key, which must belong to one of the signers
+    pub fn sign(&mut self, private_key: &PrivateKey) -> Result<(), PrivateKeyError> {
+        let mut tx = MultiSignerTx::try_from(self.clone())?;
+        tx.sign(private_key)?;
+        *self = tx.into();
+        Ok(())
+    }
+
+    /// Copies the signatures of another signer's copy into this one, see
+    /// `MultiSignerTx::merge`
+    pub fn merge(&mut self, other: &PartiallySignedTx) -> Result<(), PrivateKeyError> {
+        let mut tx = MultiSignerTx::try_from(self.clone())?;
+        tx.merge(&MultiSignerTx::try_from(other.clone())?)?;
+        *self = tx.into();
+        Ok(())
+    }
+
+    /// True once every signer has signed
+    pub fn is_complete(&self) -> bool {
+        self.signers.iter().all(|s| !s.signature.is_empty())
+    }
+
+    /// Checks every signature and produces proto encoded TxRaw bytes ready to be
+    /// broadcast. Fails if a signature is missing, or for SIGN_MODE_DIRECT if a
+    /// signature was not made by its signer over this transaction
+    pub fn finalize(self) -> Result<Vec<u8>, PrivateKeyError> {
+        let tx = MultiSignerTx::try_from(self)?;
+        if tx.sign_mode == SignMode::Direct {
+            for (index, signature) in tx.signatures.iter().enumerate() {
+                let signature = match signature {
+                    Some(signature) => Signature::from_compact(signature)?,
+                    None => continue,
+                };
+                if !signature.verify(tx.digest(index)?, &tx.signers[index].public_key) {
+                    return Err(PrivateKeyError::InvalidMessageArgs(format!(
+                        "invalid signature of signer {}",
+                        index
+                    )));
+                }
+            }
+        }
+        tx.into_tx_raw_bytes()
+    }
+}
+
+/// Serializes to proto3 JSON in human readable formats and to the proto encoding in
+/// binary formats
+impl Serialize for PartiallySignedTx {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            self.to_json().serialize(serializer)
+        } else {
+            serializer.serialize_bytes(&self.to_bytes())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PartiallySignedTx {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let value = Value::deserialize(deserializer)?;
+            PartiallySignedTx::from_json(&value).map_err(D::Error::custom)
+        } else {
+            let bytes = Vec::<u8>::deserialize(deserializer)?;
+            PartiallySignedTx::from_bytes(&bytes).map_err(D::Error::custom)
+        }
+    }
+}
+
+/// Checks TxRaw bytes submitted by a third party before relaying them, for services
+/// that broadcast user signed transactions. Beyond verifying every signature over its
+/// SignDoc this rejects the forms the chain refuses or that a relayer could alter
+/// without invalidating the signatures: a non canonical TxRaw encoding, high-s
+/// signatures, signatures with a recovery id and signer infos repeating a public key.
+/// `account_numbers` are the on chain account numbers of the signers in AuthInfo order,
+/// only secp256k1 signers using SIGN_MODE_DIRECT are supported. Returns the signers
+pub fn verify_tx_raw(
+    bytes: &[u8],
+    chain_id: &str,
+    account_numbers: &[u64],
+) -> Result<Vec<TxSigner>, TxVerificationError> {
+    let raw = TxRaw::decode(bytes)?;
+    if raw.encode_to_vec() != bytes {
+        return Err(TxVerificationError::NonCanonicalEncoding);
+    }
+    let auth_info = AuthInfo::decode(raw.auth_info_bytes.as_slice())?;
+    let signers = auth_info.signer_infos.len();
+    if raw.signatures.len() != signers {
+        return Err(TxVerificationError::SignatureCount {
+            signers,
+            signatures: raw.signatures.len(),
+        });
+    }
+    if account_numbers.len() != signers {
+        return Err(TxVerificationError::AccountNumberCount {
+            signers,
+            account_numbers: account_numbers.len(),
+        });
+    }
+
+    let mut verified: Vec<TxSigner> = Vec::new();
+    for (index, info) in auth_info.signer_infos.iter().enumerate() {
+        match info.mode_info.as_ref().and_then(|m| m.sum.as_ref()) {
+            Some(mode_info::Sum::Single(single)) if single.mode == SignMode::Direct.as_i32() => {}
+            _ => return Err(TxVerificationError::UnsupportedSignMode(index)),
+        }
+        let public_key = match &info.public_key {
+            Some(any) if any.type_url == SECP256K1_PUBKEY_TYPE_URL => {
+                ProtoSecp256k1Pubkey::decode(any.value.as_slice())
+                    .ok()
+                    .and_then(|key| PublicKey::from_slice(&key.key, PublicKey::DEFAULT_PREFIX).ok())
+            }
+            _ => None,
+        }
+        .ok_or(TxVerificationError::UnsupportedPublicKey(index))?;
+        if let Some(first) = verified
+            .iter()
+            .position(|s| s.public_key.to_vec() == public_key.to_vec())
+        {
+            return Err(TxVerificationError::DuplicateSigner {
+                first,
+                second: index,
+            });
+        }
+
+        let signature = &raw.signatures[index];
+        match signature.len() {
+            64 => {}
+            65 => return Err(TxVerificationError::RecoveryIdPresent(index)),
+            length => return Err(TxVerificationError::InvalidSignatureLength { index, length }),
+        }
+        // from_compact normalizes to low-s, so the raw bytes are checked first
+        if !Signature::is_low_s(signature)
+            .map_err(|_| TxVerificationError::InvalidSignature(index))?
+        {
+            return Err(TxVerificationError::HighS(index));
+        }
+        let sign_doc = SignDoc {
+            body_bytes: raw.body_bytes.clone(),
+            auth_info_bytes: raw.auth_info_bytes.clone(),
+            chain_id: chain_id.to_string(),
+            account_number: account_numbers[index],
+        };
+        let digest: [u8; 32] = Sha256::digest(sign_doc.encode_to_vec()).into();
+        let valid = Signature::from_compact(signature)
+            .map(|s| s.verify(digest, &public_key))
+            .unwrap_or(false);
+        if !valid {
+            return Err(TxVerificationError::InvalidSignature(index));
+        }
+        verified.push(TxSigner {
+            public_key,
+            account_number: account_numbers[index],
+            sequence: info.sequence,
+        });
+    }
+    Ok(verified)
+}
+
+/// The denom length assumed by `estimate_tx_size`, long enough for ibc/{hash} denoms
+const ESTIMATE_DENOM_LEN: usize = 68;
+
+/// Estimates the size in bytes of the TxRaw that would carry `messages` and `memo`
+/// signed by `num_signers` secp256k1 keys, for comparison against the `max_tx_bytes`
+/// mempool limit of a node. The estimate assumes a single coin fee with an ibc
+/// denom and the largest possible gas limit, sequences and timeout height, so the
+/// actual transaction will be at most this size. Extension options and unordered tx fields are not included.
+pub fn estimate_tx_size(messages: &[Msg], memo: &str, num_signers: usize) -> usize {
+    let body = TxBody {
+        messages: messages.iter().map(|msg| msg.0.clone()).collect(),
+        memo: memo.to_string(),
+        timeout_height: u64::MAX,
+        extension_options: Vec::new(),
+        non_critical_extension_options: Vec::new(),
+    };
+    let key = ProtoSecp256k1Pubkey { key: vec![0; 33] };
+    let signer_info = SignerInfo {
+        public_key: Some(encode_any(key, SECP256K1_PUBKEY_TYPE_URL)),
+        mode_info: Some(ModeInfo {
+            sum: Some(mode_info::Sum::Single(mode_info::Single { mode: 1 })),
+        }),
+        sequence: u64::MAX,
+    };
+    let fee = ProtoFee {
+        amount: vec![cosmos_sdk_proto::cosmos::base::v1beta1::Coin {
+            denom: "a".repeat(ESTIMATE_DENOM_LEN),
+            amount: Uint256::max_value().to_string(),
+        }],
+        gas_limit: u64::MAX,
+        payer: String::new(),
+        granter: String::new(),
+    };
+    let auth_info = AuthInfo {
+        signer_infos: vec![signer_info; num_signers],
+        fee: Some(fee),
+    };
+    let tx_raw = TxRaw {
+        body_bytes: body.encode_to_vec(),
+        auth_info_bytes: auth_info.encode_to_vec(),
+        signatures: vec![vec![0; 64]; num_signers],
+    };
+    tx_raw.encoded_len()
+}
+
+/// A transaction decoded from the raw bytes found in blocks and the mempool
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedTx {
+    /// The uppercase hex sha256 of the raw bytes, the hash the chain uses to identify the tx
+    pub hash: String,
+    pub messages: Vec<Any>,
+    pub memo: String,
+    pub timeout_height: u64,
+    /// None if the tx did not specify a fee
+    pub fee: Option<Fee>,
+    /// The account sequence of each signer, in signer order
+    pub sequences: Vec<u64>,
+}
+
+impl DecodedTx {
+    /// Decodes proto encoded TxRaw bytes
+    pub fn decode(bytes: &[u8]) -> Result<DecodedTx, DecodeError> {
+        let raw = TxRaw::decode(bytes)?;
+        let body = TxBody::decode(raw.body_bytes.as_slice())?;
+        let auth_info = AuthInfo::decode(raw.auth_info_bytes.as_slice())?;
+        Ok(DecodedTx {
+            hash: bytes_to_hex_str(&Sha256::digest(bytes)).to_uppercase(),
+            messages: body.messages,
+            memo: body.memo,
+            timeout_height: body.timeout_height,
+            fee: auth_info.fee.map(|f| f.into()),
+            sequences: auth_info.signer_infos.iter().map(|s| s.sequence).collect(),
+        })
+    }
+
+    /// The type urls of the messages in this tx
+    pub fn message_types(&self) -> Vec<&str> {
+        self.messages.iter().map(|m| m.type_url.as_str()).collect()
+    }
+
+    /// The messages of this tx decoded with `registry`, messages of unregistered types
+    /// are left encoded
+    pub fn typed_messages(&self, registry: &AnyRegistry) -> Result<Vec<DecodedAny>, DecodeError> {
+        self.messages
+            .iter()
+            .map(|msg| registry.decode(msg.clone()))
+            .collect()
+    }
+
+    /// The price per unit of gas this tx pays in `denom`, None if the tx has no
+    /// fee or gas limit, or does not pay in `denom`
+    pub fn gas_price(&self, denom: &str) -> Option<f64> {
+        let fee = self.fee.as_ref()?;
+        if fee.gas_limit == 0 {
+            return None;
+        }
+        let coin = fee.amount.iter().find(|c| c.denom == denom)?;
+        let amount: f64 = coin.amount.to_string().parse().ok()?;
+        Some(amount / fee.gas_limit as f64)
+    }
+}
+
+/// The fields of a DecodedTx in binary formats, which can't hold the JSON values used
+/// by the human readable representation
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+struct DecodedTxBinary {
+    hash: String,
+    messages: Vec<Msg>,
+    memo: String,
+    timeout_height: u64,
+    fee: Option<Fee>,
+    sequences: Vec<u64>,
+}
+
+impl From<&DecodedTx> for DecodedTxBinary {
+    fn from(tx: &DecodedTx) -> Self {
+        DecodedTxBinary {
+            hash: tx.hash.clone(),
+            messages: tx.messages.iter().cloned().map(Msg::from).collect(),
+            memo: tx.memo.clone(),
+            timeout_height: tx.timeout_height,
+            fee: tx.fee.clone(),
+            sequences: tx.sequences.clone(),
+        }
+    }
+}
+
+impl From<DecodedTxBinary> for DecodedTx {
+    fn from(tx: DecodedTxBinary) -> Self {
+        DecodedTx {
+            hash: tx.hash,
+            messages: tx.messages.into_iter().map(Any::from).collect(),
+            memo: tx.memo,
+            timeout_height: tx.timeout_height,
+            fee: tx.fee,
+            sequences: tx.sequences,
+        }
+    }
+}
+
+/// Serializes to proto3 JSON style, camelCase fields with messages in the same
+/// representation as `Msg` and 64 bit integers as strings. Binary formats hold the
+/// fields in order with messages in the binary form of `Msg`
+impl Serialize for DecodedTx {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return DecodedTxBinary::from(self).serialize(serializer);
+        }
+        let fee: Option<ProtoFee> = self.fee.clone().map(|f| f.into());
+        json!({
+            "hash": self.hash,
+            "messages": self.messages.to_json(),
+            "memo": self.memo,
+            "timeoutHeight": self.timeout_height.to_json(),
+            "fee": fee.to_json(),
+            "sequences": self.sequences.to_json(),
+        })
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DecodedTx {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return DecodedTxBinary::deserialize(deserializer).map(DecodedTx::from);
+        }
+        let value = Value::deserialize(deserializer)?;
+        let parse = || -> Result<DecodedTx, String> {
+            let object = as_object(&value)?;
+            let fee: Option<ProtoFee> = ProtoJson::from_json(field(object, "fee", "fee"))?;
+            Ok(DecodedTx {
+                hash: ProtoJson::from_json(field(object, "hash", "hash"))?,
+                messages: ProtoJson::from_json(field(object, "messages", "messages"))?,
+                memo: ProtoJson::from_json(field(object, "memo", "memo"))?,
+                timeout_height: ProtoJson::from_json(field(
+                    object,
+                    "timeoutHeight",
+                    "timeout_height",
+                ))?,
+                fee: fee.map(|f| f.into()),
+                sequences: ProtoJson::from_json(field(object, "sequences", "sequences"))?,
+            })
+        };
+        parse().map_err(D::Error::custom)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl borsh::BorshSerialize for DecodedTx {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        borsh::BorshSerialize::serialize(&DecodedTxBinary::from(self), writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl borsh::BorshDeserialize for DecodedTx {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        <DecodedTxBinary as borsh::BorshDeserialize>::deserialize_reader(reader)
+            .map(DecodedTx::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coin::Coin;
+    use crate::error::TxValidationError;
+    use crate::private_key::PrivateKey;
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+
+    /// A stand in for a remote signing service, holds the key on the "other side"
+    struct RemoteSigner(PrivateKey);
+
+    impl AsyncSigner for RemoteSigner {
+        fn public_key(&self) -> Result<PublicKey, PrivateKeyError> {
+            self.0.to_public_key(PublicKey::DEFAULT_PREFIX)
+        }
+
+        async fn sign_digest(&self, digest: [u8; 32]) -> Result<[u8; 64], PrivateKeyError> {
+            tokio::task::yield_now().await;
+            self.0.sign_digest(digest).await
+        }
+    }
+
+    /// A message sending nothing from the key's own address to itself
+    fn self_send(private_key: &PrivateKey) -> Vec<Msg> {
+        let address = private_key.to_address("cosmos").unwrap();
+        let send = MsgSend {
+            amount: vec![],
+            from_address: address.to_string(),
+            to_address: address.to_string(),
+        };
+        vec![Msg::new("/cosmos.bank.v1beta1.MsgSend", send)]
+    }
+
+    #[actix_rt::test]
+    async fn test_external_signer_matches_local() {
+        let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let address = private_key.to_address("cosmos").unwrap();
+        let coin = Coin {
+            denom: "validatortoken".to_string(),
+            amount: crate::u256!(1),
+        };
+        let send = MsgSend {
+            amount: vec![coin.clone().into()],
+            from_address: address.to_string(),
+            to_address: "cosmos1pr2n6tfymnn2tk6rkxlu9q5q2zq5ka3wtu7sdj".to_string(),
+        };
+        let args = MessageArgs {
+            sequence: 0,
+            account_number: 0,
+            chain_id: "mychainid".to_string(),
+            fee: Fee {
+                amount: vec![coin],
+                gas_limit: 500_000,
+                granter: None,
+                payer: None,
+            },
+            timeout_height: 100,
+            ..Default::default()
+        };
+        let msg = Msg::new("/cosmos.bank.v1beta1.MsgSend", send);
+
+        let signer = RemoteSigner(private_key.clone());
+        let unsigned = UnsignedTx::new(
+            std::slice::from_ref(&msg),
+            args.clone(),
+            "",
+            signer.public_key().unwrap(),
+        )
+        .unwrap();
+        let remote = unsigned.sign_with(&signer).await.unwrap();
+        let local = private_key.sign_std_msg(&[msg], args, "").unwrap();
+        assert_eq!(remote, local);
+
+        let decoded = DecodedTx::decode(&local).unwrap();
+        assert_eq!(
+            decoded.message_types(),
+            vec!["/cosmos.bank.v1beta1.MsgSend"]
+        );
+        assert_eq!(decoded.timeout_height, 100);
+        assert_eq!(decoded.sequences, vec![0]);
+        assert_eq!(decoded.gas_price("validatortoken"), Some(1.0 / 500_000.0));
+        assert_eq!(decoded.hash.len(), 64);
+
+        let json = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(json["messages"][0]["@type"], "/cosmos.bank.v1beta1.MsgSend");
+        assert_eq!(json["timeoutHeight"], "100");
+        let parsed: DecodedTx = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, decoded);
+
+        #[cfg(feature = "borsh")]
+        {
+            let bytes = borsh::to_vec(&decoded).unwrap();
+            assert_eq!(borsh::from_slice::<DecodedTx>(&bytes).unwrap(), decoded);
+        }
+    }
+
+    #[test]
+    fn test_tx_builder_matches_positional() {
+        let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let address = private_key.to_address("cosmos").unwrap();
+        let msg = Msg::new(
+            "/cosmos.bank.v1beta1.MsgSend",
+            MsgSend {
+                amount: vec![],
+                from_address: address.to_string(),
+                to_address: address.to_string(),
+            },
+        );
+        let fee = Fee {
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+        let args = MessageArgs {
+            sequence: 4,
+            account_number: 9,
+            chain_id: "mychainid".to_string(),
+            fee: fee.clone(),
+            timeout_height: 100,
+            ..Default::default()
+        };
+        let positional = private_key
+            .sign_std_msg(std::slice::from_ref(&msg), args.clone(), "memo")
+            .unwrap();
+        let built = TxBuilder::from_args(args.clone())
+            .msg(msg.clone())
+            .memo("memo")
+            .sign_to_bytes(&private_key)
+            .unwrap();
+        assert_eq!(built, positional);
+
+        let public_key = private_key.to_public_key("cosmospub").unwrap();
+        let sign_doc = TxBuilder::new("mychainid")
+            .msg(msg.clone())
+            .fee(fee.clone())
+            .fee_granter(address)
+            .signer(public_key, 9, 4)
+            .sign_doc()
+            .unwrap();
+        assert_eq!(sign_doc.account_number, 9);
+        let auth_info = AuthInfo::decode(sign_doc.auth_info_bytes.as_slice()).unwrap();
+        assert_eq!(auth_info.fee.unwrap().granter, address.to_string());
+        assert_eq!(auth_info.signer_infos[0].sequence, 4);
+
+        let other = PrivateKey::from_secret_hkdf(b"otherSecret", b"", b"").unwrap();
+        let res = TxBuilder::from_args(args)
+            .msg(msg)
+            .signer(public_key, 9, 4)
+            .sign(&other);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_multi_signer_tx() {
+        let first = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let second = PrivateKey::from_secret_hkdf(b"otherSecret", b"", b"").unwrap();
+        let first_pub = first.to_public_key("cosmospub").unwrap();
+        let second_pub = second.to_public_key("cosmospub").unwrap();
+        let msg = Msg::new(
+            "/cosmos.bank.v1beta1.MsgSend",
+            MsgSend {
+                amount: vec![],
+                from_address: first.to_address("cosmos").unwrap().to_string(),
+                to_address: second.to_address("cosmos").unwrap().to_string(),
+            },
+        );
+        let fee = Fee {
+            gas_limit: 200_000,
+            ..Default::default()
+        };
+        let unsigned = TxBuilder::new("mychainid")
+            .msg(msg.clone())
+            .fee(fee.clone())
+            .signer(first_pub, 9, 4)
+            .co_signer(second_pub, 12, 1)
+            .build_multi_signer()
+            .unwrap();
+        assert_eq!(unsigned.signer_index(&second_pub), Some(1));
+        assert_eq!(unsigned.sign_doc(1).unwrap().account_number, 12);
+
+        // each party signs its own copy
+        let mut ours = unsigned.clone();
+        ours.sign(&first).unwrap();
+        let mut theirs = unsigned.clone();
+        theirs.sign(&second).unwrap();
+        assert!(!ours.is_complete());
+        assert!(ours.clone().into_tx().is_err());
+        ours.merge(&theirs).unwrap();
+        assert!(ours.is_complete());
+
+        let first_sig = first.sign_compact(unsigned.digest(0).unwrap()).unwrap();
+        let second_sig = second.sign_compact(unsigned.digest(1).unwrap()).unwrap();
+        let tx = ours.into_tx().unwrap();
+        assert_eq!(tx.signatures, vec![first_sig.to_vec(), second_sig.to_vec()]);
+        let signer_infos = tx.auth_info.unwrap().signer_infos;
+        assert_eq!(signer_infos.len(), 2);
+        assert_eq!(signer_infos[1].sequence, 1);
+
+        let outsider = PrivateKey::from_secret_hkdf(b"thirdSecret", b"", b"").unwrap();
+        assert!(unsigned.clone().sign(&outsider).is_err());
+        let other_tx = TxBuilder::new("mychainid")
+            .msg(msg.clone())
+            .fee(fee.clone())
+            .memo("different")
+            .signer(first_pub, 9, 4)
+            .co_signer(second_pub, 12, 1)
+            .build_multi_signer()
+            .unwrap();
+        assert!(theirs.merge(&other_tx).is_err());
+        let single = TxBuilder::new("mychainid")
+            .msg(msg)
+            .fee(fee)
+            .signer(first_pub, 9, 4)
+            .co_signer(second_pub, 12, 1)
+            .build_unsigned();
+        assert!(single.is_err());
+    }
+
+    #[test]
+    fn test_verify_tx_raw() {
+        let first = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let second = PrivateKey::from_secret_hkdf(b"otherSecret", b"", b"").unwrap();
+        let first_pub = first.to_public_key("cosmospub").unwrap();
+        let second_pub = second.to_public_key("cosmospub").unwrap();
+        let msg = Msg::new(
+            "/cosmos.bank.v1beta1.MsgSend",
+            MsgSend {
+                amount: vec![],
+                from_address: first.to_address("cosmos").unwrap().to_string(),
+                to_address: second.to_address("cosmos").unwrap().to_string(),
+            },
+        );
+        let fee = Fee {
+            gas_limit: 200_000,
+            ..Default::default()
+        };
+        let builder = TxBuilder::new("mychainid").msg(msg).fee(fee);
+        let mut tx = builder
+            .clone()
+            .signer(first_pub, 9, 4)
+            .co_signer(second_pub, 12, 1)
+            .build_multi_signer()
+            .unwrap();
+        tx.sign(&first).unwrap();
+        tx.sign(&second).unwrap();
+        let bytes = tx.into_tx_raw_bytes().unwrap();
+        let signers = verify_tx_raw(&bytes, "mychainid", &[9, 12]).unwrap();
+        assert_eq!(signers[1].public_key, second_pub);
+        assert_eq!(signers[1].sequence, 1);
+        assert_eq!(
+            verify_tx_raw(&bytes, "otherchain", &[9, 12]),
+            Err(TxVerificationError::InvalidSignature(0))
+        );
+        assert_eq!(
+            verify_tx_raw(&bytes, "mychainid", &[9]),
+            Err(TxVerificationError::AccountNumberCount {
+                signers: 2,
+                account_numbers: 1
+            })
+        );
+
+        // the high-s twin of a valid signature still verifies but is malleable
+        let raw = TxRaw::decode(bytes.as_slice()).unwrap();
+        let mut high = raw.clone();
+        high.signatures[1] =
+            crate::signature::tests::flip_s(raw.signatures[1].clone().try_into().unwrap()).to_vec();
+        assert_eq!(
+            verify_tx_raw(&high.encode_to_vec(), "mychainid", &[9, 12]),
+            Err(TxVerificationError::HighS(1))
+        );
+        let mut recoverable = raw.clone();
+        recoverable.signatures[0].push(0);
+        assert_eq!(
+            verify_tx_raw(&recoverable.encode_to_vec(), "mychainid", &[9, 12]),
+            Err(TxVerificationError::RecoveryIdPresent(0))
+        );
+        // an unknown field appended to the TxRaw changes the hash but not the signatures
+        let mut padded = bytes.clone();
+        padded.extend_from_slice(&[0x20, 0x01]);
+        assert_eq!(
+            verify_tx_raw(&padded, "mychainid", &[9, 12]),
+            Err(TxVerificationError::NonCanonicalEncoding)
+        );
+
+        let mut duplicated = builder
+            .signer(first_pub, 9, 4)
+            .co_signer(first_pub, 9, 4)
+            .build_multi_signer()
+            .unwrap();
+        let signature = first.sign_compact(duplicated.digest(0).unwrap()).unwrap();
+        duplicated.add_signature(0, signature).unwrap();
+        duplicated.add_signature(1, signature).unwrap();
+        let bytes = duplicated.into_tx_raw_bytes().unwrap();
+        assert_eq!(
+            verify_tx_raw(&bytes, "mychainid", &[9, 9]),
+            Err(TxVerificationError::DuplicateSigner {
+                first: 0,
+                second: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_partially_signed_tx() {
+        let first = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let second = PrivateKey::from_secret_hkdf(b"otherSecret", b"", b"").unwrap();
+        let msg = Msg::new(
+            "/cosmos.bank.v1beta1.MsgSend",
+            MsgSend {
+                amount: vec![],
+                from_address: first.to_address("cosmos").unwrap().to_string(),
+                to_address: second.to_address("cosmos").unwrap().to_string(),
+            },
+        );
+        let unsigned = TxBuilder::new("mychainid")
+            .msg(msg)
+            .fee(Fee {
+                gas_limit: 200_000,
+                ..Default::default()
+            })
+            .signer(first.to_public_key("cosmospub").unwrap(), 9, 4)
+            .co_signer(second.to_public_key("cosmospub").unwrap(), 12, 1)
+            .build_multi_signer()
+            .unwrap();
+        let pst = PartiallySignedTx::from(unsigned.clone());
+        assert_eq!(MultiSignerTx::try_from(pst.clone()).unwrap(), unsigned);
+        assert_eq!(pst.signers().unwrap(), unsigned.signers());
+
+        // one signer passes a JSON file, the other the binary form
+        let mut ours: PartiallySignedTx =
+            serde_json::from_str(&serde_json::to_string(&pst).unwrap()).unwrap();
+        ours.sign(&first).unwrap();
+        let mut theirs = PartiallySignedTx::from_bytes(&pst.to_bytes()).unwrap();
+        theirs.sign(&second).unwrap();
+        assert!(!ours.is_complete());
+        assert!(ours.clone().finalize().is_err());
+        ours.merge(&theirs).unwrap();
+        assert!(ours.is_complete());
+
+        let mut expected = unsigned;
+        expected.sign(&first).unwrap();
+        expected.sign(&second).unwrap();
+        assert_eq!(
+            ours.clone().finalize().unwrap(),
+            expected.into_tx_raw_bytes().unwrap()
+        );
+
+        let mut forged = ours;
+        forged.signers[1].signature = forged.signers[0].signature.clone();
+        assert!(forged.finalize().is_err());
+    }
+
+    #[test]
+    fn test_extension_options_passthrough() {
+        let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let option = Any {
+            type_url: "/example.v1.ExtensionOption".to_string(),
+            value: vec![1, 2, 3],
+        };
+        let mut args = MessageArgs {
+            chain_id: "mychainid".to_string(),
+            extension_options: vec![option.clone()],
+            non_critical_extension_options: vec![option.clone(), option.clone()],
+            ..Default::default()
+        };
+        args.fee.gas_limit = 100_000;
+        let unsigned = UnsignedTx::new(
+            &self_send(&private_key),
+            args,
+            "memo",
+            private_key
+                .to_public_key(PublicKey::DEFAULT_PREFIX)
+                .unwrap(),
+        )
+        .unwrap();
+        let tx = unsigned.into_signed_tx([0u8; 64]);
+        let body = tx.body.unwrap();
+        assert_eq!(body.memo, "memo");
+        assert_eq!(body.extension_options, vec![option.clone()]);
+        assert_eq!(
+            body.non_critical_extension_options,
+            vec![option.clone(), option]
+        );
+    }
+
+    #[test]
+    fn test_unordered_tx() {
+        let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let public_key = private_key
+            .to_public_key(PublicKey::DEFAULT_PREFIX)
+            .unwrap();
+        let msgs = self_send(&private_key);
+        let mut args = MessageArgs {
+            chain_id: "mychainid".to_string(),
+            unordered: true,
+            ..Default::default()
+        };
+        args.fee.gas_limit = 100_000;
+        // external signers are held to the same checks as local keys
+        assert!(matches!(
+            UnsignedTx::new(&[], args.clone(), "", public_key),
+            Err(PrivateKeyError::TxValidationError(
+                TxValidationError::NoMessages
+            ))
+        ));
+        let mut zero_gas = args.clone();
+        zero_gas.fee.gas_limit = 0;
+        assert!(matches!(
+            UnsignedTx::new(&msgs, zero_gas, "", public_key),
+            Err(PrivateKeyError::TxValidationError(
+                TxValidationError::ZeroGas
+            ))
+        ));
+        assert!(matches!(
+            UnsignedTx::new(&msgs, args.clone(), "", public_key),
+            Err(PrivateKeyError::InvalidMessageArgs(_))
+        ));
+
+        args.timeout_timestamp = Some(SystemTime::now() - std::time::Duration::from_secs(1));
+        assert!(matches!(
+            UnsignedTx::new(&msgs, args.clone(), "", public_key),
+            Err(PrivateKeyError::InvalidMessageArgs(_))
+        ));
+
+        let timeout = SystemTime::now() + std::time::Duration::from_secs(60);
+        args.timeout_timestamp = Some(timeout);
+        let unsigned = UnsignedTx::new(&msgs, args.clone(), "", public_key).unwrap();
+        let body = TxBodyV053::decode(unsigned.body_buf.as_slice()).unwrap();
+        assert!(body.unordered);
+        assert_eq!(body.timeout_timestamp, Some(timeout.into()));
+
+        // without the new fields the encoding matches the older TxBody
+        args.unordered = false;
+        args.timeout_timestamp = None;
+        let unsigned = UnsignedTx::new(&msgs, args, "", public_key).unwrap();
+        let mut legacy = Vec::new();
+        unsigned.body.encode(&mut legacy).unwrap();
+        assert_eq!(unsigned.body_buf, legacy);
+    }
+
+    #[test]
+    fn test_estimate_tx_size() {
+        let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+        let address = private_key.to_address("cosmos").unwrap();
+        let coin: Coin = "1000uatom".parse().unwrap();
+        let send = MsgSend {
+            amount: vec![coin.clone().into()],
+            from_address: address.to_string(),
+            to_address: address.to_string(),
+        };
+        let msgs = vec![Msg::new("/cosmos.bank.v1beta1.MsgSend", send)];
+        let args = MessageArgs {
+            sequence: 12,
+            timeout_height: 1_000_000,
+            chain_id: "mychainid".to_string(),
+            fee: Fee {
+                amount: vec![coin],
+                gas_limit: 200_000,
+                granter: None,
+                payer: None,
+            },
+            ..Default::default()
+        };
+        let tx = private_key.sign_std_msg(&msgs, args, "memo").unwrap();
+        let estimate = estimate_tx_size(&msgs, "memo", 1);
+        assert!(estimate >= tx.len());
+        assert!(estimate < tx.len() + 200);
+        assert!(estimate_tx_size(&msgs, "memo", 2) > estimate + 64);
+    }
+
+    proptest::proptest! {
+        #[test]
+        // this tests that the encoded fields of any tx survive decoding
+        fn proptest_tx_round_trip(
+            memo in "\\PC{0,64}",
+            sequence: u64,
+            account_number: u64,
+            timeout_height: u64,
+            gas_limit in 1u64..,
+            amount: u64,
+        ) {
+            let private_key = PrivateKey::from_secret_hkdf(b"mySecret", b"", b"").unwrap();
+            let address = private_key.to_address("cosmos").unwrap();
+            let coin: Coin = format!("{}uatom", amount).parse().unwrap();
+            let send = MsgSend {
+                amount: vec![coin.clone().into()],
+                from_address: address.to_string(),
+                to_address: address.to_string(),
+            };
+            let msg = Msg::new("/cosmos.bank.v1beta1.MsgSend", send);
+            let args = MessageArgs {
+                sequence,
+                account_number,
+                timeout_height,
+                chain_id: "mychainid".to_string(),
+                fee: Fee {
+                    amount: vec![coin],
+                    gas_limit,
+                    granter: None,
+                    payer: None,
+                },
+                ..Default::default()
+            };
+            let tx = private_key.sign_std_msg(&[msg], args.clone(), memo.clone()).unwrap();
+            let decoded = DecodedTx::decode(&tx).unwrap();
+            proptest::prop_assert_eq!(decoded.memo, memo);
+            proptest::prop_assert_eq!(decoded.sequences, vec![sequence]);
+            proptest::prop_assert_eq!(decoded.timeout_height, timeout_height);
+            proptest::prop_assert_eq!(decoded.fee, Some(args.fee));
+        }
+
+        #[test]
+        // this tests that decoding arbitrary bytes returns rather than panicking
+        fn proptest_decode_arbitrary(bytes in proptest::collection::vec(0u8.., 0..512)) {
+            let _ = DecodedTx::decode(&bytes);
+        }
+    }
+
+    #[cfg(feature = "encoding-audit")]
+    #[test]
+    fn test_encoding_audit() {
+        let fee = ProtoFee {
+            amount: Vec::new(),
+            gas_limit: 100,
+            payer: String::new(),
+            granter: String::new(),
+        };
+        let auth_info = AuthInfo {
+            signer_infos: Vec::new(),
+            fee: Some(fee),
+        };
+        audit_encoding::<AuthInfo>(&auth_info.encode_to_vec(), "AuthInfo");
+
+        // a fee with its zero gas limit written out is valid protobuf, but not canonical
+        let non_canonical = [0x12, 0x02, 0x10, 0x00];
+        let result =
+            std::panic::catch_unwind(|| audit_encoding::<AuthInfo>(&non_canonical, "AuthInfo"));
+        assert!(result.is_err());
+    }
+}
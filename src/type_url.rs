@@ -0,0 +1,1021 @@
+//! Ties proto messages to their type url so that packing a message into an Any never
+//! requires typing the url by hand, implemented for every message in the proto crate
+
+/// A proto message with a known type url, the url is `/` followed by the fully
+/// qualified proto name, for example `/cosmos.bank.v1beta1.MsgSend`
+pub trait TypeUrl {
+    const TYPE_URL: &'static str;
+}
+
+macro_rules! impl_type_url {
+    ($package:literal, $module:path, [$($name:ident),* $(,)?]) => {
+        const _: () = {
+            use $module as module;
+            $(
+                impl TypeUrl for module::$name {
+                    const TYPE_URL: &'static str = concat!("/", $package, ".", stringify!($name));
+                }
+            )*
+        };
+    };
+}
+
+impl_type_url!(
+    "cosmos.auth.v1beta1",
+    cosmos_sdk_proto::cosmos::auth::v1beta1,
+    [
+        BaseAccount,
+        ModuleAccount,
+        Params,
+        GenesisState,
+        QueryAccountsRequest,
+        QueryAccountsResponse,
+        QueryAccountRequest,
+        QueryAccountResponse,
+        QueryParamsRequest,
+        QueryParamsResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.authz.v1beta1",
+    cosmos_sdk_proto::cosmos::authz::v1beta1,
+    [
+        EventGrant,
+        EventRevoke,
+        GenericAuthorization,
+        Grant,
+        GrantAuthorization,
+        MsgGrant,
+        MsgExecResponse,
+        MsgExec,
+        MsgGrantResponse,
+        MsgRevoke,
+        MsgRevokeResponse,
+        GenesisState,
+        QueryGrantsRequest,
+        QueryGrantsResponse,
+        QueryGranterGrantsRequest,
+        QueryGranterGrantsResponse,
+        QueryGranteeGrantsRequest,
+        QueryGranteeGrantsResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.bank.v1beta1",
+    cosmos_sdk_proto::cosmos::bank::v1beta1,
+    [
+        Params,
+        SendEnabled,
+        Input,
+        Output,
+        Supply,
+        DenomUnit,
+        Metadata,
+        GenesisState,
+        Balance,
+        MsgSend,
+        MsgSendResponse,
+        MsgMultiSend,
+        MsgMultiSendResponse,
+        SendAuthorization,
+        QueryBalanceRequest,
+        QueryBalanceResponse,
+        QueryAllBalancesRequest,
+        QueryAllBalancesResponse,
+        QuerySpendableBalancesRequest,
+        QuerySpendableBalancesResponse,
+        QueryTotalSupplyRequest,
+        QueryTotalSupplyResponse,
+        QuerySupplyOfRequest,
+        QuerySupplyOfResponse,
+        QueryParamsRequest,
+        QueryParamsResponse,
+        QueryDenomsMetadataRequest,
+        QueryDenomsMetadataResponse,
+        QueryDenomMetadataRequest,
+        QueryDenomMetadataResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.base.abci.v1beta1",
+    cosmos_sdk_proto::cosmos::base::abci::v1beta1,
+    [
+        TxResponse,
+        AbciMessageLog,
+        StringEvent,
+        Attribute,
+        GasInfo,
+        Result,
+        SimulationResponse,
+        MsgData,
+        TxMsgData,
+        SearchTxsResult,
+    ]
+);
+impl_type_url!(
+    "cosmos.base.kv.v1beta1",
+    cosmos_sdk_proto::cosmos::base::kv::v1beta1,
+    [Pairs, Pair,]
+);
+impl_type_url!(
+    "cosmos.base.query.v1beta1",
+    cosmos_sdk_proto::cosmos::base::query::v1beta1,
+    [PageRequest, PageResponse,]
+);
+impl_type_url!(
+    "cosmos.base.reflection.v1beta1",
+    cosmos_sdk_proto::cosmos::base::reflection::v1beta1,
+    [
+        ListAllInterfacesRequest,
+        ListAllInterfacesResponse,
+        ListImplementationsRequest,
+        ListImplementationsResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.base.snapshots.v1beta1",
+    cosmos_sdk_proto::cosmos::base::snapshots::v1beta1,
+    [
+        Snapshot,
+        Metadata,
+        SnapshotItem,
+        SnapshotStoreItem,
+        SnapshotIavlItem,
+        SnapshotExtensionMeta,
+        SnapshotExtensionPayload,
+    ]
+);
+impl_type_url!(
+    "cosmos.base.store.v1beta1",
+    cosmos_sdk_proto::cosmos::base::store::v1beta1,
+    [CommitInfo, StoreInfo, CommitId, StoreKvPair,]
+);
+impl_type_url!(
+    "cosmos.base.tendermint.v1beta1",
+    cosmos_sdk_proto::cosmos::base::tendermint::v1beta1,
+    [
+        GetValidatorSetByHeightRequest,
+        GetValidatorSetByHeightResponse,
+        GetLatestValidatorSetRequest,
+        GetLatestValidatorSetResponse,
+        Validator,
+        GetBlockByHeightRequest,
+        GetBlockByHeightResponse,
+        GetLatestBlockRequest,
+        GetLatestBlockResponse,
+        GetSyncingRequest,
+        GetSyncingResponse,
+        GetNodeInfoRequest,
+        GetNodeInfoResponse,
+        VersionInfo,
+        Module,
+    ]
+);
+impl_type_url!(
+    "cosmos.base.v1beta1",
+    cosmos_sdk_proto::cosmos::base::v1beta1,
+    [Coin, DecCoin, IntProto, DecProto,]
+);
+impl_type_url!(
+    "cosmos.crisis.v1beta1",
+    cosmos_sdk_proto::cosmos::crisis::v1beta1,
+    [GenesisState, MsgVerifyInvariant, MsgVerifyInvariantResponse,]
+);
+impl_type_url!(
+    "cosmos.crypto.ed25519",
+    cosmos_sdk_proto::cosmos::crypto::ed25519,
+    [PubKey, PrivKey,]
+);
+impl_type_url!(
+    "cosmos.crypto.multisig",
+    cosmos_sdk_proto::cosmos::crypto::multisig,
+    [LegacyAminoPubKey,]
+);
+impl_type_url!(
+    "cosmos.crypto.multisig.v1beta1",
+    cosmos_sdk_proto::cosmos::crypto::multisig::v1beta1,
+    [MultiSignature, CompactBitArray,]
+);
+impl_type_url!(
+    "cosmos.crypto.secp256k1",
+    cosmos_sdk_proto::cosmos::crypto::secp256k1,
+    [PubKey, PrivKey,]
+);
+impl_type_url!(
+    "cosmos.distribution.v1beta1",
+    cosmos_sdk_proto::cosmos::distribution::v1beta1,
+    [
+        Params,
+        ValidatorHistoricalRewards,
+        ValidatorCurrentRewards,
+        ValidatorAccumulatedCommission,
+        ValidatorOutstandingRewards,
+        ValidatorSlashEvent,
+        ValidatorSlashEvents,
+        FeePool,
+        CommunityPoolSpendProposal,
+        DelegatorStartingInfo,
+        DelegationDelegatorReward,
+        CommunityPoolSpendProposalWithDeposit,
+        DelegatorWithdrawInfo,
+        ValidatorOutstandingRewardsRecord,
+        ValidatorAccumulatedCommissionRecord,
+        ValidatorHistoricalRewardsRecord,
+        ValidatorCurrentRewardsRecord,
+        DelegatorStartingInfoRecord,
+        ValidatorSlashEventRecord,
+        GenesisState,
+        QueryParamsRequest,
+        QueryParamsResponse,
+        QueryValidatorOutstandingRewardsRequest,
+        QueryValidatorOutstandingRewardsResponse,
+        QueryValidatorCommissionRequest,
+        QueryValidatorCommissionResponse,
+        QueryValidatorSlashesRequest,
+        QueryValidatorSlashesResponse,
+        QueryDelegationRewardsRequest,
+        QueryDelegationRewardsResponse,
+        QueryDelegationTotalRewardsRequest,
+        QueryDelegationTotalRewardsResponse,
+        QueryDelegatorValidatorsRequest,
+        QueryDelegatorValidatorsResponse,
+        QueryDelegatorWithdrawAddressRequest,
+        QueryDelegatorWithdrawAddressResponse,
+        QueryCommunityPoolRequest,
+        QueryCommunityPoolResponse,
+        MsgSetWithdrawAddress,
+        MsgSetWithdrawAddressResponse,
+        MsgWithdrawDelegatorReward,
+        MsgWithdrawDelegatorRewardResponse,
+        MsgWithdrawValidatorCommission,
+        MsgWithdrawValidatorCommissionResponse,
+        MsgFundCommunityPool,
+        MsgFundCommunityPoolResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.evidence.v1beta1",
+    cosmos_sdk_proto::cosmos::evidence::v1beta1,
+    [
+        Equivocation,
+        GenesisState,
+        QueryEvidenceRequest,
+        QueryEvidenceResponse,
+        QueryAllEvidenceRequest,
+        QueryAllEvidenceResponse,
+        MsgSubmitEvidence,
+        MsgSubmitEvidenceResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.feegrant.v1beta1",
+    cosmos_sdk_proto::cosmos::feegrant::v1beta1,
+    [
+        BasicAllowance,
+        PeriodicAllowance,
+        AllowedMsgAllowance,
+        Grant,
+        GenesisState,
+        QueryAllowanceRequest,
+        QueryAllowanceResponse,
+        QueryAllowancesRequest,
+        QueryAllowancesResponse,
+        MsgGrantAllowance,
+        MsgGrantAllowanceResponse,
+        MsgRevokeAllowance,
+        MsgRevokeAllowanceResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.genutil.v1beta1",
+    cosmos_sdk_proto::cosmos::genutil::v1beta1,
+    [GenesisState,]
+);
+impl_type_url!(
+    "cosmos.gov.v1beta1",
+    cosmos_sdk_proto::cosmos::gov::v1beta1,
+    [
+        WeightedVoteOption,
+        TextProposal,
+        Deposit,
+        Proposal,
+        TallyResult,
+        Vote,
+        DepositParams,
+        VotingParams,
+        TallyParams,
+        GenesisState,
+        QueryProposalRequest,
+        QueryProposalResponse,
+        QueryProposalsRequest,
+        QueryProposalsResponse,
+        QueryVoteRequest,
+        QueryVoteResponse,
+        QueryVotesRequest,
+        QueryVotesResponse,
+        QueryParamsRequest,
+        QueryParamsResponse,
+        QueryDepositRequest,
+        QueryDepositResponse,
+        QueryDepositsRequest,
+        QueryDepositsResponse,
+        QueryTallyResultRequest,
+        QueryTallyResultResponse,
+        MsgSubmitProposal,
+        MsgSubmitProposalResponse,
+        MsgVote,
+        MsgVoteResponse,
+        MsgVoteWeighted,
+        MsgVoteWeightedResponse,
+        MsgDeposit,
+        MsgDepositResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.mint.v1beta1",
+    cosmos_sdk_proto::cosmos::mint::v1beta1,
+    [
+        Minter,
+        Params,
+        GenesisState,
+        QueryParamsRequest,
+        QueryParamsResponse,
+        QueryInflationRequest,
+        QueryInflationResponse,
+        QueryAnnualProvisionsRequest,
+        QueryAnnualProvisionsResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.params.v1beta1",
+    cosmos_sdk_proto::cosmos::params::v1beta1,
+    [
+        ParameterChangeProposal,
+        ParamChange,
+        QueryParamsRequest,
+        QueryParamsResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.slashing.v1beta1",
+    cosmos_sdk_proto::cosmos::slashing::v1beta1,
+    [
+        ValidatorSigningInfo,
+        Params,
+        GenesisState,
+        SigningInfo,
+        ValidatorMissedBlocks,
+        MissedBlock,
+        QueryParamsRequest,
+        QueryParamsResponse,
+        QuerySigningInfoRequest,
+        QuerySigningInfoResponse,
+        QuerySigningInfosRequest,
+        QuerySigningInfosResponse,
+        MsgUnjail,
+        MsgUnjailResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.staking.v1beta1",
+    cosmos_sdk_proto::cosmos::staking::v1beta1,
+    [
+        HistoricalInfo,
+        CommissionRates,
+        Commission,
+        Description,
+        Validator,
+        ValAddresses,
+        DvPair,
+        DvPairs,
+        DvvTriplet,
+        DvvTriplets,
+        Delegation,
+        UnbondingDelegation,
+        UnbondingDelegationEntry,
+        RedelegationEntry,
+        Redelegation,
+        Params,
+        DelegationResponse,
+        RedelegationEntryResponse,
+        RedelegationResponse,
+        Pool,
+        GenesisState,
+        LastValidatorPower,
+        QueryValidatorsRequest,
+        QueryValidatorsResponse,
+        QueryValidatorRequest,
+        QueryValidatorResponse,
+        QueryValidatorDelegationsRequest,
+        QueryValidatorDelegationsResponse,
+        QueryValidatorUnbondingDelegationsRequest,
+        QueryValidatorUnbondingDelegationsResponse,
+        QueryDelegationRequest,
+        QueryDelegationResponse,
+        QueryUnbondingDelegationRequest,
+        QueryUnbondingDelegationResponse,
+        QueryDelegatorDelegationsRequest,
+        QueryDelegatorDelegationsResponse,
+        QueryDelegatorUnbondingDelegationsRequest,
+        QueryDelegatorUnbondingDelegationsResponse,
+        QueryRedelegationsRequest,
+        QueryRedelegationsResponse,
+        QueryDelegatorValidatorsRequest,
+        QueryDelegatorValidatorsResponse,
+        QueryDelegatorValidatorRequest,
+        QueryDelegatorValidatorResponse,
+        QueryHistoricalInfoRequest,
+        QueryHistoricalInfoResponse,
+        QueryPoolRequest,
+        QueryPoolResponse,
+        QueryParamsRequest,
+        QueryParamsResponse,
+        MsgCreateValidator,
+        MsgCreateValidatorResponse,
+        MsgEditValidator,
+        MsgEditValidatorResponse,
+        MsgDelegate,
+        MsgDelegateResponse,
+        MsgBeginRedelegate,
+        MsgBeginRedelegateResponse,
+        MsgUndelegate,
+        MsgUndelegateResponse,
+        StakeAuthorization,
+    ]
+);
+impl_type_url!(
+    "cosmos.tx.signing.v1beta1",
+    cosmos_sdk_proto::cosmos::tx::signing::v1beta1,
+    [SignatureDescriptors, SignatureDescriptor,]
+);
+impl_type_url!(
+    "cosmos.tx.v1beta1",
+    cosmos_sdk_proto::cosmos::tx::v1beta1,
+    [
+        Tx,
+        TxRaw,
+        SignDoc,
+        TxBody,
+        AuthInfo,
+        SignerInfo,
+        ModeInfo,
+        Fee,
+        GetTxsEventRequest,
+        GetTxsEventResponse,
+        BroadcastTxRequest,
+        BroadcastTxResponse,
+        SimulateRequest,
+        SimulateResponse,
+        GetTxRequest,
+        GetTxResponse,
+        GetBlockWithTxsRequest,
+        GetBlockWithTxsResponse,
+    ]
+);
+impl_type_url!(
+    "cosmos.upgrade.v1beta1",
+    cosmos_sdk_proto::cosmos::upgrade::v1beta1,
+    [Plan, SoftwareUpgradeProposal, CancelSoftwareUpgradeProposal,]
+);
+impl_type_url!(
+    "cosmos.vesting.v1beta1",
+    cosmos_sdk_proto::cosmos::vesting::v1beta1,
+    [
+        MsgCreateVestingAccount,
+        MsgCreateVestingAccountResponse,
+        BaseVestingAccount,
+        ContinuousVestingAccount,
+        DelayedVestingAccount,
+        Period,
+        PeriodicVestingAccount,
+        PermanentLockedAccount,
+    ]
+);
+impl_type_url!(
+    "ibc.applications.transfer.v1",
+    cosmos_sdk_proto::ibc::applications::transfer::v1,
+    [
+        DenomTrace,
+        Params,
+        GenesisState,
+        MsgTransfer,
+        MsgTransferResponse,
+        QueryDenomTraceRequest,
+        QueryDenomTraceResponse,
+        QueryDenomTracesRequest,
+        QueryDenomTracesResponse,
+        QueryParamsRequest,
+        QueryParamsResponse,
+        QueryDenomHashRequest,
+        QueryDenomHashResponse,
+    ]
+);
+impl_type_url!(
+    "ibc.applications.transfer.v2",
+    cosmos_sdk_proto::ibc::applications::transfer::v2,
+    [FungibleTokenPacketData,]
+);
+impl_type_url!(
+    "ibc.core.channel.v1",
+    cosmos_sdk_proto::ibc::core::channel::v1,
+    [
+        Channel,
+        IdentifiedChannel,
+        Counterparty,
+        Packet,
+        PacketState,
+        Acknowledgement,
+        GenesisState,
+        PacketSequence,
+        QueryChannelRequest,
+        QueryChannelResponse,
+        QueryChannelsRequest,
+        QueryChannelsResponse,
+        QueryConnectionChannelsRequest,
+        QueryConnectionChannelsResponse,
+        QueryChannelClientStateRequest,
+        QueryChannelClientStateResponse,
+        QueryChannelConsensusStateRequest,
+        QueryChannelConsensusStateResponse,
+        QueryPacketCommitmentRequest,
+        QueryPacketCommitmentResponse,
+        QueryPacketCommitmentsRequest,
+        QueryPacketCommitmentsResponse,
+        QueryPacketReceiptRequest,
+        QueryPacketReceiptResponse,
+        QueryPacketAcknowledgementRequest,
+        QueryPacketAcknowledgementResponse,
+        QueryPacketAcknowledgementsRequest,
+        QueryPacketAcknowledgementsResponse,
+        QueryUnreceivedPacketsRequest,
+        QueryUnreceivedPacketsResponse,
+        QueryUnreceivedAcksRequest,
+        QueryUnreceivedAcksResponse,
+        QueryNextSequenceReceiveRequest,
+        QueryNextSequenceReceiveResponse,
+        MsgChannelOpenInit,
+        MsgChannelOpenInitResponse,
+        MsgChannelOpenTry,
+        MsgChannelOpenTryResponse,
+        MsgChannelOpenAck,
+        MsgChannelOpenAckResponse,
+        MsgChannelOpenConfirm,
+        MsgChannelOpenConfirmResponse,
+        MsgChannelCloseInit,
+        MsgChannelCloseInitResponse,
+        MsgChannelCloseConfirm,
+        MsgChannelCloseConfirmResponse,
+        MsgRecvPacket,
+        MsgRecvPacketResponse,
+        MsgTimeout,
+        MsgTimeoutResponse,
+        MsgTimeoutOnClose,
+        MsgTimeoutOnCloseResponse,
+        MsgAcknowledgement,
+        MsgAcknowledgementResponse,
+    ]
+);
+impl_type_url!(
+    "ibc.core.client.v1",
+    cosmos_sdk_proto::ibc::core::client::v1,
+    [
+        IdentifiedClientState,
+        ConsensusStateWithHeight,
+        ClientConsensusStates,
+        ClientUpdateProposal,
+        UpgradeProposal,
+        Height,
+        Params,
+        GenesisState,
+        GenesisMetadata,
+        IdentifiedGenesisMetadata,
+        QueryClientStateRequest,
+        QueryClientStateResponse,
+        QueryClientStatesRequest,
+        QueryClientStatesResponse,
+        QueryConsensusStateRequest,
+        QueryConsensusStateResponse,
+        QueryConsensusStatesRequest,
+        QueryConsensusStatesResponse,
+        QueryClientStatusRequest,
+        QueryClientStatusResponse,
+        QueryClientParamsRequest,
+        QueryClientParamsResponse,
+        QueryUpgradedClientStateRequest,
+        QueryUpgradedClientStateResponse,
+        QueryUpgradedConsensusStateRequest,
+        QueryUpgradedConsensusStateResponse,
+        MsgCreateClient,
+        MsgCreateClientResponse,
+        MsgUpdateClient,
+        MsgUpdateClientResponse,
+        MsgUpgradeClient,
+        MsgUpgradeClientResponse,
+        MsgSubmitMisbehaviour,
+        MsgSubmitMisbehaviourResponse,
+    ]
+);
+impl_type_url!(
+    "ibc.core.commitment.v1",
+    cosmos_sdk_proto::ibc::core::commitment::v1,
+    [MerkleRoot, MerklePrefix, MerklePath, MerkleProof,]
+);
+impl_type_url!(
+    "ibc.core.connection.v1",
+    cosmos_sdk_proto::ibc::core::connection::v1,
+    [
+        ConnectionEnd,
+        IdentifiedConnection,
+        Counterparty,
+        ClientPaths,
+        ConnectionPaths,
+        Version,
+        Params,
+        GenesisState,
+        QueryConnectionRequest,
+        QueryConnectionResponse,
+        QueryConnectionsRequest,
+        QueryConnectionsResponse,
+        QueryClientConnectionsRequest,
+        QueryClientConnectionsResponse,
+        QueryConnectionClientStateRequest,
+        QueryConnectionClientStateResponse,
+        QueryConnectionConsensusStateRequest,
+        QueryConnectionConsensusStateResponse,
+        MsgConnectionOpenInit,
+        MsgConnectionOpenInitResponse,
+        MsgConnectionOpenTry,
+        MsgConnectionOpenTryResponse,
+        MsgConnectionOpenAck,
+        MsgConnectionOpenAckResponse,
+        MsgConnectionOpenConfirm,
+        MsgConnectionOpenConfirmResponse,
+    ]
+);
+impl_type_url!(
+    "ibc.core.port.v1",
+    cosmos_sdk_proto::ibc::core::port::v1,
+    [QueryAppVersionRequest, QueryAppVersionResponse,]
+);
+impl_type_url!(
+    "ibc.core.types.v1",
+    cosmos_sdk_proto::ibc::core::types::v1,
+    [GenesisState,]
+);
+impl_type_url!(
+    "ibc.lightclients.localhost.v1",
+    cosmos_sdk_proto::ibc::lightclients::localhost::v1,
+    [ClientState,]
+);
+impl_type_url!(
+    "ibc.lightclients.solomachine.v1",
+    cosmos_sdk_proto::ibc::lightclients::solomachine::v1,
+    [
+        ClientState,
+        ConsensusState,
+        Header,
+        Misbehaviour,
+        SignatureAndData,
+        TimestampedSignatureData,
+        SignBytes,
+        HeaderData,
+        ClientStateData,
+        ConsensusStateData,
+        ConnectionStateData,
+        ChannelStateData,
+        PacketCommitmentData,
+        PacketAcknowledgementData,
+        PacketReceiptAbsenceData,
+        NextSequenceRecvData,
+    ]
+);
+impl_type_url!(
+    "ibc.lightclients.solomachine.v2",
+    cosmos_sdk_proto::ibc::lightclients::solomachine::v2,
+    [
+        ClientState,
+        ConsensusState,
+        Header,
+        Misbehaviour,
+        SignatureAndData,
+        TimestampedSignatureData,
+        SignBytes,
+        HeaderData,
+        ClientStateData,
+        ConsensusStateData,
+        ConnectionStateData,
+        ChannelStateData,
+        PacketCommitmentData,
+        PacketAcknowledgementData,
+        PacketReceiptAbsenceData,
+        NextSequenceRecvData,
+    ]
+);
+impl_type_url!(
+    "ibc.lightclients.tendermint.v1",
+    cosmos_sdk_proto::ibc::lightclients::tendermint::v1,
+    [ClientState, ConsensusState, Misbehaviour, Header, Fraction,]
+);
+impl_type_url!(
+    "ics23",
+    cosmos_sdk_proto::ics23,
+    [
+        ExistenceProof,
+        NonExistenceProof,
+        CommitmentProof,
+        LeafOp,
+        InnerOp,
+        ProofSpec,
+        InnerSpec,
+        BatchProof,
+        BatchEntry,
+        CompressedBatchProof,
+        CompressedBatchEntry,
+        CompressedExistenceProof,
+        CompressedNonExistenceProof,
+    ]
+);
+impl_type_url!(
+    "tendermint.abci",
+    cosmos_sdk_proto::tendermint::abci,
+    [
+        Request,
+        RequestEcho,
+        RequestFlush,
+        RequestInfo,
+        RequestSetOption,
+        RequestInitChain,
+        RequestQuery,
+        RequestBeginBlock,
+        RequestCheckTx,
+        RequestDeliverTx,
+        RequestEndBlock,
+        RequestCommit,
+        RequestListSnapshots,
+        RequestOfferSnapshot,
+        RequestLoadSnapshotChunk,
+        RequestApplySnapshotChunk,
+        Response,
+        ResponseException,
+        ResponseEcho,
+        ResponseFlush,
+        ResponseInfo,
+        ResponseSetOption,
+        ResponseInitChain,
+        ResponseQuery,
+        ResponseBeginBlock,
+        ResponseCheckTx,
+        ResponseDeliverTx,
+        ResponseEndBlock,
+        ResponseCommit,
+        ResponseListSnapshots,
+        ResponseOfferSnapshot,
+        ResponseLoadSnapshotChunk,
+        ResponseApplySnapshotChunk,
+        ConsensusParams,
+        BlockParams,
+        LastCommitInfo,
+        Event,
+        EventAttribute,
+        TxResult,
+        Validator,
+        ValidatorUpdate,
+        VoteInfo,
+        Evidence,
+        Snapshot,
+    ]
+);
+impl_type_url!(
+    "tendermint.consensus",
+    cosmos_sdk_proto::tendermint::consensus,
+    [
+        NewRoundStep,
+        NewValidBlock,
+        Proposal,
+        ProposalPol,
+        BlockPart,
+        Vote,
+        HasVote,
+        VoteSetMaj23,
+        VoteSetBits,
+        Message,
+        MsgInfo,
+        TimeoutInfo,
+        EndHeight,
+        WalMessage,
+        TimedWalMessage,
+    ]
+);
+impl_type_url!(
+    "tendermint.crypto",
+    cosmos_sdk_proto::tendermint::crypto,
+    [Proof, ValueOp, DominoOp, ProofOp, ProofOps, PublicKey,]
+);
+impl_type_url!(
+    "tendermint.libs.bits",
+    cosmos_sdk_proto::tendermint::libs::bits,
+    [BitArray,]
+);
+impl_type_url!(
+    "tendermint.mempool",
+    cosmos_sdk_proto::tendermint::mempool,
+    [Txs, Message,]
+);
+impl_type_url!(
+    "tendermint.p2p",
+    cosmos_sdk_proto::tendermint::p2p,
+    [
+        PacketPing,
+        PacketPong,
+        PacketMsg,
+        Packet,
+        AuthSigMessage,
+        NetAddress,
+        ProtocolVersion,
+        DefaultNodeInfo,
+        DefaultNodeInfoOther,
+        PexRequest,
+        PexAddrs,
+        Message,
+    ]
+);
+impl_type_url!(
+    "tendermint.privval",
+    cosmos_sdk_proto::tendermint::privval,
+    [
+        RemoteSignerError,
+        PubKeyRequest,
+        PubKeyResponse,
+        SignVoteRequest,
+        SignedVoteResponse,
+        SignProposalRequest,
+        SignedProposalResponse,
+        PingRequest,
+        PingResponse,
+        Message,
+    ]
+);
+impl_type_url!(
+    "tendermint.state",
+    cosmos_sdk_proto::tendermint::state,
+    [
+        AbciResponses,
+        ValidatorsInfo,
+        ConsensusParamsInfo,
+        Version,
+        State,
+    ]
+);
+impl_type_url!(
+    "tendermint.statesync",
+    cosmos_sdk_proto::tendermint::statesync,
+    [
+        Message,
+        SnapshotsRequest,
+        SnapshotsResponse,
+        ChunkRequest,
+        ChunkResponse,
+    ]
+);
+impl_type_url!(
+    "tendermint.types",
+    cosmos_sdk_proto::tendermint::types,
+    [
+        ValidatorSet,
+        Validator,
+        SimpleValidator,
+        PartSetHeader,
+        Part,
+        BlockId,
+        Header,
+        Data,
+        Vote,
+        Commit,
+        CommitSig,
+        Proposal,
+        SignedHeader,
+        LightBlock,
+        BlockMeta,
+        TxProof,
+        EventDataRoundState,
+        ConsensusParams,
+        BlockParams,
+        EvidenceParams,
+        ValidatorParams,
+        VersionParams,
+        HashedParams,
+        Evidence,
+        DuplicateVoteEvidence,
+        LightClientAttackEvidence,
+        EvidenceList,
+        Block,
+        CanonicalBlockId,
+        CanonicalPartSetHeader,
+        CanonicalProposal,
+        CanonicalVote,
+    ]
+);
+impl_type_url!(
+    "tendermint.version",
+    cosmos_sdk_proto::tendermint::version,
+    [App, Consensus,]
+);
+#[cfg(feature = "ethermint")]
+impl_type_url!(
+    "ethermint.crypto.v1.ethsecp256k1",
+    cosmos_sdk_proto::ethermint::crypto::v1::ethsecp256k1,
+    [PubKey, PrivKey,]
+);
+#[cfg(feature = "ethermint")]
+impl_type_url!(
+    "ethermint.evm.v1",
+    cosmos_sdk_proto::ethermint::evm::v1,
+    [
+        Params,
+        ChainConfig,
+        State,
+        TransactionLogs,
+        Log,
+        TxResult,
+        AccessTuple,
+        TraceConfig,
+        GenesisState,
+        GenesisAccount,
+        MsgEthereumTx,
+        LegacyTx,
+        AccessListTx,
+        DynamicFeeTx,
+        ExtensionOptionsEthereumTx,
+        MsgEthereumTxResponse,
+        QueryAccountRequest,
+        QueryAccountResponse,
+        QueryCosmosAccountRequest,
+        QueryCosmosAccountResponse,
+        QueryValidatorAccountRequest,
+        QueryValidatorAccountResponse,
+        QueryBalanceRequest,
+        QueryBalanceResponse,
+        QueryStorageRequest,
+        QueryStorageResponse,
+        QueryCodeRequest,
+        QueryCodeResponse,
+        QueryTxLogsRequest,
+        QueryTxLogsResponse,
+        QueryParamsRequest,
+        QueryParamsResponse,
+        EthCallRequest,
+        EstimateGasResponse,
+        QueryTraceTxRequest,
+        QueryTraceTxResponse,
+        QueryTraceBlockRequest,
+        QueryTraceBlockResponse,
+    ]
+);
+#[cfg(feature = "ethermint")]
+impl_type_url!(
+    "ethermint.feemarket.v1",
+    cosmos_sdk_proto::ethermint::feemarket::v1,
+    [
+        Params,
+        GenesisState,
+        QueryParamsRequest,
+        QueryParamsResponse,
+        QueryBaseFeeRequest,
+        QueryBaseFeeResponse,
+        QueryBlockGasRequest,
+        QueryBlockGasResponse,
+    ]
+);
+#[cfg(feature = "ethermint")]
+impl_type_url!(
+    "ethermint.types.v1",
+    cosmos_sdk_proto::ethermint::types::v1,
+    [EthAccount, ExtensionOptionsWeb3Tx,]
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Msg;
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::{MsgMultiSend, MsgSend};
+    use cosmos_sdk_proto::ibc::core::client::v1::MsgUpdateClient;
+
+    #[test]
+    fn test_type_urls() {
+        assert_eq!(MsgSend::TYPE_URL, "/cosmos.bank.v1beta1.MsgSend");
+        assert_eq!(
+            MsgUpdateClient::TYPE_URL,
+            "/ibc.core.client.v1.MsgUpdateClient"
+        );
+
+        let send = MsgSend {
+            from_address: "cosmos1from".to_string(),
+            to_address: "cosmos1to".to_string(),
+            amount: Vec::new(),
+        };
+        let msg = Msg::from_proto(send.clone());
+        assert_eq!(msg.type_url(), MsgSend::TYPE_URL);
+        assert_eq!(msg.decode_as::<MsgSend>().unwrap(), send);
+        assert!(msg.decode_as::<MsgMultiSend>().is_err());
+    }
+}
@@ -1,12 +1,17 @@
-use crate::error::{ArrayStringError, ByteDecodeError, CosmosGrpcError, SdkErrorCode};
+use crate::error::{
+    ArrayStringError, BroadcastError, ByteDecodeError, CosmosGrpcError, SdkErrorCode,
+};
 use crate::Coin;
 use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
 use prost_types::Any;
+use serde::de::{Error as DeError, MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::str;
 use std::time::Duration;
-use std::{str, usize};
 
 /// A function that takes a hexadecimal representation of bytes
 /// back into a stream of bytes.
@@ -33,44 +38,169 @@ pub fn bytes_to_hex_str(bytes: &[u8]) -> String {
         .fold(String::new(), |acc, x| acc + &x)
 }
 
-#[derive(PartialEq, Eq, Copy, Clone, Hash, Deserialize, Serialize)]
+/// A fixed capacity string that is Copy, used for bech32 prefixes so that Address and
+/// PublicKey can be Copy. Holds up to 83 bytes, the longest human readable part bech32
+/// allows. Serializes as a plain string in human readable formats, binary formats
+/// keep the layout of earlier versions
+#[derive(PartialEq, Eq, Copy, Clone, Hash)]
 pub struct ArrayString {
-    chars: [Option<char>; ArrayString::MAX_LEN],
-    used: usize,
+    bytes: [u8; ArrayString::MAX_LEN],
+    len: u8,
 }
 
 impl ArrayString {
-    const MAX_LEN: usize = 32;
+    pub const MAX_LEN: usize = 83;
 
     pub fn new(input: &str) -> Result<Self, ArrayStringError> {
         if input.len() > ArrayString::MAX_LEN {
-            Err(ArrayStringError::TooLong)
-        } else {
-            let mut ret: [Option<char>; ArrayString::MAX_LEN] = [None; ArrayString::MAX_LEN];
-            let mut counter = 0;
-            for char in input.chars() {
-                ret[counter] = Some(char);
-                counter += 1;
-            }
-            Ok(ArrayString {
-                chars: ret,
-                used: counter,
-            })
+            return Err(ArrayStringError::TooLong);
         }
+        let mut bytes = [0u8; ArrayString::MAX_LEN];
+        bytes[..input.len()].copy_from_slice(input.as_bytes());
+        Ok(ArrayString {
+            bytes,
+            len: input.len() as u8,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only ever filled from a &str
+        str::from_utf8(&self.bytes[..self.len as usize]).unwrap()
     }
 }
 
 impl Display for ArrayString {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        let mut str = String::new();
-        for c in self.chars.iter() {
-            if let Some(v) = c {
-                str.push(*v)
-            } else {
-                break;
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::fmt::Debug for ArrayString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl Serialize for ArrayString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return serializer.serialize_str(self.as_str());
+        }
+        // earlier versions had room for 32 chars, any past that follow as a string
+        let mut chars = [None; LEGACY_LEN];
+        let mut rest = String::new();
+        let mut used = 0usize;
+        for c in self.as_str().chars() {
+            match chars.get_mut(used) {
+                Some(slot) => *slot = Some(c),
+                None => rest.push(c),
             }
+            used += 1;
+        }
+        let fields = if rest.is_empty() { 2 } else { 3 };
+        let mut state = serializer.serialize_struct("ArrayString", fields)?;
+        state.serialize_field("chars", &chars)?;
+        state.serialize_field("used", &used)?;
+        if !rest.is_empty() {
+            state.serialize_field("rest", &rest)?;
+        }
+        state.end()
+    }
+}
+
+/// Earlier versions serialized the prefix as an array of 32 optional chars, both that
+/// and a plain string are accepted in human readable formats
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ArrayStringRepr {
+    Str(String),
+    Legacy {
+        chars: Vec<Option<char>>,
+        used: usize,
+    },
+}
+
+/// The number of chars earlier versions had room for
+const LEGACY_LEN: usize = 32;
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum LegacyField {
+    Chars,
+    Used,
+    Rest,
+}
+
+/// Reads the binary layout, the fields of the earlier struct followed by the chars
+/// that did not fit in it
+struct LegacyVisitor;
+
+impl LegacyVisitor {
+    fn collect<E: DeError>(
+        chars: [Option<char>; LEGACY_LEN],
+        used: usize,
+        rest: String,
+    ) -> Result<ArrayString, E> {
+        let mut value: String = chars.iter().take(used).flatten().collect();
+        value.push_str(&rest);
+        ArrayString::new(&value).map_err(E::custom)
+    }
+}
+
+impl<'de> Visitor<'de> for LegacyVisitor {
+    type Value = ArrayString;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "an ArrayString")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ArrayString, A::Error> {
+        let chars = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let used: usize = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        let rest = if used > LEGACY_LEN {
+            seq.next_element()?
+                .ok_or_else(|| A::Error::invalid_length(2, &self))?
+        } else {
+            String::new()
+        };
+        LegacyVisitor::collect(chars, used, rest)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ArrayString, A::Error> {
+        let (mut chars, mut used, mut rest) = (None, None, String::new());
+        while let Some(field) = map.next_key()? {
+            match field {
+                LegacyField::Chars => chars = Some(map.next_value()?),
+                LegacyField::Used => used = Some(map.next_value()?),
+                LegacyField::Rest => rest = map.next_value()?,
+            }
+        }
+        let chars = chars.ok_or_else(|| A::Error::missing_field("chars"))?;
+        let used = used.ok_or_else(|| A::Error::missing_field("used"))?;
+        LegacyVisitor::collect(chars, used, rest)
+    }
+}
+
+impl<'de> Deserialize<'de> for ArrayString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return deserializer.deserialize_struct(
+                "ArrayString",
+                &["chars", "used", "rest"],
+                LegacyVisitor,
+            );
         }
-        write!(f, "{}", str)
+        let value = match ArrayStringRepr::deserialize(deserializer)? {
+            ArrayStringRepr::Str(s) => s,
+            ArrayStringRepr::Legacy { chars, used } => {
+                chars.into_iter().take(used).flatten().collect()
+            }
+        };
+        ArrayString::new(&value).map_err(D::Error::custom)
     }
 }
 
@@ -134,24 +264,21 @@ pub fn determine_min_fees_and_gas(input: &TxResponse) -> Option<FeeInfo> {
     }
 }
 
-/// Checks a tx response code for known issues returns true if tx is good, false if the tx
-/// has some known error
+/// Checks a tx response code for known issues, any nonzero code is an error whatever
+/// its codespace. Errors in the sdk codespace are classified, module errors such as
+/// those of wasm or ibc are returned as `BroadcastError::Other`
 pub fn check_for_sdk_error(input: &TxResponse) -> Result<(), CosmosGrpcError> {
     // check for gas errors
     if let Some(v) = determine_min_fees_and_gas(input) {
         return Err(CosmosGrpcError::InsufficientFees { fee_info: v });
     }
 
-    // check for known errors in the sdk codespace, if the error is module
-    // specific we will not detect it and the error will go un-noticed
-    if input.codespace == "sdk" {
-        if let Some(e) = SdkErrorCode::from_code(input.code) {
-            return Err(CosmosGrpcError::TransactionFailed {
-                tx: input.clone(),
-                time: Duration::from_secs(0),
-                sdk_error: Some(e),
-            });
-        }
+    if let Some(reason) = BroadcastError::from_tx_response(input) {
+        return Err(CosmosGrpcError::TransactionFailed {
+            tx: input.clone(),
+            time: Duration::from_secs(0),
+            reason,
+        });
     }
 
     Ok(())
@@ -167,12 +294,87 @@ pub fn encode_any(input: impl prost::Message, type_url: impl Into<String>) -> An
     }
 }
 
+/// Parses a Cosmos `sdk.Dec` as returned over gRPC. Dec values in query responses are
+/// frequently serialized as their raw 18 decimal place fixed point integer (no decimal point)
+/// rather than a human readable decimal, this handles both forms.
+pub fn parse_sdk_dec(input: &str) -> Result<f64, CosmosGrpcError> {
+    let bad = || CosmosGrpcError::BadResponse(format!("Failed to parse sdk.Dec {}", input));
+    if input.contains('.') {
+        input.parse().map_err(|_| bad())
+    } else {
+        let scaled: f64 = input.parse().map_err(|_| bad())?;
+        Ok(scaled / 1e18)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use crate::u256;
 
+    #[test]
+    fn test_array_string() {
+        let long = "a".repeat(ArrayString::MAX_LEN);
+        assert_eq!(ArrayString::new(&long).unwrap().as_str(), long);
+        assert!(ArrayString::new(&format!("{}a", long)).is_err());
+
+        let prefix = ArrayString::new("cosmos").unwrap();
+        let json = serde_json::to_string(&prefix).unwrap();
+        assert_eq!(json, "\"cosmos\"");
+        assert_eq!(serde_json::from_str::<ArrayString>(&json).unwrap(), prefix);
+
+        // the form written by earlier versions
+        let mut chars: Vec<Option<char>> = "cosmos".chars().map(Some).collect();
+        chars.resize(32, None);
+        let legacy = serde_json::json!({ "chars": chars, "used": 6 });
+        assert_eq!(
+            serde_json::from_value::<ArrayString>(legacy).unwrap(),
+            prefix
+        );
+    }
+
+    /// ArrayString as it was defined before it held 83 bytes
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct LegacyArrayString {
+        chars: [Option<char>; 32],
+        used: usize,
+    }
+
+    #[test]
+    fn test_array_string_legacy_binary() {
+        let mut chars = [None; 32];
+        for (slot, c) in chars.iter_mut().zip("cosmos".chars()) {
+            *slot = Some(c);
+        }
+        let legacy = LegacyArrayString { chars, used: 6 };
+        let prefix = ArrayString::new("cosmos").unwrap();
+
+        let legacy_bytes = bincode::serialize(&legacy).unwrap();
+        assert_eq!(
+            bincode::deserialize::<ArrayString>(&legacy_bytes).unwrap(),
+            prefix
+        );
+        // and the other way, so earlier versions can still read what is written now
+        let bytes = bincode::serialize(&prefix).unwrap();
+        assert_eq!(bytes, legacy_bytes);
+        assert_eq!(
+            bincode::deserialize::<LegacyArrayString>(&bytes).unwrap(),
+            legacy
+        );
+
+        // prefixes too long for the earlier layout carry the remainder after it
+        let long = ArrayString::new(&"b".repeat(ArrayString::MAX_LEN)).unwrap();
+        let bytes = bincode::serialize(&long).unwrap();
+        assert_eq!(bincode::deserialize::<ArrayString>(&bytes).unwrap(), long);
+        let pair = (long, 7u8);
+        let bytes = bincode::serialize(&pair).unwrap();
+        assert_eq!(
+            bincode::deserialize::<(ArrayString, u8)>(&bytes).unwrap(),
+            pair
+        );
+    }
+
     #[test]
     fn test_determine_fees() {
         let below_min_fees_tx_response = TxResponse {
@@ -207,4 +409,44 @@ mod tests {
             correct_output
         );
     }
+
+    #[test]
+    fn test_check_for_sdk_error() {
+        let response = |code: u32, codespace: &str| TxResponse {
+            code,
+            codespace: codespace.to_string(),
+            gas_wanted: 200_000,
+            gas_used: 100_000,
+            ..Default::default()
+        };
+        assert!(check_for_sdk_error(&response(0, "")).is_ok());
+        // module errors fail the tx just like sdk ones
+        for codespace in ["wasm", "staking", "ibc"] {
+            match check_for_sdk_error(&response(5, codespace)) {
+                Err(CosmosGrpcError::TransactionFailed { reason, .. }) => assert_eq!(
+                    reason,
+                    BroadcastError::Other {
+                        codespace: codespace.to_string(),
+                        code: 5
+                    }
+                ),
+                other => panic!("Expected a failed tx, got {:?}", other),
+            }
+        }
+        assert!(matches!(
+            check_for_sdk_error(&response(5, "sdk")),
+            Err(CosmosGrpcError::TransactionFailed {
+                reason: BroadcastError::InsufficientFunds,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_sdk_dec() {
+        assert_eq!(parse_sdk_dec("0.020000000000000000").unwrap(), 0.02);
+        assert_eq!(parse_sdk_dec("20000000000000000").unwrap(), 0.02);
+        assert_eq!(parse_sdk_dec("0").unwrap(), 0.0);
+        assert!(parse_sdk_dec("abc").is_err());
+    }
 }
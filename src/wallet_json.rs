@@ -0,0 +1,415 @@
+//! The encrypted key store JSON of Keplr style browser wallets, so that services can
+//! accept a mnemonic or private key exported from a wallet and hand keys back in a form
+//! the wallet can import. Enabled with the `wallet-json` feature
+//!
+//! The secret, a mnemonic phrase or a hex private key, is encrypted with AES in CTR mode
+//! under a key derived from the password with scrypt or pbkdf2. The AES key size follows
+//! the derived key length. The mac is the sha256 of the second half of the derived key
+//! followed by the ciphertext, it is checked before decrypting. The kdf cost parameters
+//! of an imported key store are capped, see `MAX_SCRYPT_N` and `MAX_PBKDF2_ROUNDS`
+
+use crate::error::WalletJsonError;
+use crate::mnemonic::SecretMnemonic;
+use crate::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use crate::PrivateKey;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use rand::RngCore;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, Zeroizing};
+
+/// The key store version written by this module
+pub const KEY_STORE_VERSION: &str = "1.2";
+/// The scrypt cost used when encrypting, as used by the wallets
+pub const DEFAULT_SCRYPT_N: u64 = 131072;
+/// The largest scrypt cost accepted when decrypting, scrypt uses 128 * n * r bytes
+pub const MAX_SCRYPT_N: u64 = 1 << 18;
+/// The largest scrypt block size accepted when decrypting
+pub const MAX_SCRYPT_R: u32 = 8;
+/// The largest scrypt parallelism accepted when decrypting
+pub const MAX_SCRYPT_P: u32 = 16;
+/// The largest number of pbkdf2 rounds accepted when decrypting
+pub const MAX_PBKDF2_ROUNDS: u32 = 1_000_000;
+
+/// What a key store holds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyStoreType {
+    Mnemonic,
+    PrivateKey,
+    /// A hardware wallet, which holds no secret
+    Ledger,
+}
+
+/// The HD path of a mnemonic key store, the key is at
+/// m/44'/coin_type'/account'/change/address_index
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Bip44HdPath {
+    pub account: u32,
+    pub change: u32,
+    pub address_index: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeyStoreCrypto {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    pub kdf: String,
+    /// salt and dklen, along with n, r and p for scrypt or c for pbkdf2
+    pub kdfparams: Value,
+    pub mac: String,
+}
+
+/// An encrypted wallet key store
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletKeyStore {
+    pub version: String,
+    #[serde(rename = "type")]
+    pub key_type: KeyStoreType,
+    /// The coin type used for each chain id, chains not listed use 118
+    #[serde(default)]
+    pub coin_type_for_chain: BTreeMap<String, u32>,
+    #[serde(
+        rename = "bip44HDPath",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub bip44_hd_path: Option<Bip44HdPath>,
+    /// Wallet metadata such as the account `name`
+    #[serde(default)]
+    pub meta: BTreeMap<String, String>,
+    pub crypto: KeyStoreCrypto,
+}
+
+/// The decrypted contents of a key store
+#[derive(Debug, Clone)]
+pub enum WalletSecret {
+    Mnemonic(SecretMnemonic),
+    PrivateKey(PrivateKey),
+}
+
+/// Reads a numeric kdf parameter, the key store is untrusted so every cost parameter
+/// is bounded by `max`
+fn kdf_param(params: &Value, name: &str, max: u64) -> Result<u64, WalletJsonError> {
+    let value = params[name]
+        .as_u64()
+        .ok_or_else(|| WalletJsonError::InvalidParams(format!("missing {}", name)))?;
+    if value > max {
+        return Err(WalletJsonError::InvalidParams(format!(
+            "{} {} is above the maximum of {}",
+            name, value, max
+        )));
+    }
+    Ok(value)
+}
+
+/// Reads a kdf parameter that must fit a u32
+fn kdf_param_u32(params: &Value, name: &str, max: u32) -> Result<u32, WalletJsonError> {
+    let value = kdf_param(params, name, max as u64)?;
+    u32::try_from(value).map_err(|_| WalletJsonError::InvalidParams(format!("{} {}", name, value)))
+}
+
+fn decode_hex(value: &str, name: &str) -> Result<Vec<u8>, WalletJsonError> {
+    hex_str_to_bytes(value).map_err(|_| WalletJsonError::InvalidParams(format!("invalid {}", name)))
+}
+
+/// Derives the encryption key from `password` with the kdf of a key store
+fn derive_key(
+    kdf: &str,
+    params: &Value,
+    password: &str,
+) -> Result<Zeroizing<Vec<u8>>, WalletJsonError> {
+    let salt = decode_hex(params["salt"].as_str().unwrap_or_default(), "salt")?;
+    let dklen = kdf_param(params, "dklen", 32)? as usize;
+    if dklen != 16 && dklen != 32 {
+        return Err(WalletJsonError::InvalidParams(format!("dklen {}", dklen)));
+    }
+    let mut key = Zeroizing::new(vec![0u8; dklen]);
+    match kdf {
+        "scrypt" => {
+            let n = kdf_param(params, "n", MAX_SCRYPT_N)?;
+            if !n.is_power_of_two() || n < 2 {
+                return Err(WalletJsonError::InvalidParams(format!("scrypt n {}", n)));
+            }
+            let params = scrypt::Params::new(
+                n.trailing_zeros() as u8,
+                kdf_param_u32(params, "r", MAX_SCRYPT_R)?,
+                kdf_param_u32(params, "p", MAX_SCRYPT_P)?,
+            )
+            .map_err(|e| WalletJsonError::InvalidParams(e.to_string()))?;
+            scrypt::scrypt(password.as_bytes(), &salt, &params, &mut key)
+                .map_err(|e| WalletJsonError::InvalidParams(e.to_string()))?;
+        }
+        "pbkdf2" => {
+            let rounds = kdf_param_u32(params, "c", MAX_PBKDF2_ROUNDS)?;
+            pbkdf2::pbkdf2::<hmac::Hmac<Sha256>>(password.as_bytes(), &salt, rounds, &mut key);
+        }
+        other => return Err(WalletJsonError::UnsupportedKdf(other.to_string())),
+    }
+    Ok(key)
+}
+
+/// Applies the AES-CTR keystream, which both encrypts and decrypts
+fn apply_aes_ctr(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<(), WalletJsonError> {
+    let invalid = |_| WalletJsonError::InvalidParams("invalid iv".to_string());
+    match key.len() {
+        16 => ctr::Ctr128BE::<aes::Aes128>::new_from_slices(key, iv)
+            .map_err(invalid)?
+            .apply_keystream(data),
+        _ => ctr::Ctr128BE::<aes::Aes256>::new_from_slices(key, iv)
+            .map_err(invalid)?
+            .apply_keystream(data),
+    }
+    Ok(())
+}
+
+fn mac(key: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(&key[key.len() / 2..]);
+    hasher.update(ciphertext);
+    hasher.finalize().into()
+}
+
+impl WalletKeyStore {
+    /// Encrypts a mnemonic for import into a wallet, `name` is the account name shown
+    pub fn from_mnemonic(mnemonic: &SecretMnemonic, password: &str, name: &str) -> Self {
+        let mut store = WalletKeyStore::encrypt(
+            KeyStoreType::Mnemonic,
+            mnemonic.expose_phrase().as_bytes(),
+            password,
+            name,
+            DEFAULT_SCRYPT_N,
+        );
+        store.bip44_hd_path = Some(Bip44HdPath::default());
+        store
+    }
+
+    /// Encrypts a private key for import into a wallet, `name` is the account name shown
+    pub fn from_private_key(key: &PrivateKey, password: &str, name: &str) -> Self {
+        let hex = Zeroizing::new(bytes_to_hex_str(&key.0));
+        WalletKeyStore::encrypt(
+            KeyStoreType::PrivateKey,
+            hex.as_bytes(),
+            password,
+            name,
+            DEFAULT_SCRYPT_N,
+        )
+    }
+
+    fn encrypt(
+        key_type: KeyStoreType,
+        secret: &[u8],
+        password: &str,
+        name: &str,
+        scrypt_n: u64,
+    ) -> Self {
+        let mut rng = rand::thread_rng();
+        let mut salt = [0u8; 32];
+        rng.fill_bytes(&mut salt);
+        let mut iv = [0u8; 16];
+        rng.fill_bytes(&mut iv);
+        let kdfparams = serde_json::json!({
+            "salt": bytes_to_hex_str(&salt),
+            "dklen": 32,
+            "n": scrypt_n,
+            "r": 8,
+            "p": 1,
+        });
+        // the parameters above are always valid
+        let key = derive_key("scrypt", &kdfparams, password).unwrap();
+        let mut ciphertext = secret.to_vec();
+        apply_aes_ctr(&key, &iv, &mut ciphertext).unwrap();
+
+        let mut meta = BTreeMap::new();
+        meta.insert("name".to_string(), name.to_string());
+        WalletKeyStore {
+            version: KEY_STORE_VERSION.to_string(),
+            key_type,
+            coin_type_for_chain: BTreeMap::new(),
+            bip44_hd_path: None,
+            meta,
+            crypto: KeyStoreCrypto {
+                cipher: "aes-128-ctr".to_string(),
+                cipherparams: CipherParams {
+                    iv: bytes_to_hex_str(&iv),
+                },
+                mac: bytes_to_hex_str(&mac(&key, &ciphertext)),
+                ciphertext: bytes_to_hex_str(&ciphertext),
+                kdf: "scrypt".to_string(),
+                kdfparams,
+            },
+        }
+    }
+
+    /// Decrypts the key store, a wrong password is reported as InvalidMac
+    pub fn decrypt(&self, password: &str) -> Result<WalletSecret, WalletJsonError> {
+        if self.key_type == KeyStoreType::Ledger {
+            return Err(WalletJsonError::UnsupportedType("ledger".to_string()));
+        }
+        let crypto = &self.crypto;
+        if crypto.cipher != "aes-128-ctr" {
+            return Err(WalletJsonError::UnsupportedCipher(crypto.cipher.clone()));
+        }
+        let key = derive_key(&crypto.kdf, &crypto.kdfparams, password)?;
+        let mut plaintext = Zeroizing::new(decode_hex(&crypto.ciphertext, "ciphertext")?);
+        let expected = decode_hex(&crypto.mac, "mac")?;
+        if !bool::from(mac(&key, &plaintext)[..].ct_eq(&expected[..])) {
+            return Err(WalletJsonError::InvalidMac);
+        }
+        let iv = decode_hex(&crypto.cipherparams.iv, "iv")?;
+        apply_aes_ctr(&key, &iv, &mut plaintext)?;
+
+        let text = std::str::from_utf8(&plaintext).map_err(|_| WalletJsonError::InvalidSecret)?;
+        match self.key_type {
+            KeyStoreType::Mnemonic => SecretMnemonic::parse(text)
+                .map(WalletSecret::Mnemonic)
+                .map_err(|_| WalletJsonError::InvalidSecret),
+            _ => {
+                let mut bytes = Zeroizing::new(
+                    hex_str_to_bytes(text).map_err(|_| WalletJsonError::InvalidSecret)?,
+                );
+                if bytes.len() != 32 {
+                    return Err(WalletJsonError::InvalidSecret);
+                }
+                let mut key = PrivateKey([0; 32]);
+                key.0.copy_from_slice(&bytes);
+                bytes.zeroize();
+                Ok(WalletSecret::PrivateKey(key))
+            }
+        }
+    }
+
+    /// Decrypts the key store and returns the account key used on `chain_id`, for a
+    /// mnemonic the key at the stored HD path with the chain's coin type
+    pub fn to_private_key(
+        &self,
+        password: &str,
+        chain_id: &str,
+    ) -> Result<PrivateKey, WalletJsonError> {
+        match self.decrypt(password)? {
+            WalletSecret::PrivateKey(key) => Ok(key),
+            WalletSecret::Mnemonic(mnemonic) => {
+                let coin_type = self.coin_type_for_chain.get(chain_id).unwrap_or(&118);
+                let path = self.bip44_hd_path.unwrap_or_default();
+                let path = format!(
+                    "m/44'/{}'/{}'/{}/{}",
+                    coin_type, path.account, path.change, path.address_index
+                );
+                mnemonic
+                    .to_private_key_path(&path, "")
+                    .map_err(|e| WalletJsonError::InvalidParams(e.to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wallet_key_store() {
+        let phrase = "purse sure leg gap above pull rescue glass circle attract erupt can sail gasp shy clarify inflict anger sketch hobby scare mad reject where";
+        let mnemonic = SecretMnemonic::parse(phrase).unwrap();
+        // a low scrypt cost keeps the test fast
+        let mut store = WalletKeyStore::encrypt(
+            KeyStoreType::Mnemonic,
+            phrase.as_bytes(),
+            "password",
+            "relayer",
+            1024,
+        );
+        let json = serde_json::to_string(&store).unwrap();
+        assert!(json.contains(r#""type":"mnemonic""#));
+        assert!(json.contains(r#""kdf":"scrypt""#));
+        let decoded: WalletKeyStore = serde_json::from_str(&json).unwrap();
+        match decoded.decrypt("password").unwrap() {
+            WalletSecret::Mnemonic(m) => assert_eq!(m.expose_phrase(), phrase),
+            WalletSecret::PrivateKey(_) => panic!("expected a mnemonic"),
+        }
+        assert_eq!(
+            decoded.decrypt("wrong").unwrap_err(),
+            WalletJsonError::InvalidMac
+        );
+        store
+            .coin_type_for_chain
+            .insert("evmos_9001-2".to_string(), 60);
+        assert_eq!(
+            store.to_private_key("password", "evmos_9001-2").unwrap(),
+            mnemonic
+                .to_private_key_path("m/44'/60'/0'/0/0", "")
+                .unwrap()
+        );
+        assert_eq!(
+            store.to_private_key("password", "cosmoshub-4").unwrap(),
+            mnemonic.to_private_key("").unwrap()
+        );
+
+        let key = PrivateKey::from_secret_hkdf(b"wallet", b"", b"").unwrap();
+        let hex = bytes_to_hex_str(&key.0);
+        let store = WalletKeyStore::encrypt(
+            KeyStoreType::PrivateKey,
+            hex.as_bytes(),
+            "password",
+            "hot",
+            1024,
+        );
+        match store.decrypt("password").unwrap() {
+            WalletSecret::PrivateKey(decrypted) => assert_eq!(decrypted, key),
+            WalletSecret::Mnemonic(_) => panic!("expected a private key"),
+        }
+    }
+
+    /// A key store written by an implementation independent of this module, Node's
+    /// OpenSSL backed scrypt and AES, laid out the way Keplr exports: the aes-128-ctr
+    /// cipher name with a 32 byte derived key, so AES-256 in practice
+    const INDEPENDENT_KEY_STORE: &str = r#"{"version":"1.2","type":"mnemonic","coinTypeForChain":{},"bip44HDPath":{"account":0,"change":0,"addressIndex":0},"meta":{"name":"keplr"},"crypto":{"cipher":"aes-128-ctr","cipherparams":{"iv":"3b1f6e0d9c8a7b6f5e4d3c2b1a098f7e"},"ciphertext":"0db8e91b758d78282e01506e00263cc699de17cb4b3b867798bf84ac09029e8fc5b29b8cd96426b993b7c170e3f8506a43c0663ca0e5c4e2b07da108069a61227e4ca7763b8414db094e2a49a29270ec51fffc27c0b93e549dd40cbfb68f285bfbf5cbc6fca9962cc1cd98035999221cff76b2d69546cf22af7c967b35e6ddffe7842f9e71e4a9eabd0021","kdf":"scrypt","kdfparams":{"salt":"8f5a1c3e0b6d4f2a9e7c5b3a1d0f8e6c4b2a09f7e5d3c1b0a9f8e7d6c5b4a392","dklen":32,"n":4096,"r":8,"p":1},"mac":"f3d878e4ff24eb58f55b9d4511fdaea3a246b6c15f8809dd2c6b36e7dbd13bd4"}}"#;
+
+    #[test]
+    fn test_key_store_known_answer_and_limits() {
+        let store: WalletKeyStore = serde_json::from_str(INDEPENDENT_KEY_STORE).unwrap();
+        match store.decrypt("password").unwrap() {
+            WalletSecret::Mnemonic(m) => assert_eq!(
+                m.expose_phrase(),
+                "purse sure leg gap above pull rescue glass circle attract erupt can sail gasp shy clarify inflict anger sketch hobby scare mad reject where"
+            ),
+            WalletSecret::PrivateKey(_) => panic!("expected a mnemonic"),
+        }
+
+        // cost parameters are bounded and never truncated
+        let with_params = |kdf: &str, params: Value| {
+            let mut store = store.clone();
+            store.crypto.kdf = kdf.to_string();
+            store.crypto.kdfparams = params;
+            store.decrypt("password").unwrap_err()
+        };
+        let salt = "00";
+        for params in [
+            serde_json::json!({"salt": salt, "dklen": 32, "n": MAX_SCRYPT_N * 2, "r": 8, "p": 1}),
+            serde_json::json!({"salt": salt, "dklen": 32, "n": 1024, "r": 4294967304u64, "p": 1}),
+            serde_json::json!({"salt": salt, "dklen": 32, "n": 1024, "r": 8, "p": 4294967297u64}),
+        ] {
+            assert!(matches!(
+                with_params("scrypt", params),
+                WalletJsonError::InvalidParams(_)
+            ));
+        }
+        assert!(matches!(
+            with_params(
+                "pbkdf2",
+                serde_json::json!({"salt": salt, "dklen": 32, "c": 4294967297u64})
+            ),
+            WalletJsonError::InvalidParams(_)
+        ));
+    }
+}
@@ -0,0 +1,253 @@
+//! BIP32 extended public keys, these allow watch only wallets and indexers to
+//! enumerate the addresses of an account without access to any private key material.
+//! An xpub exported at the account level (m/44'/118'/0') can derive every address on
+//! m/44'/118'/0'/0/i
+
+use crate::error::{AddressError, HdWalletError, PrivateKeyError, PublicKeyError};
+use crate::mnemonic::SecretMnemonic;
+use crate::private_key::HdNode;
+use crate::{Address, PublicKey};
+use hmac::{Hmac, Mac};
+use ripemd::Ripemd160;
+use secp256k1::scalar::Scalar;
+use secp256k1::{PublicKey as PublicKeyEC, Secp256k1};
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt;
+use std::str::FromStr;
+
+/// Child indexes at or above this value are hardened
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// The version bytes of a mainnet xpub as defined by BIP32
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xB2, 0x1E];
+
+/// Length of a serialized extended key before base58 check encoding
+const XPUB_LENGTH: usize = 78;
+
+/// The first four bytes of the hash160 of a compressed public key, used by
+/// extended keys to identify their parent
+pub(crate) fn fingerprint(public_key: &[u8; 33]) -> [u8; 4] {
+    let hash = Ripemd160::digest(Sha256::digest(public_key));
+    let mut res = [0u8; 4];
+    res.copy_from_slice(&hash[0..4]);
+    res
+}
+
+/// A BIP32 extended public key, serialized in the standard base58 `xpub...` format
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Xpub {
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+    chain_code: [u8; 32],
+    public_key: [u8; 33],
+}
+
+impl Xpub {
+    /// Derives the extended public key at `path` from a mnemonic, typically the
+    /// account level path m/44'/118'/0'
+    pub fn from_phrase(
+        path: &str,
+        phrase: &str,
+        passphrase: &str,
+    ) -> Result<Xpub, PrivateKeyError> {
+        let key_import = SecretMnemonic::parse(phrase)?;
+        key_import.to_xpub(path, passphrase)
+    }
+
+    pub(crate) fn from_seed_and_path(path: &str, seed: &[u8]) -> Result<Xpub, PrivateKeyError> {
+        let node = HdNode::from_seed_and_path(path, seed)?;
+        Ok(Xpub {
+            depth: node.depth,
+            parent_fingerprint: node.parent_fingerprint,
+            child_number: node.child_number,
+            chain_code: node.chain_code,
+            public_key: node.public_key(),
+        })
+    }
+
+    /// Derives the non-hardened child at `index`, hardened indexes require the
+    /// private key and return an error
+    pub fn derive_child(&self, index: u32) -> Result<Xpub, HdWalletError> {
+        if index >= HARDENED_OFFSET {
+            return Err(HdWalletError::HardenedPublicDerivation(index));
+        }
+        type HmacSha512 = Hmac<Sha512>;
+        let mut hasher = HmacSha512::new_from_slice(&self.chain_code).unwrap();
+        hasher.update(&self.public_key);
+        hasher.update(&index.to_be_bytes());
+        let l_param = hasher.finalize().into_bytes();
+
+        let mut i_l = [0u8; 32];
+        i_l.copy_from_slice(&l_param[0..32]);
+        let tweak =
+            Scalar::from_be_bytes(i_l).map_err(|_| HdWalletError::InvalidChildKey(index))?;
+        let secp = Secp256k1::verification_only();
+        // our own public key was validated on construction
+        let parent = PublicKeyEC::from_slice(&self.public_key).unwrap();
+        let child = parent
+            .add_exp_tweak(&secp, &tweak)
+            .map_err(|_| HdWalletError::InvalidChildKey(index))?;
+
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&l_param[32..64]);
+        Ok(Xpub {
+            depth: self.depth.checked_add(1).ok_or_else(|| {
+                HdWalletError::InvalidPathSpec("maximum depth exceeded".to_string())
+            })?,
+            parent_fingerprint: fingerprint(&self.public_key),
+            child_number: index,
+            chain_code,
+            public_key: child.serialize(),
+        })
+    }
+
+    /// Derives a relative path of non-hardened indexes, for example `0/5`
+    pub fn derive_path(&self, path: &str) -> Result<Xpub, HdWalletError> {
+        let mut res = *self;
+        for val in path.trim_start_matches("m/").split('/') {
+            let index: u32 = val
+                .parse()
+                .map_err(|_| HdWalletError::InvalidPathSpec(path.to_string()))?;
+            res = res.derive_child(index)?;
+        }
+        Ok(res)
+    }
+
+    /// The address at 0/index below this key, for an account level xpub this is the
+    /// address of m/44'/coin_type'/account'/0/index
+    pub fn address_at(&self, prefix: &str, index: u32) -> Result<Address, PrivateKeyError> {
+        let child = self.derive_child(0)?.derive_child(index)?;
+        Ok(child.to_address(prefix)?)
+    }
+
+    /// The public key of this node, `prefix` is the bech32 prefix of the public key
+    /// for example cosmospub
+    pub fn to_public_key(&self, prefix: &str) -> Result<PublicKey, PublicKeyError> {
+        PublicKey::from_bytes(self.public_key, prefix)
+    }
+
+    /// The address of this node with the given bech32 prefix
+    pub fn to_address(&self, prefix: &str) -> Result<Address, AddressError> {
+        // the default prefix is always valid
+        let public_key = self.to_public_key(PublicKey::DEFAULT_PREFIX).unwrap();
+        public_key.to_address_with_prefix(prefix)
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn child_number(&self) -> u32 {
+        self.child_number
+    }
+
+    pub fn public_key_bytes(&self) -> [u8; 33] {
+        self.public_key
+    }
+}
+
+impl fmt::Display for Xpub {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = Vec::with_capacity(XPUB_LENGTH);
+        bytes.extend_from_slice(&XPUB_VERSION);
+        bytes.push(self.depth);
+        bytes.extend_from_slice(&self.parent_fingerprint);
+        bytes.extend_from_slice(&self.child_number.to_be_bytes());
+        bytes.extend_from_slice(&self.chain_code);
+        bytes.extend_from_slice(&self.public_key);
+        write!(f, "{}", bs58::encode(bytes).with_check().into_string())
+    }
+}
+
+impl fmt::Debug for Xpub {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Xpub({})", self)
+    }
+}
+
+impl FromStr for Xpub {
+    type Err = HdWalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(s)
+            .with_check(None)
+            .into_vec()
+            .map_err(|e| HdWalletError::InvalidXpub(e.to_string()))?;
+        if bytes.len() != XPUB_LENGTH {
+            return Err(HdWalletError::InvalidXpub(format!(
+                "expected {} bytes got {}",
+                XPUB_LENGTH,
+                bytes.len()
+            )));
+        }
+        if bytes[0..4] != XPUB_VERSION {
+            return Err(HdWalletError::InvalidXpub(
+                "not a mainnet extended public key".to_string(),
+            ));
+        }
+        let mut parent_fingerprint = [0u8; 4];
+        parent_fingerprint.copy_from_slice(&bytes[5..9]);
+        let mut child_number = [0u8; 4];
+        child_number.copy_from_slice(&bytes[9..13]);
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&bytes[13..45]);
+        let mut public_key = [0u8; 33];
+        public_key.copy_from_slice(&bytes[45..78]);
+        PublicKeyEC::from_slice(&public_key)
+            .map_err(|e| HdWalletError::InvalidXpub(e.to_string()))?;
+        Ok(Xpub {
+            depth: bytes[4],
+            parent_fingerprint,
+            child_number: u32::from_be_bytes(child_number),
+            chain_code,
+            public_key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_str_to_bytes;
+    use crate::PrivateKey;
+
+    #[test]
+    fn test_bip32_vector() {
+        // test vector 1 from BIP32
+        let seed = hex_str_to_bytes("000102030405060708090a0b0c0d0e0f").unwrap();
+        let m0h = Xpub::from_seed_and_path("m/0'", &seed).unwrap();
+        assert_eq!(m0h.to_string(), "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw");
+        let parsed: Xpub = m0h.to_string().parse().unwrap();
+        assert_eq!(parsed, m0h);
+
+        let m0h_1 = parsed.derive_child(1).unwrap();
+        assert_eq!(m0h_1.to_string(), "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ");
+        assert_eq!(m0h_1, Xpub::from_seed_and_path("m/0'/1", &seed).unwrap());
+        assert!(m0h.derive_child(HARDENED_OFFSET).is_err());
+    }
+
+    #[test]
+    fn test_watch_only_addresses() {
+        let phrase = "purse sure leg gap above pull rescue glass circle attract erupt can sail gasp shy clarify inflict anger sketch hobby scare mad reject where";
+        let xpub = Xpub::from_phrase("m/44'/118'/0'", phrase, "").unwrap();
+        for i in 0..3 {
+            let key =
+                PrivateKey::from_hd_wallet_path(&format!("m/44'/118'/0'/0/{}", i), phrase, "")
+                    .unwrap();
+            assert_eq!(
+                xpub.address_at("cosmos", i).unwrap(),
+                key.to_address("cosmos").unwrap()
+            );
+        }
+        assert_eq!(
+            xpub.derive_path("0/0")
+                .unwrap()
+                .to_address("cosmos")
+                .unwrap(),
+            "cosmos1t0sgxmpxafdfjd3k6kgg50kdgn4muh5t0phml6"
+                .parse()
+                .unwrap()
+        );
+    }
+}